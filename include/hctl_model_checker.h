@@ -0,0 +1,79 @@
+#ifndef HCTL_MODEL_CHECKER_H
+#define HCTL_MODEL_CHECKER_H
+
+/* This file is generated by cbindgen, do not modify it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Opaque handle to the symbolic state-transition graph of a BN model.
+typedef struct HctlGraph HctlGraph;
+
+// Opaque handle to a (sanitized) result of the model checking, a set of colored states.
+typedef struct HctlResult HctlResult;
+
+// Get the message of the error of the last fallible call (such as [hctl_graph_new] or
+// [hctl_check_formula]) in the current thread, or null if that call succeeded. The string is
+// owned by the library, and is valid until the next fallible call.
+const char *hctl_last_error(void);
+
+// Build the symbolic graph for a BN model in aeon format, supporting the given number of HCTL
+// variables (the maximal number of variables in the checked formulae).
+//
+// Returns null on error. The graph must be released by [hctl_graph_free].
+//
+// # Safety
+//
+// The `aeon_model` must be a valid null-terminated string.
+struct HctlGraph *hctl_graph_new(const char *aeon_model, uint16_t num_hctl_vars);
+
+// Release the graph created by [hctl_graph_new].
+//
+// # Safety
+//
+// The `graph` must be null or a pointer returned by [hctl_graph_new] that was not released yet.
+void hctl_graph_free(struct HctlGraph *graph);
+
+// Model-check the HCTL formula on the graph.
+//
+// Returns null on error (e.g., invalid formula, or the graph does not support enough HCTL
+// variables). The result must be released by [hctl_result_free].
+//
+// # Safety
+//
+// The `graph` must be a valid pointer returned by [hctl_graph_new], and the `formula` must be
+// a valid null-terminated string.
+struct HctlResult *hctl_check_formula(const struct HctlGraph *graph, const char *formula);
+
+// Release the result created by [hctl_check_formula].
+//
+// # Safety
+//
+// The `result` must be null or a pointer returned by [hctl_check_formula] that was not
+// released yet.
+void hctl_result_free(struct HctlResult *result);
+
+// Get the number of (state, color) pairs in the result, or a negative number for null result.
+//
+// # Safety
+//
+// The `result` must be null or a valid pointer returned by [hctl_check_formula].
+double hctl_result_cardinality(const struct HctlResult *result);
+
+// Get the number of colors in the result, or a negative number for null result.
+//
+// # Safety
+//
+// The `result` must be null or a valid pointer returned by [hctl_check_formula].
+double hctl_result_num_colors(const struct HctlResult *result);
+
+// Get the number of states in the result, or a negative number for null result.
+//
+// # Safety
+//
+// The `result` must be null or a valid pointer returned by [hctl_check_formula].
+double hctl_result_num_states(const struct HctlResult *result);
+
+#endif /* HCTL_MODEL_CHECKER_H */
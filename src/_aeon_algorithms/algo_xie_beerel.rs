@@ -1,19 +1,27 @@
 use crate::_aeon_algorithms::saturated_reachability::{reach_bwd, reachability_step};
+use crate::_aeon_algorithms::scc_computation::AttractorSearchProgress;
 
 use biodivine_lib_param_bn::biodivine_std::traits::Set;
-use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph,
+};
 use biodivine_lib_param_bn::VariableId;
 
 /// Uses a simplified Xie-Beerel algorithm adapted to coloured setting to find all bottom
 /// SCCs in the given `universe` set. It only tests transitions using `active_variables`.
 /// All resulting components are collected into the `components` set.
+///
+/// The progress is passed to `on_component` after each found component, and the search stops
+/// early once it returns `true`.
 pub fn xie_beerel_attractor_set(
     graph: &SymbolicAsyncGraph,
     universe: &GraphColoredVertices,
     active_variables: &[VariableId],
     mut components: GraphColoredVertices,
+    on_component: &mut dyn FnMut(&AttractorSearchProgress) -> bool,
 ) -> GraphColoredVertices {
     let mut universe = universe.clone();
+    let mut num_components = 0;
     while !universe.is_empty() {
         let pivots = universe.pick_vertex();
 
@@ -47,11 +55,73 @@ pub fn xie_beerel_attractor_set(
             }
         }
 
+        universe = universe.minus(&pivot_basin);
+
         if !pivot_component.is_empty() {
             components = components.union(&pivot_component);
+            num_components += 1;
+            let progress = AttractorSearchProgress {
+                num_components,
+                attractors: &components,
+                remaining: &universe,
+            };
+            if on_component(&progress) {
+                break;
+            }
         }
-
-        universe = universe.minus(&pivot_basin);
     }
     components
 }
+
+/// Uses the Xie-Beerel algorithm guided by the `target` set to find the colors for which some
+/// bottom SCC in the given `universe` intersects `target`. It only tests transitions using
+/// `active_variables`.
+///
+/// The pivots are always picked from the `target` states, and each color is discarded as soon
+/// as it is decided, i.e., once an attractor intersecting `target` is found, or once no
+/// `target` states remain for it. The other attractors are thus (mostly) never computed.
+pub fn xie_beerel_target_colors(
+    graph: &SymbolicAsyncGraph,
+    universe: &GraphColoredVertices,
+    active_variables: &[VariableId],
+    target: &GraphColoredVertices,
+) -> GraphColors {
+    let mut universe = universe.clone();
+    let mut found_colors = graph.mk_empty_colors();
+    loop {
+        // the colors without target states in the universe can not have any target attractor
+        let candidates = universe.intersect(target);
+        if candidates.is_empty() {
+            break;
+        }
+        universe = universe.intersect_colors(&candidates.colors());
+
+        let pivots = candidates.pick_vertex();
+        let pivot_basin = reach_bwd(graph, &pivots, &universe, active_variables);
+
+        // As in `xie_beerel_attractor_set`, only the colors for which the component of the pivot
+        // is an attractor remain in `pivot_component`.
+        let mut pivot_component = pivots.clone();
+        loop {
+            let done = reachability_step(
+                &mut pivot_component,
+                &universe,
+                active_variables,
+                |var, set| graph.var_post(var, set),
+            );
+            let escaped_basin = pivot_component.minus(&pivot_basin);
+            if !escaped_basin.is_empty() {
+                pivot_component = pivot_component.minus_colors(&escaped_basin.colors());
+            }
+            if done {
+                break;
+            }
+        }
+
+        // the pivot is a target state, so its attractor intersects the target
+        let pivot_colors = pivot_component.colors();
+        found_colors = found_colors.union(&pivot_colors);
+        universe = universe.minus(&pivot_basin).minus_colors(&pivot_colors);
+    }
+    found_colors
+}
@@ -1,5 +1,7 @@
+use crate::evaluation::reversed_graph::TransitionSystem;
+
 use biodivine_lib_param_bn::biodivine_std::traits::Set;
-use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices;
 use biodivine_lib_param_bn::VariableId;
 
 /// Performs one reachability step using the saturation scheme.
@@ -34,9 +36,8 @@ where
 
 /// Fully compute reachable states from `initial` inside `universe` using transitions under
 /// `variables`.
-#[allow(dead_code)]
-pub fn reach_fwd(
-    graph: &SymbolicAsyncGraph,
+pub fn reach_fwd<G: TransitionSystem>(
+    graph: &G,
     initial: &GraphColoredVertices,
     universe: &GraphColoredVertices,
     variables: &[VariableId],
@@ -52,8 +53,8 @@ pub fn reach_fwd(
 
 /// Fully compute back-reachable states from `initial` inside `universe` using transitions under
 /// `variables`.
-pub fn reach_bwd(
-    graph: &SymbolicAsyncGraph,
+pub fn reach_bwd<G: TransitionSystem>(
+    graph: &G,
     initial: &GraphColoredVertices,
     universe: &GraphColoredVertices,
     variables: &[VariableId],
@@ -1,7 +1,27 @@
-use crate::_aeon_algorithms::algo_xie_beerel::xie_beerel_attractor_set;
+use crate::_aeon_algorithms::algo_xie_beerel::{
+    xie_beerel_attractor_set, xie_beerel_target_colors,
+};
 use crate::_aeon_algorithms::itgr::interleaved_transition_guided_reduction;
+use crate::_aeon_algorithms::saturated_reachability::{reach_bwd, reach_fwd};
 
-use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph,
+};
+use biodivine_lib_param_bn::VariableId;
+
+/// Progress of the attractor search, reported after each attractor component found by the
+/// Xie-Beerel algorithm (see [compute_attractor_states_with_search]).
+#[derive(Clone, Copy, Debug)]
+pub struct AttractorSearchProgress<'a> {
+    /// Number of attractor components found so far. Note that one (colored) component covers
+    /// attractors of several colors.
+    pub num_components: usize,
+    /// States of the attractors found so far.
+    pub attractors: &'a GraphColoredVertices,
+    /// States of the universe that are still to be explored.
+    pub remaining: &'a GraphColoredVertices,
+}
 
 /// Computes the set of colored states contained in terminal SCCs
 /// Initial universe can be used to e.g. restrict considered colors
@@ -9,6 +29,22 @@ use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, Symboli
 pub fn compute_attractor_states(
     graph: &SymbolicAsyncGraph,
     initial_universe: GraphColoredVertices,
+) -> GraphColoredVertices {
+    compute_attractor_states_with_search(graph, initial_universe, &mut |_| false, &mut |_| {})
+}
+
+/// Computes the set of colored states contained in terminal SCCs of the `initial_universe`
+/// (which can be used to, e.g., restrict the considered colors), with control over the search.
+///
+/// After each attractor component is found, its [AttractorSearchProgress] is passed to the
+/// `on_progress` callback, and the search terminates early once `should_stop` returns `true`
+/// (e.g., see [stop_after_components] or [stop_when_intersecting]). The result then only
+/// contains the attractors found so far.
+pub fn compute_attractor_states_with_search(
+    graph: &SymbolicAsyncGraph,
+    initial_universe: GraphColoredVertices,
+    should_stop: &mut dyn FnMut(&AttractorSearchProgress) -> bool,
+    on_progress: &mut dyn FnMut(&AttractorSearchProgress),
 ) -> GraphColoredVertices {
     // First, perform ITGR reduction.
     let (universe, active_variables) =
@@ -20,9 +56,196 @@ pub fn compute_attractor_states(
         &universe,
         &active_variables,
         graph.mk_empty_colored_vertices(),
+        &mut |progress| {
+            on_progress(progress);
+            should_stop(progress)
+        },
     )
 }
 
+/// Early-termination predicate for [compute_attractor_states_with_search], stopping the search
+/// once `max_components` attractor components are found.
+pub fn stop_after_components(
+    max_components: usize,
+) -> impl FnMut(&AttractorSearchProgress) -> bool {
+    move |progress| progress.num_components >= max_components
+}
+
+/// Early-termination predicate for [compute_attractor_states_with_search], stopping the search
+/// once some attractor found so far intersects the `target` set.
+pub fn stop_when_intersecting(
+    target: &GraphColoredVertices,
+) -> impl FnMut(&AttractorSearchProgress) -> bool + '_ {
+    move |progress| !progress.attractors.intersect(target).is_empty()
+}
+
+/// Computes the colors for which some terminal SCC of the `initial_universe` intersects the
+/// `target` set, i.e., answers "does some attractor intersect the target?" for each color.
+///
+/// Unlike [compute_attractor_states], the search is guided by the `target` set and it stops for
+/// each color as soon as the answer is known, so the attractors not intersecting the `target`
+/// are (mostly) not computed at all. Good default universe is graph.mk_unit_colored_vertices().
+pub fn compute_target_attractor_colors(
+    graph: &SymbolicAsyncGraph,
+    initial_universe: GraphColoredVertices,
+    target: &GraphColoredVertices,
+) -> GraphColors {
+    // the colors without any target state can be discarded right away
+    let target = target.intersect(&initial_universe);
+    let initial_universe = initial_universe.intersect_colors(&target.colors());
+    let (universe, active_variables) =
+        interleaved_transition_guided_reduction(graph, initial_universe);
+    xie_beerel_target_colors(graph, &universe, &active_variables, &target)
+}
+
+/// Computes the set of colored states contained in fair SCCs, i.e., SCCs in which a run can stay
+/// forever under strong fairness (every variable enabled infinitely often also fires infinitely
+/// often). These include all terminal SCCs, but also SCCs that can only be left by a variable
+/// which also fires inside the SCC.
+///
+/// SCCs are enumerated from a pivot, and if some variable is enabled in an SCC but never fires
+/// inside it, the states where it is enabled are removed and the rest is decomposed again.
+pub fn compute_fair_attractor_states(
+    graph: &SymbolicAsyncGraph,
+    initial_universe: GraphColoredVertices,
+) -> GraphColoredVertices {
+    let variables: Vec<VariableId> = graph.variables().collect();
+    // steady states are always fair, as no variable is ever enabled there
+    let steady_states = initial_universe.minus(&graph.can_post(&initial_universe));
+    let mut result = steady_states.clone();
+
+    let mut universes = vec![initial_universe.minus(&steady_states)];
+    while let Some(universe) = universes.pop() {
+        let mut remaining = trim(graph, universe);
+        while !remaining.is_empty() {
+            let pivot = remaining.pick_vertex();
+            let forward = reach_fwd(graph, &pivot, &remaining, &variables);
+            let scc = reach_bwd(graph, &pivot, &forward, &variables);
+            remaining = trim(graph, remaining.minus(&scc));
+
+            // colors in which the SCC is not trivial, and the states enabling unfair variables
+            let mut cyclic_colors = graph.mk_empty_colors();
+            let mut unfair_states = graph.mk_empty_colored_vertices();
+            for var in &variables {
+                let fires_inside = graph.var_can_post_within(*var, &scc).colors();
+                let enabled = graph.var_can_post(*var, &scc);
+                cyclic_colors = cyclic_colors.union(&fires_inside);
+                unfair_states = unfair_states.union(&enabled.minus_colors(&fires_inside));
+            }
+            let scc = scc.intersect_colors(&cyclic_colors);
+            let unfair_colors = unfair_states.colors();
+            result = result.union(&scc.minus_colors(&unfair_colors));
+
+            // fair runs must eventually avoid the states enabling unfair variables
+            let reduced = scc.intersect_colors(&unfair_colors).minus(&unfair_states);
+            if !reduced.is_empty() {
+                universes.push(reduced);
+            }
+        }
+    }
+    result
+}
+
+/// Iteratively remove the states without a predecessor or a successor inside the set, as they
+/// can not lie on a cycle inside the set.
+fn trim(graph: &SymbolicAsyncGraph, set: GraphColoredVertices) -> GraphColoredVertices {
+    let mut set = set;
+    loop {
+        let trimmed = graph
+            .can_pre_within(&set)
+            .intersect(&graph.can_post_within(&set));
+        if trimmed == set {
+            return set;
+        }
+        set = trimmed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_aeon_algorithms::scc_computation::{
+        compute_attractor_states, compute_attractor_states_with_search,
+        compute_target_attractor_colors, stop_after_components, stop_when_intersecting,
+    };
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test the attractor search with early termination and progress reports.
+    fn test_attractor_search() {
+        // four fixed-point attractors
+        let bn = BooleanNetwork::try_from("a -> a\nb -> b").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let unit = graph.mk_unit_colored_vertices();
+        let all_attractors = compute_attractor_states(&graph, unit.clone());
+        assert_eq!(all_attractors.approx_cardinality(), 4.0);
+
+        let mut reported = Vec::new();
+        let result = compute_attractor_states_with_search(
+            &graph,
+            unit.clone(),
+            &mut |_| false,
+            &mut |progress| reported.push(progress.num_components),
+        );
+        assert_eq!(result, all_attractors);
+        assert_eq!(reported, vec![1, 2, 3, 4]);
+
+        let result = compute_attractor_states_with_search(
+            &graph,
+            unit.clone(),
+            &mut stop_after_components(2),
+            &mut |_| {},
+        );
+        assert_eq!(result.approx_cardinality(), 2.0);
+        assert!(result.is_subset(&all_attractors));
+
+        // the search stops once the target attractor is found
+        let a = bn.as_graph().find_variable("a").unwrap();
+        let b = bn.as_graph().find_variable("b").unwrap();
+        let target = graph
+            .fix_network_variable(a, true)
+            .intersect(&graph.fix_network_variable(b, false));
+        let result = compute_attractor_states_with_search(
+            &graph,
+            unit.clone(),
+            &mut stop_when_intersecting(&target),
+            &mut |_| {},
+        );
+        assert!(target.is_subset(&result));
+        assert!(result.is_subset(&all_attractors));
+
+        // the universe restricts the search
+        let result = compute_attractor_states(&graph, graph.fix_network_variable(a, true));
+        assert_eq!(result.approx_cardinality(), 2.0);
+    }
+
+    #[test]
+    /// Test the target-aware attractor search against the full attractor computation.
+    fn test_target_attractor_colors() {
+        // parametrized network with different attractors for different colors
+        let bn = BooleanNetwork::try_from("a -?? b\nb -?? a\nc -?? c\nb -?? c").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let unit = graph.mk_unit_colored_vertices();
+        let attractors = compute_attractor_states(&graph, unit.clone());
+        let a = bn.as_graph().find_variable("a").unwrap();
+        let c = bn.as_graph().find_variable("c").unwrap();
+
+        let targets = [
+            graph.fix_network_variable(a, true),
+            graph.fix_network_variable(c, false),
+            graph
+                .fix_network_variable(a, false)
+                .intersect(&graph.fix_network_variable(c, true)),
+            graph.mk_empty_colored_vertices(),
+        ];
+        for target in targets {
+            let colors = compute_target_attractor_colors(&graph, unit.clone(), &target);
+            assert_eq!(colors, attractors.intersect(&target).colors());
+        }
+    }
+}
+
 /*
 #[allow(dead_code)]
 /// Computes terminal SCCs and outputs the contained states to the given file
@@ -1,9 +1,11 @@
 use crate::_test_model_checking::{MODEL_CELL_CYCLE, MODEL_CELL_DIVISION, MODEL_YEAST};
+use crate::evaluation::encode_relation_variable;
 use crate::mc_utils::get_extended_symbolic_graph;
 use crate::model_checking::{
     model_check_extended_formula, model_check_extended_formula_dirty, model_check_formula,
     model_check_formula_dirty,
 };
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::BooleanNetwork;
 use std::collections::HashMap;
 
@@ -132,3 +134,50 @@ fn model_check_extended_tautologies() {
     model_check_extended_tautologies_on_bn(bn2);
     model_check_extended_tautologies_on_bn(bn3);
 }
+
+#[test]
+/// Test evaluation of extended formulae with wild-card relations between HCTL variables, which
+/// are given by raw sets over the first HCTL variables. Compare them with equivalent formulae
+/// that use only wild-card propositions (including cases where the variables are swapped).
+fn model_check_wild_card_relations() {
+    let bn = BooleanNetwork::try_from(MODEL_CELL_DIVISION).unwrap();
+    let stg = get_extended_symbolic_graph(&bn, 3).unwrap();
+
+    // relation `r` holds for the first state in `a` and the second state in `b`
+    let raw_a = model_check_formula_dirty("PleC & ~DivK", &stg).unwrap();
+    let raw_b = model_check_formula_dirty("DivJ | CtrAb", &stg).unwrap();
+    let relation = encode_relation_variable(&stg, &raw_a, 0)
+        .intersect(&encode_relation_variable(&stg, &raw_b, 1));
+    let context_sets = HashMap::from([
+        ("a".to_string(), raw_a),
+        ("b".to_string(), raw_b),
+        ("r".to_string(), relation),
+    ]);
+
+    let formulae_pairs = [
+        (
+            "!{x}: 3{y}: %r({x}, {y})% & EF {y}",
+            "!{x}: 3{y}: (@{x}: %a%) & (@{y}: %b%) & EF {y}",
+        ),
+        (
+            "!{x}: 3{y}: %r({y}, {x})% & EF {y}",
+            "!{x}: 3{y}: (@{y}: %a%) & (@{x}: %b%) & EF {y}",
+        ),
+        ("!{x}: %r({x}, {x})%", "!{x}: %a% & %b%"),
+        (
+            "!{x}: 3{y}: 3{z}: %r({z}, {x})% & (@{z}: EF {y})",
+            "!{x}: 3{y}: 3{z}: (@{z}: %a%) & (@{x}: %b%) & (@{z}: EF {y})",
+        ),
+    ];
+    for (f_with_relation, f) in formulae_pairs {
+        let result = model_check_extended_formula(f_with_relation, &stg, &context_sets).unwrap();
+        let result_v2 = model_check_extended_formula(f, &stg, &context_sets).unwrap();
+        assert!(result.as_bdd().iff(result_v2.as_bdd()).is_true());
+    }
+
+    // relations must be used with the same number of variables, and these must be quantified
+    let invalid_formulae = ["!{x}: %r({x})% & %r({x}, {x})%", "!{x}: %r({x}, {y})%"];
+    for f in invalid_formulae {
+        assert!(model_check_extended_formula(f, &stg, &context_sets).is_err());
+    }
+}
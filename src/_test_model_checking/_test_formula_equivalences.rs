@@ -59,6 +59,17 @@ fn evaluate_equivalent_formulae(bn: BooleanNetwork) {
         ("!{x}: AX {x}", "V{x}: ({x} => (AX {x}))"),
         // binder and forall equivalence v3
         ("!{x}: AF {x}", "V{x}: ({x} => (AF {x}))"),
+        // quantification over attractors
+        ("3{x} in attractors: {x}", "!{x}: AG EF {x}"),
+        (
+            "!{x, y}: 3{z}: (@{z}: AX {x}) & EF {y}",
+            "!{x}: !{y}: 3{z}: (@{z}: AX {x}) & EF {y}",
+        ),
+        // past reachability expressed via hybrid operators
+        (
+            "EP ~(!{z}: AG EF {z})",
+            "!{x}: 3{y}: @{y}: (~(!{z}: AG EF {z}) & EF {x})",
+        ),
     ];
 
     // check that the results for the two formulae are equivalent in both sanitized and
@@ -87,3 +98,54 @@ fn model_check_equivalent_formulae() {
         evaluate_equivalent_formulae(bn);
     }
 }
+
+#[test]
+/// Test evaluation of transition-labelled next operators (`EX_{v}`, `AX_{v}`) by comparing them
+/// with equivalent formulae, on model FISSION-YEAST-2008.
+fn model_check_transition_labelled_formulae() {
+    let bn = BooleanNetwork::try_from(MODEL_CELL_DIVISION).unwrap();
+    let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+    let var_names: Vec<String> = bn
+        .variables()
+        .map(|v| bn.get_variable_name(v).clone())
+        .collect();
+
+    let mut equivalent_formulae_pairs = Vec::new();
+    for name in &var_names {
+        // updating a variable always flips its value
+        equivalent_formulae_pairs.push((
+            format!("EX_{{{name}}} {name}"),
+            format!("~{name} & EX_{{{name}}} true"),
+        ));
+        // AX equivalence
+        equivalent_formulae_pairs.push((
+            format!("AX_{{{name}}} (DivK | EF CtrAb)"),
+            format!("~EX_{{{name}}} ~(DivK | EF CtrAb)"),
+        ));
+    }
+    // EX is the union of the transition-labelled variants, with self-loops on steady states
+    let ex_union: Vec<String> = var_names
+        .iter()
+        .map(|name| format!("EX_{{{name}}} (DivK & ~PleC)"))
+        .collect();
+    equivalent_formulae_pairs.push((
+        "EX (DivK & ~PleC)".to_string(),
+        format!(
+            "{} | (DivK & ~PleC & (!{{x}}: AX {{x}}))",
+            ex_union.join(" | ")
+        ),
+    ));
+
+    for (formula1, formula2) in equivalent_formulae_pairs {
+        let result1 = model_check_formula(formula1.as_str(), &stg).unwrap();
+        let result2 = model_check_formula(formula2.as_str(), &stg).unwrap();
+        assert!(result1.as_bdd().iff(result2.as_bdd()).is_true());
+
+        let result1 = model_check_formula_dirty(formula1.as_str(), &stg).unwrap();
+        let result2 = model_check_formula_dirty(formula2.as_str(), &stg).unwrap();
+        assert!(result1.as_bdd().iff(result2.as_bdd()).is_true());
+    }
+
+    // the variable must be a valid network variable
+    assert!(model_check_formula("EX_{UnknownVar} DivK", &stg).is_err());
+}
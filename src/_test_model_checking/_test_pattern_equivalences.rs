@@ -26,6 +26,15 @@ fn evaluate_equivalent_formulae_in_context(stg: &SymbolicAsyncGraph, context_set
         ("~(3{x}: @{x}: %p1%)", "V{x}: @{x}: ~%p1%"),
         // binder and forall equivalence v1
         ("!{x}: %p1%", "V{x}: ({x} => %p1%)"),
+        // attractors intersecting a target (optimized) vs the general evaluation
+        (
+            "3{x}: @{x}: (AG EF {x}) & %p1%",
+            "3{x}: (@{x}: AG EF {x}) & (@{x}: %p1%)",
+        ),
+        (
+            "3{x}: @{x}: %p2% & (AG EF {x})",
+            "3{x}: (@{x}: %p2%) & (@{x}: AG EF {x})",
+        ),
     ];
 
     for (f1, f2) in equivalent_pattern_pairs {
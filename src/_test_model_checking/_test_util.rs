@@ -1,5 +1,21 @@
 use crate::preprocessing::hctl_tree::HctlTreeNode;
 use biodivine_lib_param_bn::BooleanNetwork;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter making the temporary paths unique within the test process.
+static TEMP_PATH_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Generate a path `hctl-test-{name}-...` in the system temporary directory that is unique
+/// for this call (across the tests running in parallel, and across the test processes).
+pub(crate) fn unique_temp_path(name: &str) -> PathBuf {
+    let counter = TEMP_PATH_COUNTER.fetch_add(1, Ordering::SeqCst);
+    std::env::temp_dir().join(format!(
+        "hctl-test-{}-{}-{name}",
+        std::process::id(),
+        counter
+    ))
+}
 
 /// Generate set of `num` syntactic trees for random boolean expressions.
 /// The trees are almost full binary trees with given `height` (but there are random negation nodes between the
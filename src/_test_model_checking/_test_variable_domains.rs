@@ -2,10 +2,15 @@ use crate::_test_model_checking::_test_util::make_random_boolean_trees;
 use crate::_test_model_checking::{
     MODEL_CELL_CYCLE, MODEL_CELL_DIVISION, MODEL_YEAST, NUM_FUZZING_CASES,
 };
+use crate::evaluation::algorithm::{compute_steady_states, eval_node};
+use crate::evaluation::eval_context::EvalContext;
 use crate::mc_utils::get_extended_symbolic_graph;
 use crate::model_checking::{
     model_check_extended_formula, model_check_formula, model_check_tree_dirty,
 };
+use crate::preprocessing::parser::parse_extended_formula;
+use crate::preprocessing::utils::validate_props_and_rename_vars;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::BooleanNetwork;
 use std::collections::HashMap;
 
@@ -39,6 +44,7 @@ fn model_check_with_domains() {
                 ("3{x}:@{x}: %s% & AG EF{x}", "3{x} in %s%:@{x}:AG EF{x}"),
                 ("V{x}:@{x}: %s% => AG EF{x}", "V{x} in %s%:@{x}:AG EF{x}"),
                 ("!{x}: %s% & (AG EF {x})", "!{x} in %s%: AG EF {x}"),
+                ("3{x}: @{x}: %s% & AG EF{x}", "3{x}: @{x} in %s%: AG EF{x}"),
             ];
 
             for (f, f_with_domain) in formulae_pairs {
@@ -55,6 +61,99 @@ fn model_check_with_domains() {
     }
 }
 
+#[test]
+/// Test evaluation of HCTL formulae with domains of quantified variables given inline by Boolean
+/// expressions. Compare them with equivalent formulae without domains. Use all 3 pre-defined models.
+fn model_check_with_inline_domains() {
+    // bn for each of the 3 predefined models
+    let bn1 = BooleanNetwork::try_from(MODEL_CELL_DIVISION).unwrap();
+    let bn2 = BooleanNetwork::try_from_bnet(MODEL_CELL_CYCLE).unwrap();
+    let bn3 = BooleanNetwork::try_from_bnet(MODEL_YEAST).unwrap();
+    let bns = [bn1, bn2, bn3];
+
+    for bn in bns {
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // random boolean expressions are used as the inline domains
+        let random_boolean_trees = make_random_boolean_trees(NUM_FUZZING_CASES, 4, &bn, 0);
+        for tree in random_boolean_trees {
+            let formulae_pairs = [
+                (
+                    format!("3{{x}}: @{{x}}: {tree} & AG EF {{x}}"),
+                    format!("3{{x}} in [{tree}]: @{{x}}: AG EF {{x}}"),
+                ),
+                (
+                    format!("V{{x}}: @{{x}}: {tree} => AG EF {{x}}"),
+                    format!("V{{x}} in [{tree}]: @{{x}}: AG EF {{x}}"),
+                ),
+                (
+                    format!("!{{x}}: {tree} & (AG EF {{x}})"),
+                    format!("!{{x}} in [{tree}]: AG EF {{x}}"),
+                ),
+                (
+                    format!("3{{x}}: @{{x}}: {tree} & AG EF {{x}}"),
+                    format!("3{{x}}: @{{x}} in [{tree}]: AG EF {{x}}"),
+                ),
+            ];
+
+            for (f, f_with_domain) in formulae_pairs {
+                let res = model_check_formula(&f, &stg).unwrap();
+                let res_v2 = model_check_formula(&f_with_domain, &stg).unwrap();
+                assert!(res.as_bdd().iff(res_v2.as_bdd()).is_true());
+            }
+        }
+    }
+}
+
+#[test]
+/// Test evaluation of formulae quantifying over fair attractors (`in fair_attractors`), which
+/// always include all the (standard) attractors.
+fn model_check_with_fair_attractors() {
+    let bn1 = BooleanNetwork::try_from(MODEL_CELL_DIVISION).unwrap();
+    let bn2 = BooleanNetwork::try_from_bnet(MODEL_CELL_CYCLE).unwrap();
+    let bn3 = BooleanNetwork::try_from_bnet(MODEL_YEAST).unwrap();
+    for bn in [bn1, bn2, bn3] {
+        let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let formula = "(!{x}: AG EF {x}) & ~(3{y} in fair_attractors: {y})";
+        assert!(model_check_formula(formula, &stg).unwrap().is_empty());
+    }
+
+    // the cycle with c=0 can only be left by firing `c`, which never fires inside the cycle,
+    // so the fair attractors are exactly the attractors
+    let bn = BooleanNetwork::try_from(
+        "b -?? a\na -?? b\na -?? c\nb -?? c\nc -?? c\n$a: !b\n$b: a\n$c: c | (a & b)",
+    )
+    .unwrap();
+    let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+    let fair_attractors = model_check_formula("3{x} in fair_attractors: {x}", &stg).unwrap();
+    let attractors = model_check_formula("!{x}: AG EF {x}", &stg).unwrap();
+    assert_eq!(fair_attractors, attractors);
+
+    // the cycle `000 -> 100 -> 110 -> 111 -> 011 -> 001 -> 000` can be left by firing `b` in
+    // `000`, but `b` also fires inside the cycle, so all states are in fair attractors (the
+    // remaining two states are steady)
+    let bn = BooleanNetwork::try_from(
+        "a -?? a\nb -?? a\nc -?? a\na -?? b\nb -?? b\nc -?? b\na -?? c\nb -?? c\nc -?? c\n\
+        $a: (a & !c) | (!b & !c) | (a & !b)\n$b: !c | (a & b)\n$c: (a & b) | (b & c) | (a & c)",
+    )
+    .unwrap();
+    let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+    let formulae_pairs = [
+        ("3{x} in fair_attractors: {x}", "true"),
+        ("!{x}: AG EF {x}", "!{x}: AX {x}"),
+        (
+            "(3{x} in fair_attractors: {x}) & ~(!{y}: AG EF {y})",
+            "~(!{x}: AX {x})",
+        ),
+        ("!{x} in fair_attractors: AX {x}", "!{x}: AX {x}"),
+    ];
+    for (f1, f2) in formulae_pairs {
+        let res1 = model_check_formula(f1, &stg).unwrap();
+        let res2 = model_check_formula(f2, &stg).unwrap();
+        assert!(res1.as_bdd().iff(res2.as_bdd()).is_true());
+    }
+}
+
 #[test]
 /// Test evaluation of extended HCTL formulae, where quantified vars are given an empty domain.
 /// This is an edge-case worth testing (explicitly handled during the evaluation).
@@ -67,6 +166,10 @@ fn model_check_with_empty_domain() {
         ("3{x}: @{x}: false & (AX {x})", "3{x} in %s%: @{x}: AX {x}"),
         ("V{x}: @{x}: false => (AX {x})", "V{x} in %s%: @{x}: AX {x}"),
         ("!{x}: false & (AX {x})", "!{x} in %s%: (AX {x})"),
+        (
+            "!{x}: 3{y}: @{y}: false & AX {x}",
+            "!{x}: 3{y}: @{y} in %s%: AX {x}",
+        ),
     ];
 
     let empty_set = stg.mk_empty_colored_vertices();
@@ -83,6 +186,51 @@ fn model_check_with_empty_domain() {
     }
 }
 
+#[test]
+/// Test evaluation of extended HCTL formulae, where the domains of quantified vars are empty only
+/// for some colors. These colors are removed from the evaluation of the quantified sub-formulae.
+fn model_check_with_partially_empty_domain() {
+    // the update of `a` is unknown, and there are no fixed points if it is a negation
+    let bn = BooleanNetwork::try_from("a -?? a\na -> b\n$b: a").unwrap();
+    let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+    // we must use 'dirty' version to avoid sanitation (BDDs must retain all symbolic vars)
+    let tree = parse_extended_formula("!{x}: AX {x}").unwrap();
+    let fixed_points = model_check_tree_dirty(tree, &stg).unwrap();
+    assert!(!fixed_points.is_empty());
+    assert_ne!(fixed_points.colors(), stg.mk_unit_colors());
+
+    let formulae_pairs = [
+        (
+            "3{x}: @{x}: %s% & AG EF {x}",
+            "3{x} in %s%: @{x}: AG EF {x}",
+        ),
+        (
+            "V{x}: @{x}: %s% => AG EF {x}",
+            "V{x} in %s%: @{x}: AG EF {x}",
+        ),
+        ("!{x}: %s% & (AG EF {x})", "!{x} in %s%: AG EF {x}"),
+        (
+            "3{x}: @{x}: %s% & (V{y}: @{y}: %s% => EF {x})",
+            "3{x} in %s%: V{y} in %s%: @{y}: EF {x}",
+        ),
+    ];
+    let context = HashMap::from([("s".to_string(), fixed_points)]);
+    for (f, domain_f) in formulae_pairs {
+        let res = model_check_extended_formula(f, &stg, &context).unwrap();
+        let res_v2 = model_check_extended_formula(domain_f, &stg, &context).unwrap();
+        assert!(res.as_bdd().iff(res_v2.as_bdd()).is_true());
+    }
+
+    // the colors without fixed points are pruned for the (outer) quantifier
+    let tree = parse_extended_formula("V{x} in %s%: @{x}: AX {x}").unwrap();
+    let tree = validate_props_and_rename_vars(tree, stg.symbolic_context()).unwrap();
+    let mut eval_info = EvalContext::from_single_tree(&tree);
+    eval_info.extend_context_with_wild_cards(&HashMap::new(), &context);
+    let result = eval_node(tree, &stg, &mut eval_info, &compute_steady_states(&stg));
+    assert_eq!(eval_info.get_statistics().num_pruned_domains, 1);
+    assert_eq!(result, stg.mk_unit_colored_vertices());
+}
+
 #[test]
 /// Test evaluation of extended HCTL formulae with restricted domains of quantified variables.
 /// Consider cases with multiple domains, use all 3 pre-defined models.
@@ -153,3 +301,23 @@ fn model_check_with_domains_complex() {
         }
     }
 }
+
+#[test]
+/// Test that the same inline domain gives the same set when used both under a quantifier with
+/// another domain (where the graph is restricted to the domain of its variable) and outside.
+fn model_check_with_nested_inline_domains() {
+    let bn = BooleanNetwork::try_from("a -> b\nb -| a\n$a: !b\n$b: a").unwrap();
+    let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+
+    let nested = "3{x} in [a]: @{x}: 3{y} in [~b]: @{y}: EF {x}";
+    let outside = "3{z} in [~b]: @{z}: ~a";
+    let expected = model_check_formula(nested, &stg)
+        .unwrap()
+        .intersect(&model_check_formula(outside, &stg).unwrap());
+    let result = model_check_formula(&format!("({nested}) & ({outside})"), &stg).unwrap();
+    assert_eq!(result, expected);
+    assert!(!result.is_empty());
+    // the other way round
+    let result = model_check_formula(&format!("({outside}) & ({nested})"), &stg).unwrap();
+    assert_eq!(result, expected);
+}
@@ -21,7 +21,7 @@ mod _test_unsafe_optimization;
 mod _test_variable_domains;
 
 /// **(internal)**  Utilities used in tests, such as generating sets of random formula trees.
-mod _test_util;
+pub(crate) mod _test_util;
 
 /// In some cases, we do fuzzing tests using pseudo-random formulae/sets.
 /// This is number of such generated formulae in each of these tests.
@@ -1,20 +1,93 @@
 //! Model-checking analysis from start to finish, with progress output and result prints.
 
 use crate::evaluation::algorithm::{compute_steady_states, eval_node};
-use crate::evaluation::eval_context::EvalContext;
-use crate::mc_utils::{collect_unique_hctl_vars, get_extended_symbolic_graph};
-use crate::preprocessing::parser::{parse_extended_formula, parse_hctl_formula};
+use crate::evaluation::basins::basins_context;
+use crate::evaluation::eval_context::{
+    AnalysisLimits, CancellationFlag, EvalContext, LIMIT_CHECK_INTERVAL,
+};
+use crate::evaluation::hctl_operators_eval::{eval_af, eval_ef_saturated};
+use crate::evaluation::mark_duplicates::group_formulae_sharing_duplicates;
+use crate::evaluation::snapshots::{formula_checkpoint_path, FixedPointSnapshot, SnapshotHook};
+use crate::interventions::{rank_driver_variables, DriverImpact};
+use crate::mc_utils::{
+    collect_unique_hctl_vars, collect_unique_wild_cards, get_extended_symbolic_graph,
+    is_ctl_formula, knock_out_regulation, required_hctl_vars, with_fixed_inputs,
+    with_frozen_variables,
+};
+use crate::preprocessing::analysis::lint_formula;
+use crate::preprocessing::parser::{
+    parse_extended_formula, parse_extended_formula_with_recovery, parse_hctl_formula,
+    parse_hctl_formula_with_recovery,
+};
+use crate::preprocessing::rewriting::{optimize_formula, push_quantifiers_inward};
 use crate::preprocessing::utils::{validate_and_divide_wild_cards, validate_props_and_rename_vars};
 use crate::result_print::*;
 
 use biodivine_lib_param_bn::BooleanNetwork;
 
-use crate::evaluation::LabelToSetMap;
-use crate::generate_output::build_result_archive;
-use crate::load_inputs::load_bdd_bundle;
-use biodivine_lib_param_bn::symbolic_async_graph::SymbolicContext;
-use std::collections::HashMap;
-use std::time::SystemTime;
+use crate::evaluation::{EvalOptions, LabelToSetMap};
+use crate::generate_output::{
+    build_annotated_sbml, build_result_archive, build_result_archive_with_intermediates,
+    export_results, write_cached_result, AnalysisMetadata, ResultExportFormat,
+};
+use crate::load_inputs::{
+    load_analysis_metadata, load_cached_result, load_context_sets, load_intermediate_results,
+    load_network, load_result_archive, validate_context_sets,
+};
+use crate::model_checking::{model_check_multiple_formulae, model_check_multiple_trees};
+use crate::postprocessing::color_description::describe_colors;
+use crate::postprocessing::explanation::explain_existential_formula;
+use crate::postprocessing::influence::{compute_formula_influence, FormulaInfluence};
+use crate::postprocessing::sanitizing::{
+    extend_colored_vertices, sanitize_colored_vertices, sanitize_colors,
+};
+use crate::postprocessing::set_expressions::{evaluate_set_tree, parse_set_expression};
+use crate::postprocessing::vacuity::find_vacuity_witnesses;
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::operator_enums::{BinaryOp, UnaryOp};
+use crate::reduction::restrict_to_formulae_module;
+use crate::traces::{
+    check_formula_on_trace, find_trace_states_outside, trace_colors_in_set, Trace,
+};
+use biodivine_lib_param_bn::biodivine_std::bitvector::BitVector;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph, SymbolicContext,
+};
+use std::cmp::max;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Instant, SystemTime};
+
+/// Options of the whole model-checking analysis (see [analyse_formulae_with_options]).
+#[derive(Clone, Debug, Default)]
+pub struct AnalysisOptions {
+    /// Path of the zip archive to write the results to.
+    pub result_zip: Option<String>,
+    /// Path of the result archive providing the extended context (for the wild-card propositions
+    /// and domains of the formulae).
+    pub context_archive_path: Option<String>,
+    /// Number of threads and resource limits of the evaluation.
+    pub limits: AnalysisLimits,
+    /// Inputs and parameters fixed to the given values (see [with_fixed_inputs]).
+    pub fixed_inputs: HashMap<String, bool>,
+    /// Options of the evaluation itself.
+    pub eval_options: EvalOptions,
+    /// Result archive of a previous run, whose results (and those of the sub-formulae) are
+    /// reused instead of being recomputed (see [load_known_results]).
+    pub warm_start_archive: Option<String>,
+    /// Result cache directory, from which the results of the formulae checked before are
+    /// loaded, and where the new results are stored (see [result_cache_key]).
+    pub result_cache_dir: Option<String>,
+    /// Pre-computed steady states of the model (see [compute_steady_states]), which are otherwise
+    /// computed again. These can be sanitized, or in the symbolic context of any graph of the
+    /// model.
+    pub steady_states: Option<GraphColoredVertices>,
+}
 
 /// Perform the whole model checking analysis regarding several (individual) formulae. This
 /// comprises the complete process from the parsing to summarizing results).
@@ -30,16 +103,404 @@ pub fn analyse_formulae(
     result_zip: Option<String>,
     context_archive_path: Option<String>,
 ) -> Result<(), String> {
-    let start = SystemTime::now();
+    let options = AnalysisOptions {
+        result_zip,
+        context_archive_path,
+        ..AnalysisOptions::default()
+    };
+    analyse_formulae_with_options(bn, formulae, print_opt, &options)
+}
+
+/// Perform the whole model checking analysis regarding several (individual) formulae, same as
+/// [analyse_formulae], but with the given analysis `options`:
+///  - The formulae are evaluated using the given number of threads, respecting the resource
+///    limits. If any of the limits is reached, the evaluation stops, and only the results
+///    computed so far are summarized (and written to the archive). The running evaluations are
+///    cancelled (at their next fixed-point iteration or sub-formula), and all the evaluation
+///    threads are finished before returning. Formulae sharing some duplicate sub-formulae are
+///    evaluated by the same thread, so that they share the cached results.
+///  - The selected inputs and parameters are fixed to the given values (see [with_fixed_inputs]).
+///  - If the formulae are optimized, both their original and optimized versions are printed.
+///  - If the modular analysis is selected and applicable (see [restrict_to_formulae_module]),
+///    the formulae are analysed on the restricted network, and the results (including the
+///    archive) only cover the variables of the module. Fixed inputs (and frozen variables)
+///    outside the module are then ignored.
+///  - With a warm-start archive, the evaluation cache is pre-populated by the results of
+///    a previous run (see [load_known_results]), which are not recomputed.
+///  - With a result cache directory, the results of the formulae checked before are loaded
+///    from the cache, and the newly computed results are stored there. The results are cached
+///    by the [result_cache_key] of the (possibly restricted) model, the formula, and the options.
+///    The formulae with wild-card propositions or domains depend on the extended context, so
+///    their results are never cached. Entries that can not be read are computed again.
+pub fn analyse_formulae_with_options(
+    bn: &BooleanNetwork,
+    formulae: Vec<String>,
+    print_opt: PrintOptions,
+    options: &AnalysisOptions,
+) -> Result<(), String> {
+    analyse_formulae_streamed(bn, formulae, print_opt, options, |_, _| {})
+}
+
+/// Perform the whole model checking analysis regarding several (individual) formulae, same as
+/// [analyse_formulae_with_options], but also pass each result to the `on_result` callback as soon
+/// as it is computed (together with the index of its formula), instead of only summarizing all
+/// results at the end. This way, the results of other formulae are available while a single
+/// expensive formula is still being evaluated.
+///
+/// The results are sanitized (in the canonical context of the analysed network, which may be a
+/// module of `bn`, see [analyse_formulae_with_options]), and they come in the order in which they
+/// were computed. If a resource limit is reached, the callback is not invoked for the remaining
+/// formulae.
+pub fn analyse_formulae_streamed<F: FnMut(usize, &GraphColoredVertices)>(
+    bn: &BooleanNetwork,
+    formulae: Vec<String>,
+    print_opt: PrintOptions,
+    options: &AnalysisOptions,
+    mut on_result: F,
+) -> Result<(), String> {
+    let mut options = options.clone();
+    let module_bn = restrict_analysis_to_module(bn, &formulae, &mut options, print_opt)?;
+    let bn = module_bn.as_ref().unwrap_or(bn);
+
+    // the results of a previous run and the cached results are used the same way
+    let mut known_results = match &options.warm_start_archive {
+        Some(archive_path) => load_known_results(
+            archive_path,
+            bn,
+            &options.fixed_inputs,
+            &options.eval_options.frozen_variables,
+        )?,
+        None => Vec::new(),
+    };
+    // keys of the results that are not cached yet
+    let mut cache_keys = vec![None; formulae.len()];
+    if let Some(cache_dir) = &options.result_cache_dir {
+        let mut num_cached = 0;
+        for (i, formula) in formulae.iter().enumerate() {
+            let key = result_cache_key(bn, formula, &options.fixed_inputs, &options.eval_options);
+            if let Ok(key) = key {
+                match load_cached_result(cache_dir, &key, bn) {
+                    Ok(Some(result)) => {
+                        known_results.push((formula.clone(), result));
+                        num_cached += 1;
+                    }
+                    Ok(None) => cache_keys[i] = Some(key),
+                    // an unreadable entry is computed again (and replaced)
+                    Err(e) => {
+                        print_if_allowed(
+                            format!("Ignoring the cached result of formula {}: {e}", i + 1),
+                            print_opt,
+                        );
+                        cache_keys[i] = Some(key);
+                    }
+                }
+            }
+        }
+        print_if_allowed(
+            format!(
+                "Loaded {num_cached} out of {} results from the cache `{cache_dir}`.",
+                formulae.len()
+            ),
+            print_opt,
+        );
+    }
+
+    let result_names = (0..formulae.len())
+        .map(|i| format!("formula-{i}"))
+        .collect();
+    let model_string = bn.to_string();
+    let mut cache_error = None;
+    analyse_named_formulae(
+        bn,
+        result_names,
+        formulae.clone(),
+        print_opt,
+        &options,
+        &known_results,
+        &mut |i, result| {
+            if let (Some(cache_dir), Some(key)) = (&options.result_cache_dir, &cache_keys[i]) {
+                if let Err(e) =
+                    write_cached_result(cache_dir, key, &model_string, &formulae[i], result)
+                {
+                    cache_error = Some(format!("Result cache can not be written: {e}"));
+                }
+            }
+            on_result(i, result);
+        },
+    )?;
+    cache_error.map_or(Ok(()), Err)
+}
+
+/// If the modular analysis is selected and applicable (see [analyse_formulae_with_options]),
+/// restrict the network to the module of the formulae, and drop the fixed inputs and frozen
+/// variables (in `options`) outside the module. Return the module network (if any).
+fn restrict_analysis_to_module(
+    bn: &BooleanNetwork,
+    formulae: &[String],
+    options: &mut AnalysisOptions,
+    print_opt: PrintOptions,
+) -> Result<Option<BooleanNetwork>, String> {
+    let module_bn = if options.eval_options.modular {
+        restrict_to_formulae_module(bn, formulae)?
+    } else {
+        None
+    };
+    if let Some(module_bn) = &module_bn {
+        print_if_allowed(
+            format!(
+                "Restricted the analysis to a module with {} out of {} variables.",
+                module_bn.num_vars(),
+                bn.num_vars()
+            ),
+            print_opt,
+        );
+        options.fixed_inputs.retain(|name, _| {
+            module_bn.as_graph().find_variable(name).is_some()
+                || module_bn.find_parameter(name).is_some()
+        });
+        // frozen variables outside the module do not influence it
+        options
+            .eval_options
+            .frozen_variables
+            .retain(|name| module_bn.as_graph().find_variable(name).is_some());
+    }
+    Ok(module_bn)
+}
+
+/// Compute the key of the result of the `formula` in the result cache (see
+/// [AnalysisOptions::result_cache_dir]). The key is a stable hash (the same across runs and
+/// machines) of the canonical forms of the inputs that determine the result: the model `bn`, the
+/// formula (with renamed variables, so that formulae differing only in the names of variables
+/// share the key), the `fixed_inputs`, and the frozen variables of the `options`.
+///
+/// Return an error if the formula is not a valid HCTL formula for the model (formulae with
+/// wild-card propositions or domains can not be cached).
+pub fn result_cache_key(
+    bn: &BooleanNetwork,
+    formula: &str,
+    fixed_inputs: &HashMap<String, bool>,
+    options: &EvalOptions,
+) -> Result<String, String> {
+    let ctx = SymbolicContext::new(bn)?;
+    let tree = validate_props_and_rename_vars(parse_hctl_formula(formula)?, &ctx)?;
+    let mut fixed_inputs = fixed_inputs
+        .iter()
+        .map(|(name, value)| format!("{name}={}", *value as u8))
+        .collect::<Vec<_>>();
+    fixed_inputs.sort();
+    let mut frozen_variables = options.frozen_variables.clone();
+    frozen_variables.sort();
+    frozen_variables.dedup();
+
+    let canonical_inputs = [
+        bn.to_string(),
+        tree.to_string(),
+        fixed_inputs.join(","),
+        frozen_variables.join(","),
+    ];
+    Ok(format!(
+        "{:016x}",
+        stable_hash(&canonical_inputs.join("\0"))
+    ))
+}
+
+/// Compute the 64-bit FNV-1a hash of the `data`. Unlike the standard hasher, the hash is the same
+/// across runs, platforms, and Rust versions.
+fn stable_hash(data: &str) -> u64 {
+    data.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Load the results of a previous run from a result archive (produced by the analysis), paired
+/// with the corresponding formulae. The archive must contain results for the same model as `bn`
+/// (i.e., with the same variables and parameters), computed with the same `fixed_inputs` and
+/// `frozen_variables` (as recorded in its [AnalysisMetadata]). Archives without the metadata
+/// can only be used without any fixed inputs and frozen variables.
+///
+/// If the archive also contains the intermediate results of cached sub-formulae (see
+/// [EvalOptions::dump_intermediates]), these are included as well (after the formulae).
+///
+/// The results are in the canonical symbolic context of the model.
+pub fn load_known_results(
+    archive_path: &str,
+    bn: &BooleanNetwork,
+    fixed_inputs: &HashMap<String, bool>,
+    frozen_variables: &[String],
+) -> Result<Vec<(String, GraphColoredVertices)>, String> {
+    let (archived_bn, formulae, results) = load_result_archive(archive_path)?;
+    if archived_bn.variables().count() != bn.variables().count()
+        || archived_bn
+            .variables()
+            .any(|v| archived_bn.get_variable_name(v) != bn.get_variable_name(v))
+        || !colors_are_aligned(&archived_bn, bn)
+    {
+        return Err(format!(
+            "Archive `{archive_path}` contains results for a different model."
+        ));
+    }
+    // without the metadata, the results are named by the positions of the formulae
+    let metadata = load_analysis_metadata(archive_path)?.unwrap_or_else(|| AnalysisMetadata {
+        result_names: (0..formulae.len())
+            .map(|i| format!("formula-{i}"))
+            .collect(),
+        ..AnalysisMetadata::default()
+    });
+    let expected = analysis_metadata(Vec::new(), fixed_inputs, frozen_variables);
+    if metadata.fixed_inputs != expected.fixed_inputs
+        || metadata.frozen_variables != expected.frozen_variables
+    {
+        return Err(format!(
+            "Archive `{archive_path}` contains results computed with different fixed inputs or \
+            frozen variables."
+        ));
+    }
+
+    let mut known_results = Vec::new();
+    for (formula, name) in formulae.into_iter().zip(metadata.result_names) {
+        // the archive may contain only some of the results (e.g., if a limit was reached)
+        if let Some(result) = results.get(&name) {
+            known_results.push((formula, result.clone()));
+        }
+    }
+    // intermediate results of sub-formulae that can not be parsed as HCTL (e.g., those with
+    // wild-card propositions) can not be reused
+    for (formula, result) in load_intermediate_results(archive_path)? {
+        if parse_hctl_formula(formula.as_str()).is_ok() {
+            known_results.push((formula, result));
+        }
+    }
+    Ok(known_results)
+}
+
+/// Statistics of the evaluation shared among the formulae of one analysis (see
+/// [compute_sharing_statistics]).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SharingStatistics {
+    /// Number of all formulae.
+    pub num_formulae: usize,
+    /// Number of distinct formulae (the identical formulae are only evaluated once).
+    pub num_unique_formulae: usize,
+    /// Number of distinct sub-formulae that occur several times in the distinct formulae.
+    pub num_shared_subformulae: usize,
+    /// Number of the repeated occurrences of the shared sub-formulae, i.e., the evaluations that
+    /// are replaced by the cached results.
+    pub num_repeated_occurrences: usize,
+}
+
+/// Compute the statistics of the evaluation shared among the given formula trees (with the
+/// variables already renamed, see [validate_props_and_rename_vars]). The duplicate sub-formulae
+/// are found throughout all the (distinct) formulae, regardless of where they come from.
+pub fn compute_sharing_statistics(trees: &[HctlTreeNode]) -> SharingStatistics {
+    let representatives = find_identical_formulae(trees);
+    let unique_trees = trees
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| representatives[*i] == *i)
+        .map(|(_, tree)| tree.clone())
+        .collect::<Vec<_>>();
+    let eval_info = EvalContext::from_multiple_trees(&unique_trees);
+    SharingStatistics {
+        num_formulae: trees.len(),
+        num_unique_formulae: unique_trees.len(),
+        num_shared_subformulae: eval_info.get_duplicates().len(),
+        num_repeated_occurrences: eval_info.get_duplicates().values().sum::<i32>() as usize,
+    }
+}
+
+/// For each of the formula trees, find the index of the first identical tree (which is the tree
+/// itself if there is no identical tree before it).
+fn find_identical_formulae(trees: &[HctlTreeNode]) -> Vec<usize> {
+    let mut first_indices: HashMap<String, usize> = HashMap::new();
+    trees
+        .iter()
+        .enumerate()
+        .map(|(i, tree)| *first_indices.entry(tree.to_string()).or_insert(i))
+        .collect()
+}
+
+/// Choose the number of HCTL variables of the extended graph for the evaluation of the given
+/// (renamed) formula trees: the `requested` number if given, or the minimal number needed by the
+/// formulae (see [required_hctl_vars]). Return an error if the requested number is not enough,
+/// or if a `wild-card` is used with different numbers of variables.
+fn choose_num_hctl_vars(trees: &[HctlTreeNode], requested: Option<usize>) -> Result<usize, String> {
+    let required = required_hctl_vars(trees)?;
+    match requested {
+        Some(requested) if requested < required => Err(format!(
+            "The formulae need {required} HCTL variables, but only {requested} were requested."
+        )),
+        Some(requested) => Ok(requested),
+        None => Ok(required),
+    }
+}
+
+/// Collect the [AnalysisMetadata] of an analysis with the given result names, fixed inputs, and
+/// frozen variables.
+fn analysis_metadata(
+    result_names: Vec<String>,
+    fixed_inputs: &HashMap<String, bool>,
+    frozen_variables: &[String],
+) -> AnalysisMetadata {
+    let mut frozen_variables = frozen_variables.to_vec();
+    frozen_variables.sort();
+    frozen_variables.dedup();
+    AnalysisMetadata {
+        result_names,
+        fixed_inputs: fixed_inputs.clone().into_iter().collect(),
+        frozen_variables,
+    }
+}
+
+/// Modify the `graph` the same way as the analysis does (see [analyse_formulae_with_options]), by
+/// fixing the given inputs and freezing the given variables.
+fn modify_graph_for_analysis(
+    mut graph: SymbolicAsyncGraph,
+    fixed_inputs: &HashMap<String, bool>,
+    frozen_variables: &[String],
+) -> Result<SymbolicAsyncGraph, String> {
+    if !fixed_inputs.is_empty() {
+        graph = with_fixed_inputs(&graph, fixed_inputs)?;
+    }
+    // the variables must be frozen after fixing the inputs, which rebuilds the update functions
+    if !frozen_variables.is_empty() {
+        graph = with_frozen_variables(&graph, frozen_variables)?;
+    }
+    Ok(graph)
+}
+
+/// Internal version of [analyse_formulae_with_options], where the results are stored in the
+/// archive under the given names (one for each formula), and the evaluation cache is
+/// pre-populated by the `known_results`. The warm-start archive and result cache of the
+/// `analysis_options` are not used (they are handled by the caller), and the modular analysis
+/// is not applied. Each sanitized result is also passed to `on_result` as soon as it is computed
+/// (see [analyse_formulae_streamed]).
+fn analyse_named_formulae(
+    bn: &BooleanNetwork,
+    result_names: Vec<String>,
+    formulae: Vec<String>,
+    print_opt: PrintOptions,
+    analysis_options: &AnalysisOptions,
+    known_results: &[(String, GraphColoredVertices)],
+    on_result: &mut dyn FnMut(usize, &GraphColoredVertices),
+) -> Result<(), String> {
+    let AnalysisOptions {
+        result_zip,
+        context_archive_path,
+        limits,
+        fixed_inputs,
+        eval_options: options,
+        steady_states,
+        ..
+    } = analysis_options;
+    let start = Instant::now();
     let use_extended = context_archive_path.is_some();
     print_if_allowed(
         "============ INITIAL PHASE ============".to_string(),
         print_opt,
     );
 
-    // first parse all the formulae and count max number of HCTL variables
+    // first parse all the formulae
     let mut parsed_trees = Vec::new();
-    let mut max_num_hctl_vars = 0;
     print_if_allowed(format!("Read {} HCTL formulae.", formulae.len()), print_opt);
     print_if_allowed("-----".to_string(), print_opt);
 
@@ -58,21 +519,45 @@ pub fn analyse_formulae(
         };
         print_if_allowed(format!("Parsed version:       {tree}"), print_opt);
 
-        // validate propositions and modify variable names in the formula
-        let modified_tree = validate_props_and_rename_vars(tree, &plain_context)?;
+        // validate propositions and modify variable names in the formula, and push the
+        // quantifiers inward to reduce the scopes of the variables
+        let modified_tree =
+            push_quantifiers_inward(validate_props_and_rename_vars(tree, &plain_context)?);
         print_if_allowed(format!("Modified version:     {modified_tree}"), print_opt);
+        let modified_tree = if options.optimize {
+            let optimized_tree = push_quantifiers_inward(optimize_formula(modified_tree));
+            print_if_allowed(format!("Optimized version:    {optimized_tree}"), print_opt);
+            optimized_tree
+        } else {
+            modified_tree
+        };
         print_if_allowed("-----".to_string(), print_opt);
-
-        let num_hctl_vars = collect_unique_hctl_vars(modified_tree.clone()).len();
-        if num_hctl_vars > max_num_hctl_vars {
-            max_num_hctl_vars = num_hctl_vars;
-        }
-
         parsed_trees.push(modified_tree);
     }
 
-    // instantiate one extended STG with enough variables to evaluate all formulae
-    let graph = get_extended_symbolic_graph(bn, max_num_hctl_vars as u16)?;
+    // instantiate one extended STG with enough variables to evaluate all formulae (pure CTL
+    // formulae need none, so they are evaluated on the plain graph, unless more variables are
+    // requested, and their sub-formulae are never canonized, see `EvalContext::ctl_only`)
+    let num_hctl_vars = choose_num_hctl_vars(&parsed_trees, options.num_hctl_vars)?;
+    let message = match (parsed_trees.iter().all(is_ctl_formula), num_hctl_vars) {
+        (true, 0) => "All formulae are CTL formulae, using the plain graph without HCTL \
+            variables (and without the canonization of sub-formulae)."
+            .to_string(),
+        (true, _) => format!(
+            "All formulae are CTL formulae (evaluated without the canonization of \
+            sub-formulae), using an extended graph with {num_hctl_vars} HCTL variables."
+        ),
+        (false, _) => format!("Using an extended graph with {num_hctl_vars} HCTL variables."),
+    };
+    print_if_allowed(message, print_opt);
+    let graph = get_extended_symbolic_graph(bn, num_hctl_vars as u16)?;
+    let graph = modify_graph_for_analysis(graph, fixed_inputs, &options.frozen_variables)?;
+    if !options.frozen_variables.is_empty() {
+        print_if_allowed(
+            format!("Frozen variables: {}.", options.frozen_variables.join(", ")),
+            print_opt,
+        );
+    }
     print_if_allowed(
         format!(
             "Loaded BN model with {} components and {} parameters.",
@@ -92,23 +577,60 @@ pub fn analyse_formulae(
         ),
         print_opt,
     );
-    print_if_allowed(
-        format!(
-            "Time to parse all formulae + build STG: {}ms.",
-            start.elapsed().unwrap().as_millis()
-        ),
-        print_opt,
-    );
+    if !options.deterministic {
+        print_if_allowed(
+            format!(
+                "Time to parse all formulae + build STG: {}ms.",
+                start.elapsed().as_millis()
+            ),
+            print_opt,
+        );
+    }
     print_if_allowed("-----".to_string(), print_opt);
 
+    // results of a previous run (if provided) are processed the same way as the formulae
+    let mut known_trees = Vec::new();
+    for (formula, result) in known_results {
+        let tree = validate_props_and_rename_vars(parse_hctl_formula(formula)?, &plain_context)?;
+        let result = extend_colored_vertices(&graph, result).ok_or(format!(
+            "Result of `{formula}` is not compatible with the model."
+        ))?;
+        // the known formulae must be processed the same way as the analysed ones to match them
+        let tree = if options.optimize {
+            push_quantifiers_inward(optimize_formula(push_quantifiers_inward(tree)))
+        } else {
+            push_quantifiers_inward(tree)
+        };
+        known_trees.push((tree, result));
+    }
+    if !known_trees.is_empty() {
+        print_if_allowed(
+            format!("Loaded {} results of a previous run.", known_trees.len()),
+            print_opt,
+        );
+        print_if_allowed("-----".to_string(), print_opt);
+    }
+
     // read the contexts (corresponding raw sets) for wild-cards and domains (if provided)
     let mut props_context = HashMap::new();
     let mut domains_context = HashMap::new();
     if use_extended {
-        let all_contexts = load_bdd_bundle(
-            context_archive_path.unwrap().as_str(),
+        // the sets may come from a context with a different number of additional variables
+        let loaded_sets = load_context_sets(
+            context_archive_path.as_ref().unwrap().as_str(),
+            bn,
             graph.symbolic_context(),
         )?;
+        let mut used_labels = Vec::new();
+        for tree in &parsed_trees {
+            let (wild_card_props, var_domains) = collect_unique_wild_cards(tree.clone());
+            used_labels.extend(wild_card_props);
+            used_labels.extend(var_domains);
+        }
+        used_labels.sort();
+        used_labels.dedup();
+        let all_contexts =
+            validate_context_sets(&loaded_sets, &used_labels, bn, graph.symbolic_context())?;
         // validate all wild-cards
         for tree in &parsed_trees {
             let (tree_prop_context, tree_dom_context) =
@@ -123,79 +645,360 @@ pub fn analyse_formulae(
         );
     }
 
-    // find duplicate sub-formulae throughout all formulae + initiate caching structures
-    let mut eval_info = EvalContext::from_multiple_trees(&parsed_trees);
+    // identical formulae (after renaming their variables) are only evaluated once, and the
+    // duplicate sub-formulae are found throughout all the distinct formulae
+    let sharing = compute_sharing_statistics(&parsed_trees);
+    let representatives = find_identical_formulae(&parsed_trees);
+    let unique_trees = parsed_trees
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| representatives[*i] == *i)
+        .collect::<Vec<_>>();
     print_if_allowed(
         format!(
-            "Found following duplicate sub-formulae (canonized): {:?}",
-            eval_info.get_duplicates()
+            "Evaluating {} distinct formulae out of {}, with {} shared sub-formulae ({} repeated \
+            occurrences).",
+            sharing.num_unique_formulae,
+            sharing.num_formulae,
+            sharing.num_shared_subformulae,
+            sharing.num_repeated_occurrences,
         ),
         print_opt,
     );
-    if use_extended {
-        eval_info.extend_context_with_wild_cards(&props_context, &domains_context);
-    }
+
+    // report the duplicate sub-formulae (sorted, to make the output stable)
+    let eval_info =
+        EvalContext::from_multiple_trees(&unique_trees.iter().map(|(_, t)| t.clone()).collect());
+    let duplicates = eval_info
+        .get_duplicates()
+        .iter()
+        .collect::<BTreeMap<_, _>>();
+    print_if_allowed(
+        format!("Found following duplicate sub-formulae (canonized): {duplicates:?}"),
+        print_opt,
+    );
     print_if_allowed("-----".to_string(), print_opt);
 
-    // pre-compute states with self-loops which will be needed
-    let self_loop_states = compute_steady_states(&graph);
+    // pre-compute states with self-loops which will be needed (unless they are provided)
+    let self_loop_states = match steady_states {
+        Some(steady_states) => extend_colored_vertices(&graph, steady_states)
+            .ok_or("Steady states are not compatible with the model.".to_string())?,
+        None => compute_steady_states(&graph),
+    };
     print_if_allowed(
         "Self-loops successfully pre-computed.\n".to_string(),
         print_opt,
     );
 
+    // divide the formulae between the evaluation threads (the deterministic mode uses only one,
+    // so that the results come in the order of the formulae), keeping the formulae that share
+    // some duplicate sub-formulae in the same thread (so that they share the cache)
+    let groups = group_formulae_sharing_duplicates(
+        &unique_trees
+            .iter()
+            .map(|(_, t)| t.clone())
+            .collect::<Vec<_>>(),
+    );
+    let num_groups = groups.iter().map(|g| g + 1).max().unwrap_or(0);
+    let num_threads = if options.deterministic {
+        1
+    } else {
+        limits.num_threads.clamp(1, max(1, num_groups))
+    };
+    let mut group_trees: Vec<Vec<(usize, HctlTreeNode)>> = vec![Vec::new(); num_groups];
+    for ((i, tree), group) in unique_trees.into_iter().zip(groups) {
+        group_trees[group].push((i, tree));
+    }
+    // the largest groups are assigned first, always to the least loaded thread
+    group_trees.sort_by_key(|trees| std::cmp::Reverse(trees.len()));
+    let mut thread_inputs: Vec<Vec<(usize, HctlTreeNode)>> = vec![Vec::new(); num_threads];
+    for trees in group_trees {
+        let thread = (0..num_threads)
+            .min_by_key(|t| thread_inputs[*t].len())
+            .unwrap();
+        thread_inputs[thread].extend(trees);
+    }
+    for trees in &mut thread_inputs {
+        trees.sort_by_key(|(i, _)| *i);
+    }
+
     print_if_allowed(
         "============= EVALUATION PHASE =============".to_string(),
         print_opt,
     );
 
-    // evaluate the formulae (perform the actual model checking) and summarize results
+    // evaluate the formulae (perform the actual model checking) in separate threads, which are
+    // cancelled once a limit is reached
+    let (sender, receiver) = mpsc::channel();
+    let cancellation = CancellationFlag::new();
+    let mut handles = Vec::new();
+    for thread_trees in thread_inputs {
+        // find duplicate sub-formulae throughout thread's formulae + initiate caching structures
+        let trees = thread_trees
+            .iter()
+            .map(|(_, t)| t.clone())
+            .collect::<Vec<_>>();
+        let mut eval_info = EvalContext::from_multiple_trees(&trees);
+        if use_extended {
+            eval_info.extend_context_with_wild_cards(&props_context, &domains_context);
+        }
+        eval_info.extend_context_with_results(&known_trees);
+        if options.dump_intermediates && result_zip.is_some() {
+            eval_info.enable_intermediates();
+        }
+        eval_info.algorithms = options.algorithms;
+        eval_info.set_cancellation(cancellation.clone());
+        if let Some(threshold) = options.spill_threshold {
+            eval_info.enable_spilling(&std::env::temp_dir(), threshold)?;
+        }
+        if let Some(interval) = options.snapshot_interval {
+            let report = move |snapshot: &FixedPointSnapshot| {
+                let kind = if snapshot.is_under_approximation {
+                    "under-approximation"
+                } else {
+                    "over-approximation"
+                };
+                print_if_allowed(
+                    format!(
+                        "Snapshot of `{}` after {} iterations ({kind}): {} results, {} colors.",
+                        snapshot.formula,
+                        snapshot.iteration,
+                        snapshot.approximation.approx_cardinality(),
+                        snapshot.approximation.colors().approx_cardinality(),
+                    ),
+                    print_opt,
+                );
+            };
+            let hook = SnapshotHook::new(Arc::new(Mutex::new(report)), interval);
+            eval_info.attach_snapshots(hook);
+        }
+
+        let graph = graph.clone();
+        let self_loop_states = self_loop_states.clone();
+        let sender = sender.clone();
+        let deterministic = options.deterministic;
+        let checkpoint_path = options.checkpoint_path.clone();
+        handles.push(thread::spawn(move || {
+            for (i, parse_tree) in thread_trees {
+                // each formula writes into its own checkpoint file, so that the threads do not
+                // replace each other's snapshots
+                if let Some(path) = &checkpoint_path {
+                    if let Some(hook) = eval_info.snapshots.take() {
+                        let path = formula_checkpoint_path(Path::new(path), i + 1);
+                        eval_info.attach_snapshots(hook.with_checkpoint(path));
+                    }
+                }
+                // in the deterministic mode, this is printed together with the result (so that
+                // it is not interleaved with the output of the previous result)
+                if !deterministic {
+                    print_if_allowed(format!("Evaluating formula {}...", i + 1), print_opt);
+                }
+                let curr_comp_start = SystemTime::now();
+                let result = eval_node(parse_tree, &graph, &mut eval_info, &self_loop_states);
+                let intermediates = eval_info.take_intermediates();
+                // the results of a cancelled evaluation are not valid
+                if eval_info.is_cancelled() {
+                    return;
+                }
+                if let Some(error) = eval_info.take_error() {
+                    let _ = sender.send(Err(error));
+                    return;
+                }
+                // if the receiver is gone, there is no need to continue
+                if sender
+                    .send(Ok((i, result, curr_comp_start, intermediates)))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }));
+    }
+    drop(sender);
+
+    // collect and summarize the results as they come, while checking the resource limits
     let mut results: LabelToSetMap = LabelToSetMap::new();
-    for (i, parse_tree) in parsed_trees.iter().enumerate() {
-        let formula = formulae[i].clone();
-        print_if_allowed(format!("Evaluating formula {}...", i + 1), print_opt);
-        let curr_comp_start = SystemTime::now();
-        let result = eval_node(
-            parse_tree.clone(),
-            &graph,
-            &mut eval_info,
-            &self_loop_states,
-        );
-
-        match print_opt {
-            PrintOptions::Exhaustive => {
-                print_results_full(formula, &graph, &result, curr_comp_start, true)
+    let mut intermediates: Vec<(String, GraphColoredVertices)> = Vec::new();
+    let mut limit_reached = None;
+    let mut failure = None;
+    while results.len() < formulae.len() {
+        limit_reached = limits.check_exceeded(start);
+        if limit_reached.is_some() {
+            break;
+        }
+        match receiver.recv_timeout(LIMIT_CHECK_INTERVAL) {
+            Ok(Err(error)) => {
+                failure = Some(error);
+                break;
             }
-            PrintOptions::WithProgress => summarize_results(formula, &result, curr_comp_start),
-            PrintOptions::JustSummary => summarize_results(formula, &result, curr_comp_start),
-            PrintOptions::NoPrint => {}
+            Ok(Ok((i, result, curr_comp_start, new_intermediates))) => {
+                // several threads may compute the same sub-formulae
+                for (formula, set) in new_intermediates {
+                    if !intermediates.iter().any(|(f, _)| *f == formula) {
+                        intermediates.push((formula, set));
+                    }
+                }
+                // the result is shared by all formulae identical to the evaluated one
+                let identical = (i..formulae.len()).filter(|j| representatives[*j] == i);
+                for j in identical {
+                    let formula = formulae[j].clone();
+                    let curr_comp_start = if options.deterministic {
+                        print_if_allowed(format!("Evaluating formula {}...", j + 1), print_opt);
+                        None
+                    } else {
+                        Some(curr_comp_start)
+                    };
+                    match print_opt {
+                        PrintOptions::Exhaustive => {
+                            print_results_full(formula, &graph, &result, curr_comp_start, true)
+                        }
+                        PrintOptions::WithProgress => {
+                            summarize_results(formula, &result, curr_comp_start)
+                        }
+                        PrintOptions::JustSummary => {
+                            summarize_results(formula, &result, curr_comp_start)
+                        }
+                        PrintOptions::NoPrint => {}
+                    }
+                    on_result(j, &sanitize_colored_vertices(&graph, &result));
+                    results.insert(result_names[j].clone(), result.clone());
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err("Evaluation of formulae failed unexpectedly.".to_string());
+            }
+        }
+    }
+    // the remaining evaluations are cancelled, and all threads are finished
+    cancellation.cancel();
+    for handle in handles {
+        handle
+            .join()
+            .map_err(|_| "Evaluation of formulae failed unexpectedly.".to_string())?;
+    }
+    if let Some(error) = failure {
+        return Err(error);
+    }
+
+    if let Some(reason) = limit_reached {
+        if print_opt != PrintOptions::NoPrint {
+            println!(
+                "{reason} Only {} out of {} formulae were evaluated, returning partial results.",
+                results.len(),
+                formulae.len()
+            );
         }
-        results.insert(format!("formula-{i}"), result);
     }
 
     // create the archive for the results (for now, there'll be just the model string)
     if let Some(zip_path) = result_zip {
         print_if_allowed(format!("Writing the results to {zip_path}."), print_opt);
-        build_result_archive(
+        let metadata = analysis_metadata(result_names, fixed_inputs, &options.frozen_variables);
+        build_result_archive_with_intermediates(
             results,
             zip_path.as_str(),
             bn.to_string().as_str(),
             formulae,
+            &intermediates,
+            Some(&metadata),
         )
         .map_err(|e| e.to_string())?;
+        if !intermediates.is_empty() {
+            print_if_allowed(
+                format!(
+                    "Included {} intermediate results of cached sub-formulae.",
+                    intermediates.len()
+                ),
+                print_opt,
+            );
+        }
         print_if_allowed("Results successfully written.\n".to_string(), print_opt);
     }
 
-    print_if_allowed(
-        format!(
-            "Total computation time: {}ms",
-            start.elapsed().unwrap().as_millis()
-        ),
-        print_opt,
-    );
+    if !options.deterministic {
+        print_if_allowed(
+            format!("Total computation time: {}ms", start.elapsed().as_millis()),
+            print_opt,
+        );
+    }
     Ok(())
 }
 
+/// Perform a staged analysis (pipeline). Each stage is a list of named formulae `(name, formula)`.
+///
+/// The stages are analysed one by one (same as with [analyse_formulae_with_options]). The results
+/// of each stage are written to a result bundle `stage-{i}.zip` (with results saved under their
+/// names), which then serves as the extended context for the next stage. Therefore, the formulae
+/// of a stage can reference the results of the previous stage as wild-card propositions `%name%`
+/// or variable domains.
+///
+/// If `output_dir` is not provided, the bundles are written into a temporary directory, which is
+/// removed afterwards. The limits apply to each stage separately.
+pub fn analyse_pipeline(
+    bn: &BooleanNetwork,
+    stages: Vec<Vec<(String, String)>>,
+    print_opt: PrintOptions,
+    output_dir: Option<String>,
+    limits: AnalysisLimits,
+) -> Result<(), String> {
+    for (name, _) in stages.iter().flatten() {
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!(
+                "Invalid result name `{name}` (only alphanumeric characters and `_` are allowed)."
+            ));
+        }
+    }
+
+    let keep_bundles = output_dir.is_some();
+    let output_dir = match output_dir {
+        Some(dir) => Path::new(&dir).to_path_buf(),
+        None => std::env::temp_dir().join(format!("hctl-pipeline-{}", std::process::id())),
+    };
+    std::fs::create_dir_all(&output_dir).map_err(|e| e.to_string())?;
+
+    // steady states are computed once and shared by all stages
+    let steady_states = compute_steady_states(&SymbolicAsyncGraph::new(bn)?);
+    let mut context_archive_path = None;
+    let mut pipeline_result = Ok(());
+    for (i, stage) in stages.into_iter().enumerate() {
+        print_if_allowed(
+            format!("================ STAGE {} ================", i + 1),
+            print_opt,
+        );
+        let (result_names, formulae) = stage.into_iter().unzip();
+        let archive_path = output_dir
+            .join(format!("stage-{}.zip", i + 1))
+            .to_string_lossy()
+            .to_string();
+        let options = AnalysisOptions {
+            result_zip: Some(archive_path.clone()),
+            context_archive_path,
+            limits,
+            steady_states: Some(steady_states.clone()),
+            ..AnalysisOptions::default()
+        };
+        pipeline_result = analyse_named_formulae(
+            bn,
+            result_names,
+            formulae,
+            print_opt,
+            &options,
+            &[],
+            &mut |_, _| {},
+        );
+        if pipeline_result.is_err() {
+            break;
+        }
+        context_archive_path = Some(archive_path);
+    }
+
+    if !keep_bundles {
+        std::fs::remove_dir_all(&output_dir).map_err(|e| e.to_string())?;
+    }
+    pipeline_result
+}
+
 #[allow(dead_code)]
 /// Perform the whole model checking analysis for a single formula (complete process from
 /// the parsing to summarizing results).
@@ -219,36 +1022,2343 @@ pub fn analyse_formula(
     )
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::analysis::{analyse_formula, analyse_formulae};
-    use crate::result_print::PrintOptions;
-    use biodivine_lib_param_bn::BooleanNetwork;
+/// Results of repeated evaluation of a single formula, see [benchmark_formulae].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkRecord {
+    /// The benchmarked formula.
+    pub formula: String,
+    /// Number of runs of the evaluation.
+    pub num_runs: usize,
+    /// Mean wall time of the evaluation (in ms).
+    pub mean_ms: f64,
+    /// Median wall time of the evaluation (in ms).
+    pub median_ms: f64,
+    /// Standard deviation of the evaluation wall time (in ms).
+    pub std_dev_ms: f64,
+    /// Size of the largest BDD computed for any sub-formula.
+    pub peak_bdd_size: usize,
+    /// Size of the BDD representing the result.
+    pub result_bdd_size: usize,
+    /// Number of duplicate sub-formulae found in the formula (candidates for caching).
+    pub num_duplicates: usize,
+    /// Number of sub-formulae whose results were taken from the cache.
+    pub num_cache_hits: usize,
+}
 
-    #[test]
-    /// Simple test to check whether the whole analysis runs without an error.
-    fn test_analysis_run() {
-        let model = r"
-                $frs2:(!erk & fgfr)
-                fgfr -> frs2
-                erk -| frs2
-                $fgfr:(fgfr & !frs2)
-                frs2 -| fgfr
-                fgfr -> fgfr
-                $erk:(frs2 | shc)
-                frs2 -> erk
-                shc -> erk
-                $shc:fgfr
-                fgfr -> shc
-        ";
-        let bn = BooleanNetwork::try_from(model).unwrap();
+impl BenchmarkRecord {
+    /// Header of the CSV table with the benchmark records.
+    pub fn csv_header() -> String {
+        "formula,runs,mean_ms,median_ms,std_dev_ms,peak_bdd_size,result_bdd_size,duplicates,cache_hits"
+            .to_string()
+    }
 
-        // try both versions with exhaustive results and without them (they execute different code)
+    /// Format the record as a row of the CSV table (see [BenchmarkRecord::csv_header]).
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "\"{}\",{},{:.3},{:.3},{:.3},{},{},{},{}",
+            self.formula.replace('"', "\"\""),
+            self.num_runs,
+            self.mean_ms,
+            self.median_ms,
+            self.std_dev_ms,
+            self.peak_bdd_size,
+            self.result_bdd_size,
+            self.num_duplicates,
+            self.num_cache_hits,
+        )
+    }
+}
 
-        let formulae = vec!["!{x}: AG EF {x}".to_string(), "!{x}: AF {x}".to_string()];
-        assert!(analyse_formulae(&bn, formulae, PrintOptions::WithProgress, None, None).is_ok());
+/// Evaluate each of the formulae `num_runs` times, and collect the statistics regarding the
+/// wall time of the evaluation, sizes of the BDDs, and caching of duplicate sub-formulae.
+///
+/// The transition graph and the steady states (needed to evaluate any formula) are computed
+/// only once, and are not included in the measured times. Each formula is evaluated separately,
+/// with its own (initially empty) cache.
+pub fn benchmark_formulae(
+    bn: &BooleanNetwork,
+    formulae: Vec<String>,
+    num_runs: usize,
+) -> Result<Vec<BenchmarkRecord>, String> {
+    if num_runs == 0 {
+        return Err("Number of benchmark runs must be positive.".to_string());
+    }
 
-        let formula = "erk & fgfr & ~shc".to_string(); // simple to avoid long prints
-        assert!(analyse_formula(&bn, formula, PrintOptions::Exhaustive, None, None).is_ok());
+    // parse all the formulae and count max number of HCTL variables
+    let plain_context = SymbolicContext::new(bn)?;
+    let mut parsed_trees = Vec::new();
+    let mut max_num_hctl_vars = 0;
+    for formula in &formulae {
+        let tree = parse_hctl_formula(formula.as_str())?;
+        let modified_tree = validate_props_and_rename_vars(tree, &plain_context)?;
+        let num_hctl_vars = collect_unique_hctl_vars(modified_tree.clone()).len();
+        max_num_hctl_vars = max(max_num_hctl_vars, num_hctl_vars);
+        parsed_trees.push(modified_tree);
+    }
+    let graph = get_extended_symbolic_graph(bn, max_num_hctl_vars as u16)?;
+    let self_loop_states = compute_steady_states(&graph);
+
+    let mut records = Vec::new();
+    for (formula, tree) in formulae.into_iter().zip(parsed_trees) {
+        let mut times_ms = Vec::new();
+        let mut result_bdd_size = 0;
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        let num_duplicates = eval_info.get_duplicates().len();
+        for _ in 0..num_runs {
+            eval_info = EvalContext::from_single_tree(&tree);
+            let run_start = Instant::now();
+            let result = eval_node(tree.clone(), &graph, &mut eval_info, &self_loop_states);
+            times_ms.push(run_start.elapsed().as_secs_f64() * 1000.0);
+            result_bdd_size = result.symbolic_size();
+        }
+
+        times_ms.sort_by(|a, b| a.total_cmp(b));
+        let mean_ms = times_ms.iter().sum::<f64>() / num_runs as f64;
+        let median_ms = if num_runs % 2 == 1 {
+            times_ms[num_runs / 2]
+        } else {
+            (times_ms[num_runs / 2 - 1] + times_ms[num_runs / 2]) / 2.0
+        };
+        let variance =
+            times_ms.iter().map(|t| (t - mean_ms).powi(2)).sum::<f64>() / num_runs as f64;
+
+        records.push(BenchmarkRecord {
+            formula,
+            num_runs,
+            mean_ms,
+            median_ms,
+            std_dev_ms: variance.sqrt(),
+            peak_bdd_size: eval_info.get_statistics().peak_bdd_size,
+            result_bdd_size,
+            num_duplicates,
+            num_cache_hits: eval_info.get_statistics().num_cache_hits,
+        });
+    }
+    Ok(records)
+}
+
+/// Summary of the result of a single formula on a single model, see [check_models_in_directory].
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormulaSummary {
+    /// The evaluated formula.
+    pub formula: String,
+    /// Number of satisfying (state, color) pairs.
+    pub num_results: f64,
+    /// Number of colors for which the formula holds in some state.
+    pub num_colors: f64,
+    /// Number of states in which the formula holds for some color.
+    pub num_states: f64,
+    /// Wall time of the evaluation (in ms).
+    pub time_ms: u128,
+}
+
+/// Summaries of results of all formulae on a single model, see [check_models_in_directory].
+/// If the model could not be loaded or analysed, the `results` contain the error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelRecord {
+    /// Name of the model file.
+    pub model_name: String,
+    /// Summaries of the results, one for each formula (in order), or an error message.
+    pub results: Result<Vec<FormulaSummary>, String>,
+}
+
+/// Evaluate all formulae on a given model and summarize the results.
+fn summarize_formulae_on_model(
+    bn: &BooleanNetwork,
+    formulae: &[String],
+) -> Result<Vec<FormulaSummary>, String> {
+    let plain_context = SymbolicContext::new(bn)?;
+    let mut parsed_trees = Vec::new();
+    let mut max_num_hctl_vars = 0;
+    for formula in formulae {
+        let tree = parse_hctl_formula(formula.as_str())?;
+        let modified_tree = validate_props_and_rename_vars(tree, &plain_context)?;
+        let num_hctl_vars = collect_unique_hctl_vars(modified_tree.clone()).len();
+        max_num_hctl_vars = max(max_num_hctl_vars, num_hctl_vars);
+        parsed_trees.push(modified_tree);
+    }
+    let graph = get_extended_symbolic_graph(bn, max_num_hctl_vars as u16)?;
+    let mut eval_info = EvalContext::from_multiple_trees(&parsed_trees);
+    let self_loop_states = compute_steady_states(&graph);
+
+    let mut summaries = Vec::new();
+    for (formula, tree) in formulae.iter().zip(parsed_trees) {
+        let start = Instant::now();
+        let result = eval_node(tree, &graph, &mut eval_info, &self_loop_states);
+        summaries.push(FormulaSummary {
+            formula: formula.clone(),
+            num_results: result.approx_cardinality(),
+            num_colors: result.colors().approx_cardinality(),
+            num_states: result.vertices().approx_cardinality(),
+            time_ms: start.elapsed().as_millis(),
+        });
+    }
+    Ok(summaries)
+}
+
+/// Check the same formulae on every model file in a directory (files with extensions `aeon`,
+/// `bnet`, `sbml`, `ginml`, `zginml`, `json`, and `csv`), using `num_threads` threads (each model
+/// is analysed by one thread).
+///
+/// Return the summaries of results for each model, sorted by the model file names. Errors
+/// regarding individual models (invalid files, propositions, ...) are reported in the
+/// corresponding records, and do not stop the analysis of other models.
+pub fn check_models_in_directory(
+    models_dir: &str,
+    formulae: Vec<String>,
+    num_threads: usize,
+) -> Result<Vec<ModelRecord>, String> {
+    let mut model_paths = std::fs::read_dir(models_dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let extension = path.extension().and_then(|s| s.to_str());
+            path.is_file()
+                && matches!(
+                    extension,
+                    Some("aeon" | "bnet" | "sbml" | "ginml" | "zginml" | "json" | "csv")
+                )
+        })
+        .collect::<Vec<_>>();
+    model_paths.sort();
+
+    let analyse_model = |path: &Path| ModelRecord {
+        model_name: path.file_name().unwrap().to_string_lossy().to_string(),
+        results: load_network(path).and_then(|bn| summarize_formulae_on_model(&bn, &formulae)),
+    };
+
+    // models are divided between the threads in chunks
+    let num_threads = num_threads.clamp(1, max(1, model_paths.len()));
+    let chunk_size = model_paths.len().div_ceil(num_threads);
+    let records = thread::scope(|scope| {
+        let handles = model_paths
+            .chunks(max(1, chunk_size))
+            .map(|chunk| {
+                scope.spawn(|| {
+                    chunk
+                        .iter()
+                        .map(|path| analyse_model(path))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    Ok(records)
+}
+
+/// Check the same formulae on several models, using the threads given by the `options` (each
+/// model is evaluated by one thread). This is a library counterpart of
+/// [check_models_in_directory], returning the (sanitized) results themselves.
+///
+/// The formulae are parsed and pre-processed (including the search for duplicate sub-formulae)
+/// only once, and this is shared by all models. Errors regarding the syntax of the formulae stop
+/// the whole analysis, while errors regarding individual models (invalid propositions, ...) are
+/// reported for the corresponding models only. If a resource limit is reached, the remaining
+/// models are not evaluated, and the error is reported for them instead.
+///
+/// Return the results for each model (in order), with one result for each formula (in order).
+pub fn analyse_models(
+    models: Vec<BooleanNetwork>,
+    formulae: Vec<String>,
+    options: AnalysisLimits,
+) -> Result<Vec<Result<Vec<GraphColoredVertices>, String>>, String> {
+    let start = Instant::now();
+    let trees = formulae
+        .iter()
+        .map(|formula| parse_hctl_formula(formula.as_str()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // the propositions are validated for each model, but the renamed trees are the same
+    let validations = models
+        .iter()
+        .map(|bn| {
+            let plain_context = SymbolicContext::new(bn)?;
+            trees
+                .iter()
+                .map(|tree| validate_props_and_rename_vars(tree.clone(), &plain_context))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Vec<_>>();
+    let Some(modified_trees) = validations.iter().find_map(|v| v.as_ref().ok()) else {
+        // there are no valid models, only the errors are reported
+        return Ok(validations
+            .into_iter()
+            .map(|v| v.map(|_| Vec::new()))
+            .collect());
+    };
+    let max_num_hctl_vars = modified_trees
+        .iter()
+        .map(|tree| collect_unique_hctl_vars(tree.clone()).len())
+        .max()
+        .unwrap_or(0);
+    let initial_eval_info = EvalContext::from_multiple_trees(modified_trees);
+
+    let analyse_model = |bn: &BooleanNetwork| -> Result<Vec<GraphColoredVertices>, String> {
+        if let Some(message) = options.check_exceeded(start) {
+            return Err(message);
+        }
+        let graph = get_extended_symbolic_graph(bn, max_num_hctl_vars as u16)?;
+        let mut eval_info = initial_eval_info.clone();
+        let self_loop_states = compute_steady_states(&graph);
+        let results = modified_trees
+            .iter()
+            .map(|tree| {
+                let result = eval_node(tree.clone(), &graph, &mut eval_info, &self_loop_states);
+                sanitize_colored_vertices(&graph, &result)
+            })
+            .collect();
+        Ok(results)
+    };
+
+    // models are divided between the threads in chunks
+    let tasks = models.iter().zip(&validations).collect::<Vec<_>>();
+    let num_threads = options.num_threads.clamp(1, max(1, tasks.len()));
+    let chunk_size = tasks.len().div_ceil(num_threads);
+    let results = thread::scope(|scope| {
+        let handles = tasks
+            .chunks(max(1, chunk_size))
+            .map(|chunk| {
+                scope.spawn(|| {
+                    chunk
+                        .iter()
+                        .map(|(bn, validation)| match validation {
+                            Ok(_) => analyse_model(bn),
+                            Err(e) => Err(e.clone()),
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    Ok(results)
+}
+
+/// Format the model records as a CSV table with one row for each (model, formula) pair.
+/// Models that could not be analysed have a single row with the error message.
+pub fn model_records_to_csv(records: &[ModelRecord]) -> String {
+    let escape = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+    let mut csv = "model,formula,results,colors,states,time_ms,error\n".to_string();
+    for record in records {
+        match &record.results {
+            Ok(summaries) => {
+                for summary in summaries {
+                    csv.push_str(
+                        format!(
+                            "{},{},{},{},{},{},\n",
+                            escape(&record.model_name),
+                            escape(&summary.formula),
+                            summary.num_results,
+                            summary.num_colors,
+                            summary.num_states,
+                            summary.time_ms,
+                        )
+                        .as_str(),
+                    );
+                }
+            }
+            Err(e) => csv
+                .push_str(format!("{},,,,,,{}\n", escape(&record.model_name), escape(e)).as_str()),
+        }
+    }
+    csv
+}
+
+/// Format the model records as a JSON object, keyed by the model names. Each model maps either
+/// to a list of result summaries (one for each formula), or to an object with the error message.
+pub fn model_records_to_json(records: &[ModelRecord]) -> String {
+    let model_entries = records
+        .iter()
+        .map(|record| {
+            let value = match &record.results {
+                Ok(summaries) => {
+                    let summary_entries = summaries
+                        .iter()
+                        .map(|summary| {
+                            format!(
+                                "{{\"formula\": {}, \"results\": {}, \"colors\": {}, \"states\": {}, \"time_ms\": {}}}",
+                                json_string(&summary.formula),
+                                summary.num_results,
+                                summary.num_colors,
+                                summary.num_states,
+                                summary.time_ms,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    format!("[\n    {}\n  ]", summary_entries.join(",\n    "))
+                }
+                Err(e) => format!("{{\"error\": {}}}", json_string(e)),
+            };
+            format!("  {}: {value}", json_string(&record.model_name))
+        })
+        .collect::<Vec<_>>();
+    format!("{{\n{}\n}}\n", model_entries.join(",\n"))
+}
+
+/// Format the string as a JSON string literal (with quotes and escaped special characters).
+fn json_string(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if c.is_control() => escaped.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => escaped.push(c),
+        }
+    }
+    format!("\"{escaped}\"")
+}
+
+/// Format the impacts of fixing the driver variables (see [rank_driver_variables]) of the property
+/// given by `formula` as a JSON object with the formula and the list of impacts (in their order).
+pub fn driver_impacts_to_json(formula: &str, impacts: &[DriverImpact]) -> String {
+    let impact_entries = impacts
+        .iter()
+        .map(|impact| {
+            format!(
+                "{{\"variable\": {}, \"value\": {}, \"changed_colors\": {}, \"changed_states\": {}, \"total_colors\": {}}}",
+                json_string(&impact.variable),
+                u8::from(impact.value),
+                impact.changed_colors,
+                impact.changed_states,
+                impact.total_colors,
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "{{\n  \"formula\": {},\n  \"drivers\": [\n    {}\n  ]\n}}\n",
+        json_string(formula),
+        impact_entries.join(",\n    ")
+    )
+}
+
+/// Maximal arity of an uninterpreted function (explicit or implicit parameter), for which we
+/// do not warn about the size of the corresponding symbolic representation.
+const MAX_REASONABLE_ARITY: usize = 5;
+
+/// Print the basic information about the BN model: the number of variables, parameters,
+/// colors and input nodes. Further, warn about constructs that may hurt the model checking
+/// performance, such as uninterpreted functions with large arity.
+pub fn print_model_info(bn: &BooleanNetwork) -> Result<(), String> {
+    let graph = SymbolicAsyncGraph::new(bn)?;
+    let context = graph.symbolic_context();
+
+    println!("Variables: {}", bn.num_vars());
+    println!(
+        "Parameters: {} explicit, {} implicit (variables with unspecified update functions)",
+        bn.num_parameters(),
+        bn.num_implicit_parameters()
+    );
+    println!(
+        "Symbolic variables: {} for states, {} for parameters",
+        context.num_state_variables(),
+        context.num_parameter_variables()
+    );
+    println!("Colors: {:.0}", graph.unit_colors().approx_cardinality());
+    println!(
+        "States: {:.0}",
+        graph
+            .unit_colored_vertices()
+            .vertices()
+            .approx_cardinality()
+    );
+    let inputs = bn
+        .inputs(false)
+        .into_iter()
+        .map(|v| bn.get_variable_name(v).clone())
+        .collect::<Vec<_>>();
+    println!("Input nodes ({}): {}", inputs.len(), inputs.join(", "));
+
+    // uninterpreted functions of arity `k` need `2^k` symbolic variables
+    for param in bn.parameters() {
+        let param = bn.get_parameter(param);
+        if param.get_arity() as usize > MAX_REASONABLE_ARITY {
+            println!(
+                "Warning: parameter `{}` has arity {}, its symbolic representation may be large.",
+                param.get_name(),
+                param.get_arity()
+            );
+        }
+    }
+    for var in bn.implicit_parameters() {
+        let arity = bn.regulators(var).len();
+        if arity > MAX_REASONABLE_ARITY {
+            println!(
+                "Warning: variable `{}` has unspecified update function with {arity} regulators, \
+                its symbolic representation may be large.",
+                bn.get_variable_name(var)
+            );
+        }
+    }
+    if graph.unit_colors().is_empty() {
+        println!("Warning: the model has no valid colors (the regulation constraints are unsatisfiable).");
+    }
+    Ok(())
+}
+
+/// Build the symbolic graph for the model, with enough symbolic variables to evaluate all the
+/// (plain HCTL) formulae.
+fn get_graph_for_formulae(
+    bn: &BooleanNetwork,
+    formulae: &[String],
+) -> Result<SymbolicAsyncGraph, String> {
+    let plain_context = SymbolicContext::new(bn)?;
+    let mut max_num_hctl_vars = 0;
+    for formula in formulae {
+        let tree = validate_props_and_rename_vars(parse_hctl_formula(formula)?, &plain_context)?;
+        max_num_hctl_vars = max(max_num_hctl_vars, collect_unique_hctl_vars(tree).len());
+    }
+    get_extended_symbolic_graph(bn, max_num_hctl_vars as u16)
+}
+
+/// Check the formulae for vacuity (see [find_vacuity_witnesses]), and print a warning for each
+/// sub-formula that does not affect the result of its formula. The graph is modified the same way
+/// as by the analysis with the given `options` (fixed inputs and frozen variables).
+///
+/// Nothing is printed with [PrintOptions::NoPrint].
+pub fn print_vacuity_warnings(
+    bn: &BooleanNetwork,
+    formulae: &[String],
+    options: &AnalysisOptions,
+    print_opt: PrintOptions,
+) -> Result<(), String> {
+    let graph = get_graph_for_formulae(bn, formulae)?;
+    let graph = modify_graph_for_analysis(
+        graph,
+        &options.fixed_inputs,
+        &options.eval_options.frozen_variables,
+    )?;
+    for formula in formulae {
+        for witness in find_vacuity_witnesses(formula, &graph)? {
+            if print_opt != PrintOptions::NoPrint {
+                println!(
+                    "Warning: formula `{formula}` holds vacuously, sub-formula `{}` does not \
+                    affect the result (`{}` has the same result).",
+                    witness.subformula, witness.witness
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Compute which network variables and parameters influence each formula (see
+/// [compute_formula_influence]), and print them. Finally, print the variables and parameters
+/// that do not influence any of the formulae (i.e., that the formulae never exercise).
+pub fn print_formula_influence(bn: &BooleanNetwork, formulae: &[String]) -> Result<(), String> {
+    let graph = get_graph_for_formulae(bn, formulae)?;
+    let mut covered = FormulaInfluence::default();
+    for formula in formulae {
+        let influence = compute_formula_influence(formula, &graph)?;
+        println!("Influence of formula `{formula}`:");
+        println!("    variables: {}", influence.variables.join(", "));
+        println!("    parameters: {}", influence.parameters.join(", "));
+        println!(
+            "    implicit update functions: {}",
+            influence.implicit_functions.join(", ")
+        );
+        covered.variables.extend(influence.variables);
+        covered.parameters.extend(influence.parameters);
+        covered
+            .implicit_functions
+            .extend(influence.implicit_functions);
+    }
+
+    let context = graph.symbolic_context();
+    let unused_vars: Vec<String> = context
+        .network_variables()
+        .map(|var| context.get_network_variable_name(var))
+        .filter(|name| !covered.variables.contains(name))
+        .collect();
+    let unused_params: Vec<String> = context
+        .network_parameters()
+        .map(|param| context.get_network_parameter_name(param))
+        .filter(|name| !covered.parameters.contains(name))
+        .collect();
+    let unused_functions: Vec<String> = context
+        .network_implicit_parameters()
+        .into_iter()
+        .map(|var| context.get_network_variable_name(var))
+        .filter(|name| !covered.implicit_functions.contains(name))
+        .collect();
+    println!("Not influencing any formula:");
+    println!("    variables: {}", unused_vars.join(", "));
+    println!("    parameters: {}", unused_params.join(", "));
+    println!(
+        "    implicit update functions: {}",
+        unused_functions.join(", ")
+    );
+    Ok(())
+}
+
+/// Rank the variables of the model by how much fixing them changes the result of the property
+/// given by `formula` (see [rank_driver_variables]), and print the sorted impacts either as
+/// a table, or as JSON (if `as_json` is set).
+pub fn analyse_driver_variables(
+    bn: &BooleanNetwork,
+    formula: &str,
+    as_json: bool,
+) -> Result<(), String> {
+    let graph = get_graph_for_formulae(bn, &[formula.to_string()])?;
+    let impacts = rank_driver_variables(formula, &graph)?;
+    if as_json {
+        print!("{}", driver_impacts_to_json(formula, &impacts));
+    } else {
+        print_driver_table(formula, &impacts);
+    }
+    Ok(())
+}
+
+/// Explain a satisfied existential reachability `formula` (see [explain_existential_formula]),
+/// and print the witness color, the witness path, and the updates and regulations used on it.
+pub fn print_reachability_explanation(bn: &BooleanNetwork, formula: &str) -> Result<(), String> {
+    let graph = get_graph_for_formulae(bn, &[formula.to_string()])?;
+    let Some(explanation) = explain_existential_formula(formula, &graph)? else {
+        println!("Formula `{formula}` is not satisfied in any state.");
+        return Ok(());
+    };
+    println!("Explanation of formula `{formula}`:");
+    println!(
+        "    witness color: {}",
+        describe_colors(&graph, &explanation.color, 1)
+    );
+    let names: Vec<String> = graph
+        .variables()
+        .map(|var| graph.get_variable_name(var))
+        .collect();
+    println!("    witness path ({}):", names.join(", "));
+    for state in &explanation.path {
+        let values: String = state
+            .values()
+            .into_iter()
+            .map(|value| if value { '1' } else { '0' })
+            .collect();
+        println!("        {values}");
+    }
+    println!("    used updates: {}", explanation.updates.join(", "));
+    let regulations: Vec<String> = explanation
+        .regulations
+        .iter()
+        .map(|(regulator, target)| format!("{regulator} -> {target}"))
+        .collect();
+    println!("    used regulations: {}", regulations.join(", "));
+    Ok(())
+}
+
+/// Check the formulae on an explicit `trace` of the network (see [check_formula_on_trace]), and
+/// compare the trace with their symbolic results, i.e., print for how many colors all the states
+/// of the trace satisfy each formula, and which states do not satisfy it for any color.
+pub fn print_trace_report(
+    bn: &BooleanNetwork,
+    trace: &Trace,
+    formulae: &[String],
+) -> Result<(), String> {
+    let graph = get_graph_for_formulae(bn, formulae)?;
+    let num_colors = graph.mk_unit_colors().approx_cardinality();
+    for formula in formulae {
+        println!("Formula `{formula}`:");
+        // hybrid formulae are not supported on the trace, but they can be checked symbolically
+        match check_formula_on_trace(formula, bn, trace) {
+            Ok(values) => println!(
+                "    on the trace: {}",
+                if values.first() == Some(&true) {
+                    "holds"
+                } else {
+                    "does not hold"
+                }
+            ),
+            Err(e) => println!("    on the trace: not supported ({e})"),
+        }
+        let result = model_check_multiple_formulae(vec![formula.as_str()], &graph)?;
+        let colors = trace_colors_in_set(&graph, trace, &result[0])?;
+        println!(
+            "    all trace states satisfy it for {} of {num_colors} colors",
+            colors.approx_cardinality()
+        );
+        let outside = find_trace_states_outside(&graph, trace, &result[0])?;
+        if !outside.is_empty() {
+            let positions: Vec<String> = outside.iter().map(|i| (i + 1).to_string()).collect();
+            println!(
+                "    trace states not satisfying it for any color: {}",
+                positions.join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Validate the formulae without running the model checking (a "dry run").
+///
+/// Each formula is parsed (reporting all its syntax errors together), its variables are renamed,
+/// and its propositions are validated against the model. Suspicious constructs in the formula
+/// (see [lint_formula]) are reported as warnings. For each valid formula, print the numbers of
+/// HCTL variables, temporal and hybrid operators (as an estimate of its complexity). Finally,
+/// print the number of symbolic variables needed to evaluate all formulae. The transition graph
+/// itself is not built.
+///
+/// Return an error if any of the formulae is invalid (all errors are printed before that).
+pub fn validate_formulae(
+    bn: &BooleanNetwork,
+    formulae: Vec<String>,
+    use_extended: bool,
+) -> Result<(), String> {
+    let plain_context = SymbolicContext::new(bn)?;
+    let mut max_num_hctl_vars = 0;
+    let mut num_invalid = 0;
+
+    for (i, formula) in formulae.iter().enumerate() {
+        println!("Formula n.{}: {formula}", i + 1);
+        // all syntax errors of the formula are reported together
+        let tree = if use_extended {
+            parse_extended_formula_with_recovery(formula.as_str())
+        } else {
+            parse_hctl_formula_with_recovery(formula.as_str())
+        };
+        let modified_tree = match tree {
+            Ok(tree) => {
+                for warning in lint_formula(&tree) {
+                    println!("Warning: {warning}");
+                }
+                validate_props_and_rename_vars(tree, &plain_context).map_err(|e| vec![e])
+            }
+            Err(errors) => Err(errors.iter().map(|e| e.to_string()).collect()),
+        };
+        match modified_tree {
+            Ok(modified_tree) => {
+                let num_hctl_vars = collect_unique_hctl_vars(modified_tree.clone()).len();
+                max_num_hctl_vars = max(max_num_hctl_vars, num_hctl_vars);
+                let (num_temporal, num_hybrid) = count_temporal_and_hybrid_ops(&modified_tree);
+                println!("Modified version: {modified_tree}");
+                println!(
+                    "{num_hctl_vars} HCTL variables, {num_temporal} temporal operators, \
+                    {num_hybrid} hybrid operators, syntax tree of height {}",
+                    modified_tree.height
+                );
+            }
+            Err(errors) => {
+                for e in errors {
+                    println!("Invalid formula: {e}");
+                }
+                num_invalid += 1;
+            }
+        }
+        println!("-----");
+    }
+
+    if num_invalid > 0 {
+        return Err(format!(
+            "{num_invalid} out of {} formulae are invalid.",
+            formulae.len()
+        ));
+    }
+
+    let num_extra_vars = max_num_hctl_vars * bn.num_vars();
+    println!(
+        "All {} formulae are valid. Evaluation needs {max_num_hctl_vars} HCTL variables, \
+        i.e., {num_extra_vars} extra symbolic variables ({} symbolic variables in total).",
+        formulae.len(),
+        plain_context.bdd_variable_set().num_vars() as usize + num_extra_vars,
+    );
+    Ok(())
+}
+
+/// Count the temporal and hybrid operators in the formula tree.
+fn count_temporal_and_hybrid_ops(formula_tree: &HctlTreeNode) -> (usize, usize) {
+    match &formula_tree.node_type {
+        NodeType::Terminal(_) => (0, 0),
+        NodeType::Unary(op, child) => {
+            let (temporal, hybrid) = count_temporal_and_hybrid_ops(child);
+            if *op == UnaryOp::Not {
+                (temporal, hybrid)
+            } else {
+                (temporal + 1, hybrid)
+            }
+        }
+        NodeType::Binary(op, left, right) => {
+            let (temporal_l, hybrid_l) = count_temporal_and_hybrid_ops(left);
+            let (temporal_r, hybrid_r) = count_temporal_and_hybrid_ops(right);
+            let is_temporal = matches!(
+                op,
+                BinaryOp::EU | BinaryOp::AU | BinaryOp::EW | BinaryOp::AW
+            );
+            (
+                temporal_l + temporal_r + usize::from(is_temporal),
+                hybrid_l + hybrid_r,
+            )
+        }
+        NodeType::Hybrid(_, _, _, child) => {
+            let (temporal, hybrid) = count_temporal_and_hybrid_ops(child);
+            (temporal, hybrid + 1)
+        }
+    }
+}
+
+/// Find a witness for the given formulae, i.e., a fully specified instance of the (partially
+/// specified) BN, for which each of the formulae is satisfied in at least one state.
+///
+/// Return `None` if there is no such instance of the network.
+pub fn find_witness_network(
+    bn: &BooleanNetwork,
+    formulae: Vec<String>,
+) -> Result<Option<BooleanNetwork>, String> {
+    // parse all the formulae and count max number of HCTL variables
+    let plain_context = SymbolicContext::new(bn)?;
+    let mut parsed_trees = Vec::new();
+    let mut max_num_hctl_vars = 0;
+    for formula in formulae {
+        let tree = parse_hctl_formula(formula.as_str())?;
+        let modified_tree = validate_props_and_rename_vars(tree, &plain_context)?;
+        let num_hctl_vars = collect_unique_hctl_vars(modified_tree.clone()).len();
+        max_num_hctl_vars = max(max_num_hctl_vars, num_hctl_vars);
+        parsed_trees.push(modified_tree);
+    }
+
+    let graph = get_extended_symbolic_graph(bn, max_num_hctl_vars as u16)?;
+    let results = model_check_multiple_trees(parsed_trees, &graph)?;
+
+    // sanitized results are valid in the canonical graph, so we can intersect their colors there
+    let canonical_graph = SymbolicAsyncGraph::new(bn)?;
+    let mut valid_colors = canonical_graph.mk_unit_colors();
+    for result in results {
+        valid_colors = valid_colors.intersect(&result.colors());
+    }
+
+    if valid_colors.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(canonical_graph.pick_witness(&valid_colors)))
+    }
+}
+
+/// Model-check the formulae on the model, and return the sanitized results (valid in the
+/// canonical graph of the model).
+fn model_check_formulae_on_network(
+    bn: &BooleanNetwork,
+    formulae: &[String],
+) -> Result<Vec<GraphColoredVertices>, String> {
+    // parse all the formulae and count max number of HCTL variables
+    let plain_context = SymbolicContext::new(bn)?;
+    let mut parsed_trees = Vec::new();
+    let mut max_num_hctl_vars = 0;
+    for formula in formulae {
+        let tree = parse_hctl_formula(formula.as_str())?;
+        let modified_tree = validate_props_and_rename_vars(tree, &plain_context)?;
+        let num_hctl_vars = collect_unique_hctl_vars(modified_tree.clone()).len();
+        max_num_hctl_vars = max(max_num_hctl_vars, num_hctl_vars);
+        parsed_trees.push(modified_tree);
+    }
+
+    let graph = get_extended_symbolic_graph(bn, max_num_hctl_vars as u16)?;
+    model_check_multiple_trees(parsed_trees, &graph)
+}
+
+/// Model-check the formulae, and export the model into SBML-qual with the species annotated by
+/// their values in the satisfying states of each formula (see [build_annotated_sbml]).
+pub fn export_annotated_sbml(bn: &BooleanNetwork, formulae: Vec<String>) -> Result<String, String> {
+    let results = model_check_formulae_on_network(bn, &formulae)?;
+    let results = formulae.into_iter().zip(results).collect::<Vec<_>>();
+    build_annotated_sbml(bn, &results)
+}
+
+/// Status of a property on a model, regarding the colors for which it holds in some state.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PropertyStatus {
+    AllColors,
+    SomeColors,
+    NoColors,
+}
+
+/// Comparison of a single property on two revisions of a model, see [compare_model_revisions].
+#[derive(Clone, Debug)]
+pub struct PropertyRegression {
+    /// The compared formula.
+    pub formula: String,
+    /// Status of the property on the old model.
+    pub old_status: PropertyStatus,
+    /// Status of the property on the new model.
+    pub new_status: PropertyStatus,
+    /// Colors for which the property holds in some state of the old model, but in no state of
+    /// the new model. Only available if the colors of both models are aligned.
+    pub lost_colors: Option<GraphColors>,
+    /// Colors for which the property holds in some state of the new model, but in no state of
+    /// the old model. Only available if the colors of both models are aligned.
+    pub gained_colors: Option<GraphColors>,
+}
+
+impl PropertyRegression {
+    /// Check whether the property changed its status between the revisions (for some aligned
+    /// color, or overall if the colors can not be aligned).
+    pub fn is_changed(&self) -> bool {
+        match (&self.lost_colors, &self.gained_colors) {
+            (Some(lost), Some(gained)) => !lost.is_empty() || !gained.is_empty(),
+            _ => self.old_status != self.new_status,
+        }
+    }
+}
+
+/// Compute the status of the property given by its (sanitized) result.
+fn property_status(result: &GraphColoredVertices, graph: &SymbolicAsyncGraph) -> PropertyStatus {
+    let colors = result.colors();
+    if colors.is_empty() {
+        PropertyStatus::NoColors
+    } else if colors == graph.mk_unit_colors() {
+        PropertyStatus::AllColors
+    } else {
+        PropertyStatus::SomeColors
+    }
+}
+
+/// Check whether the colors of two models can be aligned, i.e., the models have the same
+/// explicit parameters, and the same variables with unspecified update functions (with the same
+/// regulators).
+fn colors_are_aligned(old_bn: &BooleanNetwork, new_bn: &BooleanNetwork) -> bool {
+    let explicit_parameters = |bn: &BooleanNetwork| {
+        let mut parameters = bn
+            .parameters()
+            .map(|p| (bn[p].get_name().clone(), bn[p].get_arity()))
+            .collect::<Vec<_>>();
+        parameters.sort();
+        parameters
+    };
+    let implicit_parameters = |bn: &BooleanNetwork| {
+        let mut parameters = bn
+            .implicit_parameters()
+            .into_iter()
+            .map(|v| {
+                let regulators = bn
+                    .regulators(v)
+                    .into_iter()
+                    .map(|r| bn.get_variable_name(r).clone())
+                    .collect::<Vec<_>>();
+                (bn.get_variable_name(v).clone(), regulators)
+            })
+            .collect::<Vec<_>>();
+        parameters.sort();
+        parameters
+    };
+    explicit_parameters(old_bn) == explicit_parameters(new_bn)
+        && implicit_parameters(old_bn) == implicit_parameters(new_bn)
+}
+
+/// Check the properties on two revisions of a model, and report which properties changed their
+/// status (regarding the colors for which they hold in some state).
+///
+/// If both models have the same parameters (explicit parameters, and unspecified update
+/// functions with the same regulators), their colors are aligned, and the report also contains
+/// the colors (of the new model) for which the status of each property changed. Otherwise, only
+/// the overall status of the properties is compared.
+pub fn compare_model_revisions(
+    old_bn: &BooleanNetwork,
+    new_bn: &BooleanNetwork,
+    formulae: Vec<String>,
+) -> Result<Vec<PropertyRegression>, String> {
+    let old_results = model_check_formulae_on_network(old_bn, &formulae)?;
+    let new_results = model_check_formulae_on_network(new_bn, &formulae)?;
+    let old_graph = SymbolicAsyncGraph::new(old_bn)?;
+    let new_graph = SymbolicAsyncGraph::new(new_bn)?;
+    let aligned = colors_are_aligned(old_bn, new_bn);
+
+    let mut regressions = Vec::new();
+    for ((formula, old_result), new_result) in
+        formulae.into_iter().zip(old_results).zip(new_results)
+    {
+        // colors of the old model are transferred into the context of the new model
+        let old_colors = if aligned {
+            new_graph
+                .symbolic_context()
+                .transfer_from(old_result.colors().as_bdd(), old_graph.symbolic_context())
+                .map(|bdd| GraphColors::new(bdd, new_graph.symbolic_context()))
+        } else {
+            None
+        };
+        let new_colors = new_result.colors();
+        regressions.push(PropertyRegression {
+            formula,
+            old_status: property_status(&old_result, &old_graph),
+            new_status: property_status(&new_result, &new_graph),
+            lost_colors: old_colors.as_ref().map(|old| old.minus(&new_colors)),
+            gained_colors: old_colors.as_ref().map(|old| new_colors.minus(old)),
+        });
+    }
+    Ok(regressions)
+}
+
+/// Check whether the formulae `formula1` and `formula2` are equivalent on the `graph`, and
+/// return the colored states where they differ (i.e., an empty set if they are equivalent).
+///
+/// Both formulae are evaluated together, so that their common sub-formulae are computed once.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn check_equivalence(
+    formula1: &str,
+    formula2: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<GraphColoredVertices, String> {
+    let results = model_check_multiple_formulae(vec![formula1, formula2], graph)?;
+    Ok(results[0]
+        .minus(&results[1])
+        .union(&results[1].minus(&results[0])))
+}
+
+/// Check whether the formula `formula1` implies `formula2` on the `graph`, and return the
+/// colored states where the implication fails (i.e., where `formula1` holds but `formula2`
+/// does not).
+///
+/// Both formulae are evaluated together, so that their common sub-formulae are computed once.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn check_implication(
+    formula1: &str,
+    formula2: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<GraphColoredVertices, String> {
+    let results = model_check_multiple_formulae(vec![formula1, formula2], graph)?;
+    Ok(results[0].minus(&results[1]))
+}
+
+/// Quantification of the paths in [reachability].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReachabilityMode {
+    /// Some path reaches the target set (i.e., `EF`).
+    Exists,
+    /// All paths reach the target set (i.e., `AF`).
+    Forall,
+}
+
+/// Summarized result of [reachability].
+#[derive(Clone, Debug)]
+pub struct ReachabilityResult {
+    /// States of the source set (with their colors) from which the target set is reached.
+    pub reaching_states: GraphColoredVertices,
+    /// Colors for which the target set is reached from all states of the source set (and the
+    /// source set is not empty).
+    pub colors: GraphColors,
+}
+
+/// Check the reachability of the set `to` from the set `from` on the `graph`, with the paths
+/// quantified by the given `mode`. This is a shortcut for evaluating extended formulae
+/// `%from% & EF %to%` (or `AF`) with two wild-cards.
+///
+/// The sets can be in the canonical context of the graph's network, or in the context of the
+/// `graph`. The resulting sets are sanitized (in the canonical context).
+pub fn reachability(
+    from: &GraphColoredVertices,
+    to: &GraphColoredVertices,
+    graph: &SymbolicAsyncGraph,
+    mode: ReachabilityMode,
+) -> Result<ReachabilityResult, String> {
+    let unit = graph.unit_colored_vertices();
+    let from = extend_colored_vertices(graph, from)
+        .ok_or("Source set is not compatible with the graph.".to_string())?
+        .intersect(unit);
+    let to = extend_colored_vertices(graph, to)
+        .ok_or("Target set is not compatible with the graph.".to_string())?
+        .intersect(unit);
+
+    let reaching = match mode {
+        ReachabilityMode::Exists => eval_ef_saturated(graph, &to),
+        ReachabilityMode::Forall => eval_af(graph, &to, &compute_steady_states(graph)),
+    };
+    let reaching_states = from.intersect(&reaching);
+    let colors = from.colors().minus(&from.minus(&reaching_states).colors());
+    Ok(ReachabilityResult {
+        reaching_states: sanitize_colored_vertices(graph, &reaching_states),
+        colors: sanitize_colors(graph, &colors),
+    })
+}
+
+/// Compute the weak and strong basins of the states satisfying the `formula` (such as an
+/// attractor pattern `!{x}: AG EF {x}`), and print the sizes of the sets. If `result_zip` is
+/// given, the sets are written into the result bundle labelled `target`, `target_weak_basin`,
+/// and `target_strong_basin`, so that they can be used as wild-card contexts later.
+pub fn analyse_basins(
+    bn: &BooleanNetwork,
+    formula: &str,
+    result_zip: Option<String>,
+) -> Result<(), String> {
+    let formulae = vec![formula.to_string()];
+    let target = model_check_formulae_on_network(bn, &formulae)?.remove(0);
+    // the results are sanitized, so the basins are computed in the canonical graph
+    let graph = SymbolicAsyncGraph::new(bn)?;
+    let mut targets = LabelToSetMap::new();
+    targets.insert("target".to_string(), target);
+    let mut results = basins_context(&graph, &targets);
+    results.extend(targets);
+
+    let mut labels: Vec<&String> = results.keys().collect();
+    labels.sort();
+    for label in labels {
+        let set = &results[label];
+        println!(
+            "{label}: {:.0} states in total, {:.0} unique states, {:.0} colors",
+            set.approx_cardinality(),
+            set.vertices().approx_cardinality(),
+            set.colors().approx_cardinality()
+        );
+    }
+
+    if let Some(zip_path) = result_zip {
+        println!("Writing the results to {zip_path}.");
+        build_result_archive(
+            results,
+            zip_path.as_str(),
+            bn.to_string().as_str(),
+            formulae,
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Impact of knocking out a single regulation on a property, see [regulation_knockout_screen].
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegulationImpact {
+    /// Name of the regulator of the knocked-out regulation.
+    pub regulator: String,
+    /// Name of the target of the knocked-out regulation.
+    pub target: String,
+    /// Number of colors of the perturbed model for which the property holds in some state.
+    pub satisfying_colors: f64,
+    /// Number of all colors of the perturbed model.
+    pub total_colors: f64,
+}
+
+impl RegulationImpact {
+    /// Fraction of the colors for which the property holds (zero if there are no colors).
+    pub fn satisfying_fraction(&self) -> f64 {
+        if self.total_colors == 0.0 {
+            0.0
+        } else {
+            self.satisfying_colors / self.total_colors
+        }
+    }
+}
+
+/// Compute the numbers of satisfying and all colors for the (validated) formula tree.
+fn count_satisfying_colors(
+    bn: &BooleanNetwork,
+    tree: &HctlTreeNode,
+    num_hctl_vars: usize,
+) -> Result<(f64, f64), String> {
+    let graph = get_extended_symbolic_graph(bn, num_hctl_vars as u16)?;
+    let result = model_check_multiple_trees(vec![tree.clone()], &graph)?.remove(0);
+    Ok((
+        result.colors().approx_cardinality(),
+        graph.mk_unit_colors().approx_cardinality(),
+    ))
+}
+
+/// Perform an "edge knockout screen" of the property given by `formula`. Each regulation of the
+/// model is knocked out in turn (see [knock_out_regulation], with the regulator replaced by the
+/// constant `value`), and the property is re-checked on the perturbed model.
+///
+/// Return the original impact (with empty regulator and target names), and the impacts of all
+/// knockouts ranked by the fraction of satisfying colors (the regulations whose knockout
+/// shrinks the satisfying colors the most come first). The perturbed models have the same
+/// variables, so the formula is parsed and validated only once.
+pub fn regulation_knockout_screen(
+    bn: &BooleanNetwork,
+    formula: &str,
+    value: bool,
+) -> Result<(RegulationImpact, Vec<RegulationImpact>), String> {
+    let plain_context = SymbolicContext::new(bn)?;
+    let tree = validate_props_and_rename_vars(parse_hctl_formula(formula)?, &plain_context)?;
+    let num_hctl_vars = collect_unique_hctl_vars(tree.clone()).len();
+
+    let (satisfying_colors, total_colors) = count_satisfying_colors(bn, &tree, num_hctl_vars)?;
+    let original = RegulationImpact {
+        regulator: String::new(),
+        target: String::new(),
+        satisfying_colors,
+        total_colors,
+    };
+
+    let mut impacts = Vec::new();
+    for regulation in bn.as_graph().regulations() {
+        let (regulator, target) = (regulation.get_regulator(), regulation.get_target());
+        let perturbed = knock_out_regulation(bn, regulator, target, value)?;
+        let (satisfying_colors, total_colors) =
+            count_satisfying_colors(&perturbed, &tree, num_hctl_vars)?;
+        impacts.push(RegulationImpact {
+            regulator: bn.get_variable_name(regulator).clone(),
+            target: bn.get_variable_name(target).clone(),
+            satisfying_colors,
+            total_colors,
+        });
+    }
+    impacts.sort_by(|a, b| a.satisfying_fraction().total_cmp(&b.satisfying_fraction()));
+    Ok((original, impacts))
+}
+
+/// Difference between the corresponding results of two result bundles (see
+/// [diff_result_archives]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResultDifference {
+    /// Name of the results in the bundles (i.e., `formula-{i}`).
+    pub label: String,
+    /// Formula of the results (or both formulae, if they differ between the bundles).
+    pub formula: String,
+    /// Results present only in the first bundle and only in the second bundle (in the symbolic
+    /// context of the first model), or `None` if the result is only present in one of the bundles.
+    pub sets: Option<(GraphColoredVertices, GraphColoredVertices)>,
+}
+
+/// Compute the differences between the corresponding results of two result bundles (see
+/// [build_result_archive]), sorted by the names of the results.
+///
+/// Both bundles must contain results for models with the same variables and parameters.
+/// The results are matched by their names in the archive (i.e., `formula-{i}`).
+pub fn diff_result_archives(
+    first_archive: &str,
+    second_archive: &str,
+) -> Result<Vec<ResultDifference>, String> {
+    let (first_bn, first_formulae, first_results) = load_result_archive(first_archive)?;
+    let (second_bn, second_formulae, second_results) = load_result_archive(second_archive)?;
+    let first_context = SymbolicContext::new(&first_bn)?;
+    let second_context = SymbolicContext::new(&second_bn)?;
+
+    let mut labels = first_results
+        .keys()
+        .chain(second_results.keys())
+        .cloned()
+        .collect::<Vec<_>>();
+    labels.sort();
+    labels.dedup();
+
+    let mut differences = Vec::new();
+    for label in labels {
+        // result names have form `formula-{i}`, so we can try to find the formula itself
+        let formula_idx = label
+            .strip_prefix("formula-")
+            .and_then(|idx| idx.parse::<usize>().ok());
+        let first_formula = formula_idx.and_then(|i| first_formulae.get(i));
+        let second_formula = formula_idx.and_then(|i| second_formulae.get(i));
+        let formula = match (first_formula, second_formula) {
+            (Some(f1), Some(f2)) if f1 == f2 => f1.clone(),
+            (Some(f1), Some(f2)) => format!("{f1} (first) vs. {f2} (second)"),
+            (Some(f), None) | (None, Some(f)) => f.clone(),
+            _ => label.clone(),
+        };
+
+        let (Some(first_set), Some(second_set)) =
+            (first_results.get(&label), second_results.get(&label))
+        else {
+            differences.push(ResultDifference {
+                label,
+                formula,
+                sets: None,
+            });
+            continue;
+        };
+
+        // results of the second bundle are transferred into the context of the first one
+        let second_bdd = first_context
+            .transfer_from(second_set.as_bdd(), &second_context)
+            .ok_or(
+                "Result bundles are not compatible (models differ in variables or parameters).",
+            )?;
+        let second_set = GraphColoredVertices::new(second_bdd, &first_context);
+        differences.push(ResultDifference {
+            label,
+            formula,
+            sets: Some((first_set.minus(&second_set), second_set.minus(first_set))),
+        });
+    }
+    Ok(differences)
+}
+
+/// Compare two result bundles (see [build_result_archive]) and print the differences between
+/// the corresponding results (see [diff_result_archives]).
+pub fn compare_result_archives(first_archive: &str, second_archive: &str) -> Result<(), String> {
+    for difference in diff_result_archives(first_archive, second_archive)? {
+        match difference.sets {
+            Some((only_first, only_second)) => {
+                summarize_difference(difference.formula, &only_first, &only_second)
+            }
+            None => {
+                println!(
+                    "Result `{}` is only present in one of the bundles.",
+                    difference.label
+                );
+                println!("-----");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Combine the results of one or more result archives by the set `expression` (see
+/// [crate::postprocessing::set_expressions]), print the summary of the combined set, and write it
+/// to the `result_zip` archive (if given).
+///
+/// The results are referenced by their names in the archive (e.g., `formula-0`). With several
+/// archives, the names are prefixed by the (1-based) index of the archive, e.g., `2.formula-0`.
+/// All archives must contain results for the same model.
+pub fn combine_result_archives(
+    expression: &str,
+    archive_paths: &[String],
+    result_zip: Option<String>,
+) -> Result<(), String> {
+    let tree = parse_set_expression(expression)?;
+    let (first_path, other_paths) = archive_paths
+        .split_first()
+        .ok_or("At least one result archive must be given.".to_string())?;
+    let (bn, _, first_results) = load_result_archive(first_path)?;
+    let context = SymbolicContext::new(&bn)?;
+
+    let mut results = LabelToSetMap::new();
+    if other_paths.is_empty() {
+        results = first_results;
+    } else {
+        for (label, set) in first_results {
+            results.insert(format!("1.{label}"), set);
+        }
+        for (i, path) in other_paths.iter().enumerate() {
+            let (other_bn, _, other_results) = load_result_archive(path)?;
+            let other_context = SymbolicContext::new(&other_bn)?;
+            for (label, set) in other_results {
+                let bdd = context
+                    .transfer_from(set.as_bdd(), &other_context)
+                    .ok_or(format!(
+                        "Results in {path} are not compatible with the model in {first_path}."
+                    ))?;
+                let set = GraphColoredVertices::new(bdd, &context);
+                results.insert(format!("{}.{label}", i + 2), set);
+            }
+        }
+    }
+
+    let start_time = SystemTime::now();
+    let combined = evaluate_set_tree(&tree, &results)?;
+    summarize_results(tree.to_string(), &combined, Some(start_time));
+
+    if let Some(zip_path) = result_zip {
+        println!("Writing the combined result to {zip_path}.");
+        let mut output = LabelToSetMap::new();
+        output.insert("formula-0".to_string(), combined);
+        build_result_archive(
+            output,
+            zip_path.as_str(),
+            bn.to_string().as_str(),
+            vec![expression.to_string()],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Export the results of a result archive (see [build_result_archive]) into separate files in
+/// the `output_dir`, in each of the selected `formats` (see [export_results]).
+///
+/// The results are exported in the canonical symbolic context of the archived model, so the BDD
+/// variables are the network variables and the parameters.
+pub fn export_result_archive(
+    archive_path: &str,
+    output_dir: &str,
+    formats: &[ResultExportFormat],
+) -> Result<(), String> {
+    let (bn, _, results) = load_result_archive(archive_path)?;
+    let context = SymbolicContext::new(&bn)?;
+    export_results(&results, context.bdd_variable_set(), output_dir, formats)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_model_checking::_test_util::unique_temp_path;
+    use crate::analysis::{
+        analyse_driver_variables, analyse_formula, analyse_formulae, analyse_formulae_streamed,
+        analyse_formulae_with_options, analyse_models, analyse_pipeline, benchmark_formulae,
+        check_equivalence, check_implication, check_models_in_directory, combine_result_archives,
+        compare_model_revisions, compare_result_archives, compute_sharing_statistics,
+        diff_result_archives, driver_impacts_to_json, export_annotated_sbml, export_result_archive,
+        find_witness_network, load_known_results, model_records_to_csv, model_records_to_json,
+        print_model_info, print_reachability_explanation, print_vacuity_warnings, reachability,
+        regulation_knockout_screen, result_cache_key, validate_formulae, AnalysisLimits,
+        AnalysisOptions, PropertyStatus, ReachabilityMode, SharingStatistics,
+    };
+    use crate::evaluation::algorithm::compute_steady_states;
+    use crate::evaluation::EvalOptions;
+    use crate::generate_output::{cached_result_path, write_cached_result, ResultExportFormat};
+    use crate::interventions::rank_driver_variables;
+    use crate::load_inputs::{
+        load_analysis_metadata, load_cached_result, load_intermediate_results, load_result_archive,
+    };
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::{model_check_formula, model_check_multiple_formulae};
+    use crate::preprocessing::parser::parse_hctl_formula;
+    use crate::preprocessing::utils::validate_props_and_rename_vars;
+    use crate::result_print::PrintOptions;
+    use biodivine_lib_bdd::Bdd;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::{SymbolicAsyncGraph, SymbolicContext};
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[test]
+    /// Simple test to check whether the whole analysis runs without an error.
+    fn test_analysis_run() {
+        let model = r"
+                $frs2:(!erk & fgfr)
+                fgfr -> frs2
+                erk -| frs2
+                $fgfr:(fgfr & !frs2)
+                frs2 -| fgfr
+                fgfr -> fgfr
+                $erk:(frs2 | shc)
+                frs2 -> erk
+                shc -> erk
+                $shc:fgfr
+                fgfr -> shc
+        ";
+        let bn = BooleanNetwork::try_from(model).unwrap();
+
+        // try both versions with exhaustive results and without them (they execute different code)
+
+        let formulae = vec!["!{x}: AG EF {x}".to_string(), "!{x}: AF {x}".to_string()];
+        assert!(analyse_formulae(&bn, formulae, PrintOptions::WithProgress, None, None).is_ok());
+
+        let formula = "erk & fgfr & ~shc".to_string(); // simple to avoid long prints
+        assert!(analyse_formula(&bn, formula, PrintOptions::Exhaustive, None, None).is_ok());
+    }
+
+    #[test]
+    /// Test finding witness networks for a partially specified model.
+    fn test_witness_network() {
+        let model = r"
+            a -?? b
+            b -> a
+            $a: b
+        ";
+        let bn = BooleanNetwork::try_from(model).unwrap();
+
+        // some instances of `b` have a fixed-point where `a` is false
+        let formulae = vec!["3{x}: @{x}: (AX {x}) & ~a".to_string()];
+        let witness = find_witness_network(&bn, formulae).unwrap().unwrap();
+        assert_eq!(witness.num_parameters(), 0);
+        assert!(witness
+            .variables()
+            .all(|v| witness.get_update_function(v).is_some()));
+
+        // but no instance has a fixed-point where `a` differs from `b`
+        let formulae = vec!["3{x}: @{x}: (AX {x}) & (a ^ b)".to_string()];
+        assert!(find_witness_network(&bn, formulae).unwrap().is_none());
+    }
+
+    #[test]
+    /// Test that the vacuity check uses the graph modified by the analysis options.
+    fn test_vacuity_warnings_with_options() {
+        let bn = BooleanNetwork::try_from("a -> b\n$b: a").unwrap();
+        let formulae = vec!["AG (a => AF b)".to_string()];
+        let mut options = AnalysisOptions::default();
+        assert!(print_vacuity_warnings(&bn, &formulae, &options, PrintOptions::NoPrint).is_ok());
+        options.fixed_inputs = HashMap::from([("a".to_string(), false)]);
+        assert!(print_vacuity_warnings(&bn, &formulae, &options, PrintOptions::NoPrint).is_ok());
+        // only inputs can be fixed, the same as in the analysis
+        options.fixed_inputs = HashMap::from([("b".to_string(), false)]);
+        assert!(print_vacuity_warnings(&bn, &formulae, &options, PrintOptions::NoPrint).is_err());
+    }
+
+    #[test]
+    /// Test exporting the model into SBML with annotations regarding the results.
+    fn test_annotated_sbml_export() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -> a\n$a: b\n$b: a").unwrap();
+        let formulae = vec![
+            "(!{x}: AX {x}) & a".to_string(),
+            "!{x}: AX {x}".to_string(),
+            "a & ~a".to_string(),
+        ];
+        let sbml = export_annotated_sbml(&bn, formulae).unwrap();
+        let expected_annotation =
+            "<hctl:property hctl:formula=\"(!{x}: AX {x}) &amp; a\" hctl:value=\"1\"/>\
+            <hctl:property hctl:formula=\"!{x}: AX {x}\" hctl:value=\"*\"/>\
+            <hctl:property hctl:formula=\"a &amp; ~a\" hctl:value=\"none\"/>";
+        assert_eq!(sbml.matches(expected_annotation).count(), 2);
+        // each species contains exactly one (well-formed) annotation
+        let document = roxmltree::Document::parse(sbml.as_str()).unwrap();
+        let annotated_species = document
+            .descendants()
+            .filter(|node| node.tag_name().name() == "qualitativeSpecies")
+            .filter(|node| node.children().any(|c| c.tag_name().name() == "annotation"))
+            .count();
+        assert_eq!(annotated_species, 2);
+
+        // the exported model can still be loaded
+        let (exported_bn, _) = BooleanNetwork::try_from_sbml(sbml.as_str()).unwrap();
+        assert_eq!(exported_bn.num_vars(), 2);
+        assert!(export_annotated_sbml(&bn, vec!["EF c".to_string()]).is_err());
+    }
+
+    #[test]
+    /// Test comparing properties on two revisions of a model, both with aligned colors and
+    /// without them.
+    fn test_model_revisions() {
+        let old_bn = BooleanNetwork::try_from("a -?? b\nb -? a\n$a: b").unwrap();
+        let new_bn = BooleanNetwork::try_from("a -?? b\nb -? a\n$a: !b").unwrap();
+        let formulae = vec!["!{x}: AX {x}".to_string(), "EF a".to_string()];
+        let regressions = compare_model_revisions(&old_bn, &new_bn, formulae.clone()).unwrap();
+
+        // fixed-points are lost for identity `f_b`, and gained for negation
+        assert!(regressions[0].is_changed());
+        assert_eq!(regressions[0].old_status, PropertyStatus::SomeColors);
+        let lost = regressions[0].lost_colors.as_ref().unwrap();
+        let gained = regressions[0].gained_colors.as_ref().unwrap();
+        assert_eq!(lost.approx_cardinality(), 1.0);
+        assert_eq!(gained.approx_cardinality(), 1.0);
+        assert!(!regressions[1].is_changed());
+        assert_eq!(regressions[1].new_status, PropertyStatus::AllColors);
+
+        // here, `b` has a different regulator, so the colors can not be aligned
+        let new_bn =
+            BooleanNetwork::try_from("a -? a\na -?? b\nc -? b\nc -? c\n$a: a\n$c: c").unwrap();
+        let regressions = compare_model_revisions(&old_bn, &new_bn, formulae).unwrap();
+        assert!(regressions[0].lost_colors.is_none());
+        // new model has fixed-points for all colors
+        assert_eq!(regressions[0].new_status, PropertyStatus::AllColors);
+        assert!(regressions[0].is_changed());
+    }
+
+    #[test]
+    /// Test loading the results of a previous run, and using them to warm-start the analysis.
+    fn test_warm_start() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a").unwrap();
+        let formulae = vec!["!{x}: AG EF {x}".to_string(), "a & b".to_string()];
+        let path = unique_temp_path("warm-start.zip");
+        let path = path.to_str().unwrap();
+        analyse_formulae(
+            &bn,
+            formulae.clone(),
+            PrintOptions::NoPrint,
+            Some(path.to_string()),
+            None,
+        )
+        .unwrap();
+
+        let known = load_known_results(path, &bn, &HashMap::new(), &[]).unwrap();
+        assert_eq!(known.len(), 2);
+        assert_eq!(known[0].0, formulae[0]);
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        assert_eq!(
+            known[0].1,
+            model_check_formula(&formulae[0], &graph).unwrap()
+        );
+
+        let new_formulae = vec!["EF (!{y}: AG EF {y}) & ~(a & b)".to_string()];
+        let options = AnalysisOptions {
+            warm_start_archive: Some(path.to_string()),
+            ..AnalysisOptions::default()
+        };
+        analyse_formulae_with_options(&bn, new_formulae, PrintOptions::NoPrint, &options).unwrap();
+
+        // results for a different model are rejected
+        let other_bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> c").unwrap();
+        assert!(load_known_results(path, &other_bn, &HashMap::new(), &[]).is_err());
+
+        // results computed without fixed inputs or frozen variables are rejected
+        let options = AnalysisOptions {
+            warm_start_archive: Some(path.to_string()),
+            fixed_inputs: HashMap::from([("a".to_string(), true)]),
+            ..AnalysisOptions::default()
+        };
+        let formulae = vec!["a & b".to_string()];
+        assert!(
+            analyse_formulae_with_options(&bn, formulae, PrintOptions::NoPrint, &options).is_err()
+        );
+        assert!(load_known_results(path, &bn, &HashMap::new(), &["b".to_string()]).is_err());
+    }
+
+    #[test]
+    /// Test that warm start uses the result names and fixed inputs recorded in the archive.
+    fn test_warm_start_metadata() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| b").unwrap();
+        let formulae = vec!["a & b".to_string(), "EF b".to_string()];
+        let fixed_inputs = HashMap::from([("a".to_string(), true)]);
+        let path = unique_temp_path("warm-start-metadata.zip");
+        let path = path.to_str().unwrap();
+        let options = AnalysisOptions {
+            result_zip: Some(path.to_string()),
+            fixed_inputs: fixed_inputs.clone(),
+            ..AnalysisOptions::default()
+        };
+        analyse_formulae_with_options(&bn, formulae.clone(), PrintOptions::NoPrint, &options)
+            .unwrap();
+
+        let metadata = load_analysis_metadata(path).unwrap().unwrap();
+        assert_eq!(metadata.result_names, vec!["formula-0", "formula-1"]);
+        assert_eq!(metadata.fixed_inputs.get("a"), Some(&true));
+
+        let known = load_known_results(path, &bn, &fixed_inputs, &[]).unwrap();
+        assert_eq!(known.len(), 2);
+        assert_eq!(known[1].0, formulae[1]);
+        assert!(load_known_results(path, &bn, &HashMap::new(), &[]).is_err());
+    }
+
+    #[test]
+    /// Test running the analysis with pre-computed steady states.
+    fn test_analysis_with_steady_states() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -? a\nb -> b").unwrap();
+        let steady_states = compute_steady_states(&SymbolicAsyncGraph::new(&bn).unwrap());
+        let formulae = vec![
+            "!{x}: AX {x}".to_string(),
+            "!{x}: 3{y}: (@{y}: ~{x} & AX {y}) & EF {y}".to_string(),
+        ];
+        let path = unique_temp_path("steady-states.zip");
+        let path = path.to_str().unwrap();
+        let options = AnalysisOptions {
+            result_zip: Some(path.to_string()),
+            steady_states: Some(steady_states),
+            ..AnalysisOptions::default()
+        };
+        analyse_formulae_with_options(&bn, formulae.clone(), PrintOptions::NoPrint, &options)
+            .unwrap();
+
+        // the results are the same as with the steady states computed internally
+        let (_, _, results) = load_result_archive(path).unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let expected = model_check_multiple_formulae(vec![&formulae[0], &formulae[1]], &graph);
+        for (i, expected) in expected.unwrap().iter().enumerate() {
+            let result = results.get(&format!("formula-{i}")).unwrap();
+            assert_eq!(result.as_bdd(), expected.as_bdd());
+        }
+        std::fs::remove_file(path).unwrap();
+
+        // steady states must be compatible with the model
+        let other_bn = BooleanNetwork::try_from("a -> a").unwrap();
+        let other_states = compute_steady_states(&SymbolicAsyncGraph::new(&other_bn).unwrap());
+        let options = AnalysisOptions {
+            steady_states: Some(other_states),
+            ..AnalysisOptions::default()
+        };
+        let result = analyse_formulae_with_options(&bn, formulae, PrintOptions::NoPrint, &options);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    /// Test writing result bundles, loading them back, and comparing them.
+    fn test_result_archive_diff() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a").unwrap();
+        let formulae = vec!["!{x}: AG EF {x}".to_string(), "a & b".to_string()];
+        let first_path = unique_temp_path("diff-1.zip");
+        let second_path = unique_temp_path("diff-2.zip");
+        let first_path = first_path.to_str().unwrap();
+        let second_path = second_path.to_str().unwrap();
+
+        analyse_formulae(
+            &bn,
+            formulae.clone(),
+            PrintOptions::NoPrint,
+            Some(first_path.to_string()),
+            None,
+        )
+        .unwrap();
+        let (loaded_bn, loaded_formulae, results) = load_result_archive(first_path).unwrap();
+        assert_eq!(loaded_bn.num_vars(), 2);
+        assert_eq!(loaded_formulae, formulae);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results.get("formula-1").unwrap().approx_cardinality(), 1.0);
+
+        analyse_formulae(
+            &bn,
+            vec!["a | b".to_string()],
+            PrintOptions::NoPrint,
+            Some(second_path.to_string()),
+            None,
+        )
+        .unwrap();
+        assert!(compare_result_archives(first_path, second_path).is_ok());
+
+        let differences = diff_result_archives(first_path, second_path).unwrap();
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].label, "formula-0");
+        assert_eq!(
+            differences[0].formula,
+            "!{x}: AG EF {x} (first) vs. a | b (second)"
+        );
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let (only_first, only_second) = differences[0].sets.clone().unwrap();
+        let expected = model_check_formula("(!{x}: AG EF {x}) & ~(a | b)", &stg).unwrap();
+        assert_eq!(only_first, expected);
+        let expected = model_check_formula("~(!{x}: AG EF {x}) & (a | b)", &stg).unwrap();
+        assert_eq!(only_second, expected);
+        // the second formula is only present in the first bundle
+        assert_eq!(differences[1].label, "formula-1");
+        assert_eq!(differences[1].formula, "a & b");
+        assert!(differences[1].sets.is_none());
+
+        // a bundle does not differ from itself
+        let differences = diff_result_archives(first_path, first_path).unwrap();
+        for difference in differences {
+            let (only_first, only_second) = difference.sets.unwrap();
+            assert!(only_first.is_empty() && only_second.is_empty());
+        }
+        std::fs::remove_file(first_path).unwrap();
+        std::fs::remove_file(second_path).unwrap();
+    }
+
+    #[test]
+    /// Test combining the results of result archives by set expressions.
+    fn test_combine_result_archives() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\n$a: !b\n$b: a").unwrap();
+        let paths: Vec<String> = ["1", "2", "combined"]
+            .iter()
+            .map(|i| {
+                let path = unique_temp_path(&format!("combine-{i}.zip"));
+                path.to_str().unwrap().to_string()
+            })
+            .collect();
+        let formulae = [vec!["a", "b"], vec!["a & b"]];
+        for (path, formulae) in paths.iter().zip(formulae) {
+            let formulae = formulae.iter().map(|f| f.to_string()).collect();
+            analyse_formulae(
+                &bn,
+                formulae,
+                PrintOptions::NoPrint,
+                Some(path.clone()),
+                None,
+            )
+            .unwrap();
+        }
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+
+        let expression = "formula-0 minus formula-1";
+        combine_result_archives(expression, &paths[..1], Some(paths[2].clone())).unwrap();
+        let (_, formulae, results) = load_result_archive(&paths[2]).unwrap();
+        assert_eq!(formulae, vec![expression.to_string()]);
+        let expected = model_check_formula("a & ~b", &graph).unwrap();
+        assert_eq!(results.get("formula-0").unwrap(), &expected);
+
+        // with several archives, the results are prefixed by the index of their archive
+        let expression = "union(1.formula-1, 2.formula-0)";
+        combine_result_archives(expression, &paths[..2], Some(paths[2].clone())).unwrap();
+        let (_, _, results) = load_result_archive(&paths[2]).unwrap();
+        let expected = model_check_formula("b", &graph).unwrap();
+        assert_eq!(results.get("formula-0").unwrap(), &expected);
+
+        assert!(combine_result_archives("formula-0", &paths[..2], None).is_err());
+        assert!(combine_result_archives("formula-0", &[], None).is_err());
+    }
+
+    #[test]
+    /// Test that the archives of the analysis in the deterministic mode are identical.
+    fn test_deterministic_analysis() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let formulae = vec![
+            "a & (!{x}: AG EF {x})".to_string(),
+            "EF (!{x}: AG EF {x})".to_string(),
+            "!{x}: AX {x}".to_string(),
+            "EF (a & b)".to_string(),
+        ];
+        let limits = AnalysisLimits {
+            num_threads: 3,
+            ..Default::default()
+        };
+        let options = EvalOptions {
+            dump_intermediates: true,
+            deterministic: true,
+            ..Default::default()
+        };
+        let mut archives = Vec::new();
+        for i in 0..2 {
+            let path = unique_temp_path(&format!("deterministic-{i}.zip"));
+            let path = path.to_str().unwrap().to_string();
+            let mut order = Vec::new();
+            let analysis_options = AnalysisOptions {
+                result_zip: Some(path.clone()),
+                limits,
+                eval_options: options.clone(),
+                ..AnalysisOptions::default()
+            };
+            analyse_formulae_streamed(
+                &bn,
+                formulae.clone(),
+                PrintOptions::NoPrint,
+                &analysis_options,
+                |i, _| order.push(i),
+            )
+            .unwrap();
+            // the formulae are evaluated in their order, regardless of the number of threads
+            assert_eq!(order, vec![0, 1, 2, 3]);
+            archives.push(std::fs::read(path).unwrap());
+        }
+        assert_eq!(archives[0], archives[1]);
+    }
+
+    #[test]
+    /// Test writing the intermediate results of cached sub-formulae into the result archive.
+    fn test_dump_intermediates() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let path = unique_temp_path("intermediates.zip");
+        let path = path.to_str().unwrap().to_string();
+        // the attractor pattern is shared by both formulae
+        let formulae = vec![
+            "a & (!{x}: AG EF {x})".to_string(),
+            "EF (!{x}: AG EF {x})".to_string(),
+        ];
+        let analyse = |dump_intermediates: bool| {
+            let options = EvalOptions {
+                dump_intermediates,
+                ..Default::default()
+            };
+            let options = AnalysisOptions {
+                result_zip: Some(path.clone()),
+                eval_options: options,
+                ..AnalysisOptions::default()
+            };
+            analyse_formulae_with_options(&bn, formulae.clone(), PrintOptions::NoPrint, &options)
+                .unwrap();
+        };
+
+        analyse(false);
+        assert!(load_intermediate_results(&path).unwrap().is_empty());
+
+        analyse(true);
+        let intermediates = load_intermediate_results(&path).unwrap();
+        assert_eq!(intermediates.len(), 1);
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let expected = model_check_formula("!{x}: AG EF {x}", &graph).unwrap();
+        assert_eq!(intermediates[0].1, expected);
+        // the intermediate results are not mixed with the results of the formulae
+        let (_, _, results) = load_result_archive(&path).unwrap();
+        assert_eq!(results.len(), 2);
+        // but they are reused in a warm start
+        let known_results = load_known_results(&path, &bn, &HashMap::new(), &[]).unwrap();
+        assert_eq!(known_results.len(), 3);
+        assert_eq!(known_results[2], intermediates[0]);
+    }
+
+    #[test]
+    /// Test exporting the results of an archive as `.dot` and raw BDD files.
+    fn test_export_result_archive() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\n$a: !b\n$b: a").unwrap();
+        let archive_path = unique_temp_path("export.zip");
+        let archive_path = archive_path.to_str().unwrap().to_string();
+        let export_dir = unique_temp_path("export");
+        let formulae = vec!["a & b".to_string(), "a | b".to_string()];
+        analyse_formulae(
+            &bn,
+            formulae,
+            PrintOptions::NoPrint,
+            Some(archive_path.clone()),
+            None,
+        )
+        .unwrap();
+
+        let formats = [ResultExportFormat::Dot, ResultExportFormat::RawBdd];
+        export_result_archive(&archive_path, export_dir.to_str().unwrap(), &formats).unwrap();
+        let (_, _, results) = load_result_archive(&archive_path).unwrap();
+        for (name, result) in results {
+            let dot = std::fs::read_to_string(export_dir.join(format!("{name}.dot"))).unwrap();
+            assert!(dot.starts_with("digraph"));
+            assert!(dot.contains("\"a\""));
+            let raw = std::fs::read_to_string(export_dir.join(format!("{name}.bdd"))).unwrap();
+            assert_eq!(Bdd::from_string(raw.as_str()), *result.as_bdd());
+        }
+
+        // only the selected formats are exported
+        std::fs::remove_dir_all(&export_dir).unwrap();
+        let formats = [ResultExportFormat::RawBdd];
+        export_result_archive(&archive_path, export_dir.to_str().unwrap(), &formats).unwrap();
+        assert!(export_dir.join("formula-0.bdd").exists());
+        assert!(!export_dir.join("formula-0.dot").exists());
+    }
+
+    #[test]
+    /// Test the staged analysis, where the results of one stage are used in the next one.
+    fn test_analysis_pipeline() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let output_dir = unique_temp_path("pipeline");
+        let stages = vec![
+            vec![("attr".to_string(), "!{x}: AG EF {x}".to_string())],
+            vec![
+                ("reach_attr".to_string(), "EF %attr%".to_string()),
+                (
+                    "in_attr".to_string(),
+                    "3{x} in %attr%: @{x}: AX {x}".to_string(),
+                ),
+            ],
+        ];
+        analyse_pipeline(
+            &bn,
+            stages,
+            PrintOptions::NoPrint,
+            Some(output_dir.to_str().unwrap().to_string()),
+            AnalysisLimits::default(),
+        )
+        .unwrap();
+
+        // results of the last stage are the same as for the corresponding plain formulae
+        let stage_path = output_dir.join("stage-2.zip");
+        let (_, formulae, results) = load_result_archive(stage_path.to_str().unwrap()).unwrap();
+        assert_eq!(formulae.len(), 2);
+        let graph = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let expected = model_check_formula("EF (!{x}: AG EF {x})", &graph).unwrap();
+        assert_eq!(
+            results.get("reach_attr").unwrap().as_bdd(),
+            expected.as_bdd()
+        );
+        let expected =
+            model_check_formula("3{x}: (@{x}: (!{y}: AG EF {y}) & AX {x})", &graph).unwrap();
+        assert_eq!(results.get("in_attr").unwrap().as_bdd(), expected.as_bdd());
+
+        // names of the results must be usable as wild-cards
+        let stages = vec![vec![("bad-name".to_string(), "a".to_string())]];
+        let limits = AnalysisLimits::default();
+        assert!(analyse_pipeline(&bn, stages, PrintOptions::NoPrint, None, limits).is_err());
+    }
+
+    #[test]
+    /// Test the analysis with multiple threads and with a time limit (partial results).
+    fn test_analysis_with_limits() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let formulae = vec![
+            "!{x}: AG EF {x}".to_string(),
+            "!{x}: AX {x}".to_string(),
+            "EF (a & b)".to_string(),
+        ];
+        let limits = AnalysisLimits {
+            num_threads: 2,
+            ..Default::default()
+        };
+        let archive_path = unique_temp_path("limits.zip");
+        let archive_path = archive_path.to_str().unwrap();
+        let options = AnalysisOptions {
+            result_zip: Some(archive_path.to_string()),
+            limits,
+            ..AnalysisOptions::default()
+        };
+        let res = analyse_formulae_with_options(
+            &bn,
+            formulae.clone(),
+            PrintOptions::JustSummary,
+            &options,
+        );
+        assert!(res.is_ok());
+        // all the results are computed correctly by the threads
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let canonical_graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let (_, _, results) = load_result_archive(archive_path).unwrap();
+        assert_eq!(results.len(), 3);
+        for (i, formula) in formulae.iter().enumerate() {
+            // the archived results are restricted to the valid colors
+            let expected = model_check_formula(formula, &stg).unwrap();
+            let expected = expected.intersect(canonical_graph.unit_colored_vertices());
+            assert_eq!(results.get(&format!("formula-{i}")).unwrap(), &expected);
+        }
+
+        let limits = AnalysisLimits {
+            num_threads: 2,
+            timeout: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let options = AnalysisOptions {
+            result_zip: Some(archive_path.to_string()),
+            limits,
+            ..AnalysisOptions::default()
+        };
+        let res =
+            analyse_formulae_with_options(&bn, formulae.clone(), PrintOptions::NoPrint, &options);
+        assert!(res.is_ok());
+        // the limit is checked before any result is received, so no results are present
+        let (_, loaded_formulae, results) = load_result_archive(archive_path).unwrap();
+        assert_eq!(loaded_formulae, formulae);
+        assert!(results.is_empty());
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the streamed results of the analysis are delivered for all formulae.
+    fn test_analysis_streamed() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let formulae = vec![
+            "!{x}: AG EF {x}".to_string(),
+            "!{x}: AX {x}".to_string(),
+            "EF (a & b)".to_string(),
+        ];
+        let limits = AnalysisLimits {
+            num_threads: 2,
+            ..Default::default()
+        };
+        let mut results = HashMap::new();
+        let options = AnalysisOptions {
+            limits,
+            ..AnalysisOptions::default()
+        };
+        analyse_formulae_streamed(
+            &bn,
+            formulae.clone(),
+            PrintOptions::NoPrint,
+            &options,
+            |i, result| {
+                results.insert(i, result.clone());
+            },
+        )
+        .unwrap();
+
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        assert_eq!(results.len(), formulae.len());
+        for (i, formula) in formulae.iter().enumerate() {
+            assert_eq!(results[&i], model_check_formula(formula, &stg).unwrap());
+        }
+    }
+
+    #[test]
+    /// Test the evaluation shared among identical formulae and common sub-formulae.
+    fn test_shared_evaluation() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let ctx = SymbolicContext::new(&bn).unwrap();
+        // the first two formulae are identical up to the names of variables
+        let formulae = vec![
+            "!{x}: AG EF {x}".to_string(),
+            "!{y}: AG EF {y}".to_string(),
+            "EF (!{z}: AG EF {z}) & EF a".to_string(),
+            "AX (EF a)".to_string(),
+        ];
+        let trees = formulae
+            .iter()
+            .map(|f| validate_props_and_rename_vars(parse_hctl_formula(f).unwrap(), &ctx))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = SharingStatistics {
+            num_formulae: 4,
+            num_unique_formulae: 3,
+            num_shared_subformulae: 2,
+            num_repeated_occurrences: 2,
+        };
+        assert_eq!(compute_sharing_statistics(&trees), expected);
+
+        // identical formulae still get their own results
+        let mut results = HashMap::new();
+        analyse_formulae_streamed(
+            &bn,
+            formulae.clone(),
+            PrintOptions::NoPrint,
+            &AnalysisOptions::default(),
+            |i, result| {
+                results.insert(i, result.clone());
+            },
+        )
+        .unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        assert_eq!(results.len(), formulae.len());
+        for (i, formula) in formulae.iter().enumerate() {
+            assert_eq!(results[&i], model_check_formula(formula, &stg).unwrap());
+        }
+    }
+
+    #[test]
+    /// Test reusing the results stored in the result cache.
+    fn test_result_cache() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let cache_dir = unique_temp_path("result-cache");
+        let cache_dir = cache_dir.to_str().unwrap().to_string();
+        let formulae = vec!["!{x}: AG EF {x}".to_string(), "EF a".to_string()];
+        let options = EvalOptions::default();
+        let no_inputs = HashMap::new();
+
+        // the keys do not depend on the names of variables, but they depend on the options
+        let key = result_cache_key(&bn, &formulae[0], &no_inputs, &options).unwrap();
+        let renamed_key = result_cache_key(&bn, "!{y}: AG EF {y}", &no_inputs, &options).unwrap();
+        assert_eq!(key, renamed_key);
+        let fixed_inputs = HashMap::from([("a".to_string(), true)]);
+        assert_ne!(
+            key,
+            result_cache_key(&bn, &formulae[0], &fixed_inputs, &options).unwrap()
+        );
+        assert!(result_cache_key(&bn, "EF %s%", &no_inputs, &options).is_err());
+
+        // the first run fills the cache
+        let run = |result_zip: Option<String>| {
+            let options = AnalysisOptions {
+                result_zip,
+                result_cache_dir: Some(cache_dir.clone()),
+                ..AnalysisOptions::default()
+            };
+            analyse_formulae_with_options(&bn, formulae.clone(), PrintOptions::NoPrint, &options)
+        };
+        run(None).unwrap();
+        let num_cached = std::fs::read_dir(&cache_dir).unwrap().count();
+        assert_eq!(num_cached, formulae.len());
+
+        // the second run takes the results from the cache (here, a modified one)
+        let stg = SymbolicAsyncGraph::new(&bn).unwrap();
+        let empty = stg.mk_empty_colored_vertices();
+        write_cached_result(&cache_dir, &key, &bn.to_string(), &formulae[0], &empty).unwrap();
+        let path = unique_temp_path("result-cache.zip");
+        let path = path.to_str().unwrap().to_string();
+        run(Some(path.clone())).unwrap();
+        let (_, _, results) = load_result_archive(&path).unwrap();
+        assert!(results["formula-0"].is_empty());
+        assert!(!results["formula-1"].is_empty());
+
+        // a corrupted entry is computed again and replaced
+        std::fs::write(cached_result_path(&cache_dir, &key), "corrupted").unwrap();
+        run(Some(path.clone())).unwrap();
+        let (_, _, results) = load_result_archive(&path).unwrap();
+        assert!(!results["formula-0"].is_empty());
+        assert!(load_cached_result(&cache_dir, &key, &bn).unwrap().is_some());
+        let num_cached = std::fs::read_dir(&cache_dir).unwrap().count();
+        assert_eq!(num_cached, formulae.len());
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    /// Test the automatic and the requested number of HCTL variables of the analysis.
+    fn test_num_hctl_vars_override() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let formulae = vec!["!{x}: AG EF {x}".to_string(), "AG EF a".to_string()];
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // more variables than needed give the same (sanitized) results
+        let mut results = HashMap::new();
+        let options = EvalOptions {
+            num_hctl_vars: Some(3),
+            ..Default::default()
+        };
+        let analysis_options = AnalysisOptions {
+            eval_options: options,
+            ..AnalysisOptions::default()
+        };
+        let run = analyse_formulae_streamed(
+            &bn,
+            formulae.clone(),
+            PrintOptions::NoPrint,
+            &analysis_options,
+            |i, result| {
+                results.insert(i, result.clone());
+            },
+        );
+        assert!(run.is_ok());
+        for (i, formula) in formulae.iter().enumerate() {
+            assert_eq!(results[&i], model_check_formula(formula, &stg).unwrap());
+        }
+
+        // fewer variables than needed are an error
+        let options = EvalOptions {
+            num_hctl_vars: Some(0),
+            ..Default::default()
+        };
+        let options = AnalysisOptions {
+            eval_options: options,
+            ..AnalysisOptions::default()
+        };
+        let run = analyse_formulae_with_options(&bn, formulae, PrintOptions::NoPrint, &options);
+        assert!(run.is_err());
+    }
+
+    #[test]
+    /// Test checking the equivalence and implication of formulae on a model.
+    fn test_equivalence_and_implication() {
+        let bn = BooleanNetwork::try_from("a -?? b\nb -| a\n$a: !b").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // `AG` rewritten using `EF` is equivalent, but adding `EF` is not
+        let diff = check_equivalence("AG a", "~EF ~a", &graph).unwrap();
+        assert!(diff.is_empty());
+        let diff = check_equivalence("a | b", "EF a | b", &graph).unwrap();
+        assert!(!diff.is_empty());
+
+        assert!(check_implication("a", "EF a", &graph).unwrap().is_empty());
+        let failed = check_implication("EF a", "a", &graph).unwrap();
+        assert_eq!(failed, check_equivalence("EF a", "a", &graph).unwrap());
+        assert!(!failed.is_empty());
+    }
+
+    #[test]
+    /// Test the knockout screen of regulations, ranked by the impact on the property.
+    fn test_knockout_screen() {
+        // `c` is activated by `a` and inhibited by `b`, both `a` and `b` keep their values
+        let model = "a -> a\nb -> b\na -> c\nb -| c\n$a: a\n$b: b\n$c: a & !b";
+        let bn = BooleanNetwork::try_from(model).unwrap();
+        let formula = "!{x}: AX ({x} & c)";
+        let (original, impacts) = regulation_knockout_screen(&bn, formula, false).unwrap();
+        assert_eq!(original.satisfying_colors, 1.0);
+        assert_eq!(original.total_colors, 1.0);
+
+        // fixed-point with active `c` is lost when `a` can not stay active (or activate `c`)
+        let ranked = impacts
+            .iter()
+            .map(|i| {
+                (
+                    i.regulator.as_str(),
+                    i.target.as_str(),
+                    i.satisfying_fraction(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ranked,
+            vec![
+                ("a", "a", 0.0),
+                ("a", "c", 0.0),
+                ("b", "b", 1.0),
+                ("b", "c", 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    /// Test the validation of formulae without model checking.
+    fn test_validate_formulae() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a").unwrap();
+        let formulae = vec![
+            "!{x}: AG EF {x}".to_string(),
+            "3{x}: @{x}: a EU b".to_string(),
+        ];
+        assert!(validate_formulae(&bn, formulae, false).is_ok());
+
+        // invalid proposition and syntax error
+        let formulae = vec!["!{x}: AG EF c".to_string(), "a & ".to_string()];
+        assert!(validate_formulae(&bn, formulae, false).is_err());
+
+        // wild-cards are only allowed in extended formulae
+        let formulae = vec!["!{x}: AG EF %p%".to_string()];
+        assert!(validate_formulae(&bn, formulae.clone(), false).is_err());
+        assert!(validate_formulae(&bn, formulae, true).is_ok());
+    }
+
+    #[test]
+    /// Simple test to check whether the model inspection runs without an error.
+    fn test_model_info() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b\n$a: f(b)").unwrap();
+        assert!(print_model_info(&bn).is_ok());
+    }
+
+    #[test]
+    /// Test benchmarking of formulae and the CSV output.
+    fn test_benchmark_formulae() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let formulae = vec![
+            "!{x}: (AX {x} & AX {x})".to_string(),
+            "EF (a & b)".to_string(),
+        ];
+        let records = benchmark_formulae(&bn, formulae, 3).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].num_runs, 3);
+        assert_eq!(records[0].num_duplicates, 1);
+        assert_eq!(records[0].num_cache_hits, 1);
+        assert!(records[0].peak_bdd_size >= records[0].result_bdd_size);
+        assert!(records[1].mean_ms >= 0.0);
+        assert!(records[1].to_csv_row().starts_with("\"EF (a & b)\",3,"));
+
+        assert!(benchmark_formulae(&bn, vec!["a".to_string()], 0).is_err());
+    }
+
+    #[test]
+    /// Test checking formulae on all models in a directory, and formatting the results.
+    fn test_models_in_directory() {
+        let dir = unique_temp_path("models-dir");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("m1.aeon"), "a -> b\nb -| a").unwrap();
+        std::fs::write(dir.join("m2.bnet"), "targets,factors\na, b\nc, c\nb, a").unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a model").unwrap();
+
+        let formulae = vec!["!{x}: AX {x}".to_string(), "EF c".to_string()];
+        let records = check_models_in_directory(dir.to_str().unwrap(), formulae, 2).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].model_name, "m1.aeon");
+        // the first model does not have variable `c`
+        assert!(records[0].results.is_err());
+        let summaries = records[1].results.as_ref().unwrap();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].num_states, 4.0);
+
+        let csv = model_records_to_csv(&records);
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains("\"m2.bnet\",\"EF c\",4,1,4,"));
+        let json = model_records_to_json(&records);
+        assert!(json.contains("\"m1.aeon\": {\"error\": "));
+    }
+
+    #[test]
+    /// Test checking formulae on several models at once (including an invalid model).
+    fn test_analyse_models() {
+        let models = vec![
+            BooleanNetwork::try_from("a -> b\nb -> a\n$a: b\n$b: a").unwrap(),
+            BooleanNetwork::try_from("a -> c\nc -| a").unwrap(),
+            BooleanNetwork::try_from("a -> b\nb -| a\n$a: !b\n$b: a").unwrap(),
+        ];
+        let formulae = vec!["!{x}: AX {x}".to_string(), "EF a & b".to_string()];
+        let options = AnalysisLimits {
+            num_threads: 2,
+            ..AnalysisLimits::default()
+        };
+        let results = analyse_models(models.clone(), formulae.clone(), options).unwrap();
+        assert_eq!(results.len(), 3);
+
+        // results are the same as for the individual models
+        let first = results[0].as_ref().unwrap();
+        let graph = get_extended_symbolic_graph(&models[0], 1).unwrap();
+        let expected =
+            model_check_multiple_formulae(vec!["!{x}: AX {x}", "EF a & b"], &graph).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first[0].approx_cardinality(), 2.0);
+        assert_eq!(first[0].as_bdd(), expected[0].as_bdd());
+        // the second model does not contain variable `b`
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap()[0].approx_cardinality(), 0.0);
+
+        assert!(analyse_models(models, vec!["EF (a".to_string()], options).is_err());
+    }
+
+    #[test]
+    /// Test the reachability between two sets, both for some and for all paths.
+    fn test_reachability() {
+        // `b` follows `a`, which follows the parameter `f`, and `c` oscillates independently
+        let bn = BooleanNetwork::try_from("a -> b\nc -| c\n$a: f\n$b: a\n$c: !c").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let var = |name: &str| bn.as_graph().find_variable(name).unwrap();
+        let from = graph.mk_subspace(&[(var("a"), false), (var("b"), false)]);
+        let to = graph.mk_subspace(&[(var("b"), true)]);
+
+        let result = reachability(&from, &to, &graph, ReachabilityMode::Exists).unwrap();
+        assert_eq!(result.colors.approx_cardinality(), 1.0);
+        assert_eq!(result.reaching_states.approx_cardinality(), 2.0);
+
+        // the oscillation of `c` can postpone the activation forever
+        let result = reachability(&from, &to, &graph, ReachabilityMode::Forall).unwrap();
+        assert!(result.colors.is_empty());
+        assert!(result.reaching_states.is_empty());
+
+        // the sets can also come from an extended graph
+        let extended_graph = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let result = reachability(&from, &to, &extended_graph, ReachabilityMode::Exists).unwrap();
+        assert_eq!(result.colors.approx_cardinality(), 1.0);
+    }
+
+    #[test]
+    /// Test the outputs of the driver-variable identification.
+    fn test_driver_variables_output() {
+        let bn = BooleanNetwork::try_from("a -> a\na -> b\n$a: a\n$b: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let impacts = rank_driver_variables("AG b", &graph).unwrap();
+
+        let json = driver_impacts_to_json("AG \"b\"", &impacts);
+        assert!(json.starts_with("{\n  \"formula\": \"AG \\\"b\\\"\",\n  \"drivers\": ["));
+        assert_eq!(json.matches("\"variable\"").count(), 4);
+        assert!(json.contains(
+            "{\"variable\": \"a\", \"value\": 0, \"changed_colors\": 1, \"changed_states\": 0, \"total_colors\": 1}"
+        ));
+
+        assert!(analyse_driver_variables(&bn, "AG b", false).is_ok());
+        assert!(analyse_driver_variables(&bn, "AG b", true).is_ok());
+        assert!(analyse_driver_variables(&bn, "AG c", true).is_err());
+    }
+
+    #[test]
+    /// Test printing the explanations of reachability properties.
+    fn test_reachability_explanation() {
+        let bn = BooleanNetwork::try_from("a -> c\nb -> c\na -| b\n$a: true\n$b: !a\n$c: a | b")
+            .unwrap();
+        assert!(print_reachability_explanation(&bn, "EF (a & c)").is_ok());
+        assert!(print_reachability_explanation(&bn, "EF (a & ~a)").is_ok());
+        assert!(print_reachability_explanation(&bn, "AF (a & c)").is_err());
     }
 }
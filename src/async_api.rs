@@ -0,0 +1,143 @@
+//! Async wrappers of the long-running entry points, so that the model checker can be embedded
+//! into async services without managing the threads manually. Only available with the `async`
+//! feature.
+//!
+//! The computation runs on the blocking thread pool of Tokio (see `tokio::task::spawn_blocking`),
+//! so the wrappers must be called from within a Tokio runtime. Each wrapper returns an
+//! [AsyncTask], which is a future resolving to the result of the computation, and which also
+//! carries a stream of [Progress] events (such as the results of the individual formulae).
+
+use crate::model_checking::model_check_multiple_formulae_streamed;
+
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::task::{spawn_blocking, JoinHandle};
+
+#[cfg(feature = "fs")]
+use crate::analysis::{analyse_formulae_streamed, AnalysisOptions};
+#[cfg(feature = "fs")]
+use crate::result_print::PrintOptions;
+#[cfg(feature = "fs")]
+use biodivine_lib_param_bn::BooleanNetwork;
+
+/// Progress events of a running [AsyncTask].
+#[derive(Clone, Debug)]
+pub enum Progress {
+    /// The formula with the given index was evaluated, with the given (sanitized) result.
+    FormulaEvaluated(usize, GraphColoredVertices),
+}
+
+/// A computation running on the blocking thread pool of Tokio.
+///
+/// The task is a future resolving to the result of the computation. While it runs, the
+/// [Progress] events can be received from the `progress` stream, which is closed once the
+/// computation finishes. The computation continues even if the task is dropped.
+pub struct AsyncTask<T> {
+    /// Stream of the progress events of the computation.
+    pub progress: UnboundedReceiver<Progress>,
+    handle: JoinHandle<Result<T, String>>,
+}
+
+impl<T> Future for AsyncTask<T> {
+    type Output = Result<T, String>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.handle).poll(cx).map(|result| {
+            result.map_err(|e| format!("Evaluation of formulae failed unexpectedly: {e}"))?
+        })
+    }
+}
+
+/// Async version of [model_check_multiple_formulae_streamed]. Perform the model checking for the
+/// list of HCTL formulae on a given transition `graph`, and resolve to the resulting sets of
+/// colored vertices (in the same order as input formulae).
+///
+/// Each result is also sent as [Progress::FormulaEvaluated] as soon as it is computed.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_formulae_async(
+    formulae: Vec<String>,
+    graph: SymbolicAsyncGraph,
+) -> AsyncTask<Vec<GraphColoredVertices>> {
+    let (sender, progress) = unbounded_channel();
+    let handle = spawn_blocking(move || {
+        let formulae = formulae.iter().map(|f| f.as_str()).collect::<Vec<_>>();
+        let mut results = Vec::new();
+        model_check_multiple_formulae_streamed(formulae, &graph, |i, result| {
+            // the receiver may be gone, but the results are still collected
+            let _ = sender.send(Progress::FormulaEvaluated(i, result.clone()));
+            results.push(result);
+        })?;
+        Ok(results)
+    });
+    AsyncTask { progress, handle }
+}
+
+/// Async version of [analyse_formulae_streamed]. Perform the whole model checking analysis
+/// regarding several (individual) formulae, without printing anything, and resolve once the
+/// analysis finishes (including writing the results into the result archive, if provided).
+///
+/// Each result is sent as [Progress::FormulaEvaluated] as soon as it is computed.
+#[cfg(feature = "fs")]
+pub fn analyse_formulae_async(
+    bn: BooleanNetwork,
+    formulae: Vec<String>,
+    options: AnalysisOptions,
+) -> AsyncTask<()> {
+    let (sender, progress) = unbounded_channel();
+    let handle = spawn_blocking(move || {
+        analyse_formulae_streamed(
+            &bn,
+            formulae,
+            PrintOptions::NoPrint,
+            &options,
+            |i, result| {
+                let _ = sender.send(Progress::FormulaEvaluated(i, result.clone()));
+            },
+        )
+    });
+    AsyncTask { progress, handle }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_api::{model_check_multiple_formulae_async, Progress};
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test the async model checking, including the progress events.
+    fn test_model_check_async() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let formulae = vec!["!{x}: AX {x}".to_string(), "EF (a & b)".to_string()];
+
+        let (events, results) = runtime.block_on(async {
+            let mut task = model_check_multiple_formulae_async(formulae.clone(), stg.clone());
+            let mut events = Vec::new();
+            while let Some(event) = task.progress.recv().await {
+                events.push(event);
+            }
+            (events, task.await.unwrap())
+        });
+        assert_eq!(results.len(), 2);
+        assert_eq!(events.len(), 2);
+        for (i, Progress::FormulaEvaluated(j, result)) in events.into_iter().enumerate() {
+            assert_eq!(i, j);
+            assert_eq!(result, results[i]);
+            assert_eq!(result, model_check_formula(&formulae[i], &stg).unwrap());
+        }
+
+        // errors are propagated through the future
+        let invalid = vec!["AX invalid".to_string()];
+        let result =
+            runtime.block_on(async { model_check_multiple_formulae_async(invalid, stg).await });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Attractor search on the symbolic transition graphs, i.e., the computation of the states in the
+//! terminal SCCs (by ITGR reduction followed by the Xie-Beerel algorithm, adapted from
+//! [AEON](https://biodivine.fi.muni.cz/aeon/)).
+//!
+//! Besides the full search ([compute_attractor_states]), the search can start from a custom
+//! universe, report its progress, and terminate early ([compute_attractor_states_with_search]),
+//! or only decide which colors have an attractor intersecting a target set
+//! ([compute_target_attractor_colors]).
+
+pub use crate::_aeon_algorithms::scc_computation::{
+    compute_attractor_states, compute_attractor_states_with_search, compute_fair_attractor_states,
+    compute_target_attractor_colors, stop_after_components, stop_when_intersecting,
+    AttractorSearchProgress,
+};
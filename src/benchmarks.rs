@@ -0,0 +1,243 @@
+//! Built-in benchmark suite, so that the performance changes can be measured consistently from
+//! code. Only available with the `benchmarks` feature.
+//!
+//! The suite bundles the models used in the tests (see [BenchmarkModel]) and canonical sets of
+//! formulae (see [FormulaSet]). Each combination is measured by [benchmark_formulae], i.e., the
+//! wall times of repeated evaluation together with the BDD sizes and cache statistics.
+
+use crate::analysis::{benchmark_formulae, BenchmarkRecord};
+use crate::load_inputs::{network_from_str, ModelFormat};
+
+use biodivine_lib_param_bn::BooleanNetwork;
+
+/// Model FISSION-YEAST-2008 (in bnet format).
+pub const MODEL_YEAST: &str = r"
+targets,factors
+Cdc25, ((!Cdc2_Cdc13 & (Cdc25 & !PP)) | ((Cdc2_Cdc13 & (!Cdc25 & !PP)) | (Cdc2_Cdc13 & Cdc25)))
+Cdc2_Cdc13, (!Ste9 & (!Rum1 & !Slp1))
+Cdc2_Cdc13_A, (!Ste9 & (!Rum1 & (!Slp1 & (!Wee1_Mik1 & Cdc25))))
+PP, Slp1
+Rum1, ((!SK & (!Cdc2_Cdc13 & (!Rum1 & (!Cdc2_Cdc13_A & PP)))) | ((!SK & (!Cdc2_Cdc13 & (Rum1 & !Cdc2_Cdc13_A))) | ((!SK & (!Cdc2_Cdc13 & (Rum1 & (Cdc2_Cdc13_A & PP)))) | ((!SK & (Cdc2_Cdc13 & (Rum1 & (!Cdc2_Cdc13_A & PP)))) | (SK & (!Cdc2_Cdc13 & (Rum1 & (!Cdc2_Cdc13_A & PP))))))))
+SK, Start
+Slp1, Cdc2_Cdc13_A
+Start, false
+Ste9, ((!SK & (!Cdc2_Cdc13 & (!Ste9 & (!Cdc2_Cdc13_A & PP)))) | ((!SK & (!Cdc2_Cdc13 & (Ste9 & !Cdc2_Cdc13_A))) | ((!SK & (!Cdc2_Cdc13 & (Ste9 & (Cdc2_Cdc13_A & PP)))) | ((!SK & (Cdc2_Cdc13 & (Ste9 & (!Cdc2_Cdc13_A & PP)))) | (SK & (!Cdc2_Cdc13 & (Ste9 & (!Cdc2_Cdc13_A & PP))))))))
+Wee1_Mik1, ((!Cdc2_Cdc13 & (!Wee1_Mik1 & PP)) | ((!Cdc2_Cdc13 & Wee1_Mik1) | (Cdc2_Cdc13 & (Wee1_Mik1 & PP))))
+";
+
+/// Model MAMMALIAN-CELL-CYCLE-2006 (in bnet format).
+pub const MODEL_CELL_CYCLE: &str = r"
+targets,factors
+v_Cdc20, v_CycB
+v_Cdh1, ((v_Cdc20 | (v_p27 & !v_CycB)) | !(((v_p27 | v_CycB) | v_CycA) | v_Cdc20))
+v_CycA, ((v_CycA & !(((v_Cdh1 & v_UbcH10) | v_Cdc20) | v_Rb)) | (v_E2F & !(((v_Cdh1 & v_UbcH10) | v_Cdc20) | v_Rb)))
+v_CycB, !(v_Cdc20 | v_Cdh1)
+v_CycE, (v_E2F & !v_Rb)
+v_E2F, ((v_p27 & !(v_CycB | v_Rb)) | !(((v_p27 | v_Rb) | v_CycB) | v_CycA))
+v_Rb, ((v_p27 & !(v_CycD | v_CycB)) | !((((v_CycE | v_p27) | v_CycB) | v_CycD) | v_CycA))
+v_UbcH10, (((((v_UbcH10 & ((v_Cdh1 & ((v_CycB | v_Cdc20) | v_CycA)) | !v_Cdh1)) | (v_CycA & !v_Cdh1)) | (v_Cdc20 & !v_Cdh1)) | (v_CycB & !v_Cdh1)) | !((((v_UbcH10 | v_Cdh1) | v_CycB) | v_Cdc20) | v_CycA))
+v_p27, ((v_p27 & !((v_CycD | (v_CycA & v_CycE)) | v_CycB)) | !((((v_CycE | v_p27) | v_CycB) | v_CycD) | v_CycA))
+";
+
+/// Largely parametrized version of the model ASYMMETRIC-CELL-DIVISION-B (in aeon format).
+pub const MODEL_CELL_DIVISION: &str = r"
+DivJ -?? DivK
+PleC -?? DivK
+DivK -?? DivL
+DivL -?? CckA
+CckA -?? ChpT
+ChpT -?? CpdR
+CpdR -?? ClpXP_RcdA
+ChpT -?? CtrAb
+ClpXP_RcdA -?? CtrAb
+DivK -?? DivJ
+PleC -?? DivJ
+DivK -?? PleC
+$CckA: DivL
+$ChpT: CckA
+$DivK: (!PleC & DivJ)
+";
+
+/// Models of the benchmark suite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BenchmarkModel {
+    /// Model FISSION-YEAST-2008 (10 variables, fully specified), see [MODEL_YEAST].
+    Yeast,
+    /// Model MAMMALIAN-CELL-CYCLE-2006 (10 variables, fully specified), see [MODEL_CELL_CYCLE].
+    CellCycle,
+    /// Largely parametrized model ASYMMETRIC-CELL-DIVISION-B, see [MODEL_CELL_DIVISION].
+    CellDivision,
+}
+
+impl BenchmarkModel {
+    /// All the models of the benchmark suite.
+    pub fn all() -> Vec<BenchmarkModel> {
+        vec![
+            BenchmarkModel::Yeast,
+            BenchmarkModel::CellCycle,
+            BenchmarkModel::CellDivision,
+        ]
+    }
+
+    /// Name of the model, as used in the benchmark records.
+    pub fn name(&self) -> &str {
+        match self {
+            BenchmarkModel::Yeast => "fission-yeast-2008",
+            BenchmarkModel::CellCycle => "mammalian-cell-cycle-2006",
+            BenchmarkModel::CellDivision => "asymmetric-cell-division-b",
+        }
+    }
+
+    /// Load the BN model.
+    pub fn load(&self) -> Result<BooleanNetwork, String> {
+        match self {
+            BenchmarkModel::Yeast => network_from_str(MODEL_YEAST, Some(ModelFormat::Bnet)),
+            BenchmarkModel::CellCycle => {
+                network_from_str(MODEL_CELL_CYCLE, Some(ModelFormat::Bnet))
+            }
+            BenchmarkModel::CellDivision => {
+                network_from_str(MODEL_CELL_DIVISION, Some(ModelFormat::Aeon))
+            }
+        }
+    }
+}
+
+/// Canonical sets of formulae of the benchmark suite. The formulae do not reference any
+/// propositions, so they can be evaluated on any model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FormulaSet {
+    /// Attractor states, fixed points, and states of the cyclic attractors.
+    Attractors,
+    /// Reachability of fixed points and cyclic attractors, including the nested patterns.
+    Reachability,
+    /// Formulae with several (nested) HCTL variables, such as the bistability patterns.
+    MultipleVariables,
+}
+
+impl FormulaSet {
+    /// All the formula sets of the benchmark suite.
+    pub fn all() -> Vec<FormulaSet> {
+        vec![
+            FormulaSet::Attractors,
+            FormulaSet::Reachability,
+            FormulaSet::MultipleVariables,
+        ]
+    }
+
+    /// Name of the formula set, as used in the benchmark records.
+    pub fn name(&self) -> &str {
+        match self {
+            FormulaSet::Attractors => "attractors",
+            FormulaSet::Reachability => "reachability",
+            FormulaSet::MultipleVariables => "multiple-variables",
+        }
+    }
+
+    /// The formulae of the set.
+    pub fn formulae(&self) -> Vec<String> {
+        let formulae = match self {
+            FormulaSet::Attractors => vec!["!{x}: AG EF {x}", "!{x}: AX {x}", "!{x}: AX EF {x}"],
+            FormulaSet::Reachability => vec![
+                "AF (!{x}: AX {x})",
+                "!{x}: (AX (AF {x}))",
+                "AF (!{x}: (AX (~{x} & AF {x})))",
+            ],
+            FormulaSet::MultipleVariables => vec![
+                "!{x}: 3{y}: (@{x}: ~{y} & AX {x}) & (@{y}: AX {y})",
+                "AF (!{x}: ((AX (~{x} & AF {x})) & (EF (!{y}: EX ~AF {y}))))",
+                "3{x}: 3{y}: (@{x}: ~{y} & AX {x}) & (@{y}: AX {y}) & EF ({x} & (!{z}: AX {z})) & EF ({y} & (!{z}: AX {z})) & AX (EF ({x} & (!{z}: AX {z})) ^ EF ({y} & (!{z}: AX {z})))",
+            ],
+        };
+        formulae.into_iter().map(|f| f.to_string()).collect()
+    }
+}
+
+/// Benchmark record of a single formula of the suite, see [run_benchmark_suite].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SuiteRecord {
+    /// Name of the model (see [BenchmarkModel::name]).
+    pub model: String,
+    /// Name of the formula set (see [FormulaSet::name]).
+    pub formula_set: String,
+    /// Measurements of the formula.
+    pub record: BenchmarkRecord,
+}
+
+impl SuiteRecord {
+    /// Header of the CSV table with the suite records.
+    pub fn csv_header() -> String {
+        format!("model,formula_set,{}", BenchmarkRecord::csv_header())
+    }
+
+    /// Format the record as a row of the CSV table (see [SuiteRecord::csv_header]).
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{}",
+            self.model,
+            self.formula_set,
+            self.record.to_csv_row()
+        )
+    }
+}
+
+/// Measure the evaluation of the formulae of the `formula_set` on the `model`, repeating each
+/// evaluation `num_runs` times (see [benchmark_formulae]).
+pub fn run_benchmark(
+    model: BenchmarkModel,
+    formula_set: FormulaSet,
+    num_runs: usize,
+) -> Result<Vec<BenchmarkRecord>, String> {
+    benchmark_formulae(&model.load()?, formula_set.formulae(), num_runs)
+}
+
+/// Measure all the combinations of the models and formula sets of the suite (see
+/// [run_benchmark]). The records are ordered by the models, and then by the formula sets.
+pub fn run_benchmark_suite(num_runs: usize) -> Result<Vec<SuiteRecord>, String> {
+    let mut suite_records = Vec::new();
+    for model in BenchmarkModel::all() {
+        for formula_set in FormulaSet::all() {
+            for record in run_benchmark(model, formula_set, num_runs)? {
+                suite_records.push(SuiteRecord {
+                    model: model.name().to_string(),
+                    formula_set: formula_set.name().to_string(),
+                    record,
+                });
+            }
+        }
+    }
+    Ok(suite_records)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::benchmarks::{run_benchmark, BenchmarkModel, FormulaSet, SuiteRecord};
+
+    #[test]
+    /// Test loading the models of the suite, and running a part of the suite.
+    fn test_benchmark_suite() {
+        let num_vars = BenchmarkModel::all()
+            .into_iter()
+            .map(|model| model.load().unwrap().num_vars())
+            .collect::<Vec<_>>();
+        assert_eq!(num_vars, vec![10, 10, 9]);
+
+        let records = run_benchmark(BenchmarkModel::CellCycle, FormulaSet::Attractors, 2).unwrap();
+        assert_eq!(records.len(), FormulaSet::Attractors.formulae().len());
+        for (record, formula) in records.iter().zip(FormulaSet::Attractors.formulae()) {
+            assert_eq!(record.formula, formula);
+            assert_eq!(record.num_runs, 2);
+        }
+        assert!(run_benchmark(BenchmarkModel::Yeast, FormulaSet::Reachability, 0).is_err());
+
+        let suite_record = SuiteRecord {
+            model: BenchmarkModel::CellCycle.name().to_string(),
+            formula_set: FormulaSet::Attractors.name().to_string(),
+            record: records[0].clone(),
+        };
+        let num_columns = SuiteRecord::csv_header().split(',').count();
+        assert_eq!(suite_record.to_csv_row().split(',').count(), num_columns);
+        assert!(suite_record
+            .to_csv_row()
+            .starts_with("mammalian-cell-cycle-2006,attractors,"));
+    }
+}
@@ -1,19 +1,28 @@
 //! Contains the high-level model-checking algorithm and few optimisations.
 
-use crate::_aeon_algorithms::scc_computation::compute_attractor_states;
+use crate::_aeon_algorithms::scc_computation::{
+    compute_attractor_states_with_search, compute_fair_attractor_states,
+    compute_target_attractor_colors,
+};
 use crate::evaluation::canonization::get_canonical_and_renaming;
+use crate::evaluation::debugger::NodeInspection;
 use crate::evaluation::eval_context::EvalContext;
 use crate::evaluation::hctl_operators_eval::*;
 use crate::evaluation::low_level_operations::{
     compute_valid_domain_for_var, restrict_stg_unit_bdd, substitute_hctl_var,
 };
-use crate::evaluation::{VarDomainMap, VarRenameMap};
-use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::evaluation::{ReachabilityAlgorithm, VarDomainMap, VarRenameMap};
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType, VarDomain};
 use crate::preprocessing::operator_enums::*;
+use crate::preprocessing::rewriting::contains_var;
+use crate::preprocessing::tokenizer::FAIR_ATTRACTORS_DOMAIN;
+use crate::profiling::span;
 
 use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::fixed_points::FixedPoints;
 use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use std::cmp::max;
+use std::time::Instant;
 
 /// Recursively evaluate the sub-formula represented by a `node` (of a syntactic tree) on a given `graph`.
 ///
@@ -22,17 +31,80 @@ use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, Symboli
 /// See also [EvalContext].
 ///
 /// The set of `steady_states` is used to include self-loops in computing predecessors.
+///
+/// If the annotation is enabled in the `eval_context` (see [EvalContext::enable_annotation]),
+/// the evaluated sub-formulae are recorded together with their results. If a debugger is attached
+/// (see [EvalContext::attach_debugger]), it is invoked before and after each sub-formula. Once the
+/// debugger stops the evaluation, the remaining sub-formulae evaluate to empty sets. The same
+/// holds once the evaluation is cancelled (see [EvalContext::set_cancellation]), which also
+/// interrupts the running fixed points, so the results are not valid after the cancellation.
+///
+/// If the evaluation fails (e.g., the context of a `wild-card relation` is missing), the error is
+/// recorded in the `eval_context` and the remaining sub-formulae evaluate to empty sets as well.
+/// The error must be checked using [EvalContext::take_error] before using the results.
 pub fn eval_node(
     node: HctlTreeNode,
     graph: &SymbolicAsyncGraph,
     eval_context: &mut EvalContext,
     steady_states: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    if eval_context.is_cancelled() || eval_context.error.is_some() {
+        return graph.mk_empty_colored_vertices();
+    }
+    // the first (top-level) evaluation is on the whole graph, which the built-in domains need
+    if !eval_context.pending_domains.is_empty() {
+        eval_builtin_domains(graph, eval_context, steady_states);
+    }
+    if eval_context.annotator.is_none() && eval_context.debugger.is_none() {
+        return eval_node_inner(node, graph, eval_context, steady_states);
+    }
+    let formula = node.to_string();
+    if let Some(debugger) = eval_context.debugger.as_mut() {
+        if !debugger.before_node(&formula) {
+            return graph.mk_empty_colored_vertices();
+        }
+    }
+    if let Some(annotator) = eval_context.annotator.as_mut() {
+        annotator.open_node();
+    }
+    let num_cache_hits = eval_context.statistics.num_cache_hits;
+    let start = Instant::now();
+    let result = eval_node_inner(node, graph, eval_context, steady_states);
+    let duration = start.elapsed();
+    let cache_hit = eval_context.statistics.num_cache_hits > num_cache_hits;
+    if let Some(debugger) = eval_context.debugger.as_mut() {
+        let inspection = NodeInspection {
+            formula: &formula,
+            graph,
+            result: &result,
+            duration,
+        };
+        debugger.after_node(&inspection);
+    }
+    if let Some(annotator) = eval_context.annotator.as_mut() {
+        annotator.close_node(graph, formula, &result, duration, cache_hit);
+    }
+    result
+}
+
+/// Evaluate the sub-formula represented by a `node` (see [eval_node]), without the annotation
+/// and debugging hooks.
+fn eval_node_inner(
+    node: HctlTreeNode,
+    graph: &SymbolicAsyncGraph,
+    eval_context: &mut EvalContext,
+    steady_states: &GraphColoredVertices,
 ) -> GraphColoredVertices {
     // first check whether this node does not belong in the duplicates
     let mut save_to_cache = false;
 
     // get canonized form of this sub-formula, and mapping between original and canonized variable names
-    let (canonized_form, renaming) = get_canonical_and_renaming(node.to_string());
+    // (pure CTL formulae have no variables, so their sub-formulae are already canonical)
+    let (canonized_form, renaming) = if eval_context.ctl_only {
+        (node.to_string(), VarRenameMap::new())
+    } else {
+        get_canonical_and_renaming(node.to_string())
+    };
     // rename the variables in the domain map to their canonical form (duplicate formulae are always canonical)
     // only include the FREE canonical variables that are actually contained in the sub-formula
     // example: given "!{x}:!{y}: (AX {y})", its sub-formula "AX {x}" would have one "None" domain for "var0"
@@ -49,33 +121,28 @@ pub fn eval_node(
         .duplicates
         .contains_key(&canonized_formula_with_domains)
     {
-        if eval_context
-            .cache
-            .contains_key(&canonized_formula_with_domains)
-        {
+        // get cached result, but it might be using differently named state-variables
+        // so we might have to rename them later
+        // (large results might have to be reloaded from disk)
+        let cached = eval_context.get_cached(&canonized_formula_with_domains, graph);
+        if let Ok(Some((mut result, result_renaming))) = cached {
+            eval_context.statistics.num_cache_hits += 1;
             // decrement number of duplicates left
             *eval_context
                 .duplicates
                 .get_mut(&canonized_formula_with_domains)
                 .unwrap() -= 1;
 
-            // get cached result, but it might be using differently named state-variables
-            // so we might have to rename them later
-            let (mut result, result_renaming) = eval_context
-                .cache
-                .get(&canonized_formula_with_domains)
-                .unwrap()
-                .clone();
-
             // if we already visited all of the duplicates, lets delete the cached value
             if eval_context.duplicates[&canonized_formula_with_domains] == 0 {
                 eval_context
                     .duplicates
                     .remove(&canonized_formula_with_domains);
-                eval_context.cache.remove(&canonized_formula_with_domains);
+                eval_context.remove_cached(&canonized_formula_with_domains);
             }
 
             // since we are working with canonical cache, we might need to rename vars in result bdd
+            let _span = span("cache_renaming");
             let mut reverse_renaming: VarRenameMap = VarRenameMap::new();
             for (var_curr, var_canon) in renaming.iter() {
                 reverse_renaming.insert(var_canon.clone(), var_curr.clone());
@@ -85,6 +152,22 @@ pub fn eval_node(
                 result = substitute_hctl_var(graph, &result, var_res, var_curr);
             }
             return result;
+        } else if cached.is_err() {
+            // the spilled result cannot be reloaded, so it is computed again in place of this
+            // duplicate, and cached again only if there are any duplicates left
+            eval_context.remove_cached(&canonized_formula_with_domains);
+            let remaining = eval_context
+                .duplicates
+                .get_mut(&canonized_formula_with_domains)
+                .unwrap();
+            *remaining -= 1;
+            if *remaining == 0 {
+                eval_context
+                    .duplicates
+                    .remove(&canonized_formula_with_domains);
+            } else {
+                save_to_cache = true;
+            }
         } else {
             // if the cache does not contain result for this subformula, set insert flag
             save_to_cache = true;
@@ -94,27 +177,75 @@ pub fn eval_node(
     // first lets check for special cases, which can be optimised:
     // 1) attractors
     if is_attractor_pattern(&node) {
-        let result = compute_attractor_states(graph, graph.mk_unit_colored_vertices());
+        let result = {
+            let _span = span("attractors");
+            // the search terminates early once the evaluation is cancelled
+            compute_attractor_states_with_search(
+                graph,
+                graph.mk_unit_colored_vertices(),
+                &mut |_| eval_context.is_cancelled(),
+                &mut |_| {},
+            )
+        };
+        update_peak_bdd_size(eval_context, &result);
         if save_to_cache {
-            eval_context
-                .cache
-                .insert(canonized_formula_with_domains, (result.clone(), renaming));
+            if canonical_domains.is_empty() {
+                eval_context.retain_intermediate(&canonized_form, &result);
+            }
+            eval_context.insert_cached(canonized_formula_with_domains, &result, renaming);
         }
         return result;
     }
     // 2) fixed-points
     if is_fixed_point_pattern(&node) {
+        update_peak_bdd_size(eval_context, steady_states);
         return steady_states.clone();
     }
+    // 3) existence of attractors intersecting a target (only if there are no free variables)
+    if canonical_domains.is_empty() {
+        if let Some(target_node) = get_target_attractor_pattern(&node) {
+            let target = eval_node(target_node.clone(), graph, eval_context, steady_states);
+            let colors = {
+                let _span = span("target_attractors");
+                compute_target_attractor_colors(graph, graph.mk_unit_colored_vertices(), &target)
+            };
+            let result = graph.mk_unit_colored_vertices().intersect_colors(&colors);
+            update_peak_bdd_size(eval_context, &result);
+            if save_to_cache {
+                eval_context.retain_intermediate(&canonized_form, &result);
+                eval_context.insert_cached(canonized_formula_with_domains, &result, renaming);
+            }
+            return result;
+        }
+    }
 
+    // the sub-formula is only needed to label the snapshots of its fixed point (if any)
+    let formula = match eval_context.snapshots {
+        Some(_) => node.to_string(),
+        None => String::new(),
+    };
     let result = match node.node_type {
         NodeType::Terminal(atom) => match atom {
             Atomic::True => graph.mk_unit_colored_vertices(),
             Atomic::False => graph.mk_empty_colored_vertices(),
             Atomic::Var(name) => eval_hctl_var(graph, name.as_str()),
             Atomic::Prop(name) => eval_prop(graph, &name),
+            Atomic::State(values, _) => eval_state_literal(graph, &values),
             // should not be reachable, as wild-card nodes are always evaluated earlier using cache
             Atomic::WildCardProp(_) => unreachable!(),
+            Atomic::WildCardRelation(name, vars) => {
+                // raw sets of relations are provided as the context of wild-card propositions
+                let relation_key = (format!("%{name}%"), VarDomainMap::new());
+                match eval_context.cache.get(&relation_key) {
+                    Some((relation, _)) => eval_wild_card_relation(graph, relation, &vars),
+                    None => {
+                        eval_context.fail(format!(
+                            "Wild-card relation `{name}` lacks evaluation context."
+                        ));
+                        graph.mk_empty_colored_vertices()
+                    }
+                }
+            }
         },
         NodeType::Unary(op, child) => match op {
             UnaryOp::Not => eval_neg(
@@ -131,24 +262,42 @@ pub fn eval_node(
                 &eval_node(*child, graph, eval_context, steady_states),
                 steady_states,
             ),
-            UnaryOp::EF => eval_ef_saturated(
+            UnaryOp::EXVar(var) => eval_ex_var(
                 graph,
                 &eval_node(*child, graph, eval_context, steady_states),
+                &var,
+            ),
+            UnaryOp::AXVar(var) => eval_ax_var(
+                graph,
+                &eval_node(*child, graph, eval_context, steady_states),
+                &var,
+            ),
+            UnaryOp::EF => eval_reachability(
+                eval_context.algorithms.ef,
+                graph,
+                &graph.mk_unit_colored_vertices(),
+                &eval_node(*child, graph, eval_context, steady_states),
+                &mut fixed_point_observer(eval_context, &formula, true),
             ),
             UnaryOp::AF => eval_af(
                 graph,
                 &eval_node(*child, graph, eval_context, steady_states),
                 steady_states,
             ),
-            UnaryOp::EG => eval_eg(
+            UnaryOp::EG => eval_eg_observed(
                 graph,
                 &eval_node(*child, graph, eval_context, steady_states),
                 steady_states,
+                &mut fixed_point_observer(eval_context, &formula, false),
             ),
             UnaryOp::AG => eval_ag(
                 graph,
                 &eval_node(*child, graph, eval_context, steady_states),
             ),
+            UnaryOp::EP => eval_ep(
+                graph,
+                &eval_node(*child, graph, eval_context, steady_states),
+            ),
         },
         NodeType::Binary(op, left, right) => {
             match op {
@@ -171,16 +320,19 @@ pub fn eval_node(
                     &eval_node(*left, graph, eval_context, steady_states),
                     &eval_node(*right, graph, eval_context, steady_states),
                 ),
-                BinaryOp::EU => eval_eu_saturated(
+                BinaryOp::EU => eval_reachability(
+                    eval_context.algorithms.eu,
                     graph,
                     &eval_node(*left, graph, eval_context, steady_states),
                     &eval_node(*right, graph, eval_context, steady_states),
+                    &mut fixed_point_observer(eval_context, &formula, true),
                 ),
-                BinaryOp::AU => eval_au(
+                BinaryOp::AU => eval_au_observed(
                     graph,
                     &eval_node(*left, graph, eval_context, steady_states),
                     &eval_node(*right, graph, eval_context, steady_states),
                     steady_states,
+                    &mut fixed_point_observer(eval_context, &formula, true),
                 ),
                 BinaryOp::EW => eval_ew(
                     graph,
@@ -195,13 +347,16 @@ pub fn eval_node(
                 ),
             }
         }
-        NodeType::Hybrid(HybridOp::Jump, var, _, child) => {
+        NodeType::Hybrid(HybridOp::Jump, var, maybe_domain, child) => {
             // special case for hybrid operator Jump (it is not quantifier, so it is different than the rest)
-            // mainly, we dont have to worry about the domain (which complicates other hybrid operators)
+            // mainly, its domain just restricts the states we jump to (instead of the variable)
+            let domain_set =
+                maybe_domain.map(|domain| get_domain_set(&domain, graph, eval_context));
             eval_jump(
                 graph,
                 &eval_node(*child, graph, eval_context, steady_states),
                 var.as_str(),
+                domain_set.as_ref(),
             )
         }
         NodeType::Hybrid(op, var, maybe_domain, child) => {
@@ -210,7 +365,7 @@ pub fn eval_node(
             // add the variable's domain to the eval context (the variable will be free in the sub-formulae)
             eval_context
                 .free_var_domains
-                .insert(var.clone(), maybe_domain.clone());
+                .insert(var.clone(), maybe_domain.as_ref().map(VarDomain::label));
 
             // two different options depending on if the quantified variable has restricted domain or not
             let res = match maybe_domain {
@@ -224,8 +379,9 @@ pub fn eval_node(
                     *child,
                 ),
                 Some(domain) => {
-                    // get a domain set from EvalContext, can use unwrap as it is previously checked
-                    let domain_set = eval_context.domain_raw_sets.get(domain.as_str()).unwrap();
+                    // only the colors of the current graph are relevant
+                    let domain_set = get_domain_set(&domain, graph, eval_context)
+                        .intersect_colors(graph.unit_colors());
 
                     // check edge case of an empty domain (in that case we cannot restrict the domain,
                     // there would be an error)
@@ -238,18 +394,41 @@ pub fn eval_node(
                         };
                     }
 
+                    // the same holds color-wise: the colors with an empty domain are decided right
+                    // away, and the whole sub-tree (including the quantifier) is evaluated without them
+                    let empty_colors = graph.unit_colors().minus(&domain_set.colors());
+                    let graph_in_domain = if empty_colors.is_empty() {
+                        None
+                    } else {
+                        eval_context.statistics.num_pruned_domains += 1;
+                        let in_domain = graph
+                            .unit_colored_vertices()
+                            .intersect_colors(&domain_set.colors());
+                        Some(restrict_stg_unit_bdd(graph, &in_domain))
+                    };
+                    let graph_in_domain = graph_in_domain.as_ref().unwrap_or(graph);
+
                     // restrict the var domain in unit BDD of the graph
-                    let var_domain = compute_valid_domain_for_var(graph, domain_set, var.as_str());
-                    let restricted_graph = restrict_stg_unit_bdd(graph, &var_domain);
-                    eval_hybrid_quantifier(
-                        graph,
+                    let var_domain =
+                        compute_valid_domain_for_var(graph_in_domain, &domain_set, var.as_str());
+                    let restricted_graph = restrict_stg_unit_bdd(graph_in_domain, &var_domain);
+                    let result = eval_hybrid_quantifier(
+                        graph_in_domain,
                         &restricted_graph,
                         eval_context,
                         steady_states,
                         op.clone(),
                         var.clone(),
                         *child,
-                    )
+                    );
+                    match op {
+                        HybridOp::Forall if !empty_colors.is_empty() => result.union(
+                            &graph
+                                .unit_colored_vertices()
+                                .intersect_colors(&empty_colors),
+                        ),
+                        _ => result,
+                    }
                 }
             };
 
@@ -259,15 +438,114 @@ pub fn eval_node(
         }
     };
 
+    update_peak_bdd_size(eval_context, &result);
     // save result to cache if needed
     if save_to_cache {
-        eval_context
-            .cache
-            .insert(canonized_formula_with_domains, (result.clone(), renaming));
+        // results of closed sub-formulae can be retained for later runs
+        if canonical_domains.is_empty() {
+            eval_context.retain_intermediate(&canonized_form, &result);
+        }
+        eval_context.insert_cached(canonized_formula_with_domains, &result, renaming);
     }
     result
 }
 
+/// Evaluate the EU operator (or EF, if `phi1` is the unit set) using the selected `algorithm`,
+/// passing the current approximations to the `observer`.
+fn eval_reachability(
+    algorithm: ReachabilityAlgorithm,
+    graph: &SymbolicAsyncGraph,
+    phi1: &GraphColoredVertices,
+    phi2: &GraphColoredVertices,
+    observer: &mut dyn FnMut(usize, &GraphColoredVertices) -> bool,
+) -> GraphColoredVertices {
+    match algorithm {
+        ReachabilityAlgorithm::Saturation => {
+            eval_eu_saturated_observed(graph, phi1, phi2, observer)
+        }
+        ReachabilityAlgorithm::Frontier => eval_eu_frontier(graph, phi1, phi2, observer),
+    }
+}
+
+/// Make an observer of the iterations of the fixed point of the `formula`, passing its snapshots
+/// to the snapshot hook (if attached, see [crate::evaluation::snapshots]), and interrupting the
+/// fixed point once the evaluation is cancelled (see [EvalContext::set_cancellation]).
+fn fixed_point_observer<'a>(
+    eval_context: &'a EvalContext,
+    formula: &'a str,
+    is_under_approximation: bool,
+) -> impl FnMut(usize, &GraphColoredVertices) -> bool + 'a {
+    move |iteration, approximation| {
+        if let Some(hook) = &eval_context.snapshots {
+            hook.observe(formula, is_under_approximation, iteration, approximation);
+        }
+        !eval_context.is_cancelled()
+    }
+}
+
+/// Update the statistic regarding the largest BDD computed so far.
+fn update_peak_bdd_size(eval_context: &mut EvalContext, result: &GraphColoredVertices) {
+    let statistics = &mut eval_context.statistics;
+    statistics.peak_bdd_size = max(statistics.peak_bdd_size, result.symbolic_size());
+}
+
+/// Compute the sets of the pending built-in domains of the `eval_context` (inline domains and
+/// fair attractors, see [EvalContext::pending_domains]) on the whole `graph`, and keep them in
+/// the `eval_context` with the other domains (under their labels). The `steady_states` are
+/// needed for the evaluation of the inline domains.
+///
+/// The domains must not be computed on a graph restricted to the domain of another variable, as
+/// their sets would then depend on that variable.
+fn eval_builtin_domains(
+    graph: &SymbolicAsyncGraph,
+    eval_context: &mut EvalContext,
+    steady_states: &GraphColoredVertices,
+) {
+    for domain in std::mem::take(&mut eval_context.pending_domains) {
+        let label = domain.label();
+        if eval_context.domain_raw_sets.contains_key(&label) {
+            continue;
+        }
+        let domain_set = match domain {
+            VarDomain::Inline(tree) => {
+                let mut domain_context = EvalContext::from_single_tree(&tree);
+                domain_context.algorithms = eval_context.algorithms;
+                domain_context.cancellation = eval_context.cancellation.clone();
+                let domain_set = eval_node(*tree, graph, &mut domain_context, steady_states);
+                if let Some(error) = domain_context.take_error() {
+                    eval_context.fail(error);
+                }
+                domain_set
+            }
+            // the built-in domain of fair attractors is computed by a specialized algorithm
+            VarDomain::Label(name) if name == FAIR_ATTRACTORS_DOMAIN => {
+                compute_fair_attractors(graph)
+            }
+            // the wild-card domains are given by the context
+            VarDomain::Label(_) => continue,
+        };
+        eval_context.domain_raw_sets.insert(label, domain_set);
+    }
+}
+
+/// Get the (raw) set of states of a variable's `domain` from the `eval_context`. The sets of
+/// the built-in domains are computed before the formula (see [eval_builtin_domains]), and the
+/// sets of the wild-card domains are given by the context.
+fn get_domain_set(
+    domain: &VarDomain,
+    graph: &SymbolicAsyncGraph,
+    eval_context: &mut EvalContext,
+) -> GraphColoredVertices {
+    let label = domain.label();
+    match eval_context.domain_raw_sets.get(&label) {
+        Some(domain_set) => domain_set.clone(),
+        None => {
+            eval_context.fail(format!("Domain `{domain}` lacks evaluation context."));
+            graph.mk_empty_colored_vertices()
+        }
+    }
+}
+
 /// Wrapper to recursively evaluate the formula represented by a sub-tree beginning at hybrid node
 /// specified by its `operator`, `variable` and `child_node`.
 ///
@@ -337,6 +615,42 @@ fn is_attractor_pattern(node: &HctlTreeNode) -> bool {
     }
 }
 
+/// Check whether a node represents the formula pattern for the existence of attractors
+/// intersecting a target `3{x}: @{x}: (AG EF {x}) & T` (with the conjuncts in any order),
+/// where `T` does not contain `x`. If so, return the target sub-formula `T`.
+/// This recognition step is used to later optimize the search for target attractors.
+fn get_target_attractor_pattern(node: &HctlTreeNode) -> Option<&HctlTreeNode> {
+    let NodeType::Hybrid(HybridOp::Exists, var1, None, child1) = &node.node_type else {
+        return None;
+    };
+    let NodeType::Hybrid(HybridOp::Jump, var2, None, child2) = &child1.node_type else {
+        return None;
+    };
+    let NodeType::Binary(BinaryOp::And, left, right) = &child2.node_type else {
+        return None;
+    };
+    let is_in_attractor = |node: &HctlTreeNode| match &node.node_type {
+        NodeType::Unary(UnaryOp::AG, child3) => match &child3.node_type {
+            NodeType::Unary(UnaryOp::EF, child4) => {
+                matches!(&child4.node_type, NodeType::Terminal(Atomic::Var(var3)) if var3 == var1)
+            }
+            _ => false,
+        },
+        _ => false,
+    };
+    let target = if is_in_attractor(left) {
+        right
+    } else if is_in_attractor(right) {
+        left
+    } else {
+        return None;
+    };
+    if var1 != var2 || contains_var(target, var1) {
+        return None;
+    }
+    Some(target)
+}
+
 /// Check whether a node represents the formula pattern for fixed-points `!{x}: AX {x}`.
 /// This recognition step is used to later optimize the fixed-point pattern.
 fn is_fixed_point_pattern(node: &HctlTreeNode) -> bool {
@@ -352,23 +666,36 @@ fn is_fixed_point_pattern(node: &HctlTreeNode) -> bool {
     }
 }
 
+/// Wrapper for the computation of fair attractor states, i.e., states of SCCs in which a run can
+/// stay forever, assuming that every variable enabled infinitely often eventually fires.
+/// Used to evaluate quantifiers over fair attractors (`3{x} in fair_attractors:`). Unlike the
+/// attractor pattern, fair attractors have no HCTL pattern which could be detected in formulae.
+pub fn compute_fair_attractors(graph: &SymbolicAsyncGraph) -> GraphColoredVertices {
+    let _span = span("fair_attractors");
+    compute_fair_attractor_states(graph, graph.mk_unit_colored_vertices())
+}
+
 /// Wrapper for the computation of steady states.
 /// Steady states are used for explicitly adding self-loops during the EX computation.
 /// Can also be used as optimised procedure for formula `!{x}: AX {x}`.
 pub fn compute_steady_states(graph: &SymbolicAsyncGraph) -> GraphColoredVertices {
+    let _span = span("steady_states");
     FixedPoints::symbolic(graph, &graph.mk_unit_colored_vertices())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::evaluation::algorithm::{is_attractor_pattern, is_fixed_point_pattern};
+    use crate::evaluation::algorithm::{
+        get_target_attractor_pattern, is_attractor_pattern, is_fixed_point_pattern,
+    };
     use crate::preprocessing::hctl_tree::*;
     use crate::preprocessing::operator_enums::*;
+    use crate::preprocessing::parser::parse_hctl_formula;
 
     #[test]
     /// Test recognition of fixed-point pattern.
     fn test_fixed_point_pattern() {
-        let tree = HctlTreeNode::mk_hybrid(
+        let tree = HctlTreeNode::mk_hybrid_with_domain(
             HctlTreeNode::mk_unary(HctlTreeNode::mk_variable("x"), UnaryOp::AX),
             "x",
             None,
@@ -380,7 +707,7 @@ mod tests {
     #[test]
     /// Test recognition of attractor pattern.
     fn test_attractor_pattern() {
-        let tree = HctlTreeNode::mk_hybrid(
+        let tree = HctlTreeNode::mk_hybrid_with_domain(
             HctlTreeNode::mk_unary(
                 HctlTreeNode::mk_unary(HctlTreeNode::mk_variable("x"), UnaryOp::EF),
                 UnaryOp::AG,
@@ -391,4 +718,28 @@ mod tests {
         );
         assert!(is_attractor_pattern(&tree));
     }
+
+    #[test]
+    /// Test recognition of the pattern for attractors intersecting a target.
+    fn test_target_attractor_pattern() {
+        let target = parse_hctl_formula("a & EX b").unwrap();
+        for formula in [
+            "3{x}: @{x}: (AG EF {x}) & (a & EX b)",
+            "3{x}: @{x}: (a & EX b) & AG EF {x}",
+        ] {
+            let tree = parse_hctl_formula(formula).unwrap();
+            assert_eq!(get_target_attractor_pattern(&tree), Some(&target));
+        }
+
+        for formula in [
+            "3{x}: @{x}: (AG EF {x}) & (a & EX {x})",
+            "3{x}: @{y}: (AG EF {x}) & a",
+            "3{x}: @{x}: (AG EF {x}) | a",
+            "3{x}: (@{x}: AG EF {x}) & a",
+            "!{x}: @{x}: (AG EF {x}) & a",
+        ] {
+            let tree = parse_hctl_formula(formula).unwrap();
+            assert!(get_target_attractor_pattern(&tree).is_none());
+        }
+    }
 }
@@ -0,0 +1,190 @@
+//! Introspection of the evaluation, recording the results of individual sub-formulae.
+//!
+//! When enabled in the [EvalContext] (see [EvalContext::enable_annotation]), each call of
+//! [eval_node] records the evaluated sub-formula together with the size of its result and the
+//! time it took, giving the syntactic tree annotated at each node. This helps to find out why a
+//! property does not hold, e.g., which sub-formula first collapses to the empty set (see
+//! [AnnotatedNode::find_collapse]).
+//!
+//! [EvalContext]: crate::evaluation::eval_context::EvalContext
+//! [EvalContext::enable_annotation]: crate::evaluation::eval_context::EvalContext::enable_annotation
+//! [eval_node]: crate::evaluation::algorithm::eval_node
+
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use std::time::Duration;
+
+/// Node of the syntactic tree annotated with the result of its evaluation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotatedNode {
+    /// The evaluated sub-formula.
+    pub formula: String,
+    /// Number of states satisfying the sub-formula (for some color and some values of its free
+    /// HCTL variables), approximated for large sets.
+    pub num_states: f64,
+    /// Number of colors for which the sub-formula is satisfied in some state (approximated).
+    pub num_colors: f64,
+    /// Size (number of BDD nodes) of the result.
+    pub symbolic_size: usize,
+    /// Time of the evaluation of the sub-formula, including its children.
+    pub duration: Duration,
+    /// True if the result was taken from the cache (then, the children are not evaluated).
+    pub from_cache: bool,
+    /// The full (unsanitized) result, if the results were requested to be kept.
+    pub result: Option<GraphColoredVertices>,
+    /// Annotated children of the node, in the order of their evaluation.
+    pub children: Vec<AnnotatedNode>,
+}
+
+// the numbers of states and colors are never NaN
+impl Eq for AnnotatedNode {}
+
+impl AnnotatedNode {
+    /// Check whether the result of the sub-formula is empty.
+    pub fn is_empty(&self) -> bool {
+        self.num_states == 0.0
+    }
+
+    /// Find the first evaluated node (in the order in which the results were computed) whose
+    /// result is empty, but none of its children has an empty result, i.e., the sub-formula that
+    /// first collapses to the empty set. Return `None` if no result in the tree is empty.
+    pub fn find_collapse(&self) -> Option<&AnnotatedNode> {
+        for child in &self.children {
+            if let Some(node) = child.find_collapse() {
+                return Some(node);
+            }
+        }
+        if self.is_empty() && !self.children.iter().any(|c| c.is_empty()) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    /// Format the annotated tree as an indented text, one sub-formula per line, with the numbers
+    /// of states and colors, the BDD size, and the time of its evaluation.
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        self.collect_lines(0, &mut lines);
+        lines.join("\n")
+    }
+
+    fn collect_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        lines.push(format!(
+            "{}{} [states: {}, colors: {}, BDD nodes: {}, time: {}ms{}]",
+            "  ".repeat(depth),
+            self.formula,
+            self.num_states,
+            self.num_colors,
+            self.symbolic_size,
+            self.duration.as_millis(),
+            if self.from_cache { ", cached" } else { "" },
+        ));
+        for child in &self.children {
+            child.collect_lines(depth + 1, lines);
+        }
+    }
+}
+
+/// Recorder of the annotated trees, collecting the nodes as the evaluation proceeds.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EvalAnnotator {
+    /// Keep the full results in the annotated nodes.
+    keep_results: bool,
+    /// Children collected for each node that is currently being evaluated, with the finished
+    /// (root) trees at the bottom.
+    stack: Vec<Vec<AnnotatedNode>>,
+}
+
+impl EvalAnnotator {
+    /// Create a new recorder, keeping the full results of the sub-formulae if `keep_results`.
+    pub fn new(keep_results: bool) -> EvalAnnotator {
+        EvalAnnotator {
+            keep_results,
+            stack: vec![Vec::new()],
+        }
+    }
+
+    /// Start recording the evaluation of a new node (a child of the current one).
+    pub(crate) fn open_node(&mut self) {
+        self.stack.push(Vec::new());
+    }
+
+    /// Finish the evaluation of the current node, whose sub-formula evaluated to the `result`.
+    pub(crate) fn close_node(
+        &mut self,
+        graph: &SymbolicAsyncGraph,
+        formula: String,
+        result: &GraphColoredVertices,
+        duration: Duration,
+        cache_hit: bool,
+    ) {
+        let children = self.stack.pop().unwrap_or_default();
+        // project out the free HCTL variables, so that the states and colors can be counted
+        let context = graph.symbolic_context();
+        let projected = GraphColoredVertices::new(
+            result.as_bdd().exists(context.all_extra_state_variables()),
+            context,
+        );
+        let node = AnnotatedNode {
+            formula,
+            num_states: projected.vertices().approx_cardinality(),
+            num_colors: projected.colors().approx_cardinality(),
+            symbolic_size: result.symbolic_size(),
+            duration,
+            from_cache: cache_hit && children.is_empty(),
+            result: if self.keep_results {
+                Some(result.clone())
+            } else {
+                None
+            },
+            children,
+        };
+        if self.stack.is_empty() {
+            self.stack.push(Vec::new());
+        }
+        self.stack.last_mut().unwrap().push(node);
+    }
+
+    /// Take the annotated trees of all the formulae evaluated so far (in their order).
+    pub fn take_trees(&mut self) -> Vec<AnnotatedNode> {
+        let trees = std::mem::take(&mut self.stack[0]);
+        self.stack.truncate(1);
+        trees
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula_annotated;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test annotating the syntactic tree with the results of the sub-formulae.
+    fn test_annotated_evaluation() {
+        // `a` and `b` are constant, `c` follows `a`
+        let bn = BooleanNetwork::try_from("a -> a\nb -> b\na -> c\n$a: a\n$b: b\n$c: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        let (result, tree) =
+            model_check_formula_annotated("AG (a & c) & EF ~a", &graph, false).unwrap();
+        assert!(result.is_empty());
+        assert_eq!(tree.children.len(), 2);
+        assert!(tree.children.iter().all(|c| !c.is_empty()));
+        assert!(tree.result.is_none());
+        // the conjunction itself is the first sub-formula with an empty result
+        let collapse = tree.find_collapse().unwrap();
+        assert_eq!(collapse.formula, tree.formula);
+        assert_eq!(tree.to_text().lines().count(), 8);
+
+        // here, the first collapse is deeper in the tree
+        let (_, tree) = model_check_formula_annotated("(AG c & ~a) | b", &graph, true).unwrap();
+        assert_eq!(tree.num_states, 4.0);
+        assert!(tree.result.is_some());
+        let collapse = tree.find_collapse().unwrap();
+        assert_eq!(collapse.formula, "((AG c) & (~a))");
+        assert_eq!(collapse.num_colors, 0.0);
+        assert!(tree.children[0].is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Three-valued (may/must) evaluation of HCTL formulae on parametrized networks.
+//!
+//! Instead of computing the exact set of states and colors satisfying a formula, we compute
+//! two color-independent sets of states: an under-approximation (states where the formula
+//! holds for all colors) and an over-approximation (states where the formula may hold for some
+//! color). States outside the over-approximation definitely violate the formula, and states in
+//! the over-approximation but not in the under-approximation are unknown.
+//!
+//! The transitions are abstracted into `must` transitions (present for all colors) and `may`
+//! transitions (present for some color). Since all intermediate sets are color-independent,
+//! the evaluation is typically much cheaper than the exact one on heavily parametrized networks.
+
+use crate::evaluation::algorithm::compute_steady_states;
+use crate::evaluation::hctl_operators_eval::*;
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::operator_enums::*;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+
+/// Pair of color-independent sets `(under, over)` approximating the exact result.
+pub type Approximation = (GraphColoredVertices, GraphColoredVertices);
+
+/// Pre-computed data used during the three-valued evaluation.
+pub struct ApproxContext<'a> {
+    graph: &'a SymbolicAsyncGraph,
+    /// States that are steady for all colors (no `may` transitions).
+    must_steady_states: GraphColoredVertices,
+    /// States that are steady for some color (and thus have no `must` transitions).
+    may_steady_states: GraphColoredVertices,
+}
+
+impl<'a> ApproxContext<'a> {
+    /// Pre-compute the steady states needed for the evaluation on the given `graph`.
+    pub fn new(graph: &'a SymbolicAsyncGraph) -> ApproxContext<'a> {
+        let steady_states = compute_steady_states(graph);
+        ApproxContext {
+            graph,
+            must_steady_states: for_all_colors(graph, &steady_states),
+            may_steady_states: exists_color(graph, &steady_states),
+        }
+    }
+
+    /// Evaluate EX on both approximations. The under-approximation uses `must` transitions
+    /// (and states steady for all colors), the over-approximation uses `may` transitions (and
+    /// states steady for some color).
+    fn ex(&self, (under, over): &Approximation) -> Approximation {
+        let mut must_pre = self.graph.mk_empty_colored_vertices();
+        for var in self.graph.variables() {
+            let var_pre = self.graph.var_pre(var, under);
+            must_pre = must_pre.union(&for_all_colors(self.graph, &var_pre));
+        }
+        let may_pre = exists_color(self.graph, &self.graph.pre(over));
+        (
+            must_pre.union(&under.intersect(&self.must_steady_states)),
+            may_pre.union(&over.intersect(&self.may_steady_states)),
+        )
+    }
+
+    /// Evaluate the transition-labelled EX on both approximations, analogously to [Self::ex], but
+    /// only using transitions that update the given variable (and no self-loops).
+    fn ex_var(&self, var_name: &str, (under, over): &Approximation) -> Approximation {
+        let var = self
+            .graph
+            .symbolic_context()
+            .find_network_variable(var_name)
+            .unwrap();
+        (
+            for_all_colors(self.graph, &self.graph.var_pre(var, under)),
+            exists_color(self.graph, &self.graph.var_pre(var, over)),
+        )
+    }
+
+    /// Evaluate the past step (existence of a predecessor satisfying phi) on both
+    /// approximations, analogously to [Self::ex].
+    fn ey(&self, (under, over): &Approximation) -> Approximation {
+        let mut must_post = self.graph.mk_empty_colored_vertices();
+        for var in self.graph.variables() {
+            let var_post = self.graph.var_post(var, under);
+            must_post = must_post.union(&for_all_colors(self.graph, &var_post));
+        }
+        let may_post = exists_color(self.graph, &self.graph.post(over));
+        (must_post, may_post)
+    }
+
+    /// Evaluate AX as `not EX(not phi)`.
+    fn ax(&self, phi: &Approximation) -> Approximation {
+        self.neg(&self.ex(&self.neg(phi)))
+    }
+
+    /// Negation swaps (and complements) the under- and over-approximation.
+    fn neg(&self, (under, over): &Approximation) -> Approximation {
+        (eval_neg(self.graph, over), eval_neg(self.graph, under))
+    }
+
+    /// Compute the least (or greatest) fixpoint of `step`, starting from `initial`.
+    fn fixpoint<F>(&self, initial: Approximation, step: F) -> Approximation
+    where
+        F: Fn(&Approximation) -> Approximation,
+    {
+        let mut current = initial;
+        loop {
+            let next = step(&current);
+            if next.0.as_bdd() == current.0.as_bdd() && next.1.as_bdd() == current.1.as_bdd() {
+                return current;
+            }
+            current = next;
+        }
+    }
+
+    /// Evaluate `E phi1 U phi2` as `mu Z. phi2 | (phi1 & EX Z)`.
+    fn eu(&self, phi1: &Approximation, phi2: &Approximation) -> Approximation {
+        self.fixpoint(phi2.clone(), |z| or(phi2, &and(phi1, &self.ex(z))))
+    }
+
+    /// Evaluate `A phi1 U phi2` as `mu Z. phi2 | (phi1 & AX Z)`.
+    fn au(&self, phi1: &Approximation, phi2: &Approximation) -> Approximation {
+        self.fixpoint(phi2.clone(), |z| or(phi2, &and(phi1, &self.ax(z))))
+    }
+
+    /// Evaluate `EP phi` as `mu Z. phi | EY Z`, where `EY` is the past step.
+    fn ep(&self, phi: &Approximation) -> Approximation {
+        self.fixpoint(phi.clone(), |z| or(phi, &self.ey(z)))
+    }
+
+    /// Evaluate `EG phi` as `nu Z. phi & EX Z`.
+    fn eg(&self, phi: &Approximation) -> Approximation {
+        self.fixpoint(phi.clone(), |z| and(phi, &self.ex(z)))
+    }
+
+    /// Recursively evaluate the sub-formula represented by a `node` (of a syntactic tree).
+    ///
+    /// Hybrid operators with restricted domains and wild-card propositions are not supported.
+    pub fn eval_node(&self, node: HctlTreeNode) -> Result<Approximation, String> {
+        let graph = self.graph;
+        let result = match node.node_type {
+            NodeType::Terminal(atom) => {
+                let set = match atom {
+                    Atomic::True => graph.mk_unit_colored_vertices(),
+                    Atomic::False => graph.mk_empty_colored_vertices(),
+                    Atomic::Var(name) => eval_hctl_var(graph, name.as_str()),
+                    Atomic::Prop(name) => eval_prop(graph, &name),
+                    Atomic::State(values, _) => eval_state_literal(graph, &values),
+                    Atomic::WildCardProp(name) | Atomic::WildCardRelation(name, _) => {
+                        return Err(format!(
+                        "Wild-card prop `{name}` is not supported in approximate model checking."
+                    ))
+                    }
+                };
+                (set.clone(), set)
+            }
+            NodeType::Unary(op, child) => {
+                let phi = self.eval_node(*child)?;
+                match op {
+                    UnaryOp::Not => self.neg(&phi),
+                    UnaryOp::EX => self.ex(&phi),
+                    UnaryOp::AX => self.ax(&phi),
+                    UnaryOp::EXVar(var) => self.ex_var(&var, &phi),
+                    // AX_{v} phi == not EX_{v} (not phi)
+                    UnaryOp::AXVar(var) => self.neg(&self.ex_var(&var, &self.neg(&phi))),
+                    UnaryOp::EF => self.eu(&(unit(graph), unit(graph)), &phi),
+                    UnaryOp::AF => self.au(&(unit(graph), unit(graph)), &phi),
+                    UnaryOp::EG => self.eg(&phi),
+                    // AG phi == not EF(not phi)
+                    UnaryOp::AG => {
+                        let ef = self.eu(&(unit(graph), unit(graph)), &self.neg(&phi));
+                        self.neg(&ef)
+                    }
+                    UnaryOp::EP => self.ep(&phi),
+                }
+            }
+            NodeType::Binary(op, left, right) => {
+                let phi1 = self.eval_node(*left)?;
+                let phi2 = self.eval_node(*right)?;
+                match op {
+                    BinaryOp::And => and(&phi1, &phi2),
+                    BinaryOp::Or => or(&phi1, &phi2),
+                    BinaryOp::Imp => or(&self.neg(&phi1), &phi2),
+                    BinaryOp::Iff => {
+                        or(&and(&phi1, &phi2), &and(&self.neg(&phi1), &self.neg(&phi2)))
+                    }
+                    BinaryOp::Xor => {
+                        or(&and(&phi1, &self.neg(&phi2)), &and(&self.neg(&phi1), &phi2))
+                    }
+                    BinaryOp::EU => self.eu(&phi1, &phi2),
+                    BinaryOp::AU => self.au(&phi1, &phi2),
+                    // E phi1 W phi2 == not (A (not phi1) U (not phi2))
+                    BinaryOp::EW => self.neg(&self.au(&self.neg(&phi1), &self.neg(&phi2))),
+                    // A phi1 W phi2 == not (E (not phi1) U (not phi2))
+                    BinaryOp::AW => self.neg(&self.eu(&self.neg(&phi1), &self.neg(&phi2))),
+                }
+            }
+            NodeType::Hybrid(op, var, maybe_domain, child) => {
+                if maybe_domain.is_some() {
+                    return Err(
+                        "Restricted domains are not supported in approximate model checking."
+                            .to_string(),
+                    );
+                }
+                let (under, over) = self.eval_node(*child)?;
+                let var = var.as_str();
+                match op {
+                    HybridOp::Bind => (eval_bind(graph, &under, var), eval_bind(graph, &over, var)),
+                    HybridOp::Exists => (
+                        eval_exists(graph, &under, var),
+                        eval_exists(graph, &over, var),
+                    ),
+                    HybridOp::Jump => (
+                        eval_jump(graph, &under, var, None),
+                        eval_jump(graph, &over, var, None),
+                    ),
+                    // forall x. phi == not exists x. not phi
+                    HybridOp::Forall => {
+                        let (not_under, not_over) = self.neg(&(under, over));
+                        self.neg(&(
+                            eval_exists(graph, &not_under, var),
+                            eval_exists(graph, &not_over, var),
+                        ))
+                    }
+                }
+            }
+        };
+        Ok(result)
+    }
+}
+
+/// Unit set of the graph.
+fn unit(graph: &SymbolicAsyncGraph) -> GraphColoredVertices {
+    graph.mk_unit_colored_vertices()
+}
+
+/// Component-wise intersection of two approximations.
+fn and(phi1: &Approximation, phi2: &Approximation) -> Approximation {
+    (phi1.0.intersect(&phi2.0), phi1.1.intersect(&phi2.1))
+}
+
+/// Component-wise union of two approximations.
+fn or(phi1: &Approximation, phi2: &Approximation) -> Approximation {
+    (phi1.0.union(&phi2.0), phi1.1.union(&phi2.1))
+}
+
+/// Compute the (color-independent) set of states that are in `set` for all colors.
+pub fn for_all_colors(
+    graph: &SymbolicAsyncGraph,
+    set: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    let unit = graph.mk_unit_colored_vertices();
+    unit.minus(&unit.intersect_vertices(&unit.minus(set).vertices()))
+}
+
+/// Compute the (color-independent) set of states that are in `set` for some color.
+pub fn exists_color(
+    graph: &SymbolicAsyncGraph,
+    set: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    graph
+        .mk_unit_colored_vertices()
+        .intersect_vertices(&set.vertices())
+}
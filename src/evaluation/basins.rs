@@ -0,0 +1,95 @@
+//! Computation of the basins of attraction of a target set (such as an attractor).
+//!
+//! All basins are computed for all colors at once (i.e., the basin of each color is given by the
+//! states of the resulting set with that color). The resulting sets can be used as the context
+//! of wild-card propositions in extended formulae (see [basins_context]).
+
+use crate::_aeon_algorithms::saturated_reachability::reach_bwd;
+use crate::evaluation::LabelToSetMap;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::VariableId;
+
+/// Compute the weak basin of the `target` set, i.e., all states from which some path reaches the
+/// target (including the target itself).
+///
+/// The computation uses the saturation-based backward reachability.
+pub fn weak_basin(
+    graph: &SymbolicAsyncGraph,
+    target: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    let unit = graph.mk_unit_colored_vertices();
+    let variables: Vec<VariableId> = graph.variables().collect();
+    reach_bwd(graph, &target.intersect(&unit), &unit, &variables)
+}
+
+/// Compute the strong basin of the `target` set, i.e., all states from which every reachable
+/// state can still reach the target. For an attractor, these are exactly the states from which
+/// no other attractor is reachable.
+///
+/// The strong basin is the weak basin without the states that can leave it.
+pub fn strong_basin(
+    graph: &SymbolicAsyncGraph,
+    target: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    let unit = graph.mk_unit_colored_vertices();
+    let variables: Vec<VariableId> = graph.variables().collect();
+    let weak = weak_basin(graph, target);
+    let escaping = reach_bwd(graph, &unit.minus(&weak), &unit, &variables);
+    weak.minus(&escaping)
+}
+
+/// Compute the weak and strong basins of each named target set, and return them labelled
+/// `{name}_weak_basin` and `{name}_strong_basin`, so that they can be used as the context of
+/// wild-card propositions.
+pub fn basins_context(graph: &SymbolicAsyncGraph, targets: &LabelToSetMap) -> LabelToSetMap {
+    let mut context = LabelToSetMap::new();
+    for (name, target) in targets {
+        context.insert(format!("{name}_weak_basin"), weak_basin(graph, target));
+        context.insert(format!("{name}_strong_basin"), strong_basin(graph, target));
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::basins::{basins_context, strong_basin, weak_basin};
+    use crate::evaluation::LabelToSetMap;
+    use crate::model_checking::{model_check_extended_formula, model_check_formula};
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test computing the weak and strong basins, and compare them with the corresponding
+    /// formulae.
+    fn test_basins() {
+        // bistable switch with two fixed points `a & ~b` and `~a & b`, if `f` holds
+        let bn = BooleanNetwork::try_from("a -| b\nb -| a\n$a: !b\n$b: !a & f").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let target = model_check_formula("a & ~b", &graph).unwrap();
+
+        let weak = weak_basin(&graph, &target);
+        let strong = strong_basin(&graph, &target);
+        let expected_weak = model_check_formula("EF (a & ~b)", &graph).unwrap();
+        let expected_strong = model_check_formula("AG EF (a & ~b)", &graph).unwrap();
+        assert_eq!(weak, expected_weak.intersect(graph.unit_colored_vertices()));
+        assert_eq!(
+            strong,
+            expected_strong.intersect(graph.unit_colored_vertices())
+        );
+        // without `f`, every state is in the strong basin, with `f`, only `a & ~b`
+        assert_eq!(weak.approx_cardinality(), 7.0);
+        assert_eq!(strong.approx_cardinality(), 5.0);
+
+        // the basins can be used as wild-cards
+        let targets = LabelToSetMap::from([("on".to_string(), target)]);
+        let context = basins_context(&graph, &targets);
+        assert_eq!(context.len(), 2);
+        let result =
+            model_check_extended_formula("%on_strong_basin% & b", &graph, &context).unwrap();
+        // states with `b` are only in the strong basin for the color without `f`
+        assert_eq!(result.approx_cardinality(), 2.0);
+    }
+}
@@ -55,6 +55,26 @@ pub fn canonize_subform(
                 canonical.push_str(format!("{ch}{{var{stack_len}}}").as_str());
                 stack_len += 1;
             }
+            // state literals contain no variables, but their values are enclosed in "{}"
+            '#' => {
+                canonical.push(ch);
+                for literal_char in subform_chars.by_ref() {
+                    canonical.push(literal_char);
+                    if literal_char == '}' {
+                        break;
+                    }
+                }
+            }
+            // transition-labelled operators (`EX_{v}`) refer to network variables, not HCTL vars
+            '_' if subform_chars.peek() == Some(&'{') => {
+                canonical.push(ch);
+                for name_char in subform_chars.by_ref() {
+                    canonical.push(name_char);
+                    if name_char == '}' {
+                        break;
+                    }
+                }
+            }
             // rename existing var to canonical form, or handle free variables
             // this includes variable names which are part of the "jump operator"
             '{' => {
@@ -154,6 +174,18 @@ mod tests {
             get_canonical_and_renaming(sub_formula2.to_string()),
             (sub_formula_canonized.to_string(), renaming2)
         );
+
+        // values of state literals are not variables
+        assert_eq!(
+            get_canonical("(#state{a=1, *} & {xx})".to_string()),
+            "(#state{a=1, *} & {var0})".to_string()
+        );
+
+        // variables of transition-labelled operators are network variables
+        assert_eq!(
+            get_canonical("(AX_{a} {xx})".to_string()),
+            "(AX_{a} {var0})".to_string()
+        );
     }
 
     #[test]
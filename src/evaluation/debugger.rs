@@ -0,0 +1,222 @@
+//! Hooks for debugging the evaluation step by step.
+//!
+//! An [EvalDebugger] attached to the [EvalContext] (see [EvalContext::attach_debugger]) is called
+//! before and after the evaluation of each sub-formula. After the evaluation, it can inspect the
+//! (partial) result of the sub-formula, i.e., its cardinality, support, or a sample of its states
+//! (see [NodeInspection]). At any point, the debugger can stop the whole evaluation.
+//!
+//! [EvalContext]: crate::evaluation::eval_context::EvalContext
+//! [EvalContext::attach_debugger]: crate::evaluation::eval_context::EvalContext::attach_debugger
+
+use crate::postprocessing::sampling::sample_states;
+
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Decision of the debugger whether to proceed with the evaluation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DebugAction {
+    /// Proceed with the evaluation.
+    Continue,
+    /// Stop the whole evaluation (the model checking then returns an error).
+    Stop,
+}
+
+/// Result of a single sub-formula, as presented to the debugger.
+pub struct NodeInspection<'a> {
+    /// The evaluated sub-formula.
+    pub formula: &'a str,
+    /// The graph on which the sub-formula is evaluated.
+    pub graph: &'a SymbolicAsyncGraph,
+    /// The (unsanitized) result of the sub-formula, possibly depending on its free HCTL variables.
+    pub result: &'a GraphColoredVertices,
+    /// Time of the evaluation of the sub-formula, including its children.
+    pub duration: Duration,
+}
+
+impl NodeInspection<'_> {
+    /// The result with the free HCTL variables projected out, i.e., the colored states that
+    /// satisfy the sub-formula for some values of its free variables.
+    pub fn projected_result(&self) -> GraphColoredVertices {
+        let context = self.graph.symbolic_context();
+        GraphColoredVertices::new(
+            self.result
+                .as_bdd()
+                .exists(context.all_extra_state_variables()),
+            context,
+        )
+    }
+
+    /// Number of states satisfying the sub-formula (for some color), approximated for large sets.
+    pub fn num_states(&self) -> f64 {
+        self.projected_result().vertices().approx_cardinality()
+    }
+
+    /// Number of colors for which the sub-formula is satisfied in some state (approximated).
+    pub fn num_colors(&self) -> f64 {
+        self.projected_result().colors().approx_cardinality()
+    }
+
+    /// Names of the symbolic variables the result depends on (the extra variables of the free
+    /// HCTL variables are named `{var}_extra_{i}`).
+    pub fn support(&self) -> Vec<String> {
+        let variables = self.graph.symbolic_context().bdd_variable_set();
+        let mut support: Vec<_> = self.result.as_bdd().support_set().into_iter().collect();
+        support.sort();
+        support
+            .into_iter()
+            .map(|var| variables.name_of(var))
+            .collect()
+    }
+
+    /// Pick `n` uniformly random states satisfying the sub-formula (see [sample_states]).
+    pub fn sample_states(&self, n: usize, seed: u64) -> Vec<Vec<bool>> {
+        // the projected result has no extra variables, so it is always compatible with the graph
+        sample_states(self.graph, &self.projected_result(), n, seed).unwrap()
+    }
+}
+
+/// Callbacks invoked during the evaluation. Both of them continue by default.
+pub trait EvalDebugger {
+    /// Called before the evaluation of the sub-formula `formula`.
+    fn before_node(&mut self, _formula: &str) -> DebugAction {
+        DebugAction::Continue
+    }
+
+    /// Called after the evaluation of a sub-formula, with its result.
+    fn after_node(&mut self, _inspection: &NodeInspection) -> DebugAction {
+        DebugAction::Continue
+    }
+}
+
+/// Debugger attached to the evaluation, with the information whether it stopped the evaluation.
+///
+/// The debugger is shared, so that the caller can access its state after the evaluation.
+#[derive(Clone)]
+pub struct DebuggerHook {
+    debugger: Arc<Mutex<dyn EvalDebugger + Send>>,
+    stopped: bool,
+}
+
+impl DebuggerHook {
+    /// Wrap the shared `debugger`.
+    pub fn new(debugger: Arc<Mutex<dyn EvalDebugger + Send>>) -> DebuggerHook {
+        DebuggerHook {
+            debugger,
+            stopped: false,
+        }
+    }
+
+    /// Check whether the debugger stopped the evaluation.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Invoke the debugger before the evaluation of the `formula`. Return `false` if the
+    /// evaluation is (or already was) stopped.
+    pub(crate) fn before_node(&mut self, formula: &str) -> bool {
+        if !self.stopped && self.debugger.lock().unwrap().before_node(formula) == DebugAction::Stop
+        {
+            self.stopped = true;
+        }
+        !self.stopped
+    }
+
+    /// Invoke the debugger after the evaluation of a sub-formula.
+    pub(crate) fn after_node(&mut self, inspection: &NodeInspection) {
+        if !self.stopped
+            && self.debugger.lock().unwrap().after_node(inspection) == DebugAction::Stop
+        {
+            self.stopped = true;
+        }
+    }
+}
+
+impl fmt::Debug for DebuggerHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DebuggerHook {{ stopped: {} }}", self.stopped)
+    }
+}
+
+impl PartialEq for DebuggerHook {
+    /// Hooks are equal if they share the same debugger.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.debugger, &other.debugger) && self.stopped == other.stopped
+    }
+}
+
+impl Eq for DebuggerHook {}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::debugger::{DebugAction, EvalDebugger, NodeInspection};
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::{model_check_formula, model_check_formula_debugged};
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use std::sync::{Arc, Mutex};
+
+    /// Debugger recording the evaluated sub-formulae, stopping at the first empty result.
+    #[derive(Default)]
+    struct Recorder {
+        started: Vec<String>,
+        finished: Vec<(String, f64, Vec<String>)>,
+        stop_on_empty: bool,
+    }
+
+    impl EvalDebugger for Recorder {
+        fn before_node(&mut self, formula: &str) -> DebugAction {
+            self.started.push(formula.to_string());
+            DebugAction::Continue
+        }
+
+        fn after_node(&mut self, inspection: &NodeInspection) -> DebugAction {
+            let num_states = inspection.num_states();
+            let samples = inspection.sample_states(3, 0);
+            assert!(num_states == 0.0 || samples.len() == 3);
+            self.finished.push((
+                inspection.formula.to_string(),
+                num_states,
+                inspection.support(),
+            ));
+            if self.stop_on_empty && num_states == 0.0 {
+                DebugAction::Stop
+            } else {
+                DebugAction::Continue
+            }
+        }
+    }
+
+    #[test]
+    /// Test pausing around the evaluation of sub-formulae, and stopping the evaluation.
+    fn test_debugger_hooks() {
+        // `a` and `b` are constant, `c` follows `a`
+        let bn = BooleanNetwork::try_from("a -> a\nb -> b\na -> c\n$a: a\n$b: b\n$c: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let formula = "(AG c & ~a) | b";
+        let result = model_check_formula_debugged(formula, &graph, recorder.clone()).unwrap();
+        assert_eq!(result, model_check_formula(formula, &graph).unwrap());
+        let recorder = recorder.lock().unwrap();
+        assert_eq!(recorder.started.len(), 7);
+        assert_eq!(recorder.finished.len(), 7);
+        // the children are finished before their parents
+        assert_eq!(recorder.finished[0].0, "c");
+        assert_eq!(recorder.finished[6].0, recorder.started[0]);
+        assert_eq!(recorder.finished[0].2, vec!["c".to_string()]);
+        assert_eq!(recorder.finished[6].1, 4.0);
+
+        // stop at the first empty result, without evaluating the rest of the formula
+        let recorder = Arc::new(Mutex::new(Recorder {
+            stop_on_empty: true,
+            ..Default::default()
+        }));
+        let result = model_check_formula_debugged(formula, &graph, recorder.clone());
+        assert!(result.is_err());
+        let recorder = recorder.lock().unwrap();
+        assert_eq!(recorder.finished.len(), 5);
+        assert_eq!(recorder.finished[4].0, "((AG c) & (~a))");
+    }
+}
@@ -1,12 +1,121 @@
 //! Contains the structure to hold useful data to speed-up the computation.
 
+use crate::evaluation::annotation::{AnnotatedNode, EvalAnnotator};
+use crate::evaluation::canonization::get_canonical_and_renaming;
+use crate::evaluation::debugger::{DebuggerHook, EvalDebugger};
 use crate::evaluation::mark_duplicates::{
     mark_duplicates_canonized_multiple, mark_duplicates_canonized_single,
 };
-use crate::evaluation::{FormulaWithDomains, LabelToSetMap, VarDomainMap, VarRenameMap};
-use crate::preprocessing::hctl_tree::HctlTreeNode;
-use biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices;
+use crate::evaluation::snapshots::SnapshotHook;
+use crate::evaluation::spilling::SpillStore;
+use crate::evaluation::{
+    FormulaWithDomains, LabelToSetMap, OperatorAlgorithms, VarDomainMap, VarRenameMap,
+};
+use crate::mc_utils::{collect_builtin_domains, is_ctl_formula};
+use crate::preprocessing::hctl_tree::{HctlTreeNode, VarDomain};
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shared flag cancelling the evaluation (see [EvalContext::set_cancellation]). The clones of
+/// the flag share the same state.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationFlag(Arc<AtomicBool>);
+
+impl CancellationFlag {
+    /// Create a new flag that is not raised.
+    pub fn new() -> CancellationFlag {
+        CancellationFlag::default()
+    }
+
+    /// Raise the flag, cancelling all the evaluations that share it.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the flag was raised.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for CancellationFlag {
+    /// Flags are equal if they share the same state.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationFlag {}
+
+/// How often are the resource limits checked during the evaluation.
+pub(crate) const LIMIT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Limits regarding the resources used by the model-checking analysis.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AnalysisLimits {
+    /// Number of threads used to evaluate the formulae. Each formula is evaluated by one thread.
+    pub num_threads: usize,
+    /// Time limit for the whole analysis.
+    pub timeout: Option<Duration>,
+    /// Limit on the memory used by the process (resident set size in MB). Only supported on Linux.
+    pub memory_limit_mb: Option<u64>,
+}
+
+impl Default for AnalysisLimits {
+    /// Single thread and no resource limits.
+    fn default() -> Self {
+        AnalysisLimits {
+            num_threads: 1,
+            timeout: None,
+            memory_limit_mb: None,
+        }
+    }
+}
+
+impl AnalysisLimits {
+    /// Check whether any of the limits is exceeded for computation started at `start_time`.
+    /// If so, return the message describing it.
+    pub(crate) fn check_exceeded(&self, start_time: Instant) -> Option<String> {
+        if let Some(timeout) = self.timeout {
+            if start_time.elapsed() > timeout {
+                return Some(format!("Time limit of {}s reached.", timeout.as_secs_f64()));
+            }
+        }
+        if let Some(memory_limit) = self.memory_limit_mb {
+            if current_memory_usage_mb().is_some_and(|usage| usage > memory_limit) {
+                return Some(format!("Memory limit of {memory_limit}MB reached."));
+            }
+        }
+        None
+    }
+}
+
+/// Get the current memory usage (resident set size) of the process in MB.
+/// Returns `None` if this information is not available (only supported on Linux).
+fn current_memory_usage_mb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let rss_line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let rss_kb = rss_line.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    Some(rss_kb / 1024)
+}
+
+/// Statistics collected during the evaluation, useful for benchmarking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvalStatistics {
+    /// Number of sub-formulae whose results were taken from the cache.
+    pub num_cache_hits: usize,
+    /// Size (number of BDD nodes) of the largest result computed for any sub-formula.
+    pub peak_bdd_size: usize,
+    /// Number of cached results that were spilled to disk.
+    pub num_spilled_sets: usize,
+    /// Number of evaluated quantifiers whose domain was empty for some (but not all) colors,
+    /// which were then removed from the evaluation of the quantified sub-formula.
+    pub num_pruned_domains: usize,
+}
 
 /// Struct holding information for efficient caching during the main computation.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -22,6 +131,33 @@ pub struct EvalContext {
     /// Mapping the sub-formula's `free variable names` their `domain labels` (if specified).
     /// The domains are needed if we are to compare two sub-formulae with free variables for equivalence.
     pub free_var_domains: VarDomainMap,
+    /// Statistics collected during the evaluation.
+    pub statistics: EvalStatistics,
+    /// Recorder of the annotated syntactic trees, if the annotation is enabled.
+    pub annotator: Option<EvalAnnotator>,
+    /// Debugger invoked around the evaluation of each sub-formula, if attached.
+    pub debugger: Option<DebuggerHook>,
+    /// Receiver of the snapshots of the running fixed points, if attached.
+    pub snapshots: Option<SnapshotHook>,
+    /// Algorithms used to evaluate the individual temporal operators.
+    pub algorithms: OperatorAlgorithms,
+    /// Flag signalling that the evaluation is cancelled, if set (see [EvalContext::set_cancellation]).
+    pub cancellation: Option<CancellationFlag>,
+    /// Results of the cached (duplicate) closed sub-formulae, paired with their canonical form,
+    /// if they are to be retained after the evaluation.
+    pub intermediates: Option<Vec<(String, GraphColoredVertices)>>,
+    /// Storage of the large cached results spilled to disk, if the spilling is enabled.
+    pub spill: Option<SpillStore>,
+    /// True if all evaluated formulae are pure CTL formulae (see [is_ctl_formula]), so that the
+    /// canonization of their sub-formulae (renaming of HCTL variables) can be skipped.
+    pub ctl_only: bool,
+    /// Error that interrupted the evaluation, if any (see [EvalContext::take_error]).
+    pub error: Option<String>,
+    /// Built-in domains of the formulae (inline domains and fair attractors, see
+    /// [collect_builtin_domains]) whose sets are not computed yet. They are computed on the whole
+    /// graph before the formulae themselves (at the start of the first evaluation), and then
+    /// kept in `domain_raw_sets`.
+    pub pending_domains: Vec<VarDomain>,
 }
 
 impl EvalContext {
@@ -32,6 +168,17 @@ impl EvalContext {
             cache: HashMap::new(),
             domain_raw_sets: HashMap::new(),
             free_var_domains: VarDomainMap::new(),
+            statistics: EvalStatistics::default(),
+            annotator: None,
+            debugger: None,
+            snapshots: None,
+            algorithms: OperatorAlgorithms::default(),
+            cancellation: None,
+            intermediates: None,
+            spill: None,
+            ctl_only: false,
+            error: None,
+            pending_domains: Vec::new(),
         }
     }
 
@@ -43,6 +190,17 @@ impl EvalContext {
             cache: HashMap::new(),
             domain_raw_sets: HashMap::new(),
             free_var_domains: VarDomainMap::new(),
+            statistics: EvalStatistics::default(),
+            annotator: None,
+            debugger: None,
+            snapshots: None,
+            algorithms: OperatorAlgorithms::default(),
+            cancellation: None,
+            intermediates: None,
+            spill: None,
+            ctl_only: is_ctl_formula(tree),
+            error: None,
+            pending_domains: collect_builtin_domains(tree),
         }
     }
 
@@ -54,6 +212,25 @@ impl EvalContext {
             cache: HashMap::new(),
             domain_raw_sets: HashMap::new(),
             free_var_domains: VarDomainMap::new(),
+            statistics: EvalStatistics::default(),
+            annotator: None,
+            debugger: None,
+            snapshots: None,
+            algorithms: OperatorAlgorithms::default(),
+            cancellation: None,
+            intermediates: None,
+            spill: None,
+            ctl_only: trees.iter().all(is_ctl_formula),
+            error: None,
+            pending_domains: {
+                let mut domains = Vec::new();
+                for domain in trees.iter().flat_map(collect_builtin_domains) {
+                    if !domains.contains(&domain) {
+                        domains.push(domain);
+                    }
+                }
+                domains
+            },
         }
     }
 
@@ -77,10 +254,162 @@ impl EvalContext {
         &self.free_var_domains
     }
 
+    /// Get a ref to the `statistics` field containing the statistics collected during evaluation.
+    pub fn get_statistics(&self) -> &EvalStatistics {
+        &self.statistics
+    }
+
+    /// Start recording the evaluated sub-formulae with the sizes of their results (see
+    /// [crate::evaluation::annotation]). The full results are only kept if `keep_results` is set.
+    pub fn enable_annotation(&mut self, keep_results: bool) {
+        self.annotator = Some(EvalAnnotator::new(keep_results));
+    }
+
+    /// Attach the shared `debugger`, invoked before and after the evaluation of each sub-formula
+    /// (see [crate::evaluation::debugger]).
+    pub fn attach_debugger(&mut self, debugger: Arc<Mutex<dyn EvalDebugger + Send>>) {
+        self.debugger = Some(DebuggerHook::new(debugger));
+    }
+
+    /// Attach the `hook` receiving the snapshots of the running fixed points (see
+    /// [crate::evaluation::snapshots]).
+    pub fn attach_snapshots(&mut self, hook: SnapshotHook) {
+        self.snapshots = Some(hook);
+    }
+
+    /// Set the shared `flag` that cancels the evaluation once it is raised (e.g., by another
+    /// thread, when a time limit is reached). After the cancellation, the remaining sub-formulae
+    /// (and the running fixed points) are not evaluated, and the results are not valid.
+    pub fn set_cancellation(&mut self, flag: CancellationFlag) {
+        self.cancellation = Some(flag);
+    }
+
+    /// Check whether the evaluation was cancelled (see [EvalContext::set_cancellation]).
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(|flag| flag.is_cancelled())
+    }
+
+    /// Check whether the attached debugger stopped the evaluation.
+    pub fn is_stopped(&self) -> bool {
+        self.debugger.as_ref().is_some_and(|d| d.is_stopped())
+    }
+
+    /// Record the error that interrupted the evaluation (only the first one is kept). As with the
+    /// cancellation, the remaining sub-formulae are then not evaluated, and the results are not valid.
+    pub(crate) fn fail(&mut self, message: String) {
+        if self.error.is_none() {
+            self.error = Some(message);
+        }
+    }
+
+    /// Take the error that interrupted the evaluation, if any. The results of the evaluation are
+    /// only valid if there is none.
+    pub fn take_error(&mut self) -> Option<String> {
+        self.error.take()
+    }
+
+    /// Take the annotated syntactic trees of the formulae evaluated so far (empty if the
+    /// annotation is not enabled).
+    pub fn take_annotated_trees(&mut self) -> Vec<AnnotatedNode> {
+        match self.annotator.as_mut() {
+            Some(annotator) => annotator.take_trees(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Start retaining the results of the cached (duplicate) sub-formulae without free variables,
+    /// such as the attractor states, so that they can be reused later (see
+    /// [EvalContext::take_intermediates]).
+    pub fn enable_intermediates(&mut self) {
+        self.intermediates = Some(Vec::new());
+    }
+
+    /// Record the result of a cached sub-formula (given by its canonical form), if the
+    /// intermediate results are retained.
+    pub(crate) fn retain_intermediate(
+        &mut self,
+        canonical_form: &str,
+        result: &GraphColoredVertices,
+    ) {
+        if let Some(intermediates) = self.intermediates.as_mut() {
+            intermediates.push((canonical_form.to_string(), result.clone()));
+        }
+    }
+
+    /// Take the retained results of the cached sub-formulae evaluated so far, as pairs of their
+    /// canonical forms and results (empty if the retaining is not enabled).
+    pub fn take_intermediates(&mut self) -> Vec<(String, GraphColoredVertices)> {
+        match self.intermediates.as_mut() {
+            Some(intermediates) => std::mem::take(intermediates),
+            None => Vec::new(),
+        }
+    }
+
+    /// Start spilling the cached results with at least `threshold` BDD nodes to disk (into a fresh
+    /// sub-directory of `parent_dir`, removed once the context is dropped), and reloading them
+    /// once they are needed. This lowers the memory needed by the cache at the cost of the I/O.
+    ///
+    /// If a spilled result cannot be reloaded later (e.g., the files were removed), the
+    /// sub-formula is evaluated again.
+    pub fn enable_spilling(&mut self, parent_dir: &Path, threshold: usize) -> Result<(), String> {
+        self.spill = Some(SpillStore::new(parent_dir, threshold)?);
+        Ok(())
+    }
+
+    /// Get the cached result of the `sub_formula` with its var renaming (`None` if it is not
+    /// cached), reloading it into the context of the `graph` if it was spilled to disk.
+    ///
+    /// Return an error if the spilled result cannot be reloaded.
+    pub(crate) fn get_cached(
+        &self,
+        sub_formula: &FormulaWithDomains,
+        graph: &SymbolicAsyncGraph,
+    ) -> Result<Option<(GraphColoredVertices, VarRenameMap)>, String> {
+        if let Some(cached) = self.cache.get(sub_formula) {
+            return Ok(Some(cached.clone()));
+        }
+        self.spill
+            .as_ref()
+            .and_then(|spill| spill.load(sub_formula, graph))
+            .transpose()
+    }
+
+    /// Cache the `result` of the `sub_formula` with its var `renaming`. Large results are spilled
+    /// to disk if the spilling is enabled (if the spilling fails, they are kept in memory).
+    pub(crate) fn insert_cached(
+        &mut self,
+        sub_formula: FormulaWithDomains,
+        result: &GraphColoredVertices,
+        renaming: VarRenameMap,
+    ) {
+        if let Some(spill) = self.spill.as_mut() {
+            if spill.should_spill(result)
+                && spill
+                    .spill(sub_formula.clone(), result, renaming.clone())
+                    .is_ok()
+            {
+                self.statistics.num_spilled_sets += 1;
+                return;
+            }
+        }
+        self.cache.insert(sub_formula, (result.clone(), renaming));
+    }
+
+    /// Remove the cached result of the `sub_formula` (from memory or from disk).
+    pub(crate) fn remove_cached(&mut self, sub_formula: &FormulaWithDomains) {
+        self.cache.remove(sub_formula);
+        if let Some(spill) = self.spill.as_mut() {
+            spill.remove(sub_formula);
+        }
+    }
+
     /// Extend the standard evaluation context with two kinds of "pre-computed context" regarding wild-cards.
     ///
     /// `subst_context_properties` describes context of classical `wild-card properties` and it is put
-    /// directly to the `cache` field.
+    /// directly to the `cache` field. The context of `wild-card relations` is put there as well,
+    /// and it is taken from the cache (and adjusted to the relation's variables) during evaluation.
     ///
     /// `subst_context_domains` describes context of `variable domains` and is put into the `domain_raw_sets` field.
     pub fn extend_context_with_wild_cards(
@@ -119,14 +448,44 @@ impl EvalContext {
                 .insert(domain_name.clone(), raw_set.clone());
         }
     }
+
+    /// Extend the evaluation context with known results of (closed) formulae, such as results of
+    /// a previous run. The occurrences of these formulae (or their canonically equivalent forms)
+    /// are then taken from the cache directly instead of being recomputed.
+    ///
+    /// The `known_results` pair syntactic trees with their results. The trees must be processed
+    /// the same way as the evaluated trees (e.g., with variables renamed), and the results must be
+    /// in the symbolic context of the graph used for the evaluation.
+    pub fn extend_context_with_results(
+        &mut self,
+        known_results: &[(HctlTreeNode, GraphColoredVertices)],
+    ) {
+        for (tree, result) in known_results {
+            // the formula is closed, so there are no free variables to rename or restrict
+            let (canonical_form, _) = get_canonical_and_renaming(tree.to_string());
+            let sub_formula_with_domains = (canonical_form, VarDomainMap::new());
+
+            // as with wild-cards, the first occurrence is treated as duplicate as well
+            *self
+                .duplicates
+                .entry(sub_formula_with_domains.clone())
+                .or_insert(0) += 1;
+            self.cache
+                .insert(sub_formula_with_domains, (result.clone(), HashMap::new()));
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::evaluation::eval_context::{EvalContext, VarDomainMap};
+    use crate::evaluation::algorithm::{compute_steady_states, eval_node};
+    use crate::evaluation::eval_context::{
+        CancellationFlag, EvalContext, EvalStatistics, VarDomainMap,
+    };
     use crate::mc_utils::get_extended_symbolic_graph;
     use crate::preprocessing::parser::{parse_extended_formula, parse_hctl_formula};
 
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
     use biodivine_lib_param_bn::BooleanNetwork;
 
     use std::collections::HashMap;
@@ -158,6 +517,7 @@ mod tests {
         assert!(eval_info.get_domain_raw_sets().is_empty());
         // check that free variable domains are always initially empty (formula cant have free vars)
         assert!(eval_info.get_free_var_domains().is_empty());
+        assert_eq!(eval_info.get_statistics(), &EvalStatistics::default());
     }
 
     #[test]
@@ -197,4 +557,122 @@ mod tests {
         assert_eq!(eval_info.get_cache(), &expected_cache);
         assert_eq!(eval_info.get_domain_raw_sets(), &expected_domains);
     }
+
+    #[test]
+    /// Test the evaluation with the cached results spilled to disk.
+    fn eval_context_spilling() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let self_loops = compute_steady_states(&stg);
+        let formula = "(EF (!{x}: AG EF {x})) & AX (!{y}: AG EF {y}) & AG (!{z}: AG EF {z})";
+        let tree = parse_hctl_formula(formula).unwrap();
+
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        let expected = eval_node(tree.clone(), &stg, &mut eval_info, &self_loops);
+        assert_eq!(eval_info.get_statistics().num_spilled_sets, 0);
+
+        // every cached result is spilled, and removed once all duplicates are evaluated
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        eval_info.enable_spilling(&std::env::temp_dir(), 0).unwrap();
+        let result = eval_node(tree.clone(), &stg, &mut eval_info, &self_loops);
+        assert_eq!(result, expected);
+        assert_eq!(eval_info.get_statistics().num_spilled_sets, 1);
+        assert!(eval_info.get_cache().is_empty());
+        assert_eq!(eval_info.spill.as_ref().unwrap().num_spilled(), 0);
+
+        // if a spilled result cannot be reloaded, it is computed again
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        eval_info.enable_spilling(&std::env::temp_dir(), 0).unwrap();
+        let sub_formula = eval_info.get_duplicates().keys().next().unwrap().clone();
+        let empty = stg.mk_empty_colored_vertices();
+        eval_info.insert_cached(sub_formula.clone(), &empty, HashMap::new());
+        let spill_dir = eval_info.spill.as_ref().unwrap().directory().to_path_buf();
+        for entry in std::fs::read_dir(spill_dir).unwrap() {
+            std::fs::remove_file(entry.unwrap().path()).unwrap();
+        }
+        assert!(eval_info.get_cached(&sub_formula, &stg).is_err());
+        let result = eval_node(tree, &stg, &mut eval_info, &self_loops);
+        assert_eq!(result, expected);
+        assert_eq!(eval_info.spill.as_ref().unwrap().num_spilled(), 0);
+    }
+
+    #[test]
+    /// Test that pure CTL formulae are evaluated without canonization, with the same results.
+    fn eval_context_ctl_only() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 0).unwrap();
+        let self_loops = compute_steady_states(&stg);
+        let tree = parse_hctl_formula("(AG EF a) & EX (AG EF a) & (a EU ~b)").unwrap();
+
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        assert!(eval_info.ctl_only);
+        let result = eval_node(tree.clone(), &stg, &mut eval_info, &self_loops);
+
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        eval_info.ctl_only = false;
+        assert_eq!(eval_node(tree, &stg, &mut eval_info, &self_loops), result);
+
+        // any hybrid operator (or HCTL variable) disables the CTL path
+        let hybrid_tree = parse_hctl_formula("AG EF a & (!{x}: AX {x})").unwrap();
+        assert!(!EvalContext::from_single_tree(&hybrid_tree).ctl_only);
+        assert!(!EvalContext::from_multiple_trees(&vec![hybrid_tree]).ctl_only);
+    }
+
+    #[test]
+    /// Test extension of the EvalContext with known results of formulae.
+    fn eval_context_known_results() {
+        let bn = BooleanNetwork::try_from_bnet("v1, v1").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        let syntax_tree = parse_hctl_formula("EF (!{x}: AX {x}) & (!{y}: AX {y})").unwrap();
+        let mut eval_info = EvalContext::from_single_tree(&syntax_tree);
+        let sub_formula = ("(!{var0}: (AX {var0}))".to_string(), VarDomainMap::new());
+        assert_eq!(eval_info.get_duplicates().get(&sub_formula), Some(&1));
+
+        // known result of a canonically equivalent formula is used for all occurrences
+        let known_tree = parse_hctl_formula("!{z}: AX {z}").unwrap();
+        let result = stg.mk_unit_colored_vertices();
+        eval_info.extend_context_with_results(&[(known_tree, result.clone())]);
+        assert_eq!(eval_info.get_duplicates().get(&sub_formula), Some(&2));
+        assert_eq!(
+            eval_info.get_cache().get(&sub_formula),
+            Some(&(result, HashMap::new()))
+        );
+    }
+
+    #[test]
+    /// Test cancelling the evaluation by a shared flag.
+    fn eval_context_cancellation() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let self_loops = compute_steady_states(&stg);
+        let tree = parse_hctl_formula("!{x}: AG EF {x}").unwrap();
+
+        let flag = CancellationFlag::new();
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        eval_info.set_cancellation(flag.clone());
+        assert!(!eval_info.is_cancelled());
+        let result = eval_node(tree.clone(), &stg, &mut eval_info, &self_loops);
+        assert!(!result.is_empty());
+
+        // once the (shared) flag is raised, nothing is evaluated anymore
+        flag.clone().cancel();
+        assert!(eval_info.is_cancelled());
+        assert!(eval_node(tree, &stg, &mut eval_info, &self_loops).is_empty());
+    }
+
+    #[test]
+    /// Test that a wild-card relation without context makes the evaluation fail with an error.
+    fn eval_context_missing_relation() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let self_loops = compute_steady_states(&stg);
+        let tree = parse_extended_formula("!{x}: %rel({x})%").unwrap();
+
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        let result = eval_node(tree, &stg, &mut eval_info, &self_loops);
+        assert!(result.is_empty());
+        assert!(eval_info.take_error().is_some());
+        assert!(eval_info.take_error().is_none());
+    }
 }
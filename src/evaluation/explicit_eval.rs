@@ -0,0 +1,397 @@
+//! Explicit-state evaluation of HCTL formulae, used as a reference to cross-validate the
+//! symbolic algorithm on small instances.
+//!
+//! The states of a fully specified network (i.e., a single color) are enumerated explicitly, so
+//! this is only feasible for networks with a small number of variables. The state with index `i`
+//! assigns the value `(i >> k) & 1` to the variable with index `k`. The semantics of all operators
+//! follow the symbolic algorithm (including the self-loops on steady states).
+
+use crate::mc_utils::{collect_unique_hctl_vars, get_extended_symbolic_graph};
+use crate::model_checking::model_check_formula;
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::operator_enums::*;
+use crate::preprocessing::parser::parse_hctl_formula;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+use biodivine_lib_param_bn::{BooleanNetwork, VariableId};
+use std::collections::HashMap;
+
+/// Maximal number of network variables supported by the explicit evaluation.
+pub const MAX_EXPLICIT_VARIABLES: usize = 20;
+
+/// Explicit asynchronous state-transition graph of a fully specified network.
+struct ExplicitGraph {
+    /// Successors of each state (steady states have a self-loop).
+    successors: Vec<Vec<usize>>,
+}
+
+impl ExplicitGraph {
+    /// Enumerate all states and transitions of the fully specified `bn`.
+    fn new(bn: &BooleanNetwork) -> Result<ExplicitGraph, String> {
+        let num_vars = bn.num_vars();
+        if num_vars > MAX_EXPLICIT_VARIABLES {
+            return Err(format!(
+                "Explicit evaluation supports at most {MAX_EXPLICIT_VARIABLES} variables."
+            ));
+        }
+        if bn.num_parameters() > 0 || bn.variables().any(|v| bn.get_update_function(v).is_none()) {
+            return Err("Explicit evaluation requires a fully specified network.".to_string());
+        }
+
+        let variables: Vec<VariableId> = bn.variables().collect();
+        let mut successors = Vec::with_capacity(1 << num_vars);
+        for state in 0..(1usize << num_vars) {
+            let values: HashMap<VariableId, bool> = variables
+                .iter()
+                .map(|v| (*v, state >> v.to_index() & 1 == 1))
+                .collect();
+            let mut state_successors = Vec::new();
+            for var in &variables {
+                let function = bn.get_update_function(*var).as_ref().unwrap();
+                if function.evaluate(&values) != Some(values[var]) {
+                    state_successors.push(state ^ (1 << var.to_index()));
+                }
+            }
+            if state_successors.is_empty() {
+                state_successors.push(state);
+            }
+            successors.push(state_successors);
+        }
+        Ok(ExplicitGraph { successors })
+    }
+
+    fn num_states(&self) -> usize {
+        self.successors.len()
+    }
+
+    fn ex(&self, phi: &[bool]) -> Vec<bool> {
+        self.successors
+            .iter()
+            .map(|succ| succ.iter().any(|s| phi[*s]))
+            .collect()
+    }
+
+    fn ax(&self, phi: &[bool]) -> Vec<bool> {
+        self.successors
+            .iter()
+            .map(|succ| succ.iter().all(|s| phi[*s]))
+            .collect()
+    }
+
+    /// States with a successor satisfying `phi` that is reached by updating the variable with
+    /// index `var` (self-loops of steady states are not such successors).
+    fn ex_var(&self, var: usize, phi: &[bool]) -> Vec<bool> {
+        self.successors
+            .iter()
+            .enumerate()
+            .map(|(state, succ)| {
+                let target = state ^ (1 << var);
+                succ.contains(&target) && phi[target]
+            })
+            .collect()
+    }
+
+    /// States with some predecessor satisfying `phi`.
+    fn ey(&self, phi: &[bool]) -> Vec<bool> {
+        let mut result = vec![false; self.num_states()];
+        for (state, succ) in self.successors.iter().enumerate() {
+            if phi[state] {
+                succ.iter().for_each(|s| result[*s] = true);
+            }
+        }
+        result
+    }
+
+    /// Least fixpoint `Z = phi2 | (phi1 & step(Z))`.
+    fn until(
+        &self,
+        phi1: &[bool],
+        phi2: &[bool],
+        step: fn(&Self, &[bool]) -> Vec<bool>,
+    ) -> Vec<bool> {
+        let mut current = phi2.to_vec();
+        loop {
+            let next_step = step(self, &current);
+            let next: Vec<bool> = (0..self.num_states())
+                .map(|s| phi2[s] || (phi1[s] && next_step[s]))
+                .collect();
+            if next == current {
+                return current;
+            }
+            current = next;
+        }
+    }
+
+    /// Greatest fixpoint `Z = phi & EX Z`.
+    fn eg(&self, phi: &[bool]) -> Vec<bool> {
+        let mut current = phi.to_vec();
+        loop {
+            let next_step = self.ex(&current);
+            let next: Vec<bool> = (0..self.num_states())
+                .map(|s| phi[s] && next_step[s])
+                .collect();
+            if next == current {
+                return current;
+            }
+            current = next;
+        }
+    }
+
+    /// Recursively evaluate the sub-formula represented by a `node`, with the free HCTL variables
+    /// assigned to states by `env`.
+    fn eval(
+        &self,
+        node: &HctlTreeNode,
+        bn: &BooleanNetwork,
+        env: &HashMap<String, usize>,
+    ) -> Result<Vec<bool>, String> {
+        let n = self.num_states();
+        let result = match &node.node_type {
+            NodeType::Terminal(atom) => match atom {
+                Atomic::True => vec![true; n],
+                Atomic::False => vec![false; n],
+                Atomic::Prop(name) => {
+                    let var = bn
+                        .as_graph()
+                        .find_variable(name)
+                        .ok_or(format!("There is no network variable `{name}`."))?;
+                    (0..n).map(|s| s >> var.to_index() & 1 == 1).collect()
+                }
+                Atomic::State(values, _) => {
+                    let mut result = vec![true; n];
+                    for (name, value) in values {
+                        let var = bn
+                            .as_graph()
+                            .find_variable(name)
+                            .ok_or(format!("There is no network variable `{name}`."))?;
+                        for (s, holds) in result.iter_mut().enumerate() {
+                            *holds &= (s >> var.to_index() & 1 == 1) == *value;
+                        }
+                    }
+                    result
+                }
+                Atomic::Var(name) => {
+                    let bound = *env.get(name).ok_or(format!("Variable `{name}` is free."))?;
+                    (0..n).map(|s| s == bound).collect()
+                }
+                Atomic::WildCardProp(_) | Atomic::WildCardRelation(..) => {
+                    return Err("Wild-card props are not supported in explicit evaluation.".into())
+                }
+            },
+            NodeType::Unary(op, child) => {
+                let phi = self.eval(child, bn, env)?;
+                match op {
+                    UnaryOp::Not => phi.iter().map(|v| !v).collect(),
+                    UnaryOp::EX => self.ex(&phi),
+                    UnaryOp::AX => self.ax(&phi),
+                    UnaryOp::EXVar(name) | UnaryOp::AXVar(name) => {
+                        let var = bn
+                            .as_graph()
+                            .find_variable(name)
+                            .ok_or(format!("There is no network variable `{name}`."))?
+                            .to_index();
+                        if let UnaryOp::EXVar(_) = op {
+                            self.ex_var(var, &phi)
+                        } else {
+                            // AX_{v} phi == not EX_{v} (not phi)
+                            let not_phi: Vec<bool> = phi.iter().map(|v| !v).collect();
+                            self.ex_var(var, &not_phi).iter().map(|v| !v).collect()
+                        }
+                    }
+                    UnaryOp::EF => self.until(&vec![true; n], &phi, Self::ex),
+                    UnaryOp::AF => self.until(&vec![true; n], &phi, Self::ax),
+                    UnaryOp::EG => self.eg(&phi),
+                    // AG phi == not EF(not phi)
+                    UnaryOp::AG => {
+                        let not_phi: Vec<bool> = phi.iter().map(|v| !v).collect();
+                        let ef = self.until(&vec![true; n], &not_phi, Self::ex);
+                        ef.iter().map(|v| !v).collect()
+                    }
+                    UnaryOp::EP => self.until(&vec![true; n], &phi, Self::ey),
+                }
+            }
+            NodeType::Binary(op, left, right) => {
+                let phi1 = self.eval(left, bn, env)?;
+                let phi2 = self.eval(right, bn, env)?;
+                let pointwise =
+                    |f: fn(bool, bool) -> bool| (0..n).map(|s| f(phi1[s], phi2[s])).collect();
+                let negated = |phi: &[bool]| phi.iter().map(|v| !v).collect::<Vec<_>>();
+                match op {
+                    BinaryOp::And => pointwise(|a, b| a && b),
+                    BinaryOp::Or => pointwise(|a, b| a || b),
+                    BinaryOp::Imp => pointwise(|a, b| !a || b),
+                    BinaryOp::Iff => pointwise(|a, b| a == b),
+                    BinaryOp::Xor => pointwise(|a, b| a != b),
+                    BinaryOp::EU => self.until(&phi1, &phi2, Self::ex),
+                    BinaryOp::AU => self.until(&phi1, &phi2, Self::ax),
+                    // E phi1 W phi2 == not (A (not phi1) U (not phi2))
+                    BinaryOp::EW => {
+                        negated(&self.until(&negated(&phi1), &negated(&phi2), Self::ax))
+                    }
+                    // A phi1 W phi2 == not (E (not phi1) U (not phi2))
+                    BinaryOp::AW => {
+                        negated(&self.until(&negated(&phi1), &negated(&phi2), Self::ex))
+                    }
+                }
+            }
+            NodeType::Hybrid(op, var, domain, child) => {
+                if domain.is_some() {
+                    return Err(
+                        "Restricted domains are not supported in explicit evaluation.".into(),
+                    );
+                }
+                if *op == HybridOp::Jump {
+                    let bound = *env.get(var).ok_or(format!("Variable `{var}` is free."))?;
+                    let phi = self.eval(child, bn, env)?;
+                    return Ok(vec![phi[bound]; n]);
+                }
+                // evaluate the sub-formula for each possible value of the variable
+                let mut env = env.clone();
+                let mut results = Vec::with_capacity(n);
+                for bound in 0..n {
+                    env.insert(var.clone(), bound);
+                    results.push(self.eval(child, bn, &env)?);
+                }
+                match op {
+                    HybridOp::Bind => (0..n).map(|s| results[s][s]).collect(),
+                    HybridOp::Exists => (0..n).map(|s| results.iter().any(|r| r[s])).collect(),
+                    HybridOp::Forall => (0..n).map(|s| results.iter().all(|r| r[s])).collect(),
+                    HybridOp::Jump => unreachable!(),
+                }
+            }
+        };
+        Ok(result)
+    }
+}
+
+/// Evaluate the `formula` on the fully specified network `bn` by explicitly enumerating its
+/// states, and return the vector of results indexed by the states (see the module documentation
+/// for the state encoding).
+///
+/// The network must have at most [MAX_EXPLICIT_VARIABLES] variables. To evaluate the formula for
+/// a particular color of a partially specified network, use `SymbolicAsyncGraph::pick_witness`.
+/// Note that every hybrid operator enumerates all states, so the evaluation of nested operators
+/// is only feasible for very small networks.
+pub fn model_check_formula_explicit(
+    formula: &str,
+    bn: &BooleanNetwork,
+) -> Result<Vec<bool>, String> {
+    let tree = parse_hctl_formula(formula)?;
+    let graph = ExplicitGraph::new(bn)?;
+    graph.eval(&tree, bn, &HashMap::new())
+}
+
+/// Cross-validate the symbolic model checking against the explicit evaluation (see
+/// [model_check_formula_explicit]) of the `formula` on the fully specified network `bn`.
+///
+/// Return the states (as their indices) where the results differ, i.e., an empty vector if both
+/// results are the same.
+pub fn cross_validate_formula(formula: &str, bn: &BooleanNetwork) -> Result<Vec<usize>, String> {
+    let explicit_result = model_check_formula_explicit(formula, bn)?;
+
+    let tree = parse_hctl_formula(formula)?;
+    let num_hctl_vars = collect_unique_hctl_vars(tree).len();
+    let graph = get_extended_symbolic_graph(bn, num_hctl_vars as u16)?;
+    let symbolic_result = model_check_formula(formula, &graph)?;
+
+    // states are compared in the canonical context (in which the symbolic result is sanitized)
+    let canonical_graph = SymbolicAsyncGraph::new(bn)?;
+    let variables: Vec<VariableId> = bn.variables().collect();
+    let mut mismatches = Vec::new();
+    for (state, explicit_value) in explicit_result.into_iter().enumerate() {
+        let values: Vec<(VariableId, bool)> = variables
+            .iter()
+            .map(|v| (*v, state >> v.to_index() & 1 == 1))
+            .collect();
+        let vertex = canonical_graph.mk_subspace(&values);
+        let symbolic_value = !vertex.intersect(&symbolic_result).is_empty();
+        if symbolic_value != explicit_value {
+            mismatches.push(state);
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::explicit_eval::{cross_validate_formula, model_check_formula_explicit};
+    use crate::preprocessing::hctl_tree::HctlTreeNode;
+    use crate::preprocessing::operator_enums::UnaryOp;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test the explicit evaluation on a small network.
+    fn test_explicit_evaluation() {
+        // `a` oscillates, `b` follows `a`
+        let bn = BooleanNetwork::try_from("a -| a\na -> b\n$a: !a\n$b: a").unwrap();
+        // states are indexed as `a + 2 * b`
+        let result = model_check_formula_explicit("a & ~b", &bn).unwrap();
+        assert_eq!(result, vec![false, true, false, false]);
+        // only in `a & b`, the single successor has `a` inactive
+        let result = model_check_formula_explicit("AX ~a", &bn).unwrap();
+        assert_eq!(result, vec![false, false, false, true]);
+        let result = model_check_formula_explicit("!{x}: AG EF {x}", &bn).unwrap();
+        assert_eq!(result, vec![true; 4]);
+        let result = model_check_formula_explicit("3{x}: @{x}: (a & b & AX ~a)", &bn).unwrap();
+        assert_eq!(result, vec![true; 4]);
+
+        // networks with parameters are not supported
+        let bn = BooleanNetwork::try_from("a -? a").unwrap();
+        assert!(model_check_formula_explicit("a", &bn).is_err());
+    }
+
+    #[test]
+    /// Cross-validate the symbolic algorithm against the explicit evaluation on small networks,
+    /// using both selected formulae and randomly generated ones.
+    fn test_cross_validation() {
+        let models = vec![
+            "a -| a\na -> b\n$a: !a\n$b: a",
+            "a -> b\nb -| a\nc -> c\nb -> c\n$a: !b\n$b: a\n$c: c | b",
+            "a -> a\na -| b\nb -> b\nc -| c\n$a: a\n$b: !a & b\n$c: !c",
+        ];
+        let formulae = vec![
+            "!{x}: AX {x}",
+            "!{x}: AG EF {x}",
+            "!{x}: (AX (~{x} & AF {x}))",
+            "3{x}: 3{y}: (@{x}: ~{y} & AX {x}) & (@{y}: AX {y})",
+            "V{x}: (EF {x} => AF a)",
+            "a EU b",
+            "a AU ~b",
+            "a EW b",
+            "~a AW b",
+            "EG (a | b)",
+            "AF AG b",
+            "EF #state{a=1, b=0, *}",
+            "#state{b=1, *} EU #state{a=0, *}",
+            "EX_{a} b",
+            "AX_{b} (a | EF ~b)",
+            "!{x}: AX_{a} EF {x}",
+        ];
+        for model in models {
+            let bn = BooleanNetwork::try_from(model).unwrap();
+            for formula in &formulae {
+                let mismatches = cross_validate_formula(formula, &bn).unwrap();
+                assert!(mismatches.is_empty(), "{formula} on {model}");
+            }
+
+            // random Boolean combinations of propositions wrapped in temporal operators
+            let props = vec!["a".to_string(), "b".to_string()];
+            for seed in 0..10 {
+                let tree = HctlTreeNode::new_random_boolean(3, &props, seed);
+                for op in [
+                    UnaryOp::EF,
+                    UnaryOp::AF,
+                    UnaryOp::EG,
+                    UnaryOp::AG,
+                    UnaryOp::AX,
+                    UnaryOp::EP,
+                ] {
+                    let formula = HctlTreeNode::mk_unary(tree.clone(), op).to_string();
+                    let mismatches = cross_validate_formula(formula.as_str(), &bn).unwrap();
+                    assert!(mismatches.is_empty(), "{formula} on {model}");
+                }
+            }
+        }
+    }
+}
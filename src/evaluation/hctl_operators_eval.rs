@@ -1,9 +1,13 @@
 //! Contains the implementation of symbolic evaluation of HCTL operators for Boolean network models.
 
+use crate::_aeon_algorithms::saturated_reachability::reach_bwd;
 use crate::evaluation::low_level_operations::*;
+use crate::evaluation::reversed_graph::ReversedGraph;
+use crate::profiling::span;
 
 use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::VariableId;
 
 /// Evaluate negation respecting the allowed universe.
 pub fn eval_neg(graph: &SymbolicAsyncGraph, set: &GraphColoredVertices) -> GraphColoredVertices {
@@ -56,11 +60,92 @@ pub fn eval_prop(graph: &SymbolicAsyncGraph, proposition: &str) -> GraphColoredV
     )
 }
 
+/// Return a coloured set of states given by a (partial) state literal, i.e., the conjunction of
+/// the literals given by the `values` of network variables.
+/// Note that validity of the variables must be checked beforehand.
+pub fn eval_state_literal(
+    graph: &SymbolicAsyncGraph,
+    values: &[(String, bool)],
+) -> GraphColoredVertices {
+    let context = graph.symbolic_context();
+    let bdd = values
+        .iter()
+        .fold(context.mk_constant(true), |bdd, (name, value)| {
+            // each variable is checked during preprocessing, thus it must be valid
+            let network_variable = context.find_network_variable(name).unwrap();
+            let literal = context.mk_state_variable_is_true(network_variable);
+            if *value {
+                bdd.and(&literal)
+            } else {
+                bdd.and_not(&literal)
+            }
+        });
+    GraphColoredVertices::new(bdd, context)
+}
+
 /// Evaluate atomic sub-formula containing only a HCTL variable.
 pub fn eval_hctl_var(graph: &SymbolicAsyncGraph, hctl_var_name: &str) -> GraphColoredVertices {
     create_comparator_var_state(graph, hctl_var_name)
 }
 
+/// Evaluate a wild-card relation between the states of the given HCTL variables `vars`.
+///
+/// The raw set of the `relation` encodes its i-th argument using the i-th HCTL variable (named
+/// by i+1 `x` chars, i.e., `x`, `xx`, ...). These are substituted by the actual `vars` at once.
+/// The relation must not depend on network variables, as these are used as a temporary storage
+/// when the substitution is cyclic (e.g., when swapping `x` and `xx`).
+pub fn eval_wild_card_relation(
+    graph: &SymbolicAsyncGraph,
+    relation: &GraphColoredVertices,
+    vars: &[String],
+) -> GraphColoredVertices {
+    let mut result = relation.clone();
+    // pending substitutions from the argument variable to the actual one, `None` stands for the
+    // network variables used as a temporary storage
+    let mut pending: Vec<(Option<String>, String)> = Vec::new();
+    for (i, var) in vars.iter().enumerate() {
+        let arg_var = "x".repeat(i + 1);
+        if let Some(j) = vars[..i].iter().position(|v| v == var) {
+            // variable is repeated, so the i-th argument must equal to the j-th one
+            let comparator = create_comparator_two_vars(graph, &"x".repeat(j + 1), &arg_var);
+            result = project_out_hctl_var(graph, &result.intersect(&comparator), &arg_var);
+        } else if arg_var != *var {
+            pending.push((Some(arg_var), var.clone()));
+        }
+    }
+
+    while !pending.is_empty() {
+        // substitution is safe if its target is not a source of another pending substitution
+        let safe = pending.iter().position(|(_, target)| {
+            !pending
+                .iter()
+                .any(|(source, _)| source.as_ref() == Some(target))
+        });
+        if let Some(i) = safe {
+            let (source, target) = pending.remove(i);
+            result = match source {
+                Some(source) => {
+                    let comparator = create_comparator_two_vars(graph, &source, &target);
+                    project_out_hctl_var(graph, &result.intersect(&comparator), &source)
+                }
+                None => {
+                    let comparator = create_comparator_var_state(graph, &target);
+                    project_out_bn_vars(graph, &result.intersect(&comparator))
+                }
+            };
+        } else {
+            // only cycles remain (they never contain the network variables, since the targets
+            // are distinct), so break one by moving its source to the network variables
+            let (source, target) = pending.remove(0);
+            let source = source.unwrap();
+            let comparator = create_comparator_var_state(graph, &source);
+            result = project_out_hctl_var(graph, &result.intersect(&comparator), &source);
+            pending.push((None, target));
+        }
+    }
+    result
+}
+
 /// Evaluate binder operator.
 /// It essentially does an intersection with "comparator" relation and projects out the HCTL var.
 pub fn eval_bind(
@@ -87,13 +172,20 @@ pub fn eval_exists(
 
 /// Evaluate jump operator.
 /// It essentially does an intersection with "comparator" relation and projects out the BN variables.
+///
+/// If the jump has a `domain`, it only jumps to the states of the domain, i.e., the result is
+/// empty for valuations of the variable outside the domain (`@{x} in %D%: phi == @{x}: %D% & phi`).
 pub fn eval_jump(
     graph: &SymbolicAsyncGraph,
     phi: &GraphColoredVertices,
     var_name: &str,
+    domain: Option<&GraphColoredVertices>,
 ) -> GraphColoredVertices {
     let comparator = create_comparator_var_state(graph, var_name);
-    let intersection = comparator.intersect(phi);
+    let mut intersection = comparator.intersect(phi);
+    if let Some(domain_set) = domain {
+        intersection = intersection.intersect(domain_set);
+    }
 
     // now lets project out the bdd vars coding variables from the Boolean network
     project_out_bn_vars(graph, &intersection)
@@ -106,9 +198,39 @@ pub fn eval_ex(
     phi: &GraphColoredVertices,
     self_loop_states: &GraphColoredVertices,
 ) -> GraphColoredVertices {
+    let _span = span("predecessors");
     graph.pre(phi).union(&phi.intersect(self_loop_states))
 }
 
+/// Evaluate the transition-labelled EX operator, i.e., compute states with a successor satisfying
+/// `phi` that is reached by updating the network variable `var_name`.
+///
+/// Unlike for EX, no self-loops are added - a state in which `var_name` cannot be updated has
+/// no such successor.
+pub fn eval_ex_var(
+    graph: &SymbolicAsyncGraph,
+    phi: &GraphColoredVertices,
+    var_name: &str,
+) -> GraphColoredVertices {
+    let _span = span("predecessors");
+    // the variable is checked during preprocessing, thus it must be a valid network variable
+    let network_variable = graph
+        .symbolic_context()
+        .find_network_variable(var_name)
+        .unwrap();
+    graph.var_pre(network_variable, phi)
+}
+
+/// Evaluate the transition-labelled AX operator through the EX computation, as
+/// `AX_{v} phi == not EX_{v} (not phi)`.
+pub fn eval_ax_var(
+    graph: &SymbolicAsyncGraph,
+    phi: &GraphColoredVertices,
+    var_name: &str,
+) -> GraphColoredVertices {
+    eval_neg(graph, &eval_ex_var(graph, &eval_neg(graph, phi), var_name))
+}
+
 #[allow(dead_code)]
 /// Evaluate EU operator using the classical fixpoint algorithm.
 /// Currently, this is not the most efficient version, use `eval_eu_saturated` instead.
@@ -152,8 +274,23 @@ pub fn eval_eu_saturated(
     phi1: &GraphColoredVertices,
     phi2: &GraphColoredVertices,
 ) -> GraphColoredVertices {
+    eval_eu_saturated_observed(graph, phi1, phi2, &mut |_, _| true)
+}
+
+/// Evaluate EU operator using the saturation-based algorithm, same as [eval_eu_saturated], but
+/// pass the current approximation (a subset of the result) to the `observer` after each
+/// iteration (together with the number of iterations so far). If the observer returns `false`,
+/// the computation is interrupted and the current approximation is returned.
+pub fn eval_eu_saturated_observed(
+    graph: &SymbolicAsyncGraph,
+    phi1: &GraphColoredVertices,
+    phi2: &GraphColoredVertices,
+    observer: &mut dyn FnMut(usize, &GraphColoredVertices) -> bool,
+) -> GraphColoredVertices {
+    let _span = span("eu_saturation");
     // TODO: for generating predecessors, check if including self-loops really is not needed
     let mut result = phi2.clone();
+    let mut iteration = 0;
     let mut done = false;
     while !done {
         done = true;
@@ -161,6 +298,10 @@ pub fn eval_eu_saturated(
             let update = phi1.intersect(&graph.var_pre(var, &result)).minus(&result);
             if !update.is_empty() {
                 result = result.union(&update);
+                iteration += 1;
+                if !observer(iteration, &result) {
+                    return result;
+                }
                 done = false;
                 break;
             }
@@ -179,18 +320,75 @@ pub fn eval_ef_saturated(
     eval_eu_saturated(graph, &unit_set, phi)
 }
 
+/// Evaluate EU operator using the frontier-based algorithm. In each round, only the predecessors
+/// of the states added in the previous round (the frontier) are explored, by all variables at once.
+/// The result is the same as of [eval_eu_saturated], but this outperforms the saturation on some
+/// model families. The current approximation (a subset of the result) is passed to the `observer`
+/// after each round (together with the number of rounds so far). If the observer returns `false`,
+/// the computation is interrupted and the current approximation is returned.
+pub fn eval_eu_frontier(
+    graph: &SymbolicAsyncGraph,
+    phi1: &GraphColoredVertices,
+    phi2: &GraphColoredVertices,
+    observer: &mut dyn FnMut(usize, &GraphColoredVertices) -> bool,
+) -> GraphColoredVertices {
+    let _span = span("eu_frontier");
+    let mut result = phi2.clone();
+    let mut frontier = phi2.clone();
+    let mut iteration = 0;
+    while !frontier.is_empty() {
+        frontier = phi1.intersect(&graph.pre(&frontier)).minus(&result);
+        if !frontier.is_empty() {
+            result = result.union(&frontier);
+            iteration += 1;
+            if !observer(iteration, &result) {
+                return result;
+            }
+        }
+    }
+    result
+}
+
+/// Evaluate EP operator (some past state satisfies phi), i.e., compute all states reachable from
+/// phi. This is EF evaluated on the time-reversed graph, using the saturation-based algorithm.
+pub fn eval_ep(graph: &SymbolicAsyncGraph, phi: &GraphColoredVertices) -> GraphColoredVertices {
+    let _span = span("ep_saturation");
+    let unit_set = graph.mk_unit_colored_vertices();
+    let variables: Vec<VariableId> = graph.variables().collect();
+    reach_bwd(&ReversedGraph::new(graph), phi, &unit_set, &variables)
+}
+
 /// Evaluate EG operator using the classical fixpoint algorithm.
 pub fn eval_eg(
     graph: &SymbolicAsyncGraph,
     phi: &GraphColoredVertices,
     self_loop_states: &GraphColoredVertices,
 ) -> GraphColoredVertices {
+    eval_eg_observed(graph, phi, self_loop_states, &mut |_, _| true)
+}
+
+/// Evaluate EG operator using the classical fixpoint algorithm, same as [eval_eg], but pass the
+/// current approximation (a superset of the result) to the `observer` after each iteration
+/// (together with the number of iterations so far). If the observer returns `false`, the
+/// computation is interrupted and the current approximation is returned.
+pub fn eval_eg_observed(
+    graph: &SymbolicAsyncGraph,
+    phi: &GraphColoredVertices,
+    self_loop_states: &GraphColoredVertices,
+    observer: &mut dyn FnMut(usize, &GraphColoredVertices) -> bool,
+) -> GraphColoredVertices {
+    let _span = span("eg_fixpoint");
     let mut old_set = phi.clone();
     let mut new_set = graph.mk_empty_colored_vertices();
+    let mut iteration = 0;
 
     while old_set != new_set {
         new_set = old_set.clone();
         old_set = old_set.intersect(&eval_ex(graph, &old_set, self_loop_states));
+        iteration += 1;
+        if !observer(iteration, &old_set) {
+            return old_set;
+        }
     }
     old_set
 }
@@ -234,12 +432,32 @@ pub fn eval_au(
     phi2: &GraphColoredVertices,
     self_loop_states: &GraphColoredVertices,
 ) -> GraphColoredVertices {
+    eval_au_observed(graph, phi1, phi2, self_loop_states, &mut |_, _| true)
+}
+
+/// Evaluate AU operator using the classical fixpoint algorithm, same as [eval_au], but pass the
+/// current approximation (a subset of the result) to the `observer` after each iteration
+/// (together with the number of iterations so far). If the observer returns `false`, the
+/// computation is interrupted and the current approximation is returned.
+pub fn eval_au_observed(
+    graph: &SymbolicAsyncGraph,
+    phi1: &GraphColoredVertices,
+    phi2: &GraphColoredVertices,
+    self_loop_states: &GraphColoredVertices,
+    observer: &mut dyn FnMut(usize, &GraphColoredVertices) -> bool,
+) -> GraphColoredVertices {
+    let _span = span("au_fixpoint");
     let mut old_set = phi2.clone();
     let mut new_set = graph.mk_empty_colored_vertices();
+    let mut iteration = 0;
 
     while old_set != new_set {
         new_set = old_set.clone();
         old_set = old_set.union(&phi1.intersect(&eval_ax(graph, &old_set, self_loop_states)));
+        iteration += 1;
+        if !observer(iteration, &old_set) {
+            return old_set;
+        }
     }
     old_set
 }
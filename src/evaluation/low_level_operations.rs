@@ -1,6 +1,9 @@
 //! Low-level operations and utilities needed to evaluate HCTL operators symbolically.
 //! This is a place to look for when you need to touch the underlying BDDs directly.
 
+use crate::mc_utils::{hctl_var_bdd_variables, hctl_var_name};
+use crate::profiling::span;
+
 use biodivine_lib_bdd::BddVariable;
 use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
@@ -19,6 +22,7 @@ fn create_equalizer(
     hctl_var_name: &str,
     other_hctl_var_name: Option<&str>,
 ) -> GraphColoredVertices {
+    let _span = span("comparator");
     // TODO: merge both branches to not repeat code
     let mut comparator = graph.mk_unit_colored_vertices().as_bdd().clone();
 
@@ -101,6 +105,7 @@ pub fn project_out_hctl_var(
     colored_state_set: &GraphColoredVertices,
     hctl_var_name: &str,
 ) -> GraphColoredVertices {
+    let _span = span("quantifier_projection");
     let hctl_var_id = hctl_var_name.len() - 1; // len of var codes its index
 
     // collect all BDD vars that encode the HCTL var
@@ -124,6 +129,7 @@ pub fn project_out_bn_vars(
     graph: &SymbolicAsyncGraph,
     colored_state_set: &GraphColoredVertices,
 ) -> GraphColoredVertices {
+    let _span = span("quantifier_projection");
     // project out the bdd vars coding variables from the Boolean network
     let result_bdd = colored_state_set
         .clone()
@@ -203,3 +209,50 @@ pub fn restrict_stg_unit_bdd(
     // the validity of the
     new_graph.unwrap()
 }
+
+/// Encode the (raw) set of `states` as the states of the `index`-th variable of a `wild-card
+/// relation`, i.e., move the states to the `index`-th extra BDD vars of the extended `graph`.
+///
+/// The raw sets of wild-card relations are then built from these (e.g., using intersection and
+/// union). For instance, the relation `%r({x}, {y})%` with `{x}` in `A` and `{y}` in `B` is the
+/// intersection of the encoding of `A` with index 0, and the encoding of `B` with index 1.
+pub fn encode_relation_variable(
+    graph: &SymbolicAsyncGraph,
+    states: &GraphColoredVertices,
+    index: usize,
+) -> GraphColoredVertices {
+    compute_valid_domain_for_var(graph, states, &hctl_var_name(index))
+}
+
+/// Rename the HCTL variable with index `from` to the variable with index `to` in a "dirty" `set`
+/// of the extended `graph` (e.g., to combine the results of different formulae).
+///
+/// Return an error if one of the variables is not supported by the graph, or if the `set`
+/// already depends on the variable `to`.
+pub fn rename_hctl_var(
+    graph: &SymbolicAsyncGraph,
+    set: &GraphColoredVertices,
+    from: usize,
+    to: usize,
+) -> Result<GraphColoredVertices, String> {
+    hctl_var_bdd_variables(graph, from)?;
+    let target_vars = hctl_var_bdd_variables(graph, to)?;
+    if from == to {
+        return Ok(set.clone());
+    }
+    if target_vars
+        .iter()
+        .any(|var| set.as_bdd().support_set_contains(var))
+    {
+        return Err(format!(
+            "The set already depends on HCTL variable `{}`.",
+            hctl_var_name(to)
+        ));
+    }
+    Ok(substitute_hctl_var(
+        graph,
+        set,
+        hctl_var_name(from).as_str(),
+        hctl_var_name(to).as_str(),
+    ))
+}
@@ -2,9 +2,10 @@
 //! highly useful for memoization during evaluation.
 
 use crate::evaluation::canonization::get_canonical_and_renaming;
-use crate::evaluation::{FormulaWithDomains, VarDomainMap};
-use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
-use crate::preprocessing::operator_enums::Atomic;
+use crate::evaluation::{FormulaWithDomains, VarDomainMap, VarRenameMap};
+use crate::mc_utils::is_ctl_formula;
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType, VarDomain};
+use crate::preprocessing::operator_enums::{Atomic, HybridOp};
 
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
@@ -22,11 +23,11 @@ struct NodeWithDomains<'a> {
 }
 
 impl NodeWithDomains<'_> {
-    pub fn new(subtree: &HctlTreeNode, domains: VarDomainMap) -> NodeWithDomains {
+    pub fn new(subtree: &HctlTreeNode, domains: VarDomainMap) -> NodeWithDomains<'_> {
         NodeWithDomains { subtree, domains }
     }
 
-    pub fn new_empty_doms(subtree: &HctlTreeNode) -> NodeWithDomains {
+    pub fn new_empty_doms(subtree: &HctlTreeNode) -> NodeWithDomains<'_> {
         NodeWithDomains {
             subtree,
             domains: VarDomainMap::new(),
@@ -55,6 +56,7 @@ impl PartialOrd for NodeWithDomains<'_> {
 /// This function uses canonization and thus recognizes duplicates with differently named
 /// variables (e.g., `AX {y}` and `AX {z}`).
 /// Return the CANONICAL versions of duplicate sub-formulae + the number of their appearances.
+/// If all the formulae are pure CTL formulae (see [is_ctl_formula]), the canonization is skipped.
 ///
 /// Note that except for wild-card properties, the terminal nodes (props, vars, constants)
 /// are not considered.
@@ -73,6 +75,9 @@ pub fn mark_duplicates_canonized_multiple(
     //  2) a mapping from each (canonical) free variable in the sub-formula to its corresponding domain
     let mut same_height_formulae: HashSet<FormulaWithDomains> = HashSet::new();
 
+    // pure CTL formulae do not need the canonization
+    let ctl_only = root_nodes.iter().all(is_ctl_formula);
+
     // find the maximal root height, and push each root node to the queue
     let mut last_height = 0;
     for root_node in root_nodes {
@@ -95,8 +100,12 @@ pub fn mark_duplicates_canonized_multiple(
         }
 
         // get canonical version of the current formula and corresponding variable renaming map
-        let (current_formula, renaming) =
-            get_canonical_and_renaming(current_node.subtree.to_string());
+        // (sub-formulae of pure CTL formulae have no variables, so they are already canonical)
+        let (current_formula, renaming) = if ctl_only {
+            (current_node.subtree.to_string(), VarRenameMap::new())
+        } else {
+            get_canonical_and_renaming(current_node.subtree.to_string())
+        };
 
         // rename the variables in the domain map to their canonical form (duplicate formulae are always canonical)
         // only include the FREE canonical variables that are actually contained in the sub-formula
@@ -158,12 +167,15 @@ pub fn mark_duplicates_canonized_multiple(
                 heap_queue.push(NodeWithDomains::new(left, current_node.domains.clone()));
                 heap_queue.push(NodeWithDomains::new(right, current_node.domains.clone()));
             }
-            NodeType::Hybrid(_, variable, domain, child) => {
+            NodeType::Hybrid(op, variable, domain, child) => {
                 let mut child_w_domains = NodeWithDomains::new(child, current_node.domains.clone());
                 // add the domain of the new quantified variable to the domain list
-                child_w_domains
-                    .domains
-                    .insert(variable.clone(), domain.clone());
+                // (domain of a jump only restricts the target states, not the variable)
+                if *op != HybridOp::Jump {
+                    child_w_domains
+                        .domains
+                        .insert(variable.clone(), domain.as_ref().map(VarDomain::label));
+                }
                 heap_queue.push(child_w_domains);
             }
         }
@@ -180,10 +192,101 @@ pub fn mark_duplicates_canonized_single(
     mark_duplicates_canonized_multiple(&vec![root_node.clone()])
 }
 
+/// Divide the formulae into groups such that the formulae sharing some duplicate sub-formula
+/// (see [mark_duplicates_canonized_multiple]) are in the same group. The formulae of a group can
+/// then be evaluated with a common cache (e.g., by the same thread).
+///
+/// Return the group index of each formula, where the groups are numbered in the order of their
+/// first formulae.
+pub fn group_formulae_sharing_duplicates(root_nodes: &[HctlTreeNode]) -> Vec<usize> {
+    let duplicates = mark_duplicates_canonized_multiple(&root_nodes.to_vec());
+    let ctl_only = root_nodes.iter().all(is_ctl_formula);
+
+    // union-find structure over the formulae, merging formulae with a common duplicate
+    let mut parents: Vec<usize> = (0..root_nodes.len()).collect();
+    fn find(parents: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parents[root] != root {
+            root = parents[root];
+        }
+        parents[i] = root;
+        root
+    }
+    let mut first_occurrence: HashMap<FormulaWithDomains, usize> = HashMap::new();
+    for (i, root_node) in root_nodes.iter().enumerate() {
+        let mut sub_formulae = HashSet::new();
+        collect_canonical_sub_formulae(
+            root_node,
+            &VarDomainMap::new(),
+            ctl_only,
+            &mut sub_formulae,
+        );
+        for sub_formula in sub_formulae {
+            if !duplicates.contains_key(&sub_formula) {
+                continue;
+            }
+            let j = *first_occurrence.entry(sub_formula).or_insert(i);
+            let (root_i, root_j) = (find(&mut parents, i), find(&mut parents, j));
+            parents[root_i.max(root_j)] = root_i.min(root_j);
+        }
+    }
+
+    // number the groups by their first formulae
+    let mut group_indices: HashMap<usize, usize> = HashMap::new();
+    (0..root_nodes.len())
+        .map(|i| {
+            let root = find(&mut parents, i);
+            let num_groups = group_indices.len();
+            *group_indices.entry(root).or_insert(num_groups)
+        })
+        .collect()
+}
+
+/// Collect the canonical versions of all the sub-formulae of the `node` (with the domains of their
+/// free variables), the same way as they are compared in [mark_duplicates_canonized_multiple].
+fn collect_canonical_sub_formulae(
+    node: &HctlTreeNode,
+    domains: &VarDomainMap,
+    ctl_only: bool,
+    sub_formulae: &mut HashSet<FormulaWithDomains>,
+) {
+    let (formula, renaming) = if ctl_only {
+        (node.to_string(), VarRenameMap::new())
+    } else {
+        get_canonical_and_renaming(node.to_string())
+    };
+    let mut canonical_domains = VarDomainMap::new();
+    for (variable, domain) in domains {
+        if let Some(canonical_variable) = renaming.get(variable) {
+            canonical_domains.insert(canonical_variable.clone(), domain.clone());
+        }
+    }
+    sub_formulae.insert((formula, canonical_domains));
+
+    match &node.node_type {
+        NodeType::Terminal(_) => {}
+        NodeType::Unary(_, child) => {
+            collect_canonical_sub_formulae(child, domains, ctl_only, sub_formulae)
+        }
+        NodeType::Binary(_, left, right) => {
+            collect_canonical_sub_formulae(left, domains, ctl_only, sub_formulae);
+            collect_canonical_sub_formulae(right, domains, ctl_only, sub_formulae);
+        }
+        NodeType::Hybrid(op, variable, domain, child) => {
+            let mut child_domains = domains.clone();
+            if *op != HybridOp::Jump {
+                child_domains.insert(variable.clone(), domain.as_ref().map(VarDomain::label));
+            }
+            collect_canonical_sub_formulae(child, &child_domains, ctl_only, sub_formulae);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::evaluation::mark_duplicates::{
-        mark_duplicates_canonized_multiple, mark_duplicates_canonized_single,
+        group_formulae_sharing_duplicates, mark_duplicates_canonized_multiple,
+        mark_duplicates_canonized_single,
     };
     use crate::evaluation::VarDomainMap;
     use crate::preprocessing::parser::{
@@ -326,4 +429,29 @@ mod tests {
         let duplicates = mark_duplicates_canonized_single(&tree);
         assert_eq!(duplicates, expected_duplicates);
     }
+
+    #[test]
+    /// Test dividing the formulae into groups sharing duplicate sub-formulae.
+    fn grouping_formulae_sharing_duplicates() {
+        let bn = BooleanNetwork::try_from_bnet("v1, v1\nv2, v2").unwrap();
+        let ctx = SymbolicContext::new(&bn).unwrap();
+        let formulae = [
+            "!{x}: AG EF {x}",
+            "EF v1",
+            "v1 & EX v2",
+            "!{y}: (AG EF {y} & v1)",
+            "AX v2 | EX v2",
+        ];
+        let trees = formulae
+            .iter()
+            .map(|f| parse_and_minimize_hctl_formula(&ctx, f).unwrap())
+            .collect::<Vec<_>>();
+        // the attractor pattern is shared by the first and fourth formula, `EX v2` by the third
+        // and fifth formula
+        assert_eq!(
+            group_formulae_sharing_duplicates(&trees),
+            vec![0, 1, 2, 0, 2]
+        );
+        assert!(group_formulae_sharing_duplicates(&[]).is_empty());
+    }
 }
@@ -4,12 +4,22 @@ use biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices;
 use std::collections::{BTreeMap, HashMap};
 
 pub mod algorithm;
+pub mod annotation;
+pub mod approximation;
+pub mod basins;
+pub mod debugger;
 pub mod eval_context;
+pub mod explicit_eval;
 pub mod mark_duplicates;
+pub mod reversed_graph;
+pub mod snapshots;
+pub mod spilling;
 
 mod canonization;
-mod hctl_operators_eval;
-mod low_level_operations;
+pub(crate) mod hctl_operators_eval;
+pub(crate) mod low_level_operations;
+
+pub use low_level_operations::{encode_relation_variable, rename_hctl_var};
 
 /// Shorthand for mapping of free variables to (optional) labels of their domain.
 pub type VarDomainMap = BTreeMap<String, Option<String>>;
@@ -23,3 +33,89 @@ pub type VarRenameMap = HashMap<String, String>;
 /// Shorthand for mapping between string labels (domain label, proposition, formula) and the corresponding
 /// set it evaluates to.
 pub type LabelToSetMap = HashMap<String, GraphColoredVertices>;
+
+/// Algorithm computing the fixed point of a reachability operator (`EF` or `EU`).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReachabilityAlgorithm {
+    /// Saturation: each round only adds the predecessors by the first variable that adds any new
+    /// states. This usually keeps the intermediate BDDs small.
+    #[default]
+    Saturation,
+    /// Frontier sets: each round adds the predecessors (by all variables) of only the states added
+    /// in the previous round. This outperforms the saturation on some model families.
+    Frontier,
+}
+
+impl ReachabilityAlgorithm {
+    /// Get the algorithm by its name (`saturation` or `frontier`).
+    pub fn from_name(name: &str) -> Option<ReachabilityAlgorithm> {
+        match name {
+            "saturation" => Some(ReachabilityAlgorithm::Saturation),
+            "frontier" => Some(ReachabilityAlgorithm::Frontier),
+            _ => None,
+        }
+    }
+}
+
+/// Algorithms used to evaluate the individual temporal operators. By default, the saturation is
+/// used for all of them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OperatorAlgorithms {
+    /// Algorithm computing the `EF` operator.
+    pub ef: ReachabilityAlgorithm,
+    /// Algorithm computing the `EU` operator.
+    pub eu: ReachabilityAlgorithm,
+}
+
+/// Optional settings of the evaluation.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EvalOptions {
+    /// Rewrite the formulae into forms that are (usually) evaluated faster before the evaluation.
+    /// See [crate::preprocessing::rewriting::optimize_formula].
+    pub optimize: bool,
+    /// Restrict the network to the smallest module of its regulatory graph that contains all the
+    /// propositions, if this preserves the results (see
+    /// [crate::reduction::restrict_to_formulae_module]). This is only applied by the whole
+    /// analysis (such as `analyse_formulae_with_options`), as the results then refer to the
+    /// restricted network.
+    pub modular: bool,
+    /// Names of network variables that are not allowed to fire transitions during the whole
+    /// evaluation, i.e., the formulae are evaluated on the sub-graph of the remaining update
+    /// functions (see [crate::mc_utils::with_frozen_variables]). The frozen variables keep their
+    /// value, but it is not fixed (the analysis covers all their values).
+    pub frozen_variables: Vec<String>,
+    /// Also write the results of the cached (duplicate) closed sub-formulae, such as the attractor
+    /// states, into the result archive, so that later runs can reuse them (see
+    /// [crate::generate_output::build_result_archive_with_intermediates]). This is only applied
+    /// by the whole analysis when the results are written into an archive.
+    pub dump_intermediates: bool,
+    /// Make the whole analysis reproducible bit-for-bit across runs and machines: the formulae
+    /// are evaluated by a single thread in their given order (so that the progress output, the
+    /// order of results, and the cached sub-formulae do not depend on the scheduling), and the
+    /// output omits all measured times. Note that the time and memory limits can still stop the
+    /// analysis at different points. This is only applied by the whole analysis (such as
+    /// `analyse_formulae_with_options`), the evaluation itself is always deterministic.
+    pub deterministic: bool,
+    /// Spill the cached results with at least the given number of BDD nodes to temporary files,
+    /// and reload them once they are needed (see [eval_context::EvalContext::enable_spilling]).
+    /// This is only applied by the whole analysis (such as `analyse_formulae_with_options`), for
+    /// the analyses in which the cached results would not fit into the memory.
+    pub spill_threshold: Option<usize>,
+    /// Number of HCTL variables of the extended graph used by the whole analysis (such as
+    /// `analyse_formulae_with_options`). By default, the minimal number needed by the formulae is
+    /// used (see [crate::mc_utils::required_hctl_vars]). A larger number can be set, e.g., to
+    /// produce results compatible with other runs; a smaller one is an error.
+    pub num_hctl_vars: Option<usize>,
+    /// Report a snapshot of each running fixed point (of `EF`, `EU`, `AU`, and `EG`) after every
+    /// given number of its iterations (see [snapshots]). This is only applied by the whole
+    /// analysis (such as `analyse_formulae_with_options`), which prints the sizes of the snapshots
+    /// in its progress output.
+    pub snapshot_interval: Option<usize>,
+    /// File into which the whole analysis writes the latest snapshot of a running fixed point
+    /// (see [snapshots::SnapshotHook::with_checkpoint]). Each formula writes into its own file,
+    /// given by [snapshots::formula_checkpoint_path]. Only used with `snapshot_interval`.
+    pub checkpoint_path: Option<String>,
+    /// Algorithms used to evaluate the individual temporal operators (see
+    /// [eval_context::EvalContext::algorithms]).
+    pub algorithms: OperatorAlgorithms,
+}
@@ -0,0 +1,152 @@
+//! Time-reversed view of the asynchronous transition graph.
+//!
+//! The [TransitionSystem] trait abstracts the successor and predecessor operations of the
+//! symbolic graph, so that the same (reachability) procedures can run on the graph itself or on
+//! its time-reversed view [ReversedGraph], in which `post` and `pre` are swapped. This serves as
+//! an infrastructure for past-time operators, and it also gives direct backward analyses, such
+//! as computing all states that can reach a given set (see [can_reach]).
+
+use crate::_aeon_algorithms::saturated_reachability::reach_fwd;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::VariableId;
+
+/// Transition relation over the colored states of a symbolic graph.
+pub trait TransitionSystem {
+    /// Symbolic graph whose states (and colors) the transition system ranges over.
+    fn as_symbolic_graph(&self) -> &SymbolicAsyncGraph;
+
+    /// Compute all successors of the states in the `set`.
+    fn post(&self, set: &GraphColoredVertices) -> GraphColoredVertices;
+
+    /// Compute all predecessors of the states in the `set`.
+    fn pre(&self, set: &GraphColoredVertices) -> GraphColoredVertices;
+
+    /// Compute the successors of the states in the `set` by updating the given variable.
+    fn var_post(&self, var: VariableId, set: &GraphColoredVertices) -> GraphColoredVertices;
+
+    /// Compute the predecessors of the states in the `set` by updating the given variable.
+    fn var_pre(&self, var: VariableId, set: &GraphColoredVertices) -> GraphColoredVertices;
+}
+
+impl TransitionSystem for SymbolicAsyncGraph {
+    fn as_symbolic_graph(&self) -> &SymbolicAsyncGraph {
+        self
+    }
+
+    fn post(&self, set: &GraphColoredVertices) -> GraphColoredVertices {
+        SymbolicAsyncGraph::post(self, set)
+    }
+
+    fn pre(&self, set: &GraphColoredVertices) -> GraphColoredVertices {
+        SymbolicAsyncGraph::pre(self, set)
+    }
+
+    fn var_post(&self, var: VariableId, set: &GraphColoredVertices) -> GraphColoredVertices {
+        SymbolicAsyncGraph::var_post(self, var, set)
+    }
+
+    fn var_pre(&self, var: VariableId, set: &GraphColoredVertices) -> GraphColoredVertices {
+        SymbolicAsyncGraph::var_pre(self, var, set)
+    }
+}
+
+/// Time-reversed view of a symbolic graph, i.e., the graph with all transitions reversed.
+#[derive(Clone, Copy)]
+pub struct ReversedGraph<'a> {
+    graph: &'a SymbolicAsyncGraph,
+}
+
+impl<'a> ReversedGraph<'a> {
+    /// Create the time-reversed view of the `graph`.
+    pub fn new(graph: &'a SymbolicAsyncGraph) -> ReversedGraph<'a> {
+        ReversedGraph { graph }
+    }
+
+    /// The original (not reversed) graph.
+    pub fn original(&self) -> &'a SymbolicAsyncGraph {
+        self.graph
+    }
+}
+
+impl TransitionSystem for ReversedGraph<'_> {
+    fn as_symbolic_graph(&self) -> &SymbolicAsyncGraph {
+        self.graph
+    }
+
+    fn post(&self, set: &GraphColoredVertices) -> GraphColoredVertices {
+        self.graph.pre(set)
+    }
+
+    fn pre(&self, set: &GraphColoredVertices) -> GraphColoredVertices {
+        self.graph.post(set)
+    }
+
+    fn var_post(&self, var: VariableId, set: &GraphColoredVertices) -> GraphColoredVertices {
+        self.graph.var_pre(var, set)
+    }
+
+    fn var_pre(&self, var: VariableId, set: &GraphColoredVertices) -> GraphColoredVertices {
+        self.graph.var_post(var, set)
+    }
+}
+
+/// Compute all states reachable from the `initial` states in the transition system (including
+/// the initial states themselves), using the saturation-based reachability.
+pub fn reachable_from<G: TransitionSystem>(
+    graph: &G,
+    initial: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    let symbolic_graph = graph.as_symbolic_graph();
+    let unit = symbolic_graph.mk_unit_colored_vertices();
+    let variables: Vec<VariableId> = symbolic_graph.variables().collect();
+    reach_fwd(graph, &initial.intersect(&unit), &unit, &variables)
+}
+
+/// Compute all states of the `graph` that can reach the `target` states (including the target
+/// states themselves). This is the forward reachability in the time-reversed graph, and it
+/// corresponds to the formula `EF %target%`.
+pub fn can_reach(
+    graph: &SymbolicAsyncGraph,
+    target: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    reachable_from(&ReversedGraph::new(graph), target)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::reversed_graph::{
+        can_reach, reachable_from, ReversedGraph, TransitionSystem,
+    };
+    use crate::model_checking::model_check_formula;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test that the reversed graph swaps successors and predecessors, and compare the backward
+    /// reachability with the corresponding formula.
+    fn test_reversed_graph() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -? c\n$a: !b\n$c: b & f").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let reversed = ReversedGraph::new(&graph);
+        let set = model_check_formula("a & ~c", &graph).unwrap();
+        assert_eq!(reversed.post(&set), graph.pre(&set));
+        assert_eq!(reversed.pre(&set), graph.post(&set));
+        let a = bn.as_graph().find_variable("a").unwrap();
+        assert_eq!(reversed.var_post(a, &set), graph.var_pre(a, &set));
+        assert_eq!(reversed.var_pre(a, &set), graph.var_post(a, &set));
+
+        let unit = graph.unit_colored_vertices();
+        let expected = model_check_formula("EF (a & ~c)", &graph).unwrap();
+        assert_eq!(can_reach(&graph, &set), expected.intersect(unit));
+        assert_eq!(reachable_from(&reversed, &set), can_reach(&graph, &set));
+
+        // the forward reachable states contain the initial states, and are closed under successors
+        let initial = model_check_formula("~a & ~b & ~c", &graph).unwrap();
+        let reachable = reachable_from(&graph, &initial);
+        assert!(initial.intersect(unit).is_subset(&reachable));
+        assert!(graph.post(&reachable).is_subset(&reachable));
+    }
+}
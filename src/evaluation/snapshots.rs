@@ -0,0 +1,280 @@
+//! Snapshots of the partial results of long-running fixed-point computations.
+//!
+//! A [SnapshotHook] attached to the [EvalContext] (see [EvalContext::attach_snapshots]) receives
+//! the current approximation of each running fixed point (of the `EF`, `EU`, `AU`, and `EG`
+//! operators) every few iterations. The approximations of `EF`, `EU`, and `AU` grow towards the
+//! result, so they are under-approximations that can already be acted upon (e.g., the states that
+//! are already known to reach a target). The approximations of `EG` shrink towards the result.
+//!
+//! Optionally, the latest snapshot is also written into a checkpoint file (see
+//! [SnapshotHook::with_checkpoint] and [read_checkpoint]). The file is replaced atomically, so
+//! that an interrupted write never leaves an incomplete checkpoint.
+//!
+//! [EvalContext]: crate::evaluation::eval_context::EvalContext
+//! [EvalContext::attach_snapshots]: crate::evaluation::eval_context::EvalContext::attach_snapshots
+
+use biodivine_lib_bdd::Bdd;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Counter to give unique names to the temporary checkpoint files of this process.
+static CHECKPOINT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// The current approximation of a running fixed point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedPointSnapshot {
+    /// The sub-formula whose fixed point is computed.
+    pub formula: String,
+    /// Number of the iterations of the fixed point so far.
+    pub iteration: usize,
+    /// The current approximation (with the same symbolic context as the evaluated graph).
+    pub approximation: GraphColoredVertices,
+    /// True if the approximation is a subset of the final result (`EF`, `EU`, `AU`), false if it
+    /// is a superset (`EG`).
+    pub is_under_approximation: bool,
+}
+
+/// Callback receiving the snapshots of the running fixed points.
+pub type SnapshotCallback = dyn FnMut(&FixedPointSnapshot) + Send;
+
+/// Receiver of the snapshots attached to the evaluation, with the number of iterations between
+/// two snapshots of the same fixed point, and an optional checkpoint file.
+///
+/// The callback is shared, so that the same hook can be used by several evaluation threads.
+#[derive(Clone)]
+pub struct SnapshotHook {
+    callback: Arc<Mutex<SnapshotCallback>>,
+    interval: usize,
+    checkpoint_path: Option<PathBuf>,
+}
+
+impl SnapshotHook {
+    /// Pass the snapshot of each fixed point to the `callback` after every `interval` iterations
+    /// (at least one).
+    pub fn new(callback: Arc<Mutex<SnapshotCallback>>, interval: usize) -> SnapshotHook {
+        SnapshotHook {
+            callback,
+            interval: interval.max(1),
+            checkpoint_path: None,
+        }
+    }
+
+    /// Also write each snapshot into the checkpoint file at `path` (atomically replacing the
+    /// previous one). A snapshot that can not be written is skipped.
+    ///
+    /// Hooks used by several evaluations at once should write into different files (e.g., see
+    /// [formula_checkpoint_path]), otherwise their snapshots replace each other.
+    pub fn with_checkpoint<P: AsRef<Path>>(mut self, path: P) -> SnapshotHook {
+        self.checkpoint_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Process the `approximation` of the fixed point of the `formula` after the given number
+    /// of iterations, if a snapshot is due.
+    pub(crate) fn observe(
+        &self,
+        formula: &str,
+        is_under_approximation: bool,
+        iteration: usize,
+        approximation: &GraphColoredVertices,
+    ) {
+        if iteration == 0 || !iteration.is_multiple_of(self.interval) {
+            return;
+        }
+        let snapshot = FixedPointSnapshot {
+            formula: formula.to_string(),
+            iteration,
+            approximation: approximation.clone(),
+            is_under_approximation,
+        };
+        (self.callback.lock().unwrap())(&snapshot);
+        if let Some(path) = &self.checkpoint_path {
+            let _ = write_checkpoint(path, &snapshot);
+        }
+    }
+}
+
+impl fmt::Debug for SnapshotHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SnapshotHook {{ interval: {} }}", self.interval)
+    }
+}
+
+impl PartialEq for SnapshotHook {
+    /// Hooks are equal if they share the same callback.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.callback, &other.callback)
+            && self.interval == other.interval
+            && self.checkpoint_path == other.checkpoint_path
+    }
+}
+
+impl Eq for SnapshotHook {}
+
+/// Path of the checkpoint file of the formula with the given (1-based) `number`, derived from the
+/// checkpoint `path` of the whole analysis by appending the number to the file stem (e.g.,
+/// `checkpoint.txt` gives `checkpoint-2.txt` for the second formula).
+pub fn formula_checkpoint_path(path: &Path, number: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{stem}-{number}.{}", extension.to_string_lossy()),
+        None => format!("{stem}-{number}"),
+    };
+    path.with_file_name(file_name)
+}
+
+/// Write the `snapshot` into the checkpoint file at `path`. The file contains the formula, the
+/// number of iterations, the kind of the approximation (`under` or `over`), and the BDD of the
+/// approximation, each on a separate line.
+///
+/// The snapshot is first written into a temporary file next to the checkpoint, which then
+/// replaces the checkpoint.
+fn write_checkpoint(path: &Path, snapshot: &FixedPointSnapshot) -> Result<(), std::io::Error> {
+    let id = CHECKPOINT_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = path.with_file_name(format!(".{file_name}.{}-{id}.tmp", std::process::id()));
+    let written = (|| {
+        let mut file = File::create(&temp_path)?;
+        writeln!(file, "{}", snapshot.formula)?;
+        writeln!(file, "{}", snapshot.iteration)?;
+        let kind = if snapshot.is_under_approximation {
+            "under"
+        } else {
+            "over"
+        };
+        writeln!(file, "{kind}")?;
+        snapshot.approximation.as_bdd().write_as_string(&mut file)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)
+    })();
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Read the snapshot from the checkpoint file at `path` (see [SnapshotHook::with_checkpoint]).
+/// The approximation is loaded into the symbolic context of the `graph`, which must be the same
+/// as the context of the evaluation that wrote the checkpoint.
+pub fn read_checkpoint(
+    path: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<FixedPointSnapshot, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut lines = content.lines();
+    let mut next_line = || {
+        lines
+            .next()
+            .ok_or(format!("Checkpoint `{path}` is incomplete."))
+    };
+    let formula = next_line()?.to_string();
+    let iteration = next_line()?
+        .parse::<usize>()
+        .map_err(|e| format!("Checkpoint `{path}` is invalid: {e}"))?;
+    let is_under_approximation = next_line()? == "under";
+    let bdd = Bdd::from_string(next_line()?);
+    if bdd.num_vars() != graph.symbolic_context().bdd_variable_set().num_vars() {
+        return Err(format!(
+            "Checkpoint `{path}` is not compatible with the symbolic context."
+        ));
+    }
+    Ok(FixedPointSnapshot {
+        formula,
+        iteration,
+        approximation: GraphColoredVertices::new(bdd, graph.symbolic_context()),
+        is_under_approximation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_model_checking::_test_util::unique_temp_path;
+    use crate::evaluation::algorithm::{compute_steady_states, eval_node};
+    use crate::evaluation::eval_context::EvalContext;
+    use crate::evaluation::snapshots::{
+        formula_checkpoint_path, read_checkpoint, FixedPointSnapshot, SnapshotHook,
+    };
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::preprocessing::parser::parse_and_minimize_hctl_formula;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    /// Test that the snapshots of a saturation are growing under-approximations of its result,
+    /// and that the latest snapshot is written into the checkpoint file.
+    fn test_fixed_point_snapshots() {
+        // `a` is constant, `b` follows `a`, and `c` follows `b`
+        let bn = BooleanNetwork::try_from("a -> a\na -> b\nb -> c\n$a: a\n$b: a\n$c: b").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let tree =
+            parse_and_minimize_hctl_formula(graph.symbolic_context(), "EF (a & b & c)").unwrap();
+        let steady_states = compute_steady_states(&graph);
+        let expected = eval_node(
+            tree.clone(),
+            &graph,
+            &mut EvalContext::from_single_tree(&tree),
+            &steady_states,
+        );
+
+        let snapshots = Arc::new(Mutex::new(Vec::<FixedPointSnapshot>::new()));
+        let recorded = snapshots.clone();
+        let checkpoint = unique_temp_path("checkpoint.txt");
+        let hook = SnapshotHook::new(
+            Arc::new(Mutex::new(move |snapshot: &FixedPointSnapshot| {
+                recorded.lock().unwrap().push(snapshot.clone())
+            })),
+            1,
+        )
+        .with_checkpoint(&checkpoint);
+        let mut eval_info = EvalContext::from_single_tree(&tree);
+        eval_info.attach_snapshots(hook);
+        let result = eval_node(tree, &graph, &mut eval_info, &steady_states);
+        assert_eq!(result, expected);
+
+        let snapshots = snapshots.lock().unwrap();
+        assert!(!snapshots.is_empty());
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            assert!(snapshot.is_under_approximation);
+            assert_eq!(snapshot.iteration, i + 1);
+            assert!(snapshot.approximation.is_subset(&result));
+            if i > 0 {
+                assert!(snapshots[i - 1]
+                    .approximation
+                    .is_subset(&snapshot.approximation));
+            }
+        }
+
+        let loaded = read_checkpoint(checkpoint.to_str().unwrap(), &graph).unwrap();
+        assert_eq!(&loaded, snapshots.last().unwrap());
+        // no temporary files are left behind
+        let parent = checkpoint.parent().unwrap();
+        let file_name = checkpoint.file_name().unwrap().to_str().unwrap();
+        assert!(!std::fs::read_dir(parent).unwrap().any(|entry| entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(&format!(".{file_name}."))));
+        std::fs::remove_file(checkpoint).unwrap();
+    }
+
+    #[test]
+    /// Test deriving the checkpoint files of individual formulae.
+    fn test_formula_checkpoint_path() {
+        let path = std::path::Path::new("results/checkpoint.txt");
+        assert_eq!(
+            formula_checkpoint_path(path, 2),
+            std::path::PathBuf::from("results/checkpoint-2.txt")
+        );
+        let path = std::path::Path::new("checkpoint");
+        assert_eq!(
+            formula_checkpoint_path(path, 10),
+            std::path::PathBuf::from("checkpoint-10")
+        );
+    }
+}
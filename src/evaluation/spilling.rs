@@ -0,0 +1,185 @@
+//! Contains the structures to spill large cached results to disk, for the analyses in which
+//! the cached sub-formulae would not fit into the memory.
+//!
+//! The results larger than a given threshold (in BDD nodes) are serialized into temporary files
+//! (in the binary format of `biodivine-lib-bdd`), and they are reloaded from disk once the cache
+//! needs them. See [crate::evaluation::eval_context::EvalContext::enable_spilling].
+
+use crate::evaluation::{FormulaWithDomains, VarRenameMap};
+
+use biodivine_lib_bdd::Bdd;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Counter to give unique names to the spill directories and files of this process.
+static SPILL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory holding the spilled files, which is removed (with its content) once dropped.
+#[derive(Debug, PartialEq, Eq)]
+struct SpillDirectory {
+    path: PathBuf,
+}
+
+impl Drop for SpillDirectory {
+    fn drop(&mut self) {
+        // the directory might have been removed externally, there is nothing to do then
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Storage of the cached results spilled to disk, each with its variable renaming.
+///
+/// The files live in a fresh directory, which is removed once the store (and all its clones,
+/// which share the directory) is dropped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpillStore {
+    directory: Arc<SpillDirectory>,
+    /// Minimal size (number of BDD nodes) of the results to spill.
+    threshold: usize,
+    /// Mapping between the spilled `sub-formulae` and their files and var renaming.
+    spilled: HashMap<FormulaWithDomains, (PathBuf, VarRenameMap)>,
+}
+
+impl SpillStore {
+    /// Create a store spilling the results with at least `threshold` BDD nodes into a fresh
+    /// sub-directory of `parent_dir`.
+    pub fn new(parent_dir: &Path, threshold: usize) -> Result<SpillStore, String> {
+        let id = SPILL_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = parent_dir.join(format!("hctl-spill-{}-{id}", std::process::id()));
+        std::fs::create_dir_all(&path)
+            .map_err(|e| format!("Cannot create spill directory `{}`: {e}", path.display()))?;
+        Ok(SpillStore {
+            directory: Arc::new(SpillDirectory { path }),
+            threshold,
+            spilled: HashMap::new(),
+        })
+    }
+
+    /// Get the path of the directory with the spilled files.
+    pub fn directory(&self) -> &Path {
+        self.directory.path.as_path()
+    }
+
+    /// Check whether the `set` is large enough to be spilled.
+    pub fn should_spill(&self, set: &GraphColoredVertices) -> bool {
+        set.symbolic_size() >= self.threshold
+    }
+
+    /// Number of results currently spilled to disk.
+    pub fn num_spilled(&self) -> usize {
+        self.spilled.len()
+    }
+
+    /// Check whether the result of the `sub_formula` is spilled.
+    pub fn contains(&self, sub_formula: &FormulaWithDomains) -> bool {
+        self.spilled.contains_key(sub_formula)
+    }
+
+    /// Write the result of the `sub_formula` (with its var `renaming`) to disk.
+    pub fn spill(
+        &mut self,
+        sub_formula: FormulaWithDomains,
+        set: &GraphColoredVertices,
+        renaming: VarRenameMap,
+    ) -> Result<(), String> {
+        let id = SPILL_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = self.directory().join(format!("set-{id}.bdd"));
+        write_set_file(&path, set)?;
+        if let Some((old_path, _)) = self.spilled.insert(sub_formula, (path, renaming)) {
+            let _ = std::fs::remove_file(old_path);
+        }
+        Ok(())
+    }
+
+    /// Reload the spilled result of the `sub_formula` (with its var renaming) into the
+    /// symbolic context of the `graph`, if it is spilled.
+    pub fn load(
+        &self,
+        sub_formula: &FormulaWithDomains,
+        graph: &SymbolicAsyncGraph,
+    ) -> Option<Result<(GraphColoredVertices, VarRenameMap), String>> {
+        let (path, renaming) = self.spilled.get(sub_formula)?;
+        Some(read_set_file(path, graph).map(|set| (set, renaming.clone())))
+    }
+
+    /// Remove the spilled result of the `sub_formula` (including its file), if it is spilled.
+    pub fn remove(&mut self, sub_formula: &FormulaWithDomains) {
+        if let Some((path, _)) = self.spilled.remove(sub_formula) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Write the BDD of the colored `set` into the file at `path` (in the binary format).
+pub fn write_set_file(path: &Path, set: &GraphColoredVertices) -> Result<(), String> {
+    let file =
+        File::create(path).map_err(|e| format!("Cannot create `{}`: {e}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    set.as_bdd()
+        .write_as_bytes(&mut writer)
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Cannot write `{}`: {e}", path.display()))
+}
+
+/// Read the colored set written by [write_set_file] from the file at `path`, in the symbolic
+/// context of the `graph` (which must be the same as the context it was written from).
+pub fn read_set_file(
+    path: &Path,
+    graph: &SymbolicAsyncGraph,
+) -> Result<GraphColoredVertices, String> {
+    let file = File::open(path).map_err(|e| format!("Cannot open `{}`: {e}", path.display()))?;
+    let bdd = Bdd::read_as_bytes(&mut BufReader::new(file))
+        .map_err(|e| format!("Cannot read `{}`: {e}", path.display()))?;
+    Ok(GraphColoredVertices::new(bdd, graph.symbolic_context()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::spilling::SpillStore;
+    use crate::evaluation::VarDomainMap;
+    use crate::mc_utils::get_extended_symbolic_graph;
+
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use std::collections::HashMap;
+
+    #[test]
+    /// Test spilling sets to disk and reloading them.
+    fn test_spill_store() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let set = stg.fix_network_variable(bn.as_graph().find_variable("a").unwrap(), true);
+        let sub_formula = ("(a)".to_string(), VarDomainMap::new());
+        let renaming = HashMap::from([("x".to_string(), "var0".to_string())]);
+
+        let mut store = SpillStore::new(&std::env::temp_dir(), 2).unwrap();
+        let directory = store.directory().to_path_buf();
+        assert!(store.should_spill(&set));
+        assert!(!store.should_spill(&stg.mk_empty_colored_vertices()));
+
+        store
+            .spill(sub_formula.clone(), &set, renaming.clone())
+            .unwrap();
+        assert!(store.contains(&sub_formula));
+        assert_eq!(store.num_spilled(), 1);
+        let (loaded, loaded_renaming) = store.load(&sub_formula, &stg).unwrap().unwrap();
+        assert_eq!(loaded, set);
+        assert_eq!(loaded_renaming, renaming);
+
+        store.remove(&sub_formula);
+        assert!(!store.contains(&sub_formula));
+        assert!(store.load(&sub_formula, &stg).is_none());
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+
+        // the directory is removed with the (last clone of the) store
+        let clone = store.clone();
+        drop(store);
+        assert!(directory.exists());
+        drop(clone);
+        assert!(!directory.exists());
+    }
+}
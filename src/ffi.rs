@@ -0,0 +1,239 @@
+//! C-compatible API, so that the model checker can be embedded in non-Rust tools (C/C++, or
+//! other languages with C interop). Only available with the `ffi` feature.
+//!
+//! The graph and result sets are exposed through opaque handles, which must be released by the
+//! corresponding `*_free` functions. Functions that can fail return a null pointer (or a negative
+//! number), and the error message can then be obtained by [hctl_last_error].
+//!
+//! The C header is in `include/hctl_model_checker.h` (generated by `cbindgen`).
+
+use crate::mc_utils::get_extended_symbolic_graph;
+use crate::model_checking::model_check_formula;
+
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::BooleanNetwork;
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr::null;
+
+thread_local! {
+    /// Message of the last error that occurred in the current thread.
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Opaque handle to the symbolic state-transition graph of a BN model.
+pub struct HctlGraph {
+    graph: SymbolicAsyncGraph,
+}
+
+/// Opaque handle to a (sanitized) result of the model checking, a set of colored states.
+pub struct HctlResult {
+    result: GraphColoredVertices,
+}
+
+/// Store the error message, so that it can be retrieved by [hctl_last_error].
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Run the function, and convert both the errors and panics into the "last error" message
+/// (panics must not unwind across the FFI boundary). The message of a previous error is cleared
+/// first, so it does not outlive a successful call.
+fn run_ffi_call<T>(function: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = None);
+    match catch_unwind(AssertUnwindSafe(function)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            None
+        }
+        Err(_) => {
+            set_last_error("Unexpected internal error.".to_string());
+            None
+        }
+    }
+}
+
+/// Convert the C string into a Rust string slice.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a valid null-terminated string.
+unsafe fn read_c_str<'a>(string: *const c_char) -> Result<&'a str, String> {
+    if string.is_null() {
+        return Err("Unexpected null string.".to_string());
+    }
+    CStr::from_ptr(string)
+        .to_str()
+        .map_err(|_| "String is not valid UTF-8.".to_string())
+}
+
+/// Get the message of the error of the last fallible call (such as [hctl_graph_new] or
+/// [hctl_check_formula]) in the current thread, or null if that call succeeded. The string is
+/// owned by the library, and is valid until the next fallible call.
+#[no_mangle]
+pub extern "C" fn hctl_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| match last_error.borrow().as_ref() {
+        Some(message) => message.as_ptr(),
+        None => null(),
+    })
+}
+
+/// Build the symbolic graph for a BN model in aeon format, supporting the given number of HCTL
+/// variables (the maximal number of variables in the checked formulae).
+///
+/// Returns null on error. The graph must be released by [hctl_graph_free].
+///
+/// # Safety
+///
+/// The `aeon_model` must be a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hctl_graph_new(
+    aeon_model: *const c_char,
+    num_hctl_vars: u16,
+) -> *mut HctlGraph {
+    let graph = run_ffi_call(|| {
+        let bn = BooleanNetwork::try_from(read_c_str(aeon_model)?)?;
+        let graph = get_extended_symbolic_graph(&bn, num_hctl_vars)?;
+        Ok(HctlGraph { graph })
+    });
+    match graph {
+        Some(graph) => Box::into_raw(Box::new(graph)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Release the graph created by [hctl_graph_new].
+///
+/// # Safety
+///
+/// The `graph` must be null or a pointer returned by [hctl_graph_new] that was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn hctl_graph_free(graph: *mut HctlGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Model-check the HCTL formula on the graph.
+///
+/// Returns null on error (e.g., invalid formula, or the graph does not support enough HCTL
+/// variables). The result must be released by [hctl_result_free].
+///
+/// # Safety
+///
+/// The `graph` must be a valid pointer returned by [hctl_graph_new], and the `formula` must be
+/// a valid null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn hctl_check_formula(
+    graph: *const HctlGraph,
+    formula: *const c_char,
+) -> *mut HctlResult {
+    let result = run_ffi_call(|| {
+        let graph = graph.as_ref().ok_or("Unexpected null graph.")?;
+        let result = model_check_formula(read_c_str(formula)?, &graph.graph)?;
+        Ok(HctlResult { result })
+    });
+    match result {
+        Some(result) => Box::into_raw(Box::new(result)),
+        None => std::ptr::null_mut(),
+    }
+}
+
+/// Release the result created by [hctl_check_formula].
+///
+/// # Safety
+///
+/// The `result` must be null or a pointer returned by [hctl_check_formula] that was not
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn hctl_result_free(result: *mut HctlResult) {
+    if !result.is_null() {
+        drop(Box::from_raw(result));
+    }
+}
+
+/// Get the number of (state, color) pairs in the result, or a negative number for null result.
+///
+/// # Safety
+///
+/// The `result` must be null or a valid pointer returned by [hctl_check_formula].
+#[no_mangle]
+pub unsafe extern "C" fn hctl_result_cardinality(result: *const HctlResult) -> f64 {
+    match result.as_ref() {
+        Some(result) => result.result.approx_cardinality(),
+        None => -1.0,
+    }
+}
+
+/// Get the number of colors in the result, or a negative number for null result.
+///
+/// # Safety
+///
+/// The `result` must be null or a valid pointer returned by [hctl_check_formula].
+#[no_mangle]
+pub unsafe extern "C" fn hctl_result_num_colors(result: *const HctlResult) -> f64 {
+    match result.as_ref() {
+        Some(result) => result.result.colors().approx_cardinality(),
+        None => -1.0,
+    }
+}
+
+/// Get the number of states in the result, or a negative number for null result.
+///
+/// # Safety
+///
+/// The `result` must be null or a valid pointer returned by [hctl_check_formula].
+#[no_mangle]
+pub unsafe extern "C" fn hctl_result_num_states(result: *const HctlResult) -> f64 {
+    match result.as_ref() {
+        Some(result) => result.result.vertices().approx_cardinality(),
+        None => -1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::*;
+    use std::ffi::{CStr, CString};
+
+    #[test]
+    /// Test the whole process through the C API (including errors).
+    fn test_ffi_model_checking() {
+        let model = CString::new("a -> b\nb -| a\n$a: !b\n$b: a").unwrap();
+        let attractors = CString::new("!{x}: AG EF {x}").unwrap();
+        let invalid = CString::new("!{x}: !{y}: AX {x} & {y}").unwrap();
+        unsafe {
+            let graph = hctl_graph_new(model.as_ptr(), 1);
+            assert!(!graph.is_null());
+
+            let result = hctl_check_formula(graph, attractors.as_ptr());
+            assert!(!result.is_null());
+            assert_eq!(hctl_result_cardinality(result), 4.0);
+            assert_eq!(hctl_result_num_colors(result), 1.0);
+            assert_eq!(hctl_result_num_states(result), 4.0);
+            hctl_result_free(result);
+
+            // the graph does not support enough HCTL variables
+            let result = hctl_check_formula(graph, invalid.as_ptr());
+            assert!(result.is_null());
+            assert!(!hctl_last_error().is_null());
+            assert_eq!(hctl_result_cardinality(result), -1.0);
+            hctl_graph_free(graph);
+
+            let graph = hctl_graph_new(attractors.as_ptr(), 1);
+            assert!(graph.is_null());
+            let error = CStr::from_ptr(hctl_last_error()).to_str().unwrap();
+            assert!(!error.is_empty());
+
+            // the error is cleared by a successful call
+            let graph = hctl_graph_new(model.as_ptr(), 1);
+            assert!(!graph.is_null());
+            assert!(hctl_last_error().is_null());
+            hctl_graph_free(graph);
+        }
+    }
+}
@@ -1,9 +1,88 @@
 use crate::evaluation::LabelToSetMap;
+use biodivine_lib_bdd::BddVariableSet;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices;
+use biodivine_lib_param_bn::BooleanNetwork;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
-use std::io::{ErrorKind, Write};
-use std::path::Path;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use zip::write::FileOptions;
-use zip::ZipWriter;
+use zip::{DateTime, ZipWriter};
+
+/// Directory of the result archives with the results of the cached sub-formulae (see
+/// [build_result_archive_with_intermediates]).
+pub const INTERMEDIATES_DIR: &str = "intermediates/";
+
+/// Name of the file with the [AnalysisMetadata] in the result archives.
+pub const ANALYSIS_METADATA_FILE: &str = "analysis.txt";
+
+/// Counter to give unique names to the temporary files of the result cache of this process.
+static CACHE_TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Metadata of the analysis that produced a result archive, so that its results can be reused
+/// safely (e.g., by a warm start). Stored in the archive as the [ANALYSIS_METADATA_FILE], with
+/// lines `result NAME` (one for each formula, in their order), `fix NAME=VALUE`, and
+/// `freeze NAME`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnalysisMetadata {
+    /// Names of the results of the formulae (in the order of the formulae).
+    pub result_names: Vec<String>,
+    /// Inputs and parameters fixed during the analysis (see `mc_utils::with_fixed_inputs`).
+    pub fixed_inputs: BTreeMap<String, bool>,
+    /// Variables frozen during the analysis (see `mc_utils::with_frozen_variables`), sorted.
+    pub frozen_variables: Vec<String>,
+}
+
+impl AnalysisMetadata {
+    /// Convert the metadata into the content of the [ANALYSIS_METADATA_FILE].
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        lines.extend(
+            self.result_names
+                .iter()
+                .map(|name| format!("result {name}")),
+        );
+        for (name, value) in &self.fixed_inputs {
+            lines.push(format!("fix {name}={}", *value as u8));
+        }
+        lines.extend(
+            self.frozen_variables
+                .iter()
+                .map(|name| format!("freeze {name}")),
+        );
+        lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+
+    /// Parse the content of the [ANALYSIS_METADATA_FILE].
+    pub fn from_text(text: &str) -> Result<AnalysisMetadata, String> {
+        let mut metadata = AnalysisMetadata::default();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            match line.split_once(' ') {
+                Some(("result", name)) => metadata.result_names.push(name.to_string()),
+                Some(("fix", assignment)) => {
+                    let (name, value) = match assignment.split_once('=') {
+                        Some((name, "0")) => (name, false),
+                        Some((name, "1")) => (name, true),
+                        _ => return Err(format!("Invalid fixed input `{assignment}`.")),
+                    };
+                    metadata.fixed_inputs.insert(name.to_string(), value);
+                }
+                Some(("freeze", name)) => metadata.frozen_variables.push(name.to_string()),
+                _ => return Err(format!("Invalid metadata line `{line}`.")),
+            }
+        }
+        metadata.frozen_variables.sort();
+        Ok(metadata)
+    }
+}
+
+/// Options of the files written into the archives. The modification time is fixed, so that the
+/// archives with the same content are identical (bit-for-bit).
+fn archive_file_options() -> FileOptions {
+    FileOptions::default().last_modified_time(DateTime::default())
+}
 
 /// Create a full results archive for an "result map" of `string -> colored set of states`.
 ///
@@ -16,23 +95,54 @@ pub fn build_result_archive(
     archive_name: &str,
     original_model_str: &str,
     formulae: Vec<String>,
+) -> Result<(), std::io::Error> {
+    build_result_archive_with_intermediates(
+        results,
+        archive_name,
+        original_model_str,
+        formulae,
+        &[],
+        None,
+    )
+}
+
+/// Create a full results archive, same as [build_result_archive], but also include the results
+/// of the cached (duplicate) sub-formulae, given as pairs of their canonical forms and results
+/// (see [crate::evaluation::eval_context::EvalContext::take_intermediates]).
+///
+/// The intermediate results are written into the [INTERMEDIATES_DIR] directory of the archive
+/// (as `0.bdd`, `1.bdd`, ...), together with the metadata file `intermediates.txt` listing their
+/// canonical formulae (in order). Nothing is added if there are no intermediate results.
+///
+/// If provided, the `metadata` of the analysis are written as well (see [AnalysisMetadata]).
+pub fn build_result_archive_with_intermediates(
+    results: LabelToSetMap,
+    archive_name: &str,
+    original_model_str: &str,
+    formulae: Vec<String>,
+    intermediates: &[(String, GraphColoredVertices)],
+    metadata: Option<&AnalysisMetadata>,
 ) -> Result<(), std::io::Error> {
     let archive_path = Path::new(archive_name);
     // If there are some non existing dirs in path, create them.
     let prefix = archive_path
         .parent()
-        .ok_or(std::io::Error::new(ErrorKind::Other, "Invalid path."))?;
+        .ok_or(std::io::Error::other("Invalid path."))?;
     std::fs::create_dir_all(prefix)?;
 
     // Create a zip writer for the desired archive.
     let archive = File::create(archive_path)?;
     let mut zip_writer = ZipWriter::new(archive);
 
+    // The results are sorted by their names, so that the archive does not depend on the order
+    // of the map.
+    let mut results = results.into_iter().collect::<Vec<_>>();
+    results.sort_by(|(name_1, _), (name_2, _)| name_1.cmp(name_2));
     for (set_name, set) in results.iter() {
         // The results (including empty BDDs) go directly into the zip archive.
         let bdd_file_name = format!("{}.bdd", set_name);
         zip_writer
-            .start_file(&bdd_file_name, FileOptions::default())
+            .start_file(&bdd_file_name, archive_file_options())
             .map_err(std::io::Error::from)?;
 
         set.as_bdd().write_as_string(&mut zip_writer)?;
@@ -40,22 +150,144 @@ pub fn build_result_archive(
 
     // Include the original model in the result bundle (we need to load the results back later).
     zip_writer
-        .start_file("model.aeon", FileOptions::default())
+        .start_file("model.aeon", archive_file_options())
         .map_err(std::io::Error::from)?;
     write!(zip_writer, "{original_model_str}")?;
 
     // Include the metadata file with the formulae list.
     zip_writer
-        .start_file("formulae.txt", FileOptions::default())
+        .start_file("formulae.txt", archive_file_options())
         .map_err(std::io::Error::from)?;
     for formula in formulae {
         writeln!(zip_writer, "{formula}")?;
     }
 
+    if let Some(metadata) = metadata {
+        zip_writer
+            .start_file(ANALYSIS_METADATA_FILE, archive_file_options())
+            .map_err(std::io::Error::from)?;
+        write!(zip_writer, "{}", metadata.to_text())?;
+    }
+
+    // The intermediate results are named by their position, as the formulae are not valid names.
+    if !intermediates.is_empty() {
+        for (i, (_, set)) in intermediates.iter().enumerate() {
+            zip_writer
+                .start_file(
+                    format!("{INTERMEDIATES_DIR}{i}.bdd"),
+                    archive_file_options(),
+                )
+                .map_err(std::io::Error::from)?;
+            set.as_bdd().write_as_string(&mut zip_writer)?;
+        }
+        zip_writer
+            .start_file("intermediates.txt", archive_file_options())
+            .map_err(std::io::Error::from)?;
+        for (formula, _) in intermediates {
+            writeln!(zip_writer, "{formula}")?;
+        }
+    }
+
     zip_writer.finish().map_err(std::io::Error::from)?;
     Ok(())
 }
 
+/// Path of the archive with the cached result for the given `key` in the `cache_dir` (see
+/// [write_cached_result]).
+pub fn cached_result_path(cache_dir: &str, key: &str) -> PathBuf {
+    Path::new(cache_dir).join(format!("{key}.zip"))
+}
+
+/// Write the `result` of a single `formula` into the result cache directory `cache_dir`, under
+/// the given `key` (see [crate::analysis::result_cache_key]).
+///
+/// The cached result is a standard result archive (see [build_result_archive]) with the result
+/// named `formula-0`, so it can be also used as any other archive. The archive is first written
+/// into a temporary file, which then replaces the entry, so that concurrent or interrupted runs
+/// never leave an incomplete entry in the cache.
+pub fn write_cached_result(
+    cache_dir: &str,
+    key: &str,
+    original_model_str: &str,
+    formula: &str,
+    result: &GraphColoredVertices,
+) -> Result<(), std::io::Error> {
+    let path = cached_result_path(cache_dir, key);
+    let id = CACHE_TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let temp_path = Path::new(cache_dir).join(format!(".{key}.{}-{id}.tmp", std::process::id()));
+    let written = build_result_archive(
+        LabelToSetMap::from([("formula-0".to_string(), result.clone())]),
+        temp_path
+            .to_str()
+            .ok_or(std::io::Error::other("Invalid cache path."))?,
+        original_model_str,
+        vec![formula.to_string()],
+    )
+    .and_then(|_| std::fs::rename(&temp_path, &path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+    written
+}
+
+/// Formats in which the individual result sets can be exported (see [export_results]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultExportFormat {
+    /// Graphviz rendering of the BDD (`.dot`), with named variables and without the edges
+    /// leading to the `false` terminal.
+    Dot,
+    /// Raw string serialization of the BDD by `biodivine-lib-bdd` (`.bdd`), the same as in the
+    /// result archives.
+    RawBdd,
+}
+
+impl ResultExportFormat {
+    /// Get the format by its name (`dot` or `raw`).
+    pub fn from_name(name: &str) -> Option<ResultExportFormat> {
+        match name {
+            "dot" => Some(ResultExportFormat::Dot),
+            "raw" => Some(ResultExportFormat::RawBdd),
+            _ => None,
+        }
+    }
+
+    /// Extension of the exported files.
+    pub fn extension(&self) -> &str {
+        match self {
+            ResultExportFormat::Dot => "dot",
+            ResultExportFormat::RawBdd => "bdd",
+        }
+    }
+}
+
+/// Export each of the `results` into a separate file `{name}.{extension}` in the `output_dir`,
+/// once for each of the selected `formats`. The `variables` (of the symbolic context of the
+/// results) are used to name the BDD variables in the `.dot` files.
+///
+/// This is useful when debugging the variable ordering or the support of the results.
+pub fn export_results(
+    results: &LabelToSetMap,
+    variables: &BddVariableSet,
+    output_dir: &str,
+    formats: &[ResultExportFormat],
+) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(output_dir)?;
+    for (set_name, set) in results.iter() {
+        for format in formats {
+            let file_path =
+                Path::new(output_dir).join(format!("{set_name}.{}", format.extension()));
+            let mut file = File::create(file_path)?;
+            match format {
+                ResultExportFormat::Dot => set
+                    .as_bdd()
+                    .write_as_dot_string(&mut file, variables, true)?,
+                ResultExportFormat::RawBdd => set.as_bdd().write_as_string(&mut file)?,
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Create an archive with a given name, and put the original model file and file with formulae there.
 pub fn build_initial_archive(
     archive_name: &str,
@@ -66,7 +298,7 @@ pub fn build_initial_archive(
     // If there are some non existing dirs in path, create them.
     let prefix = archive_path
         .parent()
-        .ok_or(std::io::Error::new(ErrorKind::Other, "Invalid path."))?;
+        .ok_or(std::io::Error::other("Invalid path."))?;
     std::fs::create_dir_all(prefix)?;
 
     // Create a zip writer for the desired archive.
@@ -75,13 +307,13 @@ pub fn build_initial_archive(
 
     // Include the original model in the result bundle (we need to load the results back later).
     zip_writer
-        .start_file("model.aeon", FileOptions::default())
+        .start_file("model.aeon", archive_file_options())
         .map_err(std::io::Error::from)?;
     write!(zip_writer, "{original_model_str}")?;
 
     // Include the metadata file with the formulae list.
     zip_writer
-        .start_file("formulae.txt", FileOptions::default())
+        .start_file("formulae.txt", archive_file_options())
         .map_err(std::io::Error::from)?;
     for formula in formulae {
         writeln!(zip_writer, "{formula}")?;
@@ -90,3 +322,93 @@ pub fn build_initial_archive(
     zip_writer.finish().map_err(std::io::Error::from)?;
     Ok(())
 }
+
+/// Namespace of the annotations with model-checking results in the exported SBML models.
+pub const SBML_ANNOTATION_NAMESPACE: &str =
+    "https://github.com/sybila/biodivine-hctl-model-checker";
+
+/// Escape the special XML characters in the attribute value.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Namespace of the SBML-qual elements.
+const SBML_QUAL_NAMESPACE: &str = "http://www.sbml.org/sbml/level3/version1/qual/version1";
+
+/// Export the model into SBML-qual, where each qualitative species is annotated by its value
+/// in the states satisfying each of the properties. The `results` are pairs `(formula, result)`
+/// with (sanitized) results of the model checking.
+///
+/// The value of a species for a property is `0` or `1` if it is fixed in all satisfying states,
+/// `*` if it takes both values, and `none` if there are no satisfying states.
+///
+/// The species are located in the exported XML by their IDs, and an error is returned if some
+/// species cannot be annotated.
+pub fn build_annotated_sbml(
+    bn: &BooleanNetwork,
+    results: &[(String, GraphColoredVertices)],
+) -> Result<String, String> {
+    let sbml = bn.to_sbml(None);
+    let document = roxmltree::Document::parse(sbml.as_str()).map_err(|e| e.to_string())?;
+    let species_ranges = document
+        .descendants()
+        .filter(|node| node.has_tag_name((SBML_QUAL_NAMESPACE, "qualitativeSpecies")))
+        .filter_map(|node| {
+            let id = node.attribute((SBML_QUAL_NAMESPACE, "id"))?;
+            Some((id, node.range()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut annotated_species = Vec::new();
+    for var in bn.variables() {
+        let name = bn.get_variable_name(var);
+        let range = species_ranges
+            .get(name.as_str())
+            .ok_or(format!("Species `{name}` is missing in the exported SBML."))?;
+        // species are written as empty elements, so we add the annotation as their content
+        let element = &sbml[range.clone()];
+        let start_tag = element.strip_suffix("/>").ok_or(format!(
+            "Species `{name}` cannot be annotated, its element is not empty."
+        ))?;
+        let tag_name = start_tag[1..]
+            .split(char::is_whitespace)
+            .next()
+            .unwrap_or_default();
+
+        let mut annotation = format!(
+            "{}><annotation><hctl:properties xmlns:hctl=\"{SBML_ANNOTATION_NAMESPACE}\">",
+            start_tag.trim_end()
+        );
+        for (formula, result) in results {
+            let can_be_true = !result.fix_network_variable(var, true).is_empty();
+            let can_be_false = !result.fix_network_variable(var, false).is_empty();
+            let value = match (can_be_false, can_be_true) {
+                (false, false) => "none",
+                (true, false) => "0",
+                (false, true) => "1",
+                (true, true) => "*",
+            };
+            annotation.push_str(
+                format!(
+                    "<hctl:property hctl:formula=\"{}\" hctl:value=\"{value}\"/>",
+                    escape_xml(formula)
+                )
+                .as_str(),
+            );
+        }
+        annotation.push_str(format!("</hctl:properties></annotation></{tag_name}>").as_str());
+        annotated_species.push((range.clone(), annotation));
+    }
+
+    // replace the elements from the end, so that the remaining ranges stay valid
+    annotated_species.sort_by_key(|(range, _)| range.start);
+    let mut annotated_sbml = sbml.clone();
+    for (range, annotated) in annotated_species.into_iter().rev() {
+        annotated_sbml.replace_range(range, annotated.as_str());
+    }
+    Ok(annotated_sbml)
+}
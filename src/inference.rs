@@ -0,0 +1,217 @@
+//! Checking the consistency of models with observed data.
+//!
+//! Given a set of observed (stable) phenotypes, each given as a named set of states (such as
+//! the observations loaded by `load_inputs::observations`), candidate HCTL properties are
+//! sketched automatically (see [sketch_properties]), and checked on the model. The result is
+//! a report on which colors (parametrizations) of the model are consistent with the data.
+
+use crate::evaluation::LabelToSetMap;
+use crate::mc_utils::get_extended_symbolic_graph;
+use crate::model_checking::model_check_multiple_extended_formulae;
+use crate::postprocessing::sanitizing::extend_colored_vertices;
+use crate::preprocessing::hctl_tree::HctlTreeNode;
+use crate::preprocessing::operator_enums::{BinaryOp, HybridOp, UnaryOp};
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::BooleanNetwork;
+use std::fmt;
+
+/// Candidate property sketched from the observations.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SketchedProperty {
+    /// Human-readable description of the property.
+    pub description: String,
+    /// Extended HCTL formula of the property (observations are referenced as wild-cards).
+    pub tree: HctlTreeNode,
+}
+
+/// Result of a single sketched property.
+#[derive(Clone, Debug)]
+pub struct PropertyConsistency {
+    /// Human-readable description of the property.
+    pub description: String,
+    /// Extended HCTL formula of the property.
+    pub formula: String,
+    /// Colors for which the property holds.
+    pub colors: GraphColors,
+}
+
+/// Report on the consistency of a model with the observations.
+#[derive(Clone, Debug)]
+pub struct ConsistencyReport {
+    /// Results of the individual sketched properties.
+    pub properties: Vec<PropertyConsistency>,
+    /// Colors for which all the properties hold.
+    pub consistent_colors: GraphColors,
+    /// Number of all colors of the model.
+    pub total_colors: f64,
+}
+
+impl ConsistencyReport {
+    /// Check whether the model is consistent with the observations for at least one color.
+    pub fn is_consistent(&self) -> bool {
+        !self.consistent_colors.is_empty()
+    }
+}
+
+impl fmt::Display for ConsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Checked {} properties on {:.0} colors.",
+            self.properties.len(),
+            self.total_colors
+        )?;
+        for property in &self.properties {
+            writeln!(
+                f,
+                "{:.0} colors: {}",
+                property.colors.approx_cardinality(),
+                property.description
+            )?;
+            writeln!(f, "    {}", property.formula)?;
+        }
+        write!(
+            f,
+            "The model is consistent with the observations for {:.0} colors.",
+            self.consistent_colors.approx_cardinality()
+        )
+    }
+}
+
+/// Make the attractor pattern `AG EF {x}`.
+fn mk_attractor_body(var: &str) -> HctlTreeNode {
+    let ef = HctlTreeNode::mk_unary(HctlTreeNode::mk_variable(var), UnaryOp::EF);
+    HctlTreeNode::mk_unary(ef, UnaryOp::AG)
+}
+
+/// Sketch candidate properties relating the model to the observations with the given names:
+///  - each observation is contained in some attractor (`3{x}: @{x}: (%obs% & AG EF {x})`),
+///  - every attractor contains some observation
+///    (`V{x}: @{x}: (AG EF {x} => EF (%obs_1% | ... | %obs_n%))`).
+pub fn sketch_properties(observation_names: &[String]) -> Vec<SketchedProperty> {
+    let mut properties = Vec::new();
+    for name in observation_names {
+        let body = HctlTreeNode::mk_binary(
+            HctlTreeNode::mk_wild_card(name),
+            mk_attractor_body("x"),
+            BinaryOp::And,
+        );
+        let jump = HctlTreeNode::mk_hybrid(body, "x", None, HybridOp::Jump);
+        properties.push(SketchedProperty {
+            description: format!("Observation `{name}` is contained in some attractor."),
+            tree: HctlTreeNode::mk_hybrid(jump, "x", None, HybridOp::Exists),
+        });
+    }
+
+    let any_observation = observation_names
+        .iter()
+        .map(|name| HctlTreeNode::mk_wild_card(name))
+        .reduce(|left, right| HctlTreeNode::mk_binary(left, right, BinaryOp::Or));
+    if let Some(any_observation) = any_observation {
+        let body = HctlTreeNode::mk_binary(
+            mk_attractor_body("x"),
+            HctlTreeNode::mk_unary(any_observation, UnaryOp::EF),
+            BinaryOp::Imp,
+        );
+        let jump = HctlTreeNode::mk_hybrid(body, "x", None, HybridOp::Jump);
+        properties.push(SketchedProperty {
+            description: "There is no attractor outside the observations.".to_string(),
+            tree: HctlTreeNode::mk_hybrid(jump, "x", None, HybridOp::Forall),
+        });
+    }
+    properties
+}
+
+/// Sketch the candidate properties from the named sets of `observations` (see
+/// [sketch_properties]), check them on the model, and report the colors for which they hold.
+///
+/// The observation sets must be in the canonical symbolic context of the model (or in a context
+/// with the same variables and parameters).
+pub fn check_consistency_with_observations(
+    bn: &BooleanNetwork,
+    observations: &LabelToSetMap,
+) -> Result<ConsistencyReport, String> {
+    let mut names: Vec<String> = observations.keys().cloned().collect();
+    names.sort();
+    let properties = sketch_properties(&names);
+
+    // all sketched properties use a single HCTL variable
+    let graph = get_extended_symbolic_graph(bn, 1)?;
+    let mut context_sets = LabelToSetMap::new();
+    for (name, set) in observations {
+        let set = extend_colored_vertices(&graph, set).ok_or(format!(
+            "Observation `{name}` is not compatible with the model."
+        ))?;
+        context_sets.insert(name.clone(), set);
+    }
+    let formulae: Vec<String> = properties.iter().map(|p| p.tree.to_string()).collect();
+    let results = model_check_multiple_extended_formulae(
+        formulae.iter().map(|f| f.as_str()).collect(),
+        &graph,
+        &context_sets,
+    )?;
+
+    // the results are sanitized, so they are compared with the canonical colors
+    let unit_colors = SymbolicAsyncGraph::new(bn)?.mk_unit_colors();
+    let mut consistent_colors = unit_colors.clone();
+    let mut report_properties = Vec::new();
+    for ((property, formula), result) in properties.into_iter().zip(formulae).zip(results) {
+        let colors = result.colors().intersect(&unit_colors);
+        consistent_colors = consistent_colors.intersect(&colors);
+        report_properties.push(PropertyConsistency {
+            description: property.description,
+            formula,
+            colors,
+        });
+    }
+    Ok(ConsistencyReport {
+        properties: report_properties,
+        consistent_colors,
+        total_colors: unit_colors.approx_cardinality(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::LabelToSetMap;
+    use crate::inference::{check_consistency_with_observations, sketch_properties};
+    use crate::preprocessing::parser::parse_extended_formula;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test sketching the properties and checking them against the observations.
+    fn test_consistency_with_observations() {
+        let names = vec!["on".to_string(), "off".to_string()];
+        let properties = sketch_properties(&names);
+        assert_eq!(properties.len(), 3);
+        assert_eq!(
+            properties[2].tree,
+            parse_extended_formula("V{x}: @{x}: (AG EF {x} => EF (%on% | %off%))").unwrap()
+        );
+
+        // `a` is bistable if `f` holds, and it is inactive otherwise
+        let bn = BooleanNetwork::try_from("a -> a\n$a: a & f").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let a = bn.as_graph().find_variable("a").unwrap();
+        let mut observations = LabelToSetMap::new();
+        observations.insert("on".to_string(), graph.mk_subspace(&[(a, true)]));
+        observations.insert("off".to_string(), graph.mk_subspace(&[(a, false)]));
+
+        let report = check_consistency_with_observations(&bn, &observations).unwrap();
+        assert_eq!(report.total_colors, 2.0);
+        assert_eq!(report.properties.len(), 3);
+        // the active state is an attractor only for one color
+        assert_eq!(
+            report.properties[1].description,
+            "Observation `on` is contained in some attractor."
+        );
+        assert_eq!(report.properties[1].colors.approx_cardinality(), 1.0);
+        assert_eq!(report.properties[0].colors.approx_cardinality(), 2.0);
+        assert_eq!(report.properties[2].colors.approx_cardinality(), 2.0);
+        assert_eq!(report.consistent_colors.approx_cardinality(), 1.0);
+        assert!(report.is_consistent());
+    }
+}
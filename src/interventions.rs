@@ -0,0 +1,311 @@
+//! Enumeration of minimal interventions that force a property, i.e., answering the question
+//! "which variables do I perturb to force the phenotype?".
+//!
+//! An intervention permanently fixes some network variables to given values (see
+//! [with_perturbed_variables]). It works for a color if the property holds in all states of the
+//! perturbed network. The interventions are enumerated by increasing size, and only the minimal
+//! ones are reported, i.e., those that work for some colors for which none of their proper subsets
+//! works. They are ranked by their robustness, the fraction of colors for which they work.
+//!
+//! Further, the "driver variables" of a property are identified by fixing each single variable
+//! (to `0` and to `1`) and measuring how much the satisfying colors and states change.
+
+use crate::mc_utils::{
+    check_hctl_var_support, restrict_frozen_variables, with_frozen_variables,
+    with_perturbed_variables,
+};
+use crate::model_checking::{model_check_formula, model_check_tree};
+use crate::postprocessing::sanitizing::{sanitize_colored_vertices, sanitize_colors};
+use crate::preprocessing::parser::parse_and_minimize_hctl_formula;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Intervention permanently fixing some network variables, together with the colors for which
+/// it forces the property.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Intervention {
+    /// Names of the fixed variables with their values (sorted by the names).
+    pub fixings: Vec<(String, bool)>,
+    /// Colors for which the property holds in all states of the perturbed network (sanitized).
+    pub colors: GraphColors,
+    /// Fraction of all colors for which the intervention works.
+    pub robustness: f64,
+}
+
+impl Intervention {
+    /// Number of the fixed variables.
+    pub fn size(&self) -> usize {
+        self.fixings.len()
+    }
+}
+
+/// Enumerate the minimal interventions (fixing at most `max_size` variables) that force the
+/// property given by a closed `formula` for some colors, ranked from the most robust one (and
+/// then from the smallest one).
+///
+/// An intervention is minimal if it works for some colors for which none of its proper
+/// subsets works (including the empty intervention, i.e., the unperturbed network). The
+/// interventions fixing a superset of an intervention working for all colors are not evaluated.
+///
+/// Note that the number of evaluated interventions grows exponentially with `max_size`.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn enumerate_interventions(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+    max_size: usize,
+) -> Result<Vec<Intervention>, String> {
+    let all_colors = sanitize_colors(graph, &graph.mk_unit_colors());
+    let num_colors = all_colors.approx_cardinality();
+    let variables: Vec<String> = graph
+        .variables()
+        .map(|var| graph.get_variable_name(var))
+        .collect();
+
+    // colors for which each evaluated intervention works (indexed by its sorted fixings)
+    let mut working_colors: HashMap<Vec<(String, bool)>, GraphColors> = HashMap::new();
+    working_colors.insert(Vec::new(), forced_colors(formula, graph, &[])?);
+
+    let mut interventions = Vec::new();
+    let mut candidates: Vec<Vec<(String, bool)>> = vec![Vec::new()];
+    for _ in 0..max_size {
+        // extend each candidate by a variable after its last one, so that each set is created once
+        let mut next_candidates = Vec::new();
+        for candidate in &candidates {
+            let first_index = candidate
+                .last()
+                .map(|(last, _)| variables.iter().position(|v| v == last).unwrap() + 1)
+                .unwrap_or(0);
+            for variable in &variables[first_index..] {
+                for value in [false, true] {
+                    let mut fixings = candidate.clone();
+                    fixings.push((variable.clone(), value));
+                    next_candidates.push(fixings);
+                }
+            }
+        }
+
+        for fixings in &next_candidates {
+            let subsets = proper_subsets(fixings);
+            // supersets of interventions working for all colors are never minimal
+            if subsets
+                .iter()
+                .any(|s| working_colors.get(s).is_some_and(|c| *c == all_colors))
+            {
+                continue;
+            }
+            let colors = forced_colors(formula, graph, fixings)?;
+            let is_minimal = !colors.is_empty()
+                && subsets.iter().all(|subset| {
+                    working_colors
+                        .get(subset)
+                        .is_none_or(|subset_colors| !colors.is_subset(subset_colors))
+                });
+            if is_minimal {
+                interventions.push(Intervention {
+                    fixings: fixings.clone(),
+                    robustness: colors.approx_cardinality() / num_colors,
+                    colors: colors.clone(),
+                });
+            }
+            working_colors.insert(fixings.clone(), colors);
+        }
+        candidates = next_candidates;
+    }
+
+    interventions.sort_by(|i1, i2| {
+        i2.robustness
+            .partial_cmp(&i1.robustness)
+            .unwrap_or(Ordering::Equal)
+            .then(i1.size().cmp(&i2.size()))
+            .then(i1.fixings.cmp(&i2.fixings))
+    });
+    Ok(interventions)
+}
+
+/// Impact of permanently fixing a single variable on a property, see [rank_driver_variables].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DriverImpact {
+    /// Name of the fixed variable.
+    pub variable: String,
+    /// Value of the fixed variable.
+    pub value: bool,
+    /// Number of colors that satisfy the property (in some state) only in the original or only
+    /// in the perturbed network.
+    pub changed_colors: f64,
+    /// Number of states (of the fixed subspace) satisfying the property for some color in only
+    /// one of the networks.
+    pub changed_states: f64,
+    /// Number of all colors of the network.
+    pub total_colors: f64,
+}
+
+impl DriverImpact {
+    /// Fraction of the colors whose satisfaction changed (zero if there are no colors).
+    pub fn changed_fraction(&self) -> f64 {
+        if self.total_colors == 0.0 {
+            0.0
+        } else {
+            self.changed_colors / self.total_colors
+        }
+    }
+}
+
+/// Rank the network variables by how much permanently fixing them (to `0` or `1`, see
+/// [with_perturbed_variables]) changes the result of the property given by a closed `formula`.
+///
+/// For each variable and value, the satisfying colors of the perturbed network are compared
+/// with those of the original network, and the satisfying states with those of the original
+/// network in the fixed subspace. The impacts are sorted from the most changed colors (and then
+/// states). The formula is parsed once, and both values of a variable share the frozen graph.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn rank_driver_variables(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Vec<DriverImpact>, String> {
+    let tree = parse_and_minimize_hctl_formula(graph.symbolic_context(), formula)?;
+    if !check_hctl_var_support(graph, tree.clone())? {
+        return Err("Graph does not support enough HCTL state variables".to_string());
+    }
+    let original = model_check_tree(tree.clone(), graph)?;
+    let original_colors = original.colors();
+    let total_colors = sanitize_colors(graph, &graph.mk_unit_colors()).approx_cardinality();
+
+    let mut impacts = Vec::new();
+    for var in graph.variables() {
+        let variable = graph.get_variable_name(var);
+        let frozen = with_frozen_variables(graph, std::slice::from_ref(&variable))?;
+        for value in [false, true] {
+            let perturbed = restrict_frozen_variables(&frozen, &[(variable.clone(), value)]);
+            let result = model_check_tree(tree.clone(), &perturbed)?;
+            let subspace = sanitize_colored_vertices(&perturbed, perturbed.unit_colored_vertices());
+            let original_in_subspace = original.intersect(&subspace);
+
+            let result_colors = result.colors();
+            let changed_colors = original_colors
+                .minus(&result_colors)
+                .union(&result_colors.minus(&original_colors));
+            let changed_states = original_in_subspace
+                .minus(&result)
+                .union(&result.minus(&original_in_subspace))
+                .vertices();
+            impacts.push(DriverImpact {
+                variable: variable.clone(),
+                value,
+                changed_colors: changed_colors.approx_cardinality(),
+                changed_states: changed_states.approx_cardinality(),
+                total_colors,
+            });
+        }
+    }
+
+    impacts.sort_by(|i1, i2| {
+        i2.changed_colors
+            .total_cmp(&i1.changed_colors)
+            .then(i2.changed_states.total_cmp(&i1.changed_states))
+            .then(i1.variable.cmp(&i2.variable))
+            .then(i1.value.cmp(&i2.value))
+    });
+    Ok(impacts)
+}
+
+/// Compute the (sanitized) colors for which the `formula` holds in all states of the network
+/// perturbed by the given `fixings`.
+fn forced_colors(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+    fixings: &[(String, bool)],
+) -> Result<GraphColors, String> {
+    let perturbed = with_perturbed_variables(graph, fixings)?;
+    let result = model_check_formula(formula, &perturbed)?;
+    let unit = sanitize_colored_vertices(&perturbed, perturbed.unit_colored_vertices());
+    Ok(unit.colors().minus(&unit.minus(&result).colors()))
+}
+
+/// Get all proper subsets of the `fixings` (preserving their order).
+fn proper_subsets(fixings: &[(String, bool)]) -> Vec<Vec<(String, bool)>> {
+    let num_subsets = 1usize << fixings.len();
+    (0..num_subsets - 1)
+        .map(|mask| {
+            fixings
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, fixing)| fixing.clone())
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::interventions::{enumerate_interventions, rank_driver_variables};
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test the enumeration of the minimal interventions forcing a property.
+    fn test_enumerate_interventions() {
+        // `a` keeps its value, and the update of `b` is an unknown function of `a`
+        let bn = BooleanNetwork::try_from("a -> a\na -?? b\n$a: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let interventions = enumerate_interventions("AF AG b", &graph, 2).unwrap();
+
+        let summary: Vec<(String, f64)> = interventions
+            .iter()
+            .map(|i| {
+                let fixings: Vec<String> = i
+                    .fixings
+                    .iter()
+                    .map(|(name, value)| format!("{name}={}", u8::from(*value)))
+                    .collect();
+                (fixings.join(","), i.robustness)
+            })
+            .collect();
+        // fixing `b` always works, fixing `a` only works for the colors where `b` follows it
+        assert_eq!(
+            summary,
+            vec![
+                ("b=1".to_string(), 1.0),
+                ("a=0".to_string(), 0.5),
+                ("a=1".to_string(), 0.5),
+            ]
+        );
+        assert_eq!(interventions[0].colors.approx_cardinality(), 4.0);
+
+        // fixing a non-existent variable is not possible, and the formula must be valid
+        assert!(enumerate_interventions("AF AG c", &graph, 1).is_err());
+    }
+
+    #[test]
+    /// Test the ranking of the driver variables of a property.
+    fn test_rank_driver_variables() {
+        // `a` keeps its value, and `b` follows `a`
+        let bn = BooleanNetwork::try_from("a -> a\na -> b\n$a: a\n$b: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let impacts = rank_driver_variables("AG b", &graph).unwrap();
+
+        let summary: Vec<(String, f64, f64)> = impacts
+            .iter()
+            .map(|i| {
+                let fixing = format!("{}={}", i.variable, u8::from(i.value));
+                (fixing, i.changed_colors, i.changed_states)
+            })
+            .collect();
+        // fixing any variable to `0` kills the property, fixing `b` to `1` makes it hold
+        // also in the state `01`, and fixing `a` to `1` changes nothing
+        assert_eq!(
+            summary,
+            vec![
+                ("a=0".to_string(), 1.0, 0.0),
+                ("b=0".to_string(), 1.0, 0.0),
+                ("b=1".to_string(), 0.0, 1.0),
+                ("a=1".to_string(), 0.0, 0.0),
+            ]
+        );
+        assert_eq!(impacts[0].changed_fraction(), 1.0);
+        assert!(rank_driver_variables("AG c", &graph).is_err());
+    }
+}
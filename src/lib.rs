@@ -6,18 +6,58 @@
 //!  - Searching for common sub-formulae across multiple properties.
 //!  - Optimised evaluation for several patterns, such as various attractor types or reachability.
 //!  - Simultaneous evaluation of several formulae, sharing common computation via cache.
+//!  - Interactive model-checking sessions, re-using the results of previous formulae.
+//!  - Statistical (simulation-based) checking of bounded properties on large models.
+//!  - Runtime verification of explicit traces (e.g., of stochastic simulations).
+//!  - Reductions of networks (such as percolation of constants) that speed up the model checking.
+//!  - Checking the consistency of models with observed phenotypes via automatically sketched properties.
+//!  - Enumeration of minimal and maximal trap spaces (per color) as readable subspaces.
+//!  - Enumeration of minimal interventions (variable fixings) forcing a property.
+//!
+//! Modules working with files and zip bundles (`analysis`, `generate_output`, `load_inputs`, and
+//! `result_print`) are only available with the `fs` feature (enabled by default). The `server`
+//! feature provides an HTTP/JSON server, the `wasm` feature provides
+//! a JS-friendly API for WebAssembly builds, and the `ffi` feature provides a C-compatible API.
+//! The `async` feature provides async wrappers of the long-running functions (module `async_api`),
+//! and the `benchmarks` feature provides a built-in benchmark suite (module `benchmarks`).
+//! With the `profiling` feature, the main phases of the computation are measured (see module
+//! `profiling`), and the `color-counts` feature provides exact per-color state counts (module
+//! `postprocessing::color_counts`).
 //!
 
 mod _aeon_algorithms;
+#[cfg(feature = "fs")]
 pub mod analysis;
+#[cfg(feature = "async")]
+pub mod async_api;
+pub mod attractors;
+#[cfg(feature = "benchmarks")]
+pub mod benchmarks;
 pub mod evaluation;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fs")]
 pub mod generate_output;
+pub mod inference;
+pub mod interventions;
+#[cfg(feature = "fs")]
 pub mod load_inputs;
 pub mod mc_utils;
 pub mod model_checking;
 pub mod postprocessing;
 pub mod preprocessing;
+pub mod profiling;
+pub mod reduction;
+#[cfg(feature = "fs")]
 pub mod result_print;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod session;
+pub mod simulation;
+pub mod traces;
+pub mod trap_spaces;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 /// **(internal)** Several complex test scenarios for the model checking procedure.
 #[cfg(test)]
@@ -0,0 +1,494 @@
+//! Conversion of BioModelAnalyzer (BMA) models in JSON format into Boolean networks.
+//!
+//! Variables with range of size one (e.g., `0..1`) are converted directly into Boolean
+//! variables. Larger ranges are encoded by several Boolean variables using the threshold
+//! encoding: a variable `X` with range `min..max` is represented by `X_bk` for `k` in
+//! `min+1..=max`, where `X_bk` is true iff `X >= k`. The value of such variable moves by one
+//! level at a time towards its target, so that the Boolean variables always encode a valid value
+//! (when starting from one).
+//!
+//! The target functions are evaluated for every combination of the regulator values, and the
+//! result is rounded and clamped to the range of the variable. Variables with an empty target
+//! function use the BMA default, the average of the activators minus the average of the
+//! inhibitors (the maximal value is used instead if there are no activators). Variables without
+//! any regulators keep their value.
+
+use crate::load_inputs::multivalued::{build_network, MultiValuedEncoding};
+
+use biodivine_lib_param_bn::BooleanNetwork;
+use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
+
+/// Maximal number of regulator valuations that are enumerated for a single target function.
+const MAX_VALUATIONS: usize = 1 << 16;
+
+/// Variable of the BMA model.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BmaVariable {
+    id: u32,
+    #[serde(default)]
+    name: Option<String>,
+    range_from: u32,
+    range_to: u32,
+    #[serde(default)]
+    formula: Option<String>,
+}
+
+/// Relationship of the BMA model.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BmaRelationship {
+    from_variable: u32,
+    to_variable: u32,
+    #[serde(rename = "Type")]
+    relationship_type: String,
+}
+
+/// The BMA model itself (the content of the `Model` object).
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BmaModel {
+    variables: Vec<BmaVariable>,
+    #[serde(default)]
+    relationships: Vec<BmaRelationship>,
+}
+
+/// The BMA JSON file, either with the model wrapped in `Model` (together with the layout), or
+/// the model object directly.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum BmaFile {
+    Wrapped {
+        #[serde(rename = "Model")]
+        model: BmaModel,
+    },
+    Plain(BmaModel),
+}
+
+/// Expression of the BMA target function, with variables referenced by their (sanitized) names.
+enum Expression {
+    Constant(f64),
+    Variable(String),
+    Negation(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Function(String, Vec<Expression>),
+}
+
+impl Expression {
+    /// Evaluate the expression in the given valuation of variables.
+    fn evaluate(&self, valuation: &HashMap<String, u32>) -> Result<f64, String> {
+        match self {
+            Expression::Constant(value) => Ok(*value),
+            Expression::Variable(name) => Ok(valuation[name] as f64),
+            Expression::Negation(inner) => Ok(-inner.evaluate(valuation)?),
+            Expression::Binary(op, left, right) => {
+                let (left, right) = (left.evaluate(valuation)?, right.evaluate(valuation)?);
+                match op {
+                    '+' => Ok(left + right),
+                    '-' => Ok(left - right),
+                    '*' => Ok(left * right),
+                    _ if right == 0.0 => Err("Division by zero in target function.".to_string()),
+                    _ => Ok(left / right),
+                }
+            }
+            Expression::Function(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.evaluate(valuation))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match (name.as_str(), args.as_slice()) {
+                    ("min", [_, ..]) => Ok(args.iter().copied().fold(f64::INFINITY, f64::min)),
+                    ("max", [_, ..]) => Ok(args.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+                    ("avg", [_, ..]) => Ok(args.iter().sum::<f64>() / args.len() as f64),
+                    ("ceil", [arg]) => Ok(arg.ceil()),
+                    ("floor", [arg]) => Ok(arg.floor()),
+                    ("abs", [arg]) => Ok(arg.abs()),
+                    _ => Err(format!(
+                        "Invalid use of function `{name}` in target function."
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Collect the names of variables used in the expression.
+    fn collect_variables(&self, variables: &mut BTreeSet<String>) {
+        match self {
+            Expression::Constant(_) => {}
+            Expression::Variable(name) => {
+                variables.insert(name.clone());
+            }
+            Expression::Negation(inner) => inner.collect_variables(variables),
+            Expression::Binary(_, left, right) => {
+                left.collect_variables(variables);
+                right.collect_variables(variables);
+            }
+            Expression::Function(_, args) => {
+                args.iter().for_each(|arg| arg.collect_variables(variables));
+            }
+        }
+    }
+}
+
+/// Recursive-descent parser of the BMA target functions.
+struct FormulaParser<'a> {
+    chars: Vec<char>,
+    position: usize,
+    /// Mapping of the variable IDs and original names to the sanitized names.
+    variables: &'a HashMap<String, String>,
+}
+
+impl FormulaParser<'_> {
+    /// Skip whitespace, and return the next char (without consuming it).
+    fn peek(&mut self) -> Option<char> {
+        while self.position < self.chars.len() && self.chars[self.position].is_whitespace() {
+            self.position += 1;
+        }
+        self.chars.get(self.position).copied()
+    }
+
+    /// Consume the expected char, or return an error.
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected `{expected}` in target function."))
+        }
+    }
+
+    /// Parse `term (('+' | '-') term)*`.
+    fn parse_sum(&mut self) -> Result<Expression, String> {
+        let mut expression = self.parse_product()?;
+        while let Some(op @ ('+' | '-')) = self.peek() {
+            self.position += 1;
+            let right = self.parse_product()?;
+            expression = Expression::Binary(op, Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    /// Parse `factor (('*' | '/') factor)*`.
+    fn parse_product(&mut self) -> Result<Expression, String> {
+        let mut expression = self.parse_factor()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.position += 1;
+            let right = self.parse_factor()?;
+            expression = Expression::Binary(op, Box::new(expression), Box::new(right));
+        }
+        Ok(expression)
+    }
+
+    /// Parse a number, a negation, a parenthesized expression, `var(...)`, or a function call.
+    fn parse_factor(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('-') => {
+                self.position += 1;
+                Ok(Expression::Negation(Box::new(self.parse_factor()?)))
+            }
+            Some('(') => {
+                self.position += 1;
+                let expression = self.parse_sum()?;
+                self.expect(')')?;
+                Ok(expression)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => {
+                let number = self.read_while(|c| c.is_ascii_digit() || c == '.');
+                number
+                    .parse::<f64>()
+                    .map(Expression::Constant)
+                    .map_err(|_| format!("Invalid number `{number}` in target function."))
+            }
+            Some(c) if c.is_alphabetic() => {
+                let name = self.read_while(|c| c.is_alphanumeric()).to_lowercase();
+                self.expect('(')?;
+                if name == "var" {
+                    let reference = self.read_while(|c| c != ')');
+                    self.expect(')')?;
+                    let reference = reference.trim();
+                    return match self.variables.get(reference) {
+                        Some(variable) => Ok(Expression::Variable(variable.clone())),
+                        None => Err(format!(
+                            "Unknown variable `{reference}` in target function."
+                        )),
+                    };
+                }
+                let mut args = vec![self.parse_sum()?];
+                while self.peek() == Some(',') {
+                    self.position += 1;
+                    args.push(self.parse_sum()?);
+                }
+                self.expect(')')?;
+                Ok(Expression::Function(name, args))
+            }
+            Some(c) => Err(format!("Unexpected char `{c}` in target function.")),
+            None => Err("Unexpected end of target function.".to_string()),
+        }
+    }
+
+    /// Consume the chars satisfying the condition, and return them.
+    fn read_while(&mut self, condition: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.position < self.chars.len() && condition(self.chars[self.position]) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+}
+
+/// Parse the target function of a BMA variable.
+fn parse_formula(formula: &str, variables: &HashMap<String, String>) -> Result<Expression, String> {
+    let mut parser = FormulaParser {
+        chars: formula.chars().collect(),
+        position: 0,
+        variables,
+    };
+    let expression = parser.parse_sum()?;
+    if parser.peek().is_some() {
+        return Err(format!(
+            "Unexpected trailing input in target function `{formula}`."
+        ));
+    }
+    Ok(expression)
+}
+
+/// Build the BMA default target function: average of the activators minus the average of the
+/// inhibitors (the maximal value of the target is used if there are no activators).
+fn default_formula(activators: &[String], inhibitors: &[String], max_value: u32) -> Expression {
+    let average = |regulators: &[String]| {
+        let args = regulators
+            .iter()
+            .map(|r| Expression::Variable(r.clone()))
+            .collect();
+        Expression::Function("avg".to_string(), args)
+    };
+    let positive = if activators.is_empty() {
+        Expression::Constant(max_value as f64)
+    } else {
+        average(activators)
+    };
+    if inhibitors.is_empty() {
+        positive
+    } else {
+        Expression::Binary('-', Box::new(positive), Box::new(average(inhibitors)))
+    }
+}
+
+/// Replace chars not allowed in variable names by `_`.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Convert the BMA model in JSON format into a Boolean network, encoding the variables with
+/// larger ranges (see the module documentation).
+pub fn bma_json_to_network(json: &str) -> Result<BooleanNetwork, String> {
+    let model = match serde_json::from_str::<BmaFile>(json).map_err(|e| e.to_string())? {
+        BmaFile::Wrapped { model } | BmaFile::Plain(model) => model,
+    };
+
+    // sanitized names, and a mapping of IDs and original names to them
+    let mut names = HashMap::new();
+    let mut references = HashMap::new();
+    for variable in &model.variables {
+        let name = match &variable.name {
+            Some(name) if !name.trim().is_empty() => sanitize_name(name.trim()),
+            _ => format!("v{}", variable.id),
+        };
+        if names.values().any(|n| n == &name) {
+            return Err(format!("Variable `{name}` is defined multiple times."));
+        }
+        references.insert(variable.id.to_string(), name.clone());
+        if let Some(original) = &variable.name {
+            references.insert(original.trim().to_string(), name.clone());
+        }
+        names.insert(variable.id, name);
+    }
+    let ranges = model
+        .variables
+        .iter()
+        .map(|v| (names[&v.id].clone(), (v.range_from, v.range_to)))
+        .collect();
+    let encoding = MultiValuedEncoding::new(ranges)?;
+
+    let mut updates = Vec::new();
+    for variable in &model.variables {
+        let name = &names[&variable.id];
+        let regulators_of_type = |relationship_type: &str| -> Result<Vec<String>, String> {
+            let mut regulators = Vec::new();
+            for relationship in &model.relationships {
+                if relationship.to_variable == variable.id
+                    && relationship.relationship_type == relationship_type
+                {
+                    let regulator = names.get(&relationship.from_variable).ok_or(format!(
+                        "Unknown variable `{}` in relationship.",
+                        relationship.from_variable
+                    ))?;
+                    regulators.push(regulator.clone());
+                }
+            }
+            Ok(regulators)
+        };
+
+        let formula = match variable.formula.as_deref().map(|f| f.trim()) {
+            Some(formula) if !formula.is_empty() => Some(parse_formula(formula, &references)?),
+            _ => {
+                let (activators, inhibitors) = (
+                    regulators_of_type("Activator")?,
+                    regulators_of_type("Inhibitor")?,
+                );
+                if activators.is_empty() && inhibitors.is_empty() {
+                    None
+                } else {
+                    Some(default_formula(&activators, &inhibitors, variable.range_to))
+                }
+            }
+        };
+
+        let Some(formula) = formula else {
+            // variables without regulators keep their value
+            for (_, var) in encoding.levels(name)? {
+                updates.push((var.clone(), var.clone(), BTreeSet::from([var])));
+            }
+            continue;
+        };
+
+        // enumerate the valuations of regulators, and compute the target value for each
+        let mut regulators = BTreeSet::new();
+        formula.collect_variables(&mut regulators);
+        let mut valuations = vec![HashMap::new()];
+        for regulator in &regulators {
+            let (min, max) = encoding.range(regulator)?;
+            if valuations.len() * (max - min + 1) as usize > MAX_VALUATIONS {
+                return Err(format!("Target function of `{name}` has too many inputs."));
+            }
+            valuations = valuations
+                .into_iter()
+                .flat_map(|valuation: HashMap<String, u32>| {
+                    (min..=max).map(move |value| {
+                        let mut valuation = valuation.clone();
+                        valuation.insert(regulator.clone(), value);
+                        valuation
+                    })
+                })
+                .collect();
+        }
+
+        let mut used_vars = BTreeSet::new();
+        let mut conjunctions = Vec::new();
+        for valuation in valuations {
+            let value = formula.evaluate(&valuation)?.round();
+            let target = value.clamp(variable.range_from as f64, variable.range_to as f64) as u32;
+            let literals = regulators
+                .iter()
+                .map(|r| encoding.exactly(r, valuation[r], &mut used_vars))
+                .collect::<Result<Vec<_>, _>>()?;
+            let conjunction = if literals.is_empty() {
+                "true".to_string()
+            } else {
+                format!("({})", literals.join(" & "))
+            };
+            conjunctions.push((target, conjunction));
+        }
+
+        // conditions under which the target value is at least `k`, for each encoding variable
+        // `X_bk` (which then moves towards the target step-wise)
+        let mut at_least_target = Vec::new();
+        for (k, _) in encoding.levels(name)? {
+            let disjuncts = conjunctions
+                .iter()
+                .filter(|(target, _)| *target >= k)
+                .map(|(_, conjunction)| conjunction.clone())
+                .collect::<Vec<_>>();
+            let condition = if disjuncts.is_empty() {
+                "false".to_string()
+            } else if disjuncts.len() == conjunctions.len() {
+                "true".to_string()
+            } else {
+                disjuncts.join(" | ")
+            };
+            at_least_target.push(condition);
+        }
+        updates.extend(encoding.step_updates(name, at_least_target, &used_vars)?);
+    }
+    build_network(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::load_inputs::bma::bma_json_to_network;
+    use crate::model_checking::model_check_formula;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+
+    #[test]
+    /// Test converting a BMA model with a custom and default target functions, including a
+    /// variable with a larger range.
+    fn test_bma_conversion() {
+        let json = r#"{
+  "Model": {
+    "Name": "test",
+    "Variables": [
+      {"Id": 1, "Name": "A", "RangeFrom": 0, "RangeTo": 1, "Formula": ""},
+      {"Id": 2, "Name": "B", "RangeFrom": 0, "RangeTo": 2, "Formula": "var(A) + var(3)"},
+      {"Id": 3, "Name": "C", "RangeFrom": 0, "RangeTo": 1, "Formula": ""}
+    ],
+    "Relationships": [
+      {"Id": 4, "FromVariable": 1, "ToVariable": 2, "Type": "Activator"},
+      {"Id": 5, "FromVariable": 3, "ToVariable": 2, "Type": "Activator"},
+      {"Id": 6, "FromVariable": 2, "ToVariable": 3, "Type": "Inhibitor"}
+    ]
+  },
+  "Layout": {}
+}"#;
+        let bn = bma_json_to_network(json).unwrap();
+        let names = bn
+            .variables()
+            .map(|v| bn.get_variable_name(v).clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["A", "B_b1", "B_b2", "C"]);
+
+        // A has no regulators, so it keeps its value
+        let a = bn.as_graph().find_variable("A").unwrap();
+        assert_eq!(
+            bn.get_update_function(a).as_ref().unwrap().to_string(&bn),
+            "A"
+        );
+        // C = 1 - B (rounded and clamped), so it only depends on `B >= 1`
+        let c = bn.as_graph().find_variable("C").unwrap();
+        let c_regulators = bn.regulators(c);
+        assert_eq!(c_regulators.len(), 1);
+        assert_eq!(bn.get_variable_name(c_regulators[0]), "B_b1");
+        // B can reach 2 (from 1) iff both A and C are active
+        let b2 = bn.as_graph().find_variable("B_b2").unwrap();
+        assert_eq!(bn.regulators(b2).len(), 3);
+
+        assert!(bma_json_to_network("{}").is_err());
+        let invalid =
+            r#"{"Variables": [{"Id": 1, "RangeFrom": 0, "RangeTo": 1, "Formula": "var(X)"}]}"#;
+        assert!(bma_json_to_network(invalid).is_err());
+    }
+
+    #[test]
+    /// Test that the value of a multi-valued variable changes step-wise, so that the valid
+    /// encodings never lead to the invalid ones.
+    fn test_bma_step_wise_encoding() {
+        let json = r#"{"Variables": [
+  {"Id": 1, "Name": "A", "RangeFrom": 0, "RangeTo": 1, "Formula": ""},
+  {"Id": 2, "Name": "B", "RangeFrom": 0, "RangeTo": 2, "Formula": "2"}
+]}"#;
+        let bn = bma_json_to_network(json).unwrap();
+        let stg = SymbolicAsyncGraph::new(&bn).unwrap();
+
+        // B=0 can only step to B=1, and no invalid encoding is reachable from a valid one
+        let jump = model_check_formula("~B_b1 & ~B_b2 & EX B_b2", &stg).unwrap();
+        assert!(jump.is_empty());
+        let invalid = model_check_formula("(B_b1 | ~B_b2) & EF (B_b2 & ~B_b1)", &stg).unwrap();
+        assert!(invalid.is_empty());
+        // the target is eventually reached from all the states
+        let reached = model_check_formula("AF (B_b1 & B_b2)", &stg).unwrap();
+        assert_eq!(reached, stg.mk_unit_colored_vertices());
+    }
+}
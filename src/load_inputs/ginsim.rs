@@ -0,0 +1,328 @@
+//! Conversion of GINsim models (`.ginml` files, or `.zginml` archives) into Boolean networks.
+//!
+//! Multi-valued variables are converted using the Boolean (threshold) encoding. A variable `X`
+//! with maximal value `m > 1` is represented by Boolean variables `X_b1`, ..., `X_bm`, where
+//! `X_bk` is true iff `X >= k`. As in GINsim, the value of `X` moves by one level at a time
+//! towards its target value, i.e., `X_bk` is updated to `X_b(k+1) | (X_b(k-1) & target >= k)`.
+//! Note that the resulting state space also contains states not corresponding to any valid
+//! multi-valued state (e.g., `X_b2 & !X_b1`), but these are never reached from the valid ones.
+
+use crate::load_inputs::multivalued::{build_network, MultiValuedEncoding};
+
+use biodivine_lib_param_bn::BooleanNetwork;
+use roxmltree::{Document, Node};
+use std::collections::BTreeSet;
+
+/// Name of the file with the regulatory graph in GINsim archives.
+pub const GINSIM_ARCHIVE_GRAPH_FILE: &str = "GINsim-data/regulatoryGraph.ginml";
+
+/// Variable of the GINsim model.
+struct GinsimNode {
+    id: String,
+    max_value: u32,
+    base_value: u32,
+    is_input: bool,
+    /// Rules given by the logical functions, pairs `(value, expression)`.
+    value_rules: Vec<(u32, Vec<String>)>,
+    /// Rules given by the logical parameters, pairs `(value, active interactions)`.
+    parameter_rules: Vec<(u32, Vec<String>)>,
+}
+
+/// Interaction of the GINsim model.
+struct GinsimEdge {
+    id: String,
+    from: String,
+    to: String,
+    min_value: u32,
+}
+
+/// Read the attribute of the XML node as a number, or return the default value if missing.
+fn parse_number_attribute(node: Node, attribute: &str, default: u32) -> Result<u32, String> {
+    match node.attribute(attribute) {
+        Some(value) => value
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| format!("Invalid value `{value}` of attribute `{attribute}`.")),
+        None => Ok(default),
+    }
+}
+
+/// Read the variables and interactions of the regulatory graph in the GINML document.
+fn parse_regulatory_graph(ginml: &str) -> Result<(Vec<GinsimNode>, Vec<GinsimEdge>), String> {
+    let document = Document::parse(ginml).map_err(|e| e.to_string())?;
+    let graph = document
+        .descendants()
+        .find(|n| n.has_tag_name("graph"))
+        .ok_or("GINML file does not contain a graph.".to_string())?;
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for element in graph.children().filter(|n| n.is_element()) {
+        if element.has_tag_name("node") {
+            let id = element
+                .attribute("id")
+                .ok_or("Node without `id` attribute.".to_string())?;
+            let mut node = GinsimNode {
+                id: id.to_string(),
+                max_value: parse_number_attribute(element, "maxvalue", 1)?,
+                base_value: parse_number_attribute(element, "basevalue", 0)?,
+                is_input: element.attribute("input") == Some("true"),
+                value_rules: Vec::new(),
+                parameter_rules: Vec::new(),
+            };
+            for rule in element.children().filter(|n| n.is_element()) {
+                let value = parse_number_attribute(rule, "val", 1)?;
+                if rule.has_tag_name("value") {
+                    let expressions = rule
+                        .children()
+                        .filter(|n| n.has_tag_name("exp"))
+                        .filter_map(|n| n.attribute("str"))
+                        .map(|s| s.to_string())
+                        .collect();
+                    node.value_rules.push((value, expressions));
+                } else if rule.has_tag_name("parameter") {
+                    let interactions = rule
+                        .attribute("idActiveInteractions")
+                        .unwrap_or("")
+                        .split_whitespace()
+                        .map(|s| s.to_string())
+                        .collect();
+                    node.parameter_rules.push((value, interactions));
+                }
+            }
+            nodes.push(node);
+        } else if element.has_tag_name("edge") {
+            let attribute = |name: &str| {
+                element
+                    .attribute(name)
+                    .map(|s| s.to_string())
+                    .ok_or(format!("Edge without `{name}` attribute."))
+            };
+            edges.push(GinsimEdge {
+                id: attribute("id")?,
+                from: attribute("from")?,
+                to: attribute("to")?,
+                min_value: parse_number_attribute(element, "minvalue", 1)?,
+            });
+        }
+    }
+    Ok((nodes, edges))
+}
+
+/// Translate the GINsim logical expression into a Boolean expression. The literal `X` means
+/// that `X` is at least at the threshold of its interaction with the target, and `X:k` means
+/// that `X >= k`.
+fn translate_expression(
+    expression: &str,
+    target: &str,
+    edges: &[GinsimEdge],
+    encoding: &MultiValuedEncoding,
+    used_vars: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    let mut translated = String::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '!' | '&' | '|' | '(' | ')' | ' ' => translated.push(c),
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                let threshold = if chars.next_if_eq(&':').is_some() {
+                    let mut number = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+                        number.push(c);
+                    }
+                    number
+                        .parse::<u32>()
+                        .map_err(|_| format!("Invalid threshold in expression `{expression}`."))?
+                } else {
+                    edges
+                        .iter()
+                        .find(|e| e.from == name && e.to == target)
+                        .map(|e| e.min_value)
+                        .unwrap_or(1)
+                };
+                translated.push_str(encoding.at_least(&name, threshold, used_vars)?.as_str());
+            }
+            _ => {
+                return Err(format!(
+                    "Unexpected char `{c}` in expression `{expression}`."
+                ))
+            }
+        }
+    }
+    Ok(format!("({translated})"))
+}
+
+/// Build the condition for the target value of the node being given by the logical parameter
+/// (exactly the given interactions are active).
+fn parameter_condition(
+    node: &GinsimNode,
+    active_interactions: &[String],
+    edges: &[GinsimEdge],
+    encoding: &MultiValuedEncoding,
+    used_vars: &mut BTreeSet<String>,
+) -> Result<String, String> {
+    let mut literals = Vec::new();
+    for edge in edges.iter().filter(|e| e.to == node.id) {
+        let literal = encoding.at_least(&edge.from, edge.min_value, used_vars)?;
+        if active_interactions.contains(&edge.id) {
+            literals.push(literal);
+        } else {
+            literals.push(format!("!{literal}"));
+        }
+    }
+    if literals.is_empty() {
+        Ok("true".to_string())
+    } else {
+        Ok(format!("({})", literals.join(" & ")))
+    }
+}
+
+/// Convert the GINML document (content of a `.ginml` file) into a Boolean network, encoding the
+/// multi-valued variables (see the module documentation).
+pub fn ginml_to_network(ginml: &str) -> Result<BooleanNetwork, String> {
+    let (nodes, edges) = parse_regulatory_graph(ginml)?;
+    let encoding = MultiValuedEncoding::new(
+        nodes
+            .iter()
+            .map(|n| (n.id.clone(), (0, n.max_value)))
+            .collect(),
+    )?;
+
+    let mut updates = Vec::new();
+    for node in &nodes {
+        // conditions under which the corresponding rule determines the target value
+        let mut used_vars = BTreeSet::new();
+        let mut rules = Vec::new();
+        for (value, expressions) in &node.value_rules {
+            for expression in expressions {
+                let condition =
+                    translate_expression(expression, &node.id, &edges, &encoding, &mut used_vars)?;
+                rules.push((*value, condition));
+            }
+        }
+        for (value, interactions) in &node.parameter_rules {
+            let condition =
+                parameter_condition(node, interactions, &edges, &encoding, &mut used_vars)?;
+            rules.push((*value, condition));
+        }
+
+        if node.is_input {
+            // inputs keep their value
+            for (_, var) in encoding.levels(&node.id)? {
+                updates.push((var.clone(), var.clone(), BTreeSet::from([var])));
+            }
+            continue;
+        }
+        // target is `>= k` if some rule with value `>= k` holds, or no rule holds and the base
+        // value is `>= k` (the value then moves towards the target step-wise)
+        let mut at_least_target = Vec::new();
+        for (k, _) in encoding.levels(&node.id)? {
+            let mut disjuncts = rules
+                .iter()
+                .filter(|(value, _)| *value >= k)
+                .map(|(_, condition)| condition.clone())
+                .collect::<Vec<_>>();
+            if node.base_value >= k {
+                if rules.is_empty() {
+                    disjuncts.push("true".to_string());
+                } else {
+                    let conditions = rules.iter().map(|(_, c)| c.clone()).collect::<Vec<_>>();
+                    disjuncts.push(format!("!({})", conditions.join(" | ")));
+                }
+            }
+            let condition = if disjuncts.is_empty() {
+                "false".to_string()
+            } else {
+                disjuncts.join(" | ")
+            };
+            at_least_target.push(condition);
+        }
+        updates.extend(encoding.step_updates(&node.id, at_least_target, &used_vars)?);
+    }
+    build_network(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::load_inputs::ginsim::ginml_to_network;
+    use crate::model_checking::model_check_formula;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+
+    #[test]
+    /// Test converting a GINML model with both logical functions and logical parameters,
+    /// including a multi-valued variable.
+    fn test_ginml_conversion() {
+        let ginml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gxl xmlns:xlink="http://www.w3.org/1999/xlink">
+  <graph class="regulatory" id="test" nodeorder="A B C">
+    <node id="A" maxvalue="1" input="true"/>
+    <node id="B" maxvalue="2">
+      <value val="1"><exp str="A &amp; !C"/></value>
+      <value val="2"><exp str="A &amp; C"/></value>
+    </node>
+    <node id="C" maxvalue="1" basevalue="1">
+      <parameter idActiveInteractions="B:C" val="0"/>
+    </node>
+    <edge id="A:B" from="A" to="B" minvalue="1" sign="positive"/>
+    <edge id="C:B" from="C" to="B" minvalue="1" sign="unknown"/>
+    <edge id="B:C" from="B" to="C" minvalue="2" sign="negative"/>
+  </graph>
+</gxl>"#;
+        let bn = ginml_to_network(ginml).unwrap();
+        assert_eq!(bn.num_vars(), 4);
+        let names = bn
+            .variables()
+            .map(|v| bn.get_variable_name(v).clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["A", "B_b1", "B_b2", "C"]);
+
+        // inputs are constant, and C is inhibited by B at level 2
+        let a = bn.as_graph().find_variable("A").unwrap();
+        assert_eq!(
+            bn.get_update_function(a).as_ref().unwrap().to_string(&bn),
+            "A"
+        );
+        let c = bn.as_graph().find_variable("C").unwrap();
+        let c_regulators = bn.regulators(c);
+        assert_eq!(c_regulators.len(), 1);
+        assert_eq!(bn.get_variable_name(c_regulators[0]), "B_b2");
+
+        assert!(ginml_to_network("<gxl></gxl>").is_err());
+        let invalid = r#"<gxl><graph><node id="A"><value val="1"><exp str="X"/></value></node></graph></gxl>"#;
+        assert!(ginml_to_network(invalid).is_err());
+    }
+
+    #[test]
+    /// Test that the value of a node with three levels changes step-wise towards its target,
+    /// so that the valid encodings never lead to the invalid ones.
+    fn test_ginml_step_wise_encoding() {
+        let ginml = r#"<gxl><graph class="regulatory" id="test" nodeorder="A X">
+  <node id="A" maxvalue="1" input="true"/>
+  <node id="X" maxvalue="2"><value val="2"><exp str="A"/></value></node>
+  <edge id="A:X" from="A" to="X" minvalue="1" sign="positive"/>
+</graph></gxl>"#;
+        let bn = ginml_to_network(ginml).unwrap();
+        let stg = SymbolicAsyncGraph::new(&bn).unwrap();
+
+        // the value never jumps between 0 and 2 (in either direction)
+        let jump = model_check_formula("~X_b1 & ~X_b2 & EX X_b2", &stg).unwrap();
+        assert!(jump.is_empty());
+        let jump = model_check_formula("X_b1 & X_b2 & EX ~X_b1", &stg).unwrap();
+        assert!(jump.is_empty());
+        // no invalid encoding is reachable from a valid one
+        let invalid = model_check_formula("(X_b1 | ~X_b2) & EF (X_b2 & ~X_b1)", &stg).unwrap();
+        assert!(invalid.is_empty());
+        // the value goes to 2 if A is active, and to 0 (the base value) otherwise
+        let reached = model_check_formula(
+            "(A => AF AG (X_b1 & X_b2)) & (~A => AF AG ~(X_b1 | X_b2))",
+            &stg,
+        )
+        .unwrap();
+        assert_eq!(reached, stg.mk_unit_colored_vertices());
+    }
+}
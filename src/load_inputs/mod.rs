@@ -0,0 +1,791 @@
+//! Contains wrappers for loading inputs from the files
+
+use crate::evaluation::LabelToSetMap;
+use crate::generate_output::{
+    cached_result_path, AnalysisMetadata, ANALYSIS_METADATA_FILE, INTERMEDIATES_DIR,
+};
+use crate::load_inputs::observations::load_observations;
+use crate::preprocessing::phenotypes::{process_phenotype_declarations, Phenotypes};
+use crate::preprocessing::tokenizer::STATE_KEYWORD;
+use biodivine_lib_bdd::Bdd;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, SymbolicAsyncGraph, SymbolicContext,
+};
+use biodivine_lib_param_bn::BooleanNetwork;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+use serde::Deserialize;
+
+pub mod bma;
+pub mod ginsim;
+mod multivalued;
+pub mod observations;
+pub mod truth_table;
+
+/// Formats of BN models supported by [network_from_str] and [load_network].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelFormat {
+    Aeon,
+    Bnet,
+    BooleanNet,
+    Sbml,
+    /// GINsim models (see [ginsim]).
+    Ginml,
+    /// BioModelAnalyzer models in JSON (see [bma]).
+    Bma,
+    /// Models given by truth tables in CSV (see [truth_table]).
+    TruthTable,
+}
+
+impl ModelFormat {
+    /// Get the format corresponding to the file extension (if there is one).
+    pub fn from_extension(extension: &str) -> Option<ModelFormat> {
+        match extension {
+            "aeon" => Some(ModelFormat::Aeon),
+            "bnet" => Some(ModelFormat::Bnet),
+            "booleannet" => Some(ModelFormat::BooleanNet),
+            "sbml" => Some(ModelFormat::Sbml),
+            "ginml" => Some(ModelFormat::Ginml),
+            "json" => Some(ModelFormat::Bma),
+            "csv" => Some(ModelFormat::TruthTable),
+            _ => None,
+        }
+    }
+
+    /// Guess the format of the model from its content. The `aeon` format is used when the
+    /// content does not resemble any other format.
+    pub fn detect(content: &str) -> ModelFormat {
+        let trimmed = content.trim_start();
+        if trimmed.starts_with('<') {
+            return if trimmed.contains("<sbml") {
+                ModelFormat::Sbml
+            } else {
+                ModelFormat::Ginml
+            };
+        }
+        if trimmed.starts_with('{') {
+            return ModelFormat::Bma;
+        }
+
+        let mut lines = content
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        let Some(first_line) = lines.next() else {
+            return ModelFormat::Aeon;
+        };
+        let is_regulation = ["->", "-|", "-?"]
+            .iter()
+            .any(|arrow| first_line.contains(arrow));
+        if first_line.to_lowercase().starts_with("targets") {
+            ModelFormat::Bnet
+        } else if first_line.contains("*=") || first_line.contains("* =") {
+            ModelFormat::BooleanNet
+        } else if is_regulation || first_line.starts_with('$') {
+            ModelFormat::Aeon
+        } else if first_line.contains(',') {
+            // rows of truth tables only contain values `0`, `1`, and `-`
+            let is_table_row = |line: &str| {
+                line.split(',')
+                    .all(|cell| matches!(cell.trim(), "0" | "1" | "-"))
+            };
+            match lines.next() {
+                Some(line) if is_table_row(line) => ModelFormat::TruthTable,
+                _ => ModelFormat::Bnet,
+            }
+        } else {
+            ModelFormat::Aeon
+        }
+    }
+}
+
+/// Read the BN model from a string in the given format, or detect the format automatically if
+/// it is not given (see [ModelFormat::detect]).
+///
+/// Multi-valued variables of GINsim and BMA models are encoded by several Boolean variables
+/// (see [ginsim] and [bma]).
+pub fn network_from_str(
+    content: &str,
+    format: Option<ModelFormat>,
+) -> Result<BooleanNetwork, String> {
+    match format.unwrap_or_else(|| ModelFormat::detect(content)) {
+        ModelFormat::Aeon => BooleanNetwork::try_from(content),
+        ModelFormat::Bnet => BooleanNetwork::try_from_bnet(content),
+        ModelFormat::BooleanNet => BooleanNetwork::try_from_booleannet(content).map(|(bn, _)| bn),
+        ModelFormat::Sbml => BooleanNetwork::try_from_sbml(content).map(|(bn, _)| bn),
+        ModelFormat::Ginml => ginsim::ginml_to_network(content),
+        ModelFormat::Bma => bma::bma_json_to_network(content),
+        ModelFormat::TruthTable => truth_table::truth_tables_to_network(&[content]),
+    }
+}
+
+/// Read the BN model from the file, choosing the format by its extension (see
+/// [ModelFormat::from_extension]). The format is detected from the content for files with
+/// other extensions.
+///
+/// Besides the formats supported by [network_from_str], GINsim archives (`.zginml`) are
+/// supported, and models given by truth tables can also be loaded from a directory with one
+/// `.csv` file per variable (see [truth_table]).
+pub fn load_network<T: AsRef<Path>>(model_path: T) -> Result<BooleanNetwork, String> {
+    let model_path = model_path.as_ref();
+    if model_path.is_dir() {
+        let mut table_paths = std::fs::read_dir(model_path)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("csv"))
+            .collect::<Vec<_>>();
+        table_paths.sort();
+        let tables = table_paths
+            .iter()
+            .map(|path| read_to_string(path).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tables = tables.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+        return truth_table::truth_tables_to_network(&tables);
+    }
+
+    let extension = model_path.extension().and_then(|s| s.to_str());
+    if extension == Some("zginml") {
+        let archive_file = File::open(model_path).map_err(|e| e.to_string())?;
+        let mut archive = ZipArchive::new(archive_file).map_err(|e| e.to_string())?;
+        let ginml = read_zipped_file(&mut archive, ginsim::GINSIM_ARCHIVE_GRAPH_FILE)?;
+        return ginsim::ginml_to_network(ginml.as_str());
+    }
+    let content = read_to_string(model_path).map_err(|e| format!("File not readable: {e}"))?;
+    network_from_str(
+        content.as_str(),
+        extension.and_then(ModelFormat::from_extension),
+    )
+}
+
+/// Read the formulae from the specified file. Ignore lines starting with `#` (comments).
+/// The syntax of these formulae is checked later during parsing.
+///
+/// The file can also contain phenotype declarations (see [crate::preprocessing::phenotypes]),
+/// which are expanded in the formulae.
+///
+/// If the path is `-`, the formulae are read from the standard input instead.
+pub fn load_formulae(formulae_path: &str) -> Result<Vec<String>, String> {
+    let (_, formulae) = load_formulae_with_phenotypes(formulae_path)?;
+    Ok(formulae)
+}
+
+/// Read the formulae from the specified file, same as [load_formulae], but also return the
+/// phenotypes declared in the file (so that they can be used in other formulae).
+pub fn load_formulae_with_phenotypes(
+    formulae_path: &str,
+) -> Result<(Phenotypes, Vec<String>), String> {
+    let formulae_string = if formulae_path == "-" {
+        let mut buffer = String::new();
+        std::io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(|e| e.to_string())?;
+        buffer
+    } else {
+        read_to_string(formulae_path).map_err(|e| e.to_string())?
+    };
+    process_phenotype_declarations(parse_formulae_lines(formulae_string.as_str()))
+}
+
+/// Collect the formulae from a string with one formula per line. Ignore empty lines and lines
+/// starting with `#` (comments), except for those starting with a state literal `#state{...}`.
+pub fn parse_formulae_lines(formulae_string: &str) -> Vec<String> {
+    let mut formulae: Vec<String> = Vec::new();
+    for line in formulae_string.lines() {
+        let trimmed_line = line.trim();
+        if !trimmed_line.is_empty() && !is_comment_line(trimmed_line) {
+            formulae.push(trimmed_line.to_string());
+        }
+    }
+    formulae
+}
+
+/// Check whether the (trimmed) line is a comment, i.e., it starts with `#` that does not begin
+/// a state literal `#state{...}`.
+fn is_comment_line(line: &str) -> bool {
+    match line.strip_prefix('#') {
+        Some(rest) => !rest
+            .strip_prefix(STATE_KEYWORD)
+            .is_some_and(|rest| rest.trim_start().starts_with('{')),
+        None => false,
+    }
+}
+
+/// Read the contents of a file from a zip archive into a string.
+fn read_zipped_file(reader: &mut ZipArchive<File>, file_name: &str) -> Result<String, String> {
+    let mut contents = String::new();
+    let mut file = reader.by_name(file_name).map_err(|e| e.to_string())?;
+    file.read_to_string(&mut contents)
+        .map_err(|e| e.to_string())?;
+    Ok(contents)
+}
+
+/// Read the individual BDD files in a provided (valid) archive into a map from the strings (file names) to colored sets.
+/// The files must be have the `.bdd` extension.
+pub fn load_bdd_bundle(
+    archive_path: &str,
+    symbolic_context: &SymbolicContext,
+) -> Result<LabelToSetMap, String> {
+    // Open the zip archive with classification results (existence of file must be checked).
+    let archive_file = File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| e.to_string())?;
+
+    // Load all class BDDs from files in the archive.
+    let files = archive
+        .file_names()
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+
+    let mut loaded_sets: LabelToSetMap = LabelToSetMap::new();
+
+    for filename in files {
+        // ignore files with different extensions (might be some metadata), and intermediate
+        // results of result archives
+        let extension = Path::new(&filename).extension().and_then(|s| s.to_str());
+        if !matches!(extension, Some("bdd")) || filename.starts_with(INTERMEDIATES_DIR) {
+            continue;
+        }
+
+        let name = filename.strip_suffix(".bdd").ok_or(format!(
+            "Error loading file `{filename}` from the archive {archive_path}."
+        ))?;
+
+        let bdd_string = read_zipped_file(&mut archive, filename.as_str())?;
+        let bdd = Bdd::from_string(bdd_string.as_str());
+        let set = GraphColoredVertices::new(bdd, symbolic_context);
+        loaded_sets.insert(name.to_string(), set);
+    }
+    Ok(loaded_sets)
+}
+
+/// Read the context of wild-cards for the given BN, either from a zip bundle of BDDs (see
+/// [load_bdd_bundle]), or from a CSV file with observations (see [observations]).
+pub fn load_context_sets(
+    context_path: &str,
+    bn: &BooleanNetwork,
+    symbolic_context: &SymbolicContext,
+) -> Result<LabelToSetMap, String> {
+    let extension = Path::new(context_path).extension().and_then(|s| s.to_str());
+    if matches!(extension, Some("csv")) {
+        load_observations(context_path, bn)
+    } else {
+        load_bdd_bundle(context_path, symbolic_context)
+    }
+}
+
+/// Read a result bundle produced by [crate::generate_output::build_result_archive].
+///
+/// Return the original BN model, the list of formulae (in order), and the map of result sets.
+/// The result sets are transferred into the canonical symbolic context of the loaded model
+/// (the results stored in the archive may still contain additional symbolic variables).
+pub fn load_result_archive(
+    archive_path: &str,
+) -> Result<(BooleanNetwork, Vec<String>, LabelToSetMap), String> {
+    let archive_file = File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| e.to_string())?;
+
+    // load the original model and the formulae first
+    let model_string = read_zipped_file(&mut archive, "model.aeon")?;
+    let bn = BooleanNetwork::try_from(model_string.as_str())?;
+    let formulae = read_zipped_file(&mut archive, "formulae.txt")?
+        .lines()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+
+    let canonical_graph = SymbolicAsyncGraph::new(&bn)?;
+
+    let files = archive
+        .file_names()
+        .map(|it| it.to_string())
+        .collect::<Vec<_>>();
+
+    let mut loaded_sets: LabelToSetMap = LabelToSetMap::new();
+    for filename in files {
+        // intermediate results are loaded separately (see `load_intermediate_results`)
+        if filename.starts_with(INTERMEDIATES_DIR) {
+            continue;
+        }
+        let Some(name) = filename.strip_suffix(".bdd") else {
+            continue;
+        };
+        let set = read_archived_set(&mut archive, &filename, &bn, &canonical_graph)
+            .map_err(|e| format!("{e} in {archive_path}."))?;
+        loaded_sets.insert(name.to_string(), set);
+    }
+    Ok((bn, formulae, loaded_sets))
+}
+
+/// Read the result stored under the `key` in the result cache directory `cache_dir` (see
+/// [crate::generate_output::write_cached_result]).
+///
+/// Return `None` if there is no such result, or if it was computed for a different model than
+/// `bn` (which can only happen if the cache was modified by hand). The result is in the canonical
+/// symbolic context of the model.
+pub fn load_cached_result(
+    cache_dir: &str,
+    key: &str,
+    bn: &BooleanNetwork,
+) -> Result<Option<GraphColoredVertices>, String> {
+    let path = cached_result_path(cache_dir, key);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let path = path.to_str().ok_or("Invalid cache path.".to_string())?;
+    let (cached_bn, _, mut results) = load_result_archive(path)?;
+    if cached_bn.to_string() != bn.to_string() {
+        return Ok(None);
+    }
+    Ok(results.remove("formula-0"))
+}
+
+/// Read the intermediate results of the cached sub-formulae from a result bundle produced by
+/// [crate::generate_output::build_result_archive_with_intermediates].
+///
+/// Return the pairs of canonical sub-formulae and their results, transferred into the canonical
+/// symbolic context of the archived model (same as with [load_result_archive]). The list is
+/// empty if the archive contains no intermediate results.
+pub fn load_intermediate_results(
+    archive_path: &str,
+) -> Result<Vec<(String, GraphColoredVertices)>, String> {
+    let archive_file = File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| e.to_string())?;
+    if archive.by_name("intermediates.txt").is_err() {
+        return Ok(Vec::new());
+    }
+
+    let model_string = read_zipped_file(&mut archive, "model.aeon")?;
+    let bn = BooleanNetwork::try_from(model_string.as_str())?;
+    let canonical_graph = SymbolicAsyncGraph::new(&bn)?;
+    let formulae = read_zipped_file(&mut archive, "intermediates.txt")?
+        .lines()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+
+    let mut intermediates = Vec::new();
+    for (i, formula) in formulae.into_iter().enumerate() {
+        let filename = format!("{INTERMEDIATES_DIR}{i}.bdd");
+        let set = read_archived_set(&mut archive, &filename, &bn, &canonical_graph)
+            .map_err(|e| format!("{e} in {archive_path}."))?;
+        intermediates.push((formula, set));
+    }
+    Ok(intermediates)
+}
+
+/// Load the metadata of the analysis that produced a result archive (see [AnalysisMetadata]).
+/// Return `None` if the archive has no metadata (e.g., if it was not produced by the analysis).
+pub fn load_analysis_metadata(archive_path: &str) -> Result<Option<AnalysisMetadata>, String> {
+    let archive_file = File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| e.to_string())?;
+    if archive.by_name(ANALYSIS_METADATA_FILE).is_err() {
+        return Ok(None);
+    }
+    let text = read_zipped_file(&mut archive, ANALYSIS_METADATA_FILE)?;
+    AnalysisMetadata::from_text(text.as_str())
+        .map(Some)
+        .map_err(|e| format!("{e} in {archive_path}."))
+}
+
+/// Read a result set stored in the file `filename` of a result archive (for the model `bn`),
+/// and transfer it into the context of the `canonical_graph` of the model.
+fn read_archived_set(
+    archive: &mut ZipArchive<File>,
+    filename: &str,
+    bn: &BooleanNetwork,
+    canonical_graph: &SymbolicAsyncGraph,
+) -> Result<GraphColoredVertices, String> {
+    let canonical_context = canonical_graph.symbolic_context();
+    let bdd_string = read_zipped_file(archive, filename)?;
+    let bdd = Bdd::from_string(bdd_string.as_str());
+
+    // results with additional symbolic variables must be transferred to the canonical context
+    let bdd = transfer_bdd_into_context(bn, &bdd, canonical_context).ok_or(format!(
+        "BDD in file `{filename}` is not compatible with the model"
+    ))?;
+    // restrict the set to valid colors only (results of some sub-formulae may not be restricted)
+    Ok(GraphColoredVertices::new(bdd, canonical_context)
+        .intersect(canonical_graph.unit_colored_vertices()))
+}
+
+/// Transfer a BDD, that was computed in a symbolic context of the given BN model with any number of
+/// additional symbolic variables for each network variable (or in the canonical context), into
+/// the `target_context` (also corresponding to the same BN, with any number of additional variables).
+///
+/// The number of additional variables of the BDD's context is inferred from its number of variables.
+/// Return `None` if the BDD is not compatible with the model, or if it depends on the additional
+/// variables that are not present in the target context.
+pub fn transfer_bdd_into_context(
+    bn: &BooleanNetwork,
+    bdd: &Bdd,
+    target_context: &SymbolicContext,
+) -> Option<Bdd> {
+    diagnose_bdd_transfer(bn, bdd, target_context).ok()
+}
+
+/// Same as [transfer_bdd_into_context], but if the BDD is not compatible, return a description
+/// of the problem (including the unexpected BDD variables, if any) and a suggested fix.
+fn diagnose_bdd_transfer(
+    bn: &BooleanNetwork,
+    bdd: &Bdd,
+    target_context: &SymbolicContext,
+) -> Result<Bdd, String> {
+    if bdd.num_vars() == target_context.bdd_variable_set().num_vars() {
+        return Ok(bdd.clone());
+    }
+
+    let num_canonical_vars = target_context
+        .as_canonical_context()
+        .bdd_variable_set()
+        .num_vars();
+    let num_network_vars = bn.num_vars() as u16;
+    let num_extra_vars = bdd.num_vars().checked_sub(num_canonical_vars);
+    let num_extra_vars = match num_extra_vars {
+        Some(num_extra_vars) if num_network_vars > 0 && num_extra_vars % num_network_vars == 0 => {
+            num_extra_vars
+        }
+        _ => {
+            return Err(format!(
+                "the BDD has {} variables, which does not match the model with {num_network_vars} \
+                variables and {num_canonical_vars} symbolic variables (plus any number of HCTL \
+                variables); make sure the set was computed for this model",
+                bdd.num_vars(),
+            ))
+        }
+    };
+    let num_hctl_vars = num_extra_vars / num_network_vars;
+    let extra_vars = bn.variables().map(|v| (v, num_hctl_vars)).collect();
+    let source_context = SymbolicContext::with_extra_state_variables(bn, &extra_vars)?;
+    if let Some(bdd) = target_context.transfer_from(bdd, &source_context) {
+        return Ok(bdd);
+    }
+
+    // the set depends on additional variables that are not present in the target context
+    let mut unexpected_vars = bdd
+        .support_set()
+        .into_iter()
+        .filter(|var| {
+            let name = source_context.bdd_variable_set().name_of(*var);
+            target_context
+                .bdd_variable_set()
+                .var_by_name(&name)
+                .is_none()
+        })
+        .collect::<Vec<_>>();
+    unexpected_vars.sort();
+    let unexpected_names = unexpected_vars
+        .into_iter()
+        .map(|var| source_context.bdd_variable_set().name_of(var))
+        .collect::<Vec<_>>();
+    Err(format!(
+        "the BDD depends on variables unknown to the symbolic context of the analysis ({}); \
+        project these variables out of the set, or use formulae with at least {num_hctl_vars} \
+        HCTL variables",
+        unexpected_names.join(", ")
+    ))
+}
+
+/// Validate the sets of a context for wild-cards (see [load_context_sets]) before they are used
+/// in the analysis of the model `bn`, and transfer them into the `target_context` (see
+/// [transfer_bdd_into_context]). The `used_labels` are the wild-card propositions and domains
+/// referenced by the formulae, which must all be present in the context.
+///
+/// Instead of stopping at the first problem, all problems are reported together, each with the
+/// offending label, the unexpected BDD variables (if any), and a suggested fix.
+pub fn validate_context_sets(
+    context_sets: &LabelToSetMap,
+    used_labels: &[String],
+    bn: &BooleanNetwork,
+    target_context: &SymbolicContext,
+) -> Result<LabelToSetMap, String> {
+    // the labels are sorted, so that the diagnostics do not depend on the order of the map
+    let mut labels = context_sets.keys().collect::<Vec<_>>();
+    labels.sort();
+
+    let mut diagnostics = Vec::new();
+    let mut validated_sets = LabelToSetMap::new();
+    for label in labels.iter() {
+        match diagnose_bdd_transfer(bn, context_sets[*label].as_bdd(), target_context) {
+            Ok(bdd) => {
+                let set = GraphColoredVertices::new(bdd, target_context);
+                validated_sets.insert(label.to_string(), set);
+            }
+            Err(problem) => diagnostics.push(format!("set `{label}`: {problem}")),
+        }
+    }
+    for label in used_labels {
+        if context_sets.contains_key(label) {
+            continue;
+        }
+        let fix = match labels.iter().find(|it| it.eq_ignore_ascii_case(label)) {
+            Some(similar) => format!("did you mean `{similar}`?"),
+            None => format!("add the set `{label}` to the context"),
+        };
+        diagnostics.push(format!(
+            "set `{label}` is used by the formulae, but missing in the context; {fix} (available \
+            sets: {})",
+            labels
+                .iter()
+                .map(|it| format!("`{it}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if diagnostics.is_empty() {
+        Ok(validated_sets)
+    } else {
+        Err(format!(
+            "Invalid context for wild-cards:\n - {}",
+            diagnostics.join("\n - ")
+        ))
+    }
+}
+
+/// Configuration of the model-checking analysis, loaded from a TOML file (see [load_analysis_config]).
+///
+/// All fields are optional, so that the configuration can be completed (or overridden) by other
+/// means, such as CLI arguments.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AnalysisConfig {
+    /// Path to the BN model file.
+    pub model: Option<String>,
+    /// Paths to files with formulae.
+    #[serde(default)]
+    pub formulae_files: Vec<String>,
+    /// Formulae given directly.
+    #[serde(default)]
+    pub formulae: Vec<String>,
+    /// Path to the zip bundle with context of wild-cards (for extended formulae).
+    pub extended_context: Option<String>,
+    /// Path to the zip bundle with resulting BDDs.
+    pub output_bundle: Option<String>,
+    /// Amount of printed output (`no-print`, `summary`, `with-progress`, or `exhaustive`).
+    pub print_option: Option<String>,
+    /// Parallelism and resource limits.
+    #[serde(default)]
+    pub limits: LimitsConfig,
+}
+
+/// Parallelism and resource limits part of the [AnalysisConfig].
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Number of threads.
+    pub threads: Option<usize>,
+    /// Time limit (in seconds).
+    pub timeout: Option<u64>,
+    /// Memory limit (in MB).
+    pub memory_limit: Option<u64>,
+}
+
+/// Description of a staged analysis (pipeline), loaded from a TOML file (see [load_pipeline_config]).
+///
+/// Each stage is a map of `name -> formula`. The results of each stage are saved in a result
+/// bundle (under the given names), which is then used as the extended context for the next stage.
+/// Thus, the formulae of a stage may reference the results of the previous stage as wild-card
+/// propositions (`%name%`) or variable domains.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    /// Path to the BN model file.
+    pub model: Option<String>,
+    /// Directory where the result bundles of the stages are written (`stage-{i}.zip`).
+    pub output_dir: Option<String>,
+    /// Amount of printed output (`no-print`, `summary`, `with-progress`, or `exhaustive`).
+    pub print_option: Option<String>,
+    /// Parallelism and resource limits (applied to each stage separately).
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    /// Stages of the pipeline, each stage maps names of the results to the formulae.
+    #[serde(default)]
+    pub stages: Vec<BTreeMap<String, String>>,
+}
+
+/// Read the pipeline description from a TOML file.
+///
+/// Relative paths in the description (model, output directory) are resolved relative to the
+/// directory containing the file.
+pub fn load_pipeline_config(config_path: &str) -> Result<PipelineConfig, String> {
+    let config_string = read_to_string(config_path).map_err(|e| e.to_string())?;
+    let mut config: PipelineConfig =
+        toml::from_str(config_string.as_str()).map_err(|e| e.to_string())?;
+
+    let base_dir = Path::new(config_path).parent().unwrap_or(Path::new(""));
+    let resolve = |path: &String| base_dir.join(path).to_string_lossy().to_string();
+    config.model = config.model.as_ref().map(resolve);
+    config.output_dir = config.output_dir.as_ref().map(resolve);
+    Ok(config)
+}
+
+/// Read the analysis configuration from a TOML file.
+///
+/// Relative paths in the configuration (model, formulae files, bundles) are resolved relative to
+/// the directory containing the configuration file.
+pub fn load_analysis_config(config_path: &str) -> Result<AnalysisConfig, String> {
+    let config_string = read_to_string(config_path).map_err(|e| e.to_string())?;
+    let mut config: AnalysisConfig =
+        toml::from_str(config_string.as_str()).map_err(|e| e.to_string())?;
+
+    let base_dir = Path::new(config_path).parent().unwrap_or(Path::new(""));
+    let resolve = |path: &String| base_dir.join(path).to_string_lossy().to_string();
+    config.model = config.model.as_ref().map(resolve);
+    config.formulae_files = config.formulae_files.iter().map(resolve).collect();
+    config.extended_context = config.extended_context.as_ref().map(resolve);
+    config.output_bundle = config.output_bundle.as_ref().map(resolve);
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_model_checking::_test_util::unique_temp_path;
+    use crate::evaluation::LabelToSetMap;
+    use crate::load_inputs::{
+        load_analysis_config, network_from_str, parse_formulae_lines, validate_context_sets,
+        LimitsConfig, ModelFormat,
+    };
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices;
+    use std::path::Path;
+
+    #[test]
+    /// Test loading models from strings, both with given and detected formats.
+    fn test_network_from_str() {
+        let aeon = "a -> b\nb -| a\n$a: !b\n$b: a";
+        let bnet = "targets, factors\na, !b\nb, a";
+        let booleannet = "a* = not b\nb* = a";
+        let table = "b,a\n0,1\n\na,b\n1,1";
+        let sbml = network_from_str(aeon, None).unwrap().to_sbml(None);
+        let models = [
+            (aeon, ModelFormat::Aeon),
+            (bnet, ModelFormat::Bnet),
+            (booleannet, ModelFormat::BooleanNet),
+            (sbml.as_str(), ModelFormat::Sbml),
+            (table, ModelFormat::TruthTable),
+        ];
+        for (model, format) in models {
+            assert_eq!(ModelFormat::detect(model), format);
+            let bn = network_from_str(model, Some(format)).unwrap();
+            assert_eq!(bn.num_vars(), 2);
+            assert_eq!(bn.as_graph().regulations().count(), 2);
+        }
+        assert_eq!(ModelFormat::detect("{\"Model\": {}}"), ModelFormat::Bma);
+        assert_eq!(ModelFormat::detect("<gxl></gxl>"), ModelFormat::Ginml);
+        assert_eq!(
+            ModelFormat::from_extension("csv"),
+            Some(ModelFormat::TruthTable)
+        );
+        assert!(network_from_str(bnet, Some(ModelFormat::Aeon)).is_err());
+    }
+
+    #[test]
+    /// Test collecting formulae from lines (skipping comments and empty lines).
+    fn test_parse_formulae_lines() {
+        let formulae = parse_formulae_lines("# comment\n\n  !{x}: AX {x}  \nEF a\n");
+        assert_eq!(
+            formulae,
+            vec!["!{x}: AX {x}".to_string(), "EF a".to_string()]
+        );
+
+        // lines starting with a state literal are formulae, not comments
+        let formulae = parse_formulae_lines(
+            "#state{a=1, *} & EF a\n# state of the model\n#statement\n  #state {a=0, b=1}\n",
+        );
+        assert_eq!(
+            formulae,
+            vec![
+                "#state{a=1, *} & EF a".to_string(),
+                "#state {a=0, b=1}".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    /// Test validating the context sets, and the diagnostics for the invalid ones.
+    fn test_validate_context_sets() {
+        let bn = network_from_str("a -> b\nb -| a\n$a: !b\n$b: a", None).unwrap();
+        let graph_1 = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let graph_2 = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let ctx_1 = graph_1.symbolic_context();
+        let ctx_2 = graph_2.symbolic_context();
+
+        // sets only depending on the network variables are transferred between contexts
+        let set_a = ctx_2.mk_state_variable_is_true(bn.as_graph().find_variable("a").unwrap());
+        let mut context_sets = LabelToSetMap::new();
+        context_sets.insert("A".to_string(), GraphColoredVertices::new(set_a, ctx_2));
+        let labels = vec!["A".to_string()];
+        let validated = validate_context_sets(&context_sets, &labels, &bn, ctx_1).unwrap();
+        assert_eq!(
+            validated["A"].as_bdd().num_vars(),
+            ctx_1.bdd_variable_set().num_vars()
+        );
+
+        // sets depending on the variables missing in the target context are reported
+        let extra_var = ctx_2.bdd_variable_set().var_by_name("a_extra_1").unwrap();
+        let set_b = ctx_2.bdd_variable_set().mk_var(extra_var);
+        context_sets.insert("B".to_string(), GraphColoredVertices::new(set_b, ctx_2));
+        let error = validate_context_sets(&context_sets, &labels, &bn, ctx_1).unwrap_err();
+        assert!(error.contains("set `B`"));
+        assert!(error.contains("(a_extra_1)"));
+        assert!(!error.contains("set `A`"));
+
+        // missing labels are reported with a suggestion of similarly named sets
+        let labels = vec!["a".to_string(), "C".to_string()];
+        let error = validate_context_sets(&context_sets, &labels, &bn, ctx_2).unwrap_err();
+        assert!(error.contains("set `a` is used by the formulae"));
+        assert!(error.contains("did you mean `A`?"));
+        assert!(error.contains("add the set `C` to the context"));
+
+        // sets computed for a different model are reported
+        let other_bn = network_from_str("a -> b\nb -| c\nc -> a", None).unwrap();
+        let other_graph = get_extended_symbolic_graph(&other_bn, 0).unwrap();
+        let mut context_sets = LabelToSetMap::new();
+        context_sets.insert("A".to_string(), other_graph.mk_unit_colored_vertices());
+        let labels = vec!["A".to_string()];
+        let error = validate_context_sets(&context_sets, &labels, &bn, ctx_1).unwrap_err();
+        assert!(error.contains("make sure the set was computed for this model"));
+    }
+
+    #[test]
+    /// Test loading the analysis configuration from a TOML file.
+    fn test_load_analysis_config() {
+        let dir = unique_temp_path("config");
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.toml");
+        let config_content = r#"
+            model = "model.aeon"
+            formulae_files = ["formulae.txt"]
+            formulae = ["!{x}: AG EF {x}"]
+            print_option = "no-print"
+
+            [limits]
+            threads = 2
+            timeout = 60
+        "#;
+        std::fs::write(&config_path, config_content).unwrap();
+
+        let config = load_analysis_config(config_path.to_str().unwrap()).unwrap();
+        assert_eq!(Path::new(&config.model.unwrap()), dir.join("model.aeon"));
+        assert_eq!(
+            Path::new(&config.formulae_files[0]),
+            dir.join("formulae.txt")
+        );
+        assert_eq!(config.formulae, vec!["!{x}: AG EF {x}".to_string()]);
+        assert_eq!(config.print_option, Some("no-print".to_string()));
+        assert!(config.output_bundle.is_none());
+        let expected_limits = LimitsConfig {
+            threads: Some(2),
+            timeout: Some(60),
+            memory_limit: None,
+        };
+        assert_eq!(config.limits, expected_limits);
+
+        // unknown fields are not allowed
+        std::fs::write(&config_path, "modle = \"model.aeon\"").unwrap();
+        assert!(load_analysis_config(config_path.to_str().unwrap()).is_err());
+    }
+}
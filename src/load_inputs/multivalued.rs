@@ -0,0 +1,156 @@
+//! Boolean (threshold) encoding of multi-valued variables, shared by the loaders of
+//! multi-valued model formats.
+//!
+//! A variable `X` with values `min..=max` (where `max - min > 1`) is represented by Boolean
+//! variables `X_bk` for `k` in `min+1..=max`, where `X_bk` is true iff `X >= k`. Variables with
+//! two values are represented by a single Boolean variable `X` (true iff `X = max`).
+//!
+//! The Boolean variables are updated step-wise (see [MultiValuedEncoding::step_updates]), so
+//! that the value only moves by one level towards its target. The valuations of the Boolean
+//! variables which do not encode any value (e.g., `X_b2 & !X_b1`) are still part of the state
+//! space, but they are never reached from the valid ones (they only lead to valid ones).
+
+use biodivine_lib_param_bn::BooleanNetwork;
+use std::collections::{BTreeSet, HashMap};
+
+/// Boolean encoding of multi-valued variables given by their ranges `(min, max)`.
+pub(crate) struct MultiValuedEncoding {
+    ranges: HashMap<String, (u32, u32)>,
+}
+
+impl MultiValuedEncoding {
+    /// Create the encoding for variables with given ranges.
+    pub(crate) fn new(ranges: HashMap<String, (u32, u32)>) -> Result<MultiValuedEncoding, String> {
+        for (name, (min, max)) in &ranges {
+            if min >= max {
+                return Err(format!("Variable `{name}` must have at least two values."));
+            }
+        }
+        Ok(MultiValuedEncoding { ranges })
+    }
+
+    /// Get the range `(min, max)` of the variable.
+    pub(crate) fn range(&self, name: &str) -> Result<(u32, u32), String> {
+        self.ranges
+            .get(name)
+            .copied()
+            .ok_or(format!("Unknown variable `{name}`."))
+    }
+
+    /// Pairs `(k, name)` of Boolean variables encoding the multi-valued variable (`X >= k`).
+    pub(crate) fn levels(&self, name: &str) -> Result<Vec<(u32, String)>, String> {
+        let (min, max) = self.range(name)?;
+        if max - min == 1 {
+            Ok(vec![(max, name.to_string())])
+        } else {
+            Ok((min + 1..=max)
+                .map(|k| (k, format!("{name}_b{k}")))
+                .collect())
+        }
+    }
+
+    /// Boolean expression for `name >= threshold`, collecting the used Boolean variables.
+    pub(crate) fn at_least(
+        &self,
+        name: &str,
+        threshold: u32,
+        used_vars: &mut BTreeSet<String>,
+    ) -> Result<String, String> {
+        let (min, max) = self.range(name)?;
+        if threshold <= min {
+            return Ok("true".to_string());
+        }
+        if threshold > max {
+            return Ok("false".to_string());
+        }
+        let (_, var) = self
+            .levels(name)?
+            .swap_remove((threshold - min - 1) as usize);
+        used_vars.insert(var.clone());
+        Ok(var)
+    }
+
+    /// Boolean expression for `name = level`, collecting the used Boolean variables.
+    pub(crate) fn exactly(
+        &self,
+        name: &str,
+        level: u32,
+        used_vars: &mut BTreeSet<String>,
+    ) -> Result<String, String> {
+        let at_least = self.at_least(name, level, used_vars)?;
+        let above = self.at_least(name, level + 1, used_vars)?;
+        match (at_least.as_str(), above.as_str()) {
+            (_, "false") => Ok(at_least),
+            ("true", _) => Ok(format!("!{above}")),
+            _ => Ok(format!("({at_least} & !{above})")),
+        }
+    }
+
+    /// Step-wise update functions of the Boolean variables encoding the variable `name`, given
+    /// the conditions under which its target value is at least `k`, one for each of its levels
+    /// (in the order of [MultiValuedEncoding::levels]), and the variables `used_vars` used in
+    /// these conditions.
+    ///
+    /// Each `X_bk` is updated to `X_b(k+1) | (X_b(k-1) & target >= k)`, so the value only
+    /// increases (or decreases) by one level at a time, and a valid encoding never leads to an
+    /// invalid one. Return the triplets used by [build_network].
+    pub(crate) fn step_updates(
+        &self,
+        name: &str,
+        at_least_target: Vec<String>,
+        used_vars: &BTreeSet<String>,
+    ) -> Result<Vec<(String, String, BTreeSet<String>)>, String> {
+        let levels = self.levels(name)?;
+        if levels.len() != at_least_target.len() {
+            return Err(format!("Invalid number of target levels of `{name}`."));
+        }
+        let mut updates = Vec::new();
+        for (i, ((_, var), target)) in levels.iter().zip(at_least_target).enumerate() {
+            let mut used_vars = used_vars.clone();
+            // the level can only be reached from the one below it
+            let rise = match (i.checked_sub(1).map(|j| &levels[j].1), target.as_str()) {
+                (_, "false") => target,
+                (None, _) => target,
+                (Some(below), "true") => {
+                    used_vars.insert(below.clone());
+                    below.clone()
+                }
+                (Some(below), _) => {
+                    used_vars.insert(below.clone());
+                    format!("{below} & ({target})")
+                }
+            };
+            // the level can only be left from the top one
+            let update = match (levels.get(i + 1).map(|(_, above)| above), rise.as_str()) {
+                (None, _) => rise,
+                (Some(above), "false") => {
+                    used_vars.insert(above.clone());
+                    above.clone()
+                }
+                (Some(above), _) => {
+                    used_vars.insert(above.clone());
+                    format!("{above} | ({rise})")
+                }
+            };
+            updates.push((var.clone(), update, used_vars));
+        }
+        Ok(updates)
+    }
+}
+
+/// Build the Boolean network from the update functions of the Boolean variables, given as
+/// triplets `(variable, update expression, variables used in the expression)`.
+pub(crate) fn build_network(
+    updates: Vec<(String, String, BTreeSet<String>)>,
+) -> Result<BooleanNetwork, String> {
+    let mut aeon_lines = Vec::new();
+    for (var, update, used_vars) in updates {
+        for regulator in &used_vars {
+            aeon_lines.push(format!("{regulator} -?? {var}"));
+        }
+        aeon_lines.push(format!("${var}: {update}"));
+    }
+    // regulations are over-approximated, so we infer the actual ones from the update functions
+    let bn = BooleanNetwork::try_from(aeon_lines.join("\n").as_str())?;
+    bn.infer_valid_graph()
+}
@@ -0,0 +1,154 @@
+//! Conversion of experimental observations into sets of states, which can be used as the context
+//! of wild-card propositions in extended formulae.
+//!
+//! Observations are given by a CSV table, where the header lists the observed variables and each
+//! row gives their values in one observation (`0`, `1`, or `-`, `*`, or an empty cell for an
+//! unknown value). Each observation is thus a partial state, and it is converted into the set of
+//! all states (and colors) matching it. Lines starting with `#` are ignored.
+//!
+//! The observations are labelled `obs_1`, `obs_2`, ... (in the order of the rows), so that they
+//! can be referenced in formulae as `%obs_1%`, and so on. Alternatively, the first column can be
+//! named `name`, and give the labels explicitly. For example, the attractors compatible with an
+//! observation can be described by the formula `!{x}: AG EF ({x} & %obs_1%)`.
+
+use crate::evaluation::LabelToSetMap;
+
+use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+use biodivine_lib_param_bn::{BooleanNetwork, VariableId};
+use std::fs::read_to_string;
+
+/// Name of the (optional) first column with the labels of the observations.
+const NAME_COLUMN: &str = "name";
+
+/// Single observation, i.e., a partial state of the network.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Observation {
+    /// Label of the observation.
+    pub name: String,
+    /// Observed values of the variables (the unobserved variables are not listed).
+    pub values: Vec<(String, bool)>,
+}
+
+/// Parse the value of the cell in the table.
+fn parse_value(value: &str) -> Result<Option<bool>, String> {
+    match value {
+        "0" => Ok(Some(false)),
+        "1" => Ok(Some(true)),
+        "" | "-" | "*" => Ok(None),
+        _ => Err(format!("Invalid observed value `{value}`.")),
+    }
+}
+
+/// Parse the observations from the CSV table (see the module documentation).
+pub fn parse_observations(table: &str) -> Result<Vec<Observation>, String> {
+    let mut lines = table
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or("Observation table is empty.".to_string())?
+        .split(',')
+        .map(|cell| cell.trim())
+        .collect();
+    let has_names = header[0].eq_ignore_ascii_case(NAME_COLUMN);
+    let variables = if has_names { &header[1..] } else { &header };
+
+    let mut observations: Vec<Observation> = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let cells: Vec<&str> = line.split(',').map(|cell| cell.trim()).collect();
+        if cells.len() != header.len() {
+            return Err(format!(
+                "Observation `{line}` has {} values, but {} are expected.",
+                cells.len(),
+                header.len()
+            ));
+        }
+        let (name, values) = if has_names {
+            (cells[0].to_string(), &cells[1..])
+        } else {
+            (format!("obs_{}", i + 1), &cells[..])
+        };
+        if observations.iter().any(|obs| obs.name == name) {
+            return Err(format!("Observation `{name}` is defined multiple times."));
+        }
+        let mut observed = Vec::new();
+        for (variable, value) in variables.iter().zip(values) {
+            if let Some(value) = parse_value(value)? {
+                observed.push((variable.to_string(), value));
+            }
+        }
+        observations.push(Observation {
+            name,
+            values: observed,
+        });
+    }
+    Ok(observations)
+}
+
+/// Convert the observations into the sets of states (with all colors) of the given BN matching
+/// them. The sets are labelled by the names of the observations.
+pub fn observations_to_sets(
+    bn: &BooleanNetwork,
+    observations: &[Observation],
+) -> Result<LabelToSetMap, String> {
+    let graph = SymbolicAsyncGraph::new(bn)?;
+    let mut sets = LabelToSetMap::new();
+    for observation in observations {
+        let mut values: Vec<(VariableId, bool)> = Vec::new();
+        for (name, value) in &observation.values {
+            let var = bn
+                .as_graph()
+                .find_variable(name)
+                .ok_or(format!("Observed variable `{name}` is not in the model."))?;
+            values.push((var, *value));
+        }
+        sets.insert(observation.name.clone(), graph.mk_subspace(&values));
+    }
+    Ok(sets)
+}
+
+/// Read the observations from the CSV file, and convert them into the sets of states of the
+/// given BN (see [observations_to_sets]).
+pub fn load_observations(
+    observations_path: &str,
+    bn: &BooleanNetwork,
+) -> Result<LabelToSetMap, String> {
+    let table = read_to_string(observations_path).map_err(|e| e.to_string())?;
+    observations_to_sets(bn, &parse_observations(&table)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::load_inputs::observations::{observations_to_sets, parse_observations};
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test parsing the observations and converting them into sets of states.
+    fn test_observations() {
+        let table = "# comment\na,b,c\n1,0,\n-,1,*\n";
+        let observations = parse_observations(table).unwrap();
+        assert_eq!(observations.len(), 2);
+        assert_eq!(observations[0].name, "obs_1");
+        assert_eq!(
+            observations[0].values,
+            vec![("a".to_string(), true), ("b".to_string(), false)]
+        );
+        assert_eq!(observations[1].name, "obs_2");
+        assert_eq!(observations[1].values, vec![("b".to_string(), true)]);
+
+        let bn = BooleanNetwork::try_from("a -> b\nb -> c\nc -? a").unwrap();
+        let sets = observations_to_sets(&bn, &observations).unwrap();
+        assert_eq!(sets["obs_1"].vertices().approx_cardinality(), 2.0);
+        assert_eq!(sets["obs_2"].vertices().approx_cardinality(), 4.0);
+
+        // observations with explicit names
+        let observations = parse_observations("Name,a,d\nwt,1,0").unwrap();
+        assert_eq!(observations[0].name, "wt");
+        assert!(observations_to_sets(&bn, &observations).is_err());
+
+        assert!(parse_observations("a,b\n1,2").is_err());
+        assert!(parse_observations("a,b\n1").is_err());
+        assert!(parse_observations("name,a\nx,1\nx,0").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! Conversion of BN models given by explicit truth tables into Boolean networks.
+//!
+//! Each variable is given by a CSV table, where the header lists the regulators followed by the
+//! variable itself (the output column), and each row gives the values of the regulators (`0`,
+//! `1`, or `-` for any value) and the output value (`0` or `1`). The update function is true
+//! exactly for the valuations matching some row with output `1` (the rows that are not listed
+//! are thus considered to have output `0`). Lines starting with `#` are ignored.
+//!
+//! Several tables can be given in a single CSV file, separated by empty lines. Variables used as
+//! regulators without their own table are considered as inputs (keeping their value).
+
+use crate::load_inputs::multivalued::build_network;
+
+use biodivine_lib_param_bn::BooleanNetwork;
+use std::collections::BTreeSet;
+
+/// Truth table of a single variable.
+struct TruthTable {
+    variable: String,
+    regulators: Vec<String>,
+    /// Rows with output `1`, with values `Some(bool)` (or `None` for any value) of regulators.
+    true_rows: Vec<Vec<Option<bool>>>,
+}
+
+/// Parse the value of the cell in the table.
+fn parse_value(value: &str, allow_any: bool) -> Result<Option<bool>, String> {
+    match value {
+        "0" => Ok(Some(false)),
+        "1" => Ok(Some(true)),
+        "-" if allow_any => Ok(None),
+        _ => Err(format!("Invalid value `{value}` in truth table.")),
+    }
+}
+
+/// Split the CSV line into (trimmed) cells.
+fn split_line(line: &str) -> Vec<&str> {
+    line.split(',').map(|s| s.trim()).collect()
+}
+
+/// Parse a single truth table, given by its non-empty lines.
+fn parse_table(lines: &[&str]) -> Result<TruthTable, String> {
+    let mut header = split_line(lines[0]);
+    let variable = header.pop().unwrap().to_string();
+    if variable.is_empty() {
+        return Err("Truth table without an output column.".to_string());
+    }
+    let regulators = header.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    let mut true_rows = Vec::new();
+    for line in &lines[1..] {
+        let mut row = split_line(line);
+        if row.len() != regulators.len() + 1 {
+            return Err(format!(
+                "Row `{line}` does not match the header of `{variable}`."
+            ));
+        }
+        let output = row.pop().unwrap();
+        let inputs = row
+            .iter()
+            .map(|value| parse_value(value, true))
+            .collect::<Result<Vec<_>, _>>()?;
+        if parse_value(output, false)? == Some(true) {
+            true_rows.push(inputs);
+        }
+    }
+    Ok(TruthTable {
+        variable,
+        regulators,
+        true_rows,
+    })
+}
+
+/// Parse all truth tables in the CSV content (separated by empty lines).
+fn parse_tables(content: &str) -> Result<Vec<TruthTable>, String> {
+    let mut tables = Vec::new();
+    let mut block = Vec::new();
+    // trailing empty line, so that the last block is processed too
+    for line in content.lines().chain([""]) {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            if !block.is_empty() {
+                tables.push(parse_table(&block)?);
+                block.clear();
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    Ok(tables)
+}
+
+/// Convert the truth tables into a Boolean network. Each item of `tables` is the content of a
+/// CSV file with one or more truth tables (see the module documentation).
+pub fn truth_tables_to_network(tables: &[&str]) -> Result<BooleanNetwork, String> {
+    let mut parsed_tables = Vec::new();
+    for content in tables {
+        parsed_tables.append(&mut parse_tables(content)?);
+    }
+    if parsed_tables.is_empty() {
+        return Err("No truth tables were given.".to_string());
+    }
+
+    let mut defined = BTreeSet::new();
+    let mut regulators = BTreeSet::new();
+    let mut updates = Vec::new();
+    for table in parsed_tables {
+        if !defined.insert(table.variable.clone()) {
+            return Err(format!(
+                "Variable `{}` has multiple truth tables.",
+                table.variable
+            ));
+        }
+        regulators.extend(table.regulators.iter().cloned());
+
+        let conjunctions = table
+            .true_rows
+            .iter()
+            .map(|row| {
+                let literals = table
+                    .regulators
+                    .iter()
+                    .zip(row)
+                    .filter_map(|(regulator, value)| match value {
+                        Some(true) => Some(regulator.clone()),
+                        Some(false) => Some(format!("!{regulator}")),
+                        None => None,
+                    })
+                    .collect::<Vec<_>>();
+                if literals.is_empty() {
+                    "true".to_string()
+                } else {
+                    format!("({})", literals.join(" & "))
+                }
+            })
+            .collect::<Vec<_>>();
+        let update = if conjunctions.is_empty() {
+            "false".to_string()
+        } else {
+            conjunctions.join(" | ")
+        };
+        let used_vars = table.regulators.into_iter().collect();
+        updates.push((table.variable, update, used_vars));
+    }
+
+    // regulators without their own truth tables are inputs
+    for input in regulators.difference(&defined) {
+        updates.push((
+            input.clone(),
+            input.clone(),
+            BTreeSet::from([input.clone()]),
+        ));
+    }
+    build_network(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::load_inputs::truth_table::truth_tables_to_network;
+
+    #[test]
+    /// Test converting truth tables (given both in a single file and in separate files).
+    fn test_truth_table_conversion() {
+        let table_a = "# comment\nB,C,A\n0,0,0\n0,1,1\n1,-,1\n";
+        let table_b = "A,B\n0,1\n1,0";
+        let bn = truth_tables_to_network(&[table_a, table_b]).unwrap();
+        let combined = format!("{table_a}\n{table_b}");
+        let bn_combined = truth_tables_to_network(&[combined.as_str()]).unwrap();
+        assert_eq!(bn, bn_combined);
+
+        // C has no truth table, so it is an input
+        assert_eq!(bn.num_vars(), 3);
+        let a = bn.as_graph().find_variable("A").unwrap();
+        let c = bn.as_graph().find_variable("C").unwrap();
+        assert_eq!(bn.regulators(a).len(), 2);
+        assert_eq!(
+            bn.get_update_function(c).as_ref().unwrap().to_string(&bn),
+            "C"
+        );
+
+        assert!(truth_tables_to_network(&[]).is_err());
+        assert!(truth_tables_to_network(&["A,B\n0,2"]).is_err());
+        assert!(truth_tables_to_network(&["A,B\n0"]).is_err());
+        assert!(truth_tables_to_network(&[table_b, table_b]).is_err());
+    }
+}
@@ -1,18 +1,63 @@
 //! Symbolic HCTL model checker for BN models.
 //!
-//! Takes an input path to a BN model and a path to a set of HCTL formulae, and runs a model-checking analysis.
-//! During (and after) evaluation, prints the selected amount of results (default is just
-//! an aggregated information regarding the number of satisfying states/colors).
+//! The tool offers several subcommands:
+//!  - `check` takes an input path to a BN model and a path to a set of HCTL formulae, and runs a
+//!    model-checking analysis. During (and after) evaluation, prints the selected amount of results
+//!    (default is just an aggregated information regarding the number of satisfying states/colors).
+//!    The inputs and settings can also be given by a TOML configuration file (`--config`).
+//!  - `pipeline` runs a staged analysis described by a TOML file, where the results of each stage
+//!    serve as the extended context for the next stage.
+//!  - `attractors` and `fixed-points` compute attractor states and fixed-point states of a BN model.
+//!  - `witness` finds a fully specified instance of a BN model that satisfies given formulae.
+//!  - `bench` repeatedly evaluates formulae and reports the timing statistics as CSV.
+//!  - `check-trace` checks formulae on an explicit trace, such as a run of a simulation.
+//!  - `repl` starts an interactive session, evaluating formulae one by one.
+//!  - `model-info` prints basic information about a BN model.
+//!  - `diff` compares results from two result bundles.
+//!  - `combine` combines results from result bundles by a set expression.
+//!  - `serve` runs an HTTP/JSON server for the model checking (with the `server` feature).
 //!
 
-use biodivine_hctl_model_checker::analysis::analyse_formulae;
-use biodivine_hctl_model_checker::load_inputs::load_formulae;
+use biodivine_hctl_model_checker::analysis::{
+    analyse_basins, analyse_driver_variables, analyse_formulae_with_options, analyse_pipeline,
+    benchmark_formulae, check_models_in_directory, combine_result_archives,
+    compare_model_revisions, compare_result_archives, export_annotated_sbml, export_result_archive,
+    find_witness_network, model_records_to_csv, model_records_to_json, print_formula_influence,
+    print_model_info, print_reachability_explanation, print_trace_report, print_vacuity_warnings,
+    regulation_knockout_screen, validate_formulae, AnalysisOptions, BenchmarkRecord,
+};
+use biodivine_hctl_model_checker::evaluation::eval_context::AnalysisLimits;
+use biodivine_hctl_model_checker::evaluation::{
+    EvalOptions, OperatorAlgorithms, ReachabilityAlgorithm,
+};
+use biodivine_hctl_model_checker::generate_output::ResultExportFormat;
+use biodivine_hctl_model_checker::inference::check_consistency_with_observations;
+use biodivine_hctl_model_checker::load_inputs::observations::load_observations;
+use biodivine_hctl_model_checker::load_inputs::{
+    load_analysis_config, load_formulae, load_formulae_with_phenotypes, load_network,
+    load_pipeline_config, AnalysisConfig,
+};
+use biodivine_hctl_model_checker::mc_utils::{apply_input_mode, InputMode};
+use biodivine_hctl_model_checker::preprocessing::parser::parse_extended_formula;
+use biodivine_hctl_model_checker::preprocessing::phenotypes::Phenotypes;
+use biodivine_hctl_model_checker::preprocessing::tokenizer::check_strict_syntax;
+use biodivine_hctl_model_checker::profiling::take_profile;
+use biodivine_hctl_model_checker::reduction::{percolate_constants, reduce_for_formulae};
 use biodivine_hctl_model_checker::result_print::PrintOptions;
+#[cfg(feature = "server")]
+use biodivine_hctl_model_checker::server::run_server;
+use biodivine_hctl_model_checker::simulation::{estimate_formula_satisfaction, SimulationConfig};
+use biodivine_hctl_model_checker::traces::load_trace;
 
 use clap::builder::PossibleValuesParser;
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 
+use biodivine_hctl_model_checker::session::ModelCheckingSession;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
 use biodivine_lib_param_bn::BooleanNetwork;
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::{Duration, Instant};
 
 /// Structure to collect CLI arguments
 #[derive(Parser, Debug)]
@@ -22,70 +67,1220 @@ use biodivine_lib_param_bn::BooleanNetwork;
     about = "Symbolic HCTL model checker for Boolean network models."
 )]
 struct Arguments {
-    /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet).
-    model_path: String,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// Available subcommands of the tool.
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Model-check HCTL formulae on a given BN model.
+    Check {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        /// Omitted when `--models-dir` is used (then, the only positional argument is the formulae path),
+        /// or when the model is given by the `--config` file.
+        #[clap(required_unless_present_any = ["models_dir", "config"])]
+        model_path: Option<String>,
+
+        /// Path to a file with formulae to check (use `-` to read them from the standard input).
+        formulae_path: Option<String>,
+
+        /// Formula to check, given directly. Can be repeated, and combined with a formulae file.
+        #[clap(short = 'f', long = "formula")]
+        formulae: Vec<String>,
+
+        /// Path to an additional file with formulae to check. Can be repeated. The formulae of all
+        /// files are checked together: identical formulae are evaluated only once, and the common
+        /// sub-formulae are shared across the files.
+        #[clap(
+            long = "formulae-file",
+            value_name = "PATH",
+            conflicts_with = "models_dir"
+        )]
+        formulae_files: Vec<String>,
+
+        /// Path to the zip with resulting BDDs. If not specified, only selected summary is printed.
+        #[clap(short, long)]
+        output_bundle: Option<String>,
+
+        /// Choice of the amount of output regarding computation and results.
+        /// Default is just an aggregated information regarding the number of satisfying states/colors
+        /// (`summary`).
+        #[clap(short, long, value_parser = PossibleValuesParser::new(["no-print", "summary", "with-progress", "exhaustive"]))]
+        print_option: Option<String>,
+
+        /// Model-check extended formula (that may contain wild-card propositions and variable domains) by providing
+        /// a path to zip bundle of BDDs specifying context of wild-cards (or a CSV file with observations, which
+        /// are available as wild-cards `%obs_1%`, `%obs_2%`, ...).
+        #[clap(short, long)]
+        extended_context: Option<String>,
+
+        /// Only parse and validate the formulae against the model, and estimate the resources
+        /// needed, without running the model checking.
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Reject the textual aliases of the operators (such as `AND` or `EXISTS`) in the
+        /// formulae, and only allow the symbols.
+        #[clap(long)]
+        strict_syntax: bool,
+
+        /// Check the formulae on every model file (aeon, sbml, bnet, ginml, zginml, json, csv) in the given
+        /// directory, and aggregate the results into a single table (models are processed in
+        /// `--threads` threads).
+        #[clap(long, conflicts_with_all = ["output_bundle", "extended_context", "dry_run"])]
+        models_dir: Option<String>,
+
+        /// Path to write the aggregated results of `--models-dir` (JSON if the path ends with
+        /// `.json`, CSV otherwise). If not specified, CSV is printed.
+        #[clap(long, requires = "models_dir")]
+        batch_output: Option<String>,
+
+        /// Path to a TOML configuration file with the inputs and settings of the analysis (model,
+        /// formulae, bundles, print option, limits). The CLI arguments override the config values.
+        #[clap(long, conflicts_with = "models_dir")]
+        config: Option<String>,
+
+        /// Treatment of the input variables (with no regulators): `parametrized` inputs are
+        /// constants given by colors, `free` inputs can change their value at any time.
+        /// By default, the inputs are treated as given by the model.
+        #[clap(long, conflicts_with = "models_dir", value_parser = PossibleValuesParser::new(["parametrized", "free"]))]
+        inputs: Option<String>,
+
+        /// Fix the value of an input variable (or a constant parameter) during the analysis,
+        /// given as `NAME=0` or `NAME=1`. Can be repeated.
+        #[clap(long = "fix", conflicts_with = "models_dir")]
+        fixed_inputs: Vec<String>,
+
+        /// Freeze a network variable during the analysis, so that it never fires a transition
+        /// (but its value is not fixed). Can be repeated.
+        #[clap(long = "freeze", conflicts_with_all = ["models_dir", "percolate", "reduce", "warm_start"])]
+        frozen_variables: Vec<String>,
+
+        /// After the analysis, check whether the formulae hold vacuously (e.g., `AG (p => AF q)`
+        /// holding just because `p` is unreachable), and print a warning if so.
+        #[clap(long, conflicts_with_all = ["models_dir", "extended_context"])]
+        vacuity: bool,
+
+        /// After the analysis, print which network variables and parameters influence the result
+        /// of each formula, and which of them do not influence any formula.
+        #[clap(long, conflicts_with_all = ["models_dir", "extended_context"])]
+        influence: bool,
+
+        /// Path to a result bundle of a previous run on the same model. The results of formulae
+        /// (and sub-formulae) stored in the bundle are reused instead of being recomputed.
+        #[clap(long, conflicts_with = "models_dir")]
+        warm_start: Option<String>,
+
+        /// Directory of the result cache. The results of formulae checked before on the same model
+        /// (with the same options) are loaded from the cache instead of being recomputed, and the
+        /// new results are added to it.
+        #[clap(long, value_name = "DIR", conflicts_with_all = ["models_dir", "warm_start"])]
+        result_cache: Option<String>,
+
+        /// Rewrite the formulae into forms that are (usually) evaluated faster, such as
+        /// `~EF ~phi` to `AG phi`. Both the original and optimized versions are printed.
+        #[clap(long, conflicts_with = "models_dir")]
+        optimize: bool,
+
+        /// Also write the results of the cached (duplicate) sub-formulae without free variables,
+        /// such as the attractor states, into the output bundle, so that later runs (e.g., with
+        /// `--warm-start`) or other tools can reuse them.
+        #[clap(long, conflicts_with = "models_dir")]
+        dump_intermediates: bool,
+
+        /// Make the results and the output reproducible bit-for-bit across runs and machines. The
+        /// formulae are evaluated one by one (regardless of `--threads`), and no measured times
+        /// are printed.
+        #[clap(long, conflicts_with = "models_dir")]
+        deterministic: bool,
+
+        /// Spill the cached results of duplicate sub-formulae with at least the given number of
+        /// BDD nodes to temporary files, and reload them once they are needed. This lowers the
+        /// memory needed by the analysis at the cost of the disk I/O.
+        #[clap(long, value_name = "NODES", conflicts_with = "models_dir")]
+        spill_threshold: Option<usize>,
+
+        /// Number of HCTL variables of the extended graph. By default, the minimal number needed
+        /// by the formulae is used.
+        #[clap(long, value_name = "N", conflicts_with = "models_dir")]
+        num_hctl_vars: Option<usize>,
+
+        /// Report a snapshot (the size of the current approximation) of each running fixed point
+        /// after every given number of its iterations. Snapshots of `EF`, `EU`, and `AU` are
+        /// under-approximations of their results. Printed only with the progress print options.
+        #[clap(long, value_name = "N", conflicts_with = "models_dir")]
+        snapshot_interval: Option<usize>,
+
+        /// File to write the latest snapshot of a running fixed point into (see
+        /// `--snapshot-interval`), so that the partial result survives an interrupted analysis.
+        /// Each formula uses its own file, with its number appended to the file name.
+        #[clap(long, requires = "snapshot_interval", conflicts_with = "models_dir")]
+        checkpoint: Option<String>,
+
+        /// Algorithm computing the `EF` operator: `saturation` (default) or `frontier` (each
+        /// round only explores the predecessors of the states added in the previous one).
+        #[clap(long, conflicts_with = "models_dir", value_parser = PossibleValuesParser::new(["saturation", "frontier"]))]
+        ef_algorithm: Option<String>,
+
+        /// Algorithm computing the `EU` operator: `saturation` (default) or `frontier`.
+        #[clap(long, conflicts_with = "models_dir", value_parser = PossibleValuesParser::new(["saturation", "frontier"]))]
+        eu_algorithm: Option<String>,
+
+        /// Directory to additionally export each result of the output bundle into, as a separate
+        /// file (in the formats given by `--export-format`).
+        #[clap(long, conflicts_with = "models_dir")]
+        export_dir: Option<String>,
+
+        /// Format of the results exported into `--export-dir`: `dot` (Graphviz rendering of the
+        /// BDD) or `raw` (string serialization of the BDD). Can be repeated. By default, both
+        /// formats are used.
+        #[clap(long, requires = "export_dir", value_parser = PossibleValuesParser::new(["dot", "raw"]))]
+        export_format: Vec<String>,
+
+        /// Profile the main phases of the computation, print their summary, and write the profile
+        /// to the given file in the "folded stacks" format (e.g., for `flamegraph.pl`). Requires
+        /// the `profiling` feature.
+        #[clap(long, conflicts_with = "models_dir")]
+        profile: Option<String>,
+
+        /// Analyse the formulae only on the smallest module of the regulatory graph (a strongly
+        /// connected component with all its upstream variables) containing their propositions.
+        /// This is only applied to reachability properties (using `EF`, `EU`, and `AG`), and the
+        /// results then only cover the variables of the module.
+        #[clap(long, conflicts_with_all = ["models_dir", "warm_start"])]
+        modular: bool,
+
+        /// Before the analysis, percolate the constant update functions through the network and
+        /// remove the variables that become constant. The results then only cover the states
+        /// where these variables have their fixed values.
+        #[clap(long, conflicts_with_all = ["models_dir", "fixed_inputs"])]
+        percolate: bool,
+
+        /// Before the analysis, eliminate the mediator variables that are not referenced by the
+        /// formulae, if this preserves their results (currently only for the fixed-point
+        /// properties). The eliminated variables are listed in the report.
+        #[clap(long, conflicts_with = "models_dir")]
+        reduce: bool,
+
+        #[clap(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Run a staged analysis, where the results of each stage are used as the extended context
+    /// (wild-cards and domains) for the next stage.
+    Pipeline {
+        /// Path to a TOML file with the description of the pipeline stages.
+        pipeline_path: String,
+
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        /// Overrides the model given in the pipeline file.
+        #[clap(short, long)]
+        model_path: Option<String>,
+
+        /// Directory to write the result bundles of the stages (`stage-{i}.zip`). Overrides the
+        /// directory given in the pipeline file. If not specified, the bundles are not kept.
+        #[clap(short, long)]
+        output_dir: Option<String>,
+
+        /// Choice of the amount of output regarding computation and results (default `summary`).
+        #[clap(short, long, value_parser = PossibleValuesParser::new(["no-print", "summary", "with-progress", "exhaustive"]))]
+        print_option: Option<String>,
+
+        #[clap(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Compute all attractor states of a given BN model (for all its colors).
+    Attractors {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to the zip with resulting BDDs. If not specified, only selected summary is printed.
+        #[clap(short, long)]
+        output_bundle: Option<String>,
+
+        /// Choice of the amount of output regarding computation and results.
+        #[clap(short, long, default_value = "summary", value_parser = PossibleValuesParser::new(["no-print", "summary", "with-progress", "exhaustive"]))]
+        print_option: String,
+
+        #[clap(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Compute all fixed-point states of a given BN model (for all its colors).
+    FixedPoints {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to the zip with resulting BDDs. If not specified, only selected summary is printed.
+        #[clap(short, long)]
+        output_bundle: Option<String>,
+
+        /// Choice of the amount of output regarding computation and results.
+        #[clap(short, long, default_value = "summary", value_parser = PossibleValuesParser::new(["no-print", "summary", "with-progress", "exhaustive"]))]
+        print_option: String,
+
+        #[clap(flatten)]
+        limits: LimitArgs,
+    },
+
+    /// Find a fully specified instance of a BN model for which each formula holds in some state.
+    Witness {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to a file with formulae to check (use `-` to read them from the standard input).
+        formulae_path: Option<String>,
+
+        /// Formula to check, given directly. Can be repeated, and combined with a formulae file.
+        #[clap(short = 'f', long = "formula")]
+        formulae: Vec<String>,
+
+        /// Path to write the witness network (in aeon format). If not specified, it is printed.
+        #[clap(short, long)]
+        output_path: Option<String>,
+    },
+
+    /// Export the model into SBML-qual, with species annotated by their values in the states
+    /// satisfying each formula (`0`/`1` if fixed, `*` if not fixed, `none` if unsatisfiable).
+    ExportSbml {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to a file with formulae to check (use `-` to read them from the standard input).
+        formulae_path: Option<String>,
+
+        /// Formula to check, given directly. Can be repeated, and combined with a formulae file.
+        #[clap(short = 'f', long = "formula")]
+        formulae: Vec<String>,
+
+        /// Path to write the annotated SBML model. If not specified, it is printed.
+        #[clap(short, long)]
+        output_path: Option<String>,
+    },
+
+    /// Check the formulae on two revisions of a BN model, and report the properties that changed
+    /// their status (and for how many colors, if the colors of the models can be aligned).
+    Regression {
+        /// Path to the old revision of the model (in one of supported formats).
+        old_model_path: String,
+
+        /// Path to the new revision of the model (in one of supported formats).
+        new_model_path: String,
+
+        /// Path to a file with formulae to check (use `-` to read them from the standard input).
+        formulae_path: Option<String>,
+
+        /// Formula to check, given directly. Can be repeated, and combined with a formulae file.
+        #[clap(short = 'f', long = "formula")]
+        formulae: Vec<String>,
+    },
+
+    /// Evaluate each formula repeatedly and report the timing and BDD statistics as CSV.
+    Bench {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to a file with formulae to check (use `-` to read them from the standard input).
+        formulae_path: Option<String>,
+
+        /// Formula to check, given directly. Can be repeated, and combined with a formulae file.
+        #[clap(short = 'f', long = "formula")]
+        formulae: Vec<String>,
+
+        /// Number of evaluation runs for each formula.
+        #[clap(short = 'n', long, default_value_t = 5)]
+        runs: usize,
+
+        /// Path to write the CSV table. If not specified, it is printed.
+        #[clap(short, long)]
+        output_path: Option<String>,
+    },
+
+    /// Estimate the satisfaction of bounded properties by sampling colors and random asynchronous
+    /// trajectories (a statistical fallback for models too large for the symbolic checking).
+    Simulate {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to a file with formulae to check (use `-` to read them from the standard input).
+        formulae_path: Option<String>,
+
+        /// Formula to check, given directly. Can be repeated, and combined with a formulae file.
+        #[clap(short = 'f', long = "formula")]
+        formulae: Vec<String>,
+
+        /// Number of sampled trajectories for each formula.
+        #[clap(short = 'n', long, default_value_t = 1000)]
+        samples: usize,
+
+        /// Number of states of each trajectory.
+        #[clap(short, long, default_value_t = 100)]
+        length: usize,
+
+        /// Seed of the random number generator.
+        #[clap(long, default_value_t = 0)]
+        seed: u64,
+    },
+
+    /// Check formulae on an explicit trace (e.g., a run of a stochastic simulation), and compare
+    /// the trace with the symbolic results of the formulae.
+    CheckTrace {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to the trace, a CSV table (with a column for each variable and a row for each
+        /// state), or a JSON list of states.
+        trace_path: String,
+
+        /// Path to a file with formulae to check (use `-` to read them from the standard input).
+        formulae_path: Option<String>,
 
-    /// Path to a file with formulae to check.
-    formulae_path: String,
+        /// Formula to check, given directly. Can be repeated, and combined with a formulae file.
+        #[clap(short = 'f', long = "formula")]
+        formulae: Vec<String>,
+    },
 
-    /// Path to the zip with resulting BDD dumps. If not specified, only selected summary is printed.
-    #[clap(short, long)]
-    output_bundle: Option<String>,
+    /// Start an interactive session, evaluating formulae typed by the user one by one.
+    Repl {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
 
-    /// Choice of the amount of output regarding computation and results.
-    /// Default is just an aggregated information regarding the number of satisfying states/colors
-    #[clap(short, long, default_value = "summary", value_parser = PossibleValuesParser::new(["no-print", "summary", "with-progress", "exhaustive"]))]
-    print_option: String,
+        #[clap(flatten)]
+        limits: LimitArgs,
+    },
 
-    /// Model-check extended formula (that may contain wild-card propositions and variable domains) by providing
-    /// a path to zip bundle of BDDs specifying context of wild-cards.
-    #[clap(short, long)]
-    extended_context: Option<String>,
+    /// Print basic information about a BN model and warn about constructs that may slow down
+    /// the model checking.
+    ModelInfo {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+    },
+
+    /// Knock out each regulation of a BN model in turn, re-check the property, and rank the
+    /// regulations by how much their knockout shrinks the set of satisfying colors.
+    KnockoutScreen {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// The property to check (the colors for which it holds in some state are counted).
+        formula: String,
+
+        /// Constant value (`0` or `1`) that replaces the regulator in the target's update function.
+        #[clap(long, default_value = "0", value_parser = PossibleValuesParser::new(["0", "1"]))]
+        value: String,
+    },
+
+    /// Fix each variable of a BN model to `0` and to `1` in turn, re-check the property, and rank
+    /// the variables by how much the fixing changes the satisfying colors and states.
+    Drivers {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// The property to check.
+        formula: String,
+
+        /// Print the ranking as JSON instead of a table.
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Explain a satisfied existential reachability property (`EF` or `EU`) by a witness path,
+    /// and the minimal set of updates and the regulations used on it.
+    Explain {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// The property to explain (`EF phi` or `psi EU phi`).
+        formula: String,
+    },
+
+    /// Check the consistency of a BN model with observed phenotypes, using automatically sketched
+    /// properties (each observation is in some attractor, and no attractor is outside them).
+    Consistency {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// Path to a CSV file with the observations (one partial state per row).
+        observations_path: String,
+    },
+
+    /// Compute the weak and strong basins of the states satisfying a formula (such as an
+    /// attractor), and optionally save them into a result bundle usable as wild-card contexts.
+    Basins {
+        /// Path to a file with BN model file in one of supported formats (aeon, sbml, bnet, ginml, zginml, json, csv).
+        model_path: String,
+
+        /// The formula describing the target set (e.g., `!{x}: AG EF {x}` for all attractors).
+        formula: String,
+
+        /// Path to a zip bundle into which the target set and its basins are written.
+        #[clap(short, long)]
+        output_bundle: Option<String>,
+    },
+
+    /// Compare the results in two result bundles (produced by other subcommands).
+    Diff {
+        /// Path to the first zip bundle with results.
+        first_bundle: String,
+
+        /// Path to the second zip bundle with results.
+        second_bundle: String,
+    },
+
+    /// Combine the results from zip bundles by a set expression, such as
+    /// `intersect(formula-0, formula-1) minus formula-2`.
+    Combine {
+        /// Expression over the names of the results, using operations `union`, `intersect`, and
+        /// `minus` (as functions or infix operators). With several bundles, the names are prefixed
+        /// by the (1-based) index of the bundle, e.g., `2.formula-0`.
+        expression: String,
+
+        /// Paths to the zip bundles with results (for the same model).
+        #[clap(required = true)]
+        bundles: Vec<String>,
+
+        /// Path to the zip to write the combined result to.
+        #[clap(short, long)]
+        output_bundle: Option<String>,
+    },
+
+    /// Run an HTTP/JSON server, which allows to upload models, submit formulae, poll the
+    /// progress, and fetch the results. Only available with the `server` feature.
+    #[cfg(feature = "server")]
+    Serve {
+        /// Address to listen on.
+        #[clap(long, default_value = "127.0.0.1:8080")]
+        address: String,
+    },
 }
 
-/// Wrapper function to invoke the model checker, works with CLI arguments.
-fn main() {
-    let args = Arguments::parse();
+/// Arguments regarding parallelism and resource limits of the computation.
+#[derive(Args, Debug)]
+struct LimitArgs {
+    /// Number of threads used to evaluate the formulae (each formula is evaluated by one thread).
+    /// Default is 1.
+    #[clap(long)]
+    threads: Option<usize>,
+
+    /// Time limit for the computation (in seconds). If reached, partial results are returned.
+    #[clap(long)]
+    timeout: Option<u64>,
+
+    /// Memory limit for the computation (in MB, Linux only). If reached, partial results are returned.
+    #[clap(long)]
+    memory_limit: Option<u64>,
+}
 
-    // read the BN model
-    let maybe_bn = BooleanNetwork::try_from_file(args.model_path.as_str());
-    if maybe_bn.is_err() {
-        println!("Model is corrupted or does not have any supported format.");
-        println!("{}", maybe_bn.err().unwrap());
-        return;
+impl LimitArgs {
+    /// Convert the CLI arguments into the limits used by the library.
+    fn to_limits(&self) -> AnalysisLimits {
+        AnalysisLimits {
+            num_threads: self.threads.unwrap_or(1),
+            timeout: self.timeout.map(Duration::from_secs),
+            memory_limit_mb: self.memory_limit,
+        }
     }
-    let bn = maybe_bn.unwrap();
-
-    // read the formulae
-    let maybe_formulae = load_formulae(args.formulae_path.as_str());
-    if maybe_formulae.is_err() {
-        println!("Formulae file is corrupted or does not have the supported format.");
-        println!("{}", maybe_formulae.err().unwrap());
-        return;
+}
+
+/// Read the BN model from a file, with a more descriptive error message.
+fn load_model(model_path: &str) -> Result<BooleanNetwork, String> {
+    load_network(model_path)
+        .map_err(|e| format!("Model is corrupted or does not have any supported format.\n{e}"))
+}
+
+/// Collect the formulae from a file (if given) and the inline formulae, with a more descriptive
+/// error message. At least one formula must be provided.
+fn collect_formulae(
+    formulae_path: Option<String>,
+    inline_formulae: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut formulae = Vec::new();
+    let mut phenotypes = Phenotypes::new();
+    if let Some(path) = formulae_path {
+        (phenotypes, formulae) = load_formulae_with_phenotypes(path.as_str()).map_err(|e| {
+            format!("Formulae file is corrupted or does not have the supported format.\n{e}")
+        })?;
+    }
+    // inline formulae can also use the phenotypes declared in the file
+    for formula in inline_formulae {
+        if phenotypes.is_empty() {
+            formulae.push(formula);
+        } else {
+            formulae.push(phenotypes.expand_formula(&formula)?);
+        }
+    }
+
+    if formulae.is_empty() {
+        return Err("No formulae provided (use a formulae file or `-f` option).".to_string());
+    }
+    Ok(formulae)
+}
+
+/// Convert the print option string into the corresponding enum variant.
+fn parse_print_option(print_option: &str) -> Result<PrintOptions, String> {
+    match print_option {
+        "no-print" => Ok(PrintOptions::NoPrint),
+        "summary" => Ok(PrintOptions::JustSummary),
+        "with-progress" => Ok(PrintOptions::WithProgress),
+        "exhaustive" => Ok(PrintOptions::Exhaustive),
+        // CLI values are validated earlier, but the value may also come from the config file
+        _ => Err(format!("Wrong print option \"{print_option}\".")),
+    }
+}
+
+/// Convert the (optional) algorithm name into the corresponding enum variant, the saturation is
+/// used by default.
+fn parse_algorithm(algorithm: Option<String>) -> Result<ReachabilityAlgorithm, String> {
+    match algorithm {
+        Some(name) => ReachabilityAlgorithm::from_name(name.as_str())
+            .ok_or(format!("Wrong algorithm \"{name}\".")),
+        None => Ok(ReachabilityAlgorithm::default()),
     }
-    let formulae = maybe_formulae.unwrap();
-
-    // compute the results
-    let print_option = match args.print_option.as_str() {
-        "no-print" => PrintOptions::NoPrint,
-        "summary" => PrintOptions::JustSummary,
-        "with-progress" => PrintOptions::WithProgress,
-        "exhaustive" => PrintOptions::Exhaustive,
-        // this cant really happen (would cause error earlier), just here to have exhaustive match
-        _ => panic!(
-            "{}",
-            format!("Wrong print option \"{}\".", args.print_option.as_str())
-        ),
-    };
-
-    let res = analyse_formulae(
-        &bn,
-        formulae,
-        print_option,
-        args.output_bundle,
-        args.extended_context,
+}
+
+/// Reduce the model by the percolation of constants, and replace the percolated variables in
+/// the formulae by their values. Print the summary of the reduction (unless `print_opt` is
+/// [PrintOptions::NoPrint]).
+fn percolate_model(
+    bn: &BooleanNetwork,
+    formulae: Vec<String>,
+    print_opt: PrintOptions,
+) -> Result<(BooleanNetwork, Vec<String>), String> {
+    let percolated = percolate_constants(bn)?;
+    let mut fixed_values = percolated.fixed_values.iter().collect::<Vec<_>>();
+    fixed_values.sort();
+    let fixed_values = fixed_values
+        .into_iter()
+        .map(|(name, value)| format!("{name}={}", u8::from(*value)))
+        .collect::<Vec<_>>();
+    if print_opt != PrintOptions::NoPrint {
+        println!(
+            "Percolated {} constant variables: {}",
+            fixed_values.len(),
+            fixed_values.join(", ")
+        );
+    }
+    let formulae = formulae
+        .iter()
+        .map(|formula| {
+            let tree = parse_extended_formula(formula)?;
+            Ok(percolated.percolate_formula(tree)?.to_string())
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((percolated.network, formulae))
+}
+
+/// Eliminate the mediator variables of the model that are not needed to evaluate the formulae.
+/// Print the eliminated variables as a certificate of the reduction.
+fn reduce_model(bn: BooleanNetwork, formulae: &[String]) -> Result<BooleanNetwork, String> {
+    let reduced = reduce_for_formulae(&bn, formulae)?;
+    if reduced.eliminated.is_empty() {
+        println!("No variables eliminated (the reduction only applies to fixed-point properties).");
+    } else {
+        println!(
+            "Eliminated {} variables (preserving fixed points):",
+            reduced.eliminated.len()
+        );
+        for record in &reduced.eliminated {
+            println!(
+                "  {} := {} (substituted into: {})",
+                record.name,
+                record.function,
+                record.targets.join(", ")
+            );
+        }
+    }
+    Ok(reduced.network)
+}
+
+/// Parse the values of fixed inputs given as `NAME=0` or `NAME=1`.
+fn parse_fixed_inputs(fixed_inputs: &[String]) -> Result<HashMap<String, bool>, String> {
+    let mut values = HashMap::new();
+    for assignment in fixed_inputs {
+        let value = match assignment.split_once('=') {
+            Some((name, "0")) => (name.trim(), false),
+            Some((name, "1")) => (name.trim(), true),
+            _ => {
+                return Err(format!(
+                    "Invalid input value `{assignment}` (use `NAME=0` or `NAME=1`)."
+                ))
+            }
+        };
+        values.insert(value.0.to_string(), value.1);
+    }
+    Ok(values)
+}
+
+/// Collect the formulae given by the configuration file (from the formulae files first, then
+/// the formulae given directly).
+fn collect_config_formulae(config: &AnalysisConfig) -> Result<Vec<String>, String> {
+    let mut formulae = load_formulae_files(&config.formulae_files)?;
+    formulae.extend(config.formulae.clone());
+    Ok(formulae)
+}
+
+/// Load the formulae from all the given files (in the given order), with a more descriptive
+/// error message.
+fn load_formulae_files(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut formulae = Vec::new();
+    for path in paths {
+        formulae.extend(load_formulae(path.as_str()).map_err(|e| {
+            format!(
+                "Formulae file `{path}` is corrupted or does not have the supported format.\n{e}"
+            )
+        })?);
+    }
+    Ok(formulae)
+}
+
+/// Help message for the interactive mode.
+const REPL_HELP: &str = "Enter a formula to evaluate it, or one of the following commands:
+  let <NAME> = <FORMULA>   evaluate the formula and store its result as wild-card `%NAME%`
+  :names                   list the stored results
+  :help                    print this message
+  :quit                    exit the session";
+
+/// Print the numbers of states and colors in the result of the interactive session.
+fn print_repl_result(result: &GraphColoredVertices, start_time: Instant) {
+    println!(
+        "{} results ({} colors, {} states) in {}ms",
+        result.approx_cardinality(),
+        result.colors().approx_cardinality(),
+        result.vertices().approx_cardinality(),
+        start_time.elapsed().as_millis()
     );
+}
+
+/// Run the interactive session, reading the formulae (and commands) from the standard input.
+/// The `limits` apply to the evaluation of each formula.
+fn run_repl(bn: BooleanNetwork, limits: AnalysisLimits) -> Result<(), String> {
+    let mut session = ModelCheckingSession::new(bn)?;
+    session.set_limits(limits);
+    println!(
+        "Loaded model with {} variables.",
+        session.get_network().num_vars()
+    );
+    println!("{REPL_HELP}");
+
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        if stdin.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            return Ok(());
+        }
+
+        let line = line.trim();
+        let start_time = Instant::now();
+        match line {
+            "" => {}
+            ":quit" | ":q" => return Ok(()),
+            ":help" => println!("{REPL_HELP}"),
+            ":names" => {
+                let mut names = session.get_named_results().keys().collect::<Vec<_>>();
+                names.sort();
+                for name in names {
+                    let set = session.get_named_results().get(name).unwrap();
+                    println!("%{name}%: {} results", set.approx_cardinality());
+                }
+            }
+            _ => {
+                let result = if let Some(definition) = line.strip_prefix("let ") {
+                    match definition.split_once('=') {
+                        Some((name, formula)) => session.evaluate_and_store(name.trim(), formula),
+                        None => Err("Expected a definition `let <NAME> = <FORMULA>`.".to_string()),
+                    }
+                } else {
+                    session.evaluate(line)
+                };
+                match result {
+                    Ok(result) => print_repl_result(&result, start_time),
+                    Err(e) => println!("Error: {e}"),
+                }
+            }
+        }
+    }
+}
+
+/// Check that the formulae do not use the textual aliases of the operators (the strict mode).
+fn check_formulae_syntax(formulae: &[String]) -> Result<(), String> {
+    for formula in formulae {
+        check_strict_syntax(formula).map_err(|e| format!("Formula `{formula}` is invalid: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Run the selected subcommand.
+fn run_command(command: Command) -> Result<(), String> {
+    match command {
+        Command::Check {
+            model_path,
+            formulae_path,
+            formulae,
+            formulae_files,
+            output_bundle,
+            print_option,
+            extended_context,
+            dry_run,
+            strict_syntax,
+            models_dir,
+            batch_output,
+            config,
+            inputs,
+            fixed_inputs,
+            frozen_variables,
+            vacuity,
+            influence,
+            warm_start,
+            result_cache,
+            optimize,
+            dump_intermediates,
+            deterministic,
+            spill_threshold,
+            num_hctl_vars,
+            snapshot_interval,
+            checkpoint,
+            ef_algorithm,
+            eu_algorithm,
+            export_dir,
+            export_format,
+            profile,
+            modular,
+            percolate,
+            reduce,
+            mut limits,
+        } => {
+            if let Some(models_dir) = models_dir {
+                // without the model path, the only positional argument is the formulae path
+                if model_path.is_some() && formulae_path.is_some() {
+                    return Err(
+                        "With `--models-dir`, only the formulae path is expected.".to_string()
+                    );
+                }
+                let formulae = collect_formulae(model_path.or(formulae_path), formulae)?;
+                if strict_syntax {
+                    check_formulae_syntax(&formulae)?;
+                }
+                let num_threads = limits.threads.unwrap_or(1);
+                let records =
+                    check_models_in_directory(models_dir.as_str(), formulae, num_threads)?;
+                return match batch_output {
+                    Some(path) if path.ends_with(".json") => {
+                        std::fs::write(path, model_records_to_json(&records))
+                            .map_err(|e| e.to_string())
+                    }
+                    Some(path) => std::fs::write(path, model_records_to_csv(&records))
+                        .map_err(|e| e.to_string()),
+                    None => {
+                        print!("{}", model_records_to_csv(&records));
+                        Ok(())
+                    }
+                };
+            }
+
+            // values given by the CLI arguments override those from the config file
+            let config = match config {
+                Some(config_path) => load_analysis_config(config_path.as_str())
+                    .map_err(|e| format!("Config file is invalid.\n{e}"))?,
+                None => AnalysisConfig::default(),
+            };
+            let Some(model_path) = model_path.or(config.model.clone()) else {
+                return Err("No model provided (use a model path or the config file).".to_string());
+            };
+            let output_bundle = output_bundle.or(config.output_bundle.clone());
+            let extended_context = extended_context.or(config.extended_context.clone());
+            let print_option = print_option
+                .or(config.print_option.clone())
+                .unwrap_or("summary".to_string());
+            let print_opt = parse_print_option(print_option.as_str())?;
+            limits.threads = limits.threads.or(config.limits.threads);
+            limits.timeout = limits.timeout.or(config.limits.timeout);
+            limits.memory_limit = limits.memory_limit.or(config.limits.memory_limit);
+
+            let mut bn = load_model(model_path.as_str())?;
+            match inputs.as_deref() {
+                Some("parametrized") => bn = apply_input_mode(&bn, &InputMode::Parametrized)?,
+                Some("free") => bn = apply_input_mode(&bn, &InputMode::Free)?,
+                _ => {}
+            }
+            let mut formulae = if formulae_path.is_some() || !formulae.is_empty() {
+                collect_formulae(formulae_path, formulae)?
+            } else if formulae_files.is_empty() {
+                collect_formulae(None, collect_config_formulae(&config)?)?
+            } else {
+                Vec::new()
+            };
+            formulae.extend(load_formulae_files(&formulae_files)?);
+            if strict_syntax {
+                check_formulae_syntax(&formulae)?;
+            }
+            let (bn, formulae) = if percolate {
+                percolate_model(&bn, formulae, print_opt)?
+            } else {
+                (bn, formulae)
+            };
+            let bn = if reduce {
+                reduce_model(bn, &formulae)?
+            } else {
+                bn
+            };
+            if dry_run {
+                return validate_formulae(&bn, formulae, extended_context.is_some());
+            }
+            if profile.is_some() && cfg!(not(feature = "profiling")) {
+                return Err("Profiling requires the `profiling` feature.".to_string());
+            }
+            // the results are exported from the output bundle after the analysis
+            let export = match export_dir {
+                Some(_) if output_bundle.is_none() => {
+                    return Err("Exporting the results requires an output bundle.".to_string())
+                }
+                Some(export_dir) => Some((export_dir, output_bundle.clone().unwrap())),
+                None => None,
+            };
+            let options = EvalOptions {
+                optimize,
+                modular,
+                frozen_variables,
+                dump_intermediates,
+                deterministic,
+                spill_threshold,
+                num_hctl_vars,
+                snapshot_interval,
+                checkpoint_path: checkpoint,
+                algorithms: OperatorAlgorithms {
+                    ef: parse_algorithm(ef_algorithm)?,
+                    eu: parse_algorithm(eu_algorithm)?,
+                },
+            };
+            let analysis_options = AnalysisOptions {
+                result_zip: output_bundle,
+                context_archive_path: extended_context,
+                limits: limits.to_limits(),
+                fixed_inputs: parse_fixed_inputs(&fixed_inputs)?,
+                eval_options: options,
+                warm_start_archive: warm_start,
+                result_cache_dir: result_cache,
+                steady_states: None,
+            };
+            analyse_formulae_with_options(&bn, formulae.clone(), print_opt, &analysis_options)?;
+            if vacuity {
+                print_vacuity_warnings(&bn, &formulae, &analysis_options, print_opt)?;
+            }
+            if influence {
+                print_formula_influence(&bn, &formulae)?;
+            }
+            if let Some((export_dir, bundle)) = export {
+                let formats = if export_format.is_empty() {
+                    vec![ResultExportFormat::Dot, ResultExportFormat::RawBdd]
+                } else {
+                    export_format
+                        .iter()
+                        .filter_map(|f| ResultExportFormat::from_name(f))
+                        .collect()
+                };
+                export_result_archive(bundle.as_str(), export_dir.as_str(), &formats)?;
+            }
+            if let Some(profile_path) = profile {
+                let profile = take_profile();
+                println!("Profile of the computation:\n{}", profile.summary());
+                std::fs::write(profile_path, profile.to_folded()).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+        Command::Pipeline {
+            pipeline_path,
+            model_path,
+            output_dir,
+            print_option,
+            mut limits,
+        } => {
+            let config = load_pipeline_config(pipeline_path.as_str())
+                .map_err(|e| format!("Pipeline file is invalid.\n{e}"))?;
+            let Some(model_path) = model_path.or(config.model) else {
+                return Err("No model provided (use `-m` or the pipeline file).".to_string());
+            };
+            if config.stages.is_empty() {
+                return Err("The pipeline file does not contain any stages.".to_string());
+            }
+            let print_option = print_option
+                .or(config.print_option)
+                .unwrap_or("summary".to_string());
+            limits.threads = limits.threads.or(config.limits.threads);
+            limits.timeout = limits.timeout.or(config.limits.timeout);
+            limits.memory_limit = limits.memory_limit.or(config.limits.memory_limit);
+
+            let bn = load_model(model_path.as_str())?;
+            let stages = config
+                .stages
+                .into_iter()
+                .map(|stage| stage.into_iter().collect())
+                .collect();
+            analyse_pipeline(
+                &bn,
+                stages,
+                parse_print_option(print_option.as_str())?,
+                output_dir.or(config.output_dir),
+                limits.to_limits(),
+            )
+        }
+        Command::Attractors {
+            model_path,
+            output_bundle,
+            print_option,
+            limits,
+        } => {
+            // this formula pattern is evaluated by a dedicated attractor algorithm
+            let bn = load_model(model_path.as_str())?;
+            let options = AnalysisOptions {
+                result_zip: output_bundle,
+                limits: limits.to_limits(),
+                ..AnalysisOptions::default()
+            };
+            analyse_formulae_with_options(
+                &bn,
+                vec!["!{x}: AG EF {x}".to_string()],
+                parse_print_option(print_option.as_str())?,
+                &options,
+            )
+        }
+        Command::FixedPoints {
+            model_path,
+            output_bundle,
+            print_option,
+            limits,
+        } => {
+            // this formula pattern is evaluated by a dedicated fixed-point algorithm
+            let bn = load_model(model_path.as_str())?;
+            let options = AnalysisOptions {
+                result_zip: output_bundle,
+                limits: limits.to_limits(),
+                ..AnalysisOptions::default()
+            };
+            analyse_formulae_with_options(
+                &bn,
+                vec!["!{x}: AX {x}".to_string()],
+                parse_print_option(print_option.as_str())?,
+                &options,
+            )
+        }
+        Command::Witness {
+            model_path,
+            formulae_path,
+            formulae,
+            output_path,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            let formulae = collect_formulae(formulae_path, formulae)?;
+            match find_witness_network(&bn, formulae)? {
+                None => println!("There is no instance of the model satisfying all formulae."),
+                Some(witness) => {
+                    if let Some(path) = output_path {
+                        std::fs::write(path, witness.to_string()).map_err(|e| e.to_string())?;
+                    } else {
+                        println!("{witness}");
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::ExportSbml {
+            model_path,
+            formulae_path,
+            formulae,
+            output_path,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            let formulae = collect_formulae(formulae_path, formulae)?;
+            let sbml = export_annotated_sbml(&bn, formulae)?;
+            if let Some(path) = output_path {
+                std::fs::write(path, sbml).map_err(|e| e.to_string())?;
+            } else {
+                println!("{sbml}");
+            }
+            Ok(())
+        }
+        Command::Regression {
+            old_model_path,
+            new_model_path,
+            formulae_path,
+            formulae,
+        } => {
+            let old_bn = load_model(old_model_path.as_str())?;
+            let new_bn = load_model(new_model_path.as_str())?;
+            let formulae = collect_formulae(formulae_path, formulae)?;
+            for regression in compare_model_revisions(&old_bn, &new_bn, formulae)? {
+                let change = if regression.is_changed() {
+                    "CHANGED"
+                } else {
+                    "unchanged"
+                };
+                println!("{}: {change}", regression.formula);
+                println!(
+                    "  holds for: {:?} (old) vs. {:?} (new)",
+                    regression.old_status, regression.new_status
+                );
+                if let (Some(lost), Some(gained)) =
+                    (&regression.lost_colors, &regression.gained_colors)
+                {
+                    println!(
+                        "  lost for {} colors, gained for {} colors",
+                        lost.approx_cardinality(),
+                        gained.approx_cardinality()
+                    );
+                }
+            }
+            Ok(())
+        }
+        Command::Bench {
+            model_path,
+            formulae_path,
+            formulae,
+            runs,
+            output_path,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            let formulae = collect_formulae(formulae_path, formulae)?;
+            let records = benchmark_formulae(&bn, formulae, runs)?;
+            let mut csv = BenchmarkRecord::csv_header();
+            for record in records {
+                csv.push('\n');
+                csv.push_str(record.to_csv_row().as_str());
+            }
+            if let Some(path) = output_path {
+                std::fs::write(path, csv + "\n").map_err(|e| e.to_string())?;
+            } else {
+                println!("{csv}");
+            }
+            Ok(())
+        }
+        Command::Simulate {
+            model_path,
+            formulae_path,
+            formulae,
+            samples,
+            length,
+            seed,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            let graph = SymbolicAsyncGraph::new(&bn)?;
+            let config = SimulationConfig {
+                num_samples: samples,
+                trajectory_length: length,
+                seed,
+            };
+            for formula in collect_formulae(formulae_path, formulae)? {
+                let estimate = estimate_formula_satisfaction(formula.as_str(), &graph, &config)?;
+                println!(
+                    "{formula}: {:.4} (95% CI [{:.4}, {:.4}], {} of {} runs)",
+                    estimate.estimate,
+                    estimate.lower_bound,
+                    estimate.upper_bound,
+                    estimate.num_successes,
+                    estimate.num_samples
+                );
+            }
+            Ok(())
+        }
+        Command::CheckTrace {
+            model_path,
+            trace_path,
+            formulae_path,
+            formulae,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            let trace = load_trace(trace_path.as_str())?;
+            print_trace_report(&bn, &trace, &collect_formulae(formulae_path, formulae)?)
+        }
+        Command::Repl { model_path, limits } => {
+            let bn = load_model(model_path.as_str())?;
+            run_repl(bn, limits.to_limits())
+        }
+        Command::ModelInfo { model_path } => {
+            let bn = load_model(model_path.as_str())?;
+            print_model_info(&bn)
+        }
+        Command::KnockoutScreen {
+            model_path,
+            formula,
+            value,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            let (original, impacts) =
+                regulation_knockout_screen(&bn, formula.as_str(), value == "1")?;
+            println!(
+                "Original model: {} of {} colors satisfy the property.",
+                original.satisfying_colors, original.total_colors
+            );
+            println!("regulator,target,satisfying_colors,total_colors,satisfying_fraction");
+            for impact in impacts {
+                println!(
+                    "{},{},{},{},{:.4}",
+                    impact.regulator,
+                    impact.target,
+                    impact.satisfying_colors,
+                    impact.total_colors,
+                    impact.satisfying_fraction()
+                );
+            }
+            Ok(())
+        }
+        Command::Drivers {
+            model_path,
+            formula,
+            json,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            analyse_driver_variables(&bn, formula.as_str(), json)
+        }
+        Command::Explain {
+            model_path,
+            formula,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            print_reachability_explanation(&bn, formula.as_str())
+        }
+        Command::Consistency {
+            model_path,
+            observations_path,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            let observations = load_observations(observations_path.as_str(), &bn)?;
+            let report = check_consistency_with_observations(&bn, &observations)?;
+            println!("{report}");
+            Ok(())
+        }
+        Command::Basins {
+            model_path,
+            formula,
+            output_bundle,
+        } => {
+            let bn = load_model(model_path.as_str())?;
+            analyse_basins(&bn, formula.as_str(), output_bundle)
+        }
+        Command::Diff {
+            first_bundle,
+            second_bundle,
+        } => compare_result_archives(first_bundle.as_str(), second_bundle.as_str()),
+        Command::Combine {
+            expression,
+            bundles,
+            output_bundle,
+        } => combine_result_archives(expression.as_str(), &bundles, output_bundle),
+        #[cfg(feature = "server")]
+        Command::Serve { address } => run_server(address.as_str()),
+    }
+}
+
+/// Wrapper function to invoke the model checker, works with CLI arguments.
+fn main() {
+    let args = Arguments::parse();
 
+    let res = run_command(args.command);
     if res.is_err() {
         println!("{}", res.err().unwrap());
     }
@@ -1,12 +1,21 @@
 //! Model checking utilities such as generating extended STG or checking if an STG supports
 //! enough sets of symbolic variables.
 
-use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType, VarDomain};
 use crate::preprocessing::operator_enums::{Atomic, HybridOp};
+use crate::preprocessing::tokenizer::{
+    FAIR_ATTRACTORS_DOMAIN, PRODUCT_NAME_SEPARATOR, PRODUCT_PREFIXES,
+};
+use crate::profiling::span;
 
-use biodivine_lib_param_bn::symbolic_async_graph::{SymbolicAsyncGraph, SymbolicContext};
-use biodivine_lib_param_bn::BooleanNetwork;
+use biodivine_lib_bdd::BddVariable;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, SymbolicAsyncGraph, SymbolicContext,
+};
+use biodivine_lib_param_bn::{BooleanNetwork, FnUpdate, Monotonicity, RegulatoryGraph, VariableId};
 
+use std::cmp::max;
 use std::collections::{HashMap, HashSet};
 
 /// Create an extended symbolic transition graph that supports the number of needed HCTL variables.
@@ -16,6 +25,7 @@ pub fn get_extended_symbolic_graph(
     bn: &BooleanNetwork,
     num_hctl_vars: u16,
 ) -> Result<SymbolicAsyncGraph, String> {
+    let _span = span("graph_construction");
     // for each BN var, `num_hctl_vars` new BDD vars must be created
     let mut map_num_vars = HashMap::new();
     for bn_var in bn.variables() {
@@ -27,6 +37,745 @@ pub fn get_extended_symbolic_graph(
     SymbolicAsyncGraph::with_custom_context(bn, context, unit)
 }
 
+/// Get the canonical name of the HCTL variable with the given `index`, as used in the BDDs of the
+/// extended graphs (`x` for index 0, `xx` for index 1, ...). The variables of the formulae are
+/// renamed to these names before the evaluation (see [validate_props_and_rename_vars]).
+///
+/// [validate_props_and_rename_vars]: crate::preprocessing::utils::validate_props_and_rename_vars
+pub fn hctl_var_name(index: usize) -> String {
+    "x".repeat(index + 1)
+}
+
+/// Get the index of the HCTL variable given by its canonical name (see [hctl_var_name]), or
+/// `None` if the name is not canonical.
+pub fn hctl_var_index(name: &str) -> Option<usize> {
+    if name.is_empty() || name.chars().any(|c| c != 'x') {
+        return None;
+    }
+    Some(name.len() - 1)
+}
+
+/// Get the number of HCTL variables supported by the extended `graph` (i.e., the number of extra
+/// symbolic variables per each network variable).
+pub fn num_hctl_vars(graph: &SymbolicAsyncGraph) -> usize {
+    graph
+        .variables()
+        .map(|var| graph.symbolic_context().extra_state_variables(var).len())
+        .min()
+        .unwrap_or(0)
+}
+
+/// Get the BDD variables that encode the HCTL variable with the given `index` in the extended
+/// `graph`, one for each network variable (in their order). The value of the `i`-th BDD variable
+/// is the value of the `i`-th network variable in the state assigned to the HCTL variable.
+///
+/// In the BDDs of the "dirty" results, the extra BDD variables of the network variable `v` are
+/// named `{v}_extra_{index}`, and they can also be obtained by
+/// `graph.symbolic_context().get_extra_state_variable(v, index)`.
+pub fn hctl_var_bdd_variables(
+    graph: &SymbolicAsyncGraph,
+    index: usize,
+) -> Result<Vec<BddVariable>, String> {
+    if index >= num_hctl_vars(graph) {
+        return Err(format!(
+            "The graph does not support HCTL variable `{}`.",
+            hctl_var_name(index)
+        ));
+    }
+    let context = graph.symbolic_context();
+    Ok(graph
+        .variables()
+        .map(|var| context.get_extra_state_variable(var, index))
+        .collect())
+}
+
+/// Transfer a "dirty" `set` of the extended `source` graph into the context of the `target`
+/// graph of the same network (with a different number of HCTL variables), keeping the values
+/// of the HCTL variables. The result is restricted to the unit set of the `target`.
+///
+/// Return an error if the `set` depends on an HCTL variable not supported by the `target`.
+pub fn transfer_hctl_set(
+    source: &SymbolicAsyncGraph,
+    set: &GraphColoredVertices,
+    target: &SymbolicAsyncGraph,
+) -> Result<GraphColoredVertices, String> {
+    let bdd = target
+        .symbolic_context()
+        .transfer_from(set.as_bdd(), source.symbolic_context())
+        .ok_or("The set depends on variables not supported by the target graph.".to_string())?;
+    Ok(GraphColoredVertices::new(bdd, target.symbolic_context())
+        .intersect(target.unit_colored_vertices()))
+}
+
+/// Build the product of two BNs, with disjoint variables (and parameters) of both models. The
+/// names of the first model are prefixed by `m1__`, and the names of the second model by `m2__`.
+///
+/// Under the asynchronous semantics, the dynamics of the product is the interleaving of the
+/// dynamics of both models. The propositions of the formulae can refer to the variables of the
+/// models as `m1.x` and `m2.x`, so that the formulae can relate the behaviour of both models.
+/// The names of the models must not contain the separator `__` (see [PRODUCT_NAME_SEPARATOR]),
+/// so that the qualified names are unambiguous.
+pub fn build_product_network(
+    first: &BooleanNetwork,
+    second: &BooleanNetwork,
+) -> Result<BooleanNetwork, String> {
+    let models = [first, second];
+    for bn in models {
+        let variable_names = bn.variables().map(|v| bn.get_variable_name(v).clone());
+        let parameter_names = bn.parameters().map(|p| bn[p].get_name().clone());
+        if let Some(name) = variable_names
+            .chain(parameter_names)
+            .find(|name| name.contains(PRODUCT_NAME_SEPARATOR))
+        {
+            return Err(format!(
+                "Name `{name}` can't contain `{PRODUCT_NAME_SEPARATOR}` in a product network."
+            ));
+        }
+    }
+    let product_name =
+        |i: usize, name: &str| format!("{}{PRODUCT_NAME_SEPARATOR}{name}", PRODUCT_PREFIXES[i]);
+
+    let variable_names = models
+        .iter()
+        .enumerate()
+        .flat_map(|(i, bn)| {
+            bn.variables()
+                .map(move |v| product_name(i, bn.get_variable_name(v)))
+        })
+        .collect();
+    let mut graph = RegulatoryGraph::new(variable_names);
+    for (i, bn) in models.iter().enumerate() {
+        for regulation in bn.as_graph().regulations() {
+            graph.add_regulation(
+                product_name(i, bn.get_variable_name(regulation.get_regulator())).as_str(),
+                product_name(i, bn.get_variable_name(regulation.get_target())).as_str(),
+                regulation.is_observable(),
+                regulation.get_monotonicity(),
+            )?;
+        }
+    }
+
+    let mut product = BooleanNetwork::new(graph);
+    for (i, bn) in models.iter().enumerate() {
+        let mut parameter_map = HashMap::new();
+        for parameter in bn.parameters() {
+            let name = product_name(i, bn[parameter].get_name());
+            let new_parameter = product.add_parameter(name.as_str(), bn[parameter].get_arity())?;
+            parameter_map.insert(parameter, new_parameter);
+        }
+        let variable_map = bn
+            .variables()
+            .map(|v| {
+                let name = product_name(i, bn.get_variable_name(v));
+                (v, product.as_graph().find_variable(name.as_str()).unwrap())
+            })
+            .collect::<HashMap<_, _>>();
+        for var in bn.variables() {
+            if let Some(update_function) = bn.get_update_function(var) {
+                let renamed = update_function.rename_all(&variable_map, &parameter_map);
+                product.set_update_function(variable_map[&var], Some(renamed))?;
+            }
+        }
+    }
+    Ok(product)
+}
+
+/// Build the product of two BNs (see [build_product_network]), and create its extended
+/// symbolic transition graph that supports the number of needed HCTL variables.
+pub fn get_product_symbolic_graph(
+    first: &BooleanNetwork,
+    second: &BooleanNetwork,
+    num_hctl_vars: u16,
+) -> Result<SymbolicAsyncGraph, String> {
+    let product = build_product_network(first, second)?;
+    get_extended_symbolic_graph(&product, num_hctl_vars)
+}
+
+/// Treatment of the input variables of a BN when constructing the symbolic transition graph
+/// (see [find_input_variables] for what is considered an input).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InputMode {
+    /// Inputs are constants, with their values given by colors (quantified as parameters).
+    Parametrized,
+    /// Inputs are free, and can change their value at any time (oscillating independently of
+    /// the rest of the network).
+    Free,
+    /// Inputs are fixed to the given values. Inputs missing in the map are parametrized.
+    Fixed(HashMap<String, bool>),
+}
+
+/// Find the input variables of a BN. These are the variables with no regulators (with any
+/// update function), and the variables only regulated by themselves with identity (or missing)
+/// update function, such as `x, x` in the bnet format.
+pub fn find_input_variables(bn: &BooleanNetwork) -> Vec<VariableId> {
+    bn.variables()
+        .filter(|var| {
+            let regulators = bn.regulators(*var);
+            if regulators.is_empty() {
+                return true;
+            }
+            regulators == vec![*var]
+                && match bn.get_update_function(*var) {
+                    None => true,
+                    Some(update_function) => *update_function == FnUpdate::Var(*var),
+                }
+        })
+        .collect()
+}
+
+/// Modify the BN so that its input variables are treated according to the `input_mode`. Each
+/// input either gets an implicit (zero-arity) update function, a negation of itself (so that it
+/// can always change its value), or a constant update function.
+///
+/// Note that the states where a fixed input has a different value are still part of the network,
+/// use [get_extended_symbolic_graph_with_inputs] to remove them from the graph.
+pub fn apply_input_mode(
+    bn: &BooleanNetwork,
+    input_mode: &InputMode,
+) -> Result<BooleanNetwork, String> {
+    let inputs = find_input_variables(bn);
+    if let InputMode::Fixed(values) = input_mode {
+        for name in values.keys() {
+            let is_input = bn
+                .as_graph()
+                .find_variable(name)
+                .is_some_and(|var| inputs.contains(&var));
+            if !is_input {
+                return Err(format!("Variable `{name}` is not an input of the network."));
+            }
+        }
+    }
+    let fixed_value = |var: VariableId| match input_mode {
+        InputMode::Fixed(values) => values.get(bn.get_variable_name(var)).copied(),
+        _ => None,
+    };
+
+    // copy the regulations, with self-regulations of the inputs replaced as needed
+    let mut graph = RegulatoryGraph::new(bn.as_graph().variable_names());
+    for regulation in bn.as_graph().regulations() {
+        if !inputs.contains(&regulation.get_target()) {
+            graph.add_regulation(
+                bn.get_variable_name(regulation.get_regulator()),
+                bn.get_variable_name(regulation.get_target()),
+                regulation.is_observable(),
+                regulation.get_monotonicity(),
+            )?;
+        }
+    }
+    if *input_mode == InputMode::Free {
+        for var in &inputs {
+            let name = bn.get_variable_name(*var);
+            graph.add_regulation(name, name, true, Some(Monotonicity::Inhibition))?;
+        }
+    }
+
+    // parameters keep their IDs, so the update functions can be copied directly
+    let mut modified = BooleanNetwork::new(graph);
+    for parameter in bn.parameters() {
+        modified.add_parameter(bn[parameter].get_name(), bn[parameter].get_arity())?;
+    }
+    for var in bn.variables() {
+        let update_function = if !inputs.contains(&var) {
+            bn.get_update_function(var).clone()
+        } else if let Some(value) = fixed_value(var) {
+            Some(FnUpdate::Const(value))
+        } else if *input_mode == InputMode::Free {
+            Some(FnUpdate::mk_not(FnUpdate::Var(var)))
+        } else {
+            None
+        };
+        modified.set_update_function(var, update_function)?;
+    }
+    Ok(modified)
+}
+
+/// Create an extended symbolic transition graph that supports the number of needed HCTL
+/// variables (see [get_extended_symbolic_graph]), with the input variables of the BN treated
+/// according to the `input_mode` (see [apply_input_mode]).
+///
+/// With [InputMode::Fixed], only the states with the given values of inputs are in the graph.
+pub fn get_extended_symbolic_graph_with_inputs(
+    bn: &BooleanNetwork,
+    num_hctl_vars: u16,
+    input_mode: &InputMode,
+) -> Result<SymbolicAsyncGraph, String> {
+    let modified = apply_input_mode(bn, input_mode)?;
+    let mut map_num_vars = HashMap::new();
+    for bn_var in modified.variables() {
+        map_num_vars.insert(bn_var, num_hctl_vars);
+    }
+    let context = SymbolicContext::with_extra_state_variables(&modified, &map_num_vars)?;
+    let mut unit = context.mk_constant(true);
+    if let InputMode::Fixed(values) = input_mode {
+        for (name, value) in values {
+            let var = modified.as_graph().find_variable(name).unwrap();
+            let literal = context.mk_state_variable_is_true(var);
+            unit = if *value {
+                unit.and(&literal)
+            } else {
+                unit.and_not(&literal)
+            };
+        }
+    }
+    SymbolicAsyncGraph::with_custom_context(&modified, context, unit)
+}
+
+/// Prefix of the phase variables added by [build_scheduled_network]. The variable `phase_i`
+/// (for `i >= 2`) is true once the `i`-th phase of the schedule has started.
+pub const PHASE_VARIABLE_PREFIX: &str = "phase_";
+
+/// Build a network in which the input variables follow a piecewise-constant `schedule`, i.e., a
+/// sequence of phases, each giving the values of the scheduled inputs (all phases must assign
+/// the same inputs).
+///
+/// The phases are encoded by new variables `phase_2`, ..., `phase_k` (see
+/// [PHASE_VARIABLE_PREFIX]), where `phase_i` means that the `i`-th phase has started. The phases
+/// advance monotonically, and the next phase can start at any time. The scheduled inputs are
+/// substituted by their values in the current phase in all update functions, so that the
+/// network switches to the new input values together with the phase. The inputs themselves are
+/// kept as observables following the schedule (possibly with a delay). Hence, the properties
+/// scoped to phases should use the phase variables, e.g., `AG (~phase_2 => ~c)` states that `c`
+/// is inactive during the first phase. Use [get_scheduled_symbolic_graph] to only consider the
+/// consistent encodings of phases.
+///
+/// The targets of the scheduled inputs must have explicit update functions.
+pub fn build_scheduled_network(
+    bn: &BooleanNetwork,
+    schedule: &[HashMap<String, bool>],
+) -> Result<BooleanNetwork, String> {
+    let first_phase = schedule
+        .first()
+        .ok_or("The schedule must have at least one phase.".to_string())?;
+    let inputs = find_input_variables(bn);
+    let mut scheduled = Vec::new();
+    for name in first_phase.keys() {
+        match bn.as_graph().find_variable(name) {
+            Some(var) if inputs.contains(&var) => scheduled.push(var),
+            _ => return Err(format!("Variable `{name}` is not an input of the network.")),
+        }
+    }
+    scheduled.sort();
+    for (i, phase) in schedule.iter().enumerate() {
+        let same_inputs = phase.len() == scheduled.len()
+            && scheduled
+                .iter()
+                .all(|var| phase.contains_key(bn.get_variable_name(*var)));
+        if !same_inputs {
+            return Err(format!(
+                "Phase {} of the schedule must assign the same inputs as the first phase.",
+                i + 1
+            ));
+        }
+    }
+    let targets: HashSet<VariableId> = scheduled
+        .iter()
+        .flat_map(|var| bn.targets(*var))
+        .filter(|target| !scheduled.contains(target))
+        .collect();
+    for target in &targets {
+        if bn.get_update_function(*target).is_none() {
+            return Err(format!(
+                "Variable `{}` regulated by a scheduled input must have an explicit update function.",
+                bn.get_variable_name(*target)
+            ));
+        }
+    }
+
+    let phase_names: Vec<String> = (2..=schedule.len())
+        .map(|i| format!("{PHASE_VARIABLE_PREFIX}{i}"))
+        .collect();
+    let mut variable_names = bn.as_graph().variable_names();
+    for name in &phase_names {
+        if variable_names.contains(name) {
+            return Err(format!("Network already contains variable `{name}`."));
+        }
+        variable_names.push(name.clone());
+    }
+
+    // original variables keep their IDs, the phase variables are added after them
+    let mut graph = RegulatoryGraph::new(variable_names);
+    for regulation in bn.as_graph().regulations() {
+        let (regulator, target) = (regulation.get_regulator(), regulation.get_target());
+        if scheduled.contains(&regulator) || scheduled.contains(&target) {
+            continue;
+        }
+        // the substitution may make the target independent of its other regulators
+        graph.add_regulation(
+            bn.get_variable_name(regulator),
+            bn.get_variable_name(target),
+            regulation.is_observable() && !targets.contains(&target),
+            regulation.get_monotonicity(),
+        )?;
+    }
+    for (i, name) in phase_names.iter().enumerate() {
+        if i > 0 {
+            graph.add_regulation(
+                &phase_names[i - 1],
+                name,
+                true,
+                Some(Monotonicity::Activation),
+            )?;
+        }
+        for var in scheduled.iter().chain(targets.iter()) {
+            graph.add_regulation(name, bn.get_variable_name(*var), false, None)?;
+        }
+    }
+
+    // parameters keep their IDs, so the update functions can be copied directly
+    let mut modified = BooleanNetwork::new(graph);
+    for parameter in bn.parameters() {
+        modified.add_parameter(bn[parameter].get_name(), bn[parameter].get_arity())?;
+    }
+    let phase_vars: Vec<VariableId> = phase_names
+        .iter()
+        .map(|name| modified.as_graph().find_variable(name).unwrap())
+        .collect();
+    // phase `i` (indexed from 0) holds iff it has started and the next one has not
+    let in_phase = |i: usize| {
+        let started = if i == 0 {
+            FnUpdate::mk_true()
+        } else {
+            FnUpdate::mk_var(phase_vars[i - 1])
+        };
+        match phase_vars.get(i) {
+            Some(next) => started.and(FnUpdate::mk_var(*next).negation()),
+            None => started,
+        }
+    };
+    let scheduled_values: HashMap<VariableId, FnUpdate> = scheduled
+        .iter()
+        .map(|var| {
+            let phases: Vec<FnUpdate> = schedule
+                .iter()
+                .enumerate()
+                .filter(|(_, phase)| phase[bn.get_variable_name(*var)])
+                .map(|(i, _)| in_phase(i))
+                .collect();
+            (*var, FnUpdate::mk_disjunction(&phases).simplify_constants())
+        })
+        .collect();
+
+    for var in bn.variables() {
+        let update_function = if let Some(value) = scheduled_values.get(&var) {
+            Some(value.clone())
+        } else {
+            bn.get_update_function(var).as_ref().map(|function| {
+                scheduled_values
+                    .iter()
+                    .fold(function.clone(), |acc, (input, value)| {
+                        acc.substitute_variable(*input, value)
+                    })
+            })
+        };
+        modified.set_update_function(var, update_function)?;
+    }
+    // the first phase variable can switch at any time, the others after their predecessor
+    for (i, var) in phase_vars.iter().enumerate() {
+        let update_function = if i == 0 {
+            FnUpdate::mk_true()
+        } else {
+            FnUpdate::mk_var(phase_vars[i - 1])
+        };
+        modified.set_update_function(*var, Some(update_function))?;
+    }
+    Ok(modified)
+}
+
+/// Build the network following the input `schedule` (see [build_scheduled_network]), and create
+/// its extended symbolic transition graph that supports the number of needed HCTL variables.
+///
+/// Only the states with a consistent encoding of phases (each started phase has all previous
+/// phases started) are in the graph.
+pub fn get_scheduled_symbolic_graph(
+    bn: &BooleanNetwork,
+    schedule: &[HashMap<String, bool>],
+    num_hctl_vars: u16,
+) -> Result<SymbolicAsyncGraph, String> {
+    let scheduled = build_scheduled_network(bn, schedule)?;
+    let mut map_num_vars = HashMap::new();
+    for bn_var in scheduled.variables() {
+        map_num_vars.insert(bn_var, num_hctl_vars);
+    }
+    let context = SymbolicContext::with_extra_state_variables(&scheduled, &map_num_vars)?;
+    let mut unit = context.mk_constant(true);
+    for i in 3..=schedule.len() {
+        let find_phase = |i: usize| {
+            let name = format!("{PHASE_VARIABLE_PREFIX}{i}");
+            let var = scheduled.as_graph().find_variable(&name).unwrap();
+            context.mk_state_variable_is_true(var)
+        };
+        unit = unit.and(&find_phase(i).imp(&find_phase(i - 1)));
+    }
+    SymbolicAsyncGraph::with_custom_context(&scheduled, context, unit)
+}
+
+/// Restrict the unit BDD of the `graph`, so that the selected inputs and parameters hold the
+/// given values. Each name in `assignments` must be either an input variable (see
+/// [find_input_variables], its state is fixed, as well as its implicit update function if it is
+/// a parametrized input), or a zero-arity parameter. Other variables can not be fixed, as that
+/// would cut their dynamics (see [with_frozen_variables] or [with_perturbed_variables] instead).
+///
+/// Explicit update functions of the fixed inputs (other than the identity) are replaced by the
+/// given constants, the same way as by [apply_input_mode], so that no transition leaves the
+/// restricted unit (e.g., for `$a: true` fixed to `a = 0`).
+///
+/// This allows to analyse the same network under different environmental conditions without
+/// modifying the network itself.
+pub fn with_fixed_inputs(
+    graph: &SymbolicAsyncGraph,
+    assignments: &HashMap<String, bool>,
+) -> Result<SymbolicAsyncGraph, String> {
+    let bn = graph
+        .as_network()
+        .ok_or("Graph is not based on a Boolean network.".to_string())?;
+    let context = graph.symbolic_context();
+    let mut unit = graph.unit_colored_vertices().as_bdd().clone();
+    let inputs = find_input_variables(bn);
+    let mut modified_bn = bn.clone();
+    for (name, value) in assignments {
+        // literals that must hold the given value
+        let literals = if let Some(var) = bn.as_graph().find_variable(name) {
+            if !inputs.contains(&var) {
+                return Err(format!(
+                    "Variable `{name}` is not an input, its value can not be fixed."
+                ));
+            }
+            match bn.get_update_function(var) {
+                // parametrized input, its (constant) implicit function must be fixed too
+                None if bn.regulators(var).is_empty() => vec![
+                    context.mk_state_variable_is_true(var),
+                    context.mk_implicit_function_is_true(var, &[]),
+                ],
+                // the explicit function could change the value, so it is replaced
+                Some(function) if *function != FnUpdate::Var(var) => {
+                    modified_bn.set_update_function(var, Some(FnUpdate::Const(*value)))?;
+                    vec![context.mk_state_variable_is_true(var)]
+                }
+                _ => vec![context.mk_state_variable_is_true(var)],
+            }
+        } else if let Some(parameter) = context.find_network_parameter(name) {
+            if bn[parameter].get_arity() != 0 {
+                return Err(format!(
+                    "Parameter `{name}` is not a constant, its value can not be fixed."
+                ));
+            }
+            vec![context.mk_uninterpreted_function_is_true(parameter, &[])]
+        } else {
+            return Err(format!("There is no variable or parameter `{name}`."));
+        };
+        for literal in literals {
+            unit = if *value {
+                unit.and(&literal)
+            } else {
+                unit.and_not(&literal)
+            };
+        }
+    }
+    SymbolicAsyncGraph::with_custom_context(&modified_bn, context.clone(), unit)
+}
+
+/// Create a copy of the `graph` in which the given network variables are `frozen`, i.e., they
+/// never fire a transition (their update functions are replaced by identities). The resulting
+/// graph is a sub-graph of the original one with the same states and colors, and it is
+/// symbolically compatible with the original graph.
+///
+/// This allows to analyse the network with a module "switched off" without modifying the network
+/// itself.
+pub fn with_frozen_variables(
+    graph: &SymbolicAsyncGraph,
+    frozen: &[String],
+) -> Result<SymbolicAsyncGraph, String> {
+    let context = graph.symbolic_context();
+    let mut frozen_vars = HashSet::new();
+    for name in frozen {
+        let var = context
+            .find_network_variable(name)
+            .ok_or(format!("There is no network variable named {name}."))?;
+        frozen_vars.insert(var);
+    }
+    let update_functions = graph
+        .variables()
+        .map(|var| {
+            if frozen_vars.contains(&var) {
+                // the variable is updated to its current value, so its transitions are empty
+                context.mk_state_variable_is_true(var)
+            } else {
+                graph.get_symbolic_fn_update(var).clone()
+            }
+        })
+        .collect();
+    // SAFETY: The functions (and the unit set) are in the symbolic context of the graph, and
+    // they only differ from the valid functions of the graph by the frozen variables.
+    unsafe {
+        Ok(SymbolicAsyncGraph::new_raw(
+            graph.as_network().cloned(),
+            context.clone(),
+            graph.unit_colored_vertices().as_bdd().clone(),
+            update_functions,
+        ))
+    }
+}
+
+/// Create a copy of the `graph` in which the given network variables are permanently fixed to
+/// the given values (e.g., by a knock-out or an over-expression), i.e., they are frozen (see
+/// [with_frozen_variables]) and the states are restricted to their fixed values. The resulting
+/// graph is symbolically compatible with the original graph.
+pub fn with_perturbed_variables(
+    graph: &SymbolicAsyncGraph,
+    fixings: &[(String, bool)],
+) -> Result<SymbolicAsyncGraph, String> {
+    let names: Vec<String> = fixings.iter().map(|(name, _)| name.clone()).collect();
+    let frozen = with_frozen_variables(graph, &names)?;
+    Ok(restrict_frozen_variables(&frozen, fixings))
+}
+
+/// Restrict the states of the `frozen` graph (see [with_frozen_variables]) to the given values
+/// of its frozen variables. All the variables of `fixings` MUST be frozen in the graph.
+///
+/// This allows to share one frozen graph between the perturbations fixing the same variables
+/// (to different values).
+pub(crate) fn restrict_frozen_variables(
+    frozen: &SymbolicAsyncGraph,
+    fixings: &[(String, bool)],
+) -> SymbolicAsyncGraph {
+    let context = frozen.symbolic_context();
+    let mut unit = frozen.unit_colored_vertices().as_bdd().clone();
+    for (name, value) in fixings {
+        // the variable is valid, as it was already frozen
+        let var = context.find_network_variable(name).unwrap();
+        let literal = context.mk_state_variable_is_true(var);
+        unit = if *value {
+            unit.and(&literal)
+        } else {
+            unit.and_not(&literal)
+        };
+    }
+    let update_functions = frozen
+        .variables()
+        .map(|var| frozen.get_symbolic_fn_update(var).clone())
+        .collect();
+    // SAFETY: The frozen variables never change, so the restricted unit set is closed under the
+    // transitions of the frozen graph.
+    unsafe {
+        SymbolicAsyncGraph::new_raw(
+            frozen.as_network().cloned(),
+            context.clone(),
+            unit,
+            update_functions,
+        )
+    }
+}
+
+/// Modify the BN by knocking out the regulation of `target` by `regulator`. The regulation is
+/// removed, and the regulator is replaced by the constant `value` in the update function of the
+/// target (unspecified update functions simply lose the argument).
+///
+/// Since the explicit update function of the target may become independent of its other
+/// regulators, their regulations are no longer required to be observable.
+pub fn knock_out_regulation(
+    bn: &BooleanNetwork,
+    regulator: VariableId,
+    target: VariableId,
+    value: bool,
+) -> Result<BooleanNetwork, String> {
+    if bn.as_graph().find_regulation(regulator, target).is_none() {
+        return Err(format!(
+            "There is no regulation `{}` -> `{}`.",
+            bn.get_variable_name(regulator),
+            bn.get_variable_name(target)
+        ));
+    }
+    let explicit_target = bn.get_update_function(target).is_some();
+
+    let mut graph = RegulatoryGraph::new(bn.as_graph().variable_names());
+    for regulation in bn.as_graph().regulations() {
+        if regulation.get_regulator() == regulator && regulation.get_target() == target {
+            continue;
+        }
+        let relaxed = explicit_target && regulation.get_target() == target;
+        graph.add_regulation(
+            bn.get_variable_name(regulation.get_regulator()),
+            bn.get_variable_name(regulation.get_target()),
+            regulation.is_observable() && !relaxed,
+            regulation.get_monotonicity(),
+        )?;
+    }
+
+    // parameters keep their IDs, so the update functions can be copied directly
+    let mut modified = BooleanNetwork::new(graph);
+    for parameter in bn.parameters() {
+        modified.add_parameter(bn[parameter].get_name(), bn[parameter].get_arity())?;
+    }
+    for var in bn.variables() {
+        let update_function = if var == target {
+            bn.get_update_function(var)
+                .as_ref()
+                .map(|function| function.substitute_variable(regulator, &FnUpdate::Const(value)))
+        } else {
+            bn.get_update_function(var).clone()
+        };
+        modified.set_update_function(var, update_function)?;
+    }
+    Ok(modified)
+}
+
+/// Module of the regulatory graph: a strongly connected component, together with all variables
+/// that (transitively) regulate it. The dynamics of the variables in a module does not depend on
+/// any variable outside of it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegulatoryModule {
+    /// The strongly connected component that defines the module.
+    pub component: HashSet<VariableId>,
+    /// All variables of the module (the component and its upstream variables).
+    pub variables: HashSet<VariableId>,
+}
+
+/// Decompose the regulatory graph into the strongly connected components (including the trivial
+/// ones), and compute the module of each component (see [RegulatoryModule]).
+///
+/// The modules are sorted by their size (number of variables), starting with the smallest.
+pub fn decompose_regulatory_graph(graph: &RegulatoryGraph) -> Vec<RegulatoryModule> {
+    let mut modules = Vec::new();
+    let mut remaining: HashSet<VariableId> = graph.variables().collect();
+    for var in graph.variables() {
+        if !remaining.contains(&var) {
+            continue;
+        }
+        let mut variables = graph.transitive_regulators(var);
+        variables.insert(var);
+        let targets = graph.transitive_targets(var);
+        let mut component: HashSet<VariableId> = variables
+            .iter()
+            .filter(|v| targets.contains(v))
+            .cloned()
+            .collect();
+        component.insert(var);
+        remaining.retain(|v| !component.contains(v));
+        modules.push(RegulatoryModule {
+            component,
+            variables,
+        });
+    }
+    modules.sort_by_key(|module| module.variables.len());
+    modules
+}
+
+/// Find the smallest module of the regulatory graph (see [decompose_regulatory_graph]) that
+/// contains all variables with the given names. Return `None` if there is no such module, or
+/// if some of the names are not variables of the graph.
+pub fn find_module_for_propositions(
+    graph: &RegulatoryGraph,
+    propositions: &HashSet<String>,
+) -> Option<RegulatoryModule> {
+    let mut variables = HashSet::new();
+    for name in propositions {
+        variables.insert(graph.find_variable(name)?);
+    }
+    decompose_regulatory_graph(graph)
+        .into_iter()
+        .find(|module| module.variables.is_superset(&variables))
+}
+
 /// Compute the set of all uniquely named HCTL variables in the formula tree.
 ///
 /// Variable names are collected from three quantifiers: `bind`, `exists`, `forall` (which is sufficient,
@@ -71,8 +820,47 @@ fn collect_unique_hctl_vars_recursive(
     seen_vars
 }
 
-/// Compute the set of all uniquely named `wild-card propositions` and the set of all
-/// `variable domains` in the formula tree.
+/// Compute the minimal number of HCTL variables (i.e., the number of extra symbolic variables per
+/// network variable) of an extended graph in which all the given formula trees can be evaluated.
+/// This covers both the quantified variables and the variables of the `wild-card relations`.
+///
+/// The variables of the trees must already be renamed (see
+/// [crate::preprocessing::utils::validate_props_and_rename_vars]). Pure CTL formulae need no
+/// HCTL variables at all.
+///
+/// Return an error if a `wild-card` is used with different numbers of variables (see
+/// [collect_wild_card_arities]).
+pub fn required_hctl_vars(trees: &[HctlTreeNode]) -> Result<usize, String> {
+    let mut required = 0;
+    for tree in trees {
+        let max_arity = collect_wild_card_arities(tree)?
+            .into_values()
+            .max()
+            .unwrap_or(0);
+        required = max(
+            required,
+            max(collect_unique_hctl_vars(tree.clone()).len(), max_arity),
+        );
+    }
+    Ok(required)
+}
+
+/// Check whether the formula tree is a pure CTL formula, i.e., it has no hybrid operators and no
+/// HCTL variables (not even in wild-card relations). Such formulae can be evaluated without any
+/// HCTL-variable bookkeeping (no extended graph, no canonization of sub-formulae).
+pub fn is_ctl_formula(formula_tree: &HctlTreeNode) -> bool {
+    match &formula_tree.node_type {
+        NodeType::Terminal(Atomic::Var(_) | Atomic::WildCardRelation(..)) => false,
+        NodeType::Terminal(_) => true,
+        NodeType::Unary(_, child) => is_ctl_formula(child),
+        NodeType::Binary(_, left, right) => is_ctl_formula(left) && is_ctl_formula(right),
+        NodeType::Hybrid(..) => false,
+    }
+}
+
+/// Compute the set of all uniquely named `wild-card propositions` (including the wild-card
+/// relations) and the set of all `variable domains` in the formula tree. Inline domains
+/// (`in [expression]`) are not included, since they are not provided as wild-card contexts.
 pub fn collect_unique_wild_cards(formula_tree: HctlTreeNode) -> (HashSet<String>, HashSet<String>) {
     let mut wild_card_props = HashSet::new();
     let mut var_domains = HashSet::new();
@@ -89,7 +877,7 @@ fn collect_unique_wild_cards_recursive(
 ) {
     match formula_tree.node_type {
         NodeType::Terminal(atom) => {
-            if let Atomic::WildCardProp(prop_name) = atom {
+            if let Atomic::WildCardProp(prop_name) | Atomic::WildCardRelation(prop_name, _) = atom {
                 seen_props.insert(prop_name);
             }
         }
@@ -101,8 +889,11 @@ fn collect_unique_wild_cards_recursive(
             collect_unique_wild_cards_recursive(*right, seen_props, seen_domains);
         }
         NodeType::Hybrid(_, _, optional_domain, child) => {
-            if let Some(domain) = optional_domain {
-                seen_domains.insert(domain);
+            // inline domains and fair attractors are not wild-cards
+            if let Some(VarDomain::Label(domain)) = optional_domain {
+                if domain != FAIR_ATTRACTORS_DOMAIN {
+                    seen_domains.insert(domain);
+                }
             }
 
             collect_unique_wild_cards_recursive(*child, seen_props, seen_domains);
@@ -110,31 +901,124 @@ fn collect_unique_wild_cards_recursive(
     }
 }
 
+/// Collect all the (distinct) built-in domains in the formula tree, i.e., the inline domains
+/// (`in [expression]`) and the domain of fair attractors (`in fair_attractors`), in the order of
+/// their first occurrence. Their sets are computed by the evaluation itself (unlike the sets of
+/// the wild-card domains, see [collect_unique_wild_cards]).
+pub fn collect_builtin_domains(formula_tree: &HctlTreeNode) -> Vec<VarDomain> {
+    let mut domains = Vec::new();
+    collect_builtin_domains_recursive(formula_tree, &mut domains);
+    domains
+}
+
+/// Recursive fn to collect the built-in domains in the formula tree.
+fn collect_builtin_domains_recursive(formula_tree: &HctlTreeNode, domains: &mut Vec<VarDomain>) {
+    match &formula_tree.node_type {
+        NodeType::Terminal(_) => {}
+        NodeType::Unary(_, child) => collect_builtin_domains_recursive(child, domains),
+        NodeType::Binary(_, left, right) => {
+            collect_builtin_domains_recursive(left, domains);
+            collect_builtin_domains_recursive(right, domains);
+        }
+        NodeType::Hybrid(_, _, domain, child) => {
+            let is_builtin = match domain {
+                Some(VarDomain::Inline(_)) => true,
+                Some(VarDomain::Label(label)) => label == FAIR_ATTRACTORS_DOMAIN,
+                None => false,
+            };
+            if let Some(domain) = domain.as_ref().filter(|_| is_builtin) {
+                if !domains.contains(domain) {
+                    domains.push(domain.clone());
+                }
+            }
+            collect_builtin_domains_recursive(child, domains);
+        }
+    }
+}
+
+/// Compute the number of variables of each `wild-card relation` in the formula tree, with
+/// `wild-card propositions` having no variables.
+///
+/// Return an error if the same name is used with different numbers of variables.
+pub fn collect_wild_card_arities(
+    formula_tree: &HctlTreeNode,
+) -> Result<HashMap<String, usize>, String> {
+    let mut arities = HashMap::new();
+    collect_wild_card_arities_recursive(formula_tree, &mut arities)?;
+    Ok(arities)
+}
+
+/// Recursive fn to compute the number of variables of each `wild-card relation` in the formula tree.
+fn collect_wild_card_arities_recursive(
+    formula_tree: &HctlTreeNode,
+    arities: &mut HashMap<String, usize>,
+) -> Result<(), String> {
+    match &formula_tree.node_type {
+        NodeType::Terminal(atom) => {
+            let (name, arity) = match atom {
+                Atomic::WildCardProp(name) => (name, 0),
+                Atomic::WildCardRelation(name, vars) => (name, vars.len()),
+                _ => return Ok(()),
+            };
+            if *arities.entry(name.clone()).or_insert(arity) != arity {
+                return Err(format!(
+                    "Wild-card `{name}` is used with different numbers of variables."
+                ));
+            }
+            Ok(())
+        }
+        NodeType::Unary(_, child) | NodeType::Hybrid(_, _, _, child) => {
+            collect_wild_card_arities_recursive(child, arities)
+        }
+        NodeType::Binary(_, left, right) => {
+            collect_wild_card_arities_recursive(left, arities)?;
+            collect_wild_card_arities_recursive(right, arities)
+        }
+    }
+}
+
 /// Check that extended symbolic graph's BDD supports enough extra variables for the evaluation of
 /// the formula given by a `hctl_syntactic_tree`.
-/// There must be `num_hctl_vars` extra symbolic BDD vars for each BN variable.
-pub fn check_hctl_var_support(stg: &SymbolicAsyncGraph, hctl_syntactic_tree: HctlTreeNode) -> bool {
-    let num_hctl_vars = collect_unique_hctl_vars(hctl_syntactic_tree).len();
+/// There must be `num_hctl_vars` extra symbolic BDD vars for each BN variable. Moreover, the raw
+/// set of a `wild-card relation` encodes its variables using the first extra BDD vars, so there
+/// must be enough of these as well.
+///
+/// Return an error if a `wild-card` is used with different numbers of variables.
+pub fn check_hctl_var_support(
+    stg: &SymbolicAsyncGraph,
+    hctl_syntactic_tree: HctlTreeNode,
+) -> Result<bool, String> {
+    let num_hctl_vars = required_hctl_vars(&[hctl_syntactic_tree])?;
     for bn_var in stg.variables() {
         if num_hctl_vars > stg.symbolic_context().extra_state_variables(bn_var).len() {
-            return false;
+            return Ok(false);
         }
     }
-    true
+    Ok(true)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::evaluation::rename_hctl_var;
     use crate::mc_utils::{
-        check_hctl_var_support, collect_unique_hctl_vars, collect_unique_wild_cards,
-        get_extended_symbolic_graph,
+        apply_input_mode, build_product_network, check_hctl_var_support, collect_builtin_domains,
+        collect_unique_hctl_vars, collect_unique_wild_cards, decompose_regulatory_graph,
+        find_input_variables, find_module_for_propositions, get_extended_symbolic_graph,
+        get_extended_symbolic_graph_with_inputs, get_product_symbolic_graph,
+        get_scheduled_symbolic_graph, hctl_var_bdd_variables, hctl_var_index, hctl_var_name,
+        is_ctl_formula, num_hctl_vars, required_hctl_vars, transfer_hctl_set, with_fixed_inputs,
+        InputMode,
     };
+    use crate::model_checking::{model_check_formula, model_check_tree_dirty};
+    use crate::preprocessing::hctl_tree::HctlTreeNode;
     use crate::preprocessing::parser::{
         parse_and_minimize_hctl_formula, parse_extended_formula, parse_hctl_formula,
     };
     use crate::preprocessing::utils::validate_props_and_rename_vars;
 
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
     use biodivine_lib_param_bn::BooleanNetwork;
+    use std::collections::HashMap;
 
     use biodivine_lib_param_bn::symbolic_async_graph::SymbolicContext;
     use std::collections::HashSet;
@@ -180,6 +1064,53 @@ mod tests {
         let (props, domains) = collect_unique_wild_cards(tree.clone());
         assert_eq!(props, expected_props);
         assert_eq!(domains, expected_domains);
+
+        // the built-in domains are not wild-cards
+        let formula = "3{x} in [a & b]: @{x}: (V{y} in fair_attractors: !{z} in [a & b]: %A%)";
+        let tree = parse_extended_formula(formula).unwrap();
+        let domains = collect_builtin_domains(&tree);
+        assert_eq!(domains.len(), 2);
+        assert_eq!(domains[0].label(), "[(a & b)]");
+        assert_eq!(domains[1].label(), "fair_attractors");
+        assert!(collect_unique_wild_cards(tree).1.is_empty());
+    }
+
+    #[test]
+    /// Test detection of pure CTL formulae (without hybrid operators and HCTL variables).
+    fn test_is_ctl_formula() {
+        let ctl_tree = parse_extended_formula("AG EF (a & %A%) | (a EU ~b)").unwrap();
+        assert!(is_ctl_formula(&ctl_tree));
+        let hctl_tree = parse_hctl_formula("AG (!{x}: AX {x})").unwrap();
+        assert!(!is_ctl_formula(&hctl_tree));
+        let var_tree = parse_extended_formula("3{x}: EF {x}").unwrap();
+        assert!(!is_ctl_formula(&var_tree));
+    }
+
+    #[test]
+    /// Test computing the number of HCTL variables needed by several formulae.
+    fn test_required_hctl_vars() {
+        let ctx = SymbolicContext::new(&BooleanNetwork::try_from_bnet("v1, v1").unwrap()).unwrap();
+        let formulae = [
+            "AG EF v1",
+            "!{x}: AX {x}",
+            "!{x}: 3{y}: (@{x}: ~{y} & AX {x})",
+        ];
+        let trees: Vec<HctlTreeNode> = formulae
+            .iter()
+            .map(|f| parse_and_minimize_hctl_formula(&ctx, f).unwrap())
+            .collect();
+        assert_eq!(required_hctl_vars(&trees[..1]), Ok(0));
+        assert_eq!(required_hctl_vars(&trees[..2]), Ok(1));
+        assert_eq!(required_hctl_vars(&trees), Ok(2));
+        assert_eq!(required_hctl_vars(&[]), Ok(0));
+
+        // wild-card relations need as many variables as their arity
+        let tree = parse_extended_formula("%rel({x}, {xx})%").unwrap();
+        assert_eq!(required_hctl_vars(&[tree]), Ok(2));
+
+        // wild-cards used with different numbers of variables are reported
+        let tree = parse_extended_formula("%rel({x})% & %rel({x}, {xx})%").unwrap();
+        assert!(required_hctl_vars(&[tree]).is_err());
     }
 
     #[test]
@@ -196,10 +1127,245 @@ mod tests {
 
         // the stg that supports the same amount variables as the formula (3)
         let stg = get_extended_symbolic_graph(&bn, 3).unwrap();
-        assert!(check_hctl_var_support(&stg, tree.clone()));
+        assert_eq!(check_hctl_var_support(&stg, tree.clone()), Ok(true));
 
         // the stg that supports less variables than the formula (1 vs 3)
         let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
-        assert!(!check_hctl_var_support(&stg, tree));
+        assert_eq!(check_hctl_var_support(&stg, tree), Ok(false));
+    }
+
+    #[test]
+    /// Test building the product of two models, and checking formulae relating both of them.
+    fn test_product_network() {
+        let wild_type = BooleanNetwork::try_from("a -> b\nb -> a\n$a: b\n$b: a").unwrap();
+        let mutant = BooleanNetwork::try_from("a -> b\n$a: true\n$b: a & p").unwrap();
+        let product = build_product_network(&wild_type, &mutant).unwrap();
+        assert_eq!(product.num_vars(), 4);
+        assert_eq!(product.num_parameters(), 1);
+        assert!(product.as_graph().find_variable("m2__b").is_some());
+
+        // both models can be in a fixed-point where `a` and `b` are active at the same time
+        let graph = get_product_symbolic_graph(&wild_type, &mutant, 1).unwrap();
+        let formula = "!{x}: AX {x} & m1.a & m1.b & m2.a & m2.b";
+        let result = model_check_formula(formula, &graph).unwrap();
+        // only for the color where `p` is true
+        assert_eq!(result.colors().approx_cardinality(), 1.0);
+        assert!(model_check_formula("m3.a", &graph).is_err());
+
+        // names containing the separator would be ambiguous
+        let invalid = BooleanNetwork::try_from("m1__a -> b\n$b: m1__a").unwrap();
+        assert!(build_product_network(&wild_type, &invalid).is_err());
+    }
+
+    #[test]
+    /// Test the different modes of handling the input variables when creating the graph.
+    fn test_input_modes() {
+        let bn = BooleanNetwork::try_from_bnet("targets, factors\na, a\nb, a & !b\n").unwrap();
+        let a = bn.as_graph().find_variable("a").unwrap();
+        assert_eq!(find_input_variables(&bn), vec![a]);
+
+        // parametrized input is a constant given by the color
+        let parametrized = apply_input_mode(&bn, &InputMode::Parametrized).unwrap();
+        assert!(parametrized.get_update_function(a).is_none());
+        let graph =
+            get_extended_symbolic_graph_with_inputs(&bn, 1, &InputMode::Parametrized).unwrap();
+        assert_eq!(graph.mk_unit_colors().approx_cardinality(), 2.0);
+        let result = model_check_formula("AG a | AG ~a", &graph).unwrap();
+        assert_eq!(result.approx_cardinality(), 4.0);
+
+        // free input can always change its value
+        let graph = get_extended_symbolic_graph_with_inputs(&bn, 1, &InputMode::Free).unwrap();
+        let result = model_check_formula("AG (EF a & EF ~a)", &graph).unwrap();
+        assert_eq!(result.approx_cardinality(), 4.0);
+
+        // fixed input only allows states with the given value
+        let values = HashMap::from([("a".to_string(), true)]);
+        let graph =
+            get_extended_symbolic_graph_with_inputs(&bn, 1, &InputMode::Fixed(values)).unwrap();
+        let result = model_check_formula("AG a", &graph).unwrap();
+        assert_eq!(result.approx_cardinality(), 2.0);
+        assert_eq!(
+            model_check_formula("true", &graph)
+                .unwrap()
+                .approx_cardinality(),
+            2.0
+        );
+
+        let values = HashMap::from([("b".to_string(), true)]);
+        assert!(apply_input_mode(&bn, &InputMode::Fixed(values)).is_err());
+    }
+
+    #[test]
+    /// Test fixing the values of inputs and parameters in the graph.
+    fn test_fixed_inputs() {
+        // `a` is a parametrized input and `p` is a constant parameter
+        let bn = BooleanNetwork::try_from("a -> b\nb -> c\n$b: a & p\n$c: b").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        assert_eq!(graph.mk_unit_colors().approx_cardinality(), 4.0);
+
+        let values = HashMap::from([("a".to_string(), true)]);
+        let fixed_graph = with_fixed_inputs(&graph, &values).unwrap();
+        assert_eq!(fixed_graph.mk_unit_colors().approx_cardinality(), 2.0);
+        assert_eq!(fixed_graph.mk_unit_vertices().approx_cardinality(), 4.0);
+
+        // with `p` inactive, `b` is eventually inactive forever
+        let values = HashMap::from([("a".to_string(), true), ("p".to_string(), false)]);
+        let fixed_graph = with_fixed_inputs(&graph, &values).unwrap();
+        assert_eq!(fixed_graph.mk_unit_colors().approx_cardinality(), 1.0);
+        let result = model_check_formula("AF AG ~b", &fixed_graph).unwrap();
+        assert_eq!(result.approx_cardinality(), 4.0);
+
+        let values = HashMap::from([("x".to_string(), true)]);
+        assert!(with_fixed_inputs(&graph, &values).is_err());
+        // `b` is regulated by `a`, so it is not an input
+        let values = HashMap::from([("b".to_string(), true)]);
+        assert!(with_fixed_inputs(&graph, &values).is_err());
+    }
+
+    #[test]
+    /// Test fixing inputs with explicit update functions that disagree with the fixed value.
+    fn test_fixed_explicit_inputs() {
+        let bn = BooleanNetwork::try_from("a -> b\n$a: true\n$b: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let values = HashMap::from([("a".to_string(), false)]);
+        let fixed_graph = with_fixed_inputs(&graph, &values).unwrap();
+        let unit = fixed_graph.mk_unit_colored_vertices();
+        assert_eq!(unit.vertices().approx_cardinality(), 2.0);
+
+        // `a` can not be reached from the restricted unit, and all the transitions stay in it
+        let reached = model_check_formula("~a & EF a", &fixed_graph).unwrap();
+        assert!(reached.is_empty());
+        let attractors = model_check_formula("!{x}: AG EF {x}", &fixed_graph).unwrap();
+        let steady_states = model_check_formula("!{x}: AX {x}", &fixed_graph).unwrap();
+        assert_eq!(attractors, steady_states);
+        assert_eq!(attractors.vertices().approx_cardinality(), 1.0);
+
+        // the same holds for an input given by a parameter
+        let bn = BooleanNetwork::try_from("a -> b\n$a: p\n$b: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let fixed_graph = with_fixed_inputs(&graph, &values).unwrap();
+        let reached = model_check_formula("~a & EF a", &fixed_graph).unwrap();
+        assert!(reached.is_empty());
+    }
+
+    #[test]
+    /// Test checking formulae scoped to the phases of a piecewise-constant input schedule.
+    fn test_input_schedule() {
+        // `Start` activates `b` (which then stays active), `c` needs `b` without `Start`
+        let bn = BooleanNetwork::try_from(
+            "Start -> b\nb -> b\nStart -| c\nb -> c\n$b: Start | b\n$c: !Start & b",
+        )
+        .unwrap();
+        let schedule = vec![
+            HashMap::from([("Start".to_string(), true)]),
+            HashMap::from([("Start".to_string(), false)]),
+        ];
+        let graph = get_scheduled_symbolic_graph(&bn, &schedule, 1).unwrap();
+        assert_eq!(graph.mk_unit_colors().approx_cardinality(), 1.0);
+        let initial = model_check_formula("~phase_2 & ~b & ~c", &graph).unwrap();
+
+        // `c` can only be activated in the second phase, after `b` was activated in the first one
+        let formula = "~phase_2 & ~b & ~c & EF (phase_2 & c)";
+        assert_eq!(model_check_formula(formula, &graph).unwrap(), initial);
+        let formula = "~phase_2 & ~b & ~c & AG (~phase_2 => ~c)";
+        assert_eq!(model_check_formula(formula, &graph).unwrap(), initial);
+        let formula = "~phase_2 & ~b & ~c & AX (phase_2 => AG ~b)";
+        assert_eq!(model_check_formula(formula, &graph).unwrap(), initial);
+
+        // without the first phase, `b` is never activated
+        let graph = get_scheduled_symbolic_graph(&bn, &schedule[1..], 1).unwrap();
+        assert!(model_check_formula("~b & EF b", &graph).unwrap().is_empty());
+
+        // only consistent encodings of three phases are considered
+        let mut schedule = schedule.clone();
+        schedule.push(HashMap::from([("Start".to_string(), true)]));
+        let graph = get_scheduled_symbolic_graph(&bn, &schedule, 1).unwrap();
+        assert_eq!(graph.mk_unit_vertices().approx_cardinality(), 24.0);
+        let result = model_check_formula("~phase_2 & EF phase_3", &graph).unwrap();
+        assert_eq!(result.approx_cardinality(), 8.0);
+
+        let invalid_schedules = vec![
+            vec![],
+            vec![HashMap::from([("b".to_string(), true)])],
+            vec![
+                HashMap::from([("Start".to_string(), true)]),
+                HashMap::from([("c".to_string(), false)]),
+            ],
+        ];
+        for invalid in invalid_schedules {
+            assert!(get_scheduled_symbolic_graph(&bn, &invalid, 1).is_err());
+        }
+    }
+
+    #[test]
+    /// Test the decomposition of the regulatory graph into modules.
+    fn test_regulatory_modules() {
+        // `a` and `b` form a cycle regulated by `i`, `c` is downstream, `d` is isolated
+        let bn =
+            BooleanNetwork::try_from("i -> a\na -> b\nb -| a\nb -> c\nc -> c\nd -> d").unwrap();
+        let graph = bn.as_graph();
+        let var = |name: &str| graph.find_variable(name).unwrap();
+        let modules = decompose_regulatory_graph(graph);
+        assert_eq!(modules.len(), 4);
+        let cycle = modules
+            .iter()
+            .find(|module| module.component.contains(&var("a")))
+            .unwrap();
+        assert_eq!(cycle.component, HashSet::from([var("a"), var("b")]));
+        assert_eq!(
+            cycle.variables,
+            HashSet::from([var("i"), var("a"), var("b")])
+        );
+
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        let module = find_module_for_propositions(graph, &names(&["i", "b"])).unwrap();
+        assert_eq!(module, *cycle);
+        let module = find_module_for_propositions(graph, &names(&["c"])).unwrap();
+        assert_eq!(module.variables.len(), 4);
+        assert!(find_module_for_propositions(graph, &names(&["c", "d"])).is_none());
+        assert!(find_module_for_propositions(graph, &names(&["x"])).is_none());
+    }
+
+    #[test]
+    /// Test the mapping between HCTL variables and their BDD variables, and renaming and
+    /// transferring "dirty" sets with free HCTL variables.
+    fn test_hctl_var_mapping() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -> a\n$a: b\n$b: a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 2).unwrap();
+        assert_eq!(num_hctl_vars(&graph), 2);
+        assert_eq!(hctl_var_name(1), "xx");
+        assert_eq!(hctl_var_index("xx"), Some(1));
+        assert_eq!(hctl_var_index("y"), None);
+
+        let variables = graph.symbolic_context().bdd_variable_set();
+        let names: Vec<String> = hctl_var_bdd_variables(&graph, 1)
+            .unwrap()
+            .into_iter()
+            .map(|var| variables.name_of(var))
+            .collect();
+        assert_eq!(names, vec!["a_extra_1", "b_extra_1"]);
+        assert!(hctl_var_bdd_variables(&graph, 2).is_err());
+
+        // relation between states and the fixed points reachable from them
+        let tree = parse_hctl_formula("EF ({x} & AX {x})").unwrap();
+        let relation = model_check_tree_dirty(tree, &graph).unwrap();
+        let renamed = rename_hctl_var(&graph, &relation, 0, 1).unwrap();
+        let tree = parse_hctl_formula("EF ({xx} & AX {xx})").unwrap();
+        assert_eq!(renamed, model_check_tree_dirty(tree, &graph).unwrap());
+        assert_eq!(rename_hctl_var(&graph, &renamed, 1, 0).unwrap(), relation);
+        let both = renamed.intersect(&relation);
+        assert!(rename_hctl_var(&graph, &both, 0, 1).is_err());
+        assert!(rename_hctl_var(&graph, &relation, 0, 2).is_err());
+
+        // sets can be transferred to graphs with other numbers of HCTL variables
+        let larger_graph = get_extended_symbolic_graph(&bn, 3).unwrap();
+        let transferred = transfer_hctl_set(&graph, &renamed, &larger_graph).unwrap();
+        assert_eq!(
+            transfer_hctl_set(&larger_graph, &transferred, &graph).unwrap(),
+            renamed
+        );
+        let smaller_graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        assert!(transfer_hctl_set(&graph, &relation, &smaller_graph).is_ok());
+        assert!(transfer_hctl_set(&graph, &renamed, &smaller_graph).is_err());
     }
 }
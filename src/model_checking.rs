@@ -3,21 +3,36 @@
 //!  - variants for both single or multiple formulae
 //!  - variants for formulae given by a string or a syntactic tree
 //!  - `dirty` variants that do not sanitize the resulting BDDs (and thus, the BDDs retain additional symbolic vars)
+//!  - `dual` variants returning both the dirty and the sanitized results
 //!  - variants allowing `extended` HCTL with special propositions referencing raw sets
 //!  - variants using potentially unsafe optimizations, targeted for specific use cases
+//!  - three-valued (may/must) variant computing under- and over-approximations of the result
+//!  - variant returning the syntactic tree annotated with the results of the sub-formulae
+//!  - variant invoking a debugger around the evaluation of each sub-formula
+//!  - variant restricting the graph to a given set (e.g., a result of a previous formula)
+//!  - `streamed` variants delivering each result through a callback as soon as it is computed
 
 use crate::evaluation::algorithm::{compute_steady_states, eval_node};
+use crate::evaluation::annotation::AnnotatedNode;
+use crate::evaluation::approximation::{ApproxContext, Approximation};
+use crate::evaluation::debugger::EvalDebugger;
 use crate::evaluation::eval_context::EvalContext;
 use crate::mc_utils::*;
-use crate::postprocessing::sanitizing::sanitize_colored_vertices;
+use crate::postprocessing::sanitizing::{
+    extend_colored_vertices, extend_to_graph, sanitize_colored_vertices, DualResult,
+};
 use crate::preprocessing::hctl_tree::HctlTreeNode;
 use crate::preprocessing::parser::{
     parse_and_minimize_extended_formula, parse_and_minimize_hctl_formula,
 };
+use crate::preprocessing::rewriting::{optimize_formula, push_quantifiers_inward};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
-use crate::evaluation::LabelToSetMap;
+use crate::evaluation::{EvalOptions, LabelToSetMap, OperatorAlgorithms};
 use crate::preprocessing::utils::validate_and_divide_wild_cards;
+use biodivine_lib_bdd::Bdd;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
 
 /// Perform the model checking for the list of HCTL formulae given by their syntax trees on a given transition `graph`.
@@ -30,24 +45,91 @@ pub fn model_check_multiple_trees_dirty(
     formula_trees: Vec<HctlTreeNode>,
     graph: &SymbolicAsyncGraph,
 ) -> Result<Vec<GraphColoredVertices>, String> {
-    // find duplicate sub-formulae throughout all formulae + initiate caching structures
-    let mut eval_info = EvalContext::from_multiple_trees(&formula_trees);
     // pre-compute states with self-loops which will be needed during eval
     let self_loop_states = compute_steady_states(graph);
+    model_check_multiple_trees_with_steady_states_dirty(formula_trees, graph, &self_loop_states)
+}
 
-    // evaluate the formulae (perform the actual model checking) and collect results
+/// Perform the model checking for the list of HCTL formulae given by their syntax trees on a given
+/// transition `graph`, using the pre-computed `steady_states` (see [compute_steady_states]) instead
+/// of computing them again. Do not sanitize the results.
+///
+/// The steady states can be sanitized, or in the symbolic context of the `graph`. They must come
+/// from the same model (e.g., computed once on a graph without additional symbolic variables, and
+/// then reused for all graphs extended with HCTL variables).
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_trees_with_steady_states_dirty(
+    formula_trees: Vec<HctlTreeNode>,
+    graph: &SymbolicAsyncGraph,
+    steady_states: &GraphColoredVertices,
+) -> Result<Vec<GraphColoredVertices>, String> {
     let mut results: Vec<GraphColoredVertices> = Vec::new();
-    for parse_tree in formula_trees {
-        results.push(eval_node(
-            parse_tree,
-            graph,
-            &mut eval_info,
-            &self_loop_states,
-        ));
-    }
+    model_check_trees_streamed_dirty(
+        formula_trees,
+        graph,
+        steady_states,
+        OperatorAlgorithms::default(),
+        &mut |_, result| results.push(result),
+    )?;
     Ok(results)
 }
 
+/// Internal version of [model_check_multiple_trees_with_steady_states_dirty], which delivers each
+/// result to the `on_result` callback (together with the index of its formula) as soon as it is
+/// computed. The results are delivered in the order of the formulae. The temporal operators are
+/// evaluated by the given `algorithms`.
+fn model_check_trees_streamed_dirty(
+    formula_trees: Vec<HctlTreeNode>,
+    graph: &SymbolicAsyncGraph,
+    steady_states: &GraphColoredVertices,
+    algorithms: OperatorAlgorithms,
+    on_result: &mut dyn FnMut(usize, GraphColoredVertices),
+) -> Result<(), String> {
+    let self_loop_states = extend_colored_vertices(graph, steady_states)
+        .ok_or("Steady states are not compatible with the graph.".to_string())?;
+    // push the quantifiers inward to reduce the scopes of HCTL variables
+    let formula_trees: Vec<HctlTreeNode> = formula_trees
+        .into_iter()
+        .map(push_quantifiers_inward)
+        .collect();
+    // find duplicate sub-formulae throughout all formulae + initiate caching structures
+    let mut eval_info = EvalContext::from_multiple_trees(&formula_trees);
+    eval_info.algorithms = algorithms;
+
+    // evaluate the formulae (perform the actual model checking) and pass on the results
+    for (i, parse_tree) in formula_trees.into_iter().enumerate() {
+        on_result(
+            i,
+            eval_node(parse_tree, graph, &mut eval_info, &self_loop_states),
+        );
+    }
+    Ok(())
+}
+
+/// Perform the model checking for the list of HCTL formulae given by their syntax trees on a given
+/// transition `graph`, same as [model_check_multiple_trees], but instead of returning all results
+/// at the end, deliver each (sanitized) result to the `on_result` callback as soon as it is
+/// computed, together with the index of its formula. The callback can, e.g., forward the results
+/// to a channel.
+///
+/// The formulae are evaluated in the given order, so the results of the cheap formulae placed
+/// before an expensive one are available without waiting for it.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_trees_streamed<F: FnMut(usize, GraphColoredVertices)>(
+    formula_trees: Vec<HctlTreeNode>,
+    graph: &SymbolicAsyncGraph,
+    mut on_result: F,
+) -> Result<(), String> {
+    let self_loop_states = compute_steady_states(graph);
+    model_check_trees_streamed_dirty(
+        formula_trees,
+        graph,
+        &self_loop_states,
+        OperatorAlgorithms::default(),
+        &mut |i, result| on_result(i, sanitize_colored_vertices(graph, &result)),
+    )
+}
+
 /// Perform the model checking for a formula given by its syntactic tree, but do not sanitize the results.
 /// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
 pub fn model_check_tree_dirty(
@@ -76,6 +158,21 @@ pub fn model_check_multiple_trees(
     Ok(sanitized_results)
 }
 
+/// Perform the model checking for the list of HCTL formulae given by their syntax trees, and
+/// return both the dirty and the sanitized results (see [DualResult]). The trees may contain free
+/// variables (whose symbolic variables are kept in the dirty results).
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_trees_dual(
+    formula_trees: Vec<HctlTreeNode>,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Vec<DualResult>, String> {
+    let results = model_check_multiple_trees_dirty(formula_trees, graph)?;
+    Ok(results
+        .into_iter()
+        .map(|result| DualResult::new(graph, result))
+        .collect())
+}
+
 /// Perform the model checking for the list of HCTL formula given by its syntactic tree on a given transition `graph`.
 /// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
 /// Return the resulting set of colored vertices.
@@ -98,7 +195,7 @@ fn parse_and_validate(
     for formula in formulae {
         let tree = parse_and_minimize_hctl_formula(graph.symbolic_context(), formula)?;
         // check that given extended symbolic graph supports enough stated variables
-        if !check_hctl_var_support(graph, tree.clone()) {
+        if !check_hctl_var_support(graph, tree.clone())? {
             return Err("Graph does not support enough HCTL state variables".to_string());
         }
         parsed_trees.push(tree);
@@ -119,6 +216,70 @@ pub fn model_check_multiple_formulae(
     model_check_multiple_trees(parsed_trees, graph)
 }
 
+/// Perform the model checking for the list of HCTL formulae on a given transition `graph`, and
+/// deliver each (sanitized) result to the `on_result` callback as soon as it is computed (see
+/// [model_check_multiple_trees_streamed]).
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_formulae_streamed<F: FnMut(usize, GraphColoredVertices)>(
+    formulae: Vec<&str>,
+    graph: &SymbolicAsyncGraph,
+    on_result: F,
+) -> Result<(), String> {
+    let parsed_trees = parse_and_validate(formulae, graph)?;
+    model_check_multiple_trees_streamed(parsed_trees, graph, on_result)
+}
+
+/// Perform the model checking for the list of HCTL formulae on a given transition `graph`, using
+/// the pre-computed `steady_states` (see [model_check_multiple_trees_with_steady_states_dirty]).
+/// Return the resulting sets of colored vertices (in the same order as input formulae).
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_formulae_with_steady_states(
+    formulae: Vec<&str>,
+    graph: &SymbolicAsyncGraph,
+    steady_states: &GraphColoredVertices,
+) -> Result<Vec<GraphColoredVertices>, String> {
+    let parsed_trees = parse_and_validate(formulae, graph)?;
+    let results =
+        model_check_multiple_trees_with_steady_states_dirty(parsed_trees, graph, steady_states)?;
+    Ok(results
+        .iter()
+        .map(|x| sanitize_colored_vertices(graph, x))
+        .collect())
+}
+
+/// Perform the model checking for the list of HCTL formulae on a given transition `graph`, with
+/// the given evaluation `options` (e.g., with the formulae optimized by [optimize_formula], with
+/// some variables frozen by [with_frozen_variables], or with the frontier-based reachability).
+/// Return the resulting sets of colored vertices (in the same order as input formulae).
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_formulae_with_options(
+    formulae: Vec<&str>,
+    graph: &SymbolicAsyncGraph,
+    options: &EvalOptions,
+) -> Result<Vec<GraphColoredVertices>, String> {
+    let mut parsed_trees = parse_and_validate(formulae, graph)?;
+    if options.optimize {
+        parsed_trees = parsed_trees.into_iter().map(optimize_formula).collect();
+    }
+    let frozen_graph;
+    let graph = if options.frozen_variables.is_empty() {
+        graph
+    } else {
+        frozen_graph = with_frozen_variables(graph, &options.frozen_variables)?;
+        &frozen_graph
+    };
+    let self_loop_states = compute_steady_states(graph);
+    let mut results = Vec::new();
+    model_check_trees_streamed_dirty(
+        parsed_trees,
+        graph,
+        &self_loop_states,
+        options.algorithms,
+        &mut |_, result| results.push(sanitize_colored_vertices(graph, &result)),
+    )?;
+    Ok(results)
+}
+
 /// Perform the model checking for the list of formulae, but do not sanitize the results.
 /// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
 pub fn model_check_multiple_formulae_dirty(
@@ -131,6 +292,67 @@ pub fn model_check_multiple_formulae_dirty(
     model_check_multiple_trees_dirty(parsed_trees, graph)
 }
 
+/// Perform the model checking for the list of formulae, and return both the dirty and the
+/// sanitized results (see [DualResult]), so that follow-up computations can use the symbolic
+/// variables of the HCTL variables without evaluating the formulae again.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_formulae_dual(
+    formulae: Vec<&str>,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Vec<DualResult>, String> {
+    let parsed_trees = parse_and_validate(formulae, graph)?;
+    model_check_multiple_trees_dual(parsed_trees, graph)
+}
+
+/// Perform the model checking for a given formula, and return both the dirty and the sanitized
+/// result (see [DualResult]).
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_formula_dual(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<DualResult, String> {
+    Ok(model_check_multiple_formulae_dual(vec![formula], graph)?.remove(0))
+}
+
+/// Perform the model checking for the list of HCTL formulae on a given transition `graph`, with
+/// the cache pre-populated by the `known_results` of formulae evaluated previously (see
+/// [EvalContext::extend_context_with_results]). Sub-formulae with known results are not recomputed.
+///
+/// The known results can be sanitized (as returned by other model-checking functions), or in the
+/// symbolic context of the `graph`. They must come from the same model.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_multiple_formulae_with_cache(
+    formulae: Vec<&str>,
+    graph: &SymbolicAsyncGraph,
+    known_results: &[(String, GraphColoredVertices)],
+) -> Result<Vec<GraphColoredVertices>, String> {
+    // known formulae are processed the same way as the evaluated ones (but they do not need
+    // to be supported by the graph, as they are never evaluated)
+    let mut known_trees = Vec::new();
+    for (formula, result) in known_results {
+        let tree = parse_and_minimize_hctl_formula(graph.symbolic_context(), formula)?;
+        let result = extend_colored_vertices(graph, result).ok_or(format!(
+            "Result of `{formula}` is not compatible with the graph."
+        ))?;
+        known_trees.push((push_quantifiers_inward(tree), result));
+    }
+
+    let formula_trees: Vec<HctlTreeNode> = parse_and_validate(formulae, graph)?
+        .into_iter()
+        .map(push_quantifiers_inward)
+        .collect();
+    let mut eval_info = EvalContext::from_multiple_trees(&formula_trees);
+    eval_info.extend_context_with_results(&known_trees);
+    let self_loop_states = compute_steady_states(graph);
+
+    let mut results = Vec::new();
+    for parse_tree in formula_trees {
+        let result = eval_node(parse_tree, graph, &mut eval_info, &self_loop_states);
+        results.push(sanitize_colored_vertices(graph, &result));
+    }
+    Ok(results)
+}
+
 /// Perform the model checking for a given HCTL formula on a given transition `graph`.
 /// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
 /// Return the resulting set of colored vertices.
@@ -142,6 +364,113 @@ pub fn model_check_formula(
     Ok(result[0].clone())
 }
 
+/// Create a copy of the `graph` restricted to the states and colors of the `restriction` set
+/// (sanitized, e.g., a result of a previous model checking, or in the context of the `graph`).
+/// The restricted graph is the sub-graph induced by the set, i.e., the transitions leaving the set
+/// are removed (and the states with no transitions left become steady states). It is symbolically
+/// compatible with the original graph.
+///
+/// This allows to chain analyses, e.g., to analyse the dynamics inside the result of a previous
+/// formula.
+pub fn with_restricted_unit(
+    graph: &SymbolicAsyncGraph,
+    restriction: &GraphColoredVertices,
+) -> Result<SymbolicAsyncGraph, String> {
+    let restriction = extend_to_graph(graph, restriction)?;
+    let unit = graph
+        .unit_colored_vertices()
+        .intersect(&restriction)
+        .into_bdd();
+    let context = graph.symbolic_context();
+    let update_functions = graph
+        .variables()
+        .map(|var| {
+            // the variable can only change if both the state and its successor are in the unit
+            let state_var = context.get_state_variable(var);
+            let literal = context.mk_state_variable_is_true(var);
+            let flipped_unit = literal
+                .and(&unit.var_restrict(state_var, false))
+                .or(&literal.not().and(&unit.var_restrict(state_var, true)));
+            let stays_in_unit = unit.and(&flipped_unit);
+            Bdd::if_then_else(&stays_in_unit, graph.get_symbolic_fn_update(var), &literal)
+        })
+        .collect();
+    // SAFETY: The functions only differ from the valid functions of the graph by removing the
+    // transitions leaving the restricted unit set, so the unit set is closed under the transitions.
+    unsafe {
+        Ok(SymbolicAsyncGraph::new_raw(
+            graph.as_network().cloned(),
+            context.clone(),
+            unit,
+            update_functions,
+        ))
+    }
+}
+
+/// Perform the model checking for a given HCTL formula on the `graph` restricted to the states
+/// and colors of the `restriction` set (see [with_restricted_unit]), i.e., on the sub-graph
+/// induced by the set. The `restriction` can be sanitized (e.g., a result of a previous model
+/// checking), or in the symbolic context of the `graph`.
+///
+/// The HCTL variables only range over the states of the restriction, and the (sanitized) result
+/// is a subset of it.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_under_restriction(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+    restriction: &GraphColoredVertices,
+) -> Result<GraphColoredVertices, String> {
+    let restricted_graph = with_restricted_unit(graph, restriction)?;
+    let result = model_check_formula(formula, &restricted_graph)?;
+    // the propositions are not restricted to the unit set, so the result might exceed it
+    let unit =
+        sanitize_colored_vertices(&restricted_graph, restricted_graph.unit_colored_vertices());
+    Ok(result.intersect(&unit))
+}
+
+/// Perform the model checking for a given HCTL formula on a given transition `graph`, and return
+/// the (sanitized) result together with the syntactic tree annotated at each node with the size of
+/// its result and the time of its evaluation (see [crate::evaluation::annotation]). The full
+/// results of the sub-formulae are only kept in the tree if `keep_results` is set.
+///
+/// The tree is the one actually evaluated, i.e., after the preprocessing of the formula.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_formula_annotated(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+    keep_results: bool,
+) -> Result<(GraphColoredVertices, AnnotatedNode), String> {
+    let tree = push_quantifiers_inward(parse_and_validate(vec![formula], graph)?.remove(0));
+    let mut eval_info = EvalContext::from_single_tree(&tree);
+    eval_info.enable_annotation(keep_results);
+    let self_loop_states = compute_steady_states(graph);
+    let result = eval_node(tree, graph, &mut eval_info, &self_loop_states);
+    let annotated_tree = eval_info.take_annotated_trees().remove(0);
+    Ok((sanitize_colored_vertices(graph, &result), annotated_tree))
+}
+
+/// Perform the model checking for a given HCTL formula on a given transition `graph`, invoking
+/// the shared `debugger` before and after the evaluation of each sub-formula (see
+/// [crate::evaluation::debugger]). Return the (sanitized) result, or an error if the debugger
+/// stopped the evaluation.
+///
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_formula_debugged(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+    debugger: Arc<Mutex<dyn EvalDebugger + Send>>,
+) -> Result<GraphColoredVertices, String> {
+    let tree = push_quantifiers_inward(parse_and_validate(vec![formula], graph)?.remove(0));
+    let mut eval_info = EvalContext::from_single_tree(&tree);
+    eval_info.attach_debugger(debugger);
+    let self_loop_states = compute_steady_states(graph);
+    let result = eval_node(tree, graph, &mut eval_info, &self_loop_states);
+    if eval_info.is_stopped() {
+        return Err("Evaluation was stopped by the debugger.".to_string());
+    }
+    Ok(sanitize_colored_vertices(graph, &result))
+}
+
 /// Perform the model checking for given formula, but do not sanitize the result.
 /// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
 pub fn model_check_formula_dirty(
@@ -169,7 +498,7 @@ fn parse_and_validate_extended(
         let tree = parse_and_minimize_extended_formula(graph.symbolic_context(), formula)?;
 
         // check that given extended symbolic graph supports enough stated variables
-        if !check_hctl_var_support(graph, tree.clone()) {
+        if !check_hctl_var_support(graph, tree.clone())? {
             return Err("Graph does not support enough HCTL state variables".to_string());
         }
 
@@ -190,6 +519,7 @@ fn parse_and_validate_extended(
 ///
 /// The `context_props` is a mapping determining how `wild-card propositions` and `variable domains` are evaluated.
 /// These BDDs must only depend on BN variables and parameters, not on any other symbolic variables.
+/// The only exception are `wild-card relations` (see [crate::evaluation::encode_relation_variable]).
 pub fn model_check_multiple_extended_formulae_dirty(
     formulae: Vec<&str>,
     stg: &SymbolicAsyncGraph,
@@ -198,6 +528,11 @@ pub fn model_check_multiple_extended_formulae_dirty(
     // get the abstract syntactic trees and divide context sets, plus check compatibility with graph
     let (parsed_trees, context_props, context_domains) =
         parse_and_validate_extended(formulae, stg, context_sets)?;
+    // push the quantifiers inward to reduce the scopes of HCTL variables
+    let parsed_trees: Vec<HctlTreeNode> = parsed_trees
+        .into_iter()
+        .map(push_quantifiers_inward)
+        .collect();
 
     // prepare the extended evaluation context
 
@@ -218,6 +553,9 @@ pub fn model_check_multiple_extended_formulae_dirty(
             &self_loop_states,
         ));
     }
+    if let Some(error) = eval_info.take_error() {
+        return Err(error);
+    }
     Ok(results)
 }
 
@@ -227,6 +565,7 @@ pub fn model_check_multiple_extended_formulae_dirty(
 ///
 /// The `context_props` is a mapping determining how `wild-card propositions` and `variable domains` are evaluated.
 /// These BDDs must only depend on BN variables and parameters, not on any other symbolic variables.
+/// The only exception are `wild-card relations` (see [crate::evaluation::encode_relation_variable]).
 pub fn model_check_multiple_extended_formulae(
     formulae: Vec<&str>,
     stg: &SymbolicAsyncGraph,
@@ -247,6 +586,7 @@ pub fn model_check_multiple_extended_formulae(
 ///
 /// The `context_props` is a mapping determining how `wild-card propositions` and `variable domains` are evaluated.
 /// These BDDs must only depend on BN variables and parameters, not on any other symbolic variables.
+/// The only exception are `wild-card relations` (see [crate::evaluation::encode_relation_variable]).
 pub fn model_check_extended_formula(
     formula: &str,
     stg: &SymbolicAsyncGraph,
@@ -262,6 +602,7 @@ pub fn model_check_extended_formula(
 ///
 /// The `context_props` is a mapping determining how `wild-card propositions` and `variable domains` are evaluated.
 /// These BDDs must only depend on BN variables and parameters, not on any other symbolic variables.
+/// The only exception are `wild-card relations` (see [crate::evaluation::encode_relation_variable]).
 pub fn model_check_extended_formula_dirty(
     formula: &str,
     stg: &SymbolicAsyncGraph,
@@ -295,15 +636,49 @@ pub fn model_check_formula_unsafe_ex(
     Ok(result)
 }
 
+/// Perform the three-valued (may/must) model checking for a given HCTL formula on a given
+/// transition `graph`. Instead of the exact result, return a pair of color-independent sets
+/// `(under, over)`: the formula holds for all colors in the states of `under`, it is violated for
+/// all colors in the states outside `over`, and the result is unknown in the remaining states.
+///
+/// This is typically much cheaper than the exact model checking on heavily parametrized networks.
+/// Hybrid operators with restricted domains are not supported.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn model_check_formula_approx(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Approximation, String> {
+    let tree = parse_and_validate(vec![formula], graph)?[0].clone();
+    let (under, over) = ApproxContext::new(graph).eval_node(tree)?;
+    Ok((
+        sanitize_colored_vertices(graph, &under),
+        sanitize_colored_vertices(graph, &over),
+    ))
+}
+
 #[cfg(test)]
 /// Some basic tests for the model-checking procedure and corresponding utilities. Note that larger tests
 /// involving complex models and formulae are in module [crate::_test_model_checking].
 mod tests {
 
+    use crate::evaluation::algorithm::compute_steady_states;
+    use crate::evaluation::{EvalOptions, OperatorAlgorithms, ReachabilityAlgorithm};
     use crate::mc_utils::get_extended_symbolic_graph;
-    use crate::model_checking::{model_check_formula, parse_and_validate_extended};
+    use crate::model_checking::{
+        model_check_formula, model_check_formula_approx, model_check_formula_dirty,
+        model_check_formula_dual, model_check_multiple_formulae,
+        model_check_multiple_formulae_dual, model_check_multiple_formulae_streamed,
+        model_check_multiple_formulae_with_cache, model_check_multiple_formulae_with_options,
+        model_check_multiple_formulae_with_steady_states, model_check_multiple_trees_dual,
+        model_check_tree_dirty, model_check_under_restriction, parse_and_validate_extended,
+    };
+    use crate::postprocessing::sanitizing::{extend_colored_vertices, sanitize_colored_vertices};
+    use crate::preprocessing::parser::parse_hctl_formula;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
     use biodivine_lib_param_bn::BooleanNetwork;
     use std::collections::HashMap;
+    use std::sync::mpsc;
 
     #[test]
     /// Test that function errors correctly if graph object does not support enough state variables.
@@ -382,4 +757,308 @@ mod tests {
             "Var domain `b` lacks evaluation context.".to_string()
         );
     }
+
+    #[test]
+    /// Test that the three-valued model checking gives sound approximations of the exact result
+    /// on a parametrized network, and exact results on a fully specified network.
+    fn model_check_approx() {
+        let formulae = vec![
+            "a",
+            "EX a",
+            "AX ~a",
+            "EF (a & b)",
+            "AG EF b",
+            "!{x}: AX {x}",
+            "!{x}: AG EF {x}",
+            "a EU ~b",
+            "b AW a",
+            "EP (a & ~b)",
+            "3{x}: @{x}: (a => AX a)",
+            "V{x}: @{x}: (a ^ b)",
+        ];
+
+        let bn_parametrized =
+            BooleanNetwork::try_from("a -?? b\nb -?? a\nb -| b\n$a: f(b)\n$b: !b | a").unwrap();
+        let stg = get_extended_symbolic_graph(&bn_parametrized, 1).unwrap();
+        let unit = sanitize_colored_vertices(&stg, &stg.mk_unit_colored_vertices());
+        for formula in &formulae {
+            let exact = model_check_formula(formula, &stg).unwrap();
+            let (under, over) = model_check_formula_approx(formula, &stg).unwrap();
+            // under-approximation holds for all colors, over-approximation covers all colors
+            let violated = unit.minus(&exact).vertices();
+            assert!(under.vertices().intersect(&violated).is_empty());
+            assert!(exact.vertices().is_subset(&over.vertices()));
+            assert!(under.is_subset(&over));
+        }
+
+        let bn_specified =
+            BooleanNetwork::try_from("a -> b\nb -> a\nb -| b\n$a: b\n$b: !b | a").unwrap();
+        let stg = get_extended_symbolic_graph(&bn_specified, 1).unwrap();
+        for formula in &formulae {
+            let exact = model_check_formula(formula, &stg).unwrap();
+            let (under, over) = model_check_formula_approx(formula, &stg).unwrap();
+            assert_eq!(under.as_bdd(), exact.as_bdd());
+            assert_eq!(over.as_bdd(), exact.as_bdd());
+        }
+
+        // restricted domains are not supported
+        assert!(model_check_formula_approx("!{x} in %s%: AX {x}", &stg).is_err());
+    }
+
+    #[test]
+    /// Test the model checking with the cache pre-populated by known results.
+    fn model_check_with_cache() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -| b\n$a: !b\n$b: a & !b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let attractors = model_check_formula("!{x}: AG EF {x}", &stg).unwrap();
+        let formula = "EF (!{y}: AG EF {y}) & ~(!{x}: AG EF {x})";
+        let expected = model_check_formula(formula, &stg).unwrap();
+
+        // sanitized results (and the results in the graph context) can be used
+        let known = vec![("!{z}: AG EF {z}".to_string(), attractors.clone())];
+        let results = model_check_multiple_formulae_with_cache(vec![formula], &stg, &known);
+        assert_eq!(results.unwrap()[0], expected);
+        let extended = extend_colored_vertices(&stg, &attractors).unwrap();
+        let known = vec![("!{z}: AG EF {z}".to_string(), extended)];
+        let results = model_check_multiple_formulae_with_cache(vec![formula], &stg, &known);
+        assert_eq!(results.unwrap()[0], expected);
+
+        // the known result is really used instead of recomputing it
+        let known = vec![(
+            "!{x}: AG EF {x}".to_string(),
+            stg.mk_empty_colored_vertices(),
+        )];
+        let results = model_check_multiple_formulae_with_cache(vec![formula], &stg, &known);
+        assert!(results.unwrap()[0].is_empty());
+
+        // known results must be compatible with the graph
+        let other_bn = BooleanNetwork::try_from("a -> a").unwrap();
+        let other_stg = get_extended_symbolic_graph(&other_bn, 1).unwrap();
+        let known = vec![("a".to_string(), other_stg.mk_unit_colored_vertices())];
+        assert!(model_check_multiple_formulae_with_cache(vec!["a"], &stg, &known).is_err());
+    }
+
+    #[test]
+    /// Test model checking with steady states pre-computed on a different graph of the same model.
+    fn model_check_with_steady_states() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -? a\nb -| b\n$b: a & !b").unwrap();
+        let steady_states = compute_steady_states(&SymbolicAsyncGraph::new(&bn).unwrap());
+
+        let formulae = ["!{x}: AX {x}", "EF a", "!{x}: 3{y}: (@{x}: ~{y} & EX {y})"];
+        for num_vars in [1, 2] {
+            let stg = get_extended_symbolic_graph(&bn, num_vars).unwrap();
+            let formulae = &formulae[..num_vars as usize + 1];
+            let results = model_check_multiple_formulae_with_steady_states(
+                formulae.to_vec(),
+                &stg,
+                &steady_states,
+            )
+            .unwrap();
+            for (formula, result) in formulae.iter().zip(results) {
+                assert_eq!(result, model_check_formula(formula, &stg).unwrap());
+            }
+        }
+
+        // steady states must be compatible with the graph
+        let other_bn = BooleanNetwork::try_from("a -> a").unwrap();
+        let other_states = compute_steady_states(&SymbolicAsyncGraph::new(&other_bn).unwrap());
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        assert!(
+            model_check_multiple_formulae_with_steady_states(vec!["a"], &stg, &other_states)
+                .is_err()
+        );
+    }
+
+    #[test]
+    /// Test that the streamed results are delivered in order, and match the standard results.
+    fn model_check_streamed() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -? a\nb -| b\nc -| c").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let formulae = vec!["!{x}: AX {x}", "EF (a & b)", "!{x}: AG EF {x}"];
+
+        // results can be forwarded to a channel
+        let (sender, receiver) = mpsc::channel();
+        model_check_multiple_formulae_streamed(formulae.clone(), &stg, |i, result| {
+            sender.send((i, result)).unwrap()
+        })
+        .unwrap();
+        drop(sender);
+        let results = receiver.iter().collect::<Vec<_>>();
+        assert_eq!(results.len(), formulae.len());
+        for (j, (i, result)) in results.into_iter().enumerate() {
+            assert_eq!(i, j);
+            assert_eq!(result, model_check_formula(formulae[i], &stg).unwrap());
+        }
+
+        // invalid formulae are reported before any evaluation
+        let mut num_results = 0;
+        let invalid = vec!["a", "!{x}: !{y}: (AX {x} & AX {y})"];
+        let result = model_check_multiple_formulae_streamed(invalid, &stg, |_, _| num_results += 1);
+        assert!(result.is_err());
+        assert_eq!(num_results, 0);
+    }
+
+    #[test]
+    /// Test that the optimized formulae have the same results as the original ones.
+    fn model_check_with_optimization() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -? a\nb -| b\nc -| c").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let formulae = vec![
+            "~EF ~(a | ~AX ~b)",
+            "EF EF a & AG AG ~EG ~c",
+            "(a & EF b) | (c & a) | (a & b)",
+            "!{x}: (AG EF {x} & EF ~a & AX {x})",
+            "3{y}: !{x}: (AG EF {x} & ~(@{y}: ~{x}))",
+        ];
+        let options = EvalOptions {
+            optimize: true,
+            ..Default::default()
+        };
+        let results =
+            model_check_multiple_formulae_with_options(formulae.clone(), &stg, &options).unwrap();
+        for (formula, result) in formulae.into_iter().zip(results) {
+            assert_eq!(result, model_check_formula(formula, &stg).unwrap());
+        }
+    }
+
+    #[test]
+    /// Test the evaluation with frozen variables, which never fire any transitions.
+    fn model_check_with_frozen_variables() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -? a\nb -| b\nc -| c").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let unit = sanitize_colored_vertices(&stg, stg.unit_colored_vertices());
+        let formulae = vec!["(c & AG c) | (~c & AG ~c)", "!{x}: AX {x}"];
+
+        // the value of frozen `c` never changes (but it oscillates otherwise)
+        let options = EvalOptions {
+            frozen_variables: vec!["c".to_string()],
+            ..Default::default()
+        };
+        let results =
+            model_check_multiple_formulae_with_options(formulae.clone(), &stg, &options).unwrap();
+        assert_eq!(results[0], unit);
+        assert_ne!(model_check_formula(formulae[0], &stg).unwrap(), unit);
+
+        // when all variables are frozen, every state is a fixed point
+        let options = EvalOptions {
+            frozen_variables: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            ..Default::default()
+        };
+        let results =
+            model_check_multiple_formulae_with_options(formulae.clone(), &stg, &options).unwrap();
+        assert_eq!(results[1], unit);
+
+        // frozen variables must be valid network variables
+        let options = EvalOptions {
+            frozen_variables: vec!["d".to_string()],
+            ..Default::default()
+        };
+        assert!(model_check_multiple_formulae_with_options(formulae, &stg, &options).is_err());
+    }
+
+    #[test]
+    /// Test that the frontier-based reachability gives the same results as the saturation, for
+    /// each combination of the algorithms selected for `EF` and `EU`.
+    fn model_check_with_frontier_reachability() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -? a\nb -| b\nc -| c\na -> c").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let formulae = vec![
+            "EF (a & b & ~c)",
+            "(a | c) EU (~a & b)",
+            "!{x}: AG EF {x}",
+            "!{x}: (a EU (~a & EF {x}))",
+            "3{x}: @{x}: EF (~{x} & EF {x})",
+        ];
+        let expected = model_check_multiple_formulae(formulae.clone(), &stg).unwrap();
+        for ef in [
+            ReachabilityAlgorithm::Saturation,
+            ReachabilityAlgorithm::Frontier,
+        ] {
+            for eu in [
+                ReachabilityAlgorithm::Saturation,
+                ReachabilityAlgorithm::Frontier,
+            ] {
+                let options = EvalOptions {
+                    algorithms: OperatorAlgorithms { ef, eu },
+                    ..Default::default()
+                };
+                let results =
+                    model_check_multiple_formulae_with_options(formulae.clone(), &stg, &options)
+                        .unwrap();
+                assert_eq!(results, expected);
+            }
+        }
+    }
+
+    #[test]
+    /// Test the model checking on a graph restricted to a result of a previous formula.
+    fn test_model_check_under_restriction() {
+        // `a` keeps its value, and `b` follows `a`
+        let bn = BooleanNetwork::try_from("a -> a\na -> b\n$a: a\n$b: a").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // restricting to the whole graph changes nothing
+        let unit = stg.mk_unit_colored_vertices();
+        for formula in ["EF b", "AX ~b", "!{x}: AX {x}"] {
+            assert_eq!(
+                model_check_under_restriction(formula, &stg, &unit).unwrap(),
+                model_check_formula(formula, &stg).unwrap()
+            );
+        }
+
+        // `b` can be turned on, but not inside the (sanitized) result of `~b`
+        let restriction = model_check_formula("~b", &stg).unwrap();
+        assert!(!model_check_formula("EF b", &stg).unwrap().is_empty());
+        assert!(model_check_under_restriction("EF b", &stg, &restriction)
+            .unwrap()
+            .is_empty());
+        // the state variables only range over the restriction
+        let result = model_check_under_restriction("3{x}: @{x}: ~a", &stg, &restriction).unwrap();
+        assert_eq!(result, restriction);
+        assert!(
+            model_check_under_restriction("3{x}: @{x}: b", &stg, &restriction)
+                .unwrap()
+                .is_empty()
+        );
+
+        // the restriction must come from a compatible graph
+        let other_bn = BooleanNetwork::try_from("a -> b\nb -> c\nc -> a").unwrap();
+        let other_stg = get_extended_symbolic_graph(&other_bn, 1).unwrap();
+        let other_set = model_check_formula("a", &other_stg).unwrap();
+        assert!(model_check_under_restriction("a", &stg, &other_set).is_err());
+    }
+
+    #[test]
+    /// Test the model checking returning both the dirty and the sanitized results.
+    fn test_model_check_dual() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -> a\n$a: b\n$b: a").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // the free variable `x` is kept in the dirty result
+        let tree = parse_hctl_formula("EF ({x} & AG EF {x})").unwrap();
+        let dual = model_check_multiple_trees_dual(vec![tree.clone()], &stg)
+            .unwrap()
+            .remove(0);
+        assert_eq!(dual.dirty, model_check_tree_dirty(tree, &stg).unwrap());
+        let (retained, _) = dual.retaining(&stg, &["x"]).unwrap();
+        assert_eq!(retained.as_bdd().cardinality(), 6.0);
+        // the sanitized result is the same as if no variables were retained
+        let (projected, _) = dual.retaining(&stg, &[]).unwrap();
+        assert_eq!(dual.sanitized, projected);
+
+        // moving a sanitized subset to the dirty form and back
+        let subset = dual.sanitized.pick_singleton();
+        let dirty_subset = dual.to_dirty(&stg, &subset).unwrap();
+        assert!(dirty_subset.is_subset(&dual.dirty));
+        assert_eq!(dual.to_sanitized(&stg, &dirty_subset), subset);
+
+        let duals = model_check_multiple_formulae_dual(vec!["a", "EF b"], &stg).unwrap();
+        assert_eq!(duals.len(), 2);
+        assert_eq!(duals[0].sanitized, model_check_formula("a", &stg).unwrap());
+        let dual = model_check_formula_dual("!{x}: AX {x}", &stg).unwrap();
+        assert_eq!(
+            dual.dirty,
+            model_check_formula_dirty("!{x}: AX {x}", &stg).unwrap()
+        );
+    }
 }
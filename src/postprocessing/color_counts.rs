@@ -0,0 +1,199 @@
+//! Numbers of satisfying states for individual colors of a model-checking result.
+//!
+//! The summary of a result only reports the total numbers of states and colors, which hides the
+//! (often huge) variance across the parametrizations. Here, the satisfying states are counted
+//! exactly for each color (or for a uniform sample of colors), and the counts can be printed as
+//! a table or a histogram.
+
+use crate::postprocessing::sampling::valuation_to_color;
+use crate::postprocessing::sanitizing::{
+    extend_to_graph, sanitize_colored_vertices, sanitize_colors,
+};
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph,
+};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use rand::prelude::StdRng;
+use rand::SeedableRng;
+
+/// Maximal width of the bars of the histogram (see [color_count_histogram]).
+const MAX_BAR_WIDTH: usize = 50;
+
+/// Number of states satisfying a result for a single color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColorStateCount {
+    /// The (single) color, in the canonical symbolic context.
+    pub color: GraphColors,
+    /// Values of the symbolic parameter variables that describe the color, e.g., `0110`.
+    pub label: String,
+    /// Exact number of the satisfying states.
+    pub num_states: BigUint,
+}
+
+/// Count the states of the `result` for each color of the `graph` (including the colors with no
+/// satisfying states), or for a uniform sample of at most `sample_size` colors (picked with the
+/// given `seed`), if given. The `result` can be a (sanitized) result of the model checking on
+/// the `graph`.
+///
+/// The counts are sorted by the labels of the colors.
+pub fn count_states_per_color(
+    graph: &SymbolicAsyncGraph,
+    result: &GraphColoredVertices,
+    sample: Option<(usize, u64)>,
+) -> Result<Vec<ColorStateCount>, String> {
+    let result = sanitize_colored_vertices(graph, &extend_to_graph(graph, result)?);
+    let context = graph.symbolic_context().as_canonical_context();
+    let all_colors = sanitize_colors(graph, &graph.mk_unit_colors());
+
+    let colors = match sample {
+        Some((sample_size, seed)) if (sample_size as f64) < all_colors.approx_cardinality() => {
+            // sample the valuations of the colors uniformly, until enough distinct ones are found
+            let mut sampler = all_colors
+                .as_bdd()
+                .mk_uniform_valuation_sampler(StdRng::seed_from_u64(seed));
+            let mut sampled = Vec::new();
+            let mut remaining = all_colors.clone();
+            while sampled.len() < sample_size {
+                let valuation = all_colors
+                    .as_bdd()
+                    .random_valuation_sample(&mut sampler)
+                    .unwrap();
+                let color = valuation_to_color(&context, &valuation);
+                if !color.intersect(&remaining).is_empty() {
+                    remaining = remaining.minus(&color);
+                    sampled.push(color);
+                }
+            }
+            sampled
+        }
+        _ => {
+            let mut colors = Vec::new();
+            let mut remaining = all_colors;
+            while !remaining.is_empty() {
+                let color = remaining.pick_singleton();
+                remaining = remaining.minus(&color);
+                colors.push(color);
+            }
+            colors
+        }
+    };
+
+    let mut counts: Vec<ColorStateCount> = colors
+        .into_iter()
+        .map(|color| {
+            let valuation = color.as_bdd().sat_witness().unwrap();
+            let label = context
+                .parameter_variables()
+                .iter()
+                .map(|var| if valuation.value(*var) { '1' } else { '0' })
+                .collect();
+            let num_states = result
+                .intersect_colors(&color)
+                .vertices()
+                .exact_cardinality();
+            ColorStateCount {
+                color,
+                label,
+                num_states,
+            }
+        })
+        .collect();
+    counts.sort_by(|a, b| a.label.cmp(&b.label));
+    Ok(counts)
+}
+
+/// Format the counts as a CSV table with columns `color` and `num_states`.
+pub fn color_counts_to_csv(counts: &[ColorStateCount]) -> String {
+    let mut table = String::from("color,num_states\n");
+    for count in counts {
+        table.push_str(format!("{},{}\n", count.label, count.num_states).as_str());
+    }
+    table
+}
+
+/// Format the counts as a text histogram with (at most) `num_bins` bins of the same width,
+/// spanning from the minimal to the maximal count. Each line gives the (inclusive) range of the
+/// bin, a bar, and the number of colors in the bin.
+pub fn color_count_histogram(counts: &[ColorStateCount], num_bins: usize) -> String {
+    let values: Vec<f64> = counts
+        .iter()
+        .map(|count| count.num_states.to_f64().unwrap_or(f64::INFINITY))
+        .collect();
+    if values.is_empty() || num_bins == 0 {
+        return String::new();
+    }
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    // integer counts, so there is no point in bins narrower than one state
+    let num_bins = num_bins.min((max - min) as usize + 1);
+    let width = (max - min + 1.0) / num_bins as f64;
+
+    let mut bins = vec![0usize; num_bins];
+    for value in &values {
+        let bin = (((value - min) / width) as usize).min(num_bins - 1);
+        bins[bin] += 1;
+    }
+    let largest_bin = *bins.iter().max().unwrap();
+    let mut histogram = String::new();
+    for (i, size) in bins.iter().enumerate() {
+        let low = (min + i as f64 * width).ceil();
+        let high = (min + (i + 1) as f64 * width).ceil() - 1.0;
+        let bar_width = (size * MAX_BAR_WIDTH).div_ceil(largest_bin);
+        histogram.push_str(format!("[{low}, {high}] {} {size}\n", "#".repeat(bar_width)).as_str());
+    }
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use crate::postprocessing::color_counts::{
+        color_count_histogram, color_counts_to_csv, count_states_per_color,
+    };
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use num_bigint::BigUint;
+
+    #[test]
+    /// Test counting the satisfying states for each color, and printing the counts.
+    fn test_color_counts() {
+        // `a` is a constant input, and `b` is given by a parameter of arity one (4 colors)
+        let bn = BooleanNetwork::try_from("a -? b\nb -> c\n$b: f(a)\n$c: b").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // there is exactly one fixed point for every color
+        let result = model_check_formula("!{x}: AX {x}", &graph).unwrap();
+        let counts = count_states_per_color(&graph, &result, None).unwrap();
+        assert_eq!(counts.len(), 4);
+        assert!(counts.iter().all(|c| c.num_states == BigUint::from(1u32)));
+        assert_eq!(counts[0].label, "001");
+        assert_eq!(color_counts_to_csv(&counts).lines().count(), 5);
+        let histogram = color_count_histogram(&counts, 10);
+        assert_eq!(histogram.trim(), format!("[1, 1] {} 4", "#".repeat(50)));
+
+        // colors where the result is empty are included as well
+        let result = model_check_formula("AG b", &graph).unwrap();
+        let counts = count_states_per_color(&graph, &result, None).unwrap();
+        let values: Vec<u32> = counts
+            .iter()
+            .map(|c| c.num_states.to_string().parse().unwrap())
+            .collect();
+        assert_eq!(values, vec![0, 2, 2, 0]);
+        let histogram = color_count_histogram(&counts, 2);
+        let lines: Vec<&str> = histogram.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("[0, 1] ") && lines[1].starts_with("[2, 2] "));
+
+        // sampled colors are distinct
+        let result = model_check_formula("EF c", &graph).unwrap();
+        let sample = count_states_per_color(&graph, &result, Some((2, 0))).unwrap();
+        assert_eq!(sample.len(), 2);
+        assert_ne!(sample[0].label, sample[1].label);
+        assert!(sample
+            .iter()
+            .all(|c| c.num_states >= BigUint::from(7u32) && c.num_states <= BigUint::from(8u32)));
+    }
+}
@@ -0,0 +1,213 @@
+//! Human-readable descriptions of color sets (e.g., of the classes of a classification), given
+//! as a disjunction of constraints on the entries of the function tables of the parameters.
+//!
+//! An entry of an explicit parameter `f` of arity two is written as `f(0, 1)`, and an entry of
+//! the implicit (unspecified) update function of a variable `b` as `f_b(a=0, c=1)`, listing the
+//! regulators of `b`. For example, `(f(1) = 1 & f_b(a=0) = 0) | f(0) = 0` describes the colors
+//! where either the function `f` holds for `1` and the update function of `b` does not hold for
+//! inactive `a`, or `f` does not hold for `0`.
+//!
+//! The description only has to agree with the set on the valid colors of the graph (the colors
+//! satisfying the regulation constraints), which often makes it much shorter.
+
+use crate::postprocessing::sanitizing::sanitize_colors;
+
+use biodivine_lib_bdd::{Bdd, BddPartialValuation, BddVariable};
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Description of a color set by a disjunction of clauses, each being a conjunction of
+/// constraints on the function table entries (see the module documentation).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColorDescription {
+    /// Clauses of the disjunction, each given by its constraints, e.g., `f(0, 1) = 1`.
+    pub clauses: Vec<Vec<String>>,
+    /// True if the description was cut off after the maximal number of clauses, so that it only
+    /// describes a subset of the colors.
+    pub truncated: bool,
+}
+
+impl fmt::Display for ColorDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.clauses.is_empty() {
+            return write!(f, "false");
+        }
+        let clauses: Vec<String> = self
+            .clauses
+            .iter()
+            .map(|clause| match clause.len() {
+                0 => "true".to_string(),
+                1 => clause[0].clone(),
+                _ if self.clauses.len() == 1 => clause.join(" & "),
+                _ => format!("({})", clause.join(" & ")),
+            })
+            .collect();
+        write!(f, "{}", clauses.join(" | "))?;
+        if self.truncated {
+            write!(f, " | ...")?;
+        }
+        Ok(())
+    }
+}
+
+/// Name the entries of all function tables of the graph by their readable form.
+fn name_table_entries(graph: &SymbolicAsyncGraph) -> HashMap<BddVariable, String> {
+    let context = graph.symbolic_context().as_canonical_context();
+    let format_args = |args: Vec<String>| {
+        if args.is_empty() {
+            String::new()
+        } else {
+            format!("({})", args.join(", "))
+        }
+    };
+    let mut names = HashMap::new();
+    for parameter in context.network_parameters() {
+        let name = context.get_network_parameter_name(parameter);
+        for (row, var) in context.get_explicit_function_table(parameter) {
+            let args = row.iter().map(|v| (*v as u8).to_string()).collect();
+            names.insert(var, format!("{name}{}", format_args(args)));
+        }
+    }
+    for variable in context.network_implicit_parameters() {
+        let name = context.get_network_variable_name(variable);
+        // the arguments of implicit functions are the regulators (sorted by their IDs)
+        let regulators: Vec<String> = match graph.as_network() {
+            Some(bn) => bn
+                .regulators(variable)
+                .into_iter()
+                .map(|r| bn.get_variable_name(r).clone())
+                .collect(),
+            None => Vec::new(),
+        };
+        for (row, var) in context.get_implicit_function_table(variable).unwrap() {
+            let args = row
+                .iter()
+                .enumerate()
+                .map(|(i, v)| match regulators.get(i) {
+                    Some(regulator) => format!("{regulator}={}", *v as u8),
+                    None => (*v as u8).to_string(),
+                })
+                .collect();
+            names.insert(var, format!("f_{name}{}", format_args(args)));
+        }
+    }
+    names
+}
+
+/// Compute a small DNF of the `bdd` (with at most `max_clauses` clauses). If the optimized DNF
+/// is too large, the first `max_clauses` clauses of the plain DNF are returned instead, and the
+/// result is marked as truncated.
+///
+/// The optimized DNF is computed by `Bdd::_to_optimized_dnf`, which can be interrupted once the
+/// DNF grows too large, unlike the stable `Bdd::to_optimized_dnf`. The underscore marks it as
+/// unstable, so the version of `biodivine-lib-bdd` is pinned to the tested one in `Cargo.toml`.
+fn bounded_dnf(bdd: &Bdd, max_clauses: usize) -> (Vec<BddPartialValuation>, bool) {
+    let optimized = bdd._to_optimized_dnf(true, &|dnf: &[BddPartialValuation]| {
+        if dnf.len() > max_clauses {
+            Err(())
+        } else {
+            Ok(())
+        }
+    });
+    match optimized {
+        Ok(dnf) if dnf.len() <= max_clauses => (dnf, false),
+        _ => (bdd.sat_clauses().take(max_clauses).collect(), true),
+    }
+}
+
+/// Describe the `colors` of the `graph` by a (small) disjunction of constraints on the function
+/// table entries (see the module documentation), with at most `max_clauses` clauses. The
+/// `colors` can be sanitized, or in the context of the (extended) graph.
+///
+/// If the description needs more clauses, it is truncated (and only describes a subset of the
+/// colors).
+pub fn describe_colors(
+    graph: &SymbolicAsyncGraph,
+    colors: &GraphColors,
+    max_clauses: usize,
+) -> ColorDescription {
+    let context = graph.symbolic_context();
+    let colors = if colors.as_bdd().num_vars() == context.bdd_variable_set().num_vars() {
+        sanitize_colors(graph, colors)
+    } else {
+        colors.clone()
+    };
+    let unit = sanitize_colors(graph, &graph.mk_unit_colors());
+
+    let (dnf, truncated) = bounded_dnf(colors.as_bdd(), max_clauses);
+    // the invalid colors are "don't care" values, so the literals of each clause that are not
+    // needed to stay within the colors (on the valid colors) can be dropped
+    let variables = context.as_canonical_context().bdd_variable_set().clone();
+    let mut relaxed_dnf: Vec<BddPartialValuation> = Vec::new();
+    for mut clause in dnf {
+        for (var, _) in clause.to_values() {
+            let mut candidate = clause.clone();
+            candidate.unset_value(var);
+            let implicant = variables
+                .mk_conjunctive_clause(&candidate)
+                .and(unit.as_bdd());
+            if implicant.imp(colors.as_bdd()).is_true() {
+                clause = candidate;
+            }
+        }
+        if !relaxed_dnf.contains(&clause) {
+            relaxed_dnf.push(clause);
+        }
+    }
+
+    let names = name_table_entries(graph);
+    let clauses = relaxed_dnf
+        .iter()
+        .map(|clause| {
+            let mut literals: Vec<(BddVariable, bool)> = clause.to_values();
+            literals.sort();
+            literals
+                .into_iter()
+                .map(|(var, value)| format!("{} = {}", names[&var], value as u8))
+                .collect()
+        })
+        .collect();
+    ColorDescription { clauses, truncated }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use crate::postprocessing::color_description::describe_colors;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test describing the colors of results by constraints on the function tables.
+    fn test_color_description() {
+        // `a` is a constant input, and `b` is given by an (observable) implicit function of `a`
+        let bn = BooleanNetwork::try_from("a -? b\nb -> c\n$c: b & p").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // fixed points with active `c` need both `p` and the update function of `b` to hold
+        let result = model_check_formula("!{x}: AX {x} & c & a", &graph).unwrap();
+        let description = describe_colors(&graph, &result.colors(), 10);
+        assert!(!description.truncated);
+        assert_eq!(description.clauses.len(), 1);
+        assert_eq!(description.to_string(), "f_a = 1 & f_b(a=1) = 1 & p = 1");
+
+        // all valid colors are described by `true`, and none by `false`
+        let description = describe_colors(&graph, &graph.mk_unit_colors(), 10);
+        assert_eq!(description.to_string(), "true");
+        let result = model_check_formula("c & ~c", &graph).unwrap();
+        assert_eq!(
+            describe_colors(&graph, &result.colors(), 10).to_string(),
+            "false"
+        );
+
+        // the description is truncated if it needs too many clauses
+        let result = model_check_formula("!{x}: AX {x} & ~c", &graph).unwrap();
+        let description = describe_colors(&graph, &result.colors(), 10);
+        assert!(description.clauses.len() > 1);
+        let truncated = describe_colors(&graph, &result.colors(), 1);
+        assert!(truncated.truncated);
+        assert_eq!(truncated.clauses.len(), 1);
+        assert!(truncated.to_string().ends_with(" | ..."));
+    }
+}
@@ -0,0 +1,329 @@
+//! Explanations of satisfied existential reachability properties (`EF` and `EU`), i.e., which
+//! mechanisms of the network actually realize the behaviour.
+//!
+//! The explanation is based on a single witness path (for a single color) from a satisfying
+//! state to the target. The path is obtained from the backward saturation layers of the target,
+//! and the set of variables updated on the path is then minimized by freezing the updated
+//! variables one by one (see [with_frozen_variables]) while the target stays reachable. Finally,
+//! the regulations used on the path are those whose regulator actually affects the fired update
+//! function in the state where the update fires.
+
+use crate::mc_utils::{check_hctl_var_support, with_frozen_variables};
+use crate::model_checking::model_check_tree;
+use crate::postprocessing::sanitizing::{extend_to_graph, sanitize_colors};
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::operator_enums::{BinaryOp, UnaryOp};
+use crate::preprocessing::parser::parse_and_minimize_hctl_formula;
+
+use biodivine_lib_param_bn::biodivine_std::bitvector::{ArrayBitVector, BitVector};
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph,
+};
+use biodivine_lib_param_bn::VariableId;
+
+/// Explanation of a reachability witness for a single color.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReachabilityExplanation {
+    /// The color of the witness (sanitized).
+    pub color: GraphColors,
+    /// States of the (shortest) witness path, from the source state to a target state.
+    pub path: Vec<ArrayBitVector>,
+    /// Names of the variables updated on the path. No single one of them can be frozen without
+    /// losing the reachability of the target (for this color and source state).
+    pub updates: Vec<String>,
+    /// Regulations `(regulator, target)` used by the updates on the path, i.e., those whose
+    /// regulator value decides the update in some state of the path where the target fires.
+    pub regulations: Vec<(String, String)>,
+}
+
+/// Explain a satisfied (closed) existential reachability `formula`, i.e., `EF phi` or
+/// `psi EU phi`. The witness starts in a satisfying state outside of `phi` (if there is one).
+///
+/// Return `None` if the formula is not satisfied in any state.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn explain_existential_formula(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Option<ReachabilityExplanation>, String> {
+    let tree = parse_and_minimize_hctl_formula(graph.symbolic_context(), formula)?;
+    if !check_hctl_var_support(graph, tree.clone())? {
+        return Err("Graph does not support enough HCTL state variables".to_string());
+    }
+    let (path, target) = match &tree.node_type {
+        NodeType::Unary(UnaryOp::EF, target) => (None, target),
+        NodeType::Binary(BinaryOp::EU, path, target) => (Some(path), target),
+        _ => {
+            return Err(format!(
+                "Formula `{formula}` is not an existential reachability (`EF` or `EU`) formula."
+            ))
+        }
+    };
+    let target = eval_in_graph(target, graph)?;
+    let universe = match path {
+        Some(path) => eval_in_graph(path, graph)?.union(&target),
+        None => graph.mk_unit_colored_vertices(),
+    };
+    let satisfying = eval_in_graph(&tree, graph)?;
+    // prefer witnesses that are not trivial
+    let non_trivial = satisfying.minus(&target);
+    let source = if non_trivial.is_empty() {
+        satisfying
+    } else {
+        non_trivial
+    };
+    Ok(explain_reachability(graph, &source, &target, &universe))
+}
+
+/// Explain the reachability of the `target` set from the `source` set through the `universe`
+/// (all sets in the symbolic context of the `graph`). The witness is selected for a single color
+/// and a single source state that can reach the target.
+///
+/// Return `None` if the target is not reachable from the source for any color.
+pub fn explain_reachability(
+    graph: &SymbolicAsyncGraph,
+    source: &GraphColoredVertices,
+    target: &GraphColoredVertices,
+    universe: &GraphColoredVertices,
+) -> Option<ReachabilityExplanation> {
+    let target = target.intersect(universe);
+    let reaching = backward_layers(graph, &target, universe, None)?
+        .pop()
+        .unwrap();
+    let reaching_sources = source.intersect(universe).intersect(&reaching);
+    if reaching_sources.is_empty() {
+        return None;
+    }
+    let source_state = reaching_sources.pick_singleton();
+    let color = source_state.colors();
+    let target = target.intersect_colors(&color);
+    let universe = universe.intersect_colors(&color);
+
+    // freeze the updated variables one by one, as long as the target stays reachable
+    let mut frozen: Vec<String> = Vec::new();
+    let mut path = find_path(graph, &source_state, &target, &universe).unwrap();
+    for var in updated_variables(graph, &path) {
+        let mut candidate = frozen.clone();
+        candidate.push(graph.get_variable_name(var));
+        // the variables are valid, so freezing them is always possible
+        let frozen_graph = with_frozen_variables(graph, &candidate).unwrap();
+        if let Some(candidate_path) = find_path(&frozen_graph, &source_state, &target, &universe) {
+            frozen = candidate;
+            path = candidate_path;
+        }
+    }
+
+    let updates = updated_variables(graph, &path);
+    let mut regulations = Vec::new();
+    for (state, next_state) in path.iter().zip(path.iter().skip(1)) {
+        let fired = graph
+            .variables()
+            .find(|var| {
+                !next_state
+                    .intersect(&graph.var_post(*var, state))
+                    .is_empty()
+            })
+            .unwrap();
+        for regulator in graph.variables() {
+            let regulation = (
+                graph.get_variable_name(regulator),
+                graph.get_variable_name(fired),
+            );
+            if !regulations.contains(&regulation) && decides_update(graph, state, regulator, fired)
+            {
+                regulations.push(regulation);
+            }
+        }
+    }
+    regulations.sort_by_key(|(regulator, target)| {
+        let context = graph.symbolic_context();
+        (
+            context.find_network_variable(target),
+            context.find_network_variable(regulator),
+        )
+    });
+
+    Some(ReachabilityExplanation {
+        color: sanitize_colors(graph, &color),
+        path: path.iter().map(single_state).collect(),
+        updates: updates
+            .into_iter()
+            .map(|var| graph.get_variable_name(var))
+            .collect(),
+        regulations,
+    })
+}
+
+/// Evaluate the (closed) formula `tree`, and get the result in the symbolic context of `graph`.
+fn eval_in_graph(
+    tree: &HctlTreeNode,
+    graph: &SymbolicAsyncGraph,
+) -> Result<GraphColoredVertices, String> {
+    extend_to_graph(graph, &model_check_tree(tree.clone(), graph)?)
+}
+
+/// Compute the backward saturation layers of the `target` through the `universe`, i.e., the
+/// `i`-th layer contains the states that reach the target in at most `i` steps. If the `source`
+/// is given, the computation stops at the first layer containing it.
+///
+/// Return `None` if the `source` is given, but it can not reach the target.
+fn backward_layers(
+    graph: &SymbolicAsyncGraph,
+    target: &GraphColoredVertices,
+    universe: &GraphColoredVertices,
+    source: Option<&GraphColoredVertices>,
+) -> Option<Vec<GraphColoredVertices>> {
+    let mut layers = vec![target.clone()];
+    loop {
+        let last = layers.last().unwrap();
+        if let Some(source) = source {
+            if source.is_subset(last) {
+                return Some(layers);
+            }
+        }
+        let next = last.union(&graph.pre(last).intersect(universe));
+        if next == *last {
+            return if source.is_some() { None } else { Some(layers) };
+        }
+        layers.push(next);
+    }
+}
+
+/// Find the shortest path (for a single color) from the `source` state to the `target` through
+/// the `universe`, as a list of singleton sets of the states.
+fn find_path(
+    graph: &SymbolicAsyncGraph,
+    source: &GraphColoredVertices,
+    target: &GraphColoredVertices,
+    universe: &GraphColoredVertices,
+) -> Option<Vec<GraphColoredVertices>> {
+    let layers = backward_layers(graph, target, universe, Some(source))?;
+    let mut path = vec![source.clone()];
+    // the source is only in the last layer, each step moves it one layer closer to the target
+    for layer in layers.iter().rev().skip(1) {
+        let state = path.last().unwrap();
+        let next = graph
+            .variables()
+            .map(|var| graph.var_post(var, state).intersect(layer))
+            .find(|next| !next.is_empty())
+            .unwrap();
+        path.push(next.pick_singleton());
+    }
+    Some(path)
+}
+
+/// Collect the variables updated on the `path` (in the order of the network variables).
+fn updated_variables(graph: &SymbolicAsyncGraph, path: &[GraphColoredVertices]) -> Vec<VariableId> {
+    graph
+        .variables()
+        .filter(|var| {
+            path.iter()
+                .zip(path.iter().skip(1))
+                .any(|(state, next)| !next.intersect(&graph.var_post(*var, state)).is_empty())
+        })
+        .collect()
+}
+
+/// Check whether the value of the `regulator` in the (singleton) `state` decides the update of
+/// the `target` variable, i.e., whether flipping it changes the value of the update function.
+fn decides_update(
+    graph: &SymbolicAsyncGraph,
+    state: &GraphColoredVertices,
+    regulator: VariableId,
+    target: VariableId,
+) -> bool {
+    let update = graph.get_symbolic_fn_update(target);
+    let mut flipped_state = single_state(state);
+    flipped_state.flip(regulator.to_index());
+    let flipped = graph
+        .vertex(&flipped_state)
+        .intersect_colors(&state.colors());
+    let value = !state.as_bdd().and(update).is_false();
+    let flipped_value = !flipped.as_bdd().and(update).is_false();
+    value != flipped_value
+}
+
+/// Get the single state of the (singleton) colored set.
+fn single_state(state: &GraphColoredVertices) -> ArrayBitVector {
+    state.vertices().materialize().iter().next().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::postprocessing::explanation::{explain_existential_formula, explain_reachability};
+    use biodivine_lib_param_bn::biodivine_std::bitvector::{ArrayBitVector, BitVector};
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::{BooleanNetwork, VariableId};
+
+    #[test]
+    /// Test explaining a reachability property through its witness path.
+    fn test_explain_reachability() {
+        // `c` can be switched on either through `a` (which always turns on), or through `b`
+        // (which can only turn on when `a` is off)
+        let bn = BooleanNetwork::try_from("a -> c\nb -> c\na -| b\n$a: true\n$b: !a\n$c: a | b")
+            .unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let source = graph.vertex(&ArrayBitVector::from(vec![false, false, false]));
+        let target = graph
+            .fix_network_variable(VariableId::from_index(0), true)
+            .intersect(&graph.fix_network_variable(VariableId::from_index(2), true));
+        let unit = graph.mk_unit_colored_vertices();
+        let explanation = explain_reachability(&graph, &source, &target, &unit).unwrap();
+
+        // the shortest path first turns on `a`, which then turns on `c` (regardless of `b`)
+        assert_eq!(explanation.color.approx_cardinality(), 1.0);
+        assert_eq!(explanation.updates, vec!["a", "c"]);
+        let path: Vec<Vec<bool>> = explanation.path.iter().map(|s| s.values()).collect();
+        assert_eq!(
+            path,
+            vec![
+                vec![false, false, false],
+                vec![true, false, false],
+                vec![true, false, true],
+            ]
+        );
+        assert_eq!(
+            explanation.regulations,
+            vec![("a".to_string(), "c".to_string())]
+        );
+
+        // `b` can never turn on once `a` is on
+        let source = graph.vertex(&ArrayBitVector::from(vec![true, false, false]));
+        let target = graph.fix_network_variable(VariableId::from_index(1), true);
+        assert!(explain_reachability(&graph, &source, &target, &unit).is_none());
+    }
+
+    #[test]
+    /// Test explaining existential reachability formulae.
+    fn test_explain_existential_formula() {
+        let bn = BooleanNetwork::try_from("a -> c\nb -> c\na -| b\n$a: true\n$b: !a\n$c: a | b")
+            .unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let explanation = explain_existential_formula("EF (c & a)", &graph)
+            .unwrap()
+            .unwrap();
+        // the witness starts outside the target
+        assert!(explanation.path.len() >= 2);
+        assert!(!explanation.updates.is_empty());
+
+        // `c` can only turn on while `b` is on (without `a`)
+        let explanation = explain_existential_formula("(~a & ~c) EU (c & ~a)", &graph)
+            .unwrap()
+            .unwrap();
+        assert_eq!(explanation.updates, vec!["b", "c"]);
+        assert_eq!(
+            explanation.regulations,
+            vec![
+                ("a".to_string(), "b".to_string()),
+                ("b".to_string(), "c".to_string())
+            ]
+        );
+
+        // the formula must be satisfied, and it must be existential reachability
+        assert!(explain_existential_formula("EF (a & ~a)", &graph)
+            .unwrap()
+            .is_none());
+        assert!(explain_existential_formula("AF c", &graph).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Symbolic stepping through the transition graph, allowing to interleave a manual exploration
+//! of the graph with the model checking (e.g., when debugging why a property fails).
+
+use crate::postprocessing::sanitizing::{
+    extend_colors, extend_to_graph, sanitize_colored_vertices,
+};
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph,
+};
+use biodivine_lib_param_bn::VariableId;
+
+use rand::Rng;
+
+/// Compute all successors of the colored states in the `set` in the (extended) `graph`.
+///
+/// The `set` can be a (sanitized) result of the model checking, and the result is sanitized as
+/// well, so that a manual exploration of the graph can be interleaved with the model checking.
+pub fn successors(
+    graph: &SymbolicAsyncGraph,
+    set: &GraphColoredVertices,
+) -> Result<GraphColoredVertices, String> {
+    let set = extend_to_graph(graph, set)?;
+    Ok(sanitize_colored_vertices(graph, &graph.post(&set)))
+}
+
+/// Compute all predecessors of the colored states in the `set` in the (extended) `graph`. As in
+/// [successors], both the `set` and the result can be sanitized.
+pub fn predecessors(
+    graph: &SymbolicAsyncGraph,
+    set: &GraphColoredVertices,
+) -> Result<GraphColoredVertices, String> {
+    let set = extend_to_graph(graph, set)?;
+    Ok(sanitize_colored_vertices(graph, &graph.pre(&set)))
+}
+
+/// Pick a random successor of the `state` (values of the network variables, in their order) for
+/// the given single `color`, i.e., update one of the variables that can change their value. Return
+/// `None` if there is no such variable (the state is a steady state of the color).
+pub fn random_successor<R: Rng>(
+    graph: &SymbolicAsyncGraph,
+    state: &[bool],
+    color: &GraphColors,
+    rng: &mut R,
+) -> Result<Option<Vec<bool>>, String> {
+    if state.len() != graph.num_vars() {
+        return Err(format!(
+            "State has {} values, but the network has {} variables.",
+            state.len(),
+            graph.num_vars()
+        ));
+    }
+    if !color.is_singleton() {
+        return Err("Exactly one color must be given.".to_string());
+    }
+    let color = extend_colors(graph, color)
+        .ok_or("The color is not compatible with the graph.".to_string())?;
+    let values: Vec<(VariableId, bool)> = graph.variables().zip(state.iter().copied()).collect();
+    let colored_state = graph.mk_subspace(&values).intersect_colors(&color);
+    if colored_state.is_empty() {
+        return Err("The color is not valid in the graph.".to_string());
+    }
+
+    let enabled: Vec<VariableId> = graph
+        .variables()
+        .filter(|var| !graph.var_post(*var, &colored_state).is_empty())
+        .collect();
+    if enabled.is_empty() {
+        return Ok(None);
+    }
+    let var = enabled[rng.gen_range(0..enabled.len())];
+    let mut successor = state.to_vec();
+    successor[var.to_index()] = !successor[var.to_index()];
+    Ok(Some(successor))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use crate::postprocessing::exploration::{predecessors, random_successor, successors};
+
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use rand::prelude::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    /// Test stepping through the graph, interleaved with the model checking.
+    fn test_symbolic_stepping() {
+        // bistable switch with fixed points `a & ~b` and `~a & b`
+        let bn = BooleanNetwork::try_from("a -| b\nb -| a\n$a: !b\n$b: !a").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        let set = model_check_formula("a & b", &graph).unwrap();
+        let expected = model_check_formula("a ^ b", &graph).unwrap();
+        assert_eq!(successors(&graph, &set).unwrap(), expected);
+        let set = model_check_formula("a & ~b", &graph).unwrap();
+        let expected = model_check_formula("a <=> b", &graph).unwrap();
+        assert_eq!(predecessors(&graph, &set).unwrap(), expected);
+
+        // the colors can be given both in the extended and the sanitized context
+        let mut rng = StdRng::seed_from_u64(0);
+        let color = set.colors();
+        let successor = random_successor(&graph, &[true, true], &color, &mut rng).unwrap();
+        assert!(successor == Some(vec![true, false]) || successor == Some(vec![false, true]));
+        let color = graph.mk_unit_colors();
+        let successor = random_successor(&graph, &[true, false], &color, &mut rng).unwrap();
+        assert!(successor.is_none());
+        assert!(random_successor(&graph, &[true], &color, &mut rng).is_err());
+    }
+}
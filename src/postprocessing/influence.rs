@@ -0,0 +1,145 @@
+//! Influence (coverage) analysis of formulae, i.e., which parts of the network the results of
+//! formulae actually depend on.
+//!
+//! The dependencies are obtained by inspecting the support sets of the BDDs representing the
+//! result of a formula and the results of all its sub-formulae. A network variable influences
+//! the formula if any of these sets depends on its state variable, and a parameter (or an
+//! implicit update function) influences the formula if any set depends on its symbolic variables.
+//! Symbolic variables that only occur due to the constraints of the unit set (such as regulation
+//! monotonicity) are not considered.
+
+use crate::mc_utils::check_hctl_var_support;
+use crate::model_checking::model_check_multiple_trees_dirty;
+use crate::preprocessing::hctl_tree::*;
+use crate::preprocessing::parser::parse_and_minimize_hctl_formula;
+
+use biodivine_lib_bdd::BddVariable;
+use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+use std::collections::HashSet;
+
+/// Parts of the network that influence the satisfaction of a formula. All items are sorted by
+/// their order in the network.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FormulaInfluence {
+    /// Names of network variables whose state occurs in the result or intermediate results.
+    pub variables: Vec<String>,
+    /// Names of explicit parameters (uninterpreted functions) that occur in the results.
+    pub parameters: Vec<String>,
+    /// Names of variables whose implicit (unspecified) update functions occur in the results.
+    pub implicit_functions: Vec<String>,
+}
+
+/// Recursively collect all unique sub-formulae of the tree (including the tree itself).
+fn collect_subformulae(node: &HctlTreeNode, subformulae: &mut Vec<HctlTreeNode>) {
+    if subformulae.contains(node) {
+        return;
+    }
+    match &node.node_type {
+        NodeType::Terminal(_) => {}
+        NodeType::Unary(_, child) => collect_subformulae(child, subformulae),
+        NodeType::Binary(_, left, right) => {
+            collect_subformulae(left, subformulae);
+            collect_subformulae(right, subformulae);
+        }
+        NodeType::Hybrid(_, _, _, child) => collect_subformulae(child, subformulae),
+    }
+    subformulae.push(node.clone());
+}
+
+/// Compute which network variables, parameters, and implicit update functions influence the
+/// result of the `formula` on the `graph`.
+///
+/// Apart from the final result, the results of all sub-formulae are inspected, since a part of
+/// the network may affect an intermediate set even if it is eventually quantified away.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn compute_formula_influence(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<FormulaInfluence, String> {
+    let tree = parse_and_minimize_hctl_formula(graph.symbolic_context(), formula)?;
+    if !check_hctl_var_support(graph, tree.clone())? {
+        return Err("Graph does not support enough HCTL state variables".to_string());
+    }
+    let mut subformulae = Vec::new();
+    collect_subformulae(&tree, &mut subformulae);
+
+    // dirty results are sufficient, since only the state and parameter variables are inspected
+    let unit = graph.mk_unit_colored_vertices();
+    let mut support: HashSet<BddVariable> = HashSet::new();
+    for result in model_check_multiple_trees_dirty(subformulae, graph)? {
+        let result = result.as_bdd().and(unit.as_bdd());
+        for var in result.support_set() {
+            // the set depends on the variable only if projecting it out changes the set
+            if !support.contains(&var) && result.var_exists(var).and(unit.as_bdd()) != result {
+                support.insert(var);
+            }
+        }
+    }
+
+    let context = graph.symbolic_context();
+    let mut influence = FormulaInfluence::default();
+    for var in context.network_variables() {
+        if support.contains(&context.get_state_variable(var)) {
+            influence
+                .variables
+                .push(context.get_network_variable_name(var));
+        }
+        if let Some(table) = context.get_implicit_function_table(var) {
+            if table
+                .symbolic_variables()
+                .iter()
+                .any(|v| support.contains(v))
+            {
+                influence
+                    .implicit_functions
+                    .push(context.get_network_variable_name(var));
+            }
+        }
+    }
+    for param in context.network_parameters() {
+        let table = context.get_explicit_function_table(param);
+        if table
+            .symbolic_variables()
+            .iter()
+            .any(|v| support.contains(v))
+        {
+            influence
+                .parameters
+                .push(context.get_network_parameter_name(param));
+        }
+    }
+    Ok(influence)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::postprocessing::influence::compute_formula_influence;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test computing which variables and parameters influence the results of formulae.
+    fn test_formula_influence() {
+        let bn = BooleanNetwork::try_from(
+            "a -?? b\nb -> a\nc -?? c\nc -> d\n$a: b\n$b: p(a)\n$d: c & q",
+        )
+        .unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // the `c` and `d` components do not affect the reachability of `a`
+        let influence = compute_formula_influence("EF a", &graph).unwrap();
+        assert_eq!(influence.variables, vec!["a", "b"]);
+        assert_eq!(influence.parameters, vec!["p"]);
+        assert!(influence.implicit_functions.is_empty());
+
+        let influence = compute_formula_influence("!{x}: AX (d | {x})", &graph).unwrap();
+        assert_eq!(influence.variables, vec!["a", "b", "c", "d"]);
+        assert_eq!(influence.parameters, vec!["p", "q"]);
+        assert_eq!(influence.implicit_functions, vec!["c"]);
+
+        // propositions alone do not depend on the dynamics
+        let influence = compute_formula_influence("c & ~d", &graph).unwrap();
+        assert_eq!(influence.variables, vec!["c", "d"]);
+        assert!(influence.parameters.is_empty());
+    }
+}
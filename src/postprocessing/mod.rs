@@ -1,3 +1,12 @@
 //! Components regarding the postprocessing of model-checking results.
 
+#[cfg(feature = "color-counts")]
+pub mod color_counts;
+pub mod color_description;
+pub mod explanation;
+pub mod exploration;
+pub mod influence;
+pub mod sampling;
 pub mod sanitizing;
+pub mod set_expressions;
+pub mod vacuity;
@@ -0,0 +1,138 @@
+//! Uniform sampling of the states and colors of model-checking results, to get a few concrete
+//! witnesses of what a formula actually describes.
+//!
+//! The sampling is performed directly on the BDDs (by weighted path counting), so every
+//! satisfying state (or color) has the same probability of being picked, regardless of the
+//! structure of the BDD. The samples are picked independently (with repetition).
+
+use crate::postprocessing::sanitizing::{extend_to_graph, sanitize_colored_vertices};
+
+use biodivine_lib_bdd::{Bdd, BddPartialValuation, BddValuation, BddVariable};
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph, SymbolicContext,
+};
+use rand::prelude::StdRng;
+use rand::SeedableRng;
+
+/// Pick `n` uniformly random valuations of the `bdd` (which must not be empty).
+fn sample_valuations(bdd: &Bdd, n: usize, seed: u64) -> Vec<BddValuation> {
+    let mut sampler = bdd.mk_uniform_valuation_sampler(StdRng::seed_from_u64(seed));
+    (0..n)
+        .map(|_| bdd.random_valuation_sample(&mut sampler).unwrap())
+        .collect()
+}
+
+/// Convert the sampled valuation into the (single) color given by its parameter variables.
+pub(crate) fn valuation_to_color(
+    context: &SymbolicContext,
+    valuation: &BddValuation,
+) -> GraphColors {
+    let mut clause = BddPartialValuation::empty();
+    for var in context.parameter_variables() {
+        clause.set_value(*var, valuation.value(*var));
+    }
+    let bdd = context.bdd_variable_set().mk_conjunctive_clause(&clause);
+    GraphColors::new(bdd, context)
+}
+
+/// Transfer the `result` into the canonical context of the `graph` (see [sanitize_colored_vertices]).
+fn canonical_result(
+    graph: &SymbolicAsyncGraph,
+    result: &GraphColoredVertices,
+) -> Result<(GraphColoredVertices, SymbolicContext), String> {
+    let result = sanitize_colored_vertices(graph, &extend_to_graph(graph, result)?);
+    Ok((result, graph.symbolic_context().as_canonical_context()))
+}
+
+/// Pick `n` uniformly random states of the `result` (a result of the model checking on the
+/// `graph`, possibly sanitized), using the given `seed`. Each state is given by the values of the
+/// network variables (in their order), and it satisfies the result for at least one color.
+///
+/// Return an empty list if the result is empty.
+pub fn sample_states(
+    graph: &SymbolicAsyncGraph,
+    result: &GraphColoredVertices,
+    n: usize,
+    seed: u64,
+) -> Result<Vec<Vec<bool>>, String> {
+    let (result, context) = canonical_result(graph, result)?;
+    let vertices = result.vertices();
+    if vertices.is_empty() {
+        return Ok(Vec::new());
+    }
+    let state_variables: &[BddVariable] = context.state_variables();
+    Ok(sample_valuations(vertices.as_bdd(), n, seed)
+        .iter()
+        .map(|valuation| {
+            state_variables
+                .iter()
+                .map(|var| valuation.value(*var))
+                .collect()
+        })
+        .collect())
+}
+
+/// Pick `n` uniformly random colors of the `result` (a result of the model checking on the
+/// `graph`, possibly sanitized), using the given `seed`. The colors are given as singleton sets
+/// in the canonical symbolic context.
+///
+/// Return an empty list if the result is empty.
+pub fn sample_colors(
+    graph: &SymbolicAsyncGraph,
+    result: &GraphColoredVertices,
+    n: usize,
+    seed: u64,
+) -> Result<Vec<GraphColors>, String> {
+    let (result, context) = canonical_result(graph, result)?;
+    let colors = result.colors();
+    if colors.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(sample_valuations(colors.as_bdd(), n, seed)
+        .iter()
+        .map(|valuation| valuation_to_color(&context, valuation))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use crate::postprocessing::sampling::{sample_colors, sample_states};
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test sampling the states and colors of a result.
+    fn test_uniform_sampling() {
+        // `a` is a constant input, `b` is given by a parameter of arity one, `c` keeps its value
+        let bn = BooleanNetwork::try_from("a -? b\nc -? c\n$b: f(a)\n$c: c").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // the result has three satisfying states (for all colors)
+        let result = model_check_formula("b & (a | c)", &graph).unwrap();
+        let states = sample_states(&graph, &result, 300, 7).unwrap();
+        assert_eq!(states.len(), 300);
+        assert!(states.iter().all(|s| s[1] && (s[0] || s[2])));
+        // all three satisfying states are picked with the same probability
+        for expected in [[true, true, false], [false, true, true], [true, true, true]] {
+            let count = states.iter().filter(|s| **s == expected).count();
+            assert!(count > 70 && count < 130);
+        }
+        assert_eq!(states, sample_states(&graph, &result, 300, 7).unwrap());
+
+        // the fixed points of the form `b & ~a` only exist for the colors where `f(false)` holds
+        let result = model_check_formula("!{x}: AX {x} & b & ~a", &graph).unwrap();
+        let colors = sample_colors(&graph, &result, 20, 0).unwrap();
+        let expected = result.colors();
+        assert_eq!(expected.approx_cardinality(), 1.0);
+        assert!(colors
+            .iter()
+            .all(|c| c.is_singleton() && c.is_subset(&expected)));
+
+        let result = model_check_formula("b & ~b", &graph).unwrap();
+        assert!(sample_states(&graph, &result, 5, 0).unwrap().is_empty());
+        assert!(sample_colors(&graph, &result, 5, 0).unwrap().is_empty());
+    }
+}
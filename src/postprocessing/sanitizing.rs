@@ -1,8 +1,13 @@
 //! Contains operations to sanitize bdds of their additional symbolic variables,
 //! making them compatible with remaining biodivine libraries.
+use crate::evaluation::low_level_operations::{project_out_hctl_var, rename_hctl_var};
+use crate::mc_utils::{hctl_var_index, hctl_var_name, num_hctl_vars};
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::symbolic_async_graph::{
-    GraphColoredVertices, GraphColors, GraphVertices, SymbolicAsyncGraph,
+    GraphColoredVertices, GraphColors, GraphVertices, SymbolicAsyncGraph, SymbolicContext,
 };
+use std::collections::HashMap;
 
 /// Sanitize underlying BDD of a given coloured state set by removing the symbolic variables
 /// that were used for representing HCTL state-variables. At the moment, we remove all symbolic
@@ -18,6 +23,66 @@ pub fn sanitize_colored_vertices(
     GraphColoredVertices::new(sanitized_result_bdd, &canonical_context)
 }
 
+/// Sanitize underlying BDD of a given coloured state set by removing the symbolic variables of
+/// all HCTL variables except the `retained` ones, obtaining a relation between the states and the
+/// valuations of the retained (free) variables, e.g., pairs of states and their attractor states.
+///
+/// The HCTL variables are given by their canonical names used in the BDDs of the `stg` (`x`,
+/// `xx`, ...). The result is in a new symbolic context (also returned) that only has one set of
+/// extra variables per each retained variable. The retained variables are ordered by their
+/// indices, and the `i`-th of them is encoded by `context.get_extra_state_variable(var, i)` for
+/// each network variable `var` (the value of `var` in its state).
+///
+/// Return an error if a variable is not valid in the `stg`, or if the `stg` has no network.
+pub fn sanitize_colored_vertices_retaining(
+    stg: &SymbolicAsyncGraph,
+    colored_vertices: &GraphColoredVertices,
+    retained: &[&str],
+) -> Result<(GraphColoredVertices, SymbolicContext), String> {
+    let bn = stg
+        .as_network()
+        .ok_or("The graph has no underlying network.".to_string())?;
+    let context = stg.symbolic_context();
+    let num_hctl_vars = num_hctl_vars(stg);
+    let mut indices = Vec::new();
+    for name in retained {
+        match hctl_var_index(name) {
+            Some(index) if index < num_hctl_vars => indices.push(index),
+            _ => {
+                return Err(format!(
+                    "`{name}` is not a valid HCTL variable of the graph."
+                ))
+            }
+        }
+    }
+    indices.sort();
+    indices.dedup();
+
+    // project out the other HCTL variables
+    let mut set = colored_vertices.clone();
+    for index in (0..num_hctl_vars).filter(|i| !indices.contains(i)) {
+        set = project_out_hctl_var(stg, &set, hctl_var_name(index).as_str());
+    }
+    // move the retained variables to the first positions (keeping their order, so that the
+    // target positions are always free)
+    for (position, index) in indices.iter().enumerate() {
+        set = rename_hctl_var(stg, &set, *index, position)?;
+    }
+
+    let map_num_vars: HashMap<_, _> = bn
+        .variables()
+        .map(|var| (var, indices.len() as u16))
+        .collect();
+    let retaining_context = SymbolicContext::with_extra_state_variables(bn, &map_num_vars)?;
+    let bdd = retaining_context
+        .transfer_from(set.as_bdd(), context)
+        .ok_or("The set is not compatible with the graph.".to_string())?;
+    Ok((
+        GraphColoredVertices::new(bdd, &retaining_context),
+        retaining_context,
+    ))
+}
+
 /// Sanitize underlying BDD of a given colour set by removing the symbolic variables
 /// that were used for representing HCTL state-variables. At the moment, we remove all symbolic
 /// variables.
@@ -40,13 +105,138 @@ pub fn sanitize_vertices(stg: &SymbolicAsyncGraph, vertices: &GraphVertices) ->
     GraphVertices::new(sanitized_result_bdd, &canonical_context)
 }
 
+/// Transfer a coloured state set from the canonical symbolic context (e.g., a sanitized result)
+/// into the context of the given (extended) graph, restricting it to the graph's unit set. This
+/// is an inverse of [sanitize_colored_vertices]. Sets already in the context of the graph are only
+/// restricted to the unit set.
+///
+/// Return `None` if the set is not compatible with the graph.
+pub fn extend_colored_vertices(
+    stg: &SymbolicAsyncGraph,
+    colored_vertices: &GraphColoredVertices,
+) -> Option<GraphColoredVertices> {
+    let context = stg.symbolic_context();
+    let unit = stg.mk_unit_colored_vertices();
+    if colored_vertices.as_bdd().num_vars() == context.bdd_variable_set().num_vars() {
+        return Some(colored_vertices.intersect(&unit));
+    }
+    let canonical_context = context.as_canonical_context();
+    if colored_vertices.as_bdd().num_vars() != canonical_context.bdd_variable_set().num_vars() {
+        return None;
+    }
+    let extended_bdd = context.transfer_from(colored_vertices.as_bdd(), &canonical_context)?;
+    Some(GraphColoredVertices::new(
+        extended_bdd.and(unit.as_bdd()),
+        context,
+    ))
+}
+
+/// Transfer a colour set from the canonical symbolic context (e.g., a sanitized result) into the
+/// context of the given (extended) graph, restricting it to the graph's unit colours. This is an
+/// inverse of [sanitize_colors]. Sets already in the context of the graph are only restricted to
+/// the unit colours.
+///
+/// Return `None` if the set is not compatible with the graph.
+pub fn extend_colors(stg: &SymbolicAsyncGraph, colors: &GraphColors) -> Option<GraphColors> {
+    let context = stg.symbolic_context();
+    if colors.as_bdd().num_vars() == context.bdd_variable_set().num_vars() {
+        return Some(colors.intersect(&stg.mk_unit_colors()));
+    }
+    let canonical_context = context.as_canonical_context();
+    if colors.as_bdd().num_vars() != canonical_context.bdd_variable_set().num_vars() {
+        return None;
+    }
+    let extended_bdd = context.transfer_from(colors.as_bdd(), &canonical_context)?;
+    Some(GraphColors::new(extended_bdd, context).intersect(&stg.mk_unit_colors()))
+}
+
+/// Transfer the (possibly sanitized) `set` into the symbolic context of the `graph`.
+pub(crate) fn extend_to_graph(
+    graph: &SymbolicAsyncGraph,
+    set: &GraphColoredVertices,
+) -> Result<GraphColoredVertices, String> {
+    extend_colored_vertices(graph, set)
+        .ok_or("The set is not compatible with the graph.".to_string())
+}
+
+/// Result of the model checking in both forms: the dirty one (in the symbolic context of the
+/// graph, including the symbolic variables of HCTL variables), and the sanitized one (in the
+/// canonical context, with the free HCTL variables projected out). The sets can be moved between
+/// both forms by the helper methods, which need the graph used for the model checking.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DualResult {
+    /// The result in the symbolic context of the graph (not sanitized).
+    pub dirty: GraphColoredVertices,
+    /// The sanitized result (see [sanitize_colored_vertices]), with the free HCTL variables
+    /// projected out.
+    pub sanitized: GraphColoredVertices,
+}
+
+impl DualResult {
+    /// Create the dual result from the `dirty` result computed on the `stg`.
+    pub fn new(stg: &SymbolicAsyncGraph, dirty: GraphColoredVertices) -> DualResult {
+        DualResult {
+            sanitized: project_and_sanitize(stg, &dirty),
+            dirty,
+        }
+    }
+
+    /// Get the part of the dirty result with the states and colors of the `set` (typically a
+    /// subset of the sanitized result, such as a sample or a projection).
+    ///
+    /// Return `None` if the set is not compatible with the `stg`.
+    pub fn to_dirty(
+        &self,
+        stg: &SymbolicAsyncGraph,
+        set: &GraphColoredVertices,
+    ) -> Option<GraphColoredVertices> {
+        Some(self.dirty.intersect(&extend_colored_vertices(stg, set)?))
+    }
+
+    /// Sanitize the `set` (typically a subset of the dirty result, such as a result of a
+    /// follow-up computation), projecting out its free HCTL variables.
+    pub fn to_sanitized(
+        &self,
+        stg: &SymbolicAsyncGraph,
+        set: &GraphColoredVertices,
+    ) -> GraphColoredVertices {
+        project_and_sanitize(stg, set)
+    }
+
+    /// Sanitize the dirty result, but retain the given HCTL variables (see
+    /// [sanitize_colored_vertices_retaining]).
+    pub fn retaining(
+        &self,
+        stg: &SymbolicAsyncGraph,
+        retained: &[&str],
+    ) -> Result<(GraphColoredVertices, SymbolicContext), String> {
+        sanitize_colored_vertices_retaining(stg, &self.dirty, retained)
+    }
+}
+
+/// Project out all HCTL variables from the `colored_vertices`, and sanitize the result.
+fn project_and_sanitize(
+    stg: &SymbolicAsyncGraph,
+    colored_vertices: &GraphColoredVertices,
+) -> GraphColoredVertices {
+    let mut projected = colored_vertices.clone();
+    for index in 0..num_hctl_vars(stg) {
+        projected = project_out_hctl_var(stg, &projected, hctl_var_name(index).as_str());
+    }
+    sanitize_colored_vertices(stg, &projected)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::evaluation::algorithm::compute_steady_states;
+    use crate::evaluation::low_level_operations::project_out_hctl_var;
     use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_tree_dirty;
     use crate::postprocessing::sanitizing::{
-        sanitize_colored_vertices, sanitize_colors, sanitize_vertices,
+        sanitize_colored_vertices, sanitize_colored_vertices_retaining, sanitize_colors,
+        sanitize_vertices,
     };
+    use crate::preprocessing::parser::parse_hctl_formula;
     use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
     use biodivine_lib_param_bn::BooleanNetwork;
 
@@ -95,4 +285,36 @@ mod tests {
             fp_vertices_sanitized.as_bdd().to_string()
         );
     }
+
+    #[test]
+    /// Test sanitizing a relation between states and a free HCTL variable, retaining the variable.
+    fn test_sanitize_retaining() {
+        // two fixed points `00` and `11`, both reachable from the remaining two states
+        let bn = BooleanNetwork::try_from("a -> b\nb -> a\n$a: b\n$b: a").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+        // pairs of states and the attractor states reachable from them
+        let tree = parse_hctl_formula("EF ({xx} & AG EF {xx})").unwrap();
+        let relation = model_check_tree_dirty(tree, &stg).unwrap();
+
+        let (retained, context) =
+            sanitize_colored_vertices_retaining(&stg, &relation, &["xx"]).unwrap();
+        assert_eq!(context.num_extra_state_variables(), 2);
+        assert_eq!(retained.as_bdd().cardinality(), 6.0);
+        // the state `01` is paired with both fixed points (the first retained variable)
+        let [a, b]: [_; 2] = bn.variables().collect::<Vec<_>>().try_into().unwrap();
+        let variables = context.bdd_variable_set();
+        let pair_01_11 = context
+            .mk_state_variable_is_true(b)
+            .and_not(&context.mk_state_variable_is_true(a))
+            .and(&variables.mk_var(context.get_extra_state_variable(a, 0)))
+            .and(&variables.mk_var(context.get_extra_state_variable(b, 0)));
+        assert!(pair_01_11.imp(retained.as_bdd()).is_true());
+
+        // retaining no variables is the same as projecting them out and sanitizing
+        let (retained, _) = sanitize_colored_vertices_retaining(&stg, &relation, &[]).unwrap();
+        let projected = project_out_hctl_var(&stg, &relation, "xx");
+        assert_eq!(retained, sanitize_colored_vertices(&stg, &projected));
+        assert!(sanitize_colored_vertices_retaining(&stg, &relation, &["xxx"]).is_err());
+        assert!(sanitize_colored_vertices_retaining(&stg, &relation, &["y"]).is_err());
+    }
 }
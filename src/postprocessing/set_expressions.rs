@@ -0,0 +1,276 @@
+//! Expressions combining named result sets by set operations, such as
+//! `intersect(res1, res2) minus res3`.
+//!
+//! An expression is either a name of a set (consisting of letters, digits, and characters `_`,
+//! `-`, `.`), a function `union(e1, e2, ...)`, `intersect(e1, e2, ...)`, or `minus(e1, e2)`, or
+//! two expressions joined by an infix operator `union`, `intersect`, or `minus`. The infix
+//! operators have the same priority and are evaluated from left to right, so that
+//! `a union b minus c` means `(a union b) minus c`. Parentheses can be used as usual.
+
+use crate::evaluation::LabelToSetMap;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices;
+use std::fmt;
+
+/// Set operations supported in the expressions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SetOperation {
+    /// Union of the sets (`union`).
+    Union,
+    /// Intersection of the sets (`intersect`).
+    Intersect,
+    /// Difference of the sets (`minus`).
+    Minus,
+}
+
+impl SetOperation {
+    /// Get the operation with the given keyword, if there is one.
+    fn from_keyword(keyword: &str) -> Option<SetOperation> {
+        match keyword {
+            "union" => Some(SetOperation::Union),
+            "intersect" => Some(SetOperation::Intersect),
+            "minus" => Some(SetOperation::Minus),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SetOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetOperation::Union => write!(f, "union"),
+            SetOperation::Intersect => write!(f, "intersect"),
+            SetOperation::Minus => write!(f, "minus"),
+        }
+    }
+}
+
+/// Syntactic tree of an expression over named sets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SetExpression {
+    /// Reference to a named set.
+    Name(String),
+    /// Operation applied to the (two or more) arguments, from left to right.
+    Operation(SetOperation, Vec<SetExpression>),
+}
+
+impl fmt::Display for SetExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetExpression::Name(name) => write!(f, "{name}"),
+            SetExpression::Operation(operation, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+                write!(f, "{operation}({})", arguments.join(", "))
+            }
+        }
+    }
+}
+
+/// Tokens of the expressions.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Word(String),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+/// Check whether the character can be a part of a name (or of an operation keyword).
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/// Split the expression into tokens.
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::LeftParen),
+            ')' => tokens.push(Token::RightParen),
+            ',' => tokens.push(Token::Comma),
+            c if c.is_whitespace() => continue,
+            c if is_name_char(c) => {
+                let mut word = c.to_string();
+                while let Some(next) = chars.next_if(|c| is_name_char(*c)) {
+                    word.push(next);
+                }
+                tokens.push(Token::Word(word));
+            }
+            _ => return Err(format!("Unexpected character `{c}` in the expression.")),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser over the list of tokens.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Expected {expected:?}, found {token:?}.")),
+            None => Err(format!(
+                "Expected {expected:?}, found end of the expression."
+            )),
+        }
+    }
+
+    /// Parse a sequence of terms joined by infix operations (left-associative).
+    fn parse_expression(&mut self) -> Result<SetExpression, String> {
+        let mut expression = self.parse_term()?;
+        while let Some(Token::Word(word)) = self.peek() {
+            let Some(operation) = SetOperation::from_keyword(word) else {
+                return Err(format!("Expected an operation, found `{word}`."));
+            };
+            self.position += 1;
+            let right = self.parse_term()?;
+            expression = SetExpression::Operation(operation, vec![expression, right]);
+        }
+        Ok(expression)
+    }
+
+    /// Parse a name, a function application, or an expression in parentheses.
+    fn parse_term(&mut self) -> Result<SetExpression, String> {
+        match self.next() {
+            Some(Token::LeftParen) => {
+                let expression = self.parse_expression()?;
+                self.expect(Token::RightParen)?;
+                Ok(expression)
+            }
+            Some(Token::Word(word)) => match SetOperation::from_keyword(&word) {
+                Some(operation) => {
+                    self.expect(Token::LeftParen)?;
+                    let mut arguments = vec![self.parse_expression()?];
+                    while self.peek() == Some(&Token::Comma) {
+                        self.position += 1;
+                        arguments.push(self.parse_expression()?);
+                    }
+                    self.expect(Token::RightParen)?;
+                    if arguments.len() < 2 {
+                        return Err(format!("Operation `{operation}` needs two arguments."));
+                    }
+                    if operation == SetOperation::Minus && arguments.len() != 2 {
+                        return Err("Operation `minus` needs exactly two arguments.".to_string());
+                    }
+                    Ok(SetExpression::Operation(operation, arguments))
+                }
+                None => Ok(SetExpression::Name(word)),
+            },
+            Some(token) => Err(format!("Unexpected {token:?} in the expression.")),
+            None => Err("Unexpected end of the expression.".to_string()),
+        }
+    }
+}
+
+/// Parse the expression over named sets (see the module documentation).
+pub fn parse_set_expression(expression: &str) -> Result<SetExpression, String> {
+    let mut parser = Parser {
+        tokens: tokenize(expression)?,
+        position: 0,
+    };
+    let tree = parser.parse_expression()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected {token:?} in the expression."));
+    }
+    Ok(tree)
+}
+
+/// Evaluate the parsed expression over the named `sets` (which must share a symbolic context).
+pub fn evaluate_set_tree(
+    tree: &SetExpression,
+    sets: &LabelToSetMap,
+) -> Result<GraphColoredVertices, String> {
+    match tree {
+        SetExpression::Name(name) => sets
+            .get(name)
+            .cloned()
+            .ok_or(format!("Set `{name}` is not available.")),
+        SetExpression::Operation(operation, arguments) => {
+            let mut result = evaluate_set_tree(&arguments[0], sets)?;
+            for argument in &arguments[1..] {
+                let argument = evaluate_set_tree(argument, sets)?;
+                result = match operation {
+                    SetOperation::Union => result.union(&argument),
+                    SetOperation::Intersect => result.intersect(&argument),
+                    SetOperation::Minus => result.minus(&argument),
+                };
+            }
+            Ok(result)
+        }
+    }
+}
+
+/// Parse and evaluate the `expression` over the named `sets` (see the module documentation), such
+/// as the results loaded from a result archive.
+pub fn evaluate_set_expression(
+    expression: &str,
+    sets: &LabelToSetMap,
+) -> Result<GraphColoredVertices, String> {
+    evaluate_set_tree(&parse_set_expression(expression)?, sets)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::LabelToSetMap;
+    use crate::model_checking::model_check_formula;
+    use crate::postprocessing::set_expressions::{evaluate_set_expression, parse_set_expression};
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test parsing and evaluating expressions over named sets.
+    fn test_set_expressions() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -> c\n$b: a\n$c: b").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let mut sets = LabelToSetMap::new();
+        for (name, formula) in [("formula-0", "a"), ("formula-1", "b"), ("c.set", "c")] {
+            sets.insert(
+                name.to_string(),
+                model_check_formula(formula, &graph).unwrap(),
+            );
+        }
+        let expected = |formula: &str| model_check_formula(formula, &graph).unwrap();
+
+        let result = evaluate_set_expression("intersect(formula-0, formula-1) minus c.set", &sets);
+        assert_eq!(result.unwrap(), expected("a & b & ~c"));
+        let result = evaluate_set_expression("formula-0 union formula-1 minus c.set", &sets);
+        assert_eq!(result.unwrap(), expected("(a | b) & ~c"));
+        let result = evaluate_set_expression("formula-0 union (formula-1 minus c.set)", &sets);
+        assert_eq!(result.unwrap(), expected("a | (b & ~c)"));
+        let result = evaluate_set_expression("union(formula-0, formula-1, c.set)", &sets);
+        assert_eq!(result.unwrap(), expected("a | b | c"));
+
+        let tree = parse_set_expression("a union b minus minus(c, d)").unwrap();
+        assert_eq!(tree.to_string(), "minus(union(a, b), minus(c, d))");
+
+        let invalid = vec![
+            "",
+            "a union",
+            "a b",
+            "minus(a, b, c)",
+            "union(a)",
+            "(a union b",
+            "a + b",
+            "unknown",
+        ];
+        for expression in invalid {
+            assert!(evaluate_set_expression(expression, &sets).is_err());
+        }
+    }
+}
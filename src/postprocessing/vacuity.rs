@@ -0,0 +1,210 @@
+//! Detection of properties that are satisfied vacuously.
+//!
+//! A formula is satisfied vacuously with respect to its sub-formula, if replacing the sub-formula
+//! by a constant does not change the result. For instance, `AG (p => AF q)` holds vacuously if
+//! `p` is unreachable, because then also `AG (p => false)` holds. The occurrences with positive
+//! polarity are replaced by `false`, and the occurrences with negative polarity by `true`, so
+//! that the resulting witness formula implies the original one.
+//!
+//! All occurrences of proper sub-formulae are considered, except those under equivalence or xor
+//! (which have no polarity).
+
+use crate::mc_utils::check_hctl_var_support;
+use crate::model_checking::model_check_multiple_trees;
+use crate::preprocessing::hctl_tree::*;
+use crate::preprocessing::operator_enums::*;
+use crate::preprocessing::parser::parse_and_minimize_hctl_formula;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+
+/// Witness formula for a vacuity check, obtained by replacing `subformula` by a constant.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VacuityWitness {
+    pub subformula: HctlTreeNode,
+    pub witness: HctlTreeNode,
+}
+
+/// Check whether the node is a Boolean constant (which does not need a vacuity check).
+fn is_constant(node: &HctlTreeNode) -> bool {
+    matches!(
+        node.node_type,
+        NodeType::Terminal(Atomic::True) | NodeType::Terminal(Atomic::False)
+    )
+}
+
+/// Recursively collect the pairs `(subformula, node with the subformula replaced)` for the
+/// sub-tree `node`, including the node itself. The `polarity` of the node is `None` if it is
+/// under equivalence or xor (such nodes are not replaced, but their sub-formulae are searched).
+fn collect_replacements(
+    node: &HctlTreeNode,
+    polarity: Option<bool>,
+) -> Vec<(HctlTreeNode, HctlTreeNode)> {
+    let mut replacements = Vec::new();
+    // replace positive occurrences by `false`, negative ones by `true`
+    if let Some(p) = polarity {
+        if !is_constant(node) {
+            replacements.push((node.clone(), HctlTreeNode::mk_constant(!p)));
+        }
+    }
+    replacements.extend(collect_replacements_below(node, polarity));
+    replacements
+}
+
+/// Same as [collect_replacements], but only for the proper sub-formulae of the `node`.
+fn collect_replacements_below(
+    node: &HctlTreeNode,
+    polarity: Option<bool>,
+) -> Vec<(HctlTreeNode, HctlTreeNode)> {
+    let mut replacements = Vec::new();
+    match &node.node_type {
+        NodeType::Terminal(_) => {}
+        NodeType::Unary(op, child) => {
+            // all the temporal operators are monotone in their sub-formulae
+            let child_polarity = if *op == UnaryOp::Not {
+                polarity.map(|p| !p)
+            } else {
+                polarity
+            };
+            for (sub, replaced) in collect_replacements(child, child_polarity) {
+                replacements.push((sub, HctlTreeNode::mk_unary(replaced, op.clone())));
+            }
+        }
+        NodeType::Binary(op, left, right) => {
+            let (left_polarity, right_polarity) = match op {
+                BinaryOp::Imp => (polarity.map(|p| !p), polarity),
+                BinaryOp::Iff | BinaryOp::Xor => (None, None),
+                _ => (polarity, polarity),
+            };
+            for (sub, replaced) in collect_replacements(left, left_polarity) {
+                let node = HctlTreeNode::mk_binary(replaced, *right.clone(), op.clone());
+                replacements.push((sub, node));
+            }
+            for (sub, replaced) in collect_replacements(right, right_polarity) {
+                let node = HctlTreeNode::mk_binary(*left.clone(), replaced, op.clone());
+                replacements.push((sub, node));
+            }
+        }
+        NodeType::Hybrid(op, var, domain, child) => {
+            for (sub, replaced) in collect_replacements(child, polarity) {
+                let node =
+                    HctlTreeNode::mk_hybrid_with_domain(replaced, var, domain.clone(), op.clone());
+                replacements.push((sub, node));
+            }
+        }
+    }
+    replacements
+}
+
+/// Generate the witness formulae for the vacuity checks of the formula given by its syntax tree,
+/// one for each occurrence of a (non-constant) proper sub-formula with a defined polarity.
+pub fn generate_vacuity_witnesses(tree: &HctlTreeNode) -> Vec<VacuityWitness> {
+    collect_replacements_below(tree, Some(true))
+        .into_iter()
+        .map(|(subformula, witness)| VacuityWitness {
+            subformula,
+            witness,
+        })
+        .collect()
+}
+
+/// Check whether the `formula` is satisfied vacuously on the `graph`, and return the witnesses
+/// of the vacuity. That is, the witness formulae that have the same (non-empty) result as the
+/// original formula.
+///
+/// The original formula and all witnesses are evaluated together, so that the results of the
+/// shared sub-formulae are computed only once.
+/// The `graph` object MUST support enough sets of symbolic variables to represent all occurring HCTL vars.
+pub fn find_vacuity_witnesses(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Vec<VacuityWitness>, String> {
+    let tree = parse_and_minimize_hctl_formula(graph.symbolic_context(), formula)?;
+    if !check_hctl_var_support(graph, tree.clone())? {
+        return Err("Graph does not support enough HCTL state variables".to_string());
+    }
+    let witnesses = generate_vacuity_witnesses(&tree);
+    let mut trees = vec![tree];
+    trees.extend(witnesses.iter().map(|w| w.witness.clone()));
+    let results = model_check_multiple_trees(trees, graph)?;
+
+    let original = &results[0];
+    if original.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(witnesses
+        .into_iter()
+        .zip(results.iter().skip(1))
+        .filter(|(_, result)| *result == original)
+        .map(|(witness, _)| witness)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::postprocessing::vacuity::{find_vacuity_witnesses, generate_vacuity_witnesses};
+    use crate::preprocessing::parser::parse_hctl_formula;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test generating the witness formulae with respect to the polarity of sub-formulae.
+    fn test_generate_witnesses() {
+        let tree = parse_hctl_formula("AG (a => AF b) & ~(b => EX a)").unwrap();
+        let witnesses = generate_vacuity_witnesses(&tree)
+            .into_iter()
+            .map(|w| (w.subformula, w.witness))
+            .collect::<Vec<_>>();
+        // all proper sub-formulae are replaced, with respect to their polarity
+        let expected = [
+            ("AG (a => AF b)", "false & ~(b => EX a)"),
+            ("a => AF b", "AG false & ~(b => EX a)"),
+            ("a", "AG (true => AF b) & ~(b => EX a)"),
+            ("AF b", "AG (a => false) & ~(b => EX a)"),
+            ("b", "AG (a => AF false) & ~(b => EX a)"),
+            ("~(b => EX a)", "AG (a => AF b) & false"),
+            ("b => EX a", "AG (a => AF b) & ~true"),
+            ("b", "AG (a => AF b) & ~(false => EX a)"),
+            ("EX a", "AG (a => AF b) & ~(b => true)"),
+            ("a", "AG (a => AF b) & ~(b => EX true)"),
+        ];
+        assert_eq!(witnesses.len(), expected.len());
+        for (subformula, witness) in expected {
+            let subformula = parse_hctl_formula(subformula).unwrap();
+            let witness = parse_hctl_formula(witness).unwrap();
+            assert!(witnesses.contains(&(subformula, witness)));
+        }
+
+        // no witnesses under equivalence
+        let tree = parse_hctl_formula("(a => b) <=> EF b").unwrap();
+        assert!(generate_vacuity_witnesses(&tree).is_empty());
+    }
+
+    #[test]
+    /// Test detecting vacuously satisfied properties.
+    fn test_vacuity_detection() {
+        // `a` is eventually inactive forever, and `b` keeps its value
+        let bn = BooleanNetwork::try_from("b -> b\n$a: false\n$b: b").unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+
+        // holds only where `b` is never active, so `AF a` (and its `a`) does not matter
+        let witnesses = find_vacuity_witnesses("AG (b => AF a)", &graph).unwrap();
+        let subformulae = witnesses
+            .iter()
+            .map(|w| w.subformula.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(subformulae, vec!["(AF a)", "a"]);
+
+        // `AF ~a` holds everywhere, so the antecedent does not matter
+        let witnesses = find_vacuity_witnesses("AG (a => AF ~a)", &graph).unwrap();
+        let subformulae = witnesses
+            .iter()
+            .map(|w| w.subformula.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(subformulae, vec!["a"]);
+
+        // non-vacuous property
+        let witnesses = find_vacuity_witnesses("AG (b => AX b)", &graph).unwrap();
+        assert!(witnesses.is_empty());
+    }
+}
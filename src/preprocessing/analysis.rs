@@ -0,0 +1,606 @@
+//! Structural analysis of syntactic trees of HCTL formulae, i.e., which variables are free, what
+//! is the scope of each quantifier, which propositions occur under it, and how many copies of the
+//! symbolic variables the model checking needs.
+//!
+//! The analysis works with the variable names of the given tree (e.g., as returned by
+//! [crate::preprocessing::parser::parse_hctl_formula]), so it can be used on formulae that
+//! are not valid yet, such as those with free variables.
+//!
+//! Further, [validate_formula] validates a formula against a network without building its
+//! transition graph, and collects all the problems found as structured diagnostics (e.g., for
+//! instant feedback in front-ends). These also include the warnings of [lint_formula] about
+//! suspicious (but valid) constructs, such as unused variables or constant sub-formulae.
+
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType, VarDomain};
+use crate::preprocessing::operator_enums::{Atomic, BinaryOp, HybridOp, UnaryOp};
+use crate::preprocessing::parser::parse_hctl_formula_with_recovery;
+use crate::preprocessing::utils::validate_props_and_rename_vars;
+
+use biodivine_lib_param_bn::symbolic_async_graph::SymbolicContext;
+use biodivine_lib_param_bn::BooleanNetwork;
+use std::cmp::max;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Scope of a single quantifier (binder `!`, existential `3`, or universal `V`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuantifierScope {
+    /// The quantifier operator.
+    pub operator: HybridOp,
+    /// Name of the quantified variable.
+    pub variable: String,
+    /// Domain of the variable, if it is restricted.
+    pub domain: Option<String>,
+    /// The sub-formula in which the variable is quantified.
+    pub scope: String,
+    /// Variables of the enclosing quantifiers (from the outermost one).
+    pub enclosing: Vec<String>,
+    /// Propositions occurring in the scope, including the variables of state literals (sorted).
+    pub propositions: Vec<String>,
+    /// True if the variable is referenced in its scope (by `{x}`, `@{x}`, or a wild-card relation).
+    pub is_used: bool,
+}
+
+/// Structural information about a formula, see [analyse_formula_structure].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FormulaStructure {
+    /// Variables referenced outside the scope of their quantifiers (sorted).
+    pub free_variables: Vec<String>,
+    /// Scopes of all quantifiers (in the pre-order of the tree).
+    pub quantifiers: Vec<QuantifierScope>,
+    /// Number of copies of the symbolic variables needed to evaluate the formula, i.e., the
+    /// number of its free variables and the maximal nesting depth of its quantifiers (the
+    /// variables of quantifiers that are not nested share the same copy).
+    pub num_variable_copies: usize,
+}
+
+/// Analyse the structure of the formula given by its syntactic `tree`.
+pub fn analyse_formula_structure(tree: &HctlTreeNode) -> FormulaStructure {
+    let mut structure = FormulaStructure::default();
+    let mut free_variables = BTreeSet::new();
+    let max_depth = analyse_recursive(tree, &mut Vec::new(), &mut structure, &mut free_variables);
+    structure.num_variable_copies = free_variables.len() + max_depth;
+    structure.free_variables = free_variables.into_iter().collect();
+    structure
+}
+
+/// Recursively collect the quantifier scopes and free variables of the `node`, with the variables
+/// of the `enclosing` quantifiers. Return the maximal nesting depth of quantifiers in the node.
+fn analyse_recursive(
+    node: &HctlTreeNode,
+    enclosing: &mut Vec<String>,
+    structure: &mut FormulaStructure,
+    free_variables: &mut BTreeSet<String>,
+) -> usize {
+    // variables referenced outside the scope of their quantifiers are free
+    let mut reference = |var: &String, enclosing: &Vec<String>| {
+        if !enclosing.contains(var) {
+            free_variables.insert(var.clone());
+        }
+    };
+    match &node.node_type {
+        NodeType::Terminal(Atomic::Var(var)) => {
+            reference(var, enclosing);
+            0
+        }
+        NodeType::Terminal(Atomic::WildCardRelation(_, vars)) => {
+            vars.iter().for_each(|var| reference(var, enclosing));
+            0
+        }
+        NodeType::Terminal(_) => 0,
+        NodeType::Unary(_, child) => analyse_recursive(child, enclosing, structure, free_variables),
+        NodeType::Binary(_, left, right) => max(
+            analyse_recursive(left, enclosing, structure, free_variables),
+            analyse_recursive(right, enclosing, structure, free_variables),
+        ),
+        NodeType::Hybrid(HybridOp::Jump, var, _, child) => {
+            reference(var, enclosing);
+            analyse_recursive(child, enclosing, structure, free_variables)
+        }
+        NodeType::Hybrid(op, var, domain, child) => {
+            structure.quantifiers.push(QuantifierScope {
+                operator: op.clone(),
+                variable: var.clone(),
+                domain: domain.as_ref().map(VarDomain::label),
+                scope: child.formula_str.clone(),
+                enclosing: enclosing.clone(),
+                propositions: collect_propositions(child).into_iter().collect(),
+                is_used: references_variable(child, var),
+            });
+            enclosing.push(var.clone());
+            let depth = analyse_recursive(child, enclosing, structure, free_variables);
+            enclosing.pop();
+            depth + 1
+        }
+    }
+}
+
+/// Stage of the validation in which a problem was found.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiagnosticKind {
+    /// The formula can not be tokenized or parsed.
+    Syntax,
+    /// A proposition (or a variable of a state literal or a labelled operator) is not valid.
+    Proposition,
+    /// An HCTL variable is free, or quantified several times in one sub-formula.
+    Variable,
+}
+
+/// A single problem found in a formula.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// Stage of the validation that found the problem.
+    pub kind: DiagnosticKind,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            DiagnosticKind::Syntax => "syntax error",
+            DiagnosticKind::Proposition => "invalid proposition",
+            DiagnosticKind::Variable => "invalid variable",
+        };
+        write!(f, "{kind}: {}", self.message)
+    }
+}
+
+/// Result of the validation of a formula, see [validate_formula].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FormulaDiagnostics {
+    /// All problems found in the formula (empty if the formula is valid).
+    pub diagnostics: Vec<Diagnostic>,
+    /// Structure of the formula, if it could be parsed.
+    pub structure: Option<FormulaStructure>,
+    /// The formula with the variables renamed to their canonical names, if it is valid.
+    pub canonical_formula: Option<String>,
+    /// Warnings about suspicious constructs (see [lint_formula]), if the formula could be parsed.
+    pub warnings: Vec<LintWarning>,
+}
+
+impl FormulaDiagnostics {
+    /// Check whether the formula is valid (there are no problems).
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+/// Validate the `formula` against the network `bn`, i.e., parse it, check its propositions, and
+/// analyse its variables, using only the symbolic context of the network (the transition graph
+/// is not built). All the problems found are collected, so that they can be reported together.
+///
+/// Return an error if the symbolic context of the network can not be created.
+pub fn validate_formula(formula: &str, bn: &BooleanNetwork) -> Result<FormulaDiagnostics, String> {
+    let context = SymbolicContext::new(bn)?;
+    let mut result = FormulaDiagnostics::default();
+    let tree = match parse_hctl_formula_with_recovery(formula) {
+        Ok(tree) => tree,
+        Err(errors) => {
+            result
+                .diagnostics
+                .extend(errors.iter().map(|error| Diagnostic {
+                    kind: DiagnosticKind::Syntax,
+                    message: error.to_string(),
+                }));
+            return Ok(result);
+        }
+    };
+
+    for name in collect_network_variable_references(&tree) {
+        if context.find_network_variable(name.as_str()).is_none() {
+            result.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::Proposition,
+                message: format!("There is no network variable named {name}."),
+            });
+        }
+    }
+    result.warnings = lint_formula(&tree);
+    let structure = analyse_formula_structure(&tree);
+    for var in &structure.free_variables {
+        result.diagnostics.push(Diagnostic {
+            kind: DiagnosticKind::Variable,
+            message: format!("Variable {var} is free."),
+        });
+    }
+    for quantifier in &structure.quantifiers {
+        if quantifier.enclosing.contains(&quantifier.variable) {
+            result.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::Variable,
+                message: format!(
+                    "Variable {} is quantified several times in one sub-formula.",
+                    quantifier.variable
+                ),
+            });
+        }
+    }
+    result.structure = Some(structure);
+
+    // the remaining checks (e.g., of state literals or inline domains) are done by the renaming
+    if result.is_valid() {
+        match validate_props_and_rename_vars(tree, &context) {
+            Ok(renamed_tree) => result.canonical_formula = Some(renamed_tree.to_string()),
+            Err(message) => result.diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::Proposition,
+                message,
+            }),
+        }
+    }
+    Ok(result)
+}
+
+/// Kind of a suspicious construct found by [lint_formula].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LintKind {
+    /// A binder `!{x}` whose variable is never used.
+    UnusedBinder,
+    /// A quantifier `3{x}` or `V{x}` whose variable never occurs in its scope.
+    UnusedQuantifier,
+    /// A jump `@{x}` directly under the binder `!{x}` (it jumps to the current state).
+    RedundantJump,
+    /// A double negation `~~phi`.
+    DoubleNegation,
+    /// A sub-formula that is syntactically constant, such as `phi & ~phi` or `EF true`.
+    ConstantSubformula,
+}
+
+/// A warning about a suspicious construct found by [lint_formula].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LintWarning {
+    /// Kind of the construct.
+    pub kind: LintKind,
+    /// The sub-formula with the construct.
+    pub subformula: String,
+    /// Description of the construct.
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Find suspicious constructs in the formula given by its syntactic `tree`, i.e., those that are
+/// valid, but have no effect or likely do not express what was intended: unused variables,
+/// redundant jumps, double negations, and syntactically constant sub-formulae (only the largest
+/// ones are reported). The warnings are returned in the pre-order of the tree.
+pub fn lint_formula(tree: &HctlTreeNode) -> Vec<LintWarning> {
+    let mut warnings = Vec::new();
+    lint_recursive(tree, false, &mut warnings);
+    warnings
+}
+
+/// Recursively collect the warnings for the `node`. If `in_constant` is true, the node is a part
+/// of a larger constant sub-formula (which is already reported).
+fn lint_recursive(node: &HctlTreeNode, in_constant: bool, warnings: &mut Vec<LintWarning>) {
+    let mut warn = |kind, message| {
+        warnings.push(LintWarning {
+            kind,
+            subformula: node.formula_str.clone(),
+            message,
+        })
+    };
+    let constant = constant_value(node);
+    if let (Some(value), false) = (constant, in_constant) {
+        if !matches!(node.node_type, NodeType::Terminal(_)) {
+            let value = if value { "true" } else { "false" };
+            warn(
+                LintKind::ConstantSubformula,
+                format!("Sub-formula `{node}` is always {value}."),
+            );
+        }
+    }
+    match &node.node_type {
+        NodeType::Unary(UnaryOp::Not, child)
+            if matches!(child.node_type, NodeType::Unary(UnaryOp::Not, _)) =>
+        {
+            warn(
+                LintKind::DoubleNegation,
+                format!("Double negation `{node}` can be removed."),
+            );
+        }
+        NodeType::Hybrid(op, var, _, child) if *op != HybridOp::Jump => {
+            if !references_variable(child, var) {
+                let (kind, operator) = match op {
+                    HybridOp::Bind => (LintKind::UnusedBinder, "binder"),
+                    _ => (LintKind::UnusedQuantifier, "quantifier"),
+                };
+                warn(
+                    kind,
+                    format!(
+                        "Variable {var} is never used, so the {operator} `{op}{{{var}}}` \
+                        has no effect."
+                    ),
+                );
+            }
+            if let NodeType::Hybrid(HybridOp::Jump, jump_var, _, _) = &child.node_type {
+                if *op == HybridOp::Bind && jump_var == var {
+                    warn(
+                        LintKind::RedundantJump,
+                        format!("Jump `@{{{var}}}` directly under `!{{{var}}}` has no effect."),
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let in_constant = in_constant || constant.is_some();
+    match &node.node_type {
+        NodeType::Terminal(_) => {}
+        NodeType::Unary(_, child) | NodeType::Hybrid(_, _, _, child) => {
+            lint_recursive(child, in_constant, warnings)
+        }
+        NodeType::Binary(_, left, right) => {
+            lint_recursive(left, in_constant, warnings);
+            lint_recursive(right, in_constant, warnings);
+        }
+    }
+}
+
+/// Get the value of the `node`, if it is syntactically constant (i.e., it is constant regardless
+/// of the propositions and the dynamics of the network).
+fn constant_value(node: &HctlTreeNode) -> Option<bool> {
+    // operands that are equal or complementary (one is a negation of the other)
+    let is_complement = |left: &HctlTreeNode, right: &HctlTreeNode| {
+        let negates = |a: &HctlTreeNode, b: &HctlTreeNode| matches!(&a.node_type, NodeType::Unary(UnaryOp::Not, inner) if **inner == *b);
+        negates(left, right) || negates(right, left)
+    };
+    match &node.node_type {
+        NodeType::Terminal(Atomic::True) => Some(true),
+        NodeType::Terminal(Atomic::False) => Some(false),
+        NodeType::Terminal(_) => None,
+        NodeType::Unary(UnaryOp::Not, child) => constant_value(child).map(|value| !value),
+        // paths start in the current state, so the future (and past) of a constant is constant
+        NodeType::Unary(
+            UnaryOp::EF | UnaryOp::AF | UnaryOp::EG | UnaryOp::AG | UnaryOp::EP,
+            child,
+        ) => constant_value(child),
+        // successors can be missing, so the next-state operators are not constant
+        NodeType::Unary(..) => None,
+        NodeType::Binary(op, left, right) => {
+            let (left_value, right_value) = (constant_value(left), constant_value(right));
+            match op {
+                BinaryOp::And => match (left_value, right_value) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ if is_complement(left, right) => Some(false),
+                    _ => None,
+                },
+                BinaryOp::Or => match (left_value, right_value) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ if is_complement(left, right) => Some(true),
+                    _ => None,
+                },
+                BinaryOp::Imp => match (left_value, right_value) {
+                    (Some(false), _) | (_, Some(true)) => Some(true),
+                    (Some(true), Some(false)) => Some(false),
+                    _ if left == right => Some(true),
+                    _ => None,
+                },
+                BinaryOp::Iff | BinaryOp::Xor => {
+                    let is_iff = *op == BinaryOp::Iff;
+                    match (left_value, right_value) {
+                        (Some(l), Some(r)) => Some((l == r) == is_iff),
+                        _ if left == right => Some(is_iff),
+                        _ if is_complement(left, right) => Some(!is_iff),
+                        _ => None,
+                    }
+                }
+                // until is decided by its right operand, if it is constant
+                BinaryOp::EU | BinaryOp::AU => right_value,
+                BinaryOp::EW | BinaryOp::AW => match (left_value, right_value) {
+                    (_, Some(true)) | (Some(true), _) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                },
+            }
+        }
+        // quantifiers over a restricted domain can range over an empty set
+        NodeType::Hybrid(HybridOp::Exists | HybridOp::Forall, _, Some(_), _) => None,
+        NodeType::Hybrid(_, _, _, child) => constant_value(child),
+    }
+}
+
+/// Collect the names of the network variables referenced by the `node` (by propositions, state
+/// literals, and labelled operators such as `EX_{a}`).
+fn collect_network_variable_references(node: &HctlTreeNode) -> BTreeSet<String> {
+    let mut names = collect_propositions(node);
+    collect_labels_recursive(node, &mut names);
+    names
+}
+
+/// Recursively collect the variables labelling the operators (such as `EX_{a}`) of the `node`.
+fn collect_labels_recursive(node: &HctlTreeNode, names: &mut BTreeSet<String>) {
+    match &node.node_type {
+        NodeType::Terminal(_) => {}
+        NodeType::Unary(op, child) => {
+            if let UnaryOp::EXVar(name) | UnaryOp::AXVar(name) = op {
+                names.insert(name.clone());
+            }
+            collect_labels_recursive(child, names);
+        }
+        NodeType::Binary(_, left, right) => {
+            collect_labels_recursive(left, names);
+            collect_labels_recursive(right, names);
+        }
+        NodeType::Hybrid(_, _, _, child) => collect_labels_recursive(child, names),
+    }
+}
+
+/// Collect the propositions of the `node`, including the variables of its state literals.
+fn collect_propositions(node: &HctlTreeNode) -> BTreeSet<String> {
+    match &node.node_type {
+        NodeType::Terminal(Atomic::Prop(name)) => BTreeSet::from([name.clone()]),
+        NodeType::Terminal(Atomic::State(values, _)) => {
+            values.iter().map(|(name, _)| name.clone()).collect()
+        }
+        NodeType::Terminal(_) => BTreeSet::new(),
+        NodeType::Unary(_, child) | NodeType::Hybrid(_, _, _, child) => collect_propositions(child),
+        NodeType::Binary(_, left, right) => {
+            let mut propositions = collect_propositions(left);
+            propositions.extend(collect_propositions(right));
+            propositions
+        }
+    }
+}
+
+/// Check whether the `node` references the variable `var` (not shadowed by a nested quantifier).
+fn references_variable(node: &HctlTreeNode, var: &String) -> bool {
+    match &node.node_type {
+        NodeType::Terminal(Atomic::Var(name)) => name == var,
+        NodeType::Terminal(Atomic::WildCardRelation(_, vars)) => vars.contains(var),
+        NodeType::Terminal(_) => false,
+        NodeType::Unary(_, child) => references_variable(child, var),
+        NodeType::Binary(_, left, right) => {
+            references_variable(left, var) || references_variable(right, var)
+        }
+        NodeType::Hybrid(HybridOp::Jump, name, _, child) => {
+            name == var || references_variable(child, var)
+        }
+        NodeType::Hybrid(_, name, _, child) => name != var && references_variable(child, var),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preprocessing::analysis::{
+        analyse_formula_structure, lint_formula, validate_formula, DiagnosticKind, LintKind,
+    };
+    use crate::preprocessing::operator_enums::HybridOp;
+    use crate::preprocessing::parser::parse_hctl_formula;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test the structural analysis of a closed formula with nested and sequential quantifiers.
+    fn test_closed_formula_structure() {
+        let tree =
+            parse_hctl_formula("(!{x}: AX ({x} & (3{y}: @{y}: a))) & (V{z}: EF #state{b=1, *})")
+                .unwrap();
+        let structure = analyse_formula_structure(&tree);
+        assert!(structure.free_variables.is_empty());
+        // `y` is nested in `x`, while `z` can reuse the first copy
+        assert_eq!(structure.num_variable_copies, 2);
+
+        let scopes: Vec<(HybridOp, &str, Vec<String>, bool)> = structure
+            .quantifiers
+            .iter()
+            .map(|q| {
+                (
+                    q.operator.clone(),
+                    q.variable.as_str(),
+                    q.enclosing.clone(),
+                    q.is_used,
+                )
+            })
+            .collect();
+        assert_eq!(
+            scopes,
+            vec![
+                (HybridOp::Bind, "x", vec![], true),
+                (HybridOp::Exists, "y", vec!["x".to_string()], true),
+                (HybridOp::Forall, "z", vec![], false),
+            ]
+        );
+        assert_eq!(structure.quantifiers[0].propositions, vec!["a"]);
+        assert_eq!(structure.quantifiers[2].propositions, vec!["b"]);
+    }
+
+    #[test]
+    /// Test the structural analysis of a formula with free variables.
+    fn test_free_variables_structure() {
+        let tree = parse_hctl_formula("EF ({x} & (@{y}: 3{x}: AX {x}))").unwrap();
+        let structure = analyse_formula_structure(&tree);
+        assert_eq!(structure.free_variables, vec!["x", "y"]);
+        assert_eq!(structure.num_variable_copies, 3);
+        assert_eq!(structure.quantifiers.len(), 1);
+        assert!(structure.quantifiers[0].is_used);
+        assert!(structure.quantifiers[0].propositions.is_empty());
+    }
+
+    #[test]
+    /// Test the validation of formulae collecting all the problems.
+    fn test_validate_formula() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a").unwrap();
+
+        let diagnostics = validate_formula("!{x}: AX ({x} & EF a)", &bn).unwrap();
+        assert!(diagnostics.is_valid());
+        assert!(diagnostics.warnings.is_empty());
+        assert_eq!(diagnostics.structure.unwrap().num_variable_copies, 1);
+        assert!(diagnostics.canonical_formula.is_some());
+
+        let diagnostics = validate_formula("EF (a & ", &bn).unwrap();
+        assert_eq!(diagnostics.diagnostics.len(), 2);
+        assert_eq!(diagnostics.diagnostics[0].kind, DiagnosticKind::Syntax);
+        assert!(diagnostics.structure.is_none());
+
+        // all problems are reported together
+        let diagnostics = validate_formula("EX_{c} ({y} & d & (!{x}: !{x}: a))", &bn).unwrap();
+        let kinds: Vec<DiagnosticKind> = diagnostics.diagnostics.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiagnosticKind::Proposition,
+                DiagnosticKind::Proposition,
+                DiagnosticKind::Variable,
+                DiagnosticKind::Variable,
+            ]
+        );
+        assert_eq!(
+            diagnostics.diagnostics[2].to_string(),
+            "invalid variable: Variable y is free."
+        );
+        assert!(diagnostics.canonical_formula.is_none());
+
+        // problems found by the final renaming pass are reported as well
+        let diagnostics = validate_formula("#state{a=1}", &bn).unwrap();
+        assert!(!diagnostics.is_valid());
+    }
+
+    #[test]
+    /// Test the warnings about suspicious constructs.
+    fn test_lint_formula() {
+        let kinds = |formula: &str| -> Vec<LintKind> {
+            let tree = parse_hctl_formula(formula).unwrap();
+            lint_formula(&tree).iter().map(|w| w.kind).collect()
+        };
+
+        // no warnings for usual formulae
+        assert!(kinds("!{x}: AG EF {x}").is_empty());
+        assert!(kinds("3{x}: 3{y}: (@{x}: ~{y} & AX {x}) & (@{y}: AX {y})").is_empty());
+        assert!(kinds("EF (a & true) | AX false").is_empty());
+
+        assert_eq!(kinds("!{x}: AX a"), vec![LintKind::UnusedBinder]);
+        assert_eq!(
+            kinds("3{x}: V{y}: @{x}: a"),
+            vec![LintKind::UnusedQuantifier]
+        );
+        assert_eq!(kinds("!{x}: @{x}: AX {x}"), vec![LintKind::RedundantJump]);
+        assert_eq!(kinds("EF ~~a"), vec![LintKind::DoubleNegation]);
+
+        // only the largest constant sub-formulae are reported
+        let tree = parse_hctl_formula("EF (b | AG (a & ~a)) & (EF c => EF c)").unwrap();
+        let warnings = lint_formula(&tree);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings
+            .iter()
+            .all(|w| w.kind == LintKind::ConstantSubformula));
+        assert_eq!(
+            warnings[0].message,
+            "Sub-formula `(AG (a & (~a)))` is always false."
+        );
+        assert_eq!(warnings[1].subformula, "((EF c) => (EF c))");
+
+        // quantifiers over restricted domains can range over an empty set
+        let tree = parse_hctl_formula("3{x} in [a]: @{x}: true").unwrap();
+        let warnings = lint_formula(&tree);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].subformula.starts_with("(@{x}"));
+        let tree = parse_hctl_formula("3{x}: @{x}: true").unwrap();
+        let warnings = lint_formula(&tree);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].subformula.starts_with("(3{x}"));
+    }
+}
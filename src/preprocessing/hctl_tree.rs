@@ -3,6 +3,7 @@
 use crate::preprocessing::operator_enums::*;
 use crate::preprocessing::parser::parse_hctl_tokens;
 use crate::preprocessing::tokenizer::HctlToken;
+use crate::preprocessing::tokenizer::FAIR_ATTRACTORS_DOMAIN;
 
 use rand::prelude::StdRng;
 use rand::{RngCore, SeedableRng};
@@ -15,14 +16,46 @@ use std::fmt;
 ///     - A "terminal" node, containing a single atomic value (proposition, variable, ...).
 ///     - A "unary" node, with a `UnaryOp` and a sub-formula.
 ///     - A "binary" node, with a `BinaryOp` and two sub-formulae.
-///     - A "hybrid" node, with a `HybridOp`, a string variable name, an optional string label
-///     for a variable's domain, and a sub-formula.
+///     - A "hybrid" node, with a `HybridOp`, a string variable name, an optional variable's
+///     domain, and a sub-formula.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum NodeType {
     Terminal(Atomic),
     Unary(UnaryOp, Box<HctlTreeNode>),
     Binary(BinaryOp, Box<HctlTreeNode>, Box<HctlTreeNode>),
-    Hybrid(HybridOp, String, Option<String>, Box<HctlTreeNode>),
+    Hybrid(HybridOp, String, Option<VarDomain>, Box<HctlTreeNode>),
+}
+
+/// Enum of possible domains of a variable of a hybrid operator.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum VarDomain {
+    /// Domain given by its label, i.e., a wild-card domain (`in %domain%`), or the built-in
+    /// domain of fair attractors (`in fair_attractors`).
+    Label(String),
+    /// Domain given inline by a propositional expression (`in [expression]`), already parsed.
+    Inline(Box<HctlTreeNode>),
+}
+
+impl VarDomain {
+    /// Get the label identifying the set of the domain's states. The label of an inline domain
+    /// is its (canonical) expression in square brackets.
+    pub fn label(&self) -> String {
+        match self {
+            VarDomain::Label(label) => label.clone(),
+            VarDomain::Inline(tree) => format!("[{tree}]"),
+        }
+    }
+}
+
+impl fmt::Display for VarDomain {
+    /// Write the domain as it appears in a formula.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarDomain::Label(label) if label == FAIR_ATTRACTORS_DOMAIN => write!(f, "{label}"),
+            VarDomain::Label(label) => write!(f, "%{label}%"),
+            VarDomain::Inline(tree) => write!(f, "[{tree}]"),
+        }
+    }
 }
 
 /// A single node in a syntax tree of a HCTL formula.
@@ -50,17 +83,30 @@ impl HctlTreeNode {
 
     /// Create a "hybrid" [HctlTreeNode] from the given arguments.
     ///
-    /// See also [NodeType::Hybrid].
+    /// The optional domain is a name of a wild-card domain (or one of the built-in domains).
+    /// To use an inline domain, see [HctlTreeNode::mk_hybrid_with_domain].
     pub fn mk_hybrid(
         child: HctlTreeNode,
         var: &str,
         domain: Option<String>,
         op: HybridOp,
     ) -> HctlTreeNode {
-        let domain_string = if domain.is_some() {
-            format!(" in %{}%", domain.clone().unwrap())
-        } else {
-            String::new()
+        Self::mk_hybrid_with_domain(child, var, domain.map(VarDomain::Label), op)
+    }
+
+    /// Create a "hybrid" [HctlTreeNode] from the given arguments, with an arbitrary
+    /// [VarDomain] (a named domain or an inline expression).
+    ///
+    /// See also [NodeType::Hybrid].
+    pub fn mk_hybrid_with_domain(
+        child: HctlTreeNode,
+        var: &str,
+        domain: Option<VarDomain>,
+        op: HybridOp,
+    ) -> HctlTreeNode {
+        let domain_string = match &domain {
+            Some(dom) => format!(" in {dom}"),
+            None => String::new(),
         };
         HctlTreeNode {
             formula_str: format!("({op}{{{var}}}{domain_string}: {child})"),
@@ -69,6 +115,32 @@ impl HctlTreeNode {
         }
     }
 
+    /// Create a quantifier whose variable ranges over attractor states, i.e., the syntactic
+    /// sugar `3{x} in attractors: phi` (or `V{x} in attractors: phi`). It is desugared into
+    /// `3{x}: ((@{x}: AG EF {x}) & phi)` (or `V{x}: ((@{x}: AG EF {x}) => phi)`).
+    ///
+    /// Inside `phi`, the variable represents the attractor containing its state. For example,
+    /// `@{x}: EP s` says that the attractor is reachable from the states `s`.
+    ///
+    /// Panics if `op` is not a quantifier.
+    pub fn mk_attractor_quantifier(child: HctlTreeNode, var: &str, op: HybridOp) -> HctlTreeNode {
+        let in_attractor = HctlTreeNode::mk_hybrid_with_domain(
+            HctlTreeNode::mk_unary(
+                HctlTreeNode::mk_unary(HctlTreeNode::mk_variable(var), UnaryOp::EF),
+                UnaryOp::AG,
+            ),
+            var,
+            None,
+            HybridOp::Jump,
+        );
+        let guarded = match op {
+            HybridOp::Exists => HctlTreeNode::mk_binary(in_attractor, child, BinaryOp::And),
+            HybridOp::Forall => HctlTreeNode::mk_binary(in_attractor, child, BinaryOp::Imp),
+            _ => panic!("Only quantifiers can range over attractors."),
+        };
+        HctlTreeNode::mk_hybrid(guarded, var, None, op)
+    }
+
     /// Create a "hybrid" [HctlTreeNode] from the given arguments.
     ///
     /// See also [NodeType::Unary].
@@ -124,6 +196,20 @@ impl HctlTreeNode {
         Self::mk_atom(Atomic::WildCardProp(prop_name.to_string()))
     }
 
+    /// Create a [HctlTreeNode] representing a "wild-card" relation between HCTL variables.
+    ///
+    /// See also [NodeType::Terminal] and [Atomic::WildCardRelation].
+    pub fn mk_wild_card_relation(relation_name: &str, vars: Vec<String>) -> HctlTreeNode {
+        Self::mk_atom(Atomic::WildCardRelation(relation_name.to_string(), vars))
+    }
+
+    /// Create a [HctlTreeNode] representing a (partial) state given by the values of variables.
+    ///
+    /// See also [NodeType::Terminal] and [Atomic::State].
+    pub fn mk_state(values: Vec<(String, bool)>, partial: bool) -> HctlTreeNode {
+        Self::mk_atom(Atomic::State(values, partial))
+    }
+
     /// A helper function which creates a new [HctlTreeNode] for the given [Atomic] value.
     fn mk_atom(atom: Atomic) -> HctlTreeNode {
         HctlTreeNode {
@@ -187,7 +273,8 @@ impl fmt::Display for HctlTreeNode {
 
 #[cfg(test)]
 mod tests {
-    use crate::preprocessing::hctl_tree::HctlTreeNode;
+    use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType, VarDomain};
+    use crate::preprocessing::operator_enums::HybridOp;
     use crate::preprocessing::tokenizer::{try_tokenize_extended_formula, try_tokenize_formula};
 
     #[test]
@@ -219,4 +306,39 @@ mod tests {
         assert_eq!(node1, node11);
         assert_eq!(node2, node22);
     }
+
+    #[test]
+    /// Test that hybrid nodes with a named domain can be created both from a plain name and
+    /// from a [VarDomain], and that inline domains are displayed in brackets.
+    fn hybrid_domain_constructors() {
+        let child = HctlTreeNode::mk_variable("x");
+        let named = HctlTreeNode::mk_hybrid(
+            child.clone(),
+            "x",
+            Some("dom".to_string()),
+            HybridOp::Exists,
+        );
+        let labelled = HctlTreeNode::mk_hybrid_with_domain(
+            child.clone(),
+            "x",
+            Some(VarDomain::Label("dom".to_string())),
+            HybridOp::Exists,
+        );
+        assert_eq!(named, labelled);
+        assert_eq!(named.to_string(), "(3{x} in %dom%: {x})");
+
+        let tokens = try_tokenize_formula("a & b".to_string()).unwrap();
+        let expr = HctlTreeNode::from_tokens(&tokens).unwrap();
+        let inline = HctlTreeNode::mk_hybrid_with_domain(
+            child,
+            "x",
+            Some(VarDomain::Inline(Box::new(expr))),
+            HybridOp::Exists,
+        );
+        assert!(matches!(
+            inline.node_type,
+            NodeType::Hybrid(_, _, Some(VarDomain::Inline(_)), _)
+        ));
+        assert_eq!(inline.to_string(), "(3{x} in [(a & b)]: {x})");
+    }
 }
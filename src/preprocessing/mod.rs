@@ -1,9 +1,14 @@
 //! Components responsible for the preprocessing of HCTL formulae before model checking.
 //!
-//! That is, tokenization, parsing, validation, and variable renaming.
+//! That is, tokenization, parsing, validation, variable renaming, and rewriting. Formulae can
+//! also reference named phenotypes, which are expanded before the parsing. The structure of the
+//! parsed formulae (free variables, scopes of quantifiers) can be inspected by module `analysis`.
 
+pub mod analysis;
 pub mod hctl_tree;
 pub mod operator_enums;
 pub mod parser;
+pub mod phenotypes;
+pub mod rewriting;
 pub mod tokenizer;
 pub mod utils;
@@ -12,6 +12,13 @@ pub enum UnaryOp {
     AF,  // 'AF'
     EG,  // 'EG'
     AG,  // 'AG'
+    EP,  // 'EP'
+    /// Some successor reached by updating the given network variable satisfies the formula,
+    /// e.g., `EX_{v}`
+    EXVar(String),
+    /// All successors reached by updating the given network variable satisfy the formula,
+    /// e.g., `AX_{v}`
+    AXVar(String),
 }
 
 /// Enum for all possible binary operators occurring in a HCTL formula string.
@@ -49,12 +56,20 @@ pub enum Atomic {
     True,                 // A true constant
     False,                // A false constant
     WildCardProp(String), // A wild-card proposition name
+    /// A wild-card relation between the states of HCTL variables, given by its name and the
+    /// variables, e.g., `%basin({x}, {y})%`
+    WildCardRelation(String, Vec<String>),
+    /// A (partial) state given by values of network variables, and whether it is partial
+    /// (unlisted variables can have any value), e.g., `#state{v1=1, v2=0, *}`
+    State(Vec<(String, bool)>, bool),
 }
 
 impl fmt::Display for UnaryOp {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             UnaryOp::Not => write!(f, "~"),
+            UnaryOp::EXVar(var) => write!(f, "EX_{{{var}}}"),
+            UnaryOp::AXVar(var) => write!(f, "AX_{{{var}}}"),
             // temporal operators are displayed as they are
             c => write!(f, "{c:?}"),
         }
@@ -94,6 +109,20 @@ impl fmt::Display for Atomic {
             Atomic::True => write!(f, "True"),
             Atomic::False => write!(f, "False"),
             Atomic::WildCardProp(name) => write!(f, "%{name}%"),
+            Atomic::WildCardRelation(name, vars) => {
+                let vars: Vec<String> = vars.iter().map(|var| format!("{{{var}}}")).collect();
+                write!(f, "%{name}({})%", vars.join(", "))
+            }
+            Atomic::State(values, partial) => {
+                let mut items: Vec<String> = values
+                    .iter()
+                    .map(|(name, value)| format!("{name}={}", u8::from(*value)))
+                    .collect();
+                if *partial {
+                    items.push("*".to_string());
+                }
+                write!(f, "#state{{{}}}", items.join(", "))
+            }
         }
     }
 }
@@ -10,7 +10,8 @@
 use crate::preprocessing::hctl_tree::*;
 use crate::preprocessing::operator_enums::*;
 use crate::preprocessing::tokenizer::{
-    try_tokenize_extended_formula, try_tokenize_formula, HctlToken,
+    is_inline_domain, try_tokenize_extended_formula, try_tokenize_formula,
+    try_tokenize_with_recovery, HctlToken, SyntaxError,
 };
 use crate::preprocessing::utils::validate_props_and_rename_vars;
 use biodivine_lib_param_bn::symbolic_async_graph::SymbolicContext;
@@ -35,6 +36,40 @@ pub fn parse_extended_formula(formula: &str) -> Result<HctlTreeNode, String> {
     Ok(tree)
 }
 
+/// Parse an HCTL formula string representation into an actual formula tree, recovering from
+/// the syntax errors, so that all the (independent) errors are reported together.
+///
+/// NEEDS to call [validate_props_and_rename_vars] to fully finish the preprocessing step.
+pub fn parse_hctl_formula_with_recovery(formula: &str) -> Result<HctlTreeNode, Vec<SyntaxError>> {
+    parse_with_recovery(formula, false)
+}
+
+/// Parse an extended HCTL formula string representation into an actual formula tree, recovering
+/// from the syntax errors, so that all the (independent) errors are reported together.
+///
+/// NEEDS to call [validate_props_and_rename_vars] to fully finish the preprocessing step.
+pub fn parse_extended_formula_with_recovery(
+    formula: &str,
+) -> Result<HctlTreeNode, Vec<SyntaxError>> {
+    parse_with_recovery(formula, true)
+}
+
+/// Tokenize and parse the formula with error recovery, see [parse_hctl_formula_with_recovery].
+fn parse_with_recovery(
+    formula: &str,
+    parse_wild_cards: bool,
+) -> Result<HctlTreeNode, Vec<SyntaxError>> {
+    let (tokens, mut errors) = try_tokenize_with_recovery(formula, parse_wild_cards);
+    match parse_hctl_tokens(&tokens) {
+        Ok(tree) if errors.is_empty() => Ok(tree),
+        Ok(_) => Err(errors),
+        Err(_) => {
+            collect_parse_errors(&tokens, &mut errors);
+            Err(errors)
+        }
+    }
+}
+
 /// Parse an HCTL formula string representation into an actual formula tree with renamed (minimized)
 /// set of variables.
 /// Basically a wrapper for the whole preprocessing step (tokenize + parse + rename vars).
@@ -59,9 +94,50 @@ pub fn parse_and_minimize_extended_formula(
     Ok(tree)
 }
 
+/// Parse the propositional expression of an inline domain (`[expression]`, see
+/// [is_inline_domain]) into a formula tree. Both `~` and `!` can be used as a negation inside
+/// the expression.
+///
+/// NEEDS to call [validate_props_and_rename_vars] to check the propositions.
+pub fn parse_inline_domain(domain: &str) -> Result<HctlTreeNode, String> {
+    let expression = domain
+        .strip_prefix('[')
+        .and_then(|d| d.strip_suffix(']'))
+        .ok_or(format!("Domain `{domain}` is not an inline expression."))?;
+    let tree = parse_hctl_formula(&expression.replace('!', "~"))?;
+    if !is_propositional(&tree) {
+        return Err(format!(
+            "Domain expression `{expression}` must be a propositional formula."
+        ));
+    }
+    Ok(tree)
+}
+
+/// Check whether the tree is a Boolean expression over propositions.
+pub(crate) fn is_propositional(node: &HctlTreeNode) -> bool {
+    match &node.node_type {
+        NodeType::Terminal(atom) => matches!(
+            atom,
+            Atomic::Prop(_) | Atomic::State(..) | Atomic::True | Atomic::False
+        ),
+        NodeType::Unary(op, child) => *op == UnaryOp::Not && is_propositional(child),
+        NodeType::Binary(op, left, right) => {
+            matches!(
+                op,
+                BinaryOp::And | BinaryOp::Or | BinaryOp::Xor | BinaryOp::Imp | BinaryOp::Iff
+            ) && is_propositional(left)
+                && is_propositional(right)
+        }
+        NodeType::Hybrid(..) => false,
+    }
+}
+
 /// Predicate for whether given token represents hybrid operator.
 fn is_hybrid(token: &HctlToken) -> bool {
-    matches!(token, HctlToken::Hybrid(..))
+    matches!(
+        token,
+        HctlToken::Hybrid(..) | HctlToken::AttractorQuantifier(..)
+    )
 }
 
 /// Predicate for whether given token represents temporal binary operator.
@@ -82,22 +158,96 @@ fn is_unary(token: &HctlToken) -> bool {
 
 /// Utility method to find the first occurrence of a specific token in the token tree.
 fn index_of_first(tokens: &[HctlToken], token: HctlToken) -> Option<usize> {
-    return tokens.iter().position(|t| *t == token);
+    tokens.iter().position(|t| *t == token)
 }
 
 /// Utility method to find the first occurrence of a hybrid operator in the token tree.
 fn index_of_first_hybrid(tokens: &[HctlToken]) -> Option<usize> {
-    return tokens.iter().position(is_hybrid);
+    tokens.iter().position(is_hybrid)
 }
 
 /// Utility method to find the first occurrence of a binary temporal operator in the token tree.
 fn index_of_first_binary_temp(tokens: &[HctlToken]) -> Option<usize> {
-    return tokens.iter().position(is_binary_temporal);
+    tokens.iter().position(is_binary_temporal)
 }
 
 /// Utility method to find the first occurrence of an unary operator in the token tree.
 fn index_of_first_unary(tokens: &[HctlToken]) -> Option<usize> {
-    return tokens.iter().position(is_unary);
+    tokens.iter().position(is_unary)
+}
+
+/// Utility method to find the binary operator at which the parser splits the tokens, i.e., the
+/// first occurrence of the weakest binary operator (see the operator precedence).
+fn index_of_weakest_binary(tokens: &[HctlToken]) -> Option<usize> {
+    [
+        BinaryOp::Iff,
+        BinaryOp::Imp,
+        BinaryOp::Or,
+        BinaryOp::Xor,
+        BinaryOp::And,
+    ]
+    .into_iter()
+    .find_map(|op| index_of_first(tokens, HctlToken::Binary(op)))
+    .or_else(|| index_of_first_binary_temp(tokens))
+}
+
+/// Predicate for whether the token tree contains a placeholder of an invalid segment.
+fn contains_invalid(tokens: &[HctlToken]) -> bool {
+    tokens.iter().any(|token| match token {
+        HctlToken::Invalid => true,
+        HctlToken::Tokens(inner) => contains_invalid(inner),
+        _ => false,
+    })
+}
+
+/// Check that the hybrid operator at index `i` is not preceded by other type of operators.
+fn check_hybrid_precedence(tokens: &[HctlToken], i: usize) -> Result<(), String> {
+    if i > 0 && !is_hybrid(&tokens[i - 1]) {
+        return Err(format!(
+            "Hybrid operator can't be directly preceded by {}.",
+            &tokens[i - 1]
+        ));
+    }
+    Ok(())
+}
+
+/// Collect the errors of parsing the `tokens`, recovering from them. The parts of the formula that
+/// are parsed independently (operands of binary operators, sub-formulae of unary and hybrid
+/// operators, blocks in parentheses) are checked separately, so that all their errors are found.
+///
+/// Errors caused by the invalid segments skipped by the tokenizer are not reported again.
+fn collect_parse_errors(tokens: &[HctlToken], errors: &mut Vec<SyntaxError>) {
+    let Err(message) = parse_hctl_tokens(tokens) else {
+        return;
+    };
+    let num_errors = errors.len();
+    let mk_error = |message| SyntaxError {
+        position: None,
+        message,
+    };
+    if let Some(i) = index_of_first_hybrid(tokens) {
+        // errors of the operator itself are independent of errors of its sub-formula
+        if let Err(e) = check_hybrid_precedence(tokens, i) {
+            errors.push(mk_error(e));
+        }
+        if let HctlToken::Hybrid(_, _, Some(domain)) = &tokens[i] {
+            if is_inline_domain(domain) {
+                errors.extend(parse_inline_domain(domain).err().map(mk_error));
+            }
+        }
+        collect_parse_errors(&tokens[(i + 1)..], errors);
+    } else if let Some(i) = index_of_weakest_binary(tokens) {
+        collect_parse_errors(&tokens[..i], errors);
+        collect_parse_errors(&tokens[(i + 1)..], errors);
+    } else if let [HctlToken::Unary(_), sub_formula @ ..] = tokens {
+        collect_parse_errors(sub_formula, errors);
+    } else if let [HctlToken::Tokens(inner)] = tokens {
+        collect_parse_errors(inner, errors);
+    }
+    // the error itself is reported only if it was not found in any of the parts
+    if errors.len() == num_errors && !contains_invalid(tokens) {
+        errors.push(mk_error(message));
+    }
 }
 
 /// Parse `tokens` of HCTL formula into an abstract syntax tree using recursive steps.
@@ -113,17 +263,27 @@ fn parse_1_hybrid(tokens: &[HctlToken]) -> Result<HctlTreeNode, String> {
     let hybrid_token = index_of_first_hybrid(tokens);
     Ok(if let Some(i) = hybrid_token {
         // perform check that hybrid operator is not preceded by other type of operators
-        if i > 0 && !matches!(&tokens[i - 1], HctlToken::Hybrid(..)) {
-            return Err(format!(
-                "Hybrid operator can't be directly preceded by {}.",
-                &tokens[i - 1]
-            ));
-        }
+        check_hybrid_precedence(tokens, i)?;
         match &tokens[i] {
-            HctlToken::Hybrid(op, var, domain) => HctlTreeNode::mk_hybrid(
+            HctlToken::Hybrid(op, var, domain) => {
+                // inline domains are parsed here, the other domains are given by their labels
+                let domain = match domain {
+                    Some(dom) if is_inline_domain(dom) => {
+                        Some(VarDomain::Inline(Box::new(parse_inline_domain(dom)?)))
+                    }
+                    Some(dom) => Some(VarDomain::Label(dom.clone())),
+                    None => None,
+                };
+                HctlTreeNode::mk_hybrid_with_domain(
+                    parse_1_hybrid(&tokens[(i + 1)..])?,
+                    var.as_str(),
+                    domain,
+                    op.clone(),
+                )
+            }
+            HctlToken::AttractorQuantifier(op, var) => HctlTreeNode::mk_attractor_quantifier(
                 parse_1_hybrid(&tokens[(i + 1)..])?,
                 var.as_str(),
-                domain.clone(),
                 op.clone(),
             ),
             _ => unreachable!(), // we already made sure that this is indeed a hybrid token
@@ -267,6 +427,12 @@ fn parse_9_terminal_and_parentheses(tokens: &[HctlToken]) -> Result<HctlTreeNode
                 HctlToken::Atom(Atomic::WildCardProp(name)) => {
                     return Ok(HctlTreeNode::mk_wild_card(name.as_str()))
                 }
+                HctlToken::Atom(Atomic::WildCardRelation(name, vars)) => {
+                    return Ok(HctlTreeNode::mk_wild_card_relation(name, vars.clone()))
+                }
+                HctlToken::Atom(Atomic::State(values, partial)) => {
+                    return Ok(HctlTreeNode::mk_state(values.clone(), *partial))
+                }
                 // recursively solve sub-formulae in parentheses
                 HctlToken::Tokens(inner) => return parse_hctl_tokens(inner),
                 _ => {} // otherwise, fall through to the error at the end.
@@ -280,7 +446,10 @@ fn parse_9_terminal_and_parentheses(tokens: &[HctlToken]) -> Result<HctlTreeNode
 mod tests {
     use crate::preprocessing::hctl_tree::*;
     use crate::preprocessing::operator_enums::*;
-    use crate::preprocessing::parser::{parse_extended_formula, parse_hctl_formula};
+    use crate::preprocessing::parser::{
+        parse_extended_formula, parse_extended_formula_with_recovery, parse_hctl_formula,
+        parse_hctl_formula_with_recovery,
+    };
 
     #[test]
     /// Test whether several valid HCTL formulae are parsed without causing errors.
@@ -310,11 +479,46 @@ mod tests {
             "((prop1 <=> ((PROP2 | False) => True)) AU (True ^ False))"
         );
 
+        // quantifiers over attractors are desugared
+        let valid5 = "V{x} in attractors: @{x}: EP a";
+        let tree = parse_hctl_formula(valid5).unwrap();
+        assert_eq!(
+            tree.as_str(),
+            "(V{x}: ((@{x}: (AG (EF {x}))) => (@{x}: (EP a))))"
+        );
+
+        // inline domains are normalized, and the result can be parsed again
+        let valid6 = "!{x} in [Casp3 & !Growth]: AX {x}";
+        let tree = parse_hctl_formula(valid6).unwrap();
+        assert_eq!(tree.as_str(), "(!{x} in [(Casp3 & (~Growth))]: (AX {x}))");
+        assert_eq!(parse_hctl_formula(tree.as_str()).unwrap(), tree);
+        // the domain expression is parsed only once, and kept in the tree
+        let NodeType::Hybrid(_, _, Some(VarDomain::Inline(domain)), _) = &tree.node_type else {
+            panic!("Expected an inline domain.");
+        };
+        assert_eq!(domain.as_str(), "(Casp3 & (~Growth))");
+
+        // transition-labelled next operators are displayed in the same way
+        let valid7 = "!{x}: AX_{SK} EX_{p1} {x}";
+        let tree = parse_hctl_formula(valid7).unwrap();
+        assert_eq!(tree.as_str(), "(!{x}: (AX_{SK} (EX_{p1} {x})))");
+        assert_eq!(parse_hctl_formula(tree.as_str()).unwrap(), tree);
+
+        // built-in domain of fair attractors is kept in the tree
+        let valid8 = "3{x} in fair_attractors: AX {x}";
+        let tree = parse_hctl_formula(valid8).unwrap();
+        assert_eq!(tree.as_str(), "(3{x} in fair_attractors: (AX {x}))");
+        assert_eq!(parse_hctl_formula(tree.as_str()).unwrap(), tree);
+
         // all formulae must be correctly parsed also using the extended version of HCTL
         assert!(parse_extended_formula(valid1).is_ok());
         assert!(parse_extended_formula(valid2).is_ok());
         assert!(parse_extended_formula(valid3).is_ok());
         assert!(parse_extended_formula(valid4).is_ok());
+        assert!(parse_extended_formula(valid5).is_ok());
+        assert!(parse_extended_formula(valid6).is_ok());
+        assert!(parse_extended_formula(valid7).is_ok());
+        assert!(parse_extended_formula(valid8).is_ok());
     }
 
     #[test]
@@ -363,7 +567,7 @@ mod tests {
         assert_eq!(parse_hctl_formula(formula).unwrap(), expected_tree);
 
         let formula = "!{x}: (AX {x})";
-        let expected_tree = HctlTreeNode::mk_hybrid(
+        let expected_tree = HctlTreeNode::mk_hybrid_with_domain(
             HctlTreeNode::mk_unary(HctlTreeNode::mk_variable("x"), UnaryOp::AX),
             "x",
             None,
@@ -387,6 +591,9 @@ mod tests {
             "prop1 prop2",
             "AU !{x}: {x}",
             "AF (AF !{x}: {x})",
+            "!{x} in [a &]: {x}",
+            "!{x} in [AX a]: {x}",
+            "!{x} in [a & {x}]: {x}",
         ];
 
         for formula in invalid_formulae {
@@ -415,4 +622,36 @@ mod tests {
             "(!{x}: (3{y}: ((@{x}: ((~{y}) & %s%)) & (@{y}: %s%))))"
         );
     }
+
+    #[test]
+    /// Test that parsing with recovery reports several independent errors together.
+    fn parse_invalid_formulae_with_recovery() {
+        // valid formulae are parsed the same way
+        let formula = "!{x}: AG EF {x}";
+        assert_eq!(
+            parse_hctl_formula_with_recovery(formula).unwrap(),
+            parse_hctl_formula(formula).unwrap()
+        );
+
+        // two errors found by the tokenizer, and one by the parser
+        let errors = parse_hctl_formula_with_recovery("(a & $b) | (c = d) | (EF )").unwrap_err();
+        let positions: Vec<Option<usize>> = errors.iter().map(|e| e.position).collect();
+        assert_eq!(positions, vec![Some(6), Some(16), None]);
+        assert_eq!(errors[2].message, "Expected formula, found nothing.");
+
+        // errors of hybrid operators are independent of errors of their sub-formulae
+        let errors = parse_hctl_formula_with_recovery("AF !{x} in [a &]: ({x} |)").unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0]
+            .message
+            .starts_with("Hybrid operator can't be directly preceded"));
+
+        // a single error is reported the same way as by the standard parser
+        let errors = parse_extended_formula_with_recovery("EF %p% & ").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            parse_extended_formula("EF %p% & ").unwrap_err()
+        );
+    }
 }
@@ -0,0 +1,244 @@
+//! Named phenotypes that can be referenced in HCTL formulae.
+//!
+//! A phenotype is declared on a single line (usually in a formulae file), either as a Boolean
+//! expression over network variables, or as a subspace given by the values of some variables:
+//!
+//! ```text
+//! phenotype Apoptosis = Casp3 & !Growth
+//! phenotype Proliferation = [Growth=1, Casp3=0]
+//! ```
+//!
+//! Boolean expressions use the same operators as HCTL formulae (negation can be written both as
+//! `~` and `!`), and they can reference previously declared phenotypes. In formulae, phenotypes
+//! are referenced by their names, either as plain propositions (`EF Apoptosis`) or as wild-card
+//! propositions (`EF %Apoptosis%`), and they are replaced by their definitions before the
+//! evaluation. Phenotype names take precedence over the names of network variables.
+
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::operator_enums::{Atomic, BinaryOp, UnaryOp};
+use crate::preprocessing::parser::{is_propositional, parse_extended_formula, parse_hctl_formula};
+
+use std::collections::BTreeMap;
+
+/// Keyword that starts the declaration of a phenotype.
+const PHENOTYPE_KEYWORD: &str = "phenotype";
+
+/// Collection of named phenotypes, each given by a propositional formula tree.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Phenotypes {
+    definitions: BTreeMap<String, HctlTreeNode>,
+}
+
+impl Phenotypes {
+    /// Create an empty collection of phenotypes.
+    pub fn new() -> Phenotypes {
+        Phenotypes::default()
+    }
+
+    /// Add a phenotype with the given `name`, defined either by a Boolean expression or by a
+    /// subspace (see the module documentation). The definition can reference the phenotypes
+    /// that were added before.
+    pub fn add_phenotype(&mut self, name: &str, definition: &str) -> Result<(), String> {
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(format!("Invalid phenotype name `{name}`."));
+        }
+        if self.definitions.contains_key(name) {
+            return Err(format!("Phenotype `{name}` is defined multiple times."));
+        }
+        let definition = definition.trim();
+        let tree = if let Some(subspace) = definition
+            .strip_prefix('[')
+            .and_then(|d| d.strip_suffix(']'))
+        {
+            parse_subspace(subspace)?
+        } else {
+            // hybrid operators are not allowed, so `!` can only mean negation
+            let tree = parse_hctl_formula(&definition.replace('!', "~"))
+                .map_err(|e| format!("Invalid definition of phenotype `{name}`: {e}"))?;
+            if !is_propositional(&tree) {
+                return Err(format!(
+                    "Definition of phenotype `{name}` must be a Boolean expression."
+                ));
+            }
+            self.substitute_phenotypes(tree)
+        };
+        self.definitions.insert(name.to_string(), tree);
+        Ok(())
+    }
+
+    /// Get the definition of the phenotype with the given name, if it exists.
+    pub fn get_phenotype(&self, name: &str) -> Option<&HctlTreeNode> {
+        self.definitions.get(name)
+    }
+
+    /// Iterate over the names of all phenotypes (in alphabetical order).
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.definitions.keys()
+    }
+
+    /// Number of phenotypes in the collection.
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    /// Check whether there are no phenotypes in the collection.
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+
+    /// Replace all propositions and wild-card propositions that refer to phenotypes by the
+    /// definitions of the phenotypes.
+    pub fn substitute_phenotypes(&self, tree: HctlTreeNode) -> HctlTreeNode {
+        match tree.node_type {
+            NodeType::Terminal(Atomic::Prop(ref name))
+            | NodeType::Terminal(Atomic::WildCardProp(ref name)) => {
+                match self.definitions.get(name) {
+                    Some(definition) => definition.clone(),
+                    None => tree,
+                }
+            }
+            NodeType::Terminal(_) => tree,
+            NodeType::Unary(op, child) => {
+                HctlTreeNode::mk_unary(self.substitute_phenotypes(*child), op)
+            }
+            NodeType::Binary(op, left, right) => HctlTreeNode::mk_binary(
+                self.substitute_phenotypes(*left),
+                self.substitute_phenotypes(*right),
+                op,
+            ),
+            NodeType::Hybrid(op, var, domain, child) => HctlTreeNode::mk_hybrid_with_domain(
+                self.substitute_phenotypes(*child),
+                &var,
+                domain,
+                op,
+            ),
+        }
+    }
+
+    /// Replace the phenotypes in the (possibly extended) formula string by their definitions.
+    /// Formulae that do not reference any phenotype are returned unchanged.
+    pub fn expand_formula(&self, formula: &str) -> Result<String, String> {
+        let tree = parse_extended_formula(formula)?;
+        let expanded = self.substitute_phenotypes(tree.clone());
+        if expanded == tree {
+            Ok(formula.to_string())
+        } else {
+            Ok(expanded.to_string())
+        }
+    }
+}
+
+/// Parse the subspace given as a list of `NAME=0` or `NAME=1` into a conjunction of literals.
+fn parse_subspace(subspace: &str) -> Result<HctlTreeNode, String> {
+    let mut literals = Vec::new();
+    for assignment in subspace.split(',').map(|a| a.trim()) {
+        let literal = match assignment.split_once('=') {
+            Some((name, "0")) => {
+                HctlTreeNode::mk_unary(HctlTreeNode::mk_proposition(name.trim()), UnaryOp::Not)
+            }
+            Some((name, "1")) => HctlTreeNode::mk_proposition(name.trim()),
+            _ => {
+                return Err(format!(
+                    "Invalid subspace value `{assignment}` (use `NAME=0` or `NAME=1`)."
+                ))
+            }
+        };
+        literals.push(literal);
+    }
+    // the conjunction is right-associative, same as the parsed ones
+    Ok(literals
+        .into_iter()
+        .rev()
+        .reduce(|right, left| HctlTreeNode::mk_binary(left, right, BinaryOp::And))
+        .unwrap())
+}
+
+/// Parse the phenotype declaration `phenotype NAME = DEFINITION`. Return `None` if the line
+/// is not a phenotype declaration.
+pub fn parse_phenotype_declaration(line: &str) -> Option<Result<(String, String), String>> {
+    let rest = line.trim().strip_prefix(PHENOTYPE_KEYWORD)?;
+    if !rest.starts_with(char::is_whitespace) {
+        // a formula starting with a proposition such as `phenotype_x`
+        return None;
+    }
+    Some(match rest.split_once('=') {
+        Some((name, definition)) => Ok((name.trim().to_string(), definition.trim().to_string())),
+        None => Err(format!(
+            "Invalid phenotype declaration `{}` (use `phenotype NAME = DEFINITION`).",
+            line.trim()
+        )),
+    })
+}
+
+/// Divide the lines (formulae and phenotype declarations) into the declared phenotypes and the
+/// formulae, and replace the phenotypes in the formulae by their definitions. The phenotypes can
+/// be declared anywhere among the formulae. If there are no phenotypes, the formulae are
+/// returned unchanged (and their syntax is not checked).
+pub fn process_phenotype_declarations(
+    lines: Vec<String>,
+) -> Result<(Phenotypes, Vec<String>), String> {
+    let mut phenotypes = Phenotypes::new();
+    let mut formulae = Vec::new();
+    for line in lines {
+        match parse_phenotype_declaration(&line) {
+            Some(declaration) => {
+                let (name, definition) = declaration?;
+                phenotypes.add_phenotype(&name, &definition)?;
+            }
+            None => formulae.push(line),
+        }
+    }
+    if phenotypes.is_empty() {
+        return Ok((phenotypes, formulae));
+    }
+    let formulae = formulae
+        .iter()
+        .map(|formula| phenotypes.expand_formula(formula))
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((phenotypes, formulae))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preprocessing::parser::parse_hctl_formula;
+    use crate::preprocessing::phenotypes::{process_phenotype_declarations, Phenotypes};
+
+    #[test]
+    /// Test declaring phenotypes and expanding them in formulae.
+    fn test_phenotypes() {
+        let lines = vec![
+            "phenotype Apoptosis = Casp3 & !Growth".to_string(),
+            "EF Apoptosis".to_string(),
+            "phenotype Proliferation = [Growth=1, Casp3=0]".to_string(),
+            "phenotype Any = Apoptosis | Proliferation".to_string(),
+            "!{x}: AX ({x} & ~%Any%)".to_string(),
+            "AG phenotype_x".to_string(),
+        ];
+        let (phenotypes, formulae) = process_phenotype_declarations(lines).unwrap();
+        assert_eq!(phenotypes.len(), 3);
+        assert_eq!(
+            phenotypes.get_phenotype("Proliferation").unwrap(),
+            &parse_hctl_formula("Growth & ~Casp3").unwrap()
+        );
+        assert_eq!(formulae.len(), 3);
+        assert_eq!(
+            parse_hctl_formula(&formulae[0]).unwrap(),
+            parse_hctl_formula("EF (Casp3 & ~Growth)").unwrap()
+        );
+        let expected = "!{x}: AX ({x} & ~((Casp3 & ~Growth) | (Growth & ~Casp3)))";
+        assert_eq!(
+            parse_hctl_formula(&formulae[1]).unwrap(),
+            parse_hctl_formula(expected).unwrap()
+        );
+        // formulae without phenotypes are kept as they are
+        assert_eq!(formulae[2], "AG phenotype_x");
+
+        let mut phenotypes = Phenotypes::new();
+        assert!(phenotypes.add_phenotype("A", "EF a").is_err());
+        assert!(phenotypes.add_phenotype("A", "[a=2]").is_err());
+        assert!(phenotypes.add_phenotype("A b", "a").is_err());
+        assert!(phenotypes.add_phenotype("A", "a").is_ok());
+        assert!(phenotypes.add_phenotype("A", "b").is_err());
+        assert!(process_phenotype_declarations(vec!["phenotype A".to_string()]).is_err());
+    }
+}
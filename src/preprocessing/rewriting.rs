@@ -0,0 +1,400 @@
+//! Semantics-preserving rewriting of HCTL syntactic trees, used to optimize the evaluation.
+//!
+//! Currently, hybrid quantifiers (and binders) are pushed inward past operators whose other
+//! operands do not mention the quantified variable. This shrinks the scope over which the extra
+//! symbolic variables are alive, and thus reduces the size of intermediate BDDs.
+//!
+//! Further, an (opt-in) optimization pass rewrites formulae into forms that the evaluator handles
+//! better, such as removing redundant negations or splitting off the attractor pattern, which has
+//! a specialized algorithm. See [optimize_formula].
+
+use crate::preprocessing::hctl_tree::*;
+use crate::preprocessing::operator_enums::*;
+
+/// Check whether the HCTL variable `var` occurs in the formula tree (either as an atomic
+/// proposition `{x}`, in a wild-card relation, or in a hybrid operator).
+pub(crate) fn contains_var(node: &HctlTreeNode, var: &str) -> bool {
+    match &node.node_type {
+        NodeType::Terminal(Atomic::Var(name)) => name == var,
+        NodeType::Terminal(Atomic::WildCardRelation(_, vars)) => vars.iter().any(|v| v == var),
+        NodeType::Terminal(_) => false,
+        NodeType::Unary(_, child) => contains_var(child, var),
+        NodeType::Binary(_, left, right) => contains_var(left, var) || contains_var(right, var),
+        NodeType::Hybrid(_, name, _, child) => name == var || contains_var(child, var),
+    }
+}
+
+/// Check whether the hybrid operator `outer` (with variable `var`) can be swapped with the
+/// hybrid operator `inner` (with a different variable) directly below it.
+fn can_swap(outer: &HybridOp, inner: &HybridOp) -> bool {
+    match outer {
+        // quantified variables are rigid, so they commute with binders and jumps
+        HybridOp::Exists => !matches!(inner, HybridOp::Forall),
+        HybridOp::Forall => !matches!(inner, HybridOp::Exists),
+        // binder can not be moved below the jump (it would bind a different state)
+        HybridOp::Bind => !matches!(inner, HybridOp::Jump),
+        HybridOp::Jump => false,
+    }
+}
+
+/// Dual of the hybrid quantifier, used when pushing it into the left side of implication.
+fn dual(op: &HybridOp) -> HybridOp {
+    match op {
+        HybridOp::Exists => HybridOp::Forall,
+        HybridOp::Forall => HybridOp::Exists,
+        _ => op.clone(),
+    }
+}
+
+/// Push the hybrid quantifier `op` with variable `var` into the (already rewritten) `child` as
+/// deep as possible, and return the resulting tree.
+fn push_quantifier(op: HybridOp, var: &str, child: HctlTreeNode) -> HctlTreeNode {
+    // quantifier on a variable that does not occur is redundant (binder or quantification over
+    // the whole non-empty state space)
+    if !contains_var(&child, var) {
+        return child;
+    }
+
+    let keep = |child: HctlTreeNode| HctlTreeNode::mk_hybrid(child, var, None, op.clone());
+    match child.node_type.clone() {
+        // binder is deterministic, so it commutes with negation
+        NodeType::Unary(UnaryOp::Not, inner) if op == HybridOp::Bind => {
+            HctlTreeNode::mk_unary(push_quantifier(op, var, *inner), UnaryOp::Not)
+        }
+        // rigid quantifiers commute with temporal operators of the same kind
+        NodeType::Unary(
+            temporal_op @ (UnaryOp::EX | UnaryOp::EXVar(_) | UnaryOp::EF | UnaryOp::EP),
+            inner,
+        ) if op == HybridOp::Exists => {
+            HctlTreeNode::mk_unary(push_quantifier(op, var, *inner), temporal_op)
+        }
+        NodeType::Unary(temporal_op @ (UnaryOp::AX | UnaryOp::AXVar(_) | UnaryOp::AG), inner)
+            if op == HybridOp::Forall =>
+        {
+            HctlTreeNode::mk_unary(push_quantifier(op, var, *inner), temporal_op)
+        }
+        NodeType::Binary(bin_op @ (BinaryOp::And | BinaryOp::Or), left, right) => {
+            if !contains_var(&right, var) {
+                HctlTreeNode::mk_binary(push_quantifier(op, var, *left), *right, bin_op)
+            } else if !contains_var(&left, var) {
+                HctlTreeNode::mk_binary(*left, push_quantifier(op, var, *right), bin_op)
+            } else {
+                keep(child)
+            }
+        }
+        // `phi1 => phi2` is `~phi1 | phi2`, so the quantifier is dualized on the left side
+        NodeType::Binary(BinaryOp::Imp, left, right) => {
+            if !contains_var(&right, var) {
+                HctlTreeNode::mk_binary(
+                    push_quantifier(dual(&op), var, *left),
+                    *right,
+                    BinaryOp::Imp,
+                )
+            } else if !contains_var(&left, var) {
+                HctlTreeNode::mk_binary(*left, push_quantifier(op, var, *right), BinaryOp::Imp)
+            } else {
+                keep(child)
+            }
+        }
+        // binder commutes with all Boolean operators
+        NodeType::Binary(bin_op @ (BinaryOp::Iff | BinaryOp::Xor), left, right)
+            if op == HybridOp::Bind =>
+        {
+            if !contains_var(&right, var) {
+                HctlTreeNode::mk_binary(push_quantifier(op, var, *left), *right, bin_op)
+            } else if !contains_var(&left, var) {
+                HctlTreeNode::mk_binary(*left, push_quantifier(op, var, *right), bin_op)
+            } else {
+                keep(child)
+            }
+        }
+        // adjacent operators are swapped, so that both can be pushed further
+        NodeType::Hybrid(inner_op, inner_var, None, inner)
+            if inner_var != var && can_swap(&op, &inner_op) =>
+        {
+            HctlTreeNode::mk_hybrid_with_domain(
+                push_quantifier(op, var, *inner),
+                inner_var.as_str(),
+                None,
+                inner_op,
+            )
+        }
+        _ => keep(child),
+    }
+}
+
+/// Push hybrid quantifiers and binders inward past operators that do not mention the quantified
+/// variable, such as `3{x}: (a & AX {x})` to `a & (3{x}: AX {x})`. Adjacent quantifiers are
+/// swapped when possible, so that they can be pushed further as well.
+///
+/// Operators with restricted domains and jumps are kept in place. Quantifiers on variables that
+/// do not occur in their sub-formula are removed (assuming a non-empty state space).
+pub fn push_quantifiers_inward(tree: HctlTreeNode) -> HctlTreeNode {
+    match tree.node_type {
+        NodeType::Terminal(_) => tree,
+        NodeType::Unary(op, child) => HctlTreeNode::mk_unary(push_quantifiers_inward(*child), op),
+        NodeType::Binary(op, left, right) => HctlTreeNode::mk_binary(
+            push_quantifiers_inward(*left),
+            push_quantifiers_inward(*right),
+            op,
+        ),
+        NodeType::Hybrid(op, var, domain, child) => {
+            let child = push_quantifiers_inward(*child);
+            if domain.is_some() || op == HybridOp::Jump {
+                HctlTreeNode::mk_hybrid_with_domain(child, var.as_str(), domain, op)
+            } else {
+                push_quantifier(op, var.as_str(), child)
+            }
+        }
+    }
+}
+
+/// Dual of the temporal operator, such that `~op ~phi` is equivalent to `dual op phi`.
+fn temporal_dual(op: &UnaryOp) -> Option<UnaryOp> {
+    match op {
+        UnaryOp::EX => Some(UnaryOp::AX),
+        UnaryOp::AX => Some(UnaryOp::EX),
+        UnaryOp::EXVar(var) => Some(UnaryOp::AXVar(var.clone())),
+        UnaryOp::AXVar(var) => Some(UnaryOp::EXVar(var.clone())),
+        UnaryOp::EF => Some(UnaryOp::AG),
+        UnaryOp::AG => Some(UnaryOp::EF),
+        UnaryOp::AF => Some(UnaryOp::EG),
+        UnaryOp::EG => Some(UnaryOp::AF),
+        UnaryOp::EP | UnaryOp::Not => None,
+    }
+}
+
+/// Collect the operands of a (possibly nested) conjunction.
+fn collect_conjuncts(node: HctlTreeNode, conjuncts: &mut Vec<HctlTreeNode>) {
+    match node.node_type {
+        NodeType::Binary(BinaryOp::And, left, right) => {
+            collect_conjuncts(*left, conjuncts);
+            collect_conjuncts(*right, conjuncts);
+        }
+        _ => conjuncts.push(node),
+    }
+}
+
+/// Make a conjunction of the given operands (`true` if there are none). The conjunction is
+/// right-associative, same as the parsed ones.
+fn mk_conjunction(conjuncts: Vec<HctlTreeNode>) -> HctlTreeNode {
+    conjuncts
+        .into_iter()
+        .rev()
+        .reduce(|right, left| HctlTreeNode::mk_binary(left, right, BinaryOp::And))
+        .unwrap_or(HctlTreeNode::mk_constant(true))
+}
+
+/// Check whether the node is `AG EF {var}` or `AX {var}`, i.e., the body of the attractor or
+/// fixed-point pattern, which the evaluator computes using specialized algorithms.
+fn is_pattern_body(node: &HctlTreeNode, var: &str) -> bool {
+    let is_var = |node: &HctlTreeNode| matches!(&node.node_type, NodeType::Terminal(Atomic::Var(name)) if name == var);
+    match &node.node_type {
+        NodeType::Unary(UnaryOp::AG, child) => {
+            matches!(&child.node_type, NodeType::Unary(UnaryOp::EF, inner) if is_var(inner))
+        }
+        NodeType::Unary(UnaryOp::AX, child) => is_var(child),
+        _ => false,
+    }
+}
+
+/// Apply one optimization rule to the root of the (already optimized) tree, if possible.
+fn optimize_root(node: &HctlTreeNode) -> Option<HctlTreeNode> {
+    match &node.node_type {
+        NodeType::Unary(UnaryOp::Not, child) => match &child.node_type {
+            // `~~phi` to `phi`
+            NodeType::Unary(UnaryOp::Not, inner) => Some(*inner.clone()),
+            // `~EF ~phi` to `AG phi` (and similarly for other temporal operators)
+            NodeType::Unary(op, inner) => match (&inner.node_type, temporal_dual(op)) {
+                (NodeType::Unary(UnaryOp::Not, phi), Some(dual)) => {
+                    Some(HctlTreeNode::mk_unary(*phi.clone(), dual))
+                }
+                _ => None,
+            },
+            _ => None,
+        },
+        // `EF EF phi` to `EF phi` (and similarly for other idempotent operators)
+        NodeType::Unary(op @ (UnaryOp::EF | UnaryOp::AF | UnaryOp::EG | UnaryOp::AG), child) => {
+            match &child.node_type {
+                NodeType::Unary(inner_op, _) if inner_op == op => Some(*child.clone()),
+                _ => None,
+            }
+        }
+        // `(psi & phi1) | (psi & phi2)` to `psi & (phi1 | phi2)`
+        NodeType::Binary(BinaryOp::Or, left, right) => {
+            let (mut left_conjuncts, mut right_conjuncts) = (Vec::new(), Vec::new());
+            collect_conjuncts(*left.clone(), &mut left_conjuncts);
+            collect_conjuncts(*right.clone(), &mut right_conjuncts);
+            let common: Vec<HctlTreeNode> = left_conjuncts
+                .iter()
+                .filter(|c| right_conjuncts.contains(c))
+                .cloned()
+                .collect();
+            if common.is_empty() {
+                return None;
+            }
+            left_conjuncts.retain(|c| !common.contains(c));
+            right_conjuncts.retain(|c| !common.contains(c));
+            // if one of the branches consists only of the common conjuncts, it absorbs the other
+            if left_conjuncts.is_empty() || right_conjuncts.is_empty() {
+                return Some(mk_conjunction(common));
+            }
+            let rest = HctlTreeNode::mk_binary(
+                mk_conjunction(left_conjuncts),
+                mk_conjunction(right_conjuncts),
+                BinaryOp::Or,
+            );
+            Some(HctlTreeNode::mk_binary(
+                mk_conjunction(common),
+                rest,
+                BinaryOp::And,
+            ))
+        }
+        // `!{x}: (AG EF {x} & phi)` to `(!{x}: AG EF {x}) & (!{x}: phi)`, so that the attractor
+        // (or fixed-point) pattern is computed separately and then intersected
+        NodeType::Hybrid(HybridOp::Bind, var, None, child) => {
+            let mut conjuncts = Vec::new();
+            collect_conjuncts(*child.clone(), &mut conjuncts);
+            let (patterns, rest): (Vec<_>, Vec<_>) =
+                conjuncts.into_iter().partition(|c| is_pattern_body(c, var));
+            if patterns.is_empty() || rest.is_empty() {
+                return None;
+            }
+            let bind = |node: HctlTreeNode| {
+                HctlTreeNode::mk_hybrid_with_domain(node, var.as_str(), None, HybridOp::Bind)
+            };
+            let mut split: Vec<HctlTreeNode> = Vec::new();
+            for pattern in patterns.into_iter().map(bind) {
+                if !split.contains(&pattern) {
+                    split.push(pattern);
+                }
+            }
+            split.push(bind(mk_conjunction(rest)));
+            Some(mk_conjunction(split))
+        }
+        _ => None,
+    }
+}
+
+/// Rewrite the formula into a form that is (usually) evaluated faster, while preserving its
+/// semantics. The following rules are applied (bottom-up, as long as possible):
+///  - redundant negations are removed, e.g., `~EF ~phi` to `AG phi`, or `~~phi` to `phi`,
+///  - nested idempotent operators are merged, e.g., `EF EF phi` to `EF phi`,
+///  - conjuncts shared by both branches of a disjunction are hoisted out of it,
+///    e.g., `(psi & phi1) | (psi & phi2)` to `psi & (phi1 | phi2)`,
+///  - the attractor and fixed-point patterns are split off the binder, e.g.,
+///    `!{x}: (AG EF {x} & phi)` to `(!{x}: AG EF {x}) & (!{x}: phi)`.
+pub fn optimize_formula(tree: HctlTreeNode) -> HctlTreeNode {
+    let node = match tree.node_type {
+        NodeType::Terminal(_) => tree,
+        NodeType::Unary(op, child) => HctlTreeNode::mk_unary(optimize_formula(*child), op),
+        NodeType::Binary(op, left, right) => {
+            HctlTreeNode::mk_binary(optimize_formula(*left), optimize_formula(*right), op)
+        }
+        NodeType::Hybrid(op, var, domain, child) => {
+            HctlTreeNode::mk_hybrid_with_domain(optimize_formula(*child), var.as_str(), domain, op)
+        }
+    };
+    // the result of a rule may enable further rules
+    match optimize_root(&node) {
+        Some(rewritten) => optimize_formula(rewritten),
+        None => node,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preprocessing::parser::{parse_extended_formula, parse_hctl_formula};
+    use crate::preprocessing::rewriting::{optimize_formula, push_quantifiers_inward};
+
+    /// Check that the rewritten `formula` equals the `expected` formula.
+    fn assert_rewritten(formula: &str, expected: &str) {
+        let tree = push_quantifiers_inward(parse_extended_formula(formula).unwrap());
+        assert_eq!(tree, parse_extended_formula(expected).unwrap());
+    }
+
+    #[test]
+    /// Test pushing quantifiers inward past various operators.
+    fn test_push_quantifiers() {
+        assert_rewritten("3{x}: (a & EF {x})", "a & EF (3{x}: {x})");
+        assert_rewritten("V{x}: ({x} => a)", "(3{x}: {x}) => a");
+        assert_rewritten("!{x}: ~(a | AX {x})", "~(a | (!{x}: AX {x}))");
+        assert_rewritten("!{x}: (AX {x} <=> a)", "(!{x}: AX {x}) <=> a");
+        assert_rewritten(
+            "3{x}: 3{y}: (AX {x} & EF {y})",
+            "(3{x}: AX {x}) & EF (3{y}: {y})",
+        );
+        // adjacent operators are swapped, so that the outer one can be pushed further
+        assert_rewritten(
+            "3{x}: !{y}: AX ({y} & EF {x})",
+            "!{y}: 3{x}: AX ({y} & EF {x})",
+        );
+        assert_rewritten(
+            "!{x}: 3{y}: ((@{y}: a) & AX {x})",
+            "(3{y}: @{y}: a) & (!{x}: AX {x})",
+        );
+        // unused quantifiers are removed
+        assert_rewritten("!{x}: AX a", "AX a");
+    }
+
+    #[test]
+    /// Test that the operators that can not be moved are kept in place.
+    fn test_keep_quantifiers() {
+        let formulae = vec![
+            "!{x}: AX {x}",
+            "!{x}: AG EF {x}",
+            "3{x}: AX {x}",
+            "!{x}: @{x}: AX {x}",
+            "3{x} in %d%: (a & {x})",
+            "!{x}: (AX {x} & EF {x})",
+        ];
+        for formula in formulae {
+            let tree = parse_extended_formula(formula).unwrap();
+            assert_eq!(push_quantifiers_inward(tree.clone()), tree);
+        }
+        // the binder can not be moved below the jump
+        let tree = parse_hctl_formula("!{x}: 3{y}: @{y}: AX {x}").unwrap();
+        assert_eq!(
+            push_quantifiers_inward(tree),
+            parse_hctl_formula("3{y}: !{x}: @{y}: AX {x}").unwrap()
+        );
+    }
+
+    #[test]
+    /// Test the rules of the optimization pass.
+    fn test_optimize_formula() {
+        let pairs = vec![
+            ("~EF ~a", "AG a"),
+            ("~AX ~(a & ~EG ~b)", "EX (a & AF b)"),
+            ("~~a", "a"),
+            ("EF EF (a | AG AG b)", "EF (a | AG b)"),
+            // rules are applied repeatedly
+            ("~EF ~(~EF ~a)", "AG a"),
+            ("(a & EF b) | (c & a)", "a & (EF b | c)"),
+            ("(a & b) | a", "a"),
+            ("!{x}: (AG EF {x} & a)", "(!{x}: AG EF {x}) & (!{x}: a)"),
+            (
+                "!{x}: (AX {x} & (EF a & AG EF {x}))",
+                "(!{x}: AX {x}) & (!{x}: AG EF {x}) & (!{x}: EF a)",
+            ),
+        ];
+        for (formula, expected) in pairs {
+            let tree = optimize_formula(parse_hctl_formula(formula).unwrap());
+            assert_eq!(tree, parse_hctl_formula(expected).unwrap());
+        }
+
+        // formulae that can not be optimized are kept
+        let formulae = vec![
+            "~EF a",
+            "EF AF a",
+            "(a & b) | (c & d)",
+            "!{x}: AG EF {x}",
+            "!{x}: (AG EF {y} & a)",
+            "!{x} in %d%: (AG EF {x} & a)",
+        ];
+        for formula in formulae {
+            let tree = parse_extended_formula(formula).unwrap();
+            assert_eq!(optimize_formula(tree.clone()), tree);
+        }
+    }
+}
@@ -6,26 +6,125 @@ use std::fmt;
 use std::iter::Peekable;
 use std::str::Chars;
 
+/// Prefixes of the variable names of the first and the second model in the product network
+/// (see `mc_utils::build_product_network`). Only these prefixes can qualify propositions.
+pub const PRODUCT_PREFIXES: [&str; 2] = ["m1", "m2"];
+
+/// Separator between the model prefix and the original name in the product network. Qualified
+/// propositions `m1.x` in the formulae are translated to `m1__x` by the tokenizer.
+pub const PRODUCT_NAME_SEPARATOR: &str = "__";
+
+/// Keyword of the state-literal atoms (`#state{v1=1, v2=0, *}`).
+pub const STATE_KEYWORD: &str = "state";
+
+/// Keyword for the domain of quantifiers ranging over attractors (`3{x} in attractors:`).
+pub const ATTRACTORS_DOMAIN: &str = "attractors";
+
+/// Keyword for the built-in domain of fair attractor states (`3{x} in fair_attractors:`).
+pub const FAIR_ATTRACTORS_DOMAIN: &str = "fair_attractors";
+
+/// Textual aliases of the operators (e.g., for properties written for other model checkers), and
+/// the symbols of the corresponding operators. The aliases are rejected in the strict mode (see
+/// [check_strict_syntax]).
+pub const OPERATOR_ALIASES: [(&str, &str); 8] = [
+    ("NOT", "~"),
+    ("AND", "&"),
+    ("OR", "|"),
+    ("IMPLIES", "=>"),
+    ("EXISTS", "3"),
+    ("FORALL", "V"),
+    ("BIND", "!"),
+    ("JUMP", "@"),
+];
+
+/// Domain of a variable bound by a hybrid operator.
+#[derive(Clone)]
+enum VarDomain {
+    /// No domain is specified.
+    Any,
+    /// Domain given by a wild-card proposition (`in %domain%`).
+    WildCard(String),
+    /// The variable ranges over attractor states (`in attractors`).
+    Attractors,
+    /// Domain given inline by a propositional expression (`in [expression]`).
+    Inline(String),
+    /// The variable ranges over states of fair attractors (`in fair_attractors`).
+    FairAttractors,
+}
+
+/// Syntax error found in a formula.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyntaxError {
+    /// Position of the character (indexed from 1) at which the error was detected, if known
+    /// (errors found during parsing are not tied to particular characters).
+    pub position: Option<usize>,
+    /// Description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} (at character {position})", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Errors collected while tokenizing with recovery.
+struct Recovery {
+    /// Number of characters of the whole formula.
+    num_chars: usize,
+    /// Errors found so far.
+    errors: Vec<SyntaxError>,
+}
+
+impl Recovery {
+    /// Report an error detected after the characters preceding the `remaining_chars` were read.
+    fn report(&mut self, remaining_chars: &Peekable<Chars>, message: String) {
+        let position = self.num_chars - remaining_chars.clone().count();
+        self.errors.push(SyntaxError {
+            position: Some(position),
+            message,
+        });
+    }
+}
+
+/// Predicate for whether the domain of a hybrid operator is given inline by a propositional
+/// expression (`[expression]`), instead of a label of a wild-card domain (`%domain%`).
+pub fn is_inline_domain(domain: &str) -> bool {
+    domain.starts_with('[') && domain.ends_with(']')
+}
+
 /// Enum of all possible tokens occurring in a HCTL formula string.
+///
+/// The enum is `non_exhaustive`, since new kinds of tokens (such as the [HctlToken::Invalid]
+/// placeholder of the tokenizing with recovery) are added together with new syntax. Matches on
+/// the tokens outside of this crate thus need a wild-card arm.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
 pub enum HctlToken {
-    /// Unary operators: '~','EX','AX','EF','AF','EG','AG'.
+    /// Unary operators: '~','EX','AX','EF','AF','EG','AG','EX_{v}','AX_{v}'.
     Unary(UnaryOp),
     /// Binary operators: '&','|','^','=>','<=>','EU','AU','EW','AW'.
     Binary(BinaryOp),
     /// Hybrid operator (and its variable, and optional domain): '!', '@', '3', 'V'.
     Hybrid(HybridOp, String, Option<String>),
-    /// Proposition, variable, 'true'/'false' constants, wild-card property.
+    /// Quantifier over attractors (and its variable): '3{x} in attractors:', 'V{x} in attractors:'.
+    AttractorQuantifier(HybridOp, String),
+    /// Proposition, variable, 'true'/'false' constants, wild-card property, state literal.
     Atom(Atomic),
     /// A block of tokens inside parentheses.
     Tokens(Vec<HctlToken>),
+    /// Placeholder of an invalid segment, skipped when tokenizing with recovery.
+    Invalid,
 }
 
 /// Try to tokenize given HCTL formula string.
 ///
 /// This is a wrapper for the (more general) recursive [try_tokenize_formula]` function.
 pub fn try_tokenize_formula(formula: String) -> Result<Vec<HctlToken>, String> {
-    try_tokenize_recursive(&mut formula.chars().peekable(), true, false)
+    try_tokenize_recursive(&mut formula.chars().peekable(), true, false, false, None)
 }
 
 /// Try to tokenize given `extended` HCTL formula string. That means that formula can include
@@ -33,204 +132,351 @@ pub fn try_tokenize_formula(formula: String) -> Result<Vec<HctlToken>, String> {
 ///
 /// This is a wrapper for the (more general) recursive [try_tokenize_formula]` function.
 pub fn try_tokenize_extended_formula(formula: String) -> Result<Vec<HctlToken>, String> {
-    try_tokenize_recursive(&mut formula.chars().peekable(), true, true)
+    try_tokenize_recursive(&mut formula.chars().peekable(), true, true, false, None)
+}
+
+/// Check that the (possibly extended) HCTL formula string does not use any of the textual aliases
+/// of the operators (see [OPERATOR_ALIASES]), which are rejected in the strict mode. Other syntax
+/// errors found by the tokenizer are returned as well.
+pub fn check_strict_syntax(formula: &str) -> Result<(), String> {
+    try_tokenize_recursive(&mut formula.chars().peekable(), true, true, true, None)?;
+    Ok(())
+}
+
+/// Tokenize given HCTL formula string (extended, if `parse_wild_cards` is `true`), recovering from
+/// the errors, so that all the (independent) errors are reported together.
+///
+/// Return the tokens (with the invalid segments replaced by [HctlToken::Invalid]), and the list
+/// of errors found (empty if the formula was tokenized successfully).
+pub fn try_tokenize_with_recovery(
+    formula: &str,
+    parse_wild_cards: bool,
+) -> (Vec<HctlToken>, Vec<SyntaxError>) {
+    let mut recovery = Recovery {
+        num_chars: formula.chars().count(),
+        errors: Vec::new(),
+    };
+    let tokens = try_tokenize_recursive(
+        &mut formula.chars().peekable(),
+        true,
+        parse_wild_cards,
+        false,
+        Some(&mut recovery),
+    )
+    .unwrap_or_default(); // errors are reported to the recovery instead
+    (tokens, recovery.errors)
 }
 
 /// Process a peekable iterator of characters into a vector of `HctlToken`s.
 ///
 /// If `parse_wild_cards` is `true`, `wild-card propositions` and `variable domains` are allowed to
 /// be in the formula. Otherwise, only classical HCTL components are allowed.
+///
+/// If `strict` is `true`, the textual aliases of the operators are not allowed.
+///
+/// If `recovery` is given, errors are reported to it instead of being returned, and the invalid
+/// segments are replaced by [HctlToken::Invalid] (see [try_tokenize_with_recovery]).
 fn try_tokenize_recursive(
     input_chars: &mut Peekable<Chars>,
     top_level: bool,
     parse_wild_cards: bool,
+    strict: bool,
+    mut recovery: Option<&mut Recovery>,
 ) -> Result<Vec<HctlToken>, String> {
     let mut output = Vec::new();
 
     while let Some(c) = input_chars.next() {
-        match c {
-            c if c.is_whitespace() => {} // skip whitespace
-            '~' => output.push(HctlToken::Unary(UnaryOp::Not)),
-            '&' => output.push(HctlToken::Binary(BinaryOp::And)),
-            '|' => output.push(HctlToken::Binary(BinaryOp::Or)),
-            '^' => output.push(HctlToken::Binary(BinaryOp::Xor)),
-            '=' => {
+        let result = match c {
+            ')' => {
+                if !top_level {
+                    return Ok(output);
+                }
+                Err("Unexpected ')' without opening counterpart.".to_string())
+            }
+            // start a nested token group
+            '(' => try_tokenize_recursive(
+                input_chars,
+                false,
+                parse_wild_cards,
+                strict,
+                recovery.as_deref_mut(),
+            )
+            .map(|token_group| output.push(HctlToken::Tokens(token_group))),
+            c => try_tokenize_token(c, input_chars, parse_wild_cards, strict, &mut output),
+        };
+        if let Err(message) = result {
+            let Some(recovery) = recovery.as_deref_mut() else {
+                return Err(message);
+            };
+            recovery.report(input_chars, message);
+            skip_invalid_segment(input_chars);
+            output.push(HctlToken::Invalid);
+        }
+    }
+
+    if top_level {
+        return Ok(output);
+    }
+    let message = "Expected ')' to previously encountered opening counterpart.".to_string();
+    match recovery {
+        Some(recovery) => {
+            recovery.report(input_chars, message);
+            Ok(output)
+        }
+        None => Err(message),
+    }
+}
+
+/// Process the token starting with the (already consumed) character `c`, and push it to the
+/// `output`. Parentheses are processed by the caller.
+fn try_tokenize_token(
+    c: char,
+    input_chars: &mut Peekable<Chars>,
+    parse_wild_cards: bool,
+    strict: bool,
+    output: &mut Vec<HctlToken>,
+) -> Result<(), String> {
+    match c {
+        c if c.is_whitespace() => {} // skip whitespace
+        '~' => output.push(HctlToken::Unary(UnaryOp::Not)),
+        '&' => output.push(HctlToken::Binary(BinaryOp::And)),
+        '|' => output.push(HctlToken::Binary(BinaryOp::Or)),
+        '^' => output.push(HctlToken::Binary(BinaryOp::Xor)),
+        '=' => {
+            if Some('>') == input_chars.next() {
+                output.push(HctlToken::Binary(BinaryOp::Imp));
+            } else {
+                return Err("Expected '>' after '='.".to_string());
+            }
+        }
+        '<' => {
+            if Some('=') == input_chars.next() {
                 if Some('>') == input_chars.next() {
-                    output.push(HctlToken::Binary(BinaryOp::Imp));
+                    output.push(HctlToken::Binary(BinaryOp::Iff));
                 } else {
-                    return Err("Expected '>' after '='.".to_string());
+                    return Err("Expected '>' after '<='.".to_string());
                 }
+            } else {
+                return Err("Expected '=' after '<'.".to_string());
             }
-            '<' => {
-                if Some('=') == input_chars.next() {
-                    if Some('>') == input_chars.next() {
-                        output.push(HctlToken::Binary(BinaryOp::Iff));
-                    } else {
-                        return Err("Expected '>' after '<='.".to_string());
+        }
+        // '>' is invalid as a start of a token
+        '>' => return Err("Unexpected '>'.".to_string()),
+
+        // pattern E{temporal}, must not be just a part of some proposition name
+        'E' if is_valid_temp_op(input_chars.peek()) || input_chars.peek() == Some(&'P') => {
+            if let Some(c2) = input_chars.next() {
+                // transition-labelled next operator EX_{var}
+                if c2 == 'X' {
+                    if let Some(var) = collect_update_var(input_chars)? {
+                        output.push(HctlToken::Unary(UnaryOp::EXVar(var)));
+                        return Ok(());
                     }
-                } else {
-                    return Err("Expected '=' after '<'.".to_string());
                 }
-            }
-            // '>' is invalid as a start of a token
-            '>' => return Err("Unexpected '>'.".to_string()),
-
-            // pattern E{temporal}, must not be just a part of some proposition name
-            'E' if is_valid_temp_op(input_chars.peek()) => {
-                if let Some(c2) = input_chars.next() {
-                    // check that it is not just a part of some proposition name
-                    if let Some(c3) = input_chars.peek() {
-                        if is_valid_in_name(*c3) {
-                            let name = collect_name(input_chars)?;
-                            output.push(HctlToken::Atom(Atomic::Prop(
-                                c.to_string() + c2.to_string().as_str() + &name,
-                            )));
-                            continue;
-                        }
+                // check that it is not just a part of some proposition name
+                if let Some(c3) = input_chars.peek() {
+                    if is_valid_in_name(*c3) {
+                        let name =
+                            c.to_string() + c2.to_string().as_str() + &collect_name(input_chars)?;
+                        return push_name_token(
+                            name,
+                            input_chars,
+                            parse_wild_cards,
+                            strict,
+                            output,
+                        );
                     }
+                }
 
-                    match c2 {
-                        'X' => output.push(HctlToken::Unary(UnaryOp::EX)),
-                        'F' => output.push(HctlToken::Unary(UnaryOp::EF)),
-                        'G' => output.push(HctlToken::Unary(UnaryOp::EG)),
-                        'U' => output.push(HctlToken::Binary(BinaryOp::EU)),
-                        'W' => output.push(HctlToken::Binary(BinaryOp::EW)),
-                        _ => return Err(format!("Unexpected char '{c2}' after 'E'.")),
-                    }
-                } else {
-                    return Err("Expected one of '{X,F,G,U,W}' after 'E'.".to_string());
+                match c2 {
+                    'X' => output.push(HctlToken::Unary(UnaryOp::EX)),
+                    'F' => output.push(HctlToken::Unary(UnaryOp::EF)),
+                    'G' => output.push(HctlToken::Unary(UnaryOp::EG)),
+                    'P' => output.push(HctlToken::Unary(UnaryOp::EP)),
+                    'U' => output.push(HctlToken::Binary(BinaryOp::EU)),
+                    'W' => output.push(HctlToken::Binary(BinaryOp::EW)),
+                    _ => return Err(format!("Unexpected char '{c2}' after 'E'.")),
                 }
+            } else {
+                return Err("Expected one of '{X,F,G,P,U,W}' after 'E'.".to_string());
             }
+        }
 
-            // pattern A{temporal}, must not be just a part of some proposition name
-            'A' if is_valid_temp_op(input_chars.peek()) => {
-                if let Some(c2) = input_chars.next() {
-                    // check that it is not just a part of some proposition name
-                    if let Some(c3) = input_chars.peek() {
-                        if is_valid_in_name(*c3) {
-                            let name = collect_name(input_chars)?;
-                            output.push(HctlToken::Atom(Atomic::Prop(
-                                c.to_string() + c2.to_string().as_str() + &name,
-                            )));
-                            continue;
-                        }
+        // pattern A{temporal}, must not be just a part of some proposition name
+        'A' if is_valid_temp_op(input_chars.peek()) => {
+            if let Some(c2) = input_chars.next() {
+                // transition-labelled next operator AX_{var}
+                if c2 == 'X' {
+                    if let Some(var) = collect_update_var(input_chars)? {
+                        output.push(HctlToken::Unary(UnaryOp::AXVar(var)));
+                        return Ok(());
                     }
-                    match c2 {
-                        'X' => output.push(HctlToken::Unary(UnaryOp::AX)),
-                        'F' => output.push(HctlToken::Unary(UnaryOp::AF)),
-                        'G' => output.push(HctlToken::Unary(UnaryOp::AG)),
-                        'U' => output.push(HctlToken::Binary(BinaryOp::AU)),
-                        'W' => output.push(HctlToken::Binary(BinaryOp::AW)),
-                        _ => return Err(format!("Unexpected char '{c2}' after 'A'.")),
+                }
+                // check that it is not just a part of some proposition name
+                if let Some(c3) = input_chars.peek() {
+                    if is_valid_in_name(*c3) {
+                        let name =
+                            c.to_string() + c2.to_string().as_str() + &collect_name(input_chars)?;
+                        return push_name_token(
+                            name,
+                            input_chars,
+                            parse_wild_cards,
+                            strict,
+                            output,
+                        );
                     }
-                } else {
-                    return Err("Expected one of '{X,F,G,U,W}' after 'A'.".to_string());
                 }
-            }
-            '!' => {
-                // collect the variable name via inside helper function
-                let (name, domain) =
-                    collect_var_and_dom_from_operator(input_chars, '!', parse_wild_cards)?;
-                output.push(HctlToken::Hybrid(HybridOp::Bind, name, domain));
-            }
-            // "3" can be either exist quantifier or part of some proposition
-            '3' if !is_valid_in_name_optional(input_chars.peek()) => {
-                // collect the variable name via inside helper function
-                let (name, domain) =
-                    collect_var_and_dom_from_operator(input_chars, '3', parse_wild_cards)?;
-                output.push(HctlToken::Hybrid(HybridOp::Exists, name, domain));
-            }
-            // "V" can be either forall quantifier or part of some proposition
-            'V' if !is_valid_in_name_optional(input_chars.peek()) => {
-                // collect the variable name via inside helper function
-                let (name, domain) =
-                    collect_var_and_dom_from_operator(input_chars, 'V', parse_wild_cards)?;
-                output.push(HctlToken::Hybrid(HybridOp::Forall, name, domain));
-            }
-            '@' => {
-                // collect the variable name via inside helper function
-                let (name, domain) = collect_var_and_dom_from_operator(input_chars, '@', false)?;
-                if domain.is_some() {
-                    return Err("Cannot specify domain after '@'.".to_string());
+                match c2 {
+                    'X' => output.push(HctlToken::Unary(UnaryOp::AX)),
+                    'F' => output.push(HctlToken::Unary(UnaryOp::AF)),
+                    'G' => output.push(HctlToken::Unary(UnaryOp::AG)),
+                    'U' => output.push(HctlToken::Binary(BinaryOp::AU)),
+                    'W' => output.push(HctlToken::Binary(BinaryOp::AW)),
+                    _ => return Err(format!("Unexpected char '{c2}' after 'A'.")),
                 }
-                output.push(HctlToken::Hybrid(HybridOp::Jump, name, None));
+            } else {
+                return Err("Expected one of '{X,F,G,U,W}' after 'A'.".to_string());
             }
-            // long name for hybrid operators (\bind, \exists, \forall, \jump)
-            '\\' => {
-                // collect the name of the operator, and its variable/domain
-                let operator_name = collect_name(input_chars)?;
-                if &operator_name == "exists" {
-                    let (name, domain) =
-                        collect_var_and_dom_from_operator(input_chars, '3', parse_wild_cards)?;
-                    output.push(HctlToken::Hybrid(HybridOp::Exists, name, domain));
-                } else if operator_name == "forall" {
-                    let (name, domain) =
-                        collect_var_and_dom_from_operator(input_chars, 'V', parse_wild_cards)?;
-                    output.push(HctlToken::Hybrid(HybridOp::Forall, name, domain));
-                } else if operator_name == "bind" {
-                    let (name, domain) =
-                        collect_var_and_dom_from_operator(input_chars, '!', parse_wild_cards)?;
-                    output.push(HctlToken::Hybrid(HybridOp::Bind, name, domain));
-                } else if operator_name == "jump" {
-                    let (name, domain) =
-                        collect_var_and_dom_from_operator(input_chars, '@', false)?;
-                    if domain.is_some() {
-                        return Err("Cannot specify domain after '@' operator.".to_string());
-                    }
-                    output.push(HctlToken::Hybrid(HybridOp::Jump, name, None));
-                } else {
-                    return Err(format!("Invalid hybrid operator `\\{operator_name}`."));
-                }
+        }
+        '!' => {
+            // collect the variable name via inside helper function
+            let (names, domain) =
+                collect_var_and_dom_from_operator(input_chars, '!', parse_wild_cards)?;
+            output.extend(mk_hybrid_tokens(HybridOp::Bind, names, domain)?);
+        }
+        // "3" can be either exist quantifier or part of some proposition
+        '3' if !is_valid_in_name_optional(input_chars.peek()) => {
+            // collect the variable name via inside helper function
+            let (names, domain) =
+                collect_var_and_dom_from_operator(input_chars, '3', parse_wild_cards)?;
+            output.extend(mk_hybrid_tokens(HybridOp::Exists, names, domain)?);
+        }
+        // "V" can be either forall quantifier or part of some proposition
+        'V' if !is_valid_in_name_optional(input_chars.peek()) => {
+            // collect the variable name via inside helper function
+            let (names, domain) =
+                collect_var_and_dom_from_operator(input_chars, 'V', parse_wild_cards)?;
+            output.extend(mk_hybrid_tokens(HybridOp::Forall, names, domain)?);
+        }
+        '@' => {
+            // collect the variable name via inside helper function
+            let (names, domain) =
+                collect_var_and_dom_from_operator(input_chars, '@', parse_wild_cards)?;
+            output.extend(mk_hybrid_tokens(HybridOp::Jump, names, domain)?);
+        }
+        // long name for hybrid operators (\bind, \exists, \forall, \jump)
+        '\\' => {
+            // collect the name of the operator, and its variable/domain
+            let operator_name = collect_name(input_chars)?;
+            let (operator, op_char) = match operator_name.as_str() {
+                "exists" => (HybridOp::Exists, '3'),
+                "forall" => (HybridOp::Forall, 'V'),
+                "bind" => (HybridOp::Bind, '!'),
+                "jump" => (HybridOp::Jump, '@'),
+                _ => return Err(format!("Invalid hybrid operator `\\{operator_name}`.")),
+            };
+            let (names, domain) =
+                collect_var_and_dom_from_operator(input_chars, op_char, parse_wild_cards)?;
+            output.extend(mk_hybrid_tokens(operator, names, domain)?);
+        }
+        // variable name
+        '{' => {
+            let name = collect_name(input_chars)?;
+            if name.is_empty() {
+                return Err("Variable name can't be empty.".to_string());
             }
-            ')' => {
-                return if !top_level {
-                    Ok(output)
-                } else {
-                    Err("Unexpected ')' without opening counterpart.".to_string())
-                }
+            output.push(HctlToken::Atom(Atomic::Var(name)));
+            if Some('}') != input_chars.next() {
+                return Err("Expected '}' without opening counterpart.".to_string());
             }
-            '(' => {
-                // start a nested token group
-                let token_group = try_tokenize_recursive(input_chars, false, parse_wild_cards)?;
-                output.push(HctlToken::Tokens(token_group));
+        }
+        // wild-card proposition name, possibly with HCTL variables of a wild-card relation
+        '%' if parse_wild_cards => {
+            let name = collect_name(input_chars)?;
+            if name.is_empty() {
+                return Err("Wild-card proposition name can't be empty.".to_string());
             }
-            // variable name
-            '{' => {
-                let name = collect_name(input_chars)?;
-                if name.is_empty() {
-                    return Err("Variable name can't be empty.".to_string());
-                }
-                output.push(HctlToken::Atom(Atomic::Var(name)));
-                if Some('}') != input_chars.next() {
-                    return Err("Expected '}' without opening counterpart.".to_string());
-                }
+            if input_chars.next_if_eq(&'(').is_some() {
+                let vars = collect_relation_vars(input_chars)?;
+                output.push(HctlToken::Atom(Atomic::WildCardRelation(name, vars)));
+            } else {
+                output.push(HctlToken::Atom(Atomic::WildCardProp(name)));
             }
-            // wild-card proposition name
-            '%' if parse_wild_cards => {
-                let name = collect_name(input_chars)?;
-                if name.is_empty() {
-                    return Err("Wild-card proposition name can't be empty.".to_string());
+            if Some('%') != input_chars.next() {
+                return Err("Expected '%' after wild-card proposition name.".to_string());
+            }
+        }
+        // state literal
+        '#' => output.push(HctlToken::Atom(collect_state_literal(input_chars)?)),
+        // proposition name or constant
+        // these 2 are NOT distinguished now but later during parsing
+        c if is_valid_in_name(c) => {
+            let mut name = c.to_string() + &collect_name(input_chars)?;
+            // qualified proposition `m1.x` refers to a variable of a product network
+            if PRODUCT_PREFIXES.contains(&name.as_str()) && input_chars.next_if_eq(&'.').is_some() {
+                let qualified_name = collect_name(input_chars)?;
+                if qualified_name.is_empty() {
+                    return Err(format!("Expected a name after `{name}.`."));
                 }
-                output.push(HctlToken::Atom(Atomic::WildCardProp(name)));
-                if Some('%') != input_chars.next() {
-                    return Err("Expected '%' after wild-card proposition name.".to_string());
+                if qualified_name.contains(PRODUCT_NAME_SEPARATOR) {
+                    return Err(format!(
+                        "Qualified name `{name}.{qualified_name}` can't contain `{PRODUCT_NAME_SEPARATOR}`."
+                    ));
                 }
+                name = format!("{name}{PRODUCT_NAME_SEPARATOR}{qualified_name}");
+                output.push(HctlToken::Atom(Atomic::Prop(name)));
+            } else {
+                push_name_token(name, input_chars, parse_wild_cards, strict, output)?;
             }
-            // proposition name or constant
-            // these 2 are NOT distinguished now but later during parsing
-            c if is_valid_in_name(c) => {
-                let name = collect_name(input_chars)?;
-                output.push(HctlToken::Atom(Atomic::Prop(c.to_string() + &name)));
-            }
-            _ => return Err(format!("Unexpected char '{c}'.")),
         }
+        _ => return Err(format!("Unexpected char '{c}'.")),
     }
+    Ok(())
+}
 
-    if top_level {
-        Ok(output)
-    } else {
-        Err("Expected ')' to previously encountered opening counterpart.".to_string())
+/// Push the token for the `name`, which is either a textual alias of an operator (see
+/// [OPERATOR_ALIASES]), or a proposition (or a constant).
+fn push_name_token(
+    name: String,
+    input_chars: &mut Peekable<Chars>,
+    parse_wild_cards: bool,
+    strict: bool,
+    output: &mut Vec<HctlToken>,
+) -> Result<(), String> {
+    let Some((_, symbol)) = OPERATOR_ALIASES.iter().find(|(alias, _)| *alias == name) else {
+        output.push(HctlToken::Atom(Atomic::Prop(name)));
+        return Ok(());
+    };
+    if strict {
+        return Err(format!(
+            "Operator alias `{name}` is not allowed in the strict mode, use `{symbol}` instead."
+        ));
     }
+    let operator = match *symbol {
+        "3" => HybridOp::Exists,
+        "V" => HybridOp::Forall,
+        "!" => HybridOp::Bind,
+        "@" => HybridOp::Jump,
+        // aliases of unary and binary operators are tokenized the same way as their symbols
+        _ => {
+            output.extend(try_tokenize_formula(symbol.to_string())?);
+            return Ok(());
+        }
+    };
+    let op_char = symbol.chars().next().unwrap();
+    let (names, domain) =
+        collect_var_and_dom_from_operator(input_chars, op_char, parse_wild_cards)?;
+    output.extend(mk_hybrid_tokens(operator, names, domain)?);
+    Ok(())
+}
+
+/// Skip the rest of an invalid segment of the formula, i.e., everything until the next
+/// whitespace, parenthesis, or boolean operator, where the tokenizing can continue.
+fn skip_invalid_segment(chars: &mut Peekable<Chars>) {
+    while chars
+        .next_if(|c| !c.is_whitespace() && !"()&|^~".contains(*c))
+        .is_some()
+    {}
 }
 
 /// Check all whitespaces at the front of the iterator.
@@ -280,66 +526,244 @@ fn collect_name(input_chars: &mut Peekable<Chars>) -> Result<String, String> {
     Ok(name.into_iter().collect())
 }
 
-/// Retrieve the name of the variable, and optional name for the domain, bound by a hybrid operator.
-/// Operator character is consumed by caller and is given as input for error msg purposes.
+/// Retrieve the network variable of a transition-labelled next operator, given as `_{var}` right
+/// after `EX` or `AX`. If the input does not continue with `_{`, nothing is consumed (the operator
+/// can still be a part of some proposition name, such as `EX_1`).
+fn collect_update_var(input_chars: &mut Peekable<Chars>) -> Result<Option<String>, String> {
+    let mut lookahead = input_chars.clone();
+    if lookahead.next() != Some('_') || lookahead.next() != Some('{') {
+        return Ok(None);
+    }
+    // skip the '_{' part
+    input_chars.next();
+    input_chars.next();
+    let name = collect_name(input_chars)?;
+    if name.is_empty() {
+        return Err("Network variable name can't be empty (in 'X_' segment).".to_string());
+    }
+    if Some('}') != input_chars.next() {
+        return Err("Expected '}' after network variable name (in 'X_' segment).".to_string());
+    }
+    Ok(Some(name))
+}
+
+/// Retrieve the state literal `#state{v1=1, v2=0, *}` from the input, where `*` (which must be
+/// the last item) marks a partial state. The character '#' is already consumed by the caller.
+fn collect_state_literal(input_chars: &mut Peekable<Chars>) -> Result<Atomic, String> {
+    if collect_name(input_chars)? != STATE_KEYWORD {
+        return Err(format!("Expected '{STATE_KEYWORD}' after '#'."));
+    }
+    skip_whitespaces(input_chars);
+    if Some('{') != input_chars.next() {
+        return Err(format!("Expected '{{' after '#{STATE_KEYWORD}'."));
+    }
+    let mut values = Vec::new();
+    let mut partial = false;
+    loop {
+        skip_whitespaces(input_chars);
+        if partial {
+            // `*` must be the last item
+            break;
+        }
+        if input_chars.next_if_eq(&'*').is_some() {
+            partial = true;
+        } else {
+            let name = collect_name(input_chars)?;
+            if name.is_empty() {
+                return Err("Expected a variable name in the state literal.".to_string());
+            }
+            skip_whitespaces(input_chars);
+            if Some('=') != input_chars.next() {
+                return Err(format!("Expected '=' after `{name}` in the state literal."));
+            }
+            skip_whitespaces(input_chars);
+            let value = match input_chars.next() {
+                Some('0') => false,
+                Some('1') => true,
+                _ => return Err(format!("Expected '0' or '1' as the value of `{name}`.")),
+            };
+            values.push((name, value));
+        }
+        skip_whitespaces(input_chars);
+        if input_chars.next_if_eq(&',').is_none() {
+            break;
+        }
+    }
+    if Some('}') != input_chars.next() {
+        return Err("Expected '}' at the end of the state literal.".to_string());
+    }
+    Ok(Atomic::State(values, partial))
+}
+
+/// Retrieve the HCTL variables of a wild-card relation, in the form `{x}, {y})` (the list can
+/// contain whitespaces). The opening '(' is already consumed by the caller.
+fn collect_relation_vars(input_chars: &mut Peekable<Chars>) -> Result<Vec<String>, String> {
+    let mut vars = Vec::new();
+    loop {
+        skip_whitespaces(input_chars);
+        if Some('{') != input_chars.next() {
+            return Err("Expected '{' before variable of a wild-card relation.".to_string());
+        }
+        let name = collect_name(input_chars)?;
+        if name.is_empty() {
+            return Err("Variable name can't be empty.".to_string());
+        }
+        if Some('}') != input_chars.next() {
+            return Err("Expected '}' after variable of a wild-card relation.".to_string());
+        }
+        vars.push(name);
+        skip_whitespaces(input_chars);
+        match input_chars.next() {
+            Some(',') => {}
+            Some(')') => return Ok(vars),
+            _ => return Err("Expected ',' or ')' after variable of a wild-card relation.".into()),
+        }
+    }
+}
+
+/// Retrieve the names of the variables (usually just one), and optional domain, bound by a hybrid
+/// operator. Operator character is consumed by caller and is given as input for error msg purposes.
 ///
-/// Domains are allowed (but not required) only if `parse_domains` is true.
+/// Wild-card domains are allowed (but not required) only if `parse_domains` is true. The domain
+/// of attractor states (`in attractors`) and inline domains (`in [expression]`) are allowed in
+/// all formulae.
 fn collect_var_and_dom_from_operator(
     input_chars: &mut Peekable<Chars>,
     operator: char,
     parse_domains: bool,
-) -> Result<(String, Option<String>), String> {
+) -> Result<(Vec<String>, VarDomain), String> {
     // there might be few spaces first
     skip_whitespaces(input_chars);
-    // now collect the variable name itself- it is in the form {var_name} for now
+    // now collect the variable names - in the form {var_name}, or {var_name1, var_name2, ...}
     if Some('{') != input_chars.next() {
         return Err(format!("Expected '{{' after '{operator}'."));
     }
-    let name = collect_name(input_chars)?;
-    if name.is_empty() {
-        return Err("Variable name can't be empty.".to_string());
-    }
-    if Some('}') != input_chars.next() {
-        return Err(format!(
-            "Expected '}}' after variable name (in '{operator}' segment)."
-        ));
+    let mut names = Vec::new();
+    loop {
+        let name = collect_name(input_chars)?;
+        if name.is_empty() {
+            return Err("Variable name can't be empty.".to_string());
+        }
+        if names.contains(&name) {
+            return Err(format!(
+                "Variable {name} is listed several times (in '{operator}' segment)."
+            ));
+        }
+        names.push(name);
+        match input_chars.next() {
+            Some('}') => break,
+            Some(',') => skip_whitespaces(input_chars),
+            _ => {
+                return Err(format!(
+                    "Expected '}}' after variable name (in '{operator}' segment)."
+                ))
+            }
+        }
     }
     skip_whitespaces(input_chars);
 
-    let mut domain = None;
-    if parse_domains {
-        // there are 2 options:
-        // a) domain is specified and thus relevant chars form "in %domain%:"
-        // b) domain is not specified and thus next char must be ":"
-        if let Some('i') = input_chars.peek() {
-            // the "in" part
-            input_chars.next();
-            if Some('n') != input_chars.next() {
-                return Err("Expected 'n' after 'i' (in domain specification).".to_string());
-            }
-            skip_whitespaces(input_chars);
+    // there are 4 options:
+    // a) domain is specified and thus relevant chars form "in %domain%:"
+    // b) domain is given inline and thus relevant chars form "in [expression]:"
+    // c) variable ranges over attractors and thus relevant chars form "in attractors:"
+    // d) domain is not specified and thus next char must be ":"
+    let mut domain = VarDomain::Any;
+    if let Some('i') = input_chars.peek() {
+        // the "in" part
+        input_chars.next();
+        if Some('n') != input_chars.next() {
+            return Err("Expected 'n' after 'i' (in domain specification).".to_string());
+        }
+        skip_whitespaces(input_chars);
 
+        if input_chars.next_if_eq(&'%').is_some() {
             // the "%domain%" part
-            if Some('%') != input_chars.next() {
-                return Err("Expected '%' before domain name.".to_string());
+            if !parse_domains {
+                return Err("Wild-card domains are only allowed in extended formulae.".to_string());
             }
             let domain_name = collect_name(input_chars)?;
             if domain_name.is_empty() {
                 return Err("Variable's domain name can't be empty.".to_string());
             }
-            domain = Some(domain_name);
+            if domain_name == FAIR_ATTRACTORS_DOMAIN {
+                return Err(format!("Domain name '{domain_name}' is reserved."));
+            }
+            domain = VarDomain::WildCard(domain_name);
             if Some('%') != input_chars.next() {
                 return Err("Expected '%' after domain name.".to_string());
             }
-            skip_whitespaces(input_chars);
+        } else if input_chars.next_if_eq(&'[').is_some() {
+            // the "[expression]" part, the expression itself is checked by the parser
+            let mut expression = String::new();
+            loop {
+                match input_chars.next() {
+                    Some(']') => break,
+                    Some(c) => expression.push(c),
+                    None => return Err("Expected ']' after domain expression.".to_string()),
+                }
+            }
+            if expression.trim().is_empty() {
+                return Err("Variable's domain expression can't be empty.".to_string());
+            }
+            domain = VarDomain::Inline(format!("[{expression}]"));
+        } else {
+            domain = match collect_name(input_chars)?.as_str() {
+                ATTRACTORS_DOMAIN => VarDomain::Attractors,
+                FAIR_ATTRACTORS_DOMAIN => VarDomain::FairAttractors,
+                _ => {
+                    return Err(format!(
+                        "Expected '%', '[', '{ATTRACTORS_DOMAIN}' or '{FAIR_ATTRACTORS_DOMAIN}' before domain name."
+                    ))
+                }
+            };
         }
+        skip_whitespaces(input_chars);
     }
     if Some(':') != input_chars.next() {
         return Err(format!(
             "Expected ':' after segment of hybrid operator '{operator}'."
         ));
     }
-    Ok((name, domain))
+    Ok((names, domain))
+}
+
+/// Make the tokens for the hybrid operator with the given variables and their domain.
+/// Several variables `!{x, y}:` are a syntactic sugar for nested operators `!{x}: !{y}:` (so `y`
+/// is in the scope of `x`), all with the same domain. Jump can only have a single variable.
+fn mk_hybrid_tokens(
+    op: HybridOp,
+    names: Vec<String>,
+    domain: VarDomain,
+) -> Result<Vec<HctlToken>, String> {
+    if op == HybridOp::Jump && names.len() > 1 {
+        return Err("Cannot jump to several variables at once.".to_string());
+    }
+    names
+        .into_iter()
+        .map(|name| mk_hybrid_token(op.clone(), name, domain.clone()))
+        .collect()
+}
+
+/// Make the token for the hybrid operator with the given variable and its domain.
+/// Only quantifiers can range over attractors, other domains are allowed for all hybrid operators.
+fn mk_hybrid_token(op: HybridOp, name: String, domain: VarDomain) -> Result<HctlToken, String> {
+    match (op, domain) {
+        (op @ (HybridOp::Exists | HybridOp::Forall), VarDomain::Attractors) => {
+            Ok(HctlToken::AttractorQuantifier(op, name))
+        }
+        (op, VarDomain::Attractors) => Err(format!(
+            "Only quantifiers can range over attractors, not '{op}'."
+        )),
+        (op, VarDomain::WildCard(domain) | VarDomain::Inline(domain)) => {
+            Ok(HctlToken::Hybrid(op, name, Some(domain)))
+        }
+        (op, VarDomain::FairAttractors) => Ok(HctlToken::Hybrid(
+            op,
+            name,
+            Some(FAIR_ATTRACTORS_DOMAIN.to_string()),
+        )),
+        (op, VarDomain::Any) => Ok(HctlToken::Hybrid(op, name, None)),
+    }
 }
 
 impl fmt::Display for HctlToken {
@@ -347,7 +771,7 @@ impl fmt::Display for HctlToken {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             HctlToken::Unary(UnaryOp::Not) => write!(f, "~"),
-            HctlToken::Unary(c) => write!(f, "{c:?}"), // unary temporal
+            HctlToken::Unary(c) => write!(f, "{c}"), // unary temporal
             HctlToken::Binary(BinaryOp::And) => write!(f, "&"),
             HctlToken::Binary(BinaryOp::Or) => write!(f, "|"),
             HctlToken::Binary(BinaryOp::Xor) => write!(f, "^"),
@@ -355,12 +779,23 @@ impl fmt::Display for HctlToken {
             HctlToken::Binary(BinaryOp::Iff) => write!(f, "<=>"),
             HctlToken::Binary(c) => write!(f, "{c:?}"), // binary temporal
             HctlToken::Hybrid(op, var, None) => write!(f, "{op:?} {{{var}}}:"),
+            HctlToken::Hybrid(op, var, Some(dom))
+                if is_inline_domain(dom) || dom == FAIR_ATTRACTORS_DOMAIN =>
+            {
+                write!(f, "{op:?} {{{var}}} in {dom}:")
+            }
             HctlToken::Hybrid(op, var, Some(dom)) => write!(f, "{op:?} {{{var}}} in %{dom}%:"),
+            HctlToken::AttractorQuantifier(op, var) => {
+                write!(f, "{op:?} {{{var}}} in {ATTRACTORS_DOMAIN}:")
+            }
             HctlToken::Atom(Atomic::Prop(name)) => write!(f, "{name}"),
             HctlToken::Atom(Atomic::Var(name)) => write!(f, "{{{name}}}"),
             HctlToken::Atom(Atomic::WildCardProp(name)) => write!(f, "%{name}%"),
+            HctlToken::Atom(relation @ Atomic::WildCardRelation(..)) => write!(f, "{relation}"),
+            HctlToken::Atom(state @ Atomic::State(..)) => write!(f, "{state}"),
             HctlToken::Atom(constant) => write!(f, "{constant:?}"),
             HctlToken::Tokens(_) => write!(f, "( TOKENS )"), // debug purposes only
+            HctlToken::Invalid => write!(f, "INVALID"),
         }
     }
 }
@@ -387,7 +822,8 @@ pub fn print_tokens(tokens: &Vec<HctlToken>) {
 mod tests {
     use crate::preprocessing::operator_enums::*;
     use crate::preprocessing::tokenizer::{
-        try_tokenize_extended_formula, try_tokenize_formula, HctlToken,
+        check_strict_syntax, try_tokenize_extended_formula, try_tokenize_formula,
+        try_tokenize_with_recovery, HctlToken, SyntaxError,
     };
 
     #[test]
@@ -482,6 +918,64 @@ mod tests {
             ]),
         ];
         assert_eq!(tokens, expected_tokens);
+
+        // qualified propositions refer to variables of a product network
+        let tokens = try_tokenize_formula("m1.a & m2.a".to_string()).unwrap();
+        let expected_tokens = vec![
+            HctlToken::Atom(Atomic::Prop("m1__a".to_string())),
+            HctlToken::Binary(BinaryOp::And),
+            HctlToken::Atom(Atomic::Prop("m2__a".to_string())),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        // quantifiers ranging over attractors
+        let tokens =
+            try_tokenize_formula("V{x} in attractors: \\exists{y} in attractors: a".to_string())
+                .unwrap();
+        let expected_tokens = vec![
+            HctlToken::AttractorQuantifier(HybridOp::Forall, "x".to_string()),
+            HctlToken::AttractorQuantifier(HybridOp::Exists, "y".to_string()),
+            HctlToken::Atom(Atomic::Prop("a".to_string())),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        // fair attractors are a built-in domain (allowed for all hybrid operators)
+        let tokens =
+            try_tokenize_formula("!{x} in fair_attractors: @{x} in fair_attractors: a".to_string())
+                .unwrap();
+        let fair_domain = Some("fair_attractors".to_string());
+        let expected_tokens = vec![
+            HctlToken::Hybrid(HybridOp::Bind, "x".to_string(), fair_domain.clone()),
+            HctlToken::Hybrid(HybridOp::Jump, "x".to_string(), fair_domain),
+            HctlToken::Atom(Atomic::Prop("a".to_string())),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        // state literals (partial and full)
+        let tokens =
+            try_tokenize_formula("#state{a=1, b = 0, *} & #state { a=0,b=1 }".to_string()).unwrap();
+        let expected_tokens = vec![
+            HctlToken::Atom(Atomic::State(
+                vec![("a".to_string(), true), ("b".to_string(), false)],
+                true,
+            )),
+            HctlToken::Binary(BinaryOp::And),
+            HctlToken::Atom(Atomic::State(
+                vec![("a".to_string(), false), ("b".to_string(), true)],
+                false,
+            )),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        // past operator, and a proposition starting with it
+        let tokens = try_tokenize_formula("EP naive & EPx".to_string()).unwrap();
+        let expected_tokens = vec![
+            HctlToken::Unary(UnaryOp::EP),
+            HctlToken::Atom(Atomic::Prop("naive".to_string())),
+            HctlToken::Binary(BinaryOp::And),
+            HctlToken::Atom(Atomic::Prop("EPx".to_string())),
+        ];
+        assert_eq!(tokens, expected_tokens);
     }
 
     #[test]
@@ -518,7 +1012,20 @@ mod tests {
             "( p1",
             "p1 <> p2",
             "p1 >= p2",
+            "!{x} in attractors: AG {x}",
+            "@{x} in attractors: {x}",
+            "3{x} in attr: {x}",
+            "#stat{a=1}",
+            "#state{a=2}",
+            "#state{*, a=1}",
+            "#state{a=1",
+            "#state{=1}",
+            "3{x} in %attr%: {x}",
             "p1 <= p2",
+            "m1. & p2",
+            "m1.a__b",
+            "m3.a",
+            "0.5",
         ];
 
         for formula in invalid_formulae {
@@ -573,6 +1080,33 @@ mod tests {
         assert_eq!(tokens, expected_tokens);
         assert_eq!(tokens_v2, expected_tokens);
 
+        // wild-card relations between HCTL variables
+        let formula = "3{x}: %r( {x} , {y} )%";
+        assert!(try_tokenize_formula(formula.to_string()).is_err());
+        let tokens = try_tokenize_extended_formula(formula.to_string()).unwrap();
+        let expected_tokens = vec![
+            HctlToken::Hybrid(HybridOp::Exists, "x".to_string(), None),
+            HctlToken::Atom(Atomic::WildCardRelation(
+                "r".to_string(),
+                vec!["x".to_string(), "y".to_string()],
+            )),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        // jumps can be restricted to a domain too
+        let formula = "3{x}: @{x} in %dom_x%: {x}";
+        let formula_v2 = "\\exists {x}: \\jump {x} in %dom_x%: {x}";
+        assert!(try_tokenize_formula(formula.to_string()).is_err());
+        let tokens = try_tokenize_extended_formula(formula.to_string()).unwrap();
+        let tokens_v2 = try_tokenize_extended_formula(formula_v2.to_string()).unwrap();
+        let expected_tokens = vec![
+            HctlToken::Hybrid(HybridOp::Exists, "x".to_string(), None),
+            HctlToken::Hybrid(HybridOp::Jump, "x".to_string(), Some("dom_x".to_string())),
+            HctlToken::Atom(Atomic::Var("x".to_string())),
+        ];
+        assert_eq!(tokens, expected_tokens);
+        assert_eq!(tokens_v2, expected_tokens);
+
         let formula = "!{x} in %dom_x%: %wild_card%";
         let formula_v2 = "\\bind {x} in %dom_x%: %wild_card%";
         assert!(try_tokenize_formula(formula.to_string()).is_err());
@@ -587,6 +1121,85 @@ mod tests {
         assert_eq!(tokens_v2, expected_tokens);
     }
 
+    #[test]
+    /// Test tokenization process on formulae where hybrid operators have several variables, which
+    /// is a syntactic sugar for nested operators.
+    fn tokenize_multiple_vars() {
+        let formula = "!{x, y}: 3{z,w} in attractors: @{x}: {y}";
+        let nested_formula = "!{x}: !{y}: 3{z} in attractors: 3{w} in attractors: @{x}: {y}";
+        let tokens = try_tokenize_formula(formula.to_string()).unwrap();
+        assert_eq!(
+            tokens,
+            try_tokenize_formula(nested_formula.to_string()).unwrap()
+        );
+        let formula_v2 = "\\bind {x, y}: \\exists {z, w} in attractors: \\jump {x}: {y}";
+        assert_eq!(
+            tokens,
+            try_tokenize_formula(formula_v2.to_string()).unwrap()
+        );
+
+        let invalid_formulae = vec![
+            "!{x,}: {x}",
+            "!{x y}: {x}",
+            "!{x, x}: {x}",
+            "!{x, y}: @{x, y}: {x}",
+        ];
+        for formula in invalid_formulae {
+            assert!(try_tokenize_formula(formula.to_string()).is_err())
+        }
+    }
+
+    #[test]
+    /// Test tokenization process on formulae with transition-labelled next operators.
+    fn tokenize_transition_labelled_ops() {
+        let formula = "EX_{a} (AX_{b_1} EX_1)";
+        let tokens = try_tokenize_formula(formula.to_string()).unwrap();
+        let expected_tokens = vec![
+            HctlToken::Unary(UnaryOp::EXVar("a".to_string())),
+            HctlToken::Tokens(vec![
+                HctlToken::Unary(UnaryOp::AXVar("b_1".to_string())),
+                HctlToken::Atom(Atomic::Prop("EX_1".to_string())),
+            ]),
+        ];
+        assert_eq!(tokens, expected_tokens);
+
+        let invalid_formulae = vec!["EX_{} a", "EX_{a a", "AX_{a b} a"];
+        for formula in invalid_formulae {
+            assert!(try_tokenize_formula(formula.to_string()).is_err())
+        }
+    }
+
+    #[test]
+    /// Test tokenization process on formulae with inline variable domains, which are allowed
+    /// also in standard HCTL formulae.
+    fn tokenize_inline_domains() {
+        let formula = "3{x} in [a & !b]: {x}";
+        let tokens = try_tokenize_formula(formula.to_string()).unwrap();
+        let expected_tokens = vec![
+            HctlToken::Hybrid(
+                HybridOp::Exists,
+                "x".to_string(),
+                Some("[a & !b]".to_string()),
+            ),
+            HctlToken::Atom(Atomic::Var("x".to_string())),
+        ];
+        assert_eq!(tokens, expected_tokens);
+        assert_eq!(
+            try_tokenize_extended_formula(formula.to_string()).unwrap(),
+            tokens
+        );
+
+        let invalid_formulae = vec![
+            "3{x} in [a & b: {x}",
+            "3{x} in []: {x}",
+            "3{x} in [  ]: {x}",
+            "3{x} in a & b]: {x}",
+        ];
+        for formula in invalid_formulae {
+            assert!(try_tokenize_formula(formula.to_string()).is_err())
+        }
+    }
+
     #[test]
     /// Test tokenization process on an extended HCTL formula with several whitespaces.
     fn tokenize_extended_with_whitespaces() {
@@ -602,24 +1215,101 @@ mod tests {
             "!{x} in: AG EF {x}",
             "\\bind {x} in: AG EF {x}",
             "!{x} i %d%: AG EF {x}",
+            "3{x} in %fair_attractors%: {x}",
             "\\bind {x} i %d%: AG EF {x}",
             "!{x} in %%: AG EF {x}",
             "\\bind {x} in %%: AG EF {x}",
             "!{x} %d%: AG EF {x}",
             "!{x} in abc: AG EF {x}",
             "!{} in %d%: AG EF {x}",
-            "3{x}: @{x} in %s%: AG EF {x}",
-            "\\exists {x}: \\jump {x} in %s%: AG EF {x}",
             "%%",
             "%ddd %",
             "%ddd*%",
             "A & d %",
             "A & %d",
             "A & d%",
+            "%r(%",
+            "%r()%",
+            "%r({x}%",
+            "%r({x} {y})%",
+            "%r(x)%",
+            "%r({x})",
         ];
 
         for formula in invalid_formulae {
             assert!(try_tokenize_extended_formula(formula.to_string()).is_err())
         }
     }
+
+    #[test]
+    /// Test that tokenizing with recovery reports all errors and skips the invalid segments.
+    fn tokenize_with_recovery() {
+        let (tokens, errors) = try_tokenize_with_recovery("a & $b", false);
+        assert_eq!(
+            tokens,
+            vec![
+                HctlToken::Atom(Atomic::Prop("a".to_string())),
+                HctlToken::Binary(BinaryOp::And),
+                HctlToken::Invalid,
+            ]
+        );
+        assert_eq!(
+            errors,
+            vec![SyntaxError {
+                position: Some(5),
+                message: "Unexpected char '$'.".to_string(),
+            }]
+        );
+
+        // errors inside and outside of parentheses, and a missing closing parenthesis
+        let (tokens, errors) = try_tokenize_with_recovery("(a = b) & c > (d", false);
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[2].position, Some(16));
+        assert_eq!(tokens.len(), 5);
+
+        // valid formulae are tokenized without errors
+        let (tokens, errors) = try_tokenize_with_recovery("!{x}: AG %p%", true);
+        assert!(errors.is_empty());
+        assert_eq!(
+            tokens,
+            try_tokenize_extended_formula("!{x}: AG %p%".to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    /// Test tokenization of the textual aliases of the operators, and their rejection in the
+    /// strict mode.
+    fn tokenize_operator_aliases() {
+        let formula =
+            "EXISTS {x}: BIND {y}: JUMP {x}: NOT {y} AND AX {x} OR b IMPLIES FORALL {z}: {z}";
+        let expected = "3{x}: !{y}: @{x}: ~ {y} & AX {x} | b => V{z}: {z}";
+        assert_eq!(
+            try_tokenize_formula(formula.to_string()).unwrap(),
+            try_tokenize_formula(expected.to_string()).unwrap()
+        );
+        assert!(check_strict_syntax(expected).is_ok());
+        assert!(check_strict_syntax(formula).is_err());
+        assert!(check_strict_syntax("a AND b").is_err());
+
+        // aliases must be whole words, other names are propositions
+        let tokens = try_tokenize_formula("ANDROID & EXISTS_1 & and".to_string()).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                HctlToken::Atom(Atomic::Prop("ANDROID".to_string())),
+                HctlToken::Binary(BinaryOp::And),
+                HctlToken::Atom(Atomic::Prop("EXISTS_1".to_string())),
+                HctlToken::Binary(BinaryOp::And),
+                HctlToken::Atom(Atomic::Prop("and".to_string())),
+            ]
+        );
+        assert!(check_strict_syntax("ANDROID & EXISTS_1 & and").is_ok());
+
+        // domains work the same way as with the symbols
+        let tokens = try_tokenize_extended_formula("FORALL {x} in %d%: {x}".to_string());
+        assert_eq!(
+            tokens.unwrap(),
+            try_tokenize_extended_formula("V{x} in %d%: {x}".to_string()).unwrap()
+        );
+    }
 }
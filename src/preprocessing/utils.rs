@@ -2,11 +2,11 @@
 //! syntactic trees.
 
 use crate::evaluation::LabelToSetMap;
-use crate::mc_utils::collect_unique_wild_cards;
+use crate::mc_utils::{collect_unique_wild_cards, collect_wild_card_arities};
 use crate::preprocessing::hctl_tree::*;
-use crate::preprocessing::operator_enums::{Atomic, HybridOp};
+use crate::preprocessing::operator_enums::{Atomic, HybridOp, UnaryOp};
 use biodivine_lib_param_bn::symbolic_async_graph::SymbolicContext;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Checks that all HCTL variables in the formula's syntactic tree are quantified (exactly once) and that
 /// its propositions are valid BN variables.
@@ -32,7 +32,7 @@ fn validate_and_rename_recursive(
     // If we find hybrid node with binder or exist, we add new var-name to rename_dict and stack (x, xx, xxx...)
     // After we leave this binder/exist, we remove its var from rename_dict
     // When we find terminal with free var or jump node, we rename the var using rename-dict
-    return match orig_tree.node_type {
+    match orig_tree.node_type {
         // rename vars in terminal state-var nodes
         NodeType::Terminal(ref atom) => match atom {
             Atomic::Var(name) => {
@@ -43,6 +43,17 @@ fn validate_and_rename_recursive(
                 let renamed_var = renaming_map.get(name.as_str()).unwrap();
                 Ok(HctlTreeNode::mk_variable(renamed_var))
             }
+            Atomic::WildCardRelation(name, vars) => {
+                // all variables of the relation must be quantified as well
+                let mut renamed_vars = Vec::new();
+                for var in vars {
+                    let renamed_var = renaming_map
+                        .get(var.as_str())
+                        .ok_or(format!("Variable {var} is free in `{atom}`."))?;
+                    renamed_vars.push(renamed_var.clone());
+                }
+                Ok(HctlTreeNode::mk_wild_card_relation(name, renamed_vars))
+            }
             Atomic::Prop(name) => {
                 // check that proposition corresponds to valid BN variable
                 if ctx.find_network_variable(name).is_none() {
@@ -51,11 +62,36 @@ fn validate_and_rename_recursive(
                     Ok(orig_tree)
                 }
             }
+            Atomic::State(values, partial) => {
+                // check that the variables are valid and distinct BN variables
+                let mut seen = HashSet::new();
+                for (name, _) in values {
+                    if ctx.find_network_variable(name).is_none() {
+                        return Err(format!("There is no network variable named {name}."));
+                    }
+                    if !seen.insert(name) {
+                        return Err(format!("Variable {name} is repeated in the state {atom}."));
+                    }
+                }
+                // a state that is not partial must specify all variables
+                if !partial && values.len() != ctx.num_state_variables() {
+                    return Err(format!(
+                        "State {atom} must specify all variables (or end with `*`)."
+                    ));
+                }
+                Ok(orig_tree)
+            }
             // constants or wild-card propositions are always considered fine
-            _ => return Ok(orig_tree),
+            _ => Ok(orig_tree),
         },
         // just dive one level deeper for unary nodes, and rename string
         NodeType::Unary(op, child) => {
+            // transition-labelled operators must refer to valid BN variables
+            if let UnaryOp::EXVar(name) | UnaryOp::AXVar(name) = &op {
+                if ctx.find_network_variable(name).is_none() {
+                    return Err(format!("There is no network variable named {name}."));
+                }
+            }
             let node =
                 validate_and_rename_recursive(*child, renaming_map, last_used_name.clone(), ctx)?;
             Ok(HctlTreeNode::mk_unary(node, op))
@@ -89,6 +125,14 @@ fn validate_and_rename_recursive(
                 _ => {}
             }
 
+            // propositions of an inline domain must be valid as well
+            let domain = match domain {
+                Some(VarDomain::Inline(tree)) => Some(VarDomain::Inline(Box::new(
+                    validate_props_and_rename_vars(*tree, ctx)?,
+                ))),
+                domain => domain,
+            };
+
             // dive deeper
             let node = validate_and_rename_recursive(
                 *child,
@@ -104,22 +148,25 @@ fn validate_and_rename_recursive(
 
             // rename the variable in the node
             let renamed_var = renaming_map.get(var.as_str()).unwrap();
-            Ok(HctlTreeNode::mk_hybrid(
+            Ok(HctlTreeNode::mk_hybrid_with_domain(
                 node,
                 renamed_var.as_str(),
                 domain,
                 op,
             ))
         }
-    };
+    }
 }
 
 /// Check that all wild-card propositions and variable domains in the formula's syntactic tree have
-/// their corresponding "raw set" (context) in `context_sets`.
+/// their corresponding "raw set" (context) in `context_sets`. Wild-card relations are given
+/// by the context of wild-card propositions, and they must be used with the same number of
+/// variables everywhere.
 pub fn validate_wild_cards(
     tree: &HctlTreeNode,
     context_sets: &LabelToSetMap,
 ) -> Result<(), String> {
+    collect_wild_card_arities(tree)?;
     let (wild_card_props, var_domains) = collect_unique_wild_cards(tree.clone());
     // check that all occurring wild-card props are present in `context_props`
     for wild_card in wild_card_props {
@@ -145,11 +192,13 @@ pub fn validate_wild_cards(
 /// Check that all wild-card propositions and variable domains in the formula's syntactic tree have
 /// their corresponding "raw set" (context) in `substitution_context`.
 ///
-/// Returns two individual context subsets, one for wild-card propositions, and the other for variable domains.
+/// Returns two individual context subsets, one for wild-card propositions (and relations), and the
+/// other for variable domains.
 pub fn validate_and_divide_wild_cards(
     tree: &HctlTreeNode,
     context_sets: &LabelToSetMap,
 ) -> Result<(LabelToSetMap, LabelToSetMap), String> {
+    collect_wild_card_arities(tree)?;
     let mut context_domains = HashMap::new();
     let mut context_props = HashMap::new();
 
@@ -332,5 +381,17 @@ mod tests {
         let tree = parse_hctl_formula(formula).unwrap();
 
         assert!(validate_props_and_rename_vars(tree, &symbolic_context).is_err());
+
+        // states with invalid, repeated, or missing variables
+        for formula in ["#state{x=1}", "#state{v1=1, v1=1}", "#state{v1=1, v2=1, *}"] {
+            let tree = parse_hctl_formula(formula).unwrap();
+            assert!(validate_props_and_rename_vars(tree, &symbolic_context).is_err());
+        }
+        let bn = BooleanNetwork::try_from_bnet("v1, v2\nv2, v1").unwrap();
+        let symbolic_context = SymbolicContext::new(&bn).unwrap();
+        let tree = parse_hctl_formula("#state{v1=1}").unwrap();
+        assert!(validate_props_and_rename_vars(tree, &symbolic_context).is_err());
+        let tree = parse_hctl_formula("#state{v2=0, v1=1}").unwrap();
+        assert!(validate_props_and_rename_vars(tree, &symbolic_context).is_ok());
     }
 }
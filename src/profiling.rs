@@ -0,0 +1,212 @@
+//! Lightweight internal profiler, measuring the time spent in the main phases of the model
+//! checking, such as the graph construction, the saturation of `EU`, the projection of quantified
+//! variables, or the renaming of cached results.
+//!
+//! The phases are marked by [span] guards, which only measure the time with the `profiling`
+//! feature. Without it, the spans do nothing (and the profile stays empty). The spans of all
+//! threads are aggregated into one global profile, which can be taken by [take_profile], and
+//! printed as a summary or exported in the "folded stacks" format of flamegraph tools.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+#[cfg(feature = "profiling")]
+use std::cell::RefCell;
+#[cfg(feature = "profiling")]
+use std::sync::Mutex;
+#[cfg(feature = "profiling")]
+use std::time::Instant;
+
+/// Aggregated measurements of the spans with the same stack.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SpanStats {
+    /// Number of the finished spans.
+    pub count: usize,
+    /// Total time spent in the spans (including the nested spans).
+    pub total_time: Duration,
+    /// Time spent in the spans themselves (excluding the nested spans).
+    pub self_time: Duration,
+}
+
+/// Profile of the computation, collected by the [span] guards.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    /// Measurements of the spans by their stacks, i.e., the names of the nested spans (from
+    /// the outermost one) joined by `;`.
+    pub spans: BTreeMap<String, SpanStats>,
+}
+
+impl Profile {
+    /// Check whether no spans were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Export the profile in the "folded stacks" format (`outer;inner <value>` on each line),
+    /// which can be rendered by flamegraph tools (e.g., `flamegraph.pl` or `inferno`). The values
+    /// are the self times of the stacks in microseconds.
+    pub fn to_folded(&self) -> String {
+        self.spans
+            .iter()
+            .map(|(stack, stats)| format!("{stack} {}\n", stats.self_time.as_micros()))
+            .collect()
+    }
+
+    /// Summarize the self times and numbers of calls of the individual spans (regardless of their
+    /// stacks), sorted from the most expensive span.
+    pub fn summary(&self) -> String {
+        let mut totals: BTreeMap<&str, (Duration, usize)> = BTreeMap::new();
+        for (stack, stats) in &self.spans {
+            let name = stack.rsplit(';').next().unwrap();
+            let entry = totals.entry(name).or_default();
+            entry.0 += stats.self_time;
+            entry.1 += stats.count;
+        }
+        let mut totals = totals.into_iter().collect::<Vec<_>>();
+        totals.sort_by(|(_, (time_1, _)), (_, (time_2, _))| time_2.cmp(time_1));
+
+        let mut summary = format!("{:<24}{:>14}{:>10}\n", "span", "self time", "calls");
+        for (name, (time, count)) in totals {
+            let time = format!("{:.3}ms", time.as_secs_f64() * 1000.0);
+            summary.push_str(format!("{name:<24}{time:>14}{count:>10}\n").as_str());
+        }
+        summary
+    }
+}
+
+/// Global profile, aggregating the spans of all threads.
+#[cfg(feature = "profiling")]
+static PROFILE: Mutex<Profile> = Mutex::new(Profile {
+    spans: BTreeMap::new(),
+});
+
+#[cfg(feature = "profiling")]
+thread_local! {
+    /// Open spans of the current thread (from the outermost one), each with the time spent in
+    /// its finished nested spans.
+    static OPEN_SPANS: RefCell<Vec<(&'static str, Duration)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Guard of a profiling span, see [span]. The span ends when the guard is dropped.
+#[must_use = "the span ends when the guard is dropped"]
+pub struct SpanGuard {
+    #[cfg(feature = "profiling")]
+    start: Instant,
+}
+
+/// Start a span with the given `name`, nested in the currently open span of this thread (if
+/// any). The span ends when the returned guard is dropped. Without the `profiling` feature, this
+/// does nothing.
+pub fn span(name: &'static str) -> SpanGuard {
+    #[cfg(feature = "profiling")]
+    {
+        OPEN_SPANS.with(|spans| spans.borrow_mut().push((name, Duration::ZERO)));
+        SpanGuard {
+            start: Instant::now(),
+        }
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        let _ = name;
+        SpanGuard {}
+    }
+}
+
+#[cfg(feature = "profiling")]
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        OPEN_SPANS.with(|spans| {
+            let mut spans = spans.borrow_mut();
+            let stack = spans
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(";");
+            let (_, nested_time) = spans.pop().unwrap();
+            if let Some((_, parent_nested_time)) = spans.last_mut() {
+                *parent_nested_time += elapsed;
+            }
+
+            let mut profile = PROFILE.lock().unwrap();
+            let stats = profile.spans.entry(stack).or_default();
+            stats.count += 1;
+            stats.total_time += elapsed;
+            stats.self_time += elapsed.saturating_sub(nested_time);
+        });
+    }
+}
+
+/// Take the profile collected so far (by all threads), and start a new one. Without the
+/// `profiling` feature, the profile is always empty.
+pub fn take_profile() -> Profile {
+    #[cfg(feature = "profiling")]
+    {
+        std::mem::take(&mut *PROFILE.lock().unwrap())
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        Profile::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::profiling::{Profile, SpanStats};
+    use std::time::Duration;
+
+    #[test]
+    /// Test the exports of the profile.
+    fn test_profile_exports() {
+        let mut profile = Profile::default();
+        let stats = |count, total_ms, self_ms| SpanStats {
+            count,
+            total_time: Duration::from_millis(total_ms),
+            self_time: Duration::from_millis(self_ms),
+        };
+        profile
+            .spans
+            .insert("eg_fixpoint".to_string(), stats(1, 10, 4));
+        profile
+            .spans
+            .insert("eg_fixpoint;predecessors".to_string(), stats(5, 6, 6));
+        profile
+            .spans
+            .insert("predecessors".to_string(), stats(2, 1, 1));
+
+        let folded = profile.to_folded();
+        assert_eq!(
+            folded,
+            "eg_fixpoint 4000\neg_fixpoint;predecessors 6000\npredecessors 1000\n"
+        );
+        // spans of the same name are merged, the most expensive one is the first
+        let summary = profile.summary();
+        let lines = summary.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("predecessors") && lines[1].ends_with("7.000ms         7"));
+        assert!(lines[2].starts_with("eg_fixpoint"));
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    /// Test that the spans of the evaluation are recorded.
+    fn test_profiling_spans() {
+        use crate::mc_utils::get_extended_symbolic_graph;
+        use crate::model_checking::model_check_formula;
+        use crate::profiling::take_profile;
+        use biodivine_lib_param_bn::BooleanNetwork;
+
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        model_check_formula("EF (a & b) & (3{x}: @{x}: AX {x})", &stg).unwrap();
+        model_check_formula("EG a | (a AU b)", &stg).unwrap();
+        // the profile is global, so it is only taken once (other tests may run in parallel)
+        let profile = take_profile();
+        assert!(profile.spans.contains_key("graph_construction"));
+        assert!(profile.spans.contains_key("eu_saturation"));
+        let summary = profile.summary();
+        assert!(summary.contains("quantifier_projection"));
+        assert!(summary.contains("eg_fixpoint"));
+        assert!(summary.contains("au_fixpoint"));
+    }
+}
@@ -0,0 +1,790 @@
+//! Reductions of Boolean networks that make the model checking cheaper, together with mappings
+//! that lift the results of the reduced network back to the original one.
+//!
+//! Percolation of constants: variables with constant update functions (such as inputs fixed by
+//! [crate::mc_utils::apply_input_mode]) are substituted into the update functions of their
+//! targets, which may make further variables constant, and so on. The percolated variables are
+//! then removed from the network, together with the parameters that are no longer used.
+//!
+//! The states where all percolated variables have their fixed values form a trap space of the
+//! original network, and the reduced network describes exactly the dynamics inside this trap
+//! space (which contains all attractors). The results of the reduced network are thus lifted
+//! into this trap space. Since the states outside the trap space are not represented, formulae
+//! quantifying over all states (`3{x}`, `V{x}`) or reasoning about the past (`EP`) are not
+//! supported.
+//!
+//! Elimination of variables: a mediator variable (without a self-regulation) is removed, and
+//! its update function is substituted into the update functions of its targets. This preserves
+//! the fixed points exactly (they are in a one-to-one correspondence given by the projection),
+//! but in the asynchronous semantics, it does not preserve the reachability or the complex
+//! attractors in general. Therefore, the elimination is only applied automatically to formulae
+//! describing fixed points (see [reduce_for_formulae]).
+//!
+//! Restriction to a module: the variables that (transitively) regulate a strongly connected
+//! component of the regulatory graph evolve independently of the rest of the network (see
+//! [crate::mc_utils::RegulatoryModule]). Every path of the module can be extended to a path of
+//! the whole network, and every path of the network projects to a path of the module. Therefore,
+//! reachability properties (`EF`, `EU`, `AG`) referencing only the variables of the module can be
+//! evaluated on the module alone (see [restrict_to_formulae_module]).
+
+use crate::mc_utils::{
+    collect_unique_hctl_vars, find_module_for_propositions, get_extended_symbolic_graph,
+    RegulatoryModule,
+};
+use crate::model_checking::model_check_multiple_trees;
+use crate::postprocessing::sanitizing::{extend_colored_vertices, sanitize_colored_vertices};
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::operator_enums::{Atomic, BinaryOp, HybridOp, UnaryOp};
+use crate::preprocessing::parser::{parse_and_minimize_hctl_formula, parse_extended_formula};
+
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, SymbolicAsyncGraph, SymbolicContext,
+};
+use biodivine_lib_param_bn::{BooleanNetwork, FnUpdate, ParameterId, RegulatoryGraph, VariableId};
+use std::collections::{HashMap, HashSet};
+
+/// Network reduced by the percolation of constants (see [percolate_constants]).
+#[derive(Clone, Debug)]
+pub struct PercolatedNetwork {
+    /// The reduced network, without the percolated variables.
+    pub network: BooleanNetwork,
+    /// Fixed values of the percolated variables (that were removed from the network).
+    pub fixed_values: HashMap<String, bool>,
+}
+
+/// Build a network without the `removed` variables, where the remaining variables have the
+/// given update `functions` (indexed by the variables of the original `bn`).
+///
+/// The regulations of the modified explicit update functions are no longer required to be
+/// observable, they keep their monotonicity only if `keep_monotonicity` is set, and their new
+/// arguments get new (unspecified) regulations. Parameters that are no longer used are removed.
+fn remove_variables(
+    bn: &BooleanNetwork,
+    removed: &HashSet<VariableId>,
+    functions: &[Option<FnUpdate>],
+    keep_monotonicity: bool,
+) -> Result<BooleanNetwork, String> {
+    // the remaining variables keep their relative order
+    let remaining: Vec<VariableId> = bn.variables().filter(|v| !removed.contains(v)).collect();
+    let variable_map: HashMap<VariableId, VariableId> = remaining
+        .iter()
+        .enumerate()
+        .map(|(i, var)| (*var, VariableId::from_index(i)))
+        .collect();
+    let is_modified = |var: VariableId| {
+        functions[var.to_index()].is_some()
+            && &functions[var.to_index()] != bn.get_update_function(var)
+    };
+
+    let mut graph = RegulatoryGraph::new(
+        remaining
+            .iter()
+            .map(|var| bn.get_variable_name(*var).clone())
+            .collect(),
+    );
+    for regulation in bn.as_graph().regulations() {
+        let (regulator, target) = (regulation.get_regulator(), regulation.get_target());
+        if removed.contains(&regulator) || removed.contains(&target) {
+            continue;
+        }
+        // modified functions may no longer depend on their other regulators
+        let relaxed = is_modified(target);
+        graph.add_regulation(
+            bn.get_variable_name(regulator),
+            bn.get_variable_name(target),
+            regulation.is_observable() && !relaxed,
+            if relaxed && !keep_monotonicity {
+                None
+            } else {
+                regulation.get_monotonicity()
+            },
+        )?;
+    }
+    for target in remaining.iter().filter(|var| is_modified(**var)) {
+        let function = functions[target.to_index()].as_ref().unwrap();
+        for regulator in function.collect_arguments() {
+            if bn.as_graph().find_regulation(regulator, *target).is_none() {
+                graph.add_regulation(
+                    bn.get_variable_name(regulator),
+                    bn.get_variable_name(*target),
+                    false,
+                    None,
+                )?;
+            }
+        }
+    }
+
+    // only the parameters that are still used are kept
+    let mut network = BooleanNetwork::new(graph);
+    let mut parameter_map: HashMap<ParameterId, ParameterId> = HashMap::new();
+    for parameter in bn.parameters() {
+        let is_used = remaining.iter().any(|var| {
+            functions[var.to_index()]
+                .as_ref()
+                .is_some_and(|f| f.contains_parameter(parameter))
+        });
+        if is_used {
+            let new_id =
+                network.add_parameter(bn[parameter].get_name(), bn[parameter].get_arity())?;
+            parameter_map.insert(parameter, new_id);
+        }
+    }
+    for var in &remaining {
+        let function = functions[var.to_index()]
+            .as_ref()
+            .map(|f| f.rename_all(&variable_map, &parameter_map));
+        network.set_update_function(variable_map[var], function)?;
+    }
+    Ok(network)
+}
+
+/// Percolate the constant update functions through the network, and remove the variables that
+/// become constant. See the module documentation for the relation of the reduced network to the
+/// original one.
+///
+/// The constants are only substituted into explicit update functions (and simplified
+/// syntactically). Variables regulating a variable with an unspecified (implicit) update function
+/// are never removed, since that would change its parameters.
+pub fn percolate_constants(bn: &BooleanNetwork) -> Result<PercolatedNetwork, String> {
+    let mut functions: Vec<Option<FnUpdate>> = bn
+        .variables()
+        .map(|var| bn.get_update_function(var).clone())
+        .collect();
+    let mut fixed: HashMap<VariableId, bool> = HashMap::new();
+
+    // percolate the constants until there are no new ones
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for var in bn.variables() {
+            if fixed.contains_key(&var) {
+                continue;
+            }
+            let value = match functions[var.to_index()]
+                .as_ref()
+                .and_then(|f| f.as_const())
+            {
+                Some(value) => value,
+                None => continue,
+            };
+            let targets = bn.targets(var);
+            if targets
+                .iter()
+                .any(|target| functions[target.to_index()].is_none())
+            {
+                continue;
+            }
+            fixed.insert(var, value);
+            for target in targets {
+                let function = functions[target.to_index()].as_ref().unwrap();
+                functions[target.to_index()] = Some(
+                    function
+                        .substitute_variable(var, &FnUpdate::Const(value))
+                        .simplify_constants(),
+                );
+            }
+            changed = true;
+        }
+    }
+
+    let removed = fixed.keys().copied().collect();
+    let network = remove_variables(bn, &removed, &functions, true)?;
+    let fixed_values = fixed
+        .into_iter()
+        .map(|(var, value)| (bn.get_variable_name(var).clone(), value))
+        .collect();
+    Ok(PercolatedNetwork {
+        network,
+        fixed_values,
+    })
+}
+
+impl PercolatedNetwork {
+    /// Replace the propositions of the percolated variables in the formula tree by their fixed
+    /// values. Return an error if the formula quantifies over all states or contains past
+    /// operators (see the module docs).
+    pub fn percolate_formula(&self, tree: HctlTreeNode) -> Result<HctlTreeNode, String> {
+        match tree.node_type {
+            NodeType::Terminal(Atomic::Prop(ref name)) => match self.fixed_values.get(name) {
+                Some(value) => Ok(HctlTreeNode::mk_constant(*value)),
+                None => Ok(tree),
+            },
+            // literals of the percolated variables are either satisfied, or the state is empty
+            NodeType::Terminal(Atomic::State(ref values, partial)) => {
+                let mut remaining = Vec::new();
+                for (name, value) in values {
+                    match self.fixed_values.get(name) {
+                        Some(fixed) if fixed != value => {
+                            return Ok(HctlTreeNode::mk_constant(false))
+                        }
+                        Some(_) => {}
+                        None => remaining.push((name.clone(), *value)),
+                    }
+                }
+                Ok(HctlTreeNode::mk_state(remaining, partial))
+            }
+            NodeType::Terminal(_) => Ok(tree),
+            // percolated variables are constant, so they can never be updated
+            NodeType::Unary(UnaryOp::EXVar(ref name), _)
+                if self.fixed_values.contains_key(name) =>
+            {
+                Ok(HctlTreeNode::mk_constant(false))
+            }
+            NodeType::Unary(UnaryOp::AXVar(ref name), _)
+                if self.fixed_values.contains_key(name) =>
+            {
+                Ok(HctlTreeNode::mk_constant(true))
+            }
+            // predecessors may lie outside the percolated trap space
+            NodeType::Unary(UnaryOp::EP, _) => {
+                Err("Past operators are not supported on percolated networks.".to_string())
+            }
+            NodeType::Unary(op, child) => {
+                Ok(HctlTreeNode::mk_unary(self.percolate_formula(*child)?, op))
+            }
+            NodeType::Binary(op, left, right) => Ok(HctlTreeNode::mk_binary(
+                self.percolate_formula(*left)?,
+                self.percolate_formula(*right)?,
+                op,
+            )),
+            NodeType::Hybrid(HybridOp::Exists | HybridOp::Forall, ..) => Err(
+                "Quantifiers over all states are not supported on percolated networks.".to_string(),
+            ),
+            NodeType::Hybrid(op, var, domain, child) => Ok(HctlTreeNode::mk_hybrid_with_domain(
+                self.percolate_formula(*child)?,
+                var.as_str(),
+                domain,
+                op,
+            )),
+        }
+    }
+
+    /// Lift the `set` of colored states of the reduced network (sanitized, or valid in the
+    /// `reduced_graph`) into the `graph` of the original network. The percolated variables get their fixed
+    /// values, and the removed parameters are not restricted (beyond the valid colors of `graph`).
+    ///
+    /// Return `None` if the `graph` is not based on the original network.
+    pub fn lift_colored_vertices(
+        &self,
+        reduced_graph: &SymbolicAsyncGraph,
+        set: &GraphColoredVertices,
+        graph: &SymbolicAsyncGraph,
+    ) -> Option<GraphColoredVertices> {
+        let original = graph.as_network()?;
+        let reduced_context = SymbolicContext::new(&self.network).ok()?;
+        let original_context = SymbolicContext::new(original).ok()?;
+        // the symbolic variables of both networks have the same names
+        let sanitized =
+            sanitize_colored_vertices(reduced_graph, &extend_colored_vertices(reduced_graph, set)?);
+        let bdd = original_context.transfer_from(sanitized.as_bdd(), &reduced_context)?;
+        let lifted = GraphColoredVertices::new(bdd, &original_context);
+
+        let context = graph.symbolic_context();
+        let mut result = extend_colored_vertices(graph, &lifted)?.into_bdd();
+        for (name, value) in &self.fixed_values {
+            let var = original.as_graph().find_variable(name)?;
+            let literal = context.mk_state_variable_is_true(var);
+            result = if *value {
+                result.and(&literal)
+            } else {
+                result.and_not(&literal)
+            };
+        }
+        let unit = graph.mk_unit_colored_vertices();
+        Some(GraphColoredVertices::new(
+            result.and(unit.as_bdd()),
+            context,
+        ))
+    }
+}
+
+/// Perform the model checking for the list of HCTL formulae on the network of the given `graph`,
+/// reduced by the percolation of constants (see [percolate_constants]). The results are lifted
+/// back to the original network (and sanitized), i.e., they only contain states of the
+/// percolated trap space.
+///
+/// The formulae must not contain quantifiers over all states (`3{x}`, `V{x}`) or past operators
+/// (`EP`).
+pub fn model_check_formulae_percolated(
+    formulae: Vec<&str>,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Vec<GraphColoredVertices>, String> {
+    let bn = graph
+        .as_network()
+        .ok_or("Graph is not based on a Boolean network.".to_string())?;
+    let percolated = percolate_constants(bn)?;
+
+    let mut trees = Vec::new();
+    let mut num_hctl_vars = 0;
+    for formula in formulae {
+        let tree = parse_and_minimize_hctl_formula(graph.symbolic_context(), formula)?;
+        let tree = percolated.percolate_formula(tree)?;
+        num_hctl_vars = num_hctl_vars.max(collect_unique_hctl_vars(tree.clone()).len());
+        trees.push(tree);
+    }
+    let reduced_graph = get_extended_symbolic_graph(&percolated.network, num_hctl_vars as u16)?;
+    model_check_multiple_trees(trees, &reduced_graph)?
+        .iter()
+        .map(|result| {
+            let lifted = percolated
+                .lift_colored_vertices(&reduced_graph, result, graph)
+                .ok_or("Results can not be lifted to the original network.".to_string())?;
+            Ok(sanitize_colored_vertices(graph, &lifted))
+        })
+        .collect()
+}
+
+/// Variable removed from the network by [eliminate_variable], which serves as a certificate of
+/// the reduction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EliminatedVariable {
+    /// Name of the eliminated variable.
+    pub name: String,
+    /// Update function of the eliminated variable, which was substituted into its targets.
+    pub function: String,
+    /// Names of the targets of the eliminated variable.
+    pub targets: Vec<String>,
+}
+
+/// Network reduced by the elimination of variables (see [reduce_for_formulae]).
+#[derive(Clone, Debug)]
+pub struct ReducedNetwork {
+    /// The reduced network, without the eliminated variables.
+    pub network: BooleanNetwork,
+    /// Eliminated variables, in the order of elimination.
+    pub eliminated: Vec<EliminatedVariable>,
+}
+
+/// Check whether the variable can be eliminated from the network. That is, it has an explicit
+/// update function, it does not regulate itself, and its function and the functions of all its
+/// targets are fully specified (so that the colors of the network are not affected).
+fn can_eliminate(bn: &BooleanNetwork, var: VariableId) -> bool {
+    let is_specified = |var: VariableId| {
+        bn.get_update_function(var)
+            .as_ref()
+            .is_some_and(|f| f.collect_parameters().is_empty())
+    };
+    !bn.regulators(var).contains(&var)
+        && is_specified(var)
+        && bn.targets(var).into_iter().all(is_specified)
+}
+
+/// Eliminate the variable `var` from the network, substituting its update function into the
+/// update functions of its targets (see the module documentation). Return the reduced network,
+/// together with the record of the elimination.
+///
+/// The variable must have a fully specified update function without a self-regulation, and all
+/// its targets must have fully specified update functions.
+pub fn eliminate_variable(
+    bn: &BooleanNetwork,
+    var: VariableId,
+) -> Result<(BooleanNetwork, EliminatedVariable), String> {
+    let name = bn.get_variable_name(var).clone();
+    if !can_eliminate(bn, var) {
+        return Err(format!("Variable `{name}` can not be eliminated."));
+    }
+    let function = bn.get_update_function(var).clone().unwrap();
+    let targets = bn.targets(var);
+    let mut functions: Vec<Option<FnUpdate>> = bn
+        .variables()
+        .map(|v| bn.get_update_function(v).clone())
+        .collect();
+    for target in &targets {
+        let target_function = functions[target.to_index()].as_ref().unwrap();
+        functions[target.to_index()] = Some(target_function.substitute_variable(var, &function));
+    }
+
+    let network = remove_variables(bn, &HashSet::from([var]), &functions, false)?;
+    let record = EliminatedVariable {
+        name,
+        function: function.to_string(bn),
+        targets: targets
+            .into_iter()
+            .map(|t| bn.get_variable_name(t).clone())
+            .collect(),
+    };
+    Ok((network, record))
+}
+
+/// Check whether the tree is a propositional formula (without temporal or hybrid operators).
+fn is_propositional(node: &HctlTreeNode) -> bool {
+    match &node.node_type {
+        NodeType::Terminal(atom) => matches!(
+            atom,
+            Atomic::Prop(_) | Atomic::State(..) | Atomic::True | Atomic::False
+        ),
+        NodeType::Unary(UnaryOp::Not, child) => is_propositional(child),
+        NodeType::Unary(..) => false,
+        NodeType::Binary(op, left, right) => {
+            !matches!(
+                op,
+                BinaryOp::EU | BinaryOp::AU | BinaryOp::EW | BinaryOp::AW
+            ) && is_propositional(left)
+                && is_propositional(right)
+        }
+        NodeType::Hybrid(..) => false,
+    }
+}
+
+/// Check whether the tree is the fixed-point pattern `!{x}: AX {x}`.
+fn is_fixed_point_pattern(node: &HctlTreeNode) -> bool {
+    match &node.node_type {
+        NodeType::Hybrid(HybridOp::Bind, var, None, child) => match &child.node_type {
+            NodeType::Unary(UnaryOp::AX, inner) => {
+                matches!(&inner.node_type, NodeType::Terminal(Atomic::Var(name)) if name == var)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Check whether the formula describes a set of fixed points, i.e., it is a conjunction of the
+/// fixed-point pattern and propositional formulae. The results of such formulae are preserved
+/// by the elimination of variables.
+fn is_fixed_point_formula(node: &HctlTreeNode) -> bool {
+    fn collect_conjuncts<'a>(node: &'a HctlTreeNode, conjuncts: &mut Vec<&'a HctlTreeNode>) {
+        match &node.node_type {
+            NodeType::Binary(BinaryOp::And, left, right) => {
+                collect_conjuncts(left, conjuncts);
+                collect_conjuncts(right, conjuncts);
+            }
+            _ => conjuncts.push(node),
+        }
+    }
+    let mut conjuncts = Vec::new();
+    collect_conjuncts(node, &mut conjuncts);
+    conjuncts.iter().any(|c| is_fixed_point_pattern(c))
+        && conjuncts
+            .iter()
+            .all(|c| is_fixed_point_pattern(c) || is_propositional(c))
+}
+
+/// Collect the names of all propositions in the tree.
+fn collect_propositions(node: &HctlTreeNode, propositions: &mut HashSet<String>) {
+    match &node.node_type {
+        NodeType::Terminal(Atomic::Prop(name)) => {
+            propositions.insert(name.clone());
+        }
+        NodeType::Terminal(Atomic::State(values, _)) => {
+            propositions.extend(values.iter().map(|(name, _)| name.clone()));
+        }
+        NodeType::Terminal(_) => {}
+        NodeType::Unary(UnaryOp::EXVar(name) | UnaryOp::AXVar(name), child) => {
+            propositions.insert(name.clone());
+            collect_propositions(child, propositions)
+        }
+        NodeType::Unary(_, child) | NodeType::Hybrid(_, _, _, child) => {
+            collect_propositions(child, propositions)
+        }
+        NodeType::Binary(_, left, right) => {
+            collect_propositions(left, propositions);
+            collect_propositions(right, propositions);
+        }
+    }
+}
+
+/// Reduce the network by eliminating all variables that are not referenced by any of the
+/// `formulae` (one by one, as long as possible), if this preserves the results of the formulae.
+///
+/// Currently, this is the case if all formulae describe fixed points, i.e., they are conjunctions
+/// of the pattern `!{x}: AX {x}` and propositional formulae. The fixed points of the reduced
+/// network are then exactly the projections of the fixed points of the original network (with
+/// the same colors). For other formulae, the network is returned unchanged (with no eliminated
+/// variables).
+pub fn reduce_for_formulae(
+    bn: &BooleanNetwork,
+    formulae: &[String],
+) -> Result<ReducedNetwork, String> {
+    let mut propositions = HashSet::new();
+    let mut preserved = true;
+    for formula in formulae {
+        let tree = parse_extended_formula(formula)?;
+        preserved = preserved && is_fixed_point_formula(&tree);
+        collect_propositions(&tree, &mut propositions);
+    }
+    let mut reduced = ReducedNetwork {
+        network: bn.clone(),
+        eliminated: Vec::new(),
+    };
+    if !preserved {
+        return Ok(reduced);
+    }
+
+    while let Some(var) = reduced.network.variables().find(|var| {
+        !propositions.contains(reduced.network.get_variable_name(*var))
+            && can_eliminate(&reduced.network, *var)
+    }) {
+        let (network, record) = eliminate_variable(&reduced.network, var)?;
+        reduced.network = network;
+        reduced.eliminated.push(record);
+    }
+    Ok(reduced)
+}
+
+/// Restrict the network to the variables of the given module of its regulatory graph, i.e.,
+/// remove all variables outside of the module (and the parameters that are no longer used).
+pub fn restrict_to_module(
+    bn: &BooleanNetwork,
+    module: &RegulatoryModule,
+) -> Result<BooleanNetwork, String> {
+    let removed: HashSet<VariableId> = bn
+        .variables()
+        .filter(|var| !module.variables.contains(var))
+        .collect();
+    let functions: Vec<Option<FnUpdate>> = bn
+        .variables()
+        .map(|var| bn.get_update_function(var).clone())
+        .collect();
+    remove_variables(bn, &removed, &functions, true)
+}
+
+/// Check whether the tree is a reachability formula, i.e., it is built from propositions using
+/// Boolean operators and `EF`, `EU`, and `AG` only. The results of such formulae are preserved
+/// by the restriction to a module containing all their propositions.
+fn is_reachability_formula(node: &HctlTreeNode) -> bool {
+    match &node.node_type {
+        NodeType::Terminal(atom) => matches!(
+            atom,
+            Atomic::Prop(_) | Atomic::State(..) | Atomic::True | Atomic::False
+        ),
+        NodeType::Unary(op, child) => {
+            matches!(op, UnaryOp::Not | UnaryOp::EF | UnaryOp::AG) && is_reachability_formula(child)
+        }
+        NodeType::Binary(op, left, right) => {
+            !matches!(op, BinaryOp::AU | BinaryOp::EW | BinaryOp::AW)
+                && is_reachability_formula(left)
+                && is_reachability_formula(right)
+        }
+        NodeType::Hybrid(..) => false,
+    }
+}
+
+/// Restrict the network to the smallest module of its regulatory graph that contains all
+/// propositions of the `formulae` (see [find_module_for_propositions]), if this preserves the
+/// results of the formulae (they must all be reachability formulae).
+///
+/// Return `None` if the restriction is not applicable, or if the module is the whole network.
+/// The results of the restricted network only cover the variables of the module, the remaining
+/// variables (and parameters) are unconstrained (up to the static constraints of the network).
+pub fn restrict_to_formulae_module(
+    bn: &BooleanNetwork,
+    formulae: &[String],
+) -> Result<Option<BooleanNetwork>, String> {
+    let mut propositions = HashSet::new();
+    for formula in formulae {
+        let tree = parse_extended_formula(formula)?;
+        if !is_reachability_formula(&tree) {
+            return Ok(None);
+        }
+        collect_propositions(&tree, &mut propositions);
+    }
+    match find_module_for_propositions(bn.as_graph(), &propositions) {
+        Some(module) if module.variables.len() < bn.num_vars() => {
+            Ok(Some(restrict_to_module(bn, &module)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use crate::postprocessing::sanitizing::sanitize_colored_vertices;
+    use crate::reduction::{
+        eliminate_variable, model_check_formulae_percolated, percolate_constants,
+        reduce_for_formulae, restrict_to_formulae_module,
+    };
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+    use biodivine_lib_param_bn::{BooleanNetwork, FnUpdate};
+
+    #[test]
+    /// Test percolating the constants through the network.
+    fn test_percolation() {
+        let bn = BooleanNetwork::try_from(
+            "i -> a\np -> a\na -> b\nb -> c\nc -| b\nd -? e\nk -> e\n\
+            $i: true\n$p: false\n$a: i & p & f(p)\n$b: (a | c) & !c\n$c: b\n$k: false",
+        )
+        .unwrap();
+        let percolated = percolate_constants(&bn).unwrap();
+        // `i` and `p` are constant, which makes `a` constant too (and removes the parameter)
+        assert_eq!(percolated.fixed_values.len(), 3);
+        assert!(percolated.fixed_values["i"]);
+        assert!(!percolated.fixed_values["p"]);
+        assert!(!percolated.fixed_values["a"]);
+        assert_eq!(percolated.network.num_vars(), 5);
+        assert_eq!(percolated.network.num_parameters(), 0);
+        let b = percolated.network.as_graph().find_variable("b").unwrap();
+        let c = percolated.network.as_graph().find_variable("c").unwrap();
+        let expected = FnUpdate::mk_var(c).and(FnUpdate::mk_var(c).negation());
+        assert_eq!(percolated.network.get_update_function(b), &Some(expected));
+        // `k` regulates a variable with an implicit function, so it is not removed
+        assert!(percolated.network.as_graph().find_variable("k").is_some());
+    }
+
+    #[test]
+    /// Test that the lifted results match the results on the original network (inside the
+    /// percolated trap space).
+    fn test_percolated_model_checking() {
+        let bn = BooleanNetwork::try_from(
+            "i -> a\na -> b\nb -> c\nc -| b\nd -? e\ne -> d\n\
+            $i: true\n$a: i\n$b: a & !c\n$c: b | f",
+        )
+        .unwrap();
+        let graph = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let trap_space = model_check_formula("i & a", &graph).unwrap();
+
+        let formulae = vec![
+            "!{x}: AG EF {x}",
+            "EF (b & ~c)",
+            "!{x}: AX (EF {x} & ~a)",
+            "!{x}: AX {x} | AG (i => EX e)",
+            "EX_{i} b | AX_{a} c",
+            "AX_{b} EX_{c} b",
+        ];
+        let results = model_check_formulae_percolated(formulae.clone(), &graph).unwrap();
+        for (formula, result) in formulae.into_iter().zip(results) {
+            let expected = model_check_formula(formula, &graph).unwrap();
+            assert_eq!(result, expected.intersect(&trap_space));
+        }
+
+        // lifted sets only contain the valid colors of the original graph
+        let percolated = percolate_constants(&bn).unwrap();
+        let reduced_graph = get_extended_symbolic_graph(&percolated.network, 2).unwrap();
+        let reduced_unit = reduced_graph.mk_unit_colored_vertices();
+        let lifted = percolated
+            .lift_colored_vertices(&reduced_graph, &reduced_unit, &graph)
+            .unwrap();
+        let unit = sanitize_colored_vertices(&graph, &graph.mk_unit_colored_vertices());
+        assert_eq!(
+            sanitize_colored_vertices(&graph, &lifted),
+            trap_space.intersect(&unit)
+        );
+
+        // quantifiers over all states and past operators are not supported
+        assert!(model_check_formulae_percolated(vec!["3{x}: @{x}: a"], &graph).is_err());
+        assert!(model_check_formulae_percolated(vec!["EP ~i"], &graph).is_err());
+        assert!(model_check_formulae_percolated(vec!["AG (b => EP c)"], &graph).is_err());
+    }
+
+    #[test]
+    /// Test eliminating a mediator variable from the network.
+    fn test_elimination() {
+        let bn = BooleanNetwork::try_from(
+            "a -> m\nm -> b\nm -| c\nb -| a\nc -> c\n$m: a\n$b: m\n$c: c | !m\n$a: !b",
+        )
+        .unwrap();
+        let m = bn.as_graph().find_variable("m").unwrap();
+        let (reduced, record) = eliminate_variable(&bn, m).unwrap();
+        assert_eq!(record.name, "m");
+        assert_eq!(record.function, "a");
+        assert_eq!(record.targets, vec!["b".to_string(), "c".to_string()]);
+        assert_eq!(reduced.num_vars(), 3);
+        let a = reduced.as_graph().find_variable("a").unwrap();
+        let b = reduced.as_graph().find_variable("b").unwrap();
+        assert_eq!(reduced.get_update_function(b), &Some(FnUpdate::mk_var(a)));
+        assert!(reduced.as_graph().find_regulation(a, b).is_some());
+
+        // variables with self-regulations (or implicit/parametrized targets) are kept
+        let c = bn.as_graph().find_variable("c").unwrap();
+        assert!(eliminate_variable(&bn, c).is_err());
+        let bn = BooleanNetwork::try_from("a -> m\nm -> b\n$m: a\n$a: true").unwrap();
+        let m = bn.as_graph().find_variable("m").unwrap();
+        assert!(eliminate_variable(&bn, m).is_err());
+    }
+
+    #[test]
+    /// Test that the fixed points of the reduced network are exactly the projections of the fixed
+    /// points of the original network, and that other formulae are not reduced.
+    fn test_reduction_for_fixed_points() {
+        let bn = BooleanNetwork::try_from(
+            "a -> m\nm -> b\nb -| a\nb -> c\nc -? c\n$a: !b & g\n$m: a\n$b: m",
+        )
+        .unwrap();
+        let formula = "(!{x}: AX {x}) & ~c".to_string();
+        let reduced = reduce_for_formulae(&bn, std::slice::from_ref(&formula)).unwrap();
+        // only `m` is eliminated (`a` and `b` have targets with parameters or implicit functions)
+        assert_eq!(reduced.eliminated.len(), 1);
+        assert_eq!(reduced.eliminated[0].name, "m");
+        assert_eq!(reduced.network.num_vars(), 3);
+
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let reduced_graph = get_extended_symbolic_graph(&reduced.network, 1).unwrap();
+        let result = model_check_formula(&formula, &graph).unwrap();
+        let reduced_result = model_check_formula(&formula, &reduced_graph).unwrap();
+
+        // project the original result onto the remaining variables
+        let original_context = SymbolicAsyncGraph::new(&bn)
+            .unwrap()
+            .symbolic_context()
+            .clone();
+        let reduced_context = SymbolicAsyncGraph::new(&reduced.network)
+            .unwrap()
+            .symbolic_context()
+            .clone();
+        let eliminated: Vec<_> = ["m"]
+            .iter()
+            .map(|name| {
+                let var = bn.as_graph().find_variable(name).unwrap();
+                original_context.get_state_variable(var)
+            })
+            .collect();
+        let projected = result.as_bdd().exists(&eliminated);
+        let projected = reduced_context
+            .transfer_from(&projected, &original_context)
+            .unwrap();
+        assert_eq!(&projected, reduced_result.as_bdd());
+        assert_eq!(
+            result.vertices().approx_cardinality(),
+            reduced_result.vertices().approx_cardinality()
+        );
+
+        // the formulae describing other properties are not reduced
+        let formulae = vec![formula, "!{x}: AG EF {x}".to_string()];
+        let reduced = reduce_for_formulae(&bn, &formulae).unwrap();
+        assert!(reduced.eliminated.is_empty());
+        assert_eq!(reduced.network.num_vars(), 4);
+    }
+
+    #[test]
+    /// Test that the results of reachability formulae on the restricted network match the results
+    /// on the whole network.
+    fn test_module_restriction() {
+        let bn = BooleanNetwork::try_from(
+            "i -> a\na -> b\nb -| a\nb -> c\nc -? d\nd -| c\n$a: i & !b\n$b: a | f\n$c: b & !d",
+        )
+        .unwrap();
+        let formulae = vec!["EF (a & b)".to_string(), "AG (b => EF ~a)".to_string()];
+        let module_bn = restrict_to_formulae_module(&bn, &formulae)
+            .unwrap()
+            .unwrap();
+        assert_eq!(module_bn.num_vars(), 3);
+        assert_eq!(module_bn.num_parameters(), 1);
+
+        let graph = get_extended_symbolic_graph(&bn, 0).unwrap();
+        let module_graph = get_extended_symbolic_graph(&module_bn, 0).unwrap();
+        for formula in &formulae {
+            let result = model_check_formula(formula, &graph).unwrap();
+            let module_result = model_check_formula(formula, &module_graph).unwrap();
+            let lifted = graph
+                .symbolic_context()
+                .transfer_from(module_result.as_bdd(), module_graph.symbolic_context())
+                .unwrap();
+            // colors of the remaining variables are only restricted by the static constraints
+            let unit = graph.unit_colored_vertices().as_bdd();
+            assert_eq!(result.as_bdd().and(unit), lifted.and(unit));
+        }
+
+        // formulae with other operators (or covering the whole network) are not restricted
+        let formulae = vec!["EF (a & b)".to_string(), "AF a".to_string()];
+        assert!(restrict_to_formulae_module(&bn, &formulae)
+            .unwrap()
+            .is_none());
+        let formulae = vec!["EF (a & d)".to_string()];
+        assert!(restrict_to_formulae_module(&bn, &formulae)
+            .unwrap()
+            .is_none());
+    }
+}
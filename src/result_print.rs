@@ -1,6 +1,9 @@
 //! Print results of the computation, either aggregated version only, or a full set of satisfying states.
 
+use crate::interventions::DriverImpact;
+
 use biodivine_lib_param_bn::biodivine_std::bitvector::BitVector;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, SymbolicAsyncGraph};
 
 use std::io::Write;
@@ -31,23 +34,53 @@ pub(crate) fn print_if_allowed(text: String, print_options: PrintOptions) {
 }
 
 /// Print general info about the resulting set of colored vertices - the cardinality of the whole
-/// set and its projections to colors and vertices (and the computation time).
+/// set and its projections to colors and vertices (and the computation time, if the `start_time`
+/// is given).
 pub(crate) fn summarize_results(
     formula: String,
     results: &GraphColoredVertices,
-    start_time: SystemTime,
+    start_time: Option<SystemTime>,
 ) {
     println!("Formula: {formula}");
-    println!(
-        "Time to model check: {}ms",
-        start_time.elapsed().unwrap().as_millis()
-    );
+    if let Some(start_time) = start_time {
+        println!(
+            "Time to model check: {}ms",
+            start_time.elapsed().unwrap().as_millis()
+        );
+    }
     println!("{} results in total", results.approx_cardinality());
     println!("{} unique colors", results.colors().approx_cardinality());
     println!("{} unique states", results.vertices().approx_cardinality());
     println!("-----");
 }
 
+/// Print the summary of differences between two results for the same formula - the sets of
+/// results present only in the first (or only in the second) version.
+pub(crate) fn summarize_difference(
+    formula: String,
+    only_first: &GraphColoredVertices,
+    only_second: &GraphColoredVertices,
+) {
+    println!("Formula: {formula}");
+    if only_first.is_empty() && only_second.is_empty() {
+        println!("Results are identical");
+    } else {
+        println!(
+            "{} results only in the first bundle ({} colors, {} states)",
+            only_first.approx_cardinality(),
+            only_first.colors().approx_cardinality(),
+            only_first.vertices().approx_cardinality(),
+        );
+        println!(
+            "{} results only in the second bundle ({} colors, {} states)",
+            only_second.approx_cardinality(),
+            only_second.colors().approx_cardinality(),
+            only_second.vertices().approx_cardinality(),
+        );
+    }
+    println!("-----");
+}
+
 /// Print the general info about the resulting set and then prints all states which are included
 /// in the resulting set for at least one color (basically 'project out the colors' and print just
 /// the states).
@@ -58,7 +91,7 @@ pub(crate) fn print_results_full(
     formula: String,
     graph: &SymbolicAsyncGraph,
     results: &GraphColoredVertices,
-    start_time: SystemTime,
+    start_time: Option<SystemTime>,
     show_names: bool,
 ) {
     // first print general summarizing information
@@ -99,3 +132,24 @@ pub(crate) fn print_results_full(
     }
     println!("-----");
 }
+
+/// Print the table of the driver variables of the property given by `formula` (see
+/// [crate::interventions::rank_driver_variables]), one fixing per row, in the given order.
+pub(crate) fn print_driver_table(formula: &str, impacts: &[DriverImpact]) {
+    println!("Formula: {formula}");
+    println!(
+        "{:<24}{:>7}{:>18}{:>18}{:>10}",
+        "variable", "value", "changed colors", "changed states", "fraction"
+    );
+    for impact in impacts {
+        println!(
+            "{:<24}{:>7}{:>18}{:>18}{:>10.4}",
+            impact.variable,
+            u8::from(impact.value),
+            impact.changed_colors,
+            impact.changed_states,
+            impact.changed_fraction(),
+        );
+    }
+    println!("-----");
+}
@@ -0,0 +1,535 @@
+//! A simple HTTP/JSON server for the model checking, so that web front-ends or workflow engines
+//! can use a persistent service instead of repeated CLI calls. Only available with the `server`
+//! feature.
+//!
+//! The server provides the following endpoints:
+//!  - `POST /models` with a BN model (in any supported format) as the body. Returns `{"model_id": ID}`.
+//!  - `POST /models/{ID}/jobs` with `{"formulae": [...]}` as the body. Queues model checking of
+//!    the formulae (evaluated in the background) and returns `{"job_id": ID}`.
+//!  - `GET /jobs/{ID}` returns the progress of the job (status, number of evaluated formulae).
+//!  - `GET /jobs/{ID}/results` returns the summaries of the results evaluated so far.
+//!  - `GET /jobs/{ID}/witness` returns an instance of the model satisfying all formulae of the job.
+//!
+//! Each request is handled by its own thread (so a witness computed on request does not block
+//! the others), and the jobs are evaluated by a fixed number of worker threads. The request
+//! bodies, the number of queued jobs, and the numbers of kept models and jobs are limited (see
+//! [ServerLimits]). When there are too many models (or finished jobs), the oldest ones are
+//! evicted.
+
+use crate::analysis::{find_witness_network, FormulaSummary};
+use crate::load_inputs::network_from_str;
+use crate::session::ModelCheckingSession;
+
+use biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices;
+use biodivine_lib_param_bn::BooleanNetwork;
+use serde_json::{json, Value};
+use tiny_http::{Header, Request, Response, Server};
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+/// Limits of the resources used by the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ServerLimits {
+    /// Maximal size of a request body (in bytes).
+    pub max_body_size: usize,
+    /// Number of worker threads evaluating the jobs.
+    pub num_workers: usize,
+    /// Maximal number of jobs waiting for a worker.
+    pub max_queued_jobs: usize,
+    /// Maximal number of kept models (the oldest ones are evicted).
+    pub max_models: usize,
+    /// Maximal number of kept jobs (the oldest finished ones are evicted).
+    pub max_jobs: usize,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        ServerLimits {
+            max_body_size: 16 * 1024 * 1024,
+            num_workers: 2,
+            max_queued_jobs: 64,
+            max_models: 32,
+            max_jobs: 256,
+        }
+    }
+}
+
+/// Status of a model-checking job.
+#[derive(Clone, Debug, PartialEq)]
+enum JobStatus {
+    Queued,
+    Running,
+    Finished,
+    Failed(String),
+}
+
+/// A model-checking job, evaluating formulae on a model in the background.
+struct Job {
+    model_id: usize,
+    formulae: Vec<String>,
+    status: JobStatus,
+    results: Vec<FormulaSummary>,
+}
+
+/// A model uploaded to the server.
+struct Model {
+    bn: BooleanNetwork,
+    /// Steady states of the model, computed by its first job and reused by the later ones.
+    steady_states: Option<GraphColoredVertices>,
+}
+
+/// Shared state of the server, the uploaded models and submitted jobs.
+struct ServerState {
+    limits: ServerLimits,
+    /// Models and jobs by their IDs (the IDs are increasing, so the oldest come first).
+    models: BTreeMap<usize, Model>,
+    jobs: BTreeMap<usize, Job>,
+    next_model_id: usize,
+    next_job_id: usize,
+    /// Queue of the jobs (by their IDs) waiting for a worker.
+    queue: SyncSender<usize>,
+}
+
+impl ServerState {
+    /// Create the state of the server, and start its worker threads. The workers run until the
+    /// process is terminated.
+    fn start(limits: ServerLimits) -> Arc<Mutex<ServerState>> {
+        let (queue, receiver) = sync_channel(limits.max_queued_jobs);
+        let state = Arc::new(Mutex::new(ServerState {
+            limits,
+            models: BTreeMap::new(),
+            jobs: BTreeMap::new(),
+            next_model_id: 0,
+            next_job_id: 0,
+            queue,
+        }));
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..limits.num_workers.max(1) {
+            let state = state.clone();
+            let receiver = receiver.clone();
+            thread::spawn(move || run_worker(&state, &receiver));
+        }
+        state
+    }
+
+    /// Evict the oldest models, and the oldest finished jobs, over the limits.
+    fn evict(&mut self) {
+        while self.models.len() > self.limits.max_models {
+            self.models.pop_first();
+        }
+        while self.jobs.len() > self.limits.max_jobs {
+            let finished = self
+                .jobs
+                .iter()
+                .find(|(_, job)| matches!(job.status, JobStatus::Finished | JobStatus::Failed(_)));
+            match finished.map(|(job_id, _)| *job_id) {
+                Some(job_id) => self.jobs.remove(&job_id),
+                None => break,
+            };
+        }
+    }
+}
+
+/// Take the queued jobs one by one and evaluate them.
+fn run_worker(state: &Arc<Mutex<ServerState>>, receiver: &Mutex<Receiver<usize>>) {
+    loop {
+        // the lock is only held while waiting, other workers wait for it
+        let job_id = match receiver.lock().unwrap().recv() {
+            Ok(job_id) => job_id,
+            Err(_) => return,
+        };
+        let status = match evaluate_job(state, job_id) {
+            Ok(()) => JobStatus::Finished,
+            Err(e) => JobStatus::Failed(e),
+        };
+        if let Some(job) = state.lock().unwrap().jobs.get_mut(&job_id) {
+            job.status = status;
+        }
+    }
+}
+
+/// Run the server on the given address (such as `127.0.0.1:8080`), with the default limits.
+/// The server runs until the process is terminated.
+pub fn run_server(address: &str) -> Result<(), String> {
+    run_server_with_limits(address, ServerLimits::default())
+}
+
+/// Run the server on the given address (such as `127.0.0.1:8080`), with the given limits of
+/// the used resources. The server runs until the process is terminated.
+pub fn run_server_with_limits(address: &str, limits: ServerLimits) -> Result<(), String> {
+    let server = Server::http(address).map_err(|e| e.to_string())?;
+    println!("Listening on {address}.");
+    serve_requests(&server, &ServerState::start(limits));
+    Ok(())
+}
+
+/// Handle the incoming requests of the `server`, each in its own thread, so that a slow request
+/// (such as the computation of a witness) does not block the others.
+fn serve_requests(server: &Server, state: &Arc<Mutex<ServerState>>) {
+    for request in server.incoming_requests() {
+        let state = state.clone();
+        thread::spawn(move || handle_request(&state, request));
+    }
+}
+
+/// Read the body of the `request`, route it, and send the (JSON) response.
+fn handle_request(state: &Arc<Mutex<ServerState>>, mut request: Request) {
+    let max_body_size = state.lock().unwrap().limits.max_body_size;
+    let (status, response) = match read_body(&mut request, max_body_size) {
+        Ok(body) => route(
+            state,
+            request.method().as_str(),
+            request.url(),
+            body.as_str(),
+        ),
+        Err((status, message)) => (status, json!({ "error": message })),
+    };
+    let json_header = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+    let response = Response::from_string(response.to_string())
+        .with_status_code(status)
+        .with_header(json_header);
+    // the client may have disconnected, which does not affect the server
+    let _ = request.respond(response);
+}
+
+/// Read the body of the request, which must not be larger than `max_size` bytes.
+fn read_body(request: &mut Request, max_size: usize) -> Result<String, (u16, String)> {
+    let too_large = (413, format!("The request body exceeds {max_size} bytes."));
+    if request
+        .body_length()
+        .is_some_and(|length| length > max_size)
+    {
+        return Err(too_large);
+    }
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(max_size as u64 + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, e.to_string()))?;
+    if body.len() > max_size {
+        return Err(too_large);
+    }
+    Ok(body)
+}
+
+/// Process the request given by its method, url and body. Return the HTTP status code and the
+/// JSON response. The query part of the url is ignored.
+fn route(state: &Arc<Mutex<ServerState>>, method: &str, url: &str, body: &str) -> (u16, Value) {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    let result = match (method, segments.as_slice()) {
+        ("POST", ["models"]) => upload_model(state, body),
+        ("POST", ["models", model_id, "jobs"]) => submit_job(state, model_id, body),
+        ("GET", ["jobs", job_id]) => job_progress(state, job_id),
+        ("GET", ["jobs", job_id, "results"]) => job_results(state, job_id),
+        ("GET", ["jobs", job_id, "witness"]) => job_witness(state, job_id),
+        _ => Err((404, format!("Unknown endpoint `{method} {path}`."))),
+    };
+    match result {
+        Ok(value) => (200, value),
+        Err((status, message)) => (status, json!({ "error": message })),
+    }
+}
+
+/// Parse the ID from the url segment.
+fn parse_id(id: &str) -> Result<usize, (u16, String)> {
+    id.parse::<usize>()
+        .map_err(|_| (400, format!("Invalid ID `{id}`.")))
+}
+
+/// Handle `POST /models`.
+fn upload_model(state: &Arc<Mutex<ServerState>>, body: &str) -> Result<Value, (u16, String)> {
+    let bn = network_from_str(body, None).map_err(|e| (400, e))?;
+    let mut state = state.lock().unwrap();
+    let model_id = state.next_model_id;
+    state.next_model_id += 1;
+    let model = Model {
+        bn,
+        steady_states: None,
+    };
+    state.models.insert(model_id, model);
+    state.evict();
+    Ok(json!({ "model_id": model_id }))
+}
+
+/// Handle `POST /models/{ID}/jobs`, and queue the job for the workers.
+fn submit_job(
+    state: &Arc<Mutex<ServerState>>,
+    model_id: &str,
+    body: &str,
+) -> Result<Value, (u16, String)> {
+    let model_id = parse_id(model_id)?;
+    let request: Value = serde_json::from_str(body).map_err(|e| (400, e.to_string()))?;
+    let formulae = request["formulae"]
+        .as_array()
+        .and_then(|formulae| {
+            formulae
+                .iter()
+                .map(|f| f.as_str().map(|f| f.to_string()))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or((400, "Expected a list of formulae.".to_string()))?;
+
+    let mut state = state.lock().unwrap();
+    if !state.models.contains_key(&model_id) {
+        return Err((404, format!("Unknown model `{model_id}`.")));
+    }
+    let job_id = state.next_job_id;
+    match state.queue.try_send(job_id) {
+        Ok(()) => {}
+        Err(TrySendError::Full(_)) => {
+            return Err((503, "Too many queued jobs, try again later.".to_string()))
+        }
+        Err(TrySendError::Disconnected(_)) => {
+            return Err((500, "The workers are not running.".to_string()))
+        }
+    }
+    state.next_job_id += 1;
+    let job = Job {
+        model_id,
+        formulae,
+        status: JobStatus::Queued,
+        results: Vec::new(),
+    };
+    state.jobs.insert(job_id, job);
+    state.evict();
+    Ok(json!({ "job_id": job_id }))
+}
+
+/// Evaluate the formulae of the job one by one, and store the results as they come. The state
+/// is only locked to read the inputs and to store the results, not during the evaluation.
+fn evaluate_job(state: &Arc<Mutex<ServerState>>, job_id: usize) -> Result<(), String> {
+    let mut locked_state = state.lock().unwrap();
+    let job = locked_state
+        .jobs
+        .get_mut(&job_id)
+        .ok_or(format!("Job `{job_id}` was evicted."))?;
+    job.status = JobStatus::Running;
+    let (model_id, formulae) = (job.model_id, job.formulae.clone());
+    let model = locked_state
+        .models
+        .get(&model_id)
+        .ok_or(format!("Model `{model_id}` was evicted."))?;
+    let (bn, steady_states) = (model.bn.clone(), model.steady_states.clone());
+    drop(locked_state);
+
+    let mut session = match steady_states {
+        Some(steady_states) => ModelCheckingSession::with_steady_states(bn, steady_states)?,
+        None => {
+            let session = ModelCheckingSession::new(bn)?;
+            let steady_states = session.get_steady_states().clone();
+            if let Some(model) = state.lock().unwrap().models.get_mut(&model_id) {
+                model.steady_states = Some(steady_states);
+            }
+            session
+        }
+    };
+    for formula in formulae {
+        let start = Instant::now();
+        let result = session.evaluate(formula.as_str())?;
+        let summary = FormulaSummary {
+            formula,
+            num_results: result.approx_cardinality(),
+            num_colors: result.colors().approx_cardinality(),
+            num_states: result.vertices().approx_cardinality(),
+            time_ms: start.elapsed().as_millis(),
+        };
+        let mut state = state.lock().unwrap();
+        let job = state
+            .jobs
+            .get_mut(&job_id)
+            .ok_or(format!("Job `{job_id}` was evicted."))?;
+        job.results.push(summary);
+    }
+    Ok(())
+}
+
+/// Handle `GET /jobs/{ID}`.
+fn job_progress(state: &Arc<Mutex<ServerState>>, job_id: &str) -> Result<Value, (u16, String)> {
+    let job_id = parse_id(job_id)?;
+    let state = state.lock().unwrap();
+    let job = state
+        .jobs
+        .get(&job_id)
+        .ok_or((404, format!("Unknown job `{job_id}`.")))?;
+    let mut progress = json!({
+        "model_id": job.model_id,
+        "evaluated": job.results.len(),
+        "total": job.formulae.len(),
+    });
+    match &job.status {
+        JobStatus::Queued => progress["status"] = json!("queued"),
+        JobStatus::Running => progress["status"] = json!("running"),
+        JobStatus::Finished => progress["status"] = json!("finished"),
+        JobStatus::Failed(e) => {
+            progress["status"] = json!("failed");
+            progress["error"] = json!(e);
+        }
+    }
+    Ok(progress)
+}
+
+/// Handle `GET /jobs/{ID}/results`.
+fn job_results(state: &Arc<Mutex<ServerState>>, job_id: &str) -> Result<Value, (u16, String)> {
+    let job_id = parse_id(job_id)?;
+    let state = state.lock().unwrap();
+    let job = state
+        .jobs
+        .get(&job_id)
+        .ok_or((404, format!("Unknown job `{job_id}`.")))?;
+    let results = job
+        .results
+        .iter()
+        .map(|summary| {
+            json!({
+                "formula": summary.formula,
+                "results": summary.num_results,
+                "colors": summary.num_colors,
+                "states": summary.num_states,
+                "time_ms": summary.time_ms as u64,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(json!({ "results": results }))
+}
+
+/// Handle `GET /jobs/{ID}/witness` (the witness is computed on request).
+fn job_witness(state: &Arc<Mutex<ServerState>>, job_id: &str) -> Result<Value, (u16, String)> {
+    let job_id = parse_id(job_id)?;
+    let locked_state = state.lock().unwrap();
+    let job = locked_state
+        .jobs
+        .get(&job_id)
+        .ok_or((404, format!("Unknown job `{job_id}`.")))?;
+    let bn = locked_state
+        .models
+        .get(&job.model_id)
+        .ok_or((404, format!("Model `{}` was evicted.", job.model_id)))?
+        .bn
+        .clone();
+    let formulae = job.formulae.clone();
+    // the request has its own thread, but the other requests also need the state
+    drop(locked_state);
+
+    match find_witness_network(&bn, formulae).map_err(|e| (400, e))? {
+        Some(witness) => Ok(json!({ "witness": witness.to_string() })),
+        None => Ok(json!({ "witness": null })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::{route, serve_requests, ServerLimits, ServerState};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+    use tiny_http::Server;
+
+    #[test]
+    /// Test the whole workflow of the server API (without the HTTP layer).
+    fn test_server_routes() {
+        let state = ServerState::start(ServerLimits::default());
+        let (status, response) = route(&state, "POST", "/models", "a -> b\nb -| a\n$a: !b\n$b: a");
+        assert_eq!(status, 200);
+        assert_eq!(response["model_id"], 0);
+        assert_eq!(route(&state, "POST", "/models", "invalid model").0, 400);
+
+        let body = r#"{"formulae": ["!{x}: AG EF {x}", "!{x}: AX {x}"]}"#;
+        let (status, response) = route(&state, "POST", "/models/0/jobs", body);
+        assert_eq!(status, 200);
+        assert_eq!(response["job_id"], 0);
+        assert_eq!(route(&state, "POST", "/models/5/jobs", body).0, 404);
+        assert_eq!(route(&state, "POST", "/models/0/jobs", "{}").0, 400);
+
+        // wait for the job to finish (the query part of the url is ignored)
+        let mut progress = route(&state, "GET", "/jobs/0?poll=1", "").1;
+        while progress["status"] == "queued" || progress["status"] == "running" {
+            thread::sleep(Duration::from_millis(10));
+            progress = route(&state, "GET", "/jobs/0?poll=1", "").1;
+        }
+        assert_eq!(progress["status"], "finished");
+        assert_eq!(progress["evaluated"], 2);
+        // steady states of the model are kept for the later jobs
+        assert!(state.lock().unwrap().models[&0].steady_states.is_some());
+
+        let results = route(&state, "GET", "/jobs/0/results", "").1;
+        assert_eq!(results["results"][0]["states"], 4.0);
+        assert_eq!(results["results"][1]["results"], 0.0);
+
+        // there are no fixed points, so no witness exists
+        let (status, response) = route(&state, "GET", "/jobs/0/witness", "");
+        assert_eq!(status, 200);
+        assert!(response["witness"].is_null());
+        assert_eq!(route(&state, "GET", "/unknown", "").0, 404);
+    }
+
+    #[test]
+    /// Test that the oldest models are evicted over the limit, and that IDs are not reused.
+    fn test_server_eviction() {
+        let limits = ServerLimits {
+            max_models: 2,
+            ..ServerLimits::default()
+        };
+        let state = ServerState::start(limits);
+        for expected_id in 0..4 {
+            let response = route(&state, "POST", "/models", "a -> b\nb -| a").1;
+            assert_eq!(response["model_id"], expected_id);
+        }
+        let model_ids = state
+            .lock()
+            .unwrap()
+            .models
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(model_ids, vec![2, 3]);
+
+        let body = r#"{"formulae": ["EF a"]}"#;
+        assert_eq!(route(&state, "POST", "/models/0/jobs", body).0, 404);
+        assert_eq!(route(&state, "POST", "/models/3/jobs", body).0, 200);
+    }
+
+    #[test]
+    /// Test that a slow request does not block the other requests. The slow request (which
+    /// stands for a long computation of a witness) is simulated by a request whose (large) body
+    /// never arrives, so that its handler is stuck until the client disconnects.
+    fn test_server_concurrent_requests() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+        let state = ServerState::start(ServerLimits::default());
+        thread::spawn(move || serve_requests(&server, &state));
+
+        let mut slow = TcpStream::connect(address).unwrap();
+        write!(
+            slow,
+            "POST /models HTTP/1.1\r\nHost: test\r\nContent-Length: 1000000\r\n\r\n"
+        )
+        .unwrap();
+        slow.flush().unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        let mut fast = TcpStream::connect(address).unwrap();
+        fast.set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        write!(
+            fast,
+            "GET /jobs/0 HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        fast.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404"));
+        drop(slow);
+    }
+}
@@ -0,0 +1,316 @@
+//! An interactive model-checking session, which keeps the symbolic transition graph and the
+//! computed results in memory, so that formulae can be evaluated one by one without repeating
+//! the expensive pre-computation (such as building the graph or computing steady states).
+//!
+//! The results can be stored under a name, and the later formulae can reference them as
+//! wild-card propositions (`%name%`) or variable domains (`!{x} in %name%:`).
+
+use crate::evaluation::algorithm::{compute_steady_states, eval_node};
+use crate::evaluation::eval_context::{
+    AnalysisLimits, CancellationFlag, EvalContext, LIMIT_CHECK_INTERVAL,
+};
+use crate::evaluation::LabelToSetMap;
+use crate::mc_utils::{collect_unique_hctl_vars, get_extended_symbolic_graph};
+use crate::postprocessing::sanitizing::{extend_colored_vertices, sanitize_colored_vertices};
+use crate::preprocessing::hctl_tree::HctlTreeNode;
+use crate::preprocessing::parser::parse_and_minimize_extended_formula;
+use crate::preprocessing::utils::validate_and_divide_wild_cards;
+
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, SymbolicAsyncGraph, SymbolicContext,
+};
+use biodivine_lib_param_bn::BooleanNetwork;
+
+use std::collections::HashMap;
+use std::thread;
+use std::time::Instant;
+
+/// Model-checking session for a single BN model.
+///
+/// The session keeps an extended symbolic graph (which is only rebuilt when a formula needs
+/// more HCTL variables than the current graph supports), the pre-computed steady states, and
+/// the results of all previously evaluated formulae.
+pub struct ModelCheckingSession {
+    /// The BN model.
+    bn: BooleanNetwork,
+    /// Extended symbolic transition graph used for the evaluation.
+    graph: SymbolicAsyncGraph,
+    /// Number of HCTL variables supported by `graph`.
+    num_hctl_vars: usize,
+    /// Pre-computed steady states (sanitized, valid in the canonical context of the model).
+    steady_states: GraphColoredVertices,
+    /// Pre-computed states with self-loops (the steady states transferred into `graph`).
+    self_loop_states: GraphColoredVertices,
+    /// Results of already evaluated formulae (valid in `graph`), keyed by their canonical form.
+    cache: HashMap<String, GraphColoredVertices>,
+    /// Named results (sanitized, valid in the canonical context of the model).
+    named_results: LabelToSetMap,
+    /// Resource limits for the evaluation of each formula.
+    limits: AnalysisLimits,
+}
+
+impl ModelCheckingSession {
+    /// Start a new session for the given BN model.
+    pub fn new(bn: BooleanNetwork) -> Result<ModelCheckingSession, String> {
+        let steady_states = compute_steady_states(&SymbolicAsyncGraph::new(&bn)?);
+        ModelCheckingSession::with_steady_states(bn, steady_states)
+    }
+
+    /// Start a new session for the given BN model, with its `steady_states` computed beforehand
+    /// (see [compute_steady_states]), so that they can be shared by multiple sessions.
+    pub fn with_steady_states(
+        bn: BooleanNetwork,
+        steady_states: GraphColoredVertices,
+    ) -> Result<ModelCheckingSession, String> {
+        let graph = get_extended_symbolic_graph(&bn, 1)?;
+        let self_loop_states = extend_colored_vertices(&graph, &steady_states)
+            .ok_or("Steady states are not compatible with the model.".to_string())?;
+        let steady_states = sanitize_colored_vertices(&graph, &self_loop_states);
+        Ok(ModelCheckingSession {
+            bn,
+            graph,
+            num_hctl_vars: 1,
+            steady_states,
+            self_loop_states,
+            cache: HashMap::new(),
+            named_results: LabelToSetMap::new(),
+            limits: AnalysisLimits::default(),
+        })
+    }
+
+    /// Set the resource limits for the evaluation of each formula. If the time or memory limit
+    /// is reached, the evaluation is cancelled and an error is returned. The number of threads
+    /// is not used, as the formulae are evaluated one by one.
+    pub fn set_limits(&mut self, limits: AnalysisLimits) {
+        self.limits = limits;
+    }
+
+    /// Get a ref to the BN model of this session.
+    pub fn get_network(&self) -> &BooleanNetwork {
+        &self.bn
+    }
+
+    /// Get a ref to the extended symbolic graph currently used for the evaluation.
+    pub fn get_graph(&self) -> &SymbolicAsyncGraph {
+        &self.graph
+    }
+
+    /// Get a ref to the steady states of the model (sanitized, valid in the canonical symbolic
+    /// context of the model).
+    pub fn get_steady_states(&self) -> &GraphColoredVertices {
+        &self.steady_states
+    }
+
+    /// Get a ref to the map of named results. The results are sanitized (valid in the canonical
+    /// symbolic context of the model).
+    pub fn get_named_results(&self) -> &LabelToSetMap {
+        &self.named_results
+    }
+
+    /// Evaluate the (extended) formula and return its sanitized result.
+    ///
+    /// The formula may reference any of the named results as wild-card propositions or
+    /// variable domains.
+    pub fn evaluate(&mut self, formula: &str) -> Result<GraphColoredVertices, String> {
+        let plain_context = SymbolicContext::new(&self.bn)?;
+        let tree = parse_and_minimize_extended_formula(&plain_context, formula)?;
+
+        // extend the graph if the formula needs more HCTL variables (previous results are dropped)
+        let num_hctl_vars = collect_unique_hctl_vars(tree.clone()).len();
+        if num_hctl_vars > self.num_hctl_vars {
+            self.num_hctl_vars = num_hctl_vars;
+            self.graph = get_extended_symbolic_graph(&self.bn, num_hctl_vars as u16)?;
+            // steady states do not depend on the HCTL variables, so they are just transferred
+            self.self_loop_states = extend_colored_vertices(&self.graph, &self.steady_states)
+                .ok_or("Steady states are not compatible with the model.".to_string())?;
+            self.cache.clear();
+        }
+
+        let cache_key = tree.to_string();
+        let result = if let Some(result) = self.cache.get(&cache_key) {
+            result.clone()
+        } else {
+            // named results must be transferred into the context of the current graph
+            let mut context_sets = LabelToSetMap::new();
+            for (name, set) in &self.named_results {
+                let bdd = self
+                    .graph
+                    .symbolic_context()
+                    .transfer_from(set.as_bdd(), &plain_context)
+                    .ok_or(format!("Result `{name}` is not compatible with the model."))?;
+                context_sets.insert(
+                    name.clone(),
+                    GraphColoredVertices::new(bdd, self.graph.symbolic_context()),
+                );
+            }
+            let (props_context, domains_context) =
+                validate_and_divide_wild_cards(&tree, &context_sets)?;
+
+            let mut eval_info = EvalContext::from_single_tree(&tree);
+            eval_info.extend_context_with_wild_cards(&props_context, &domains_context);
+            let result = self.evaluate_with_limits(tree, eval_info)?;
+            self.cache.insert(cache_key, result.clone());
+            result
+        };
+        Ok(sanitize_colored_vertices(&self.graph, &result))
+    }
+
+    /// Evaluate the tree on the current graph, cancelling the evaluation if any of the
+    /// resource limits is reached.
+    fn evaluate_with_limits(
+        &self,
+        tree: HctlTreeNode,
+        mut eval_info: EvalContext,
+    ) -> Result<GraphColoredVertices, String> {
+        if self.limits.timeout.is_none() && self.limits.memory_limit_mb.is_none() {
+            let result = eval_node(tree, &self.graph, &mut eval_info, &self.self_loop_states);
+            return match eval_info.take_error() {
+                Some(error) => Err(error),
+                None => Ok(result),
+            };
+        }
+
+        // the evaluation runs in a separate thread, while the limits are checked in this one
+        let start = Instant::now();
+        let cancellation = CancellationFlag::new();
+        eval_info.set_cancellation(cancellation.clone());
+        thread::scope(|scope| {
+            let handle = scope.spawn(move || {
+                let result = eval_node(tree, &self.graph, &mut eval_info, &self.self_loop_states);
+                (result, eval_info)
+            });
+            let mut limit_reached = None;
+            loop {
+                if limit_reached.is_none() {
+                    limit_reached = self.limits.check_exceeded(start);
+                    if limit_reached.is_some() {
+                        cancellation.cancel();
+                    }
+                }
+                if handle.is_finished() {
+                    break;
+                }
+                thread::sleep(LIMIT_CHECK_INTERVAL);
+            }
+            let (result, mut eval_info) = handle
+                .join()
+                .map_err(|_| "Evaluation thread panicked.".to_string())?;
+            // the results of a cancelled evaluation are not valid
+            match (limit_reached, eval_info.take_error()) {
+                (Some(message), _) | (None, Some(message)) => Err(message),
+                (None, None) => Ok(result),
+            }
+        })
+    }
+
+    /// Evaluate the (extended) formula, store its result under the given `name`, and return it.
+    /// If there already is a result with the same name, it is replaced.
+    pub fn evaluate_and_store(
+        &mut self,
+        name: &str,
+        formula: &str,
+    ) -> Result<GraphColoredVertices, String> {
+        let result = self.evaluate(formula)?;
+        // results of formulae referencing the replaced set are no longer valid
+        if self.named_results.contains_key(name) {
+            self.cache.clear();
+        }
+        self.named_results.insert(name.to_string(), result.clone());
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluation::eval_context::AnalysisLimits;
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use crate::session::ModelCheckingSession;
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use std::time::Duration;
+
+    #[test]
+    /// Test evaluating formulae in a session, including references to named results.
+    fn test_session_evaluation() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let mut session = ModelCheckingSession::new(bn.clone()).unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 3).unwrap();
+
+        // results are the same as with a standard model checking
+        let attractors = session
+            .evaluate_and_store("attr", "!{x}: AG EF {x}")
+            .unwrap();
+        let expected = model_check_formula("!{x}: AG EF {x}", &stg).unwrap();
+        assert_eq!(attractors.as_bdd(), expected.as_bdd());
+
+        // named results can be used as wild-cards and domains (graph is extended when needed)
+        let result = session.evaluate("EF %attr%").unwrap();
+        let expected = model_check_formula("EF (!{x}: AG EF {x})", &stg).unwrap();
+        assert_eq!(result.as_bdd(), expected.as_bdd());
+        let result = session
+            .evaluate("3{x} in %attr%: 3{y}: @{y}: AX {x}")
+            .unwrap();
+        let expected =
+            model_check_formula("3{x}: 3{y}: (@{x}: !{z}: AG EF {z}) & (@{y}: AX {x})", &stg)
+                .unwrap();
+        assert_eq!(result.as_bdd(), expected.as_bdd());
+
+        // repeated evaluation uses the cache
+        assert_eq!(
+            session.evaluate("EF %attr%").unwrap().as_bdd(),
+            session.evaluate("EF %attr%").unwrap().as_bdd()
+        );
+        assert!(session.evaluate("EF %unknown%").is_err());
+        assert_eq!(session.get_named_results().len(), 1);
+    }
+
+    #[test]
+    /// Test sharing the pre-computed steady states between sessions.
+    fn test_session_steady_states() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let session = ModelCheckingSession::new(bn.clone()).unwrap();
+        let steady_states = session.get_steady_states().clone();
+        assert_eq!(steady_states.approx_cardinality(), 2.0);
+
+        // the graph is extended while reusing the given steady states
+        let mut session =
+            ModelCheckingSession::with_steady_states(bn.clone(), steady_states).unwrap();
+        let stg = get_extended_symbolic_graph(&bn, 2).unwrap();
+        let formula = "!{x}: 3{y}: (@{y}: ~{x} & AX {y}) & AX {x}";
+        let expected = model_check_formula(formula, &stg).unwrap();
+        assert_eq!(
+            session.evaluate(formula).unwrap().as_bdd(),
+            expected.as_bdd()
+        );
+
+        // steady states must come from the same model
+        let other_bn = BooleanNetwork::try_from("a -> a\nb -> b\nc -> c").unwrap();
+        let other_session = ModelCheckingSession::new(other_bn).unwrap();
+        let other_states = other_session.get_steady_states().clone();
+        assert!(ModelCheckingSession::with_steady_states(bn, other_states).is_err());
+    }
+
+    #[test]
+    /// Test that the evaluation in a session respects the resource limits.
+    fn test_session_limits() {
+        let bn = BooleanNetwork::try_from("a -> b\nb -| a\nb -> b").unwrap();
+        let mut session = ModelCheckingSession::new(bn.clone()).unwrap();
+        let formula = "!{x}: AG EF {x}";
+
+        // the evaluation is cancelled when the time limit is reached
+        session.set_limits(AnalysisLimits {
+            timeout: Some(Duration::ZERO),
+            ..AnalysisLimits::default()
+        });
+        assert!(session.evaluate(formula).is_err());
+
+        // the cancelled result was not cached
+        session.set_limits(AnalysisLimits::default());
+        let stg = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let expected = model_check_formula(formula, &stg).unwrap();
+        assert_eq!(
+            session.evaluate(formula).unwrap().as_bdd(),
+            expected.as_bdd()
+        );
+    }
+}
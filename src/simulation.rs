@@ -0,0 +1,357 @@
+//! Statistical (simulation-based) checking of bounded properties, which can be used as a fallback
+//! for models too large for the symbolic model checking.
+//!
+//! Each sample consists of a color (picked uniformly from all valid colors), an initial state
+//! (picked uniformly from all states), and a random asynchronous trajectory of a given length
+//! (in each step, one of the variables that can change its value is updated). The temporal
+//! operators of the formula are then evaluated on the (finite) trajectory, as in bounded LTL.
+//! Since only a single run is considered, the path quantifiers are ignored (e.g., both `EF phi`
+//! and `AF phi` mean that `phi` eventually holds on the trajectory). All the next-state operators
+//! (including `EX_{v}` and `AX_{v}`) are false at the last position, which has no next state.
+//!
+//! The result is the estimated probability that a random run satisfies the property, together
+//! with its confidence interval.
+
+use crate::preprocessing::hctl_tree::{HctlTreeNode, NodeType};
+use crate::preprocessing::operator_enums::*;
+use crate::preprocessing::parser::parse_hctl_formula;
+
+use biodivine_lib_bdd::random_sampling::SamplingMethod;
+use biodivine_lib_bdd::BddPartialValuation;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::{BooleanNetwork, VariableId};
+use rand::prelude::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::collections::HashMap;
+
+/// Quantile of the standard normal distribution used for the 95% confidence intervals.
+const Z_95: f64 = 1.96;
+
+/// Settings of the statistical checking.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SimulationConfig {
+    /// Number of sampled (color, trajectory) pairs.
+    pub num_samples: usize,
+    /// Number of states of each trajectory (including the initial one).
+    pub trajectory_length: usize,
+    /// Seed of the random number generator (the results are reproducible for the same seed).
+    pub seed: u64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            num_samples: 1000,
+            trajectory_length: 100,
+            seed: 0,
+        }
+    }
+}
+
+/// Estimated probability of satisfying a property, with its 95% confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimulationEstimate {
+    pub num_samples: usize,
+    pub num_successes: usize,
+    pub estimate: f64,
+    pub lower_bound: f64,
+    pub upper_bound: f64,
+}
+
+impl SimulationEstimate {
+    /// Compute the estimate from the numbers of samples and successes, using the Wilson score
+    /// interval (which behaves well even for estimates close to 0 or 1).
+    pub fn new(num_samples: usize, num_successes: usize) -> SimulationEstimate {
+        let n = num_samples as f64;
+        let estimate = if num_samples == 0 {
+            0.0
+        } else {
+            num_successes as f64 / n
+        };
+        let (lower_bound, upper_bound) = if num_samples == 0 {
+            (0.0, 1.0)
+        } else {
+            let z2 = Z_95 * Z_95;
+            let center = (estimate + z2 / (2.0 * n)) / (1.0 + z2 / n);
+            let radius = Z_95 / (1.0 + z2 / n)
+                * (estimate * (1.0 - estimate) / n + z2 / (4.0 * n * n)).sqrt();
+            ((center - radius).max(0.0), (center + radius).min(1.0))
+        };
+        SimulationEstimate {
+            num_samples,
+            num_successes,
+            estimate,
+            lower_bound,
+            upper_bound,
+        }
+    }
+}
+
+/// Pick a uniformly random color of the `graph`, and return the corresponding fully specified
+/// network.
+fn sample_network<S: SamplingMethod>(
+    graph: &SymbolicAsyncGraph,
+    sampler: &mut S,
+) -> BooleanNetwork {
+    let context = graph.symbolic_context();
+    let valuation = graph
+        .mk_unit_colors()
+        .as_bdd()
+        .random_valuation_sample(sampler)
+        .unwrap();
+    let mut clause = BddPartialValuation::empty();
+    for var in context.parameter_variables() {
+        clause.set_value(*var, valuation.value(*var));
+    }
+    let color = context.bdd_variable_set().mk_conjunctive_clause(&clause);
+    graph.pick_witness(&GraphColors::new(color, context))
+}
+
+/// Generate a random asynchronous trajectory with `length` states in the fully specified `bn`,
+/// starting in a random state. If a steady state is reached, it is repeated until the end.
+fn simulate_trajectory<R: Rng>(bn: &BooleanNetwork, rng: &mut R, length: usize) -> Vec<Vec<bool>> {
+    let variables: Vec<VariableId> = bn.variables().collect();
+    let mut state: Vec<bool> = variables.iter().map(|_| rng.gen_bool(0.5)).collect();
+    let mut trajectory = Vec::with_capacity(length);
+    while trajectory.len() < length {
+        trajectory.push(state.clone());
+        let values: HashMap<VariableId, bool> = variables
+            .iter()
+            .map(|v| (*v, state[v.to_index()]))
+            .collect();
+        // variables whose update function differs from their current value
+        let enabled: Vec<VariableId> = variables
+            .iter()
+            .filter(|v| {
+                let function = bn.get_update_function(**v).as_ref().unwrap();
+                function.evaluate(&values) != Some(state[v.to_index()])
+            })
+            .copied()
+            .collect();
+        if !enabled.is_empty() {
+            let var = enabled[rng.gen_range(0..enabled.len())];
+            state[var.to_index()] = !state[var.to_index()];
+        }
+    }
+    trajectory
+}
+
+/// Evaluate the sub-formula represented by a `node` on every position of the finite
+/// `trajectory`. Hybrid operators, HCTL variables and wild-card propositions are not supported.
+pub(crate) fn eval_on_trajectory(
+    node: &HctlTreeNode,
+    bn: &BooleanNetwork,
+    trajectory: &[Vec<bool>],
+) -> Result<Vec<bool>, String> {
+    let n = trajectory.len();
+    // evaluate `step(current, next)` backwards from the end of the trajectory
+    let backwards = |last: Vec<bool>, step: &dyn Fn(usize, bool) -> bool| {
+        let mut values = last;
+        for i in (0..n.saturating_sub(1)).rev() {
+            values[i] = step(i, values[i + 1]);
+        }
+        values
+    };
+    let result = match &node.node_type {
+        NodeType::Terminal(atom) => match atom {
+            Atomic::True => vec![true; n],
+            Atomic::False => vec![false; n],
+            Atomic::Prop(name) => {
+                let var = bn
+                    .as_graph()
+                    .find_variable(name)
+                    .ok_or(format!("There is no network variable `{name}`."))?;
+                trajectory.iter().map(|s| s[var.to_index()]).collect()
+            }
+            Atomic::State(values, _) => {
+                let mut literals = Vec::new();
+                for (name, value) in values {
+                    let var = bn
+                        .as_graph()
+                        .find_variable(name)
+                        .ok_or(format!("There is no network variable `{name}`."))?;
+                    literals.push((var.to_index(), *value));
+                }
+                trajectory
+                    .iter()
+                    .map(|s| literals.iter().all(|(i, value)| s[*i] == *value))
+                    .collect()
+            }
+            _ => {
+                return Err(format!(
+                    "Atomic `{atom}` is not supported in the statistical checking."
+                ))
+            }
+        },
+        NodeType::Unary(op, child) => {
+            let c = eval_on_trajectory(child, bn, trajectory)?;
+            match op {
+                UnaryOp::Not => c.iter().map(|v| !v).collect(),
+                UnaryOp::EX | UnaryOp::AX => (0..n).map(|i| i + 1 < n && c[i + 1]).collect(),
+                // the next step of the trajectory either updates the variable or not (as with
+                // `EX` and `AX`, there is no next step at the last position)
+                UnaryOp::EXVar(name) | UnaryOp::AXVar(name) => {
+                    let var = bn
+                        .as_graph()
+                        .find_variable(name)
+                        .ok_or(format!("There is no network variable `{name}`."))?
+                        .to_index();
+                    let updates =
+                        |i: usize| i + 1 < n && trajectory[i][var] != trajectory[i + 1][var];
+                    if let UnaryOp::EXVar(_) = op {
+                        (0..n).map(|i| updates(i) && c[i + 1]).collect()
+                    } else {
+                        (0..n)
+                            .map(|i| i + 1 < n && (!updates(i) || c[i + 1]))
+                            .collect()
+                    }
+                }
+                UnaryOp::EF | UnaryOp::AF => backwards(c.clone(), &|i, next| c[i] || next),
+                UnaryOp::EG | UnaryOp::AG => backwards(c.clone(), &|i, next| c[i] && next),
+                // the past of the position is the prefix of the trajectory
+                UnaryOp::EP => {
+                    let mut seen = false;
+                    c.iter()
+                        .map(|v| {
+                            seen |= *v;
+                            seen
+                        })
+                        .collect()
+                }
+            }
+        }
+        NodeType::Binary(op, left, right) => {
+            let l = eval_on_trajectory(left, bn, trajectory)?;
+            let r = eval_on_trajectory(right, bn, trajectory)?;
+            let pointwise = |f: fn(bool, bool) -> bool| (0..n).map(|i| f(l[i], r[i])).collect();
+            match op {
+                BinaryOp::And => pointwise(|a, b| a && b),
+                BinaryOp::Or => pointwise(|a, b| a || b),
+                BinaryOp::Imp => pointwise(|a, b| !a || b),
+                BinaryOp::Iff => pointwise(|a, b| a == b),
+                BinaryOp::Xor => pointwise(|a, b| a != b),
+                BinaryOp::EU | BinaryOp::AU => {
+                    backwards(r.clone(), &|i, next| r[i] || (l[i] && next))
+                }
+                // weak until also holds if the left operand holds until the end of the trajectory
+                BinaryOp::EW | BinaryOp::AW => {
+                    let last = (0..n).map(|i| r[i] || l[i]).collect();
+                    backwards(last, &|i, next| r[i] || (l[i] && next))
+                }
+            }
+        }
+        NodeType::Hybrid(..) => {
+            return Err(
+                "Hybrid operators are not supported in the statistical checking.".to_string(),
+            )
+        }
+    };
+    Ok(result)
+}
+
+/// Estimate the probability that a random run of the network (with a random color and a random
+/// initial state) satisfies the bounded property given by `formula`.
+///
+/// The `graph` is only used to sample the colors, so it does not need any extra symbolic
+/// variables. See the module documentation for the semantics of the formula on the runs.
+pub fn estimate_formula_satisfaction(
+    formula: &str,
+    graph: &SymbolicAsyncGraph,
+    config: &SimulationConfig,
+) -> Result<SimulationEstimate, String> {
+    let tree = parse_hctl_formula(formula)?;
+    if graph.mk_unit_colors().is_empty() {
+        return Err("The model has no valid colors.".to_string());
+    }
+    if config.trajectory_length == 0 {
+        return Err("Trajectories must contain at least one state.".to_string());
+    }
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut sampler = graph
+        .mk_unit_colors()
+        .as_bdd()
+        .mk_uniform_valuation_sampler(StdRng::seed_from_u64(rng.next_u64()));
+    let mut num_successes = 0;
+    for _ in 0..config.num_samples {
+        let bn = sample_network(graph, &mut sampler);
+        let trajectory = simulate_trajectory(&bn, &mut rng, config.trajectory_length);
+        if eval_on_trajectory(&tree, &bn, &trajectory)?[0] {
+            num_successes += 1;
+        }
+    }
+    Ok(SimulationEstimate::new(config.num_samples, num_successes))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preprocessing::parser::parse_hctl_formula;
+    use crate::simulation::{
+        estimate_formula_satisfaction, eval_on_trajectory, SimulationConfig, SimulationEstimate,
+    };
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test estimating the satisfaction of bounded properties on random runs.
+    fn test_statistical_checking() {
+        let config = SimulationConfig {
+            num_samples: 1000,
+            trajectory_length: 10,
+            seed: 42,
+        };
+
+        // `a` oscillates, so it is eventually active (and changes in each step)
+        let bn = BooleanNetwork::try_from("a -| a\n$a: !a").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let estimate = estimate_formula_satisfaction("EF a", &graph, &config).unwrap();
+        assert_eq!(estimate.estimate, 1.0);
+        assert!(estimate.lower_bound > 0.99);
+        let estimate = estimate_formula_satisfaction("EF (a & EX a)", &graph, &config);
+        assert_eq!(estimate.unwrap().num_successes, 0);
+
+        // `a` either keeps its value or oscillates (depending on the color), which is
+        // distinguished by `a => EX a` for the half of the runs starting with active `a`
+        let bn = BooleanNetwork::try_from("a -? a").unwrap();
+        let graph = SymbolicAsyncGraph::new(&bn).unwrap();
+        let estimate = estimate_formula_satisfaction("a => EX a", &graph, &config).unwrap();
+        assert!(estimate.lower_bound < 0.75 && 0.75 < estimate.upper_bound);
+
+        // hybrid operators are not supported
+        assert!(estimate_formula_satisfaction("!{x}: AX {x}", &graph, &config).is_err());
+    }
+
+    #[test]
+    /// Test that the next-state operators are consistently false at the end of the trajectory.
+    fn test_next_state_operators_on_trajectory() {
+        let bn = BooleanNetwork::try_from("a -> b\n$a: true\n$b: a").unwrap();
+        // `b` is updated in the first step, `a` never
+        let trajectory = vec![vec![true, false], vec![true, true]];
+        let expected = [
+            ("AX b", vec![true, false]),
+            ("EX b", vec![true, false]),
+            ("AX_{b} b", vec![true, false]),
+            ("EX_{b} b", vec![true, false]),
+            ("AX_{a} b", vec![true, false]),
+            ("EX_{a} b", vec![false, false]),
+        ];
+        for (formula, values) in expected {
+            let tree = parse_hctl_formula(formula).unwrap();
+            assert_eq!(eval_on_trajectory(&tree, &bn, &trajectory).unwrap(), values);
+        }
+    }
+
+    #[test]
+    /// Test the confidence intervals of the estimates.
+    fn test_confidence_intervals() {
+        let estimate = SimulationEstimate::new(100, 50);
+        assert_eq!(estimate.estimate, 0.5);
+        assert!((estimate.lower_bound - 0.404).abs() < 0.001);
+        assert!((estimate.upper_bound - 0.596).abs() < 0.001);
+
+        let estimate = SimulationEstimate::new(100, 0);
+        assert_eq!(estimate.lower_bound, 0.0);
+        assert!(estimate.upper_bound > 0.0 && estimate.upper_bound < 0.05);
+    }
+}
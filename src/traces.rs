@@ -0,0 +1,314 @@
+//! Runtime verification of explicit traces, such as the runs of stochastic simulations.
+//!
+//! A trace is a finite sequence of states of the network. It is given by a CSV table, where the
+//! header lists the network variables and each row gives their values (`0` or `1`) in one state
+//! of the trace. Lines starting with `#` are ignored, as well as an (optional) first column named
+//! `time` or `step`. With the `fs` feature, traces can also be given in JSON, as a list of
+//! objects mapping the variables to their values (see [parse_trace_json]).
+//!
+//! The formulae can be checked on the trace itself (see [check_formula_on_trace]), with the same
+//! semantics as in the statistical checking (see [crate::simulation]), i.e., the temporal
+//! operators are evaluated on the finite trace and the path quantifiers are ignored. Or, the
+//! trace can be compared with the results of the symbolic model checking, by finding the colors
+//! for which all its states are contained in a result set (see [trace_colors_in_set]).
+
+use crate::postprocessing::sanitizing::{extend_to_graph, sanitize_colors};
+use crate::preprocessing::parser::parse_hctl_formula;
+use crate::simulation::eval_on_trajectory;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColoredVertices, GraphColors, SymbolicAsyncGraph,
+};
+use biodivine_lib_param_bn::{BooleanNetwork, VariableId};
+
+/// Names of the (optional) first column with the time points of the trace.
+const TIME_COLUMNS: [&str; 2] = ["time", "step"];
+
+/// Finite sequence of (fully specified) states of a network.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Trace {
+    /// Names of the variables, in the order of the values in the states.
+    pub variables: Vec<String>,
+    /// Values of the variables in each state of the trace.
+    pub states: Vec<Vec<bool>>,
+}
+
+impl Trace {
+    /// Reorder the values of each state to follow the variables of the network (as expected by
+    /// the trajectories in [crate::simulation]). All network variables must be in the trace.
+    fn to_network_order(&self, bn: &BooleanNetwork) -> Result<Vec<Vec<bool>>, String> {
+        let indices = self.network_indices(bn.variables().map(|v| bn.get_variable_name(v)))?;
+        Ok(self
+            .states
+            .iter()
+            .map(|state| indices.iter().map(|i| state[*i]).collect())
+            .collect())
+    }
+
+    /// Find the position of each of the given variables in the trace. The trace must contain
+    /// exactly these variables.
+    fn network_indices<'a>(
+        &self,
+        names: impl Iterator<Item = &'a String>,
+    ) -> Result<Vec<usize>, String> {
+        let mut indices = Vec::new();
+        for name in names {
+            let index = self
+                .variables
+                .iter()
+                .position(|v| v == name)
+                .ok_or(format!("Network variable `{name}` is not in the trace."))?;
+            indices.push(index);
+        }
+        if indices.len() != self.variables.len() {
+            return Err("The trace contains variables that are not in the network.".to_string());
+        }
+        Ok(indices)
+    }
+}
+
+/// Parse the trace from the CSV table (see the module documentation).
+pub fn parse_trace_csv(table: &str) -> Result<Trace, String> {
+    let mut lines = table
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or("Trace table is empty.".to_string())?
+        .split(',')
+        .map(|cell| cell.trim())
+        .collect();
+    let has_time = TIME_COLUMNS
+        .iter()
+        .any(|name| header[0].eq_ignore_ascii_case(name));
+    let skipped = if has_time { 1 } else { 0 };
+
+    let mut states = Vec::new();
+    for line in lines {
+        let cells: Vec<&str> = line.split(',').map(|cell| cell.trim()).collect();
+        if cells.len() != header.len() {
+            return Err(format!(
+                "State `{line}` has {} values, but {} are expected.",
+                cells.len(),
+                header.len()
+            ));
+        }
+        let mut state = Vec::new();
+        for value in &cells[skipped..] {
+            match *value {
+                "0" => state.push(false),
+                "1" => state.push(true),
+                _ => return Err(format!("Invalid value `{value}` in the trace.")),
+            }
+        }
+        states.push(state);
+    }
+    Ok(Trace {
+        variables: header[skipped..].iter().map(|v| v.to_string()).collect(),
+        states,
+    })
+}
+
+/// Parse the trace from JSON, given as a list of states, each being an object that maps the
+/// variables to their values (`0`/`1`, or `false`/`true`). All states must list the same
+/// variables.
+#[cfg(feature = "fs")]
+pub fn parse_trace_json(json: &str) -> Result<Trace, String> {
+    use serde_json::Value;
+
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let items = value
+        .as_array()
+        .ok_or("Trace must be a list of states.".to_string())?;
+    let mut variables: Vec<String> = Vec::new();
+    let mut states = Vec::new();
+    for (i, item) in items.iter().enumerate() {
+        let object = item
+            .as_object()
+            .ok_or(format!("State {} of the trace is not an object.", i + 1))?;
+        if i == 0 {
+            variables = object.keys().cloned().collect();
+        }
+        if object.len() != variables.len() {
+            return Err(format!(
+                "State {} of the trace has {} values, but {} are expected.",
+                i + 1,
+                object.len(),
+                variables.len()
+            ));
+        }
+        let mut state = Vec::new();
+        for name in &variables {
+            let value = match object.get(name) {
+                Some(Value::Bool(value)) => *value,
+                Some(Value::Number(n)) if n.as_u64() == Some(0) => false,
+                Some(Value::Number(n)) if n.as_u64() == Some(1) => true,
+                Some(value) => return Err(format!("Invalid value `{value}` in the trace.")),
+                None => {
+                    return Err(format!(
+                        "State {} of the trace misses variable `{name}`.",
+                        i + 1
+                    ))
+                }
+            };
+            state.push(value);
+        }
+        states.push(state);
+    }
+    Ok(Trace { variables, states })
+}
+
+/// Read the trace from the file, in JSON if its extension is `json`, or in CSV otherwise.
+#[cfg(feature = "fs")]
+pub fn load_trace(trace_path: &str) -> Result<Trace, String> {
+    let content = std::fs::read_to_string(trace_path).map_err(|e| e.to_string())?;
+    if trace_path.ends_with(".json") {
+        parse_trace_json(&content)
+    } else {
+        parse_trace_csv(&content)
+    }
+}
+
+/// Evaluate the (bounded) `formula` on every position of the `trace` of the network `bn`, with
+/// the temporal operators restricted to the trace (see the module documentation). The formula
+/// holds on the whole trace if it holds at its first position.
+///
+/// Hybrid operators, HCTL variables and wild-card propositions are not supported.
+pub fn check_formula_on_trace(
+    formula: &str,
+    bn: &BooleanNetwork,
+    trace: &Trace,
+) -> Result<Vec<bool>, String> {
+    let tree = parse_hctl_formula(formula)?;
+    let trajectory = trace.to_network_order(bn)?;
+    eval_on_trajectory(&tree, bn, &trajectory)
+}
+
+/// Convert the states of the `trace` into the singleton sets of the `graph` (with all colors).
+fn trace_to_sets(
+    graph: &SymbolicAsyncGraph,
+    trace: &Trace,
+) -> Result<Vec<GraphColoredVertices>, String> {
+    let context = graph.symbolic_context();
+    let variables: Vec<VariableId> = graph.variables().collect();
+    let names: Vec<String> = variables
+        .iter()
+        .map(|v| context.get_network_variable_name(*v))
+        .collect();
+    let indices = trace.network_indices(names.iter())?;
+    Ok(trace
+        .states
+        .iter()
+        .map(|state| {
+            let values: Vec<(VariableId, bool)> = variables
+                .iter()
+                .zip(&indices)
+                .map(|(var, i)| (*var, state[*i]))
+                .collect();
+            graph.mk_subspace(&values)
+        })
+        .collect())
+}
+
+/// Compute the colors for which all states of the `trace` are contained in the `set` (e.g., in
+/// the result of the model checking of a formula on the `graph` that the trace is expected to
+/// satisfy). Like the model checking results, the colors are sanitized.
+pub fn trace_colors_in_set(
+    graph: &SymbolicAsyncGraph,
+    trace: &Trace,
+    set: &GraphColoredVertices,
+) -> Result<GraphColors, String> {
+    let set = extend_to_graph(graph, set)?;
+    let mut colors = graph.mk_unit_colors();
+    for state in trace_to_sets(graph, trace)? {
+        colors = colors.intersect(&state.intersect(&set).colors());
+    }
+    Ok(sanitize_colors(graph, &colors))
+}
+
+/// Find the positions of the states of the `trace` that are not contained in the `set` (for any
+/// color), i.e., the points where the trace violates a symbolic result.
+pub fn find_trace_states_outside(
+    graph: &SymbolicAsyncGraph,
+    trace: &Trace,
+    set: &GraphColoredVertices,
+) -> Result<Vec<usize>, String> {
+    let set = extend_to_graph(graph, set)?;
+    Ok(trace_to_sets(graph, trace)?
+        .iter()
+        .enumerate()
+        .filter(|(_, state)| state.intersect(&set).is_empty())
+        .map(|(i, _)| i)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mc_utils::get_extended_symbolic_graph;
+    use crate::model_checking::model_check_formula;
+    use crate::traces::{
+        check_formula_on_trace, find_trace_states_outside, parse_trace_csv, trace_colors_in_set,
+    };
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test parsing traces, and checking them against formulae and symbolic results.
+    fn test_trace_checking() {
+        // `b` follows `a`, and `a` is given by an (observable) implicit function with two colors
+        let bn = BooleanNetwork::try_from("a -> b\nb -? a\n$b: a").unwrap();
+        let trace = parse_trace_csv("# comment\ntime,b,a\n0,0,0\n1,0,1\n2,1,1\n").unwrap();
+        assert_eq!(trace.variables, vec!["b".to_string(), "a".to_string()]);
+        assert_eq!(trace.states.len(), 3);
+
+        let result = check_formula_on_trace("EF (a & b) & ~b", &bn, &trace).unwrap();
+        assert_eq!(result, vec![true, true, false]);
+        let result = check_formula_on_trace("AG (b => a)", &bn, &trace).unwrap();
+        assert_eq!(result, vec![true, true, true]);
+        assert!(check_formula_on_trace("!{x}: AX {x}", &bn, &trace).is_err());
+
+        // all states of the trace satisfy `b => a`, but the second one is not a fixed point
+        let graph = get_extended_symbolic_graph(&bn, 1).unwrap();
+        let set = model_check_formula("b => a", &graph).unwrap();
+        let colors = trace_colors_in_set(&graph, &trace, &set).unwrap();
+        assert_eq!(colors.approx_cardinality(), 2.0);
+        assert!(find_trace_states_outside(&graph, &trace, &set)
+            .unwrap()
+            .is_empty());
+        let set = model_check_formula("!{x}: AX {x}", &graph).unwrap();
+        let colors = trace_colors_in_set(&graph, &trace, &set).unwrap();
+        assert_eq!(colors.approx_cardinality(), 0.0);
+        assert_eq!(
+            find_trace_states_outside(&graph, &trace, &set).unwrap(),
+            vec![1]
+        );
+
+        // traces must cover exactly the network variables
+        let invalid_traces = vec!["a\n1", "a,b,c\n0,1,0", "a,b\n0,2", "a,b\n0"];
+        for invalid in invalid_traces {
+            let result =
+                parse_trace_csv(invalid).and_then(|trace| check_formula_on_trace("a", &bn, &trace));
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    /// Test parsing traces given in JSON.
+    fn test_trace_json() {
+        use crate::traces::parse_trace_json;
+
+        let json = r#"[{"a": 0, "b": 0}, {"a": 1, "b": false}, {"a": true, "b": 1}]"#;
+        let trace = parse_trace_json(json).unwrap();
+        assert_eq!(trace.variables, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(
+            trace.states,
+            vec![vec![false; 2], vec![true, false], vec![true; 2]]
+        );
+
+        assert!(parse_trace_json(r#"{"a": 0}"#).is_err());
+        assert!(parse_trace_json(r#"[{"a": 0}, {"b": 1}]"#).is_err());
+        assert!(parse_trace_json(r#"[{"a": 2}]"#).is_err());
+    }
+}
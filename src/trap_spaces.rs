@@ -0,0 +1,195 @@
+//! Enumeration of the minimal and maximal trap spaces of (partially specified) Boolean networks.
+//!
+//! A trap space is a subspace (given by the values of its fixed variables) that can not be left.
+//! The minimal trap spaces contain at least one attractor each, and they are often used as
+//! human-readable approximations of the attractors. The maximal (non-trivial) trap spaces are the
+//! first "commitments" of the dynamics, i.e., the largest subspaces in which a run can get stuck.
+//!
+//! The trap spaces are computed symbolically (for all colors at once), and then decoded into
+//! explicit subspaces for groups of colors sharing the same trap spaces.
+
+use crate::postprocessing::sanitizing::sanitize_colors;
+use crate::preprocessing::operator_enums::Atomic;
+
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
+use biodivine_lib_param_bn::trap_spaces::{
+    NetworkColoredSpaces, NetworkSpaces, SymbolicSpaceContext, TrapSpaces,
+};
+use biodivine_lib_param_bn::{BooleanNetwork, Space};
+use std::fmt;
+
+/// A subspace of the network, given by the values of its fixed variables (the remaining
+/// variables are free).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DecodedSpace {
+    /// Names and values of the fixed variables (in the order of the network variables).
+    pub fixed: Vec<(String, bool)>,
+    /// Number of the free variables.
+    pub num_free: usize,
+}
+
+impl DecodedSpace {
+    /// Decode the `space` of the network `bn`.
+    fn from_space(bn: &BooleanNetwork, space: &Space) -> DecodedSpace {
+        let fixed: Vec<(String, bool)> = space
+            .to_values()
+            .into_iter()
+            .map(|(var, value)| (bn.get_variable_name(var).clone(), value))
+            .collect();
+        DecodedSpace {
+            num_free: bn.num_vars() - fixed.len(),
+            fixed,
+        }
+    }
+
+    /// Get the state literal of the subspace (e.g., `#state{a=1, c=0, *}`), which can be
+    /// directly used in formulae.
+    pub fn to_state_literal(&self) -> String {
+        Atomic::State(self.fixed.clone(), self.num_free > 0).to_string()
+    }
+}
+
+impl fmt::Display for DecodedSpace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_state_literal())
+    }
+}
+
+/// Trap spaces shared by a group of colors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorTrapSpaces {
+    /// The colors of the group (in the canonical symbolic context of the network).
+    pub colors: GraphColors,
+    /// The trap spaces of all colors of the group (sorted).
+    pub spaces: Vec<DecodedSpace>,
+}
+
+/// Enumerate the minimal trap spaces of the network `bn`, for the groups of colors that share
+/// the same minimal trap spaces.
+///
+/// Note that the number of groups can grow with the number of colors.
+pub fn minimal_trap_spaces(bn: &BooleanNetwork) -> Result<Vec<ColorTrapSpaces>, String> {
+    let ctx = SymbolicSpaceContext::new(bn);
+    let graph = SymbolicAsyncGraph::with_space_context(bn, &ctx)?;
+    let unit = ctx.mk_unit_colored_spaces(&graph);
+    let minimal = TrapSpaces::minimal_symbolic(&ctx, &graph, &unit, None);
+    Ok(group_by_colors(bn, &ctx, &graph, &minimal))
+}
+
+/// Enumerate the maximal non-trivial trap spaces (i.e., excluding the whole state space) of the
+/// network `bn`, for the groups of colors that share the same maximal trap spaces. The colors
+/// without any non-trivial trap space form a group with no spaces.
+///
+/// Note that the number of groups can grow with the number of colors.
+pub fn maximal_trap_spaces(bn: &BooleanNetwork) -> Result<Vec<ColorTrapSpaces>, String> {
+    let ctx = SymbolicSpaceContext::new(bn);
+    let graph = SymbolicAsyncGraph::with_space_context(bn, &ctx)?;
+    let whole_space = NetworkSpaces::new(ctx.mk_space(&Space::new(bn)), &ctx);
+    let non_trivial = compute_trap_spaces(&ctx, &graph).minus_spaces(&whole_space);
+    let maximal = TrapSpaces::maximize(&ctx, &non_trivial);
+    Ok(group_by_colors(bn, &ctx, &graph, &maximal))
+}
+
+/// Compute all trap spaces of the network (for all colors) symbolically. A space is a trap space
+/// if each of its fixed variables can not be updated to the other value inside the space.
+fn compute_trap_spaces(
+    ctx: &SymbolicSpaceContext,
+    graph: &SymbolicAsyncGraph,
+) -> NetworkColoredSpaces {
+    let bdd_ctx = ctx.bdd_variable_set();
+    let mut trap_spaces = ctx.mk_unit_colored_spaces(graph).into_bdd();
+    for var in graph.variables() {
+        let update_bdd = graph.get_symbolic_fn_update(var);
+        let can_go_up = ctx.mk_can_go_to_true(update_bdd);
+        let can_go_down = ctx.mk_can_go_to_true(&update_bdd.not());
+        // the variable must be able to take all values reachable by its update
+        let is_trap_up = can_go_up.imp(&bdd_ctx.mk_var(ctx.get_positive_variable(var)));
+        let is_trap_down = can_go_down.imp(&bdd_ctx.mk_var(ctx.get_negative_variable(var)));
+        trap_spaces = trap_spaces.and(&is_trap_up).and(&is_trap_down);
+    }
+    NetworkColoredSpaces::new(trap_spaces, ctx)
+}
+
+/// Decode the colored `spaces` into the groups of colors with the same sets of spaces.
+fn group_by_colors(
+    bn: &BooleanNetwork,
+    ctx: &SymbolicSpaceContext,
+    graph: &SymbolicAsyncGraph,
+    spaces: &NetworkColoredSpaces,
+) -> Vec<ColorTrapSpaces> {
+    let unit = ctx.mk_unit_colored_spaces(graph);
+    let mut groups = Vec::new();
+    let mut remaining = graph.mk_unit_colors();
+    while !remaining.is_empty() {
+        let color = remaining.pick_singleton();
+        let color_spaces = spaces.intersect_colors(&color).spaces();
+        // colors with some other space, or missing some of the spaces, belong to other groups
+        let other_colors = spaces
+            .minus_spaces(&color_spaces)
+            .colors()
+            .union(&unit.intersect_spaces(&color_spaces).minus(spaces).colors());
+        let group_colors = remaining.minus(&other_colors);
+        remaining = remaining.minus(&group_colors);
+
+        let mut decoded: Vec<DecodedSpace> = color_spaces
+            .iter()
+            .map(|space| DecodedSpace::from_space(bn, &space))
+            .collect();
+        decoded.sort();
+        groups.push(ColorTrapSpaces {
+            colors: sanitize_colors(graph, &group_colors),
+            spaces: decoded,
+        });
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::trap_spaces::{maximal_trap_spaces, minimal_trap_spaces};
+    use biodivine_lib_param_bn::BooleanNetwork;
+
+    #[test]
+    /// Test the enumeration of the minimal and maximal trap spaces of a fully specified network.
+    fn test_trap_spaces() {
+        // `a` and `b` form a bistable switch, `c` oscillates
+        let bn =
+            BooleanNetwork::try_from("a -| b\nb -| a\nc -| c\n$a: !b\n$b: !a\n$c: !c").unwrap();
+
+        let minimal = minimal_trap_spaces(&bn).unwrap();
+        assert_eq!(minimal.len(), 1);
+        let spaces: Vec<String> = minimal[0].spaces.iter().map(|s| s.to_string()).collect();
+        assert_eq!(spaces, vec!["#state{a=0, b=1, *}", "#state{a=1, b=0, *}"]);
+        assert_eq!(minimal[0].spaces[0].num_free, 1);
+
+        // the minimal trap spaces are also maximal here
+        let maximal = maximal_trap_spaces(&bn).unwrap();
+        assert_eq!(maximal.len(), 1);
+        assert_eq!(maximal[0].spaces, minimal[0].spaces);
+    }
+
+    #[test]
+    /// Test the grouping of colors by their trap spaces.
+    fn test_trap_spaces_per_color() {
+        // the update of `a` is unknown: either a constant, a copy of `b`, or its negation
+        let bn = BooleanNetwork::try_from("b -?? a\n$b: true").unwrap();
+        let groups = minimal_trap_spaces(&bn).unwrap();
+        let total: f64 = groups.iter().map(|g| g.colors.approx_cardinality()).sum();
+        assert_eq!(total, 4.0);
+        // each color has a single fixed point (with `b` true)
+        for group in &groups {
+            assert_eq!(group.spaces.len(), 1);
+            assert_eq!(group.spaces[0].num_free, 0);
+            assert!(group.spaces[0].fixed.contains(&("b".to_string(), true)));
+        }
+        // colors where `a` is true and where it is false
+        assert_eq!(groups.len(), 2);
+
+        // the whole space is not a maximal non-trivial trap space, but `b=1` is
+        let groups = maximal_trap_spaces(&bn).unwrap();
+        assert!(groups
+            .iter()
+            .all(|g| g.spaces.iter().any(|s| s.to_string() == "#state{b=1, *}")));
+    }
+}
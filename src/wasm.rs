@@ -0,0 +1,181 @@
+//! JS-friendly entry points for WebAssembly builds (through `wasm-bindgen`), so that the model
+//! checker can run inside browser front-ends. Only available with the `wasm` feature.
+//!
+//! There is no filesystem access, models (in aeon format) and formulae are given as strings,
+//! and only the summaries of the results are returned.
+
+use crate::mc_utils::{collect_unique_hctl_vars, get_extended_symbolic_graph};
+use crate::model_checking::model_check_tree;
+use crate::preprocessing::analysis;
+use crate::preprocessing::parser::{parse_and_minimize_hctl_formula, parse_hctl_formula};
+
+use biodivine_lib_param_bn::symbolic_async_graph::{SymbolicAsyncGraph, SymbolicContext};
+use biodivine_lib_param_bn::BooleanNetwork;
+use wasm_bindgen::prelude::*;
+
+/// Summary of the model-checking result (numbers of satisfying states/colors).
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResultSummary {
+    num_results: f64,
+    num_colors: f64,
+    num_states: f64,
+}
+
+#[wasm_bindgen]
+impl ResultSummary {
+    /// Number of satisfying (state, color) pairs.
+    #[wasm_bindgen(getter)]
+    pub fn num_results(&self) -> f64 {
+        self.num_results
+    }
+
+    /// Number of colors with at least one satisfying state.
+    #[wasm_bindgen(getter)]
+    pub fn num_colors(&self) -> f64 {
+        self.num_colors
+    }
+
+    /// Number of states satisfying the formula for at least one color.
+    #[wasm_bindgen(getter)]
+    pub fn num_states(&self) -> f64 {
+        self.num_states
+    }
+}
+
+/// Summary of a BN model (numbers of variables, parameters, states, and colors).
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelSummary {
+    num_vars: usize,
+    num_parameters: usize,
+    num_states: f64,
+    num_colors: f64,
+}
+
+#[wasm_bindgen]
+impl ModelSummary {
+    /// Number of network variables.
+    #[wasm_bindgen(getter)]
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Number of symbolic variables encoding the parameters (explicit and implicit).
+    #[wasm_bindgen(getter)]
+    pub fn num_parameters(&self) -> usize {
+        self.num_parameters
+    }
+
+    /// Number of states of the state-transition graph.
+    #[wasm_bindgen(getter)]
+    pub fn num_states(&self) -> f64 {
+        self.num_states
+    }
+
+    /// Number of colors (valid parametrizations).
+    #[wasm_bindgen(getter)]
+    pub fn num_colors(&self) -> f64 {
+        self.num_colors
+    }
+}
+
+/// Parse the HCTL formula and return its canonical string representation.
+#[wasm_bindgen]
+pub fn parse_formula(formula: &str) -> Result<String, JsError> {
+    parse_hctl_formula(formula)
+        .map(|tree| tree.to_string())
+        .map_err(|e| JsError::new(e.as_str()))
+}
+
+/// Model-check the HCTL formula on the BN model (in aeon format) and summarize the result.
+#[wasm_bindgen]
+pub fn check_formula(aeon_model: &str, formula: &str) -> Result<ResultSummary, JsError> {
+    check_formula_internal(aeon_model, formula).map_err(|e| JsError::new(e.as_str()))
+}
+
+/// Summarize the BN model (in aeon format).
+#[wasm_bindgen]
+pub fn model_summary(aeon_model: &str) -> Result<ModelSummary, JsError> {
+    model_summary_internal(aeon_model).map_err(|e| JsError::new(e.as_str()))
+}
+
+/// Validate the HCTL formula against the BN model (in aeon format), without building the
+/// transition graph, and return the descriptions of all the problems found (empty if valid).
+#[wasm_bindgen]
+pub fn validate_formula(aeon_model: &str, formula: &str) -> Result<Vec<String>, JsError> {
+    validate_formula_internal(aeon_model, formula).map_err(|e| JsError::new(e.as_str()))
+}
+
+/// Internal version of [check_formula] (errors can not be converted to JS outside of wasm).
+fn check_formula_internal(aeon_model: &str, formula: &str) -> Result<ResultSummary, String> {
+    let bn = BooleanNetwork::try_from(aeon_model)?;
+    let tree = parse_and_minimize_hctl_formula(&SymbolicContext::new(&bn)?, formula)?;
+    let num_hctl_vars = collect_unique_hctl_vars(tree.clone()).len();
+    let graph = get_extended_symbolic_graph(&bn, num_hctl_vars as u16)?;
+    let result = model_check_tree(tree, &graph)?;
+    Ok(ResultSummary {
+        num_results: result.approx_cardinality(),
+        num_colors: result.colors().approx_cardinality(),
+        num_states: result.vertices().approx_cardinality(),
+    })
+}
+
+/// Internal version of [model_summary] (errors can not be converted to JS outside of wasm).
+fn model_summary_internal(aeon_model: &str) -> Result<ModelSummary, String> {
+    let bn = BooleanNetwork::try_from(aeon_model)?;
+    let graph = SymbolicAsyncGraph::new(&bn)?;
+    Ok(ModelSummary {
+        num_vars: bn.num_vars(),
+        num_parameters: graph.symbolic_context().num_parameter_variables(),
+        num_states: graph
+            .unit_colored_vertices()
+            .vertices()
+            .approx_cardinality(),
+        num_colors: graph.unit_colors().approx_cardinality(),
+    })
+}
+
+/// Internal version of [validate_formula] (errors can not be converted to JS outside of wasm).
+fn validate_formula_internal(aeon_model: &str, formula: &str) -> Result<Vec<String>, String> {
+    let bn = BooleanNetwork::try_from(aeon_model)?;
+    let diagnostics = analysis::validate_formula(formula, &bn)?;
+    Ok(diagnostics
+        .diagnostics
+        .iter()
+        .map(|d| d.to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wasm::{check_formula_internal, model_summary_internal, validate_formula_internal};
+
+    #[test]
+    /// Test the internal versions of the JS entry points.
+    fn test_wasm_entry_points() {
+        let model = "a -> b\nb -| a\n$a: !b\n$b: a";
+        let summary = model_summary_internal(model).unwrap();
+        assert_eq!(summary.num_vars, 2);
+        assert_eq!(summary.num_states, 4.0);
+        assert_eq!(summary.num_colors, 1.0);
+
+        // the only attractor is a cycle going through all the states
+        let result = check_formula_internal(model, "!{x}: AG EF {x}").unwrap();
+        assert_eq!(result.num_states, 4.0);
+        let result = check_formula_internal(model, "!{x}: AX {x}").unwrap();
+        assert_eq!(result.num_results, 0.0);
+        assert!(check_formula_internal(model, "AX invalid").is_err());
+        assert!(check_formula_internal("invalid model", "a").is_err());
+
+        assert!(validate_formula_internal(model, "!{x}: AX {x}")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            validate_formula_internal(model, "AX (c & {x})")
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+}